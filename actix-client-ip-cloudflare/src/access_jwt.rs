@@ -0,0 +1,810 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{
+    dev,
+    http::{header::HeaderName, StatusCode},
+    FromRequest, HttpRequest, ResponseError,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ring::signature::{RsaPublicKeyComponents, RSA_PKCS1_2048_8192_SHA256};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Cloudflare Access's `cf-access-jwt-assertion` header name.
+#[allow(clippy::declare_interior_mutable_const)]
+pub const CF_ACCESS_JWT_ASSERTION: HeaderName = HeaderName::from_static("cf-access-jwt-assertion");
+
+/// Minimum time between key fetches triggered by tokens signed with unknown keys, or after a fetch
+/// has failed.
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum size of a fetched key set.
+const JWKS_LIMIT: usize = 256 * 1024;
+
+/// Configuration for validating Cloudflare Access JWT assertions.
+///
+/// Tokens are validated against the signing keys that Cloudflare publishes for your team domain.
+/// Keys are fetched when first needed and cached; they are refreshed after the
+/// [cache TTL](Self::cache_ttl) elapses or when a token is signed by an unknown key, which happens
+/// when Cloudflare rotates its keys. Only one fetch is in flight at a time; concurrent requests
+/// wait for it and use its result. If a refresh fails, previously fetched keys continue to be used
+/// and the fetch is not retried for a minute.
+///
+/// Cloning produces a handle to the same key cache. An instance of this type should be placed in
+/// app data for the [`CfAccessIdentity`] extractor to retrieve.
+#[derive(Clone)]
+pub struct CfAccessConfig {
+    certs_url: String,
+    issuer: String,
+    audience: String,
+    cache_ttl: Duration,
+    leeway: Duration,
+    keys: Arc<RwLock<KeyCache>>,
+    fetch_lock: Arc<AsyncMutex<()>>,
+}
+
+impl CfAccessConfig {
+    /// Constructs new Access configuration for a team domain and application audience (AUD) tag.
+    ///
+    /// The team domain is the host name of your Cloudflare Zero Trust organization, e.g.,
+    /// `myteam.cloudflareaccess.com`. The AUD tag can be found in the application's settings in the
+    /// Zero Trust dashboard.
+    pub fn new(team_domain: impl AsRef<str>, audience: impl Into<String>) -> Self {
+        let team_domain = team_domain.as_ref();
+        let team_domain = team_domain
+            .strip_prefix("https://")
+            .unwrap_or(team_domain)
+            .trim_end_matches('/');
+
+        Self {
+            certs_url: format!("https://{team_domain}/cdn-cgi/access/certs"),
+            issuer: format!("https://{team_domain}"),
+            audience: audience.into(),
+            cache_ttl: Duration::from_secs(60 * 60),
+            leeway: Duration::from_secs(60),
+            keys: Arc::new(RwLock::new(KeyCache::default())),
+            fetch_lock: Arc::new(AsyncMutex::new(())),
+        }
+    }
+
+    /// Sets how long fetched signing keys are used before being refreshed.
+    ///
+    /// Defaults to 1 hour.
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Sets the allowed clock skew when checking token expiry and not-before times.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Replaces cached signing keys with those from a JSON Web Key Set (JWKS) document.
+    ///
+    /// Useful for pre-loading keys at startup, or for providing keys in environments without
+    /// network access. The keys are treated as freshly fetched.
+    pub fn set_jwks(&self, jwks: &[u8]) -> Result<(), CfAccessError> {
+        let keys = parse_jwks(jwks)?;
+
+        let mut cache = self.keys.write().unwrap();
+        cache.keys = keys;
+        cache.fetched_at = Some(Instant::now());
+        cache.failed_at = None;
+
+        Ok(())
+    }
+
+    /// Validates a JWT assertion, returning the identity it was issued for.
+    pub async fn validate(&self, token: &str) -> Result<CfAccessIdentity, CfAccessError> {
+        let mut parts = token.split('.');
+
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(CfAccessError::Malformed);
+        };
+
+        let jwt_header = decode_json::<JwtHeader>(header)?;
+
+        if jwt_header.alg != "RS256" {
+            return Err(CfAccessError::Malformed);
+        }
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| CfAccessError::Malformed)?;
+
+        let key = self.key(&jwt_header.kid).await?;
+        let signed = &token[..header.len() + 1 + payload.len()];
+
+        RsaPublicKeyComponents {
+            n: &key.modulus,
+            e: &key.exponent,
+        }
+        .verify(&RSA_PKCS1_2048_8192_SHA256, signed.as_bytes(), &signature)
+        .map_err(|_| CfAccessError::InvalidSignature)?;
+
+        let claims = decode_json::<serde_json::Value>(payload)?;
+        let parsed = serde_json::from_value::<Claims>(claims.clone())
+            .map_err(|_| CfAccessError::Malformed)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let leeway = self.leeway.as_secs();
+
+        if parsed.exp.saturating_add(leeway) < now {
+            return Err(CfAccessError::Expired);
+        }
+
+        if parsed
+            .nbf
+            .is_some_and(|nbf| nbf > now.saturating_add(leeway))
+        {
+            return Err(CfAccessError::NotYetValid);
+        }
+
+        if parsed.iss != self.issuer {
+            return Err(CfAccessError::InvalidIssuer);
+        }
+
+        let audience_matches = match &parsed.aud {
+            Audience::One(aud) => *aud == self.audience,
+            Audience::Many(auds) => auds.contains(&self.audience),
+        };
+
+        if !audience_matches {
+            return Err(CfAccessError::InvalidAudience);
+        }
+
+        Ok(CfAccessIdentity {
+            subject: parsed.sub,
+            email: parsed.email,
+            common_name: parsed.common_name,
+            claims: Arc::new(claims),
+        })
+    }
+
+    /// Returns the signing key with ID `kid`, fetching keys if necessary.
+    async fn key(&self, kid: &str) -> Result<RsaKey, CfAccessError> {
+        if let Some(key) = self.cached_key(kid) {
+            return key;
+        }
+
+        // requests arriving while keys are being fetched wait for, and then use, that fetch
+        let _fetching = self.fetch_lock.lock().await;
+
+        if let Some(key) = self.cached_key(kid) {
+            return key;
+        }
+
+        match self.fetch_keys().await {
+            Ok(keys) => {
+                let key = keys.get(kid).cloned();
+
+                let mut cache = self.keys.write().unwrap();
+                cache.keys = keys;
+                cache.fetched_at = Some(Instant::now());
+                cache.failed_at = None;
+
+                key.ok_or(CfAccessError::UnknownKey)
+            }
+
+            Err(err) => {
+                let mut cache = self.keys.write().unwrap();
+                cache.failed_at = Some(Instant::now());
+
+                match cache.keys.get(kid) {
+                    Some(key) => {
+                        tracing::warn!("using stale Cloudflare Access keys; refresh failed: {err}");
+                        Ok(key.clone())
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
+
+    /// Returns the result of looking up key with ID `kid` in the cache, or `None` if keys should be
+    /// fetched first.
+    fn cached_key(&self, kid: &str) -> Option<Result<RsaKey, CfAccessError>> {
+        let cache = self.keys.read().unwrap();
+
+        if let Some(fetched_at) = cache.fetched_at {
+            let age = fetched_at.elapsed();
+
+            if age < self.cache_ttl {
+                if let Some(key) = cache.keys.get(kid) {
+                    return Some(Ok(key.clone()));
+                }
+
+                // avoid fetching keys for every request with a bogus key ID
+                if age < MIN_REFETCH_INTERVAL {
+                    return Some(Err(CfAccessError::UnknownKey));
+                }
+            }
+        }
+
+        // avoid retrying a failed fetch for every request
+        if cache
+            .failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() < MIN_REFETCH_INTERVAL)
+        {
+            return Some(cache.keys.get(kid).cloned().ok_or(CfAccessError::KeyFetch));
+        }
+
+        None
+    }
+
+    async fn fetch_keys(&self) -> Result<HashMap<String, RsaKey>, CfAccessError> {
+        let client = awc::Client::new();
+
+        tracing::debug!("fetching Cloudflare Access keys");
+        let mut res = client.get(&self.certs_url).send().await.map_err(|err| {
+            tracing::error!("{err}");
+            CfAccessError::KeyFetch
+        })?;
+
+        if !res.status().is_success() {
+            tracing::error!("fetching Cloudflare Access keys returned {}", res.status());
+            return Err(CfAccessError::KeyFetch);
+        }
+
+        let body = res.body().limit(JWKS_LIMIT).await.map_err(|err| {
+            tracing::error!("{err}");
+            CfAccessError::KeyFetch
+        })?;
+
+        parse_jwks(&body)
+    }
+}
+
+impl fmt::Debug for CfAccessConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CfAccessConfig")
+            .field("certs_url", &self.certs_url)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("leeway", &self.leeway)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Default)]
+struct KeyCache {
+    keys: HashMap<String, RsaKey>,
+    fetched_at: Option<Instant>,
+    failed_at: Option<Instant>,
+}
+
+/// Big-endian RSA public key components.
+#[derive(Clone)]
+struct RsaKey {
+    modulus: Vec<u8>,
+    exponent: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: String,
+    kty: String,
+    #[serde(default)]
+    n: String,
+    #[serde(default)]
+    e: String,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    kid: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    aud: Audience,
+    iss: String,
+    exp: u64,
+    nbf: Option<u64>,
+    #[serde(default)]
+    sub: String,
+    email: Option<String>,
+    common_name: Option<String>,
+}
+
+/// Parses the RSA keys from a JWKS document, ignoring other key types.
+fn parse_jwks(jwks: &[u8]) -> Result<HashMap<String, RsaKey>, CfAccessError> {
+    let jwks = serde_json::from_slice::<Jwks>(jwks).map_err(|err| {
+        tracing::error!("invalid Cloudflare Access key set: {err}");
+        CfAccessError::KeyFetch
+    })?;
+
+    let mut keys = HashMap::new();
+
+    for jwk in jwks.keys.into_iter().filter(|jwk| jwk.kty == "RSA") {
+        let (Ok(modulus), Ok(exponent)) = (
+            URL_SAFE_NO_PAD.decode(&jwk.n),
+            URL_SAFE_NO_PAD.decode(&jwk.e),
+        ) else {
+            tracing::error!("invalid Cloudflare Access key: {}", jwk.kid);
+            return Err(CfAccessError::KeyFetch);
+        };
+
+        keys.insert(jwk.kid, RsaKey { modulus, exponent });
+    }
+
+    Ok(keys)
+}
+
+fn decode_json<T: DeserializeOwned>(part: &str) -> Result<T, CfAccessError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| CfAccessError::Malformed)?;
+
+    serde_json::from_slice(&json).map_err(|_| CfAccessError::Malformed)
+}
+
+/// Errors that can occur when validating a Cloudflare Access JWT assertion.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CfAccessError {
+    /// No [`CfAccessConfig`] was found in app data.
+    MissingConfig,
+
+    /// Request does not have a `cf-access-jwt-assertion` header.
+    MissingAssertion,
+
+    /// Token is not a well-formed RS256 JWT.
+    Malformed,
+
+    /// Token is signed by a key that is not in Cloudflare's key set.
+    UnknownKey,
+
+    /// Token signature is invalid.
+    InvalidSignature,
+
+    /// Token has expired.
+    Expired,
+
+    /// Token is not valid yet.
+    NotYetValid,
+
+    /// Token was not issued by the configured team domain.
+    InvalidIssuer,
+
+    /// Token was not issued for the configured application audience.
+    InvalidAudience,
+
+    /// Signing keys could not be fetched or parsed.
+    KeyFetch,
+}
+
+impl_more::impl_display_enum!(
+    CfAccessError,
+    MissingConfig => "Cloudflare Access configuration not in app data",
+    MissingAssertion => "Cloudflare Access JWT assertion not present",
+    Malformed => "Cloudflare Access JWT assertion is malformed",
+    UnknownKey => "Cloudflare Access JWT assertion signed by unknown key",
+    InvalidSignature => "Cloudflare Access JWT assertion signature is invalid",
+    Expired => "Cloudflare Access JWT assertion has expired",
+    NotYetValid => "Cloudflare Access JWT assertion is not valid yet",
+    InvalidIssuer => "Cloudflare Access JWT assertion issuer is invalid",
+    InvalidAudience => "Cloudflare Access JWT assertion audience is invalid",
+    KeyFetch => "failed to fetch Cloudflare Access signing keys"
+);
+
+impl std::error::Error for CfAccessError {}
+
+impl ResponseError for CfAccessError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig | Self::KeyFetch => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// Extractor for the identity of a client authenticated by Cloudflare Access.
+///
+/// Validates the `cf-access-jwt-assertion` header that Cloudflare Access adds to requests, so that
+/// requests reaching your origin server directly, without passing through Access, are rejected with
+/// `403 Forbidden`. For this extractor to work, there must be an instance of [`CfAccessConfig`] in
+/// your app data.
+///
+/// # Examples
+/// ```
+/// use actix_client_ip_cloudflare::{CfAccessConfig, CfAccessIdentity};
+/// use actix_web::{get, App};
+///
+/// #[get("/")]
+/// async fn handler(identity: CfAccessIdentity) -> String {
+///     format!("hello, {}", identity.email().unwrap_or("service"))
+/// }
+///
+/// App::new()
+///     .app_data(CfAccessConfig::new(
+///         "myteam.cloudflareaccess.com",
+///         "4714c1358e65fe4b408ad6d432a5f878f08194bdb4752441fd56faefa9b2b6f2",
+///     ))
+///     .service(handler)
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct CfAccessIdentity {
+    subject: String,
+    email: Option<String>,
+    common_name: Option<String>,
+    claims: Arc<serde_json::Value>,
+}
+
+impl CfAccessIdentity {
+    /// Returns the user's ID (the `sub` claim), which is empty for service tokens.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the user's email address, if authenticated as a user.
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    /// Returns the service token's client ID (the `common_name` claim), if authenticated with a
+    /// service token.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Returns true if the client authenticated with a service token rather than as a user.
+    pub fn is_service_token(&self) -> bool {
+        self.common_name.is_some()
+    }
+
+    /// Returns all of the token's claims.
+    pub fn claims(&self) -> &serde_json::Value {
+        &self.claims
+    }
+}
+
+impl FromRequest for CfAccessIdentity {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _pl: &mut dev::Payload) -> Self::Future {
+        let config = req.app_data::<CfAccessConfig>().cloned();
+
+        let token = req
+            .headers()
+            .get(CF_ACCESS_JWT_ASSERTION)
+            .and_then(|val| val.to_str().ok())
+            .map(str::to_owned);
+
+        Box::pin(async move {
+            let config = config.ok_or(CfAccessError::MissingConfig)?;
+            let token = token.ok_or(CfAccessError::MissingAssertion)?;
+
+            config.validate(&token).await.map_err(|err| {
+                tracing::debug!("rejecting Cloudflare Access JWT assertion: {err}");
+                err.into()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{SocketAddr, TcpListener},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use actix_web::{test::TestRequest, web, App, HttpResponse, HttpServer};
+    use ring::{
+        rand::SystemRandom,
+        rsa::PublicKeyComponents,
+        signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    // 2048-bit RSA private key (PKCS#1 DER, base64) used only for signing test tokens
+    const TEST_KEY: &str = concat!(
+        "MIIEpQIBAAKCAQEAqjleG2jL2K1ngb692DKFNxpK5otTwxDqO7N/Rb6nzUkyNXxVPxHtZpG5cHdCVUDdQEegARdF8fQ526eP",
+        "SNvU9QRm5bD/R3Xqb6HF/CjdvEjLJr1zxMtBW8EY+U1/I+mtSz0c3SCwY+4Y1FAI/6J4qrHvV3MN62vUz5Okod9+czHj+BUl",
+        "9NPOtnERcjinZboHlCQII1sho0wdfc7VoRotfVHFdSaDc20Is55U1H0CZi5JWP1q0kE6JhD3Rp1HLI5lJMqw/akds2d8Trpf",
+        "qS08Xe5iChmew2e2rHlxpxaiWUkevLHUQSYJXOA9e1Z8uaUZZyMaP+SR4hlPMBXknyXCJwIDAQABAoIBAAv4+/r+jHPhgmm4",
+        "Qj8OEpuVEFaVzx7ltivbzKRZrBC+yLbFV3OXrbRspHfaE4LOjKFESGc7PVKuxFEEQIo12A0hsaEPKW1byFDwtm2tbCcdkZhY",
+        "c+eQCjlLZBfI0Rv0CBnGIx0UewOUUA/Cz1c3lLggyr5CW+TGkVHGRyqRfyHX75zRdC3/9a8yPwfmT5hYJZmRd8ACCV766ibn",
+        "toDS6EwyE2BtQt/W2kX8rafeR0MWANIUGVJLw7ePGIGZhn+a/OwQ2ZiUJKUVnQrls3TZuAFvOnhMGn3HQoHDiuJg/0oQcJtE",
+        "C+nEx+0qFJyGfghL7TYULDe16GoLuMf2rDpB7MECgYEA0RQCXOPOONmZ3gYBZNbAiNp4VyObyBbNEdQsnjmdLtKNeoJl1T5P",
+        "2Z9klbDLYz1uYNdbXDNHS0ObYU2PUCoBjALgHC96YIKolcjym1eAB1NLnyaf+1q+Hz+Rf7tnTIPP3Rj+Ge32K2nY/p8G+M8/",
+        "EKvuRaAGifTS8HppSvVZPucCgYEA0G0bwTz/8ChuVWAzZoKo25Jt/9X3pM0ZJ5ulTcin8j+rdqf2usDciDKikNGOvKg4n8aI",
+        "IG01vwyC93xfBDFeeQy2s0WMDq3UW9Evy/PdMxbXTheCewMK7s/0UlaLq7832f6/TCYbh4k6oVKZhAnVxmtSeJr5GTq23uha",
+        "z4nbOsECgYEAuxjz0FpPlhIsHkIzhyCiZRVUMW59Hn5A/x5m2yTloxvqXWJJbJ15aV647TermmBD4Ud2yzhcKV8TsextPiiI",
+        "/9km4jIeijUCVh/f8uFHgcapWn9/FLisgFwrgSmcFB20qr+NzHhlHY54D8sRuyAC6JFjBU1h7Jx5E5gHl3udZKsCgYEAsNyl",
+        "PKjClcv5NEFZ3JbrKsdqyDmM1y+jNNUCxKqMuSgGG74TxA18FabCBBmPAc/NnOcfBEUn4mJ/j/rdtfUesaF0N1PAsYQYO4Gb",
+        "CXp6LawoL4ALnM7MLDISpPwDsU2l2zosojWEDtMICXTKCB1AZgsUBHuc/Sexnh2VUZ8thQECgYEAgg0McuQG8b7o1s63k3JI",
+        "wQHEZyznGefa5XUzws1+0uPL4uqsutNZdlLut1fHRsMQVh3ezjGKZhTuf31aZlOvBJDL/kFytF9a33i7lp/vvi9S67O2rZpu",
+        "Aczo75e40n5q7vh1GTa7AlIr4f6pifw3IoYFj+8gwQPERny88g9m3ss=",
+    );
+
+    const TEAM_DOMAIN: &str = "myteam.cloudflareaccess.com";
+    const AUD: &str = "test-aud";
+
+    fn key_pair() -> RsaKeyPair {
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(TEST_KEY)
+            .unwrap();
+        RsaKeyPair::from_der(&der).unwrap()
+    }
+
+    fn jwks() -> serde_json::Value {
+        let public = PublicKeyComponents::<Vec<u8>>::from(key_pair().public());
+
+        json!({
+            "keys": [
+                { "kid": "other", "kty": "EC", "crv": "P-256" },
+                {
+                    "kid": "test-kid",
+                    "kty": "RSA",
+                    "alg": "RS256",
+                    "n": URL_SAFE_NO_PAD.encode(public.n),
+                    "e": URL_SAFE_NO_PAD.encode(public.e),
+                },
+            ],
+        })
+    }
+
+    fn config() -> CfAccessConfig {
+        let config = CfAccessConfig::new(TEAM_DOMAIN, AUD);
+        config.set_jwks(jwks().to_string().as_bytes()).unwrap();
+        config
+    }
+
+    /// Starts a server that responds to key fetches with `status` (and the test keys, if
+    /// successful) after a short delay, and counts the fetches.
+    fn key_server(status: StatusCode) -> (SocketAddr, Arc<AtomicUsize>) {
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = HttpServer::new({
+            let fetches = Arc::clone(&fetches);
+
+            move || {
+                let fetches = Arc::clone(&fetches);
+
+                App::new().default_service(web::to(move || {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+
+                    async move {
+                        actix_web::rt::time::sleep(Duration::from_millis(50)).await;
+                        HttpResponse::build(status).json(jwks())
+                    }
+                }))
+            }
+        })
+        .workers(1)
+        .disable_signals()
+        .listen(listener)
+        .unwrap()
+        .run();
+
+        actix_web::rt::spawn(server);
+
+        (addr, fetches)
+    }
+
+    fn fetching_config(addr: SocketAddr) -> CfAccessConfig {
+        let mut config = CfAccessConfig::new(TEAM_DOMAIN, AUD);
+        config.certs_url = format!("http://{addr}/cdn-cgi/access/certs");
+        config
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn sign(kid: &str, claims: serde_json::Value) -> String {
+        let header = json!({ "alg": "RS256", "kid": kid, "typ": "JWT" });
+
+        let signed = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string()),
+        );
+
+        let key_pair = key_pair();
+        let mut signature = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                signed.as_bytes(),
+                &mut signature,
+            )
+            .unwrap();
+
+        format!("{signed}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    fn user_claims() -> serde_json::Value {
+        json!({
+            "aud": [AUD],
+            "email": "user@example.com",
+            "exp": now() + 300,
+            "iat": now(),
+            "nbf": now(),
+            "iss": format!("https://{TEAM_DOMAIN}"),
+            "sub": "7335d417-61da-459d-899c-0a01c76a2f94",
+            "type": "app",
+        })
+    }
+
+    #[actix_web::test]
+    async fn valid_user_token() {
+        let identity = config()
+            .validate(&sign("test-kid", user_claims()))
+            .await
+            .unwrap();
+
+        assert_eq!(identity.subject(), "7335d417-61da-459d-899c-0a01c76a2f94");
+        assert_eq!(identity.email(), Some("user@example.com"));
+        assert!(!identity.is_service_token());
+        assert_eq!(identity.claims()["type"], "app");
+    }
+
+    #[actix_web::test]
+    async fn valid_service_token() {
+        let claims = json!({
+            "aud": AUD,
+            "common_name": "88bf3b6d86161464f6509f7219099e57.access",
+            "exp": now() + 300,
+            "iss": format!("https://{TEAM_DOMAIN}"),
+            "sub": "",
+        });
+
+        let identity = config().validate(&sign("test-kid", claims)).await.unwrap();
+
+        assert!(identity.is_service_token());
+        assert_eq!(
+            identity.common_name(),
+            Some("88bf3b6d86161464f6509f7219099e57.access")
+        );
+        assert_eq!(identity.email(), None);
+    }
+
+    #[actix_web::test]
+    async fn invalid_tokens() {
+        let config = config();
+
+        let mut claims = user_claims();
+        claims["exp"] = json!(now() - 120);
+        let err = config.validate(&sign("test-kid", claims)).await;
+        assert!(matches!(err, Err(CfAccessError::Expired)));
+
+        let mut claims = user_claims();
+        claims["nbf"] = json!(now() + 120);
+        let err = config.validate(&sign("test-kid", claims)).await;
+        assert!(matches!(err, Err(CfAccessError::NotYetValid)));
+
+        let mut claims = user_claims();
+        claims["aud"] = json!(["other-aud"]);
+        let err = config.validate(&sign("test-kid", claims)).await;
+        assert!(matches!(err, Err(CfAccessError::InvalidAudience)));
+
+        let mut claims = user_claims();
+        claims["iss"] = json!("https://otherteam.cloudflareaccess.com");
+        let err = config.validate(&sign("test-kid", claims)).await;
+        assert!(matches!(err, Err(CfAccessError::InvalidIssuer)));
+
+        // keys were just loaded so unknown key IDs are not fetched
+        let err = config.validate(&sign("unknown-kid", user_claims())).await;
+        assert!(matches!(err, Err(CfAccessError::UnknownKey)));
+
+        // swap in payload from another token
+        let token = sign("test-kid", user_claims());
+        let mut claims = user_claims();
+        claims["email"] = json!("admin@example.com");
+        let other = sign("test-kid", claims);
+        let tampered = format!(
+            "{}.{}.{}",
+            token.split('.').next().unwrap(),
+            other.split('.').nth(1).unwrap(),
+            token.split('.').nth(2).unwrap(),
+        );
+        let err = config.validate(&tampered).await;
+        assert!(matches!(err, Err(CfAccessError::InvalidSignature)));
+
+        for token in ["", "a.b", "a.b.c.d", "!!!.!!!.!!!"] {
+            let err = config.validate(token).await;
+            assert!(matches!(err, Err(CfAccessError::Malformed)), "{token}");
+        }
+    }
+
+    #[actix_web::test]
+    async fn concurrent_requests_share_key_fetch() {
+        let (addr, fetches) = key_server(StatusCode::OK);
+        let config = fetching_config(addr);
+
+        let tasks = (0..3)
+            .map(|_| {
+                let config = config.clone();
+                actix_web::rt::spawn(async move {
+                    config.validate(&sign("test-kid", user_claims())).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn failed_key_fetch_is_not_retried_immediately() {
+        let (addr, fetches) = key_server(StatusCode::SERVICE_UNAVAILABLE);
+        let config = fetching_config(addr);
+
+        for _ in 0..2 {
+            let err = config.validate(&sign("test-kid", user_claims())).await;
+            assert!(matches!(err, Err(CfAccessError::KeyFetch)));
+        }
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn extractor() {
+        let req = TestRequest::default()
+            .insert_header((CF_ACCESS_JWT_ASSERTION, sign("test-kid", user_claims())))
+            .app_data(config())
+            .to_http_request();
+        let identity = CfAccessIdentity::extract(&req).await.unwrap();
+        assert_eq!(identity.email(), Some("user@example.com"));
+
+        let req = TestRequest::default().app_data(config()).to_http_request();
+        let err = CfAccessIdentity::extract(&req).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+
+        let req = TestRequest::default()
+            .insert_header((CF_ACCESS_JWT_ASSERTION, sign("test-kid", user_claims())))
+            .to_http_request();
+        let err = CfAccessIdentity::extract(&req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -55,16 +55,27 @@
 //! their API. This feature includes `rustls` but if you prefer OpenSSL you can use it by disabling
 //! default crate features and enabling `fetch-ips-openssl` instead.
 //!
+//! `access-jwt`: Enables the [`CfAccessIdentity`] extractor for validating [Cloudflare Access] JWT
+//! assertions. Signing keys are fetched from Cloudflare over HTTPS, so this feature includes
+//! `rustls` but if you prefer OpenSSL you can enable `access-jwt-openssl` instead.
+//!
 //! [Cloudflare documentation]: https://developers.cloudflare.com/fundamentals/reference/http-request-headers
+//! [Cloudflare Access]: https://developers.cloudflare.com/cloudflare-one/identity/authorization-cookie/validating-json/
 
 #![forbid(unsafe_code)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+#[cfg(any(feature = "access-jwt-rustls", feature = "access-jwt-openssl"))]
+mod access_jwt;
 mod extract;
 mod fetch_cf_ips;
 mod header_v4;
 mod header_v6;
 
+#[cfg(any(feature = "access-jwt-rustls", feature = "access-jwt-openssl"))]
+pub use self::access_jwt::{
+    CfAccessConfig, CfAccessError, CfAccessIdentity, CF_ACCESS_JWT_ASSERTION,
+};
 #[cfg(feature = "fetch-ips")]
 pub use self::fetch_cf_ips::fetch_trusted_cf_ips;
 pub use self::{
@@ -0,0 +1,31 @@
+use digest::Digest;
+
+/// A hashing algorithm with a registered name in the [HTTP Digest Algorithm Values registry].
+///
+/// Used to find the relevant entry in `Content-Digest` fields.
+///
+/// [HTTP Digest Algorithm Values registry]: https://www.iana.org/assignments/http-digest-hash-alg/http-digest-hash-alg.xhtml
+pub trait DigestAlgorithm: Digest {
+    /// Algorithm key used in `Content-Digest` fields (e.g., `sha-256`).
+    const NAME: &'static str;
+}
+
+#[cfg(feature = "md5")]
+impl DigestAlgorithm for md5::Md5 {
+    const NAME: &'static str = "md5";
+}
+
+#[cfg(feature = "sha1")]
+impl DigestAlgorithm for sha1::Sha1 {
+    const NAME: &'static str = "sha";
+}
+
+#[cfg(feature = "sha2")]
+impl DigestAlgorithm for sha2::Sha256 {
+    const NAME: &'static str = "sha-256";
+}
+
+#[cfg(feature = "sha2")]
+impl DigestAlgorithm for sha2::Sha512 {
+    const NAME: &'static str = "sha-512";
+}
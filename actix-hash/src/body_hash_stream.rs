@@ -0,0 +1,156 @@
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_web::{dev, web::Bytes, FromRequest, HttpRequest};
+use digest::Digest;
+use futures_core::Stream;
+use futures_util::FutureExt as _;
+use pin_project_lite::pin_project;
+use tokio::sync::oneshot;
+use tracing::trace;
+
+use crate::VerifyBodyHashError;
+
+pin_project! {
+    /// Exposes the request payload as a stream, calculating a body checksum hash as it passes
+    /// through.
+    ///
+    /// Unlike [`BodyHash`](crate::BodyHash), which wraps a buffering extractor, this extractor
+    /// yields body chunks as they arrive. This allows gateways and proxies to forward the body
+    /// upstream, e.g., using `awc`'s `send_stream()`, while hashing it and without holding the
+    /// whole body in memory. The hash is available through [`hash()`](Self::hash) once the stream
+    /// has been fully consumed.
+    ///
+    /// When an expected hash is set using [`expect_hash()`](Self::expect_hash), the stream yields a
+    /// [`VerifyBodyHashError::Mismatch`] error in place of its end if the body does not match, so
+    /// that consumers, such as an upstream server, see an incomplete body instead of a complete,
+    /// unverified one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use actix_hash::BodyHashStream;
+    /// use actix_web::{web, HttpResponse};
+    /// use sha2::Sha256;
+    ///
+    /// async fn forward(
+    ///     mut body: BodyHashStream<Sha256>,
+    ///     client: web::Data<awc::Client>,
+    /// ) -> actix_web::Result<HttpResponse> {
+    ///     let hash = body.hash();
+    ///
+    ///     let res = client
+    ///         .post("http://upstream.internal/upload")
+    ///         .send_stream(body)
+    ///         .await
+    ///         .map_err(actix_web::error::ErrorBadGateway)?;
+    ///
+    ///     let hash = hash.await.expect("body should be fully forwarded");
+    ///     Ok(HttpResponse::build(res.status()).body(format!("{hash:x?}")))
+    /// }
+    /// ```
+    pub struct BodyHashStream<D> {
+        #[pin]
+        payload: dev::Payload,
+        hasher: Option<D>,
+        expected: Option<Vec<u8>>,
+        tx: Option<oneshot::Sender<Vec<u8>>>,
+        rx: Option<oneshot::Receiver<Vec<u8>>>,
+    }
+}
+
+impl<D: Digest> BodyHashStream<D> {
+    /// Sets the hash that the body is expected to have.
+    ///
+    /// If the body does not match, the stream yields an error instead of ending and the hash is not
+    /// reported. The comparison is constant-time.
+    pub fn expect_hash(mut self, hash: impl Into<Vec<u8>>) -> Self {
+        self.expected = Some(hash.into());
+        self
+    }
+
+    /// Returns a future that resolves to the body hash once the stream has been fully consumed.
+    ///
+    /// The future resolves to `None` if the stream is dropped before it ends, yields a payload
+    /// error, or fails verification against an [expected hash](Self::expect_hash).
+    ///
+    /// # Panics
+    /// Panics if called more than once.
+    pub fn hash(&mut self) -> impl Future<Output = Option<Vec<u8>>> + 'static {
+        self.rx
+            .take()
+            .expect("BodyHashStream::hash() should only be called once")
+            .map(Result::ok)
+    }
+}
+
+impl<D> fmt::Debug for BodyHashStream<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyHashStream")
+            .field("expected", &self.expected)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: Digest> FromRequest for BodyHashStream<D> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(_req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let (tx, rx) = oneshot::channel();
+
+        ready(Ok(Self {
+            payload: payload.take(),
+            hasher: Some(D::new()),
+            expected: None,
+            tx: Some(tx),
+            rx: Some(rx),
+        }))
+    }
+}
+
+impl<D: Digest> Stream for BodyHashStream<D> {
+    type Item = Result<Bytes, actix_web::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        let Some(hasher) = this.hasher else {
+            return Poll::Ready(None);
+        };
+
+        match ready!(this.payload.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                hasher.update(&chunk);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+
+            Some(Err(err)) => {
+                *this.hasher = None;
+                Poll::Ready(Some(Err(err.into())))
+            }
+
+            None => {
+                trace!("payload hashing complete");
+                let hash = this.hasher.take().unwrap().finalize();
+
+                if let Some(expected) = this.expected {
+                    use subtle::ConstantTimeEq as _;
+
+                    if !bool::from(hash.ct_eq(expected.as_slice())) {
+                        return Poll::Ready(Some(Err(VerifyBodyHashError::Mismatch.into())));
+                    }
+                }
+
+                if let Some(tx) = this.tx.take() {
+                    let _ = tx.send(hash.to_vec());
+                }
+
+                Poll::Ready(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::{error::Error as StdError, fmt};
+
+use actix_web::{dev, http::StatusCode, FromRequest, HttpRequest, ResponseError};
+use actix_web_lab::util::RequestTrailers;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{digest_field::find_digest, BodyHash, BodyHashParts, DigestAlgorithm};
+
+/// Name of the trailer field containing the expected body digest.
+const CONTENT_DIGEST: &str = "content-digest";
+
+/// Errors that can occur when verifying a body digest sent as a trailer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TrailerDigestError {
+    /// No `Content-Digest` trailer containing a digest for the expected algorithm was received.
+    Missing,
+
+    /// The `Content-Digest` trailer could not be parsed.
+    Malformed,
+
+    /// The body hash did not match the digest in the trailer.
+    Mismatch,
+}
+
+impl fmt::Display for TrailerDigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "Content-Digest trailer is missing",
+            Self::Malformed => "Content-Digest trailer is malformed",
+            Self::Mismatch => "body does not match Content-Digest trailer",
+        })
+    }
+}
+
+impl StdError for TrailerDigestError {}
+
+impl ResponseError for TrailerDigestError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Wraps an extractor, calculates a body checksum hash alongside, and verifies it against a digest
+/// sent as a request trailer.
+///
+/// Clients that upload chunked bodies can compute a digest while streaming and send it in a
+/// `Content-Digest` trailer field ([RFC 9530]), e.g., `Content-Digest: sha-256=:<base64>:`. Since
+/// trailers arrive after the body, the comparison is deferred until the body has been read in full.
+/// Extraction fails with a `400 Bad Request` response if the trailer is absent, does not contain a
+/// digest for the algorithm `D`, or does not match the body.
+///
+/// Trailers are read from the request's [`RequestTrailers`]. Note that Actix Web's own HTTP
+/// decoders do not currently surface request trailers; see its docs for supported integrations.
+///
+/// # Example
+/// ```
+/// use actix_hash::BodyHashWithTrailer;
+/// use actix_web::web;
+/// use sha2::Sha256;
+///
+/// async fn upload(body: BodyHashWithTrailer<web::Bytes, Sha256>) -> String {
+///     // body is known to match its Content-Digest trailer
+///     let body = body.into_parts().inner;
+///     format!("stored {} bytes", body.len())
+/// }
+/// ```
+///
+/// [RFC 9530]: https://datatracker.ietf.org/doc/html/rfc9530
+#[derive(Clone)]
+pub struct BodyHashWithTrailer<T, D: DigestAlgorithm> {
+    body: BodyHash<T, D>,
+}
+
+impl<T, D: DigestAlgorithm> fmt::Debug for BodyHashWithTrailer<T, D>
+where
+    BodyHash<T, D>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyHashWithTrailer")
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+impl<T, D: DigestAlgorithm> BodyHashWithTrailer<T, D> {
+    /// Returns hash slice.
+    pub fn hash(&self) -> &[u8] {
+        self.body.hash()
+    }
+
+    /// Returns body type parts, including extracted body type and hash bytes.
+    pub fn into_parts(self) -> BodyHashParts<T> {
+        self.body.into_parts()
+    }
+}
+
+impl<T, D> FromRequest for BodyHashWithTrailer<T, D>
+where
+    T: FromRequest + 'static,
+    D: DigestAlgorithm + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let req = req.clone();
+        let body_fut = BodyHash::<T, D>::from_request(&req, payload);
+
+        Box::pin(async move {
+            let body = body_fut.await.map_err(Into::into)?;
+
+            // body has been read in full so any trailers have been received
+            let trailers = RequestTrailers::of(&req)
+                .and_then(|trailers| trailers.get())
+                .ok_or(TrailerDigestError::Missing)?;
+
+            let mut found = false;
+
+            for value in trailers.get_all(CONTENT_DIGEST) {
+                let value = value.to_str().map_err(|_| TrailerDigestError::Malformed)?;
+
+                if let Some(digest) =
+                    find_digest(value, D::NAME).map_err(|_| TrailerDigestError::Malformed)?
+                {
+                    found = true;
+
+                    if !body.verify_slice(&digest) {
+                        return Err(TrailerDigestError::Mismatch.into());
+                    }
+                }
+            }
+
+            if !found {
+                return Err(TrailerDigestError::Missing.into());
+            }
+
+            Ok(Self { body })
+        })
+    }
+}
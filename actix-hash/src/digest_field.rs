@@ -0,0 +1,52 @@
+//! `Content-Digest` and legacy `Digest` field parsing.
+
+use base64::prelude::*;
+
+/// A digest field value could not be parsed.
+#[derive(Debug)]
+pub(crate) struct MalformedDigest;
+
+/// Finds and decodes the digest for `algorithm` in a `Content-Digest` dictionary ([RFC 9530]).
+///
+/// [RFC 9530]: https://datatracker.ietf.org/doc/html/rfc9530
+pub(crate) fn find_digest(
+    value: &str,
+    algorithm: &str,
+) -> Result<Option<Vec<u8>>, MalformedDigest> {
+    find_member(value, algorithm, |digest| {
+        digest.strip_prefix(':')?.strip_suffix(':')
+    })
+}
+
+/// Finds and decodes the digest for `algorithm` in a legacy `Digest` field ([RFC 3230]).
+///
+/// [RFC 3230]: https://datatracker.ietf.org/doc/html/rfc3230
+pub(crate) fn find_legacy_digest(
+    value: &str,
+    algorithm: &str,
+) -> Result<Option<Vec<u8>>, MalformedDigest> {
+    find_member(value, algorithm, |digest| Some(digest))
+}
+
+fn find_member(
+    value: &str,
+    algorithm: &str,
+    unwrap: impl Fn(&str) -> Option<&str>,
+) -> Result<Option<Vec<u8>>, MalformedDigest> {
+    for member in value.split(',') {
+        let (key, digest) = member.split_once('=').ok_or(MalformedDigest)?;
+
+        if !key.trim().eq_ignore_ascii_case(algorithm) {
+            continue;
+        }
+
+        let digest = unwrap(digest.trim()).ok_or(MalformedDigest)?;
+        let digest = BASE64_STANDARD
+            .decode(digest)
+            .map_err(|_| MalformedDigest)?;
+
+        return Ok(Some(digest));
+    }
+
+    Ok(None)
+}
@@ -18,9 +18,20 @@
 #![forbid(unsafe_code)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+mod algorithm;
 mod body_hash;
+mod body_hash_stream;
+mod body_hash_trailer;
+mod digest_field;
+mod verify_body_hash;
 
-pub use self::body_hash::{BodyHash, BodyHashParts};
+pub use self::{
+    algorithm::DigestAlgorithm,
+    body_hash::{BodyHash, BodyHashParts},
+    body_hash_stream::BodyHashStream,
+    body_hash_trailer::{BodyHashWithTrailer, TrailerDigestError},
+    verify_body_hash::{VerifyBodyHash, VerifyBodyHashError},
+};
 
 macro_rules! body_hash_alias {
     ($name:ident, $digest:path, $feature:literal, $desc:literal, $out_size:literal) => {
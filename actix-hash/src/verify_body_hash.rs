@@ -0,0 +1,162 @@
+use std::{error::Error as StdError, fmt};
+
+use actix_web::{
+    dev,
+    http::{header::HeaderName, StatusCode},
+    FromRequest, HttpRequest, ResponseError,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    digest_field::{find_digest, find_legacy_digest},
+    BodyHash, BodyHashParts, DigestAlgorithm,
+};
+
+/// Name of the header containing the expected body digest ([RFC 9530]).
+///
+/// [RFC 9530]: https://datatracker.ietf.org/doc/html/rfc9530
+const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+
+/// Name of the legacy header containing the expected body digest ([RFC 3230]).
+///
+/// [RFC 3230]: https://datatracker.ietf.org/doc/html/rfc3230
+const DIGEST: HeaderName = HeaderName::from_static("digest");
+
+/// Errors that can occur when verifying a body digest sent as a request header.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VerifyBodyHashError {
+    /// No `Content-Digest` or `Digest` header containing a digest for the expected algorithm was
+    /// sent.
+    Missing,
+
+    /// A `Content-Digest` or `Digest` header could not be parsed.
+    Malformed,
+
+    /// The body hash did not match the digest in the request headers.
+    Mismatch,
+}
+
+impl fmt::Display for VerifyBodyHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "body digest header is missing",
+            Self::Malformed => "body digest header is malformed",
+            Self::Mismatch => "body does not match digest header",
+        })
+    }
+}
+
+impl StdError for VerifyBodyHashError {}
+
+impl ResponseError for VerifyBodyHashError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Wraps an extractor, calculates a body checksum hash alongside, and verifies it against the
+/// digest sent in the request headers.
+///
+/// The expected digest is read from the `Content-Digest` header ([RFC 9530]), e.g.,
+/// `Content-Digest: sha-256=:<base64>:`, or the legacy `Digest` header ([RFC 3230]), e.g.,
+/// `Digest: SHA-256=<base64>`. Extraction fails with a `400 Bad Request` response, so the handler
+/// is not called, if neither header contains a digest for the algorithm `D` or if any such digest
+/// does not match the body. Requests without a digest are rejected before the body is read.
+///
+/// # Example
+/// ```
+/// use actix_hash::VerifyBodyHash;
+/// use actix_web::web;
+/// use sha2::Sha256;
+///
+/// async fn upload(body: VerifyBodyHash<web::Bytes, Sha256>) -> String {
+///     // body is known to match its Content-Digest header
+///     let body = body.into_parts().inner;
+///     format!("stored {} bytes", body.len())
+/// }
+/// ```
+///
+/// [RFC 9530]: https://datatracker.ietf.org/doc/html/rfc9530
+/// [RFC 3230]: https://datatracker.ietf.org/doc/html/rfc3230
+#[derive(Clone)]
+pub struct VerifyBodyHash<T, D: DigestAlgorithm> {
+    body: BodyHash<T, D>,
+}
+
+impl<T, D: DigestAlgorithm> fmt::Debug for VerifyBodyHash<T, D>
+where
+    BodyHash<T, D>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyBodyHash")
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+impl<T, D: DigestAlgorithm> VerifyBodyHash<T, D> {
+    /// Returns hash slice.
+    pub fn hash(&self) -> &[u8] {
+        self.body.hash()
+    }
+
+    /// Returns body type parts, including extracted body type and hash bytes.
+    pub fn into_parts(self) -> BodyHashParts<T> {
+        self.body.into_parts()
+    }
+}
+
+impl<T, D> FromRequest for VerifyBodyHash<T, D>
+where
+    T: FromRequest + 'static,
+    D: DigestAlgorithm + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let expected = match expected_digests(req, D::NAME) {
+            Ok(expected) if expected.is_empty() => {
+                return Box::pin(async { Err(VerifyBodyHashError::Missing.into()) })
+            }
+            Ok(expected) => expected,
+            Err(err) => return Box::pin(async { Err(err.into()) }),
+        };
+
+        let body_fut = BodyHash::<T, D>::from_request(req, payload);
+
+        Box::pin(async move {
+            let body = body_fut.await.map_err(Into::into)?;
+
+            if !expected.iter().all(|digest| body.verify_slice(digest)) {
+                return Err(VerifyBodyHashError::Mismatch.into());
+            }
+
+            Ok(Self { body })
+        })
+    }
+}
+
+/// Collects all digests for `algorithm` from the request's digest headers.
+fn expected_digests(
+    req: &HttpRequest,
+    algorithm: &str,
+) -> Result<Vec<Vec<u8>>, VerifyBodyHashError> {
+    let headers = req.headers();
+    let mut digests = Vec::new();
+
+    for value in headers.get_all(CONTENT_DIGEST) {
+        let value = value.to_str().map_err(|_| VerifyBodyHashError::Malformed)?;
+        digests.extend(find_digest(value, algorithm).map_err(|_| VerifyBodyHashError::Malformed)?);
+    }
+
+    for value in headers.get_all(DIGEST) {
+        let value = value.to_str().map_err(|_| VerifyBodyHashError::Malformed)?;
+        digests.extend(
+            find_legacy_digest(value, algorithm).map_err(|_| VerifyBodyHashError::Malformed)?,
+        );
+    }
+
+    Ok(digests)
+}
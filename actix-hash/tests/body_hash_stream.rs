@@ -0,0 +1,85 @@
+#![allow(missing_docs)]
+
+use actix_hash::BodyHashStream;
+use actix_web::{
+    body,
+    http::StatusCode,
+    test,
+    web::{self, Bytes},
+    App, HttpResponse,
+};
+use futures_util::TryStreamExt as _;
+use hex_literal::hex;
+use sha2::Sha256;
+
+const ABC_SHA256: [u8; 32] =
+    hex!("ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ad");
+
+#[actix_web::test]
+async fn hashes_passthrough_payload() {
+    let app = test::init_service(App::new().route(
+        "/",
+        web::post().to(|mut body: BodyHashStream<Sha256>| async move {
+            let hash = body.hash();
+
+            // respond with forwarded body followed by its hash
+            let mut chunks = body.try_collect::<Vec<Bytes>>().await.unwrap();
+            chunks.push(Bytes::from(hash.await.unwrap()));
+            Bytes::from(chunks.concat())
+        }),
+    ))
+    .await;
+
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, [b"abc".as_ref(), &ABC_SHA256].concat());
+
+    let req = test::TestRequest::post().to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(
+        body,
+        hex!("e3b0c442 98fc1c14 9afbf4c8 996fb924 27ae41e4 649b934c a495991b 7852b855").as_ref()
+    );
+}
+
+#[actix_web::test]
+async fn fails_stream_on_mismatch() {
+    let app = test::init_service(App::new().route(
+        "/",
+        web::post().to(|body: BodyHashStream<Sha256>| async move {
+            // streams body back to client, which is aborted if the hash does not match
+            HttpResponse::Ok().streaming(body.expect_hash(ABC_SHA256))
+        }),
+    ))
+    .await;
+
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "abc");
+
+    let req = test::TestRequest::post().set_payload("abd").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(body::to_bytes(res.into_body()).await.is_err());
+}
+
+#[actix_web::test]
+async fn hash_not_reported_on_mismatch() {
+    let app = test::init_service(App::new().route(
+        "/",
+        web::post().to(|body: BodyHashStream<Sha256>| async move {
+            let mut body = body.expect_hash(ABC_SHA256);
+            let hash = body.hash();
+
+            let err = body.try_collect::<Vec<Bytes>>().await.unwrap_err();
+            assert!(hash.await.is_none());
+
+            err.to_string()
+        }),
+    ))
+    .await;
+
+    let req = test::TestRequest::post().set_payload("abd").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "body does not match digest header");
+}
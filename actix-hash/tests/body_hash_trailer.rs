@@ -0,0 +1,77 @@
+#![allow(missing_docs)]
+
+use actix_hash::BodyHashWithTrailer;
+use actix_web::{
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue},
+        StatusCode,
+    },
+    test,
+    web::{self, Bytes},
+    App, HttpMessage as _,
+};
+use actix_web_lab::util::RequestTrailers;
+use sha2::Sha256;
+
+// base64 of SHA-256 hash of "abc"
+const ABC_SHA256: &str = "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=";
+
+fn trailers(digest: &str) -> RequestTrailers {
+    let mut fields = HeaderMap::new();
+    fields.insert(
+        HeaderName::from_static("content-digest"),
+        HeaderValue::from_str(digest).unwrap(),
+    );
+
+    let trailers = RequestTrailers::new();
+    trailers.set(fields);
+    trailers
+}
+
+#[actix_web::test]
+async fn verifies_trailer_digest() {
+    let app =
+        test::init_service(App::new().route(
+            "/",
+            web::post().to(|body: BodyHashWithTrailer<Bytes, Sha256>| async move {
+                body.into_parts().inner
+            }),
+        ))
+        .await;
+
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    req.extensions_mut()
+        .insert(trailers(&format!("sha-512=:AAAA:, sha-256=:{ABC_SHA256}:")));
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "abc");
+
+    // mismatched digest
+    let req = test::TestRequest::post().set_payload("abd").to_request();
+    req.extensions_mut()
+        .insert(trailers(&format!("sha-256=:{ABC_SHA256}:")));
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // no digest for expected algorithm
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    req.extensions_mut().insert(trailers("sha-512=:AAAA:"));
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // malformed digest
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    req.extensions_mut()
+        .insert(trailers(&format!("sha-256={ABC_SHA256}")));
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // trailer never received
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    req.extensions_mut().insert(RequestTrailers::new());
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,81 @@
+#![allow(missing_docs)]
+
+use actix_hash::VerifyBodyHash;
+use actix_web::{
+    http::StatusCode,
+    test,
+    web::{self, Bytes},
+    App,
+};
+use sha2::Sha256;
+
+// base64 of SHA-256 hash of "abc"
+const ABC_SHA256: &str = "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=";
+
+#[actix_web::test]
+async fn verifies_header_digest() {
+    let app = test::init_service(
+        App::new().route(
+            "/",
+            web::post()
+                .to(|body: VerifyBodyHash<Bytes, Sha256>| async move { body.into_parts().inner }),
+        ),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .insert_header((
+            "content-digest",
+            format!("sha-512=:AAAA:, sha-256=:{ABC_SHA256}:"),
+        ))
+        .set_payload("abc")
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "abc");
+
+    // legacy digest header
+    let req = test::TestRequest::post()
+        .insert_header(("digest", format!("SHA-256={ABC_SHA256}")))
+        .set_payload("abc")
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "abc");
+
+    // mismatched digest
+    let req = test::TestRequest::post()
+        .insert_header(("content-digest", format!("sha-256=:{ABC_SHA256}:")))
+        .set_payload("abd")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // mismatched legacy digest alongside matching content digest
+    let req = test::TestRequest::post()
+        .insert_header(("content-digest", format!("sha-256=:{ABC_SHA256}:")))
+        .insert_header(("digest", "SHA-256=AAAA"))
+        .set_payload("abc")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // no digest for expected algorithm
+    let req = test::TestRequest::post()
+        .insert_header(("content-digest", "sha-512=:AAAA:"))
+        .set_payload("abc")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // no digest headers
+    let req = test::TestRequest::post().set_payload("abc").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // malformed digest
+    let req = test::TestRequest::post()
+        .insert_header(("content-digest", format!("sha-256={ABC_SHA256}")))
+        .set_payload("abc")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
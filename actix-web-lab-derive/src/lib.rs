@@ -3,13 +3,62 @@
 #![forbid(unsafe_code)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, punctuated::Punctuated, token::Comma, DeriveInput, Ident};
+use syn::{
+    ext::IdentExt as _, parse_macro_input, punctuated::Punctuated, token::Comma, DeriveInput, Ident,
+};
 
-/// Derive a `FromRequest` implementation for an aggregate struct extractor.
+/// Derive a `FromRequest` implementation for an aggregate struct extractor or an enum that accepts
+/// multiple body formats.
+///
+/// # Structs
 ///
 /// All fields of the struct need to implement `FromRequest` unless they are marked with annotations
-/// that declare different handling is required.
+/// that declare different handling is required:
+/// - `#[from_request(copy_from_app_data)]`: the field is copied from app data of the field's type,
+///   which must implement `Copy`.
+/// - `#[from_request(app_data_type = "path::To::Type")]`: the field is cloned from app data of the
+///   given type, then converted into the field's type using its `From` implementation. Useful when
+///   data is registered under a different type than the field's, such as a `web::Data<T>` that is
+///   wrapped in a newtype.
+///
+/// # Enums
+///
+/// Each variant must be a tuple variant with a single field that implements `FromRequest`. The
+/// variant used is chosen by matching the request's `Content-Type` (ignoring parameters) against
+/// the `#[from_request(content_type = "...")]` attributes on each variant; a variant may declare
+/// several content types. At most one variant may omit the attribute, in which case it is used for
+/// requests that do not match any other variant. If there is no match and no such variant, the
+/// extractor fails with `415 Unsupported Media Type`.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use actix_web::{post, web, Responder};
+/// use actix_web_lab::FromRequest;
+///
+/// type Fields = HashMap<String, String>;
+///
+/// #[derive(Debug, FromRequest)]
+/// enum Submission {
+///     #[from_request(content_type = "application/json")]
+///     Json(web::Json<Fields>),
+///
+///     #[from_request(content_type = "application/x-www-form-urlencoded")]
+///     Form(web::Form<Fields>),
+/// }
+///
+/// #[post("/")]
+/// async fn handler(submission: Submission) -> impl Responder {
+///     let fields = match submission {
+///         Submission::Json(web::Json(fields)) => fields,
+///         Submission::Form(web::Form(fields)) => fields,
+///     };
+///     // ...
+///     # ""
+/// }
+/// ```
 ///
 /// # Examples
 /// ```
@@ -26,6 +75,24 @@ use syn::{parse_macro_input, punctuated::Punctuated, token::Comma, DeriveInput,
 ///     // equivalent to `req.app_data::<u64>().copied()`
 ///     #[from_request(copy_from_app_data)]
 ///     int: u64,
+///
+///     // equivalent to `req.app_data::<web::Data<Config>>().cloned().map(Self::from)`
+///     #[from_request(app_data_type = "web::Data<Config>")]
+///     config: ConfigHandle,
+/// }
+///
+/// #[derive(Debug)]
+/// struct Config {
+///     name: String,
+/// }
+///
+/// #[derive(Debug)]
+/// struct ConfigHandle(std::sync::Arc<Config>);
+///
+/// impl From<web::Data<Config>> for ConfigHandle {
+///     fn from(data: web::Data<Config>) -> Self {
+///         Self(data.into_inner())
+///     }
 /// }
 ///
 /// #[get("/")]
@@ -40,39 +107,47 @@ pub fn derive_from_request(input: proc_macro::TokenStream) -> proc_macro::TokenS
 
     let name = input.ident;
 
-    let data = match input.data {
-        syn::Data::Struct(data) => data,
-        syn::Data::Enum(_) | syn::Data::Union(_) => {
-            return quote! {
-                compile_error!("Deriving FromRequest is only supported on structs for now.");
-            }
-            .into();
-        }
+    let output = match input.data {
+        syn::Data::Struct(data) => derive_struct(name, data),
+        syn::Data::Enum(data) => derive_enum(name, data),
+        syn::Data::Union(_) => quote! {
+            compile_error!("Deriving FromRequest is only supported on structs and enums.");
+        },
     };
 
+    proc_macro::TokenStream::from(output)
+}
+
+fn derive_struct(name: Ident, data: syn::DataStruct) -> TokenStream {
     let fields = match data.fields {
         syn::Fields::Named(fields) => fields.named,
         syn::Fields::Unnamed(_) | syn::Fields::Unit => {
             return quote! {
                 compile_error!("Deriving FromRequest is only supported on structs with named fields for now.");
-            }
-            .into();
+            };
         }
     };
 
+    let sources = match fields
+        .iter()
+        .map(field_source)
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(sources) => sources,
+        Err(err) => return err.into_compile_error(),
+    };
+
     let field_names_joined = fields
         .iter()
         .map(|f| f.ident.clone().unwrap())
         .collect::<Punctuated<_, Comma>>();
 
     // i.e., field has no special handling, it's just extracted using its FromRequest impl
-    let fut_fields = fields.iter().filter(|field| {
-        field.attrs.is_empty()
-            || field
-                .attrs
-                .iter()
-                .any(|attr| attr.parse_args::<Ident>().is_err())
-    });
+    let fut_fields = fields
+        .iter()
+        .zip(&sources)
+        .filter(|(_, source)| matches!(source, FieldSource::Extract))
+        .map(|(field, _)| field);
 
     let field_fut_names_joined = fut_fields
         .clone()
@@ -94,42 +169,48 @@ pub fn derive_from_request(input: proc_macro::TokenStream) -> proc_macro::TokenS
         }
     });
 
-    let fields_copied_from_app_data = fields
-        .iter()
-        .filter(|field| {
-            field.attrs.iter().any(|attr| {
-                attr.parse_args::<Ident>().is_ok_and(|ident| ident == "copy_from_app_data")
-            })
-        })
-        .map(|field| {
-            let syn::Field { ident, ty, .. } = field;
+    let fields_from_app_data = fields.iter().zip(&sources).filter_map(|(field, source)| {
+        let syn::Field { ident, ty, .. } = field;
 
-            let varname = ident.clone().unwrap();
+        let varname = ident.clone().unwrap();
 
-            quote! {
-                let #varname = if let Some(st) = req.app_data::<#ty>().copied() {
-                    st
-                } else {
-                    ::actix_web_lab::__reexports::tracing::debug!(
-                        "Failed to extract `{}` for `{}` handler. For this extractor to work \
-                        correctly, pass the data to `App::app_data()`. Ensure that types align in \
-                        both the set and retrieve calls.",
-                        ::std::any::type_name::<#ty>(),
-                        req.match_name().unwrap_or_else(|| req.path())
-                    );
-
-                    return ::std::boxed::Box::pin(async move {
-                        ::std::result::Result::Err(
-                            ::actix_web_lab::__reexports::actix_web::error::ErrorInternalServerError(
-                            "Requested application data is not configured correctly. \
-                            View/enable debug logs for more details.",
-                        ))
-                    })
-                };
-            }
-        });
+        let (data_ty, value) = match source {
+            FieldSource::Extract => return None,
+            FieldSource::CopyFromAppData => (ty, quote! { *st }),
+            FieldSource::AppDataType(data_ty) => (
+                &**data_ty,
+                quote! {
+                    <#ty as ::std::convert::From<#data_ty>>::from(
+                        ::std::clone::Clone::clone(st)
+                    )
+                },
+            ),
+        };
 
-    let output = quote! {
+        Some(quote! {
+            let #varname = if let Some(st) = req.app_data::<#data_ty>() {
+                #value
+            } else {
+                ::actix_web_lab::__reexports::tracing::debug!(
+                    "Failed to extract `{}` for `{}` handler. For this extractor to work \
+                    correctly, pass the data to `App::app_data()`. Ensure that types align in \
+                    both the set and retrieve calls.",
+                    ::std::any::type_name::<#data_ty>(),
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                return ::std::boxed::Box::pin(async move {
+                    ::std::result::Result::Err(
+                        ::actix_web_lab::__reexports::actix_web::error::ErrorInternalServerError(
+                        "Requested application data is not configured correctly. \
+                        View/enable debug logs for more details.",
+                    ))
+                })
+            };
+        })
+    });
+
+    quote! {
         impl ::actix_web::FromRequest for #name {
             type Error = ::actix_web::Error;
             type Future = ::std::pin::Pin<::std::boxed::Box<
@@ -141,7 +222,7 @@ pub fn derive_from_request(input: proc_macro::TokenStream) -> proc_macro::TokenS
                 use ::actix_web_lab::__reexports::futures_util::{FutureExt as _, TryFutureExt as _};
                 use ::actix_web_lab::__reexports::tokio::try_join;
 
-                #(#fields_copied_from_app_data)*
+                #(#fields_from_app_data)*
 
                 #(#field_futs)*
 
@@ -151,7 +232,368 @@ pub fn derive_from_request(input: proc_macro::TokenStream) -> proc_macro::TokenS
                 )
            }
         }
+    }
+}
+
+/// How a struct field is populated.
+enum FieldSource {
+    /// Extracted using its `FromRequest` impl.
+    Extract,
+
+    /// Copied from app data of the field's type.
+    CopyFromAppData,
+
+    /// Cloned from app data of the given type and converted into the field's type.
+    AppDataType(Box<syn::Type>),
+}
+
+/// Determines a field's source from its `#[from_request(...)]` attributes.
+fn field_source(field: &syn::Field) -> syn::Result<FieldSource> {
+    let mut source = FieldSource::Extract;
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("from_request") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if !matches!(source, FieldSource::Extract) {
+                return Err(meta.error("only one `from_request` field attribute is allowed"));
+            }
+
+            if meta.path.is_ident("copy_from_app_data") {
+                source = FieldSource::CopyFromAppData;
+                Ok(())
+            } else if meta.path.is_ident("app_data_type") {
+                let ty = meta.value()?.parse::<syn::LitStr>()?;
+                source = FieldSource::AppDataType(Box::new(ty.parse()?));
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `from_request` field attribute"))
+            }
+        })?;
+    }
+
+    Ok(source)
+}
+
+fn derive_enum(name: Ident, data: syn::DataEnum) -> TokenStream {
+    if data.variants.is_empty() {
+        return quote! {
+            compile_error!("Deriving FromRequest on enums requires at least one variant.");
+        };
+    }
+
+    let mut dispatch_arms = Vec::new();
+    let mut fallback = None;
+
+    for variant in &data.variants {
+        let ty = match &variant.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => {
+                return syn::Error::new_spanned(
+                    variant,
+                    "Deriving FromRequest on enums requires single-field tuple variants.",
+                )
+                .to_compile_error();
+            }
+        };
+
+        let content_types = match variant_content_types(variant) {
+            Ok(content_types) => content_types,
+            Err(err) => return err.to_compile_error(),
+        };
+
+        let variant_name = &variant.ident;
+
+        let extract = quote! {
+            return ::std::boxed::Box::pin(
+                <#ty>::from_request(req, pl)
+                    .map_err(Into::into)
+                    .map_ok(Self::#variant_name)
+            );
+        };
+
+        if content_types.is_empty() {
+            if fallback.is_some() {
+                return syn::Error::new_spanned(
+                    variant,
+                    "Only one variant may omit the `content_type` attribute.",
+                )
+                .to_compile_error();
+            }
+
+            fallback = Some(extract);
+        } else {
+            dispatch_arms.push(quote! {
+                if [#(#content_types),*].iter().any(|ct| ct.eq_ignore_ascii_case(essence)) {
+                    #extract
+                }
+            });
+        }
+    }
+
+    // the essence is only needed when there is at least one content type to dispatch on
+    let essence = (!dispatch_arms.is_empty()).then(|| {
+        quote! {
+            let essence = req
+                .headers()
+                .get(::actix_web_lab::__reexports::actix_web::http::header::CONTENT_TYPE)
+                .and_then(|hdr| hdr.to_str().ok())
+                .and_then(|ct| ct.split(';').next())
+                .map(str::trim)
+                .unwrap_or("");
+        }
+    });
+
+    let fallback = fallback.unwrap_or_else(|| {
+        quote! {
+            ::actix_web_lab::__reexports::tracing::debug!(
+                "Failed to extract `{}` for `{}` handler. No variant accepts the request's content type.",
+                ::std::any::type_name::<Self>(),
+                req.match_name().unwrap_or_else(|| req.path())
+            );
+
+            let err = ::actix_web_lab::__reexports::actix_web::error::ErrorUnsupportedMediaType(
+                "Unsupported content type.",
+            );
+
+            ::std::boxed::Box::pin(async move { ::std::result::Result::Err(err) })
+        }
+    });
+
+    quote! {
+        impl ::actix_web::FromRequest for #name {
+            type Error = ::actix_web::Error;
+            type Future = ::std::pin::Pin<::std::boxed::Box<
+                dyn ::std::future::Future<Output = ::std::result::Result<Self, Self::Error>>
+            >>;
+
+            fn from_request(req: &::actix_web::HttpRequest, pl: &mut ::actix_web::dev::Payload) -> Self::Future {
+                use ::actix_web_lab::__reexports::actix_web::FromRequest as _;
+                use ::actix_web_lab::__reexports::futures_util::TryFutureExt as _;
+
+                #essence
+
+                #(#dispatch_arms)*
+
+                #fallback
+            }
+        }
+    }
+}
+
+/// Collects the values of all `#[from_request(content_type = "...")]` attributes on a variant.
+fn variant_content_types(variant: &syn::Variant) -> syn::Result<Vec<syn::LitStr>> {
+    let mut content_types = Vec::new();
+
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("from_request") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("content_type") {
+                content_types.push(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `from_request` variant attribute"))
+            }
+        })?;
+    }
+
+    Ok(content_types)
+}
+
+/// Derive an `sse::IntoEvent` implementation for a struct or enum.
+///
+/// # Enums
+///
+/// The `event` name is the variant's name and the `data` field is the variant's payload serialized
+/// as JSON:
+/// - unit variants are serialized as `null`;
+/// - single-field tuple variants are serialized as their field;
+/// - tuple variants with multiple fields are serialized as an array;
+/// - struct variants are serialized as an object keyed by field name.
+///
+/// All fields need to implement `Serialize`. Type parameters are bounded by `Serialize`.
+///
+/// # Structs
+///
+/// The `event` name is the struct's name and the whole struct is serialized as JSON, so it needs
+/// to implement `Serialize`.
+///
+/// # Attributes
+///
+/// - `#[sse(rename = "...")]`: on a struct or variant, overrides its event name.
+///
+/// # Examples
+/// ```
+/// use actix_web::web::BytesMut;
+/// use actix_web_lab::sse::{self, IntoEvent as _};
+///
+/// #[derive(sse::IntoEvent)]
+/// enum ChatEvent {
+///     #[sse(rename = "user_joined")]
+///     UserJoined {
+///         name: String,
+///     },
+///     Message(String),
+///     Shutdown,
+/// }
+///
+/// let mut buf = BytesMut::new();
+///
+/// for event in [
+///     ChatEvent::UserJoined {
+///         name: "alice".to_owned(),
+///     },
+///     ChatEvent::Message("hi".to_owned()),
+///     ChatEvent::Shutdown,
+/// ] {
+///     event.into_event().unwrap().serialize_into(&mut buf);
+/// }
+///
+/// assert_eq!(
+///     buf,
+///     "event: user_joined\ndata: {\"name\":\"alice\"}\n\n\
+///      event: Message\ndata: \"hi\"\n\n\
+///      event: Shutdown\ndata: null\n\n",
+/// );
+/// ```
+#[proc_macro_derive(IntoEvent, attributes(sse))]
+pub fn derive_into_event(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = &input.ident;
+
+    // payloads containing type parameters are serialized
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(
+            ::actix_web_lab::__reexports::serde::Serialize
+        ));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        syn::Data::Struct(_) => event_name(&input.attrs, name).map(|event| {
+            quote! {
+                ::std::result::Result::Ok(
+                    ::actix_web_lab::sse::Data::new_json(self)?.event(#event)
+                )
+            }
+        }),
+        syn::Data::Enum(data) => into_event_arms(data).map(|arms| {
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }),
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            name,
+            "Deriving IntoEvent is only supported on structs and enums.",
+        )),
+    };
+
+    let body = match body {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let output = quote! {
+        impl #impl_generics ::actix_web_lab::sse::IntoEvent for #name #ty_generics #where_clause {
+            fn into_data(
+                self,
+            ) -> ::std::result::Result<
+                ::actix_web_lab::sse::Data,
+                ::actix_web_lab::__reexports::serde_json::Error,
+            > {
+                #body
+            }
+        }
     };
 
     proc_macro::TokenStream::from(output)
 }
+
+/// Generates match arms that serialize each variant's payload into a data message.
+fn into_event_arms(data: &syn::DataEnum) -> syn::Result<Vec<TokenStream>> {
+    let serde_json = quote! { ::actix_web_lab::__reexports::serde_json };
+
+    data.variants
+        .iter()
+        .map(|variant| {
+            let variant_name = &variant.ident;
+            let event = event_name(&variant.attrs, variant_name)?;
+
+            let (pattern, payload) = match &variant.fields {
+                syn::Fields::Unit => (quote! {}, quote! { () }),
+
+                syn::Fields::Unnamed(fields) => {
+                    let bindings = (0..fields.unnamed.len())
+                        .map(|idx| format_ident!("__field{idx}"))
+                        .collect::<Vec<_>>();
+
+                    let payload = match bindings.as_slice() {
+                        [field] => quote! { #field },
+                        _ => quote! { (#(#bindings),*) },
+                    };
+
+                    (quote! { (#(#bindings),*) }, payload)
+                }
+
+                syn::Fields::Named(fields) => {
+                    let idents = fields
+                        .named
+                        .iter()
+                        .map(|field| field.ident.as_ref().unwrap())
+                        .collect::<Vec<_>>();
+                    let keys = idents.iter().map(|ident| ident.unraw().to_string());
+
+                    let payload = quote! {{
+                        let mut __map = #serde_json::Map::new();
+                        #(
+                            __map.insert(
+                                ::std::string::ToString::to_string(#keys),
+                                #serde_json::to_value(#idents)?,
+                            );
+                        )*
+                        __map
+                    }};
+
+                    (quote! { { #(#idents),* } }, payload)
+                }
+            };
+
+            Ok(quote! {
+                Self::#variant_name #pattern => ::std::result::Result::Ok(
+                    ::actix_web_lab::sse::Data::new_json(#payload)?.event(#event)
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Returns the event name from an `#[sse(rename = "...")]` attribute, or else the item's name.
+fn event_name(attrs: &[syn::Attribute], ident: &Ident) -> syn::Result<syn::LitStr> {
+    let mut event = syn::LitStr::new(&ident.unraw().to_string(), ident.span());
+
+    for attr in attrs {
+        if !attr.path().is_ident("sse") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                event = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `sse` attribute"))
+            }
+        })?;
+    }
+
+    Ok(event)
+}
@@ -0,0 +1,66 @@
+#![allow(missing_docs)]
+
+use actix_web::web::BytesMut;
+use actix_web_lab::sse::IntoEvent;
+use serde::Serialize;
+
+#[derive(IntoEvent)]
+enum Event<T> {
+    Unit,
+    Single(T),
+    Pair(u32, &'static str),
+    Named {
+        id: u32,
+        r#type: &'static str,
+    },
+    #[sse(rename = "renamed-event")]
+    Renamed(bool),
+}
+
+#[derive(Serialize, IntoEvent)]
+#[sse(rename = "tick")]
+struct Tick {
+    count: u64,
+}
+
+#[derive(Serialize, IntoEvent)]
+struct Ping;
+
+fn serialize(event: impl IntoEvent) -> BytesMut {
+    let mut buf = BytesMut::new();
+    event.into_event().unwrap().serialize_into(&mut buf);
+    buf
+}
+
+#[test]
+fn enum_variants() {
+    assert_eq!(serialize(Event::<()>::Unit), "event: Unit\ndata: null\n\n");
+    assert_eq!(
+        serialize(Event::Single(vec![1, 2])),
+        "event: Single\ndata: [1,2]\n\n"
+    );
+    assert_eq!(
+        serialize(Event::<()>::Pair(1, "a")),
+        "event: Pair\ndata: [1,\"a\"]\n\n"
+    );
+    assert_eq!(
+        serialize(Event::<()>::Named {
+            id: 42,
+            r#type: "b"
+        }),
+        "event: Named\ndata: {\"id\":42,\"type\":\"b\"}\n\n"
+    );
+    assert_eq!(
+        serialize(Event::<()>::Renamed(true)),
+        "event: renamed-event\ndata: true\n\n"
+    );
+}
+
+#[test]
+fn structs() {
+    assert_eq!(
+        serialize(Tick { count: 3 }),
+        "event: tick\ndata: {\"count\":3}\n\n"
+    );
+    assert_eq!(serialize(Ping), "event: Ping\ndata: null\n\n");
+}
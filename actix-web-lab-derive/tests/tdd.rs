@@ -56,3 +56,100 @@ async fn tdd() {
     let res = srv.post("/").send_body("foo").await.unwrap();
     assert_eq!(res.status(), StatusCode::OK);
 }
+
+#[derive(Debug, FromRequest)]
+enum Submission {
+    #[from_request(content_type = "application/json")]
+    Json(web::Json<String>),
+
+    #[from_request(content_type = "application/x-www-form-urlencoded")]
+    Form(web::Form<Vec<(String, String)>>),
+}
+
+async fn submission_handler(submission: Submission) -> impl Responder {
+    match submission {
+        Submission::Json(web::Json(msg)) => msg,
+        Submission::Form(web::Form(pairs)) => pairs[0].1.clone(),
+    }
+}
+
+#[actix_web::test]
+async fn enum_content_type_dispatch() {
+    let srv = actix_test::start(|| App::new().default_service(web::to(submission_handler)));
+
+    let mut res = srv
+        .post("/")
+        .content_type("application/json; charset=utf-8")
+        .send_body(r#""from json""#)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), "from json");
+
+    let mut res = srv
+        .post("/")
+        .content_type("application/x-www-form-urlencoded")
+        .send_body("msg=from+form")
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), "from form");
+
+    let res = srv
+        .post("/")
+        .content_type("text/plain")
+        .send_body("foo")
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    name: &'static str,
+}
+
+#[derive(Debug)]
+struct ConfigHandle(std::sync::Arc<Config>);
+
+impl From<web::Data<Config>> for ConfigHandle {
+    fn from(data: web::Data<Config>) -> Self {
+        Self(data.into_inner())
+    }
+}
+
+#[derive(Debug, FromRequest)]
+struct ConfigParts {
+    #[from_request(app_data_type = "Config")]
+    config: std::sync::Arc<Config>,
+
+    #[from_request(app_data_type = "web::Data<Config>")]
+    data: ConfigHandle,
+}
+
+async fn config_handler(parts: ConfigParts) -> impl Responder {
+    format!("{} {}", parts.config.name, parts.data.0.name)
+}
+
+#[actix_web::test]
+async fn app_data_type() {
+    let srv = actix_test::start(|| {
+        App::new()
+            .app_data(Config { name: "plain" })
+            .app_data(web::Data::new(Config { name: "data" }))
+            .default_service(web::to(config_handler))
+    });
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), "plain data");
+
+    let srv = actix_test::start(|| {
+        App::new()
+            .app_data(Config { name: "plain" })
+            .default_service(web::to(config_handler))
+    });
+
+    let res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -7,6 +7,7 @@ fn compile_macros() {
 
     t.pass("tests/trybuild/ok-no-body-type.rs");
     t.pass("tests/trybuild/ok-with-body-type.rs");
+    t.pass("tests/trybuild/ok-enum.rs");
 
     t.compile_fail("tests/trybuild/err-invalid-structures.rs");
 }
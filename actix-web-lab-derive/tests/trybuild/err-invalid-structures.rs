@@ -2,10 +2,16 @@ use actix_web_lab::FromRequest;
 
 #[derive(FromRequest)]
 enum Foo {
-    Data(()),
+    Data { data: () },
 }
 
 #[derive(FromRequest)]
 struct Bar(());
 
+#[derive(FromRequest)]
+enum Baz {
+    Data(()),
+    Other(()),
+}
+
 fn main() {}
@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+use actix_web::web;
+use actix_web_lab_derive::FromRequest;
+
+#[derive(Debug, FromRequest)]
+enum Body {
+    #[from_request(content_type = "application/json", content_type = "text/json")]
+    Json(web::Json<HashMap<String, String>>),
+
+    #[from_request(content_type = "application/x-www-form-urlencoded")]
+    Form(web::Form<HashMap<String, String>>),
+
+    Raw(web::Bytes),
+}
+
+fn main() {}
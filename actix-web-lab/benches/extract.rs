@@ -0,0 +1,71 @@
+#![allow(missing_docs)]
+
+use actix_web::{http::header, test::TestRequest, web, FromRequest as _};
+use actix_web_lab::extract::{Bytes, Json, LabConfig};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+fn rt() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+fn body() -> web::Bytes {
+    let items = (0..4_000)
+        .map(|n| format!("{{\"seq\":{n}}}"))
+        .collect::<Vec<_>>();
+    web::Bytes::from(format!("[{}]", items.join(",")))
+}
+
+fn req(body: &web::Bytes, cfg: LabConfig, known_length: bool) -> TestRequest {
+    let req = TestRequest::default()
+        .app_data(cfg)
+        .insert_header(header::ContentType::json())
+        .set_payload(body.clone());
+
+    match known_length {
+        true => req.insert_header((header::CONTENT_LENGTH, body.len())),
+        false => req,
+    }
+}
+
+fn bench_body_extractors(c: &mut Criterion) {
+    let body = body();
+    let rt = rt();
+
+    let mut group = c.benchmark_group("extract");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+
+    for (name, cfg, known_length) in [
+        ("preallocated", LabConfig::new(), true),
+        ("unknown-length", LabConfig::new(), false),
+        (
+            "no-preallocation",
+            LabConfig::new().max_preallocation(0),
+            true,
+        ),
+    ] {
+        group.bench_function(format!("bytes/{name}"), |b| {
+            b.iter_batched(
+                || req(&body, cfg.clone(), known_length).to_http_parts(),
+                |(req, mut pl)| rt.block_on(Bytes::<{ 1 << 20 }>::from_request(&req, &mut pl)),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("json/{name}"), |b| {
+            b.iter_batched(
+                || req(&body, cfg.clone(), known_length).to_http_parts(),
+                |(req, mut pl)| rt.block_on(Json::<Value>::from_request(&req, &mut pl)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_body_extractors);
+criterion_main!(benches);
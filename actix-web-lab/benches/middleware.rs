@@ -0,0 +1,97 @@
+#![allow(missing_docs)]
+
+use std::time::Duration;
+
+use actix_web::{
+    dev::{Service, ServiceResponse},
+    http::header,
+    test::{init_service, TestRequest},
+    web, App, Error, HttpResponse,
+};
+use actix_web_lab::{
+    bench::LoadTest,
+    middleware::{CatchPanic, NormalizePath, ResponseCache},
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const CONCURRENCY: usize = 16;
+
+async fn index() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "max-age=60"))
+        .body("hello world")
+}
+
+/// Measures `iters` requests to `/` against the app, excluding service construction.
+async fn run<S, B>(app: S, iters: u64) -> Duration
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = Error>,
+    B: actix_web::body::MessageBody,
+{
+    LoadTest::new()
+        .requests(iters as usize)
+        .concurrency(CONCURRENCY)
+        .run(&app, |_| TestRequest::get().uri("/"))
+        .await
+        .elapsed()
+}
+
+fn bench_middleware(c: &mut Criterion) {
+    let mut group = c.benchmark_group("middleware");
+
+    group.bench_function("baseline", |b| {
+        b.iter_custom(|iters| {
+            actix_web::rt::System::new().block_on(async {
+                let app = init_service(App::new().route("/", web::get().to(index))).await;
+                run(app, iters).await
+            })
+        })
+    });
+
+    group.bench_function("normalize_path", |b| {
+        b.iter_custom(|iters| {
+            actix_web::rt::System::new().block_on(async {
+                let app = init_service(
+                    App::new()
+                        .wrap(NormalizePath::default())
+                        .route("/", web::get().to(index)),
+                )
+                .await;
+                run(app, iters).await
+            })
+        })
+    });
+
+    group.bench_function("catch_panic", |b| {
+        b.iter_custom(|iters| {
+            actix_web::rt::System::new().block_on(async {
+                let app = init_service(
+                    App::new()
+                        .wrap(CatchPanic::default())
+                        .route("/", web::get().to(index)),
+                )
+                .await;
+                run(app, iters).await
+            })
+        })
+    });
+
+    group.bench_function("response_cache", |b| {
+        b.iter_custom(|iters| {
+            actix_web::rt::System::new().block_on(async {
+                let app = init_service(
+                    App::new()
+                        .wrap(ResponseCache::new())
+                        .route("/", web::get().to(index)),
+                )
+                .await;
+                run(app, iters).await
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_middleware);
+criterion_main!(benches);
@@ -0,0 +1,191 @@
+#![allow(missing_docs)]
+
+use std::{
+    fs::File,
+    future::poll_fn,
+    io::{self, Write as _},
+    pin::pin,
+    time::Duration,
+};
+
+use actix_web::{
+    body::{self, MessageBody},
+    test::TestRequest,
+    Responder as _,
+};
+use actix_web_lab::{
+    body::{coalesce, SendFile},
+    respond::{Download, NdJson},
+    sse,
+};
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use futures_util::stream;
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const EVENTS: usize = 10_000;
+
+fn rt() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+fn events() -> Vec<sse::Event> {
+    (0..EVENTS)
+        .map(|n| {
+            sse::Data::new(format!(
+                "{{\"seq\":{n},\"msg\":\"tick\"}}\n{{\"more\":true}}"
+            ))
+            .id(n.to_string())
+            .event("tick")
+            .into()
+        })
+        .collect()
+}
+
+fn bench_sse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sse");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+
+    group.bench_function("serialize_into", |b| {
+        let events = events();
+        let mut buf = BytesMut::new();
+
+        b.iter(|| {
+            for ev in &events {
+                ev.serialize_into(&mut buf);
+                drop(buf.split());
+            }
+        })
+    });
+
+    group.bench_function("stream", |b| {
+        let rt = rt();
+
+        b.iter_batched(
+            || sse::Sse::from_infallible_stream(stream::iter(events())),
+            |body| rt.block_on(body::to_bytes(body.boxed())).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn bench_ndjson(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ndjson");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+
+    group.bench_function("stream", |b| {
+        let rt = rt();
+
+        b.iter_batched(
+            || {
+                let items = (0..EVENTS).map(|n| json!({ "seq": n, "msg": "tick" }));
+                NdJson::new_infallible(stream::iter(items.collect::<Vec<_>>()))
+            },
+            |body| {
+                rt.block_on(body::to_bytes(body.into_body_stream().boxed()))
+                    .unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+/// Writes each chunk of `body` to `out` separately, as a server would with a socket.
+async fn write_chunks<B: MessageBody>(body: B, out: &mut File) -> io::Result<()> {
+    let mut body = pin!(body);
+
+    while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+        out.write_all(&chunk.map_err(|_| io::ErrorKind::Other)?)?;
+    }
+
+    Ok(())
+}
+
+fn bench_coalesce(c: &mut Criterion) {
+    let mut group = c.benchmark_group("coalesce");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+
+    let path = std::env::temp_dir().join(format!("actix-web-lab-bench-{}", std::process::id()));
+    let mut out = File::create(&path).unwrap();
+
+    let ndjson = || {
+        let items = (0..EVENTS).map(|n| json!({ "seq": n, "msg": "tick" }));
+        NdJson::new_infallible(stream::iter(items.collect::<Vec<_>>())).into_body_stream()
+    };
+
+    group.bench_function("ndjson_write_per_item", |b| {
+        let rt = rt();
+
+        b.iter_batched(
+            ndjson,
+            |body| rt.block_on(write_chunks(body, &mut out)).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("ndjson_write_coalesced", |b| {
+        let rt = rt();
+
+        b.iter_batched(
+            || coalesce(ndjson(), 16 * 1024, Duration::from_millis(5)),
+            |body| rt.block_on(write_chunks(body, &mut out)).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+
+    drop(out);
+    let _ = std::fs::remove_file(path);
+}
+
+const FILE_SIZE: usize = 16 * 1024 * 1024;
+
+fn bench_file(c: &mut Criterion) {
+    let mut group = c.benchmark_group("file");
+    group.throughput(Throughput::Bytes(FILE_SIZE as u64));
+
+    let path =
+        std::env::temp_dir().join(format!("actix-web-lab-bench-file-{}", std::process::id()));
+    std::fs::write(&path, vec![b'x'; FILE_SIZE]).unwrap();
+
+    group.bench_function("download_reader", |b| {
+        let rt = rt();
+        let req = TestRequest::default().to_http_request();
+
+        b.iter(|| {
+            rt.block_on(async {
+                let file = tokio::fs::File::open(&path).await.unwrap();
+                let res = Download::from_reader(file, "file", mime::APPLICATION_OCTET_STREAM)
+                    .content_length(FILE_SIZE as u64)
+                    .respond_to(&req);
+                body::to_bytes(res.into_body()).await.unwrap()
+            })
+        })
+    });
+
+    group.bench_function("send_file", |b| {
+        let rt = rt();
+
+        b.iter(|| {
+            rt.block_on(async {
+                let body = SendFile::open(&path).await.unwrap();
+                body::to_bytes(body).await.unwrap()
+            })
+        })
+    });
+
+    group.finish();
+
+    let _ = std::fs::remove_file(path);
+}
+
+criterion_group!(benches, bench_sse, bench_ndjson, bench_coalesce, bench_file);
+criterion_main!(benches);
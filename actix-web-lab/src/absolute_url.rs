@@ -0,0 +1,150 @@
+//! Absolute URL generation.
+//!
+//! See [`url_for_absolute()`] docs.
+
+use actix_http::header::Header as _;
+use actix_web::{error::UrlGenerationError, HttpRequest};
+use url::Url;
+
+use crate::{
+    extract::{Host, TrustedProxies},
+    header::XForwardedPrefix,
+};
+
+/// Generates an externally-visible absolute URL for a named resource.
+///
+/// Like [`HttpRequest::url_for()`], but the scheme and host are resolved in the same way as the
+/// [`Host`] extractor, so forwarding headers are only honored when the request comes from a
+/// [trusted proxy](TrustedProxies). For requests from trusted proxies, the path is also prefixed
+/// with the [`X-Forwarded-Prefix`](XForwardedPrefix) header, if present and valid.
+///
+/// URLs for external resources are returned unchanged.
+///
+/// Useful for building URLs for redirects, `Location` headers, and emails.
+///
+/// # Errors
+/// Returns the same errors as [`HttpRequest::url_for()`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header, HttpRequest, HttpResponse};
+/// use actix_web_lab::util::url_for_absolute;
+///
+/// async fn create_user(req: HttpRequest) -> actix_web::Result<HttpResponse> {
+///     let url = url_for_absolute(&req, "user", ["42"])?;
+///
+///     Ok(HttpResponse::Created()
+///         .insert_header((header::LOCATION, url.as_str()))
+///         .finish())
+/// }
+/// ```
+pub fn url_for_absolute<U, I>(
+    req: &HttpRequest,
+    name: &str,
+    elements: U,
+) -> Result<Url, UrlGenerationError>
+where
+    U: IntoIterator<Item = I>,
+    I: AsRef<str>,
+{
+    let url = req.url_for(name, elements)?;
+
+    // actix-web bases internal resource URLs on connection info
+    let internal_base = {
+        let conn = req.connection_info();
+        Url::parse(&format!("{}://{}", conn.scheme(), conn.host()))?
+    };
+
+    if url.origin() != internal_base.origin() {
+        return Ok(url);
+    }
+
+    let host = Host::resolve(req);
+    let mut abs_url = Url::parse(&format!("{}://{}", host.scheme(), host.authority()))?;
+
+    let prefix = if TrustedProxies::is_request_trusted(req) {
+        XForwardedPrefix::parse(req).ok()
+    } else {
+        None
+    };
+
+    match prefix {
+        Some(prefix) => {
+            let prefix = prefix.path().trim_end_matches('/');
+            abs_url.set_path(&format!("{prefix}{}", url.path()));
+        }
+        None => abs_url.set_path(url.path()),
+    }
+
+    abs_url.set_query(url.query());
+    abs_url.set_fragment(url.fragment());
+
+    Ok(abs_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        dev::Service,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    async fn generated_url(
+        app: &impl Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse,
+            Error = actix_web::Error,
+        >,
+        req: TestRequest,
+    ) -> String {
+        let res = call_service(app, req.uri("/api/users/1").to_request()).await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn resolves_host_and_prefix() {
+        let app = init_service(
+            App::new()
+                .app_data(TrustedProxies::new().trust("10.0.0.0/8"))
+                .external_resource("docs", "https://docs.example.com/{page}")
+                .service(
+                    web::scope("/api").service(web::resource("/users/{id}").name("user").to(
+                        |req: HttpRequest| async move {
+                            let user = url_for_absolute(&req, "user", ["42"]).unwrap();
+                            let docs = url_for_absolute(&req, "docs", ["intro"]).unwrap();
+                            HttpResponse::Ok().body(format!("{user} {docs}"))
+                        },
+                    )),
+                ),
+        )
+        .await;
+
+        let proxied = || {
+            TestRequest::default()
+                .insert_header(("host", "internal:8080"))
+                .insert_header(("x-forwarded-host", "example.com"))
+                .insert_header(("x-forwarded-proto", "https"))
+                .insert_header(("x-forwarded-prefix", "/app"))
+        };
+
+        assert_eq!(
+            generated_url(&app, proxied().peer_addr("10.0.0.1:1234".parse().unwrap())).await,
+            "https://example.com/app/api/users/42 https://docs.example.com/intro",
+        );
+
+        assert_eq!(
+            generated_url(&app, proxied().peer_addr("192.0.2.1:1234".parse().unwrap())).await,
+            "http://internal:8080/api/users/42 https://docs.example.com/intro",
+        );
+
+        assert_eq!(
+            generated_url(&app, proxied()).await,
+            "http://internal:8080/api/users/42 https://docs.example.com/intro",
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Asynchronous job responders and status polling endpoint.
+//!
+//! See [`Accepted`] and [`job_status()`] docs.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use actix_web::{
+    body::BoxBody,
+    dev::HttpServiceFactory,
+    error,
+    http::header::{self, HeaderValue},
+    web, HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
+};
+use serde_json::json;
+
+use crate::BoxError;
+
+/// Responder for `202 Accepted` responses to requests that start asynchronous jobs.
+///
+/// The response includes a `Location` header pointing at a URL the client can poll to check on the
+/// job's progress and, optionally, a `Retry-After` header suggesting how long to wait before
+/// polling. See [`job_status()`] for a ready-made status polling endpoint.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::respond::Accepted;
+///
+/// #[post("/reports")]
+/// async fn create_report() -> impl Responder {
+///     let job_id = "42"; // e.g., enqueue a job
+///
+///     Accepted::with_status_url(format!("/jobs/{job_id}")).retry_after(Duration::from_secs(5))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Accepted {
+    status_url: Option<String>,
+    retry_after: Option<Duration>,
+}
+
+impl Accepted {
+    /// Constructs a new `202 Accepted` responder without a status URL.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new `202 Accepted` responder with a `Location` header of `status_url`.
+    pub fn with_status_url(status_url: impl Into<String>) -> Self {
+        Self {
+            status_url: Some(status_url.into()),
+            retry_after: None,
+        }
+    }
+
+    /// Sets the `Retry-After` header, rounded down to whole seconds.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+}
+
+impl Responder for Accepted {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut res = HttpResponse::Accepted();
+
+        if let Some(status_url) = self.status_url {
+            res.insert_header((header::LOCATION, status_url));
+        }
+
+        insert_retry_after(&mut res, self.retry_after);
+
+        res.finish()
+    }
+}
+
+fn insert_retry_after(res: &mut HttpResponseBuilder, retry_after: Option<Duration>) {
+    if let Some(retry_after) = retry_after {
+        res.insert_header((
+            header::RETRY_AFTER,
+            HeaderValue::from(retry_after.as_secs()),
+        ));
+    }
+}
+
+/// Status of an asynchronous job, as reported by a [`JobStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JobStatus {
+    /// Job has not finished yet.
+    Pending {
+        /// Suggested time for clients to wait before polling again.
+        retry_after: Option<Duration>,
+    },
+
+    /// Job finished successfully.
+    Completed {
+        /// URL of the job's result, if it has one.
+        location: Option<String>,
+    },
+
+    /// Job failed.
+    Failed {
+        /// Description of the failure, which is shown to clients.
+        error: String,
+    },
+}
+
+/// A store of asynchronous job statuses, used by [`job_status()`] to answer status polls.
+pub trait JobStore: 'static {
+    /// Error type returned when statuses cannot be loaded.
+    type Error: Into<BoxError> + 'static;
+
+    /// Returns the status of the job with the given ID, or `None` if there is no such job.
+    fn status(&self, id: &str) -> impl Future<Output = Result<Option<JobStatus>, Self::Error>>;
+}
+
+/// A [`JobStore`] that keeps job statuses in memory.
+///
+/// Cloning produces a handle to the same store, so it can be shared between workers and with the
+/// tasks running the jobs.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryJobStore {
+    jobs: Arc<Mutex<HashMap<String, JobStatus>>>,
+}
+
+impl InMemoryJobStore {
+    /// Constructs a new, empty job store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the status of the job with the given ID.
+    pub fn set(&self, id: impl Into<String>, status: JobStatus) {
+        self.jobs.lock().unwrap().insert(id.into(), status);
+    }
+
+    /// Removes the job with the given ID.
+    pub fn remove(&self, id: &str) {
+        self.jobs.lock().unwrap().remove(id);
+    }
+}
+
+impl JobStore for InMemoryJobStore {
+    type Error = Infallible;
+
+    async fn status(&self, id: &str) -> Result<Option<JobStatus>, Self::Error> {
+        Ok(self.jobs.lock().unwrap().get(id).cloned())
+    }
+}
+
+/// Constructs a status polling endpoint for asynchronous jobs at `GET {path}/{id}`.
+///
+/// Responds based on the job's status in `store`:
+/// - Pending: `200 OK` with `{"status": "pending"}` and a `Retry-After` header, if set.
+/// - Completed with a result location: `303 See Other` redirecting to the result, with
+///   `{"status": "completed", "location": "..."}`.
+/// - Completed without a result location: `200 OK` with `{"status": "completed"}`.
+/// - Failed: `200 OK` with `{"status": "failed", "error": "..."}`.
+/// - Unknown job: `404 Not Found`.
+///
+/// Errors from the store result in `500 Internal Server Error` responses.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{web, App, Responder};
+/// use actix_web_lab::{
+///     respond::Accepted,
+///     web::{job_status, InMemoryJobStore, JobStatus},
+/// };
+///
+/// async fn create_report(jobs: web::Data<InMemoryJobStore>) -> impl Responder {
+///     let job_id = "42";
+///     jobs.set(job_id, JobStatus::Pending { retry_after: None });
+///
+///     // ...spawn job that updates status when done...
+///
+///     Accepted::with_status_url(format!("/jobs/{job_id}")).retry_after(Duration::from_secs(5))
+/// }
+///
+/// let jobs = InMemoryJobStore::new();
+///
+/// App::new()
+///     .app_data(web::Data::new(jobs.clone()))
+///     .route("/reports", web::post().to(create_report))
+///     .service(job_status("/jobs", jobs))
+/// # ;
+/// ```
+pub fn job_status<St: JobStore>(path: &str, store: St) -> impl HttpServiceFactory {
+    let store = Rc::new(store);
+
+    web::resource(format!("{}/{{id}}", path.trim_end_matches('/'))).route(web::get().to(
+        move |id: web::Path<String>| {
+            let store = Rc::clone(&store);
+
+            async move {
+                let status = store
+                    .status(&id)
+                    .await
+                    .map_err(|err| error::ErrorInternalServerError(err.into()))?;
+
+                Ok::<_, actix_web::Error>(status_response(status))
+            }
+        },
+    ))
+}
+
+fn status_response(status: Option<JobStatus>) -> HttpResponse {
+    match status {
+        None => HttpResponse::NotFound().finish(),
+
+        Some(JobStatus::Pending { retry_after }) => {
+            let mut res = HttpResponse::Ok();
+            insert_retry_after(&mut res, retry_after);
+            res.json(json!({ "status": "pending" }))
+        }
+
+        Some(JobStatus::Completed {
+            location: Some(location),
+        }) => HttpResponse::SeeOther()
+            .insert_header((header::LOCATION, location.as_str()))
+            .json(json!({ "status": "completed", "location": location })),
+
+        Some(JobStatus::Completed { location: None }) => {
+            HttpResponse::Ok().json(json!({ "status": "completed" }))
+        }
+
+        Some(JobStatus::Failed { error }) => {
+            HttpResponse::Ok().json(json!({ "status": "failed", "error": error }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::to_bytes,
+        http::StatusCode,
+        test::{self, TestRequest},
+        App,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn accepted_response() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Accepted::new().respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert!(!res.headers().contains_key(header::LOCATION));
+        assert!(!res.headers().contains_key(header::RETRY_AFTER));
+
+        let res = Accepted::with_status_url("/jobs/1")
+            .retry_after(Duration::from_millis(2500))
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/jobs/1");
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "2");
+        assert!(to_bytes(res.into_body()).await.unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn status_endpoint() {
+        let jobs = InMemoryJobStore::new();
+        jobs.set(
+            "pending",
+            JobStatus::Pending {
+                retry_after: Some(Duration::from_secs(5)),
+            },
+        );
+        jobs.set(
+            "done",
+            JobStatus::Completed {
+                location: Some("/reports/1".to_owned()),
+            },
+        );
+        jobs.set("done-no-result", JobStatus::Completed { location: None });
+        jobs.set(
+            "failed",
+            JobStatus::Failed {
+                error: "out of paper".to_owned(),
+            },
+        );
+
+        let app = test::init_service(App::new().service(job_status("/jobs/", jobs))).await;
+
+        let req = TestRequest::with_uri("/jobs/pending").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "5");
+        let body = to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, r#"{"status":"pending"}"#);
+
+        let req = TestRequest::with_uri("/jobs/done").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/reports/1");
+        let body = to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, r#"{"location":"/reports/1","status":"completed"}"#);
+
+        let req = TestRequest::with_uri("/jobs/done-no-result").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, r#"{"status":"completed"}"#);
+
+        let req = TestRequest::with_uri("/jobs/failed").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, r#"{"error":"out of paper","status":"failed"}"#);
+
+        let req = TestRequest::with_uri("/jobs/unknown").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,507 @@
+//! Operational control endpoints.
+//!
+//! See [`AdminScope`] docs.
+
+use std::{fmt, rc::Rc};
+
+use actix_web::{
+    guard::{self, Guard},
+    web::{self, Json, Query},
+    HttpResponse, Scope,
+};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{filter::LevelFilter, reload};
+
+use crate::middleware::{MaintenanceHandle, PanicCounters};
+
+/// Runtime control over the global log level.
+trait LogLevelControl {
+    fn current(&self) -> Result<LevelFilter, reload::Error>;
+    fn set(&self, level: LevelFilter) -> Result<(), reload::Error>;
+}
+
+impl<S: 'static> LogLevelControl for reload::Handle<LevelFilter, S> {
+    fn current(&self) -> Result<LevelFilter, reload::Error> {
+        self.with_current(|level| *level)
+    }
+
+    fn set(&self, level: LevelFilter) -> Result<(), reload::Error> {
+        self.reload(level)
+    }
+}
+
+type CacheInvalidator = Rc<dyn Fn(Option<&str>)>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MaintenanceState {
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LogLevelState {
+    level: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InvalidateQuery {
+    key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResetPanicsQuery {
+    route: Option<String>,
+}
+
+/// A scope of operational control endpoints, protected by an authorization guard.
+///
+/// Only endpoints for the subsystems that are configured are registered. Requests that do not pass
+/// the guard do not match the scope, so they fall through to the rest of the app (typically
+/// resulting in a 404 Not Found response).
+///
+/// If the guard checks a secret, such as a bearer token, it should compare it in constant time
+/// to avoid leaking the secret through response timing.
+///
+/// # Endpoints
+/// Paths are relative to the scope's path.
+///
+/// | Method | Path                | Configured With                                  | Description                                  |
+/// |--------|---------------------|--------------------------------------------------|----------------------------------------------|
+/// | GET    | `/maintenance`      | [`maintenance()`](Self::maintenance)             | Returns `{"enabled": bool}`.                 |
+/// | PUT    | `/maintenance`      | [`maintenance()`](Self::maintenance)             | Sets state from `{"enabled": bool}`.         |
+/// | GET    | `/log-level`        | [`log_level()`](Self::log_level)                 | Returns `{"level": "info"}`.                 |
+/// | PUT    | `/log-level`        | [`log_level()`](Self::log_level)                 | Sets level from `{"level": "debug"}`.        |
+/// | POST   | `/cache/invalidate` | [`cache_invalidator()`](Self::cache_invalidator) | Invalidates `?key=...`, or all if omitted.   |
+/// | POST   | `/shutdown`         | [`shutdown()`](Self::shutdown)                   | Initiates shutdown; returns `202 Accepted`.  |
+/// | GET    | `/panics`           | [`panic_counters()`](Self::panic_counters)       | Returns `{"/route": {"panics": 1, "disabled": false}}`. |
+/// | POST   | `/panics/reset`     | [`panic_counters()`](Self::panic_counters)       | Resets `?route=...`, or all if omitted.      |
+///
+/// # Examples
+/// ```
+/// use actix_web::{guard, App};
+/// use actix_web_lab::{middleware::MaintenanceMode, web::admin_scope};
+/// use tracing_subscriber::{filter::LevelFilter, prelude::*, reload};
+///
+/// let (filter, log_level) = reload::Layer::new(LevelFilter::INFO);
+/// tracing_subscriber::registry().with(filter).init();
+///
+/// let maintenance = MaintenanceMode::new().allow_path_prefix("/admin");
+///
+/// App::new()
+///     .service(
+///         admin_scope(
+///             "/admin",
+///             guard::fn_guard(|ctx| {
+///                 ctx.head()
+///                     .peer_addr
+///                     .is_some_and(|addr| addr.ip().is_loopback())
+///             }),
+///         )
+///         .maintenance(maintenance.handle())
+///         .log_level(log_level)
+///         .finish(),
+///     )
+///     .wrap(maintenance)
+/// # ;
+/// ```
+pub struct AdminScope {
+    path: String,
+    guard: Rc<dyn Guard>,
+    maintenance: Option<MaintenanceHandle>,
+    log_level: Option<Rc<dyn LogLevelControl>>,
+    cache_invalidator: Option<CacheInvalidator>,
+    shutdown: Option<Rc<dyn Fn()>>,
+    panic_counters: Option<PanicCounters>,
+}
+
+impl fmt::Debug for AdminScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminScope")
+            .field("path", &self.path)
+            .field("maintenance", &self.maintenance)
+            .field("log_level", &self.log_level.is_some())
+            .field("cache_invalidator", &self.cache_invalidator.is_some())
+            .field("shutdown", &self.shutdown.is_some())
+            .field("panic_counters", &self.panic_counters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AdminScope {
+    /// Constructs a new admin scope at `path`, accessible only to requests passing `auth_guard`.
+    pub fn new(path: impl Into<String>, auth_guard: impl Guard + 'static) -> Self {
+        Self {
+            path: path.into(),
+            guard: Rc::new(auth_guard),
+            maintenance: None,
+            log_level: None,
+            cache_invalidator: None,
+            shutdown: None,
+            panic_counters: None,
+        }
+    }
+
+    /// Exposes endpoints for toggling [maintenance mode](crate::middleware::MaintenanceMode).
+    pub fn maintenance(mut self, handle: MaintenanceHandle) -> Self {
+        self.maintenance = Some(handle);
+        self
+    }
+
+    /// Exposes endpoints for reading and changing the log level using a `tracing-subscriber`
+    /// reload handle.
+    pub fn log_level<S: 'static>(mut self, handle: reload::Handle<LevelFilter, S>) -> Self {
+        self.log_level = Some(Rc::new(handle));
+        self
+    }
+
+    /// Exposes an endpoint for invalidating cache entries.
+    ///
+    /// The invalidator is called with the requested key, or `None` if all entries should be
+    /// invalidated.
+    pub fn cache_invalidator(mut self, invalidate: impl Fn(Option<&str>) + 'static) -> Self {
+        self.cache_invalidator = Some(Rc::new(invalidate));
+        self
+    }
+
+    /// Exposes an endpoint for initiating server shutdown.
+    ///
+    /// The callback should only initiate shutdown (e.g., by sending on a channel or spawning
+    /// [`ServerHandle::stop()`](actix_web::dev::ServerHandle::stop)). Awaiting graceful shutdown
+    /// from within the callback is not possible since it would wait for the request that triggered
+    /// it.
+    pub fn shutdown(mut self, initiate: impl Fn() + 'static) -> Self {
+        self.shutdown = Some(Rc::new(initiate));
+        self
+    }
+
+    /// Exposes endpoints for inspecting and resetting [`CatchPanic`] panic counters.
+    ///
+    /// Resetting a route also re-enables it if it was disabled.
+    ///
+    /// [`CatchPanic`]: crate::middleware::CatchPanic
+    pub fn panic_counters(mut self, counters: PanicCounters) -> Self {
+        self.panic_counters = Some(counters);
+        self
+    }
+
+    /// Returns scope service to be registered on an `App`.
+    pub fn finish(self) -> Scope {
+        let auth_guard = self.guard;
+        let mut scope =
+            web::scope(&self.path).guard(guard::fn_guard(move |ctx| auth_guard.check(ctx)));
+
+        if let Some(handle) = self.maintenance {
+            let get_handle = handle.clone();
+
+            scope = scope.service(
+                web::resource("/maintenance")
+                    .route(web::get().to(move || {
+                        let enabled = get_handle.is_enabled();
+                        async move { Json(MaintenanceState { enabled }) }
+                    }))
+                    .route(web::put().to(move |body: Json<MaintenanceState>| {
+                        handle.set(body.enabled);
+                        let enabled = handle.is_enabled();
+                        async move { Json(MaintenanceState { enabled }) }
+                    })),
+            );
+        }
+
+        if let Some(control) = self.log_level {
+            let get_control = Rc::clone(&control);
+
+            scope = scope.service(
+                web::resource("/log-level")
+                    .route(web::get().to(move || {
+                        let res = log_level_response(get_control.current());
+                        async move { res }
+                    }))
+                    .route(web::put().to(move |body: Json<LogLevelState>| {
+                        let res = match body.level.parse::<LevelFilter>() {
+                            Ok(level) => log_level_response(control.set(level).map(|()| level)),
+                            Err(err) => HttpResponse::BadRequest().body(err.to_string()),
+                        };
+                        async move { res }
+                    })),
+            );
+        }
+
+        if let Some(invalidate) = self.cache_invalidator {
+            scope = scope.route(
+                "/cache/invalidate",
+                web::post().to(move |query: Query<InvalidateQuery>| {
+                    invalidate(query.key.as_deref());
+                    async { HttpResponse::NoContent().finish() }
+                }),
+            );
+        }
+
+        if let Some(initiate) = self.shutdown {
+            scope = scope.route(
+                "/shutdown",
+                web::post().to(move || {
+                    tracing::info!("shutdown initiated from admin endpoint");
+                    initiate();
+                    async { HttpResponse::Accepted().finish() }
+                }),
+            );
+        }
+
+        if let Some(counters) = self.panic_counters {
+            let get_counters = counters.clone();
+
+            scope = scope
+                .route(
+                    "/panics",
+                    web::get().to(move || {
+                        let snapshot = get_counters.snapshot();
+                        async move { Json(snapshot) }
+                    }),
+                )
+                .route(
+                    "/panics/reset",
+                    web::post().to(move |query: Query<ResetPanicsQuery>| {
+                        match query.route.as_deref() {
+                            Some(route) => counters.reset(route),
+                            None => counters.reset_all(),
+                        }
+                        async { HttpResponse::NoContent().finish() }
+                    }),
+                );
+        }
+
+        scope
+    }
+}
+
+fn log_level_response(level: Result<LevelFilter, reload::Error>) -> HttpResponse {
+    match level {
+        Ok(level) => HttpResponse::Ok().json(LogLevelState {
+            level: level.to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use actix_web::{
+        body::to_bytes,
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+    use tracing::subscriber::NoSubscriber;
+
+    use super::*;
+
+    const AUTH: (&str, &str) = ("authorization", "Bearer secret");
+
+    fn auth_guard() -> impl Guard {
+        guard::Header(AUTH.0, AUTH.1)
+    }
+
+    #[actix_web::test]
+    async fn requires_auth() {
+        let app = init_service(
+            App::new().service(
+                AdminScope::new("/admin", auth_guard())
+                    .maintenance(MaintenanceHandle::new())
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/admin/maintenance").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/admin/maintenance")
+            .insert_header(AUTH)
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn unconfigured_endpoints() {
+        let app =
+            init_service(App::new().service(AdminScope::new("/admin", auth_guard()).finish()))
+                .await;
+
+        for path in ["/admin/maintenance", "/admin/log-level", "/admin/shutdown"] {
+            let req = TestRequest::with_uri(path).insert_header(AUTH).to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[actix_web::test]
+    async fn toggle_maintenance() {
+        let handle = MaintenanceHandle::new();
+
+        let app = init_service(
+            App::new().service(
+                AdminScope::new("/admin", auth_guard())
+                    .maintenance(handle.clone())
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::put()
+            .uri("/admin/maintenance")
+            .insert_header(AUTH)
+            .set_json(MaintenanceState { enabled: true })
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            to_bytes(res.into_body()).await.ok().unwrap(),
+            r#"{"enabled":true}"#,
+        );
+        assert!(handle.is_enabled());
+    }
+
+    #[actix_web::test]
+    async fn change_log_level() {
+        let (_layer, handle) = reload::Layer::<_, NoSubscriber>::new(LevelFilter::INFO);
+
+        let app = init_service(
+            App::new().service(
+                AdminScope::new("/admin", auth_guard())
+                    .log_level(handle.clone())
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/admin/log-level")
+            .insert_header(AUTH)
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            to_bytes(res.into_body()).await.ok().unwrap(),
+            r#"{"level":"info"}"#,
+        );
+
+        let req = TestRequest::put()
+            .uri("/admin/log-level")
+            .insert_header(AUTH)
+            .set_json(LogLevelState {
+                level: "debug".to_owned(),
+            })
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(handle.clone_current(), Some(LevelFilter::DEBUG));
+
+        let req = TestRequest::put()
+            .uri("/admin/log-level")
+            .insert_header(AUTH)
+            .set_json(LogLevelState {
+                level: "loud".to_owned(),
+            })
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn inspect_and_reset_panics() {
+        use std::time::Duration;
+
+        use crate::middleware::CatchPanic;
+
+        let counters = PanicCounters::new();
+
+        let app = init_service(
+            App::new()
+                .service(
+                    AdminScope::new("/admin", auth_guard())
+                        .panic_counters(counters.clone())
+                        .finish(),
+                )
+                .route(
+                    "/disco",
+                    web::get().to(|| async {
+                        panic!("the disco");
+                        #[allow(unreachable_code)]
+                        ""
+                    }),
+                )
+                .wrap(
+                    CatchPanic::default()
+                        .counters(counters.clone())
+                        .disable_after(1, Duration::from_secs(60)),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/disco").to_request();
+        let res = actix_web::dev::Service::call(&app, req).await;
+        assert!(res.is_err());
+
+        let req = TestRequest::with_uri("/admin/panics")
+            .insert_header(AUTH)
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            to_bytes(res.into_body()).await.ok().unwrap(),
+            r#"{"/disco":{"panics":1,"disabled":true}}"#,
+        );
+
+        let req = TestRequest::post()
+            .uri("/admin/panics/reset?route=/disco")
+            .insert_header(AUTH)
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert!(!counters.is_disabled("/disco"));
+    }
+
+    #[actix_web::test]
+    async fn invalidate_cache_and_shutdown() {
+        let invalidated = Rc::new(RefCell::new(Vec::new()));
+        let shutdowns = Rc::new(RefCell::new(0));
+
+        let app = init_service(
+            App::new().service(
+                AdminScope::new("/admin", auth_guard())
+                    .cache_invalidator({
+                        let invalidated = Rc::clone(&invalidated);
+                        move |key| invalidated.borrow_mut().push(key.map(str::to_owned))
+                    })
+                    .shutdown({
+                        let shutdowns = Rc::clone(&shutdowns);
+                        move || *shutdowns.borrow_mut() += 1
+                    })
+                    .finish(),
+            ),
+        )
+        .await;
+
+        for uri in [
+            "/admin/cache/invalidate?key=users",
+            "/admin/cache/invalidate",
+        ] {
+            let req = TestRequest::post()
+                .uri(uri)
+                .insert_header(AUTH)
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        }
+
+        assert_eq!(*invalidated.borrow(), [Some("users".to_owned()), None]);
+
+        let req = TestRequest::post()
+            .uri("/admin/shutdown")
+            .insert_header(AUTH)
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(*shutdowns.borrow(), 1);
+    }
+}
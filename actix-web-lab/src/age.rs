@@ -0,0 +1,143 @@
+//! Age typed header.
+//!
+//! See [`Age`] docs.
+
+use std::{convert::Infallible, str, time::Duration};
+
+use actix_web::{
+    error::ParseError,
+    http::header::{from_one_raw_str, Header, HeaderName, HeaderValue, TryIntoHeaderValue, AGE},
+    HttpMessage,
+};
+
+/// Largest age value that should be sent, per [RFC 9111 §1.2.2].
+///
+/// [RFC 9111 §1.2.2]: https://www.rfc-editor.org/rfc/rfc9111#section-1.2.2
+const MAX_AGE_SECS: u64 = 2_147_483_648;
+
+/// The `Age` header, defined in [RFC 9111 §5.1].
+///
+/// The "Age" header field conveys the sender's estimate of the time since the response was
+/// generated or successfully validated at the origin server.
+///
+/// Values larger than 2<sup>31</sup> seconds are clamped when parsing and formatting.
+///
+/// # ABNF
+/// ```plain
+/// Age = delta-seconds
+/// ```
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::Age;
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(Age::from_secs(60));
+///
+/// assert_eq!(Age::from(Duration::from_secs(60)).as_secs(), 60);
+/// ```
+///
+/// [RFC 9111 §5.1]: https://www.rfc-editor.org/rfc/rfc9111#section-5.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Age(u64);
+
+impl Age {
+    /// Constructs a new `Age` header from a number of seconds.
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs.min(MAX_AGE_SECS))
+    }
+
+    /// Returns age in seconds.
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns age as a `Duration`.
+    pub fn as_duration(&self) -> Duration {
+        Duration::from_secs(self.0)
+    }
+}
+
+impl str::FromStr for Age {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let val = val.trim();
+
+        if val.is_empty() || !val.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseError::Header);
+        }
+
+        // delta-seconds is a sequence of digits; overflowing values are clamped
+        Ok(Self::from_secs(val.parse().unwrap_or(MAX_AGE_SECS)))
+    }
+}
+
+impl TryIntoHeaderValue for Age {
+    type Error = Infallible;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        Ok(HeaderValue::from(self.0))
+    }
+}
+
+impl Header for Age {
+    fn name() -> HeaderName {
+        AGE
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(Self::name()))
+    }
+}
+
+impl From<Duration> for Age {
+    fn from(age: Duration) -> Self {
+        Self::from_secs(age.as_secs())
+    }
+}
+
+impl From<Age> for Duration {
+    fn from(age: Age) -> Self {
+        age.as_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    #[test]
+    fn missing_header() {
+        assert_parse_fail::<Age, _, _>([""; 0]);
+        assert_parse_fail::<Age, _, _>([""]);
+    }
+
+    #[test]
+    fn bad_header() {
+        assert_parse_fail::<Age, _, _>(["-1"]);
+        assert_parse_fail::<Age, _, _>(["+1"]);
+        assert_parse_fail::<Age, _, _>(["1.5"]);
+        assert_parse_fail::<Age, _, _>(["abc"]);
+    }
+
+    #[test]
+    fn good_header() {
+        assert_parse_eq::<Age, _, _>(["0"], Age::from_secs(0));
+        assert_parse_eq::<Age, _, _>([" 60 "], Age::from_secs(60));
+        assert_parse_eq::<Age, _, _>(["99999999999999999999999"], Age::from_secs(MAX_AGE_SECS));
+    }
+
+    #[test]
+    fn clamps_large_values() {
+        assert_eq!(Age::from_secs(u64::MAX).as_secs(), MAX_AGE_SECS);
+        assert_eq!(
+            Age::from_secs(3600).try_into_value().unwrap(),
+            HeaderValue::from_static("3600"),
+        );
+    }
+}
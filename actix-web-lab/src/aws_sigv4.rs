@@ -0,0 +1,947 @@
+//! AWS Signature Version 4 request signature scheme.
+//!
+//! See [`AwsSigV4`] docs.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{
+    http::{header, StatusCode},
+    web::Bytes,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use hmac::{digest::CtOutput, Mac as _, SimpleHmac};
+use sha2::{Digest as _, Sha256};
+
+use crate::extract::RequestSignatureScheme;
+
+type HmacSha256 = SimpleHmac<Sha256>;
+
+/// Signing algorithm identifier.
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Credential scope terminator.
+const SCOPE_TERMINATOR: &str = "aws4_request";
+
+/// Payload hash placeholder used when the body is not signed.
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+const X_AMZ_DATE: &str = "x-amz-date";
+const X_AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+
+/// Configuration for the [`AwsSigV4`] signature scheme.
+///
+/// Must be registered as app data (using [`App::app_data()`]) for the scheme to find it.
+///
+/// [`App::app_data()`]: actix_web::App::app_data
+#[derive(Clone)]
+pub struct AwsSigV4Config {
+    keys: HashMap<String, String>,
+    region: Option<String>,
+    service: Option<String>,
+    max_skew: Duration,
+}
+
+impl AwsSigV4Config {
+    /// Constructs a new configuration with no keys, accepting any region and service, and allowing
+    /// 15 minutes of clock skew.
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+            region: None,
+            service: None,
+            max_skew: Duration::from_secs(15 * 60),
+        }
+    }
+
+    /// Adds an access key ID and its secret access key.
+    pub fn key(mut self, access_key_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        self.keys.insert(access_key_id.into(), secret.into());
+        self
+    }
+
+    /// Requires the credential scope to use the given region.
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Requires the credential scope to use the given service name.
+    ///
+    /// Requests for the `s3` service use S3's path canonicalization rules.
+    pub fn service(mut self, service: impl Into<String>) -> Self {
+        self.service = Some(service.into());
+        self
+    }
+
+    /// Sets the maximum allowed difference between the request timestamp and the current time.
+    pub fn max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+}
+
+impl fmt::Debug for AwsSigV4Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsSigV4Config")
+            .field("access_key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .field("max_skew", &self.max_skew)
+            .finish()
+    }
+}
+
+impl Default for AwsSigV4Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors that can occur when verifying an AWS Signature Version 4 signature.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum AwsSigV4Error {
+    /// No [`AwsSigV4Config`] was registered as app data.
+    #[display("AWS SigV4 configuration is missing from app data")]
+    MissingConfig,
+
+    /// Request does not have an `Authorization` header.
+    #[display("Authorization header is missing")]
+    MissingAuthorization,
+
+    /// The `Authorization` header is not a valid SigV4 authorization.
+    #[display("Authorization header is malformed")]
+    MalformedAuthorization,
+
+    /// Request does not have a valid `X-Amz-Date` (or `Date`) header, or it is not signed.
+    #[display("Request timestamp is missing or invalid")]
+    InvalidDate,
+
+    /// Request timestamp is too far from the current time.
+    #[display("Request timestamp is outside the allowed clock skew")]
+    RequestTimeTooSkewed,
+
+    /// The credential scope does not match the request date or the configured region or service.
+    #[display("Credential scope is invalid")]
+    InvalidScope,
+
+    /// The access key ID is not known.
+    #[display("Access key ID is not recognized")]
+    UnknownAccessKey,
+
+    /// The `X-Amz-Content-SHA256` header uses an unsupported value, such as chunked signing.
+    #[display("Payload signing mode is not supported")]
+    UnsupportedPayload,
+
+    /// The `X-Amz-Content-SHA256` header does not match the body.
+    #[display("Payload hash does not match body")]
+    PayloadHashMismatch,
+
+    /// The signature does not match the request.
+    #[display("Signature does not match")]
+    SignatureMismatch,
+}
+
+impl ResponseError for AwsSigV4Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::MissingAuthorization => StatusCode::UNAUTHORIZED,
+            Self::MalformedAuthorization | Self::InvalidDate | Self::UnsupportedPayload => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::RequestTimeTooSkewed
+            | Self::InvalidScope
+            | Self::UnknownAccessKey
+            | Self::PayloadHashMismatch
+            | Self::SignatureMismatch => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// [AWS Signature Version 4] verification scheme for use with [`RequestSignature`].
+///
+/// Verifies requests signed with `AWS4-HMAC-SHA256` in the `Authorization` header, as sent by AWS
+/// SDKs and S3-compatible clients. Keys, and optionally the expected region and service, are read
+/// from an [`AwsSigV4Config`] registered as app data.
+///
+/// Verification includes:
+/// - reconstructing the canonical request from the method, path, query string, and signed
+///   headers, which must include `host`;
+/// - checking the `X-Amz-Date` (or `Date`) timestamp is signed and within the allowed clock skew;
+/// - checking the credential scope's date, region, service, and terminator;
+/// - hashing the body as it is received and, if sent, checking it against the
+///   `X-Amz-Content-SHA256` header; `UNSIGNED-PAYLOAD` is accepted but chunked (`STREAMING-*`)
+///   payload signing is not supported;
+/// - comparing signatures in constant time.
+///
+/// Pre-signed URLs (query string authentication) are not supported.
+///
+/// The resulting signature is the verified 32-byte signature.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, App};
+/// use actix_web_lab::{
+///     extract::RequestSignature,
+///     signature_schemes::{AwsSigV4, AwsSigV4Config},
+/// };
+///
+/// #[post("/upload")]
+/// async fn upload(body: RequestSignature<web::Bytes, AwsSigV4>) -> String {
+///     let (body, _signature) = body.into_parts();
+///     format!("verified {} bytes", body.len())
+/// }
+///
+/// App::new()
+///     .app_data(
+///         AwsSigV4Config::new()
+///             .key("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY")
+///             .region("us-east-1")
+///             .service("s3"),
+///     )
+///     .service(upload);
+/// ```
+///
+/// [AWS Signature Version 4]: https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv.html
+/// [`RequestSignature`]: crate::extract::RequestSignature
+pub struct AwsSigV4 {
+    secret: String,
+    timestamp: String,
+    scope: String,
+    canonical_request: String,
+    claimed_payload_hash: Option<String>,
+    body: Sha256,
+}
+
+impl fmt::Debug for AwsSigV4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsSigV4")
+            .field("timestamp", &self.timestamp)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parsed `Authorization` header fields.
+struct Authorization<'a> {
+    access_key_id: &'a str,
+    date: &'a str,
+    region: &'a str,
+    service: &'a str,
+    terminator: &'a str,
+    signed_headers: Vec<&'a str>,
+    signature: &'a str,
+}
+
+impl<'a> Authorization<'a> {
+    fn parse(req: &'a HttpRequest) -> Result<Self, AwsSigV4Error> {
+        let value = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .ok_or(AwsSigV4Error::MissingAuthorization)?
+            .to_str()
+            .map_err(|_| AwsSigV4Error::MalformedAuthorization)?;
+
+        let params = value
+            .strip_prefix(ALGORITHM)
+            .filter(|params| params.starts_with(' '))
+            .ok_or(AwsSigV4Error::MalformedAuthorization)?;
+
+        let mut credential = None;
+        let mut signed_headers = None;
+        let mut signature = None;
+
+        for param in params.split(',') {
+            let (key, value) = param
+                .trim()
+                .split_once('=')
+                .ok_or(AwsSigV4Error::MalformedAuthorization)?;
+
+            match key {
+                "Credential" => credential = Some(value),
+                "SignedHeaders" => signed_headers = Some(value),
+                "Signature" => signature = Some(value),
+                _ => return Err(AwsSigV4Error::MalformedAuthorization),
+            }
+        }
+
+        let (Some(credential), Some(signed_headers), Some(signature)) =
+            (credential, signed_headers, signature)
+        else {
+            return Err(AwsSigV4Error::MalformedAuthorization);
+        };
+
+        let mut credential = credential.splitn(5, '/');
+        let mut next = || {
+            credential
+                .next()
+                .ok_or(AwsSigV4Error::MalformedAuthorization)
+        };
+
+        let auth = Self {
+            access_key_id: next()?,
+            date: next()?,
+            region: next()?,
+            service: next()?,
+            terminator: next()?,
+            signed_headers: signed_headers.split(';').collect(),
+            signature,
+        };
+
+        let headers_sorted = auth.signed_headers.windows(2).all(|pair| pair[0] < pair[1]);
+        let headers_valid = auth
+            .signed_headers
+            .iter()
+            .all(|name| !name.is_empty() && !name.bytes().any(|b| b.is_ascii_uppercase()));
+
+        if !headers_sorted || !headers_valid || auth.signature.len() != 64 {
+            return Err(AwsSigV4Error::MalformedAuthorization);
+        }
+
+        Ok(auth)
+    }
+}
+
+impl AwsSigV4 {
+    fn from_request(req: &HttpRequest) -> Result<Self, AwsSigV4Error> {
+        let config = req
+            .app_data::<AwsSigV4Config>()
+            .ok_or(AwsSigV4Error::MissingConfig)?;
+
+        let auth = Authorization::parse(req)?;
+
+        let (date_header, timestamp) = [X_AMZ_DATE, "date"]
+            .into_iter()
+            .find_map(|name| Some((name, req.headers().get(name)?)))
+            .ok_or(AwsSigV4Error::InvalidDate)?;
+
+        let timestamp = timestamp.to_str().map_err(|_| AwsSigV4Error::InvalidDate)?;
+        let time = parse_timestamp(timestamp).ok_or(AwsSigV4Error::InvalidDate)?;
+
+        // the string to sign always uses the ISO 8601 basic format, even when the timestamp is
+        // taken from an HTTP-date formatted `Date` header
+        let timestamp = if date_header == X_AMZ_DATE {
+            timestamp.to_owned()
+        } else {
+            format_timestamp(time)
+        };
+
+        if !auth.signed_headers.contains(&date_header) || !auth.signed_headers.contains(&"host") {
+            return Err(AwsSigV4Error::MalformedAuthorization);
+        }
+
+        let now = SystemTime::now();
+        let skew = now
+            .duration_since(time)
+            .or_else(|_| time.duration_since(now))
+            .unwrap_or_default();
+
+        if skew > config.max_skew {
+            return Err(AwsSigV4Error::RequestTimeTooSkewed);
+        }
+
+        let region_ok = config.region.as_deref().map_or(true, |r| r == auth.region);
+        let service_ok = config
+            .service
+            .as_deref()
+            .map_or(true, |s| s == auth.service);
+
+        if !timestamp.starts_with(auth.date)
+            || auth.date.len() != 8
+            || !region_ok
+            || !service_ok
+            || auth.terminator != SCOPE_TERMINATOR
+        {
+            return Err(AwsSigV4Error::InvalidScope);
+        }
+
+        let secret = config
+            .keys
+            .get(auth.access_key_id)
+            .ok_or(AwsSigV4Error::UnknownAccessKey)?;
+
+        let claimed_payload_hash = match req.headers().get(X_AMZ_CONTENT_SHA256) {
+            None => None,
+            Some(hash) => {
+                let hash = hash
+                    .to_str()
+                    .map_err(|_| AwsSigV4Error::UnsupportedPayload)?;
+
+                if hash == UNSIGNED_PAYLOAD {
+                    Some(hash.to_owned())
+                } else if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    Some(hash.to_ascii_lowercase())
+                } else {
+                    return Err(AwsSigV4Error::UnsupportedPayload);
+                }
+            }
+        };
+
+        let canonical_request = canonical_request_head(req, &auth, auth.service == "s3")?;
+
+        Ok(Self {
+            secret: secret.clone(),
+            timestamp,
+            scope: format!(
+                "{}/{}/{}/{SCOPE_TERMINATOR}",
+                auth.date, auth.region, auth.service
+            ),
+            canonical_request,
+            claimed_payload_hash,
+            body: Sha256::new(),
+        })
+    }
+
+    fn signing_key(&self) -> HmacSha256 {
+        let mut key = format!("AWS4{}", self.secret).into_bytes();
+
+        // date, region, service, and terminator
+        for part in self.scope.split('/') {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+
+        HmacSha256::new_from_slice(&key).expect("HMAC accepts any key length")
+    }
+}
+
+impl RequestSignatureScheme for AwsSigV4 {
+    type Signature = [u8; 32];
+    type Error = AwsSigV4Error;
+
+    async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+        Self::from_request(req)
+    }
+
+    async fn consume_chunk(&mut self, _req: &HttpRequest, chunk: Bytes) -> Result<(), Self::Error> {
+        self.body.update(&chunk);
+        Ok(())
+    }
+
+    async fn finalize(mut self, _req: &HttpRequest) -> Result<Self::Signature, Self::Error> {
+        let body_hash = hex(&self.body.finalize_reset());
+
+        let payload_hash = match self.claimed_payload_hash.take() {
+            None => body_hash,
+            Some(claimed) if claimed == UNSIGNED_PAYLOAD => claimed,
+            Some(claimed) if claimed == body_hash => claimed,
+            Some(_) => return Err(AwsSigV4Error::PayloadHashMismatch),
+        };
+
+        self.canonical_request.push_str(&payload_hash);
+
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{}\n{}\n{}",
+            self.timestamp,
+            self.scope,
+            hex(&Sha256::digest(&self.canonical_request)),
+        );
+
+        let mut mac = self.signing_key();
+        mac.update(string_to_sign.as_bytes());
+        Ok(mac.finalize().into_bytes().into())
+    }
+
+    fn verify(
+        signature: Self::Signature,
+        req: &HttpRequest,
+    ) -> Result<Self::Signature, Self::Error> {
+        let auth = Authorization::parse(req)?;
+        let candidate = unhex::<32>(auth.signature).ok_or(AwsSigV4Error::MalformedAuthorization)?;
+
+        // constant-time comparison
+        if CtOutput::<HmacSha256>::new(signature.into())
+            == CtOutput::<HmacSha256>::new(candidate.into())
+        {
+            Ok(signature)
+        } else {
+            Err(AwsSigV4Error::SignatureMismatch)
+        }
+    }
+}
+
+/// Builds the canonical request, up to and including the signed headers line.
+fn canonical_request_head(
+    req: &HttpRequest,
+    auth: &Authorization<'_>,
+    is_s3: bool,
+) -> Result<String, AwsSigV4Error> {
+    let mut canonical = String::new();
+
+    // method
+    canonical.push_str(req.method().as_str());
+    canonical.push('\n');
+
+    // path
+    let path = match req.uri().path() {
+        "" => "/",
+        path => path,
+    };
+    let path = uri_encode(&percent_decode(path), false);
+    if is_s3 {
+        canonical.push_str(&path);
+    } else {
+        canonical.push_str(&uri_encode(path.as_bytes(), false));
+    }
+    canonical.push('\n');
+
+    // query string
+    let mut query = req
+        .query_string()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                uri_encode(&percent_decode(key), true),
+                uri_encode(&percent_decode(value), true),
+            )
+        })
+        .collect::<Vec<_>>();
+    query.sort();
+
+    for (idx, (key, value)) in query.iter().enumerate() {
+        if idx > 0 {
+            canonical.push('&');
+        }
+
+        let _ = write!(canonical, "{key}={value}");
+    }
+    canonical.push('\n');
+
+    // headers
+    for &name in &auth.signed_headers {
+        let mut values = req
+            .headers()
+            .get_all(name)
+            .map(|value| value.to_str().map(collapse_whitespace))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| AwsSigV4Error::MalformedAuthorization)?;
+
+        // HTTP/2 requests carry the host in the URI authority instead of a header
+        if values.is_empty() && name == "host" {
+            values.extend(req.uri().authority().map(|host| host.as_str().to_owned()));
+        }
+
+        if values.is_empty() {
+            return Err(AwsSigV4Error::MalformedAuthorization);
+        }
+
+        let _ = writeln!(canonical, "{name}:{}", values.join(","));
+    }
+    canonical.push('\n');
+
+    // signed headers
+    canonical.push_str(&auth.signed_headers.join(";"));
+    canonical.push('\n');
+
+    Ok(canonical)
+}
+
+/// Parses an ISO 8601 basic format timestamp (e.g., `20150830T123600Z`) or an HTTP date.
+fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    if let Ok(date) = timestamp.parse::<header::HttpDate>() {
+        return Some(date.into());
+    }
+
+    let bytes = timestamp.as_bytes();
+
+    if bytes.len() != 16 || bytes[8] != b'T' || bytes[15] != b'Z' {
+        return None;
+    }
+
+    let num = |range: std::ops::Range<usize>| -> Option<u64> {
+        let digits = timestamp.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+
+    let (year, month, day) = (num(0..4)?, num(4..6)?, num(6..8)?);
+    let (hour, min, sec) = (num(9..11)?, num(11..13)?, num(13..15)?);
+
+    if !(1970..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || min > 59
+        || sec > 60
+    {
+        return None;
+    }
+
+    // days from civil algorithm; year is at least 1970 so no negative intermediate values
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + hour * 3_600 + min * 60 + sec;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Formats a time as an ISO 8601 basic format timestamp (e.g., `20150830T123600Z`).
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // civil from days algorithm; inverse of the one in `parse_timestamp`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let m = (5 * doy + 2) / 153;
+    let day = doy - (153 * m + 2) / 5 + 1;
+    let month = if m < 10 { m + 3 } else { m - 9 };
+    let year = era * 400 + yoe + u64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60,
+    )
+}
+
+/// Decodes percent-encoded octets, leaving invalid escapes as-is.
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            if let Some(byte) = input
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+
+        out.push(bytes[idx]);
+        idx += 1;
+    }
+
+    out
+}
+
+/// Percent-encodes all octets except unreserved characters (and `/`, unless `encode_slash`).
+fn uri_encode(input: &[u8], encode_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for &byte in input {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            byte => {
+                let _ = write!(out, "%{byte:02X}");
+            }
+        }
+    }
+
+    out
+}
+
+/// Trims a header value and collapses sequential spaces.
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+pub(crate) fn unhex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != N * 2 {
+        return None;
+    }
+
+    let mut out = [0; N];
+
+    for (idx, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web::{self, Bytes},
+        App,
+    };
+
+    use super::*;
+    use crate::extract::RequestSignature;
+
+    // from the AWS SigV4 test suite
+    const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+    const SECRET: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    const TIMESTAMP: &str = "20150830T123600Z";
+
+    fn config() -> AwsSigV4Config {
+        AwsSigV4Config::new()
+            .key(ACCESS_KEY_ID, SECRET)
+            .region("us-east-1")
+            .service("service")
+            .max_skew(Duration::from_secs(100 * 365 * 24 * 60 * 60))
+    }
+
+    fn authorization(signed_headers: &str, signature: &str) -> String {
+        format!(
+            "{ALGORITHM} Credential={ACCESS_KEY_ID}/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders={signed_headers}, Signature={signature}"
+        )
+    }
+
+    async fn status(config: AwsSigV4Config, req: TestRequest) -> StatusCode {
+        let app = init_service(App::new().app_data(config).default_service(web::to(
+            |body: RequestSignature<Bytes, AwsSigV4>| async move {
+                let (body, _) = body.into_parts();
+                body
+            },
+        )))
+        .await;
+
+        call_service(&app, req.to_request()).await.status()
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            parse_timestamp(TIMESTAMP).unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_440_938_160),
+        );
+        assert_eq!(
+            parse_timestamp("Sun, 30 Aug 2015 12:36:00 GMT").unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_440_938_160),
+        );
+        assert!(parse_timestamp("20150830T123600").is_none());
+        assert!(parse_timestamp("20151330T123600Z").is_none());
+        assert!(parse_timestamp("2015083XT123600Z").is_none());
+
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_440_938_160)),
+            TIMESTAMP,
+        );
+        assert_eq!(format_timestamp(UNIX_EPOCH), "19700101T000000Z");
+        assert_eq!(
+            format_timestamp(parse_timestamp("20240229T235959Z").unwrap()),
+            "20240229T235959Z",
+        );
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(
+            uri_encode(&percent_decode("/a%20b/c~d"), false),
+            "/a%20b/c~d"
+        );
+        assert_eq!(uri_encode(b"a+b=c/d", true), "a%2Bb%3Dc%2Fd");
+        assert_eq!(collapse_whitespace("  a   b  "), "a b");
+    }
+
+    #[actix_web::test]
+    async fn get_vanilla() {
+        let req = || {
+            TestRequest::get()
+                .uri("/")
+                .insert_header(("host", "example.amazonaws.com"))
+                .insert_header((X_AMZ_DATE, TIMESTAMP))
+        };
+
+        let valid = authorization(
+            "host;x-amz-date",
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+        );
+
+        let res = status(
+            config(),
+            req().insert_header((header::AUTHORIZATION, valid.clone())),
+        )
+        .await;
+        assert_eq!(res, StatusCode::OK);
+
+        // signature does not match
+        let invalid = authorization(
+            "host;x-amz-date",
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf30",
+        );
+        let res = status(
+            config(),
+            req().insert_header((header::AUTHORIZATION, invalid)),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        // signed content no longer matches
+        let res = status(
+            config(),
+            req()
+                .uri("/?extra=1")
+                .insert_header((header::AUTHORIZATION, valid.clone())),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        // timestamp too old
+        let res = status(
+            config().max_skew(Duration::from_secs(15 * 60)),
+            req().insert_header((header::AUTHORIZATION, valid.clone())),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        // unexpected region
+        let res = status(
+            config().region("eu-west-1"),
+            req().insert_header((header::AUTHORIZATION, valid.clone())),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        // unknown key
+        let res = status(
+            AwsSigV4Config::new().max_skew(Duration::MAX),
+            req().insert_header((header::AUTHORIZATION, valid)),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        // no authorization
+        let res = status(config(), req()).await;
+        assert_eq!(res, StatusCode::UNAUTHORIZED);
+
+        // host not signed
+        let res = status(
+            config(),
+            req().insert_header((
+                header::AUTHORIZATION,
+                authorization("x-amz-date", &"0".repeat(64)),
+            )),
+        )
+        .await;
+        assert_eq!(res, StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn get_date_header() {
+        let req = || {
+            TestRequest::get()
+                .uri("/")
+                .insert_header(("host", "example.amazonaws.com"))
+                .insert_header((header::DATE, "Sun, 30 Aug 2015 12:36:00 GMT"))
+        };
+
+        let valid = authorization(
+            "date;host",
+            "1262aceaf1a79c7f0b69fda81cd744572fcbe2e4c23b647b4de183cd5a0f1075",
+        );
+
+        let res = status(
+            config(),
+            req().insert_header((header::AUTHORIZATION, valid)),
+        )
+        .await;
+        assert_eq!(res, StatusCode::OK);
+
+        // credential scope date does not match the request date
+        let res = status(
+            config(),
+            req().insert_header((
+                header::AUTHORIZATION,
+                authorization("date;host", &"0".repeat(64)).replace("20150830", "20150831"),
+            )),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn payload_hash() {
+        let req = || {
+            TestRequest::post()
+                .uri("/")
+                .insert_header(("host", "example.amazonaws.com"))
+                .insert_header((X_AMZ_DATE, TIMESTAMP))
+                .set_payload("abc")
+        };
+
+        // sha256("def")
+        let res = status(
+            config(),
+            req()
+                .insert_header((
+                    X_AMZ_CONTENT_SHA256,
+                    "cb8379ac2098aa165029e3938a51da0bcecfc008fd6795f401178647f96c5b34",
+                ))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    authorization("host;x-amz-content-sha256;x-amz-date", &"0".repeat(64)),
+                )),
+        )
+        .await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+
+        let res = status(
+            config(),
+            req()
+                .insert_header((X_AMZ_CONTENT_SHA256, "UNSIGNED-PAYLOAD"))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    authorization(
+                        "host;x-amz-content-sha256;x-amz-date",
+                        "05e1b307416f2cbcf15dd86269efd55537182ee4416101af58762e96bba470bb",
+                    ),
+                )),
+        )
+        .await;
+        assert_eq!(res, StatusCode::OK);
+
+        let res = status(
+            config(),
+            req()
+                .insert_header((X_AMZ_CONTENT_SHA256, "STREAMING-AWS4-HMAC-SHA256-PAYLOAD"))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    authorization("host;x-amz-content-sha256;x-amz-date", &"0".repeat(64)),
+                )),
+        )
+        .await;
+        assert_eq!(res, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,3 @@
+//! Harnesses for measuring the performance of services and middleware.
+
+pub use crate::load_test::{LatencyReport, LoadTest};
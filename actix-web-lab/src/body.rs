@@ -2,8 +2,20 @@
 //!
 //! Analogous to the `body` module in Actix Web.
 
+#[cfg(feature = "compress-brotli")]
+pub use crate::body_compress::compress_br;
+#[cfg(feature = "compress-zstd")]
+pub use crate::body_compress::compress_zstd;
+#[cfg(feature = "send-file")]
+pub use crate::body_send_file::SendFile;
+#[cfg(feature = "record")]
+pub use crate::record::RecordingBody;
 pub use crate::{
     body_async_write::{writer, Writer},
-    body_channel::{channel, Sender},
+    body_channel::{channel, sync_channel, Sender, SyncSender},
+    body_coalesce::{coalesce, CoalescedBody},
     infallible_body_stream::{new_infallible_body_stream, new_infallible_sized_stream},
+    stream_metrics::{metered, MeteredBody},
+    stream_timeout::TimeoutBody,
+    throttle::{throttle, throttle_with, ThrottledBody},
 };
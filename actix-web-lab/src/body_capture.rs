@@ -0,0 +1,95 @@
+//! Bounded capture of body bytes, shared by recording and logging middleware.
+
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    dev::{Payload, ServiceRequest},
+    web::BytesMut,
+    HttpMessage as _,
+};
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+/// Copies request body chunks, up to `limit` bytes, as they are read by the handler.
+pub(crate) fn capture_request_body(req: &mut ServiceRequest, limit: usize) -> Rc<RefCell<Capture>> {
+    let capture = Rc::new(RefCell::new(Capture::new(limit)));
+    let payload = req.take_payload();
+
+    req.set_payload(Payload::from(Box::pin(CaptureStream {
+        stream: payload,
+        capture: Rc::clone(&capture),
+    }) as Pin<Box<dyn Stream<Item = _>>>));
+
+    capture
+}
+
+/// Bounded copy of body bytes.
+#[derive(Debug)]
+pub(crate) struct Capture {
+    buf: BytesMut,
+    limit: usize,
+    truncated: bool,
+}
+
+impl Capture {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            buf: BytesMut::new(),
+            limit,
+            truncated: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        let remaining = self.limit - self.buf.len();
+
+        if chunk.len() > remaining {
+            self.truncated = true;
+        }
+
+        self.buf
+            .extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+    }
+
+    /// Returns the captured bytes.
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns true if the body exceeded the capture limit.
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+pin_project! {
+    struct CaptureStream<S> {
+        #[pin]
+        stream: S,
+        capture: Rc<RefCell<Capture>>,
+    }
+}
+
+impl<S, T, E> Stream for CaptureStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: AsRef<[u8]>,
+{
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = futures_util::ready!(this.stream.poll_next(cx));
+
+        if let Some(Ok(chunk)) = &item {
+            this.capture.borrow_mut().push(chunk.as_ref());
+        }
+
+        Poll::Ready(item)
+    }
+}
@@ -5,7 +5,7 @@ use std::{
 
 use actix_web::body::{BodySize, MessageBody};
 use bytes::Bytes;
-use tokio::sync::mpsc::{error::SendError, UnboundedReceiver, UnboundedSender};
+use tokio::sync::mpsc::{self, error::SendError, UnboundedReceiver, UnboundedSender};
 
 use crate::BoxError;
 
@@ -73,6 +73,96 @@ impl<E> Sender<E> {
     }
 }
 
+/// Returns a blocking sender half and a receiver half that can be used as a body type.
+///
+/// Unlike [`channel()`], the channel is bounded: at most `buffer` chunks are held in the channel at
+/// any one time and [`SyncSender::send()`] blocks the calling thread until there is capacity. This
+/// makes it suitable for feeding response bodies from synchronous code running on blocking threads
+/// (e.g., image encoders or zip writers) without unbounded memory growth when the client reads
+/// slowly.
+///
+/// # Panics
+/// Panics if `buffer` is zero.
+///
+/// # Examples
+/// ```
+/// # use actix_web::{HttpResponse, web};
+/// use std::convert::Infallible;
+///
+/// use actix_web_lab::body;
+///
+/// # async fn index() {
+/// let (mut body_tx, body) = body::sync_channel::<Infallible>(8);
+///
+/// let _ = web::block(move || {
+///     for _ in 0..100 {
+///         body_tx
+///             .send(web::Bytes::from_static(b"chunk from a blocking thread\n"))
+///             .unwrap();
+///     }
+/// });
+///
+/// HttpResponse::Ok().body(body)
+/// # ;}
+/// ```
+pub fn sync_channel<E: Into<BoxError>>(buffer: usize) -> (SyncSender<E>, impl MessageBody) {
+    let (tx, rx) = mpsc::channel(buffer);
+    (SyncSender::new(tx), BoundedReceiver::new(rx))
+}
+
+/// A blocking, channel-like sender for body chunks.
+///
+/// See [`sync_channel()`].
+#[derive(Debug, Clone)]
+pub struct SyncSender<E> {
+    tx: mpsc::Sender<Result<Bytes, E>>,
+}
+
+impl<E> SyncSender<E> {
+    fn new(tx: mpsc::Sender<Result<Bytes, E>>) -> Self {
+        Self { tx }
+    }
+
+    /// Submits a chunk of bytes to the response body stream, blocking the current thread until
+    /// there is capacity in the channel.
+    ///
+    /// # Errors
+    /// Errors if other side of channel body was dropped, returning `chunk`.
+    ///
+    /// # Panics
+    /// Panics if called from within an async execution context. Use this sender from a blocking
+    /// thread, e.g., one started with [`web::block()`](actix_web::web::block).
+    pub fn send(&mut self, chunk: Bytes) -> Result<(), Bytes> {
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|SendError(err)| match err {
+                Ok(chunk) => chunk,
+                Err(_) => unreachable!(),
+            })
+    }
+
+    /// Closes the stream, optionally sending an error.
+    ///
+    /// # Errors
+    /// Errors if closing with error and other side of channel body was dropped, returning `error`.
+    ///
+    /// # Panics
+    /// Panics if closing with error from within an async execution context.
+    pub fn close(self, error: Option<E>) -> Result<(), E> {
+        if let Some(err) = error {
+            return self
+                .tx
+                .blocking_send(Err(err))
+                .map_err(|SendError(err)| match err {
+                    Ok(_) => unreachable!(),
+                    Err(err) => err,
+                });
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct Receiver<E> {
     rx: UnboundedReceiver<Result<Bytes, E>>,
@@ -102,12 +192,83 @@ where
     }
 }
 
+#[derive(Debug)]
+struct BoundedReceiver<E> {
+    rx: mpsc::Receiver<Result<Bytes, E>>,
+}
+
+impl<E> BoundedReceiver<E> {
+    fn new(rx: mpsc::Receiver<Result<Bytes, E>>) -> Self {
+        Self { rx }
+    }
+}
+
+impl<E> MessageBody for BoundedReceiver<E>
+where
+    E: Into<BoxError>,
+{
+    type Error = E;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io;
 
+    use actix_web::body;
+
     use super::*;
 
     static_assertions::assert_impl_all!(Sender<io::Error>: Send, Sync, Unpin);
     static_assertions::assert_impl_all!(Receiver<io::Error>: Send, Sync, Unpin, MessageBody);
+    static_assertions::assert_impl_all!(SyncSender<io::Error>: Send, Sync, Unpin);
+    static_assertions::assert_impl_all!(BoundedReceiver<io::Error>: Send, Sync, Unpin, MessageBody);
+
+    #[actix_web::test]
+    async fn sync_channel_from_blocking_thread() {
+        let (mut tx, body) = sync_channel::<io::Error>(1);
+
+        let handle = std::thread::spawn(move || {
+            for _ in 0..10 {
+                tx.send(Bytes::from_static(b"abc")).unwrap();
+            }
+        });
+
+        let bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(bytes, "abc".repeat(10));
+
+        handle.join().unwrap();
+    }
+
+    #[actix_web::test]
+    async fn sync_channel_close_with_error() {
+        let (tx, body) = sync_channel::<io::Error>(1);
+
+        std::thread::spawn(move || tx.close(Some(io::Error::other("oops"))).unwrap())
+            .join()
+            .unwrap();
+
+        assert!(body::to_bytes(body).await.is_err());
+    }
+
+    #[test]
+    fn sync_channel_receiver_dropped() {
+        let (mut tx, body) = sync_channel::<io::Error>(1);
+        drop(body);
+
+        assert_eq!(
+            tx.send(Bytes::from_static(b"abc")).unwrap_err(),
+            Bytes::from_static(b"abc"),
+        );
+    }
 }
@@ -0,0 +1,231 @@
+//! Response body chunk coalescing.
+//!
+//! See [`coalesce()`] docs.
+
+use std::{
+    fmt,
+    future::Future as _,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    web::{Bytes, BytesMut},
+};
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Sleep};
+
+/// Coalesces small chunks from `body` into chunks of at least `min_chunk` bytes.
+///
+/// Streaming bodies that yield many tiny chunks, such as those produced by serializing one item at
+/// a time with [`NdJson`](crate::respond::NdJson), [`Csv`](crate::respond::Csv), or
+/// [`DisplayStream`](crate::respond::DisplayStream), cause a separate write for each chunk. This
+/// wrapper buffers chunks that are immediately available until at least `min_chunk` bytes have
+/// been collected, reducing the per-write overhead when items are produced at high rates.
+///
+/// If the wrapped body is not ready to yield another chunk, buffered data is held for at most
+/// `max_delay` before being sent anyway, so that slow streams are not delayed indefinitely.
+/// Chunks that are at least `min_chunk` bytes long are passed through without copying.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{HttpResponse, Responder};
+/// use actix_web_lab::{body, respond::NdJson};
+/// use futures_util::stream;
+///
+/// async fn handler() -> impl Responder {
+///     let items = stream::iter((0..10_000).map(|n| serde_json::json!({ "seq": n })));
+///     let ndjson = NdJson::new_infallible(items).into_body_stream();
+///
+///     HttpResponse::Ok()
+///         .content_type(NdJson::mime())
+///         .body(body::coalesce(ndjson, 16 * 1024, Duration::from_millis(5)))
+/// }
+/// ```
+pub fn coalesce<B: MessageBody>(
+    body: B,
+    min_chunk: usize,
+    max_delay: Duration,
+) -> CoalescedBody<B> {
+    CoalescedBody {
+        body,
+        min_chunk,
+        max_delay,
+        buf: BytesMut::new(),
+        sleep: None,
+        done: false,
+        error: None,
+    }
+}
+
+pin_project! {
+    /// Body wrapper that merges small chunks into larger ones.
+    ///
+    /// Created by [`coalesce()`].
+    pub struct CoalescedBody<B: MessageBody> {
+        #[pin]
+        body: B,
+        min_chunk: usize,
+        max_delay: Duration,
+        buf: BytesMut,
+        // started when data is buffered while the wrapped body is pending
+        sleep: Option<Pin<Box<Sleep>>>,
+        // set when the wrapped body has finished, successfully or not
+        done: bool,
+        // error from the wrapped body, held until buffered data has been yielded
+        error: Option<B::Error>,
+    }
+}
+
+impl<B: MessageBody> fmt::Debug for CoalescedBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoalescedBody")
+            .field("min_chunk", &self.min_chunk)
+            .field("max_delay", &self.max_delay)
+            .field("buffered", &self.buf.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: MessageBody> MessageBody for CoalescedBody<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        while !*this.done && this.buf.len() < *this.min_chunk {
+            match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    if this.buf.is_empty() && chunk.len() >= *this.min_chunk {
+                        return Poll::Ready(Some(Ok(chunk)));
+                    }
+
+                    this.buf.extend_from_slice(&chunk);
+                }
+
+                Poll::Ready(Some(Err(err))) => {
+                    *this.error = Some(err);
+                    *this.done = true;
+                }
+
+                Poll::Ready(None) => *this.done = true,
+
+                Poll::Pending if this.buf.is_empty() => return Poll::Pending,
+
+                Poll::Pending => {
+                    let max_delay = *this.max_delay;
+                    let timer = this.sleep.get_or_insert_with(|| Box::pin(sleep(max_delay)));
+
+                    if timer.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        *this.sleep = None;
+
+        if !this.buf.is_empty() {
+            return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+        }
+
+        Poll::Ready(this.error.take().map(Err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use actix_web::body;
+    use futures_util::{stream, StreamExt as _};
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::*;
+
+    fn chunks(
+        items: &'static [&'static str],
+    ) -> body::BodyStream<impl futures_core::Stream<Item = Result<Bytes, io::Error>>> {
+        body::BodyStream::new(
+            stream::iter(items).map(|item| Ok(Bytes::from_static(item.as_bytes()))),
+        )
+    }
+
+    async fn collect<B: MessageBody>(body: B) -> Vec<Result<Bytes, String>>
+    where
+        B::Error: fmt::Display,
+    {
+        let mut body = std::pin::pin!(body);
+        let mut chunks = Vec::new();
+
+        while let Some(chunk) = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+            chunks.push(chunk.map_err(|err| err.to_string()));
+        }
+
+        chunks
+    }
+
+    #[actix_web::test]
+    async fn merges_ready_chunks() {
+        let body = coalesce(
+            chunks(&["a", "b", "c", "defgh", "i", "j"]),
+            3,
+            Duration::from_secs(1),
+        );
+
+        assert_eq!(
+            collect(body).await,
+            [Ok("abc".into()), Ok("defgh".into()), Ok("ij".into())],
+        );
+    }
+
+    #[actix_web::test]
+    async fn flushes_before_error() {
+        let items = stream::iter([
+            Ok(Bytes::from_static(b"a")),
+            Ok(Bytes::from_static(b"b")),
+            Err(io::Error::other("boom")),
+        ]);
+        let body = coalesce(body::BodyStream::new(items), 10, Duration::from_secs(1));
+
+        assert_eq!(
+            collect(body).await,
+            [Ok("ab".into()), Err("boom".to_owned())],
+        );
+    }
+
+    #[actix_web::test]
+    async fn flushes_after_max_delay() {
+        let (tx, rx) = mpsc::unbounded_channel::<Result<Bytes, io::Error>>();
+        let body = coalesce(
+            body::BodyStream::new(UnboundedReceiverStream::new(rx)),
+            1024,
+            Duration::from_millis(10),
+        );
+        let mut body = std::pin::pin!(body);
+
+        tx.send(Ok(Bytes::from_static(b"a"))).unwrap();
+        tx.send(Ok(Bytes::from_static(b"b"))).unwrap();
+
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), "ab");
+
+        drop(tx);
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert!(chunk.is_none());
+    }
+}
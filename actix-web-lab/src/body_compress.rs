@@ -0,0 +1,368 @@
+//! Streaming compression body adaptors.
+//!
+//! See [`compress_br()`] and [`compress_zstd()`] docs.
+
+use std::{
+    io::{self, Write as _},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::body::{BodySize, MessageBody};
+use bytes::{Bytes, BytesMut};
+use pin_project_lite::pin_project;
+
+use crate::BoxError;
+
+/// Compresses a body with [Brotli] as it streams.
+///
+/// `quality` ranges from 0 (fastest) to 11 (smallest); larger values are clamped. Actix Web's
+/// `Compress` middleware uses a quality of 3.
+///
+/// No headers are set by this function; the caller is responsible for setting `Content-Encoding:
+/// br` (and removing any `Content-Length`) on the response, and should only do so when the client
+/// has indicated support for it using `Accept-Encoding`. Compression happens on the async runtime's
+/// thread so large bodies with high quality settings can increase latency for other requests.
+///
+/// Buffered output is flushed whenever the wrapped body is not ready to yield another chunk, so
+/// slow streams (like [`NdJson`](crate::respond::NdJson)) are still delivered incrementally.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header, HttpResponse, Responder};
+/// use actix_web_lab::body;
+///
+/// async fn handler() -> impl Responder {
+///     let body = "a highly compressible string ".repeat(100);
+///
+///     HttpResponse::Ok()
+///         .insert_header((header::CONTENT_ENCODING, "br"))
+///         .body(body::compress_br(body, 5))
+/// }
+/// ```
+///
+/// [Brotli]: https://datatracker.ietf.org/doc/html/rfc7932
+#[cfg(feature = "compress-brotli")]
+pub fn compress_br<B: MessageBody>(body: B, quality: u32) -> impl MessageBody {
+    let encoder = brotli::CompressorWriter::new(
+        Writer::default(),
+        32 * 1024, // 32 KiB buffer
+        quality.min(11),
+        22, // window size
+    );
+
+    CompressedBody::new(body, Encoder::Brotli(Box::new(encoder)))
+}
+
+/// Compresses a body with [Zstandard] as it streams.
+///
+/// `level` ranges from negative values (fastest) to 22 (smallest); out of range values are
+/// clamped. A level of 0 selects zstd's default (currently 3).
+///
+/// No headers are set by this function; the caller is responsible for setting `Content-Encoding:
+/// zstd` (and removing any `Content-Length`) on the response, and should only do so when the client
+/// has indicated support for it using `Accept-Encoding`. Compression happens on the async runtime's
+/// thread so large bodies with high levels can increase latency for other requests.
+///
+/// Buffered output is flushed whenever the wrapped body is not ready to yield another chunk, so
+/// slow streams (like [`NdJson`](crate::respond::NdJson)) are still delivered incrementally.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header, HttpResponse, Responder};
+/// use actix_web_lab::body;
+///
+/// async fn handler() -> impl Responder {
+///     let body = "a highly compressible string ".repeat(100);
+///
+///     HttpResponse::Ok()
+///         .insert_header((header::CONTENT_ENCODING, "zstd"))
+///         .body(body::compress_zstd(body, 3))
+/// }
+/// ```
+///
+/// [Zstandard]: https://datatracker.ietf.org/doc/html/rfc8878
+#[cfg(feature = "compress-zstd")]
+pub fn compress_zstd<B: MessageBody>(body: B, level: i32) -> impl MessageBody {
+    let range = zstd::compression_level_range();
+    let level = level.clamp(*range.start(), *range.end());
+
+    // only fails if the zstd context cannot be allocated or the level is rejected
+    let encoder = zstd::stream::write::Encoder::new(Writer::default(), level)
+        .expect("zstd encoder should be created with a valid compression level");
+
+    CompressedBody::new(body, Encoder::Zstd(encoder))
+}
+
+/// Output buffer for encoders.
+#[derive(Debug, Default)]
+struct Writer {
+    buf: BytesMut,
+}
+
+impl Writer {
+    fn take(&mut self) -> Bytes {
+        self.buf.split().freeze()
+    }
+}
+
+impl io::Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+enum Encoder {
+    #[cfg(feature = "compress-brotli")]
+    Brotli(Box<brotli::CompressorWriter<Writer>>),
+
+    #[cfg(feature = "compress-zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Writer>),
+}
+
+impl Encoder {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "compress-brotli")]
+            Encoder::Brotli(enc) => enc.write_all(data),
+
+            #[cfg(feature = "compress-zstd")]
+            Encoder::Zstd(enc) => enc.write_all(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "compress-brotli")]
+            Encoder::Brotli(enc) => enc.flush(),
+
+            #[cfg(feature = "compress-zstd")]
+            Encoder::Zstd(enc) => enc.flush(),
+        }
+    }
+
+    /// Takes compressed output produced so far.
+    fn take(&mut self) -> Bytes {
+        match self {
+            #[cfg(feature = "compress-brotli")]
+            Encoder::Brotli(enc) => enc.get_mut().take(),
+
+            #[cfg(feature = "compress-zstd")]
+            Encoder::Zstd(enc) => enc.get_mut().take(),
+        }
+    }
+
+    /// Finishes the compressed stream and returns the remaining output.
+    fn finish(self) -> io::Result<Bytes> {
+        match self {
+            // brotli stream is finalized when the writer is released
+            #[cfg(feature = "compress-brotli")]
+            Encoder::Brotli(enc) => Ok(enc.into_inner().take()),
+
+            #[cfg(feature = "compress-zstd")]
+            Encoder::Zstd(enc) => Ok(enc.finish()?.take()),
+        }
+    }
+}
+
+pin_project! {
+    struct CompressedBody<B> {
+        #[pin]
+        body: B,
+
+        // None once the compressed stream is complete
+        encoder: Option<Encoder>,
+
+        // set when input has been written since the last flush
+        unflushed: bool,
+    }
+}
+
+impl<B> CompressedBody<B> {
+    fn new(body: B, encoder: Encoder) -> Self {
+        Self {
+            body,
+            encoder: Some(encoder),
+            unflushed: false,
+        }
+    }
+}
+
+impl<B: MessageBody> MessageBody for CompressedBody<B> {
+    type Error = BoxError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            let Some(encoder) = this.encoder.as_mut() else {
+                return Poll::Ready(None);
+            };
+
+            match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    encoder.write(&chunk)?;
+                    *this.unflushed = true;
+
+                    let out = encoder.take();
+
+                    if !out.is_empty() {
+                        return Poll::Ready(Some(Ok(out)));
+                    }
+                }
+
+                Poll::Ready(Some(Err(err))) => {
+                    *this.encoder = None;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+
+                Poll::Ready(None) => {
+                    let out = this.encoder.take().unwrap().finish()?;
+
+                    return if out.is_empty() {
+                        Poll::Ready(None)
+                    } else {
+                        Poll::Ready(Some(Ok(out)))
+                    };
+                }
+
+                Poll::Pending => {
+                    if !*this.unflushed {
+                        return Poll::Pending;
+                    }
+
+                    // deliver what has been compressed so far while waiting for more input
+                    encoder.flush()?;
+                    *this.unflushed = false;
+
+                    let out = encoder.take();
+
+                    return if out.is_empty() {
+                        Poll::Pending
+                    } else {
+                        Poll::Ready(Some(Ok(out)))
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use actix_web::body::{self, BodyStream};
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+    use crate::util::PollSeq;
+
+    fn text() -> String {
+        "streaming compression test data; ".repeat(200)
+    }
+
+    fn chunked_with_pause(
+    ) -> BodyStream<impl futures_core::Stream<Item = Result<Bytes, Infallible>>> {
+        let seq = PollSeq::from([
+            Poll::Ready(Some(Bytes::from_static(b"hello "))),
+            Poll::Pending,
+            Poll::Ready(Some(Bytes::from_static(b"world"))),
+            Poll::Ready(None),
+        ]);
+
+        BodyStream::new(seq.into_stream().map(Ok))
+    }
+
+    /// Polls a body to completion without a runtime, treating pending as a pause point.
+    fn poll_chunks(body: impl MessageBody) -> Vec<Bytes> {
+        let mut body = std::pin::pin!(body);
+        let waker = futures_util::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut chunks = Vec::new();
+
+        loop {
+            match body.as_mut().poll_next(&mut cx) {
+                Poll::Ready(Some(Ok(chunk))) => chunks.push(chunk),
+                Poll::Ready(Some(Err(_))) => panic!("unexpected body error"),
+                Poll::Ready(None) => return chunks,
+                Poll::Pending => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "compress-brotli")]
+    #[actix_web::test]
+    async fn brotli_round_trip() {
+        let compressed = body::to_bytes(compress_br(text(), 5)).await.ok().unwrap();
+        assert!(compressed.len() < text().len());
+
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut &compressed[..], &mut decompressed).unwrap();
+        assert_eq!(decompressed, text().as_bytes());
+    }
+
+    #[cfg(feature = "compress-brotli")]
+    #[test]
+    fn brotli_flushes_when_pending() {
+        let chunks = poll_chunks(compress_br(chunked_with_pause(), 3));
+
+        // first chunk is delivered before the stream ends
+        assert!(chunks.len() >= 2);
+
+        let compressed = chunks.concat();
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut &compressed[..], &mut decompressed).unwrap();
+        assert_eq!(decompressed, b"hello world");
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[actix_web::test]
+    async fn zstd_round_trip() {
+        let compressed = body::to_bytes(compress_zstd(text(), 100))
+            .await
+            .ok()
+            .unwrap();
+        assert!(compressed.len() < text().len());
+
+        let decompressed = zstd::decode_all(&compressed[..]).unwrap();
+        assert_eq!(decompressed, text().as_bytes());
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[test]
+    fn zstd_flushes_when_pending() {
+        let chunks = poll_chunks(compress_zstd(chunked_with_pause(), 3));
+        assert!(chunks.len() >= 2);
+
+        let decompressed = zstd::decode_all(&chunks.concat()[..]).unwrap();
+        assert_eq!(decompressed, b"hello world");
+    }
+
+    #[actix_web::test]
+    async fn propagates_body_errors() {
+        let body = BodyStream::new(stream::iter([
+            Ok(Bytes::from_static(b"foo")),
+            Err(io::Error::other("stream broke")),
+        ]));
+
+        #[cfg(feature = "compress-brotli")]
+        let body = compress_br(body, 3);
+        #[cfg(all(not(feature = "compress-brotli"), feature = "compress-zstd"))]
+        let body = compress_zstd(body, 3);
+
+        assert!(body::to_bytes(body).await.is_err());
+    }
+}
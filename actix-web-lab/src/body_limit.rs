@@ -11,12 +11,12 @@ use std::{
 
 use actix_web::{
     dev::{self, Payload},
-    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+    FromRequest, HttpRequest, ResponseError,
 };
 use derive_more::Display;
 use futures_core::Stream as _;
 
-use crate::header::ContentLength;
+use crate::lab_config::LabConfig;
 
 /// Default body size limit of 2MiB.
 pub const DEFAULT_BODY_LIMIT: usize = 2_097_152;
@@ -80,10 +80,12 @@ where
     type Future = BodyLimitFut<T, LIMIT>;
 
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_BODY_LIMIT, LabConfig::BODY);
+
         // fast check of Content-Length header
-        match req.get_header::<ContentLength>() {
+        match LabConfig::payload_length(req) {
             // CL header indicated that payload would be too large
-            Some(len) if len > LIMIT => return BodyLimitFut::new_error(BodyLimitError::Overflow),
+            Some(len) if len > limit => return BodyLimitFut::new_error(BodyLimitError::Overflow),
             _ => {}
         }
 
@@ -94,6 +96,7 @@ where
                 fut: Box::pin(T::from_request(req, payload)),
                 counter_pl: counter,
                 size: 0,
+                limit,
             },
         }
     }
@@ -138,6 +141,9 @@ where
 
         /// Running payload size count.
         size: usize,
+
+        /// Effective payload size limit.
+        limit: usize,
     },
 }
 
@@ -165,6 +171,7 @@ where
                 fut,
                 counter_pl,
                 size,
+                limit,
             } => {
                 // poll inner extractor first which also polls original payload stream
                 let res = ready!(fut.as_mut().poll(cx).map_err(BodyLimitError::Extractor)?);
@@ -174,7 +181,7 @@ where
                     // update running size
                     *size += chunk.len();
 
-                    if *size > LIMIT {
+                    if *size > *limit {
                         return Poll::Ready(Err(BodyLimitError::Overflow));
                     }
                 }
@@ -0,0 +1,295 @@
+//! File-backed response body.
+//!
+//! See [`SendFile`] docs.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::File,
+    future::Future as _,
+    io::{self, Read as _, Seek as _},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    rt::task::{spawn_blocking, JoinHandle},
+    web::Bytes,
+};
+
+/// Default size of chunks read from the file.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Default number of chunks read ahead of the consumer.
+const DEFAULT_READ_AHEAD: usize = 2;
+
+/// Message body that streams a file in chunks.
+///
+/// Each chunk is read on the blocking thread pool directly into the buffer that is sent as part of
+/// the response, so file contents are not copied through intermediate buffers the way they are
+/// when an [`AsyncRead`](tokio::io::AsyncRead) is adapted into a stream. The whole file is never
+/// held in memory; at most [`read_ahead()`](Self::read_ahead) chunks are buffered while waiting for
+/// the consumer to catch up.
+///
+/// The body is streamed from the file's current position to the end of the file, as it was when
+/// the body was constructed. The remaining length is used as the body's size so that a
+/// `Content-Length` header is sent.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{get, HttpResponse};
+/// use actix_web_lab::body::SendFile;
+///
+/// #[get("/video")]
+/// async fn video() -> actix_web::Result<HttpResponse> {
+///     let body = SendFile::open("video.mp4")
+///         .await?
+///         .chunk_size(256 * 1024)
+///         .read_ahead(4);
+///
+///     Ok(HttpResponse::Ok().content_type("video/mp4").body(body))
+/// }
+/// ```
+pub struct SendFile {
+    file: Option<File>,
+    reading: Option<JoinHandle<(File, io::Result<Bytes>)>>,
+    chunks: VecDeque<Bytes>,
+    chunk_size: usize,
+    read_ahead: usize,
+    size: u64,
+    // bytes not yet read from the file
+    remaining: u64,
+    error: Option<io::Error>,
+}
+
+impl SendFile {
+    /// Opens the file at `path` for streaming.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        Self::from_std_blocking(move || File::open(path)).await
+    }
+
+    /// Constructs a body that streams `file` from its current position.
+    pub async fn from_file(file: tokio::fs::File) -> io::Result<Self> {
+        let file = file.into_std().await;
+        Self::from_std_blocking(move || Ok(file)).await
+    }
+
+    async fn from_std_blocking(
+        file: impl FnOnce() -> io::Result<File> + Send + 'static,
+    ) -> io::Result<Self> {
+        let (file, remaining) = spawn_blocking(move || {
+            let mut file = file()?;
+            let len = file.metadata()?.len();
+            let pos = file.stream_position()?;
+            Ok::<_, io::Error>((file, len.saturating_sub(pos)))
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        Ok(Self {
+            file: Some(file),
+            reading: None,
+            chunks: VecDeque::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            read_ahead: DEFAULT_READ_AHEAD,
+            size: remaining,
+            remaining,
+            error: None,
+        })
+    }
+
+    /// Sets the maximum size of chunks read from the file, in bytes.
+    ///
+    /// Defaults to 64KiB.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be greater than 0");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the maximum number of chunks read from the file before they are consumed.
+    ///
+    /// Higher values allow file reads to keep ahead of the consumer when reads are slow, at the
+    /// cost of memory. Defaults to 2.
+    ///
+    /// # Panics
+    /// Panics if `read_ahead` is 0.
+    pub fn read_ahead(mut self, read_ahead: usize) -> Self {
+        assert!(read_ahead > 0, "read-ahead must be greater than 0");
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Starts reading the next chunk, if there is room for it.
+    fn start_read(&mut self) -> bool {
+        if self.reading.is_some() || self.chunks.len() >= self.read_ahead || self.remaining == 0 {
+            return false;
+        }
+
+        let Some(file) = self.file.take() else {
+            return false;
+        };
+
+        let len = self.remaining.min(self.chunk_size as u64);
+        self.reading = Some(spawn_blocking(move || read_chunk(file, len)));
+
+        true
+    }
+}
+
+/// Reads up to `len` bytes from `file` into a new buffer.
+fn read_chunk(file: File, len: u64) -> (File, io::Result<Bytes>) {
+    let mut buf = Vec::with_capacity(len as usize);
+
+    let res = match (&file).take(len).read_to_end(&mut buf) {
+        Ok(_) if buf.is_empty() => Err(io::ErrorKind::UnexpectedEof.into()),
+        Ok(_) => Ok(Bytes::from(buf)),
+        Err(err) => Err(err),
+    };
+
+    (file, res)
+}
+
+impl fmt::Debug for SendFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendFile")
+            .field("chunk_size", &self.chunk_size)
+            .field("read_ahead", &self.read_ahead)
+            .field("size", &self.size)
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MessageBody for SendFile {
+    type Error = io::Error;
+
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.size)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(reading) = &mut this.reading {
+                let Poll::Ready(res) = Pin::new(reading).poll(cx) else {
+                    break;
+                };
+
+                this.reading = None;
+
+                match res {
+                    Ok((file, Ok(chunk))) => {
+                        this.remaining -= chunk.len() as u64;
+                        this.chunks.push_back(chunk);
+                        this.file = Some(file);
+                    }
+                    Ok((_, Err(err))) => this.error = Some(err),
+                    Err(err) => this.error = Some(io::Error::other(err)),
+                }
+            }
+
+            if !this.start_read() {
+                break;
+            }
+        }
+
+        if let Some(chunk) = this.chunks.pop_front() {
+            // keep reading ahead while this chunk is written
+            this.start_read();
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        if this.reading.is_some() {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(this.error.take().map(Err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::SeekFrom, path::PathBuf};
+
+    use actix_web::body::to_bytes;
+    use tokio::io::AsyncSeekExt as _;
+
+    use super::*;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "actix-web-lab-send-file-{name}-{}",
+            std::process::id(),
+        ));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[actix_web::test]
+    async fn streams_file_in_chunks() {
+        let contents = (0..=255).cycle().take(10_000).collect::<Vec<u8>>();
+        let path = temp_file("chunks", &contents);
+
+        let body = SendFile::open(&path).await.unwrap().chunk_size(4096);
+        assert_eq!(body.size(), BodySize::Sized(10_000));
+
+        let mut body = std::pin::pin!(body);
+        let mut lens = Vec::new();
+        let mut read = Vec::new();
+
+        while let Some(chunk) = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+            let chunk = chunk.unwrap();
+            lens.push(chunk.len());
+            read.extend_from_slice(&chunk);
+        }
+
+        assert_eq!(lens, [4096, 4096, 1808]);
+        assert_eq!(read, contents);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn streams_from_current_position() {
+        let path = temp_file("position", b"hello world");
+
+        let mut file = tokio::fs::File::open(&path).await.unwrap();
+        file.seek(SeekFrom::Start(6)).await.unwrap();
+
+        let body = SendFile::from_file(file).await.unwrap().read_ahead(1);
+        assert_eq!(body.size(), BodySize::Sized(5));
+        assert_eq!(to_bytes(body).await.unwrap(), "world");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn empty_file() {
+        let path = temp_file("empty", b"");
+
+        let body = SendFile::open(&path).await.unwrap();
+        assert_eq!(body.size(), BodySize::Sized(0));
+        assert!(to_bytes(body).await.unwrap().is_empty());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn missing_file() {
+        let err = SendFile::open("/this/file/does/not/exist")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,330 @@
+//! Bulk CSV import endpoint with progress reporting.
+//!
+//! See [`BulkImport`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Future},
+    marker::PhantomData,
+};
+
+use actix_web::{web, Route};
+use futures_core::Stream;
+use futures_util::{future::Either, stream, FutureExt as _, StreamExt as _};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{
+    extract::CsvStream,
+    sse::{self, Sse},
+};
+
+/// Default number of rows processed between progress events.
+const DEFAULT_PROGRESS_EVERY: usize = 100;
+
+/// Default number of rows handled concurrently.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Outcome of handling a single row.
+enum Outcome {
+    /// Row was handled, successfully or not.
+    Row {
+        record: usize,
+        result: Result<(), String>,
+    },
+
+    /// Request body could not be read or parsed; no further rows will be handled.
+    Fatal(String),
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    succeeded: usize,
+    failed: usize,
+}
+
+impl Counts {
+    fn processed(&self) -> usize {
+        self.succeeded + self.failed
+    }
+
+    fn to_event(&self, name: &'static str) -> sse::Event {
+        sse::Data::new(
+            json!({
+                "processed": self.processed(),
+                "succeeded": self.succeeded,
+                "failed": self.failed,
+            })
+            .to_string(),
+        )
+        .event(name)
+        .into()
+    }
+}
+
+/// A CSV upload endpoint that handles each row and reports progress as server-sent events.
+///
+/// The request body is read using the [`CsvStream`] extractor and each row is passed to the row
+/// handler, with up to [`concurrency`](Self::concurrency) rows being handled at once. The response
+/// is an event stream, so clients can show progress while the upload is processed.
+///
+/// # Events
+/// All event data is JSON.
+///
+/// | Event       | Data                                                 | Sent                                         |
+/// |-------------|------------------------------------------------------|----------------------------------------------|
+/// | `progress`  | `{"processed": 200, "succeeded": 199, "failed": 1}`  | Every [`progress_every`](Self::progress_every) rows. |
+/// | `row_error` | `{"record": 42, "error": "..."}`                     | When the row handler returns an error.       |
+/// | `complete`  | `{"processed": 250, "succeeded": 248, "failed": 2}`  | After all rows have been handled.            |
+/// | `error`     | `{"error": "..."}`                                   | If the body cannot be read or parsed. Ends the stream. |
+///
+/// Record numbers are 1-based and include the header record, so they usually match the line
+/// numbers shown by spreadsheet applications.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::web::bulk_import;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     email: String,
+///     name: String,
+/// }
+///
+/// async fn import_user(user: User) -> Result<(), String> {
+///     if !user.email.contains('@') {
+///         return Err(format!("invalid email for {}", user.name));
+///     }
+///
+///     // insert into database...
+///     Ok(())
+/// }
+///
+/// App::new().route(
+///     "/users/import",
+///     bulk_import(import_user).concurrency(4).finish(),
+/// )
+/// # ;
+/// ```
+pub struct BulkImport<Row, F> {
+    handler: F,
+    concurrency: usize,
+    progress_every: usize,
+    _row: PhantomData<fn(Row)>,
+}
+
+impl<Row, F> fmt::Debug for BulkImport<Row, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BulkImport")
+            .field("row_type", &std::any::type_name::<Row>())
+            .field("concurrency", &self.concurrency)
+            .field("progress_every", &self.progress_every)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Row, F, Fut, E> BulkImport<Row, F>
+where
+    Row: DeserializeOwned + 'static,
+    F: Fn(Row) -> Fut + Clone + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: fmt::Display + 'static,
+{
+    /// Constructs a new bulk import endpoint builder that passes each row to `handler`.
+    pub fn new(handler: F) -> Self {
+        Self {
+            handler,
+            concurrency: DEFAULT_CONCURRENCY,
+            progress_every: DEFAULT_PROGRESS_EVERY,
+            _row: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of rows handled concurrently.
+    ///
+    /// Defaults to 8.
+    ///
+    /// # Panics
+    /// Panics if `concurrency` is zero.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "concurrency should be non-zero");
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Sets the number of rows processed between `progress` events.
+    ///
+    /// Defaults to 100.
+    ///
+    /// # Panics
+    /// Panics if `rows` is zero.
+    pub fn progress_every(mut self, rows: usize) -> Self {
+        assert!(rows > 0, "progress interval should be non-zero");
+        self.progress_every = rows;
+        self
+    }
+
+    /// Returns a `POST` route to be registered on an `App`, `Scope`, or `Resource`.
+    pub fn finish(self) -> Route {
+        let Self {
+            handler,
+            concurrency,
+            progress_every,
+            ..
+        } = self;
+
+        web::post().to(move |rows: CsvStream<Row>| {
+            let handler = handler.clone();
+
+            async move {
+                Sse::from_infallible_stream(progress_events(
+                    rows,
+                    handler,
+                    concurrency,
+                    progress_every,
+                ))
+            }
+        })
+    }
+}
+
+/// Handles each row and returns a stream of progress events.
+fn progress_events<Row, F, Fut, E>(
+    rows: CsvStream<Row>,
+    handler: F,
+    concurrency: usize,
+    progress_every: usize,
+) -> impl Stream<Item = sse::Event>
+where
+    Row: DeserializeOwned,
+    F: Fn(Row) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: fmt::Display,
+{
+    let outcomes = rows
+        .enumerate()
+        .map(move |(idx, res)| match res {
+            // data records start after the header record
+            Ok(row) => Either::Left(handler(row).map(move |result| Outcome::Row {
+                record: idx + 2,
+                result: result.map_err(|err| err.to_string()),
+            })),
+
+            Err(err) => Either::Right(ready(Outcome::Fatal(err.to_string()))),
+        })
+        .buffered(concurrency);
+
+    let state = Some((Box::pin(outcomes), Counts::default()));
+
+    stream::unfold(state, move |state| async move {
+        let (mut outcomes, mut counts) = state?;
+
+        let (events, state) = match outcomes.next().await {
+            Some(Outcome::Row { record, result }) => {
+                let mut events = Vec::new();
+
+                match result {
+                    Ok(()) => counts.succeeded += 1,
+
+                    Err(err) => {
+                        counts.failed += 1;
+
+                        let data = json!({ "record": record, "error": err }).to_string();
+                        events.push(sse::Data::new(data).event("row_error").into());
+                    }
+                }
+
+                if counts.processed() % progress_every == 0 {
+                    events.push(counts.to_event("progress"));
+                }
+
+                (events, Some((outcomes, counts)))
+            }
+
+            Some(Outcome::Fatal(err)) => {
+                let data = json!({ "error": err }).to_string();
+                (vec![sse::Data::new(data).event("error").into()], None)
+            }
+
+            None => (vec![counts.to_event("complete")], None),
+        };
+
+        Some((stream::iter(events), state))
+    })
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Item {
+        name: String,
+        qty: u32,
+    }
+
+    async fn import_item(item: Item) -> Result<(), String> {
+        if item.qty == 0 {
+            return Err(format!("{} is out of stock", item.name));
+        }
+
+        Ok(())
+    }
+
+    async fn import(body: &'static str) -> String {
+        let app = init_service(
+            App::new().route(
+                "/",
+                BulkImport::new(import_item)
+                    .concurrency(2)
+                    .progress_every(2)
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::post().set_payload(body).to_request();
+        let res = call_service(&app, req).await;
+        assert!(res.status().is_success());
+
+        let body = body::to_bytes(res.into_body()).await.ok().unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn reports_progress() {
+        let body = import("name,qty\napple,3\npear,0\nplum,1\n").await;
+
+        assert_eq!(
+            body,
+            "event: row_error\n\
+            data: {\"error\":\"pear is out of stock\",\"record\":3}\n\n\
+            event: progress\n\
+            data: {\"failed\":1,\"processed\":2,\"succeeded\":1}\n\n\
+            event: complete\n\
+            data: {\"failed\":1,\"processed\":3,\"succeeded\":2}\n\n",
+        );
+    }
+
+    #[actix_web::test]
+    async fn parse_error_ends_stream() {
+        let body = import("name,qty\napple,3\npear,lots\nplum,1\n").await;
+
+        assert_eq!(
+            body,
+            "event: error\n\
+            data: {\"error\":\"Failed to deserialize CSV record 3: CSV deserialize error: \
+            field 1: invalid digit found in string\"}\n\n",
+        );
+    }
+}
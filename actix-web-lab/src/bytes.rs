@@ -8,11 +8,13 @@ use std::{
     task::{ready, Context, Poll},
 };
 
-use actix_web::{dev, http::StatusCode, web, FromRequest, HttpMessage, HttpRequest, ResponseError};
+use actix_web::{dev, http::StatusCode, web, FromRequest, HttpRequest, ResponseError};
 use derive_more::{Display, Error};
 use futures_core::Stream as _;
 use tracing::debug;
 
+use crate::{decompress, lab_config::LabConfig};
+
 /// Default bytes payload size limit of 4MiB.
 pub const DEFAULT_BYTES_LIMIT: usize = 4_194_304;
 
@@ -27,7 +29,7 @@ pub const DEFAULT_BYTES_LIMIT: usize = 4_194_304;
 /// # Differences from `actix_web::web::Bytes`
 /// - Does not read `PayloadConfig` from app data.
 /// - Supports const-generic size limits.
-/// - Will not automatically decompress request bodies.
+/// - Only decompresses request bodies when the `decompress` crate feature is enabled.
 ///
 /// # Examples
 /// ```
@@ -147,6 +149,8 @@ pub enum BytesBody<const LIMIT: usize> {
         length: Option<usize>,
         payload: dev::Payload,
         buf: web::BytesMut,
+        /// Effective payload size limit.
+        limit: usize,
     },
 }
 
@@ -155,21 +159,22 @@ impl<const LIMIT: usize> Unpin for BytesBody<LIMIT> {}
 impl<const LIMIT: usize> BytesBody<LIMIT> {
     /// Create a new future to decode a JSON request payload.
     pub fn new(req: &HttpRequest, payload: &mut dev::Payload) -> Self {
-        let payload = payload.take();
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_BYTES_LIMIT, LabConfig::BYTES);
+        LabConfig::apply_to_payload(req, payload);
+
+        let payload = decompress::take_payload(req, payload);
 
-        let length = req
-            .get_header::<crate::header::ContentLength>()
-            .map(|cl| cl.into_inner());
+        let length = LabConfig::payload_length(req).filter(|_| !decompress::is_encoded(req));
 
         // Notice the content-length is not checked against limit here as the internal usage always
         // call BytesBody::limit after BytesBody::new and limit check to return an error variant of
         // BytesBody happens there.
 
         if let Some(len) = length {
-            if len > LIMIT {
+            if len > limit {
                 return BytesBody::Error(Some(BytesPayloadError::OverflowKnownLength {
                     length: len,
-                    limit: LIMIT,
+                    limit,
                 }));
             }
         }
@@ -177,7 +182,8 @@ impl<const LIMIT: usize> BytesBody<LIMIT> {
         BytesBody::Body {
             length,
             payload,
-            buf: web::BytesMut::with_capacity(8192),
+            buf: web::BytesMut::with_capacity(LabConfig::buffer_capacity(req, length)),
+            limit,
         }
     }
 }
@@ -189,15 +195,20 @@ impl<const LIMIT: usize> Future for BytesBody<LIMIT> {
         let this = self.get_mut();
 
         match this {
-            BytesBody::Body { buf, payload, .. } => loop {
+            BytesBody::Body {
+                buf,
+                payload,
+                limit,
+                ..
+            } => loop {
                 let res = ready!(Pin::new(&mut *payload).poll_next(cx));
 
                 match res {
                     Some(chunk) => {
                         let chunk = chunk?;
                         let buf_len = buf.len() + chunk.len();
-                        if buf_len > LIMIT {
-                            return Poll::Ready(Err(BytesPayloadError::Overflow { limit: LIMIT }));
+                        if buf_len > *limit {
+                            return Poll::Ready(Err(BytesPayloadError::Overflow { limit: *limit }));
                         } else {
                             buf.extend_from_slice(&chunk);
                         }
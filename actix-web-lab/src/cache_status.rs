@@ -0,0 +1,542 @@
+//! Cache-Status typed header.
+//!
+//! See [`CacheStatus`] docs.
+
+use std::{fmt, str};
+
+use actix_http::{
+    error::ParseError,
+    header::{Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+
+/// The `Cache-Status` header, defined in [RFC 9211].
+///
+/// Contains one [entry](CacheStatusEntry) per cache that handled the response, ordered from the
+/// cache closest to the origin server to the cache closest to the user agent. Caches should
+/// therefore append their entry to any value received from upstream.
+///
+/// # ABNF
+/// ```text
+/// Cache-Status   = sf-list
+/// cache-entry    = ( sf-token / sf-string ) *( ";" cache-param )
+/// ```
+///
+/// # Sample Values
+/// - `ExampleCache; hit`
+/// - `OriginCache; hit; ttl=1100, "CDN Company Here"; hit; ttl=545`
+/// - `ExampleCache; fwd=uri-miss; stored`
+///
+/// # Examples
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::{CacheStatus, CacheStatusEntry, ForwardReason};
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(CacheStatus(vec![
+///     CacheStatusEntry::hit("ExampleCache").ttl(376)
+/// ]));
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(CacheStatus(vec![CacheStatusEntry::forward(
+///     "ExampleCache",
+///     ForwardReason::UriMiss,
+/// )
+/// .fwd_status(200)
+/// .stored()]));
+/// ```
+///
+/// [RFC 9211]: https://www.rfc-editor.org/rfc/rfc9211
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStatus(pub Vec<CacheStatusEntry>);
+
+impl_more::forward_deref_and_mut!(CacheStatus => [CacheStatusEntry]);
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = self.0.iter();
+
+        let Some(entry) = entries.next() else {
+            return Ok(());
+        };
+
+        write!(f, "{entry}")?;
+
+        for entry in entries {
+            write!(f, ", {entry}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryIntoHeaderValue for CacheStatus {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for CacheStatus {
+    fn name() -> HeaderName {
+        HeaderName::from_static("cache-status")
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        let mut entries = Vec::new();
+
+        for hdr in msg.headers().get_all(Self::name()) {
+            let hdr = hdr.to_str().map_err(|_| ParseError::Header)?;
+
+            for entry in split_unquoted(hdr, ',') {
+                if entry.trim().is_empty() {
+                    continue;
+                }
+
+                entries.push(entry.parse()?);
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(CacheStatus(entries))
+    }
+}
+
+/// A single cache's entry in a [`CacheStatus`] header.
+///
+/// Unrecognized parameters are ignored when parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CacheStatusEntry {
+    /// Name of the cache that handled the request.
+    pub cache: String,
+
+    /// The `hit` parameter, indicating the request was satisfied by the cache.
+    pub hit: bool,
+
+    /// The `fwd` parameter, indicating why the request was forwarded towards the origin.
+    pub fwd: Option<ForwardReason>,
+
+    /// The `fwd-status` parameter, containing the status code received from the next hop.
+    pub fwd_status: Option<u16>,
+
+    /// The `ttl` parameter, containing the response's remaining freshness lifetime in seconds.
+    ///
+    /// Negative values indicate a stale response.
+    pub ttl: Option<i64>,
+
+    /// The `stored` parameter, indicating the cache stored the forwarded response.
+    pub stored: bool,
+
+    /// The `collapsed` parameter, indicating the request was collapsed with another request.
+    pub collapsed: bool,
+
+    /// The `key` parameter, containing an implementation-specific cache key.
+    pub key: Option<String>,
+
+    /// The `detail` parameter, containing implementation-specific details.
+    pub detail: Option<String>,
+}
+
+impl CacheStatusEntry {
+    /// Constructs an entry for a cache that satisfied the request without forwarding it.
+    pub fn hit(cache: impl Into<String>) -> Self {
+        Self {
+            hit: true,
+            ..Self::empty(cache.into())
+        }
+    }
+
+    /// Constructs an entry for a cache that forwarded the request towards the origin.
+    pub fn forward(cache: impl Into<String>, reason: ForwardReason) -> Self {
+        Self {
+            fwd: Some(reason),
+            ..Self::empty(cache.into())
+        }
+    }
+
+    fn empty(cache: String) -> Self {
+        Self {
+            cache,
+            hit: false,
+            fwd: None,
+            fwd_status: None,
+            ttl: None,
+            stored: false,
+            collapsed: false,
+            key: None,
+            detail: None,
+        }
+    }
+
+    /// Sets the `fwd-status` parameter.
+    pub fn fwd_status(mut self, status: u16) -> Self {
+        self.fwd_status = Some(status);
+        self
+    }
+
+    /// Sets the `ttl` parameter, in seconds.
+    pub fn ttl(mut self, ttl: i64) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the `stored` parameter.
+    pub fn stored(mut self) -> Self {
+        self.stored = true;
+        self
+    }
+
+    /// Sets the `collapsed` parameter.
+    pub fn collapsed(mut self) -> Self {
+        self.collapsed = true;
+        self
+    }
+
+    /// Sets the `key` parameter.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Sets the `detail` parameter.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+impl fmt::Display for CacheStatusEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_token_or_string(f, &self.cache)?;
+
+        if self.hit {
+            f.write_str("; hit")?;
+        }
+
+        if let Some(fwd) = &self.fwd {
+            write!(f, "; fwd={fwd}")?;
+        }
+
+        if let Some(status) = self.fwd_status {
+            write!(f, "; fwd-status={status}")?;
+        }
+
+        if let Some(ttl) = self.ttl {
+            write!(f, "; ttl={ttl}")?;
+        }
+
+        if self.stored {
+            f.write_str("; stored")?;
+        }
+
+        if self.collapsed {
+            f.write_str("; collapsed")?;
+        }
+
+        if let Some(key) = &self.key {
+            f.write_str("; key=")?;
+            fmt_string(f, key)?;
+        }
+
+        if let Some(detail) = &self.detail {
+            f.write_str("; detail=")?;
+            fmt_token_or_string(f, detail)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl str::FromStr for CacheStatusEntry {
+    type Err = ParseError;
+
+    fn from_str(entry: &str) -> Result<Self, Self::Err> {
+        let mut parts = split_unquoted(entry, ';').into_iter();
+
+        let cache = parse_token_or_string(parts.next().unwrap_or_default())?;
+        let mut entry = Self::empty(cache);
+
+        for param in parts {
+            let (key, value) = match param.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (param.trim(), None),
+            };
+
+            match key {
+                "hit" => entry.hit = parse_bool(value)?,
+                "stored" => entry.stored = parse_bool(value)?,
+                "collapsed" => entry.collapsed = parse_bool(value)?,
+                "fwd" => {
+                    let value = value.ok_or(ParseError::Header)?;
+                    entry.fwd = Some(ForwardReason::from_token(value)?);
+                }
+                "fwd-status" => {
+                    let value = value.ok_or(ParseError::Header)?;
+                    entry.fwd_status = Some(value.parse().map_err(|_| ParseError::Header)?);
+                }
+                "ttl" => {
+                    let value = value.ok_or(ParseError::Header)?;
+                    entry.ttl = Some(value.parse().map_err(|_| ParseError::Header)?);
+                }
+                "key" => {
+                    entry.key = Some(parse_string(value.ok_or(ParseError::Header)?)?);
+                }
+                "detail" => {
+                    entry.detail = Some(parse_token_or_string(value.ok_or(ParseError::Header)?)?);
+                }
+                "" => return Err(ParseError::Header),
+                _ => {}
+            }
+        }
+
+        Ok(entry)
+    }
+}
+
+/// Reasons a cache forwarded a request, used in the `fwd` parameter of a [`CacheStatusEntry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ForwardReason {
+    /// The cache was configured to not handle this request.
+    Bypass,
+
+    /// The request method's semantics require the request to be forwarded.
+    Method,
+
+    /// The cache did not contain any responses that matched the request URI.
+    UriMiss,
+
+    /// The cache contained a response that matched the request URI, but it could not select a
+    /// response based upon this request's header fields and stored `Vary` header fields.
+    VaryMiss,
+
+    /// The cache did not contain any responses that could be used to satisfy this request.
+    Miss,
+
+    /// The cache was able to select a fresh response for the request, but the request's semantics
+    /// (e.g., `Cache-Control` request directives) did not allow its use.
+    Request,
+
+    /// The cache was able to select a response for the request, but it was stale.
+    Stale,
+
+    /// The cache was able to select a partial response for the request, but it did not contain all
+    /// of the requested ranges (or the request was for the complete response).
+    Partial,
+
+    /// Any other reason token.
+    Extension(String),
+}
+
+impl ForwardReason {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Bypass => "bypass",
+            Self::Method => "method",
+            Self::UriMiss => "uri-miss",
+            Self::VaryMiss => "vary-miss",
+            Self::Miss => "miss",
+            Self::Request => "request",
+            Self::Stale => "stale",
+            Self::Partial => "partial",
+            Self::Extension(reason) => reason,
+        }
+    }
+
+    fn from_token(token: &str) -> Result<Self, ParseError> {
+        if !is_token(token) {
+            return Err(ParseError::Header);
+        }
+
+        Ok(match token {
+            "bypass" => Self::Bypass,
+            "method" => Self::Method,
+            "uri-miss" => Self::UriMiss,
+            "vary-miss" => Self::VaryMiss,
+            "miss" => Self::Miss,
+            "request" => Self::Request,
+            "stale" => Self::Stale,
+            "partial" => Self::Partial,
+            token => Self::Extension(token.to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for ForwardReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Splits `val` on `delim`, ignoring delimiters inside quoted strings.
+pub(crate) fn split_unquoted(val: &str, delim: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (idx, ch) in val.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ch if ch == delim && !in_quotes => {
+                parts.push(&val[start..idx]);
+                start = idx + ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&val[start..]);
+    parts
+}
+
+/// Returns true if `val` is a structured field token.
+fn is_token(val: &str) -> bool {
+    let mut bytes = val.bytes();
+
+    bytes
+        .next()
+        .is_some_and(|b| b.is_ascii_alphabetic() || b == b'*')
+        && bytes.all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&b))
+}
+
+fn parse_bool(val: Option<&str>) -> Result<bool, ParseError> {
+    match val {
+        None | Some("?1") => Ok(true),
+        Some("?0") => Ok(false),
+        Some(_) => Err(ParseError::Header),
+    }
+}
+
+pub(crate) fn parse_string(val: &str) -> Result<String, ParseError> {
+    let inner = val
+        .strip_prefix('"')
+        .and_then(|val| val.strip_suffix('"'))
+        .ok_or(ParseError::Header)?;
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some(ch @ ('"' | '\\')) => out.push(ch),
+                _ => return Err(ParseError::Header),
+            },
+            '"' => return Err(ParseError::Header),
+            ch => out.push(ch),
+        }
+    }
+
+    Ok(out)
+}
+
+fn parse_token_or_string(val: &str) -> Result<String, ParseError> {
+    let val = val.trim();
+
+    if val.starts_with('"') {
+        parse_string(val)
+    } else if is_token(val) {
+        Ok(val.to_owned())
+    } else {
+        Err(ParseError::Header)
+    }
+}
+
+pub(crate) fn fmt_string(f: &mut fmt::Formatter<'_>, val: &str) -> fmt::Result {
+    f.write_str("\"")?;
+
+    for ch in val.chars() {
+        if matches!(ch, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+
+        write!(f, "{ch}")?;
+    }
+
+    f.write_str("\"")
+}
+
+fn fmt_token_or_string(f: &mut fmt::Formatter<'_>, val: &str) -> fmt::Result {
+    if is_token(val) {
+        f.write_str(val)
+    } else {
+        fmt_string(f, val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::assert_parse_eq;
+
+    #[test]
+    fn parse_bool_forms_and_unknown_params() {
+        assert_parse_eq(
+            [r#"ExampleCache; hit=?1; stored=?0; foo="bar, baz"; collapsed"#],
+            CacheStatus(vec![CacheStatusEntry::hit("ExampleCache").collapsed()]),
+        );
+    }
+
+    #[test]
+    fn quoted_names() {
+        let entry = CacheStatusEntry::hit(r#"My "Edge" Cache"#).key("/a;b");
+        let formatted = entry.to_string();
+        assert_eq!(formatted, r#""My \"Edge\" Cache"; hit; key="/a;b""#);
+        assert_eq!(formatted.parse::<CacheStatusEntry>().unwrap(), entry);
+    }
+}
+
+#[cfg(test)]
+crate::test::header_test_module! {
+    CacheStatus,
+    tests_parse_and_format {
+        header_round_trip_test!(no_headers, [b""; 0], None);
+        header_round_trip_test!(empty_header, [b""; 1], None);
+        header_round_trip_test!(bad_name, [b"1cache; hit"], None);
+        header_round_trip_test!(bad_ttl, [b"ExampleCache; hit; ttl=soon"], None);
+        header_round_trip_test!(unterminated_string, [b"\"ExampleCache; hit"], None);
+
+        header_round_trip_test!(
+            hit,
+            [b"ExampleCache; hit; ttl=376"],
+            Some(CacheStatus(vec![CacheStatusEntry::hit("ExampleCache").ttl(376)]))
+        );
+
+        header_round_trip_test!(
+            forwarded,
+            [b"ExampleCache; fwd=uri-miss; fwd-status=200; stored; detail=memory"],
+            Some(CacheStatus(vec![
+                CacheStatusEntry::forward("ExampleCache", ForwardReason::UriMiss)
+                    .fwd_status(200)
+                    .stored()
+                    .detail("memory"),
+            ]))
+        );
+
+        header_round_trip_test!(
+            multiple_entries,
+            [b"OriginCache; hit; ttl=1100, \"CDN Company Here\"; hit; ttl=545"],
+            Some(CacheStatus(vec![
+                CacheStatusEntry::hit("OriginCache").ttl(1100),
+                CacheStatusEntry::hit("CDN Company Here").ttl(545),
+            ]))
+        );
+
+        header_round_trip_test!(
+            multiple_headers,
+            [&b"OriginCache; fwd=stale; ttl=-10"[..], &b"Edge; fwd=x-custom"[..]],
+            Some(CacheStatus(vec![
+                CacheStatusEntry::forward("OriginCache", ForwardReason::Stale).ttl(-10),
+                CacheStatusEntry::forward("Edge", ForwardReason::Extension("x-custom".to_owned())),
+            ]))
+        );
+    }
+}
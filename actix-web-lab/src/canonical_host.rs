@@ -0,0 +1,230 @@
+use std::{
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    web::Redirect,
+    Responder as _,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::extract::Host;
+
+type ExcludeFn = dyn Fn(&ServiceRequest) -> bool;
+
+/// Middleware to permanently redirect requests arriving on non-canonical hosts.
+///
+/// Requests for any other host (e.g., alias domains or raw IP addresses) are redirected to the
+/// canonical host using a `301 Moved Permanently` response, preserving the scheme, path, and query
+/// string. Hosts are compared case-insensitively, including the port, and are resolved in the same
+/// way as the [`Host`] extractor.
+///
+/// Complements [`RedirectHttps`](crate::middleware::RedirectHttps) and the
+/// [`redirect_to_www`](crate::middleware::redirect_to_www) family.
+///
+/// # Exclusions
+///
+/// Requests matching a predicate added using [`exclude()`](Self::exclude) are passed through
+/// without redirecting. This is useful for health checks that are addressed by IP.
+///
+/// # Examples
+/// ```
+/// # use actix_web::App;
+/// use actix_web_lab::middleware::CanonicalHost;
+///
+/// App::new().wrap(CanonicalHost::new("example.com").exclude(|req| req.path() == "/health"))
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct CanonicalHost {
+    host: Rc<str>,
+    excluded: Vec<Rc<ExcludeFn>>,
+}
+
+impl CanonicalHost {
+    /// Constructs new canonical host redirect middleware.
+    ///
+    /// The host may include a port (e.g., `example.com:8443`).
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into().to_ascii_lowercase().into(),
+            excluded: Vec::new(),
+        }
+    }
+
+    /// Excludes requests matching `predicate` from being redirected.
+    ///
+    /// Can be called multiple times to add several predicates.
+    pub fn exclude(mut self, predicate: impl Fn(&ServiceRequest) -> bool + 'static) -> Self {
+        self.excluded.push(Rc::new(predicate));
+        self
+    }
+}
+
+impl fmt::Debug for CanonicalHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CanonicalHost")
+            .field("host", &self.host)
+            .field("excluded", &self.excluded.len())
+            .finish()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CanonicalHost
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, ()>>;
+    type Error = S::Error;
+    type Transform = CanonicalHostMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CanonicalHostMiddleware {
+            service: Rc::new(service),
+            host: Rc::clone(&self.host),
+            excluded: self.excluded.clone().into(),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`CanonicalHost`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct CanonicalHostMiddleware<S> {
+    service: Rc<S>,
+    host: Rc<str>,
+    excluded: Rc<[Rc<ExcludeFn>]>,
+}
+
+impl<S, B> Service<ServiceRequest> for CanonicalHostMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, ()>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let redirect_uri = self.redirect_uri(&req);
+
+        Box::pin(async move {
+            if let Some(uri) = redirect_uri {
+                let (req, _pl) = req.into_parts();
+
+                let res = Redirect::to(uri)
+                    .using_status_code(StatusCode::MOVED_PERMANENTLY)
+                    .respond_to(&req)
+                    .map_into_right_body();
+
+                return Ok(ServiceResponse::new(req, res));
+            }
+
+            service
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+impl<S> CanonicalHostMiddleware<S> {
+    /// Returns the equivalent URI on the canonical host if `req` should be redirected.
+    fn redirect_uri(&self, req: &ServiceRequest) -> Option<String> {
+        if self.excluded.iter().any(|predicate| predicate(req)) {
+            return None;
+        }
+
+        let host = Host::resolve(req.request());
+
+        if host.authority().eq_ignore_ascii_case(&self.host) {
+            return None;
+        }
+
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map_or("/", |path_and_query| path_and_query.as_str());
+
+        Some(format!("{}://{}{path_and_query}", host.scheme(), self.host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn redirects_non_canonical_hosts() {
+        let app = init_service(
+            App::new()
+                .wrap(CanonicalHost::new("example.com").exclude(|req| req.path() == "/health"))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for host in [
+            "www.example.com",
+            "10.0.0.1",
+            "example.com:8080",
+            "alias.net",
+        ] {
+            let req = TestRequest::with_uri("/foo?bar=baz")
+                .insert_header((header::HOST, host))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY, "host: {host}");
+            assert_eq!(
+                res.headers().get(header::LOCATION).unwrap(),
+                "http://example.com/foo?bar=baz",
+            );
+        }
+
+        for host in ["example.com", "EXAMPLE.com"] {
+            let req = TestRequest::with_uri("/foo")
+                .insert_header((header::HOST, host))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK, "host: {host}");
+        }
+
+        let req = TestRequest::with_uri("/health")
+            .insert_header((header::HOST, "10.0.0.1"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn preserves_scheme() {
+        let app = init_service(
+            App::new()
+                .wrap(CanonicalHost::new("example.com:8443"))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("https://alias.net/").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            res.headers().get(header::LOCATION).unwrap(),
+            "https://example.com:8443/",
+        );
+    }
+}
@@ -1,15 +1,25 @@
 use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
     future::{ready, Ready},
     panic::AssertUnwindSafe,
     rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     error,
+    http::{Method, Uri},
+    HttpMessage as _, HttpResponse,
 };
 use futures_core::future::LocalBoxFuture;
 use futures_util::FutureExt as _;
+use serde::{ser::SerializeStruct as _, Serialize, Serializer};
+
+use crate::extract::{MatchedPath, RequestId};
 
 /// A middleware to catch panics in wrapped handlers and middleware, returning empty 500 responses.
 ///
@@ -20,6 +30,18 @@ use futures_util::FutureExt as _;
 /// It is recommended that this middleware be registered last. That is, `wrap`ed after everything
 /// else except `Logger`.
 ///
+/// # Panic Counters
+/// Panics can be counted per route pattern using a shared [`PanicCounters`] handle, which can be
+/// inspected directly or exposed through an [admin scope](crate::web::admin_scope). Routes that
+/// keep panicking can also be automatically disabled, responding with empty 503 responses until
+/// they are reset, using [`disable_after()`](Self::disable_after). Requests that do not match a
+/// route are not counted.
+///
+/// # Custom Responses
+/// By default, the 500 responses sent for panics are empty. A [`responder()`](Self::responder)
+/// can be set to build custom responses instead (e.g., JSON error bodies with an incident ID),
+/// using the panic payload and request metadata provided by [`PanicContext`].
+///
 /// # Examples
 ///
 /// ```
@@ -42,13 +64,159 @@ use futures_util::FutureExt as _;
 ///     .wrap(Logger::default())
 ///     # ;
 /// ```
-#[derive(Debug, Clone, Default)]
+///
+/// ```
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// use actix_web_lab::middleware::{CatchPanic, PanicCounters};
+///
+/// // create outside of the `HttpServer::new` closure so counters are shared by all workers
+/// let counters = PanicCounters::new();
+///
+/// App::new().wrap(
+///     CatchPanic::default()
+///         .counters(counters.clone())
+///         .disable_after(5, Duration::from_secs(60)),
+/// )
+///     # ;
+/// ```
+///
+/// ```
+/// # use actix_web::App;
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::middleware::{CatchPanic, RequestId};
+///
+/// App::new()
+///     .wrap(CatchPanic::default().responder(|panic| {
+///         tracing::error!("panic in {:?}: {:?}", panic.route(), panic.message());
+///
+///         HttpResponse::InternalServerError().json(serde_json::json!({
+///             "error": "internal server error",
+///             "incident": panic.request_id().map(ToString::to_string),
+///         }))
+///     }))
+///     .wrap(RequestId::new()) // <- wraps CatchPanic so the ID is available to the responder
+///     # ;
+/// ```
+#[derive(Clone, Default)]
 #[non_exhaustive]
-pub struct CatchPanic;
+pub struct CatchPanic {
+    counters: Option<PanicCounters>,
+    disable_after: Option<(usize, Duration)>,
+    responder: Option<PanicResponder>,
+}
+
+type PanicResponder = Rc<dyn Fn(&PanicContext<'_>) -> HttpResponse>;
+
+impl CatchPanic {
+    /// Records panics per route in `counters`.
+    pub fn counters(mut self, counters: PanicCounters) -> Self {
+        self.counters = Some(counters);
+        self
+    }
+
+    /// Disables routes that panic `max_panics` times within `window`.
+    ///
+    /// Disabled routes respond with empty 503 Service Unavailable responses, without calling the
+    /// wrapped service, until reset using [`PanicCounters::reset()`]. If no counters handle has
+    /// been set, a private one is used.
+    pub fn disable_after(mut self, max_panics: usize, window: Duration) -> Self {
+        self.counters.get_or_insert_with(PanicCounters::new);
+        self.disable_after = Some((max_panics, window));
+        self
+    }
+
+    /// Sets a function that builds the response sent when a panic is caught.
+    ///
+    /// The returned response is sent as-is, so its status code does not need to be 500.
+    pub fn responder(
+        mut self,
+        responder: impl Fn(&PanicContext<'_>) -> HttpResponse + 'static,
+    ) -> Self {
+        self.responder = Some(Rc::new(responder));
+        self
+    }
+}
+
+impl fmt::Debug for CatchPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("counters", &self.counters)
+            .field("disable_after", &self.disable_after)
+            .field("responder", &self.responder.as_ref().map(|_| "<function>"))
+            .finish()
+    }
+}
+
+/// Information about a panic caught by [`CatchPanic`], passed to its
+/// [`responder()`](CatchPanic::responder).
+pub struct PanicContext<'a> {
+    payload: &'a (dyn Any + Send),
+    req: &'a RequestMeta,
+}
+
+/// Request metadata captured before calling the wrapped service, which consumes the request.
+struct RequestMeta {
+    method: Method,
+    uri: Uri,
+    route: Option<MatchedPath>,
+    request_id: Option<RequestId>,
+}
+
+impl PanicContext<'_> {
+    /// Returns the value with which the panic was invoked, to allow down-casting.
+    pub fn payload(&self) -> &(dyn Any + Send) {
+        self.payload
+    }
+
+    /// Returns the panic message, if the panic was invoked with a string.
+    pub fn message(&self) -> Option<&str> {
+        self.payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| self.payload.downcast_ref::<String>().map(String::as_str))
+    }
+
+    /// Returns the method of the request that caused the panic.
+    pub fn method(&self) -> &Method {
+        &self.req.method
+    }
+
+    /// Returns the URI of the request that caused the panic.
+    pub fn uri(&self) -> &Uri {
+        &self.req.uri
+    }
+
+    /// Returns the [pattern of the route](MatchedPath) that panicked, if the request matched one.
+    pub fn route(&self) -> Option<&str> {
+        self.req.route.as_deref()
+    }
+
+    /// Returns the ID of the request that caused the panic.
+    ///
+    /// Only available if the [`RequestId`](crate::middleware::RequestId) middleware is registered
+    /// after (i.e., wraps) `CatchPanic`.
+    pub fn request_id(&self) -> Option<&RequestId> {
+        self.req.request_id.as_ref()
+    }
+}
+
+impl fmt::Debug for PanicContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicContext")
+            .field("message", &self.message())
+            .field("method", self.method())
+            .field("uri", self.uri())
+            .field("route", &self.route())
+            .field("request_id", &self.request_id())
+            .finish()
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for CatchPanic
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
 {
     type Response = ServiceResponse<B>;
     type Error = actix_web::Error;
@@ -59,6 +227,9 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(CatchPanicMiddleware {
             service: Rc::new(service),
+            counters: self.counters.clone(),
+            disable_after: self.disable_after,
+            responder: self.responder.clone(),
         }))
     }
 }
@@ -70,11 +241,15 @@ where
 #[allow(missing_debug_implementations)]
 pub struct CatchPanicMiddleware<S> {
     service: Rc<S>,
+    counters: Option<PanicCounters>,
+    disable_after: Option<(usize, Duration)>,
+    responder: Option<PanicResponder>,
 }
 
 impl<S, B> Service<ServiceRequest> for CatchPanicMiddleware<S>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
 {
     type Response = ServiceResponse<B>;
     type Error = actix_web::Error;
@@ -83,17 +258,171 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        // routing happens inside the wrapped service so the route is resolved up front
+        let route = (self.counters.is_some() || self.responder.is_some())
+            .then(|| req.resource_map().match_pattern(req.path()))
+            .flatten()
+            .map(|pattern| MatchedPath::from_pattern(&pattern));
+
+        let counted = self.counters.clone().zip(route.clone());
+
+        if let Some((counters, route)) = &counted {
+            if counters.is_disabled(route) {
+                return Box::pin(ready(Err(error::ErrorServiceUnavailable(""))));
+            }
+        }
+
+        let disable_after = self.disable_after;
+
+        let responder = self.responder.clone().map(|responder| {
+            let meta = RequestMeta {
+                method: req.method().clone(),
+                uri: req.uri().clone(),
+                route,
+                request_id: req.extensions().get::<RequestId>().cloned(),
+            };
+
+            (responder, meta)
+        });
+
         AssertUnwindSafe(self.service.call(req))
             .catch_unwind()
             .map(move |res| match res {
                 Ok(Ok(res)) => Ok(res),
                 Ok(Err(svc_err)) => Err(svc_err),
-                Err(_panic_err) => Err(error::ErrorInternalServerError("")),
+                Err(panic_err) => {
+                    if let Some((counters, route)) = counted {
+                        counters.record(&route, disable_after);
+                    }
+
+                    match responder {
+                        Some((responder, meta)) => {
+                            let res = responder(&PanicContext {
+                                payload: &*panic_err,
+                                req: &meta,
+                            });
+
+                            Err(error::InternalError::from_response("panic", res).into())
+                        }
+
+                        None => Err(error::ErrorInternalServerError("")),
+                    }
+                }
             })
             .boxed_local()
     }
 }
 
+/// Shared, per-route panic counters for [`CatchPanic`].
+///
+/// Cloning produces a handle to the same counters, so it can be shared between workers and kept
+/// for inspection (e.g., by an admin endpoint).
+#[derive(Debug, Clone, Default)]
+pub struct PanicCounters {
+    routes: Arc<Mutex<HashMap<String, RouteState>>>,
+}
+
+#[derive(Debug, Default)]
+struct RouteState {
+    total: u64,
+    recent: VecDeque<Instant>,
+    disabled: bool,
+}
+
+/// Panic statistics for a single route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PanicStats {
+    /// Number of panics caught since the route was last reset.
+    pub panics: u64,
+
+    /// True if the route has been disabled.
+    pub disabled: bool,
+}
+
+impl Serialize for PanicStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut stats = serializer.serialize_struct("PanicStats", 2)?;
+        stats.serialize_field("panics", &self.panics)?;
+        stats.serialize_field("disabled", &self.disabled)?;
+        stats.end()
+    }
+}
+
+impl PanicCounters {
+    /// Constructs a new set of empty counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns panic statistics for all routes that have panicked, keyed by route pattern.
+    ///
+    /// Route patterns are [normalized](MatchedPath) to remove trailing slashes.
+    pub fn snapshot(&self) -> BTreeMap<String, PanicStats> {
+        self.routes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(route, state)| {
+                let stats = PanicStats {
+                    panics: state.total,
+                    disabled: state.disabled,
+                };
+
+                (route.clone(), stats)
+            })
+            .collect()
+    }
+
+    /// Clears the counters of, and re-enables, the route with the given pattern.
+    pub fn reset(&self, route: &str) {
+        let route = MatchedPath::from_pattern(route);
+        self.routes.lock().unwrap().remove(route.as_str());
+    }
+
+    /// Clears the counters of, and re-enables, all routes.
+    pub fn reset_all(&self) {
+        self.routes.lock().unwrap().clear();
+    }
+
+    /// Returns true if the route with the given pattern has been disabled.
+    pub fn is_disabled(&self, route: &str) -> bool {
+        let route = MatchedPath::from_pattern(route);
+
+        self.routes
+            .lock()
+            .unwrap()
+            .get(route.as_str())
+            .is_some_and(|state| state.disabled)
+    }
+
+    fn record(&self, route: &MatchedPath, disable_after: Option<(usize, Duration)>) {
+        let mut routes = self.routes.lock().unwrap();
+        let state = routes.entry(route.to_string()).or_default();
+
+        state.total += 1;
+
+        let Some((max_panics, window)) = disable_after else {
+            return;
+        };
+
+        let now = Instant::now();
+        state.recent.push_back(now);
+
+        while state
+            .recent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > window)
+        {
+            state.recent.pop_front();
+        }
+
+        if state.recent.len() >= max_panics {
+            state.disabled = true;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::{
@@ -138,6 +467,65 @@ mod tests {
         assert_eq!(body, "content");
     }
 
+    #[actix_web::test]
+    async fn counts_and_disables_panicking_routes() {
+        let counters = PanicCounters::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    CatchPanic::default()
+                        .counters(counters.clone())
+                        .disable_after(2, Duration::from_secs(60)),
+                )
+                .route("/", web::get().to(|| async { "content" }))
+                .route(
+                    "/disco/{n}",
+                    #[allow(unreachable_code)]
+                    web::get().to(|| async {
+                        panic!("the disco");
+                        ""
+                    }),
+                ),
+        )
+        .await;
+
+        for (uri, status) in [
+            ("/disco/1", StatusCode::INTERNAL_SERVER_ERROR),
+            ("/disco/2", StatusCode::INTERNAL_SERVER_ERROR),
+            ("/disco/3", StatusCode::SERVICE_UNAVAILABLE),
+            ("/", StatusCode::OK),
+        ] {
+            let req = test::TestRequest::with_uri(uri).to_request();
+            let res = match app.call(req).await {
+                Ok(res) => res.status(),
+                Err(err) => err.error_response().status(),
+            };
+            assert_eq!(res, status, "unexpected status for {uri}");
+        }
+
+        assert_eq!(
+            counters.snapshot().into_iter().collect::<Vec<_>>(),
+            [(
+                "/disco/{n}".to_owned(),
+                PanicStats {
+                    panics: 2,
+                    disabled: true,
+                },
+            )],
+        );
+
+        counters.reset("/disco/{n}");
+        assert!(counters.snapshot().is_empty());
+
+        let req = test::TestRequest::with_uri("/disco/4").to_request();
+        let err = app.call(req).await.err().unwrap();
+        assert_eq!(
+            err.error_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+    }
+
     #[actix_web::test]
     async fn catch_panic_return_internal_server_error_response() {
         let app = test::init_service(test_app()).await;
@@ -152,4 +540,52 @@ mod tests {
         let body = to_bytes(res.into_body()).await.unwrap();
         assert!(body.is_empty());
     }
+
+    #[actix_web::test]
+    async fn custom_responder() {
+        let app = test::init_service(
+            App::new()
+                .wrap(CatchPanic::default().responder(|panic| {
+                    HttpResponse::InternalServerError().body(format!(
+                        "{} {} {:?} {:?} {}",
+                        panic.method(),
+                        panic.uri().path(),
+                        panic.route(),
+                        panic.message(),
+                        panic.request_id().is_some(),
+                    ))
+                }))
+                .wrap(crate::middleware::RequestId::new())
+                .route(
+                    "/disco/{n}",
+                    #[allow(unreachable_code)]
+                    web::get().to(|| async {
+                        panic!("the disco");
+                        ""
+                    }),
+                )
+                .default_service(web::to(
+                    #[allow(unreachable_code)]
+                    || async {
+                        panic!("{}", String::from("formatted"));
+                        ""
+                    },
+                )),
+        )
+        .await;
+
+        for (uri, body) in [
+            (
+                "/disco/1",
+                r#"GET /disco/1 Some("/disco/{n}") Some("the disco") true"#,
+            ),
+            ("/other", r#"GET /other None Some("formatted") true"#),
+        ] {
+            let req = test::TestRequest::with_uri(uri).to_request();
+            let res = app.call(req).await.err().unwrap().error_response();
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            let res_body = to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(res_body, body);
+        }
+    }
 }
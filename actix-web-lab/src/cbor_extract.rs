@@ -0,0 +1,320 @@
+//! CBOR extractor with const-generic payload size limit.
+
+use std::fmt;
+
+use actix_web::{
+    dev::Payload, error::PayloadError, http::StatusCode, FromRequest, HttpMessage as _,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{decompress, lab_config::LabConfig};
+
+/// Default CBOR payload size limit of 2MiB.
+pub const DEFAULT_CBOR_LIMIT: usize = 2_097_152;
+
+/// [CBOR] extractor with const-generic payload size limit.
+///
+/// Deserializes a `T` from a request body with an `application/cbor` (or `+cbor` suffixed)
+/// `Content-Type`, buffering up to `LIMIT` bytes. The default limit is 2MiB.
+///
+/// Deserialization errors include the path to the field that failed; see
+/// [`CborPayloadError::Deserialize`].
+///
+/// # Examples
+/// ```
+/// use actix_web::post;
+/// use actix_web_lab::extract::Cbor;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// #[post("/")]
+/// async fn index(info: Cbor<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// const LIMIT_32_MB: usize = 33_554_432;
+///
+/// /// Deserialize payload with a higher 32MiB limit.
+/// #[post("/big-payload")]
+/// async fn big_payload(info: Cbor<Info, LIMIT_32_MB>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+///
+/// [CBOR]: https://cbor.io/
+#[derive(Debug)]
+pub struct Cbor<T, const LIMIT: usize = DEFAULT_CBOR_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> std::ops::Deref for Cbor<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> std::ops::DerefMut for Cbor<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const LIMIT: usize> Cbor<T, LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static, const LIMIT: usize> FromRequest for Cbor<T, LIMIT> {
+    type Error = CborPayloadError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let can_parse_cbor = match req.mime_type() {
+            Ok(Some(mime)) => {
+                mime.subtype() == "cbor" || mime.suffix().is_some_and(|suffix| suffix == "cbor")
+            }
+            _ => false,
+        };
+
+        let length = req
+            .get_header::<crate::header::ContentLength>()
+            .map(|cl| cl.into_inner())
+            .filter(|_| !decompress::is_encoded(req));
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_CBOR_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let capacity = LabConfig::buffer_capacity(req, length);
+        let mut payload = decompress::take_payload(req, payload);
+        let req = req.clone();
+
+        Box::pin(async move {
+            if !can_parse_cbor {
+                return Err(CborPayloadError::ContentType);
+            }
+
+            if let Some(length) = length.filter(|&len| len > limit) {
+                return Err(CborPayloadError::OverflowKnownLength { length, limit });
+            }
+
+            let mut buf = actix_web::web::BytesMut::with_capacity(capacity);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(CborPayloadError::Payload)?;
+
+                if buf.len() + chunk.len() > limit {
+                    return Err(CborPayloadError::Overflow { limit });
+                }
+
+                buf.extend_from_slice(&chunk);
+            }
+
+            deserialize(&buf).map(Cbor).inspect_err(|err| {
+                debug!(
+                    "Failed to deserialize Cbor<{}> from payload in handler: {}: {err}",
+                    core::any::type_name::<T>(),
+                    req.match_name().unwrap_or_else(|| req.path()),
+                );
+            })
+        })
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(buf: &[u8]) -> Result<T, CborPayloadError> {
+    let mut de = serde_cbor_2::Deserializer::from_slice(buf);
+    let mut track = serde_path_to_error::Track::new();
+
+    let item = T::deserialize(serde_path_to_error::Deserializer::new(&mut de, &mut track))
+        .map_err(|source| CborPayloadError::Deserialize {
+            path: track.path(),
+            source,
+        })?;
+
+    // reject trailing data
+    de.end().map_err(|source| CborPayloadError::Deserialize {
+        path: serde_path_to_error::Track::new().path(),
+        source,
+    })?;
+
+    Ok(item)
+}
+
+/// Errors that can occur when extracting a CBOR payload.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum CborPayloadError {
+    /// Request does not have a CBOR `Content-Type`.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Payload size is bigger than allowed and content length header is set.
+    #[display("CBOR payload ({length} bytes) is larger than allowed (limit: {limit} bytes).")]
+    OverflowKnownLength {
+        /// Length reported by `Content-Length` header.
+        length: usize,
+
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload size is bigger than allowed but no content length header set.
+    #[display("CBOR payload has exceeded limit ({limit} bytes).")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload could not be deserialized into the target type.
+    #[display("{}", DeserializeDisplay(path))]
+    Deserialize {
+        /// Path to the field at which deserialization failed.
+        #[error(not(source))]
+        path: serde_path_to_error::Path,
+
+        /// Underlying CBOR error.
+        source: serde_cbor_2::Error,
+    },
+
+    /// Error that occurred when reading the payload.
+    #[display("Error that occurred when reading payload: {_0}")]
+    Payload(PayloadError),
+}
+
+struct DeserializeDisplay<'a>(&'a serde_path_to_error::Path);
+
+impl fmt::Display for DeserializeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CBOR deserialization failed")?;
+
+        if self.0.iter().len() > 0 {
+            write!(f, " at path: {}", self.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ResponseError for CborPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::OverflowKnownLength { .. } | Self::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Deserialize { .. } | Self::Payload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct MyObject {
+        name: String,
+        tags: Vec<u32>,
+    }
+
+    fn cbor_req(body: Vec<u8>) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/cbor"))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn extracts_payload() {
+        let item = MyObject {
+            name: "test".to_owned(),
+            tags: vec![1, 2],
+        };
+
+        let (req, mut pl) = cbor_req(serde_cbor_2::to_vec(&item).unwrap()).to_http_parts();
+        let extracted = Cbor::<MyObject>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(extracted.into_inner(), item);
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/vnd.example+cbor"))
+            .set_payload(serde_cbor_2::to_vec(&item).unwrap())
+            .to_http_parts();
+        assert!(Cbor::<MyObject>::from_request(&req, &mut pl).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_requests() {
+        let body = serde_cbor_2::to_vec(&MyObject {
+            name: "test".to_owned(),
+            tags: vec![],
+        })
+        .unwrap();
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload(body.clone())
+            .to_http_parts();
+        let err = Cbor::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CborPayloadError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = cbor_req(body.clone()).to_http_parts();
+        let err = Cbor::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CborPayloadError::OverflowKnownLength { limit: 4, .. }
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/cbor"))
+            .set_payload(body)
+            .to_http_parts();
+        let err = Cbor::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CborPayloadError::Overflow { limit: 4 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_web::test]
+    async fn reports_error_path() {
+        #[derive(Serialize)]
+        struct Invalid {
+            name: &'static str,
+            tags: (u32, &'static str),
+        }
+
+        let body = serde_cbor_2::to_vec(&Invalid {
+            name: "test",
+            tags: (1, "two"),
+        })
+        .unwrap();
+
+        let (req, mut pl) = cbor_req(body).to_http_parts();
+        let err = Cbor::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(
+            err.to_string().ends_with("at path: tags[1]"),
+            "unexpected error: {err}",
+        );
+    }
+}
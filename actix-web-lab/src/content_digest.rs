@@ -0,0 +1,389 @@
+//! Content-Digest and Repr-Digest typed headers.
+//!
+//! See [`ContentDigest`] docs.
+
+use std::{fmt, str};
+
+use actix_http::{
+    error::ParseError,
+    header::{Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+use base64::prelude::*;
+use sha2::{Digest as _, Sha256, Sha512};
+
+/// Hashing algorithm keys used in [`ContentDigest`] and [`ReprDigest`] headers.
+///
+/// See the [IANA Hash Algorithms for HTTP Digest Fields registry][iana].
+///
+/// [iana]: https://www.iana.org/assignments/http-digest-hash-alg/http-digest-hash-alg.xhtml
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DigestAlgorithm {
+    /// The `sha-256` algorithm.
+    Sha256,
+
+    /// The `sha-512` algorithm.
+    Sha512,
+
+    /// The `blake3` algorithm.
+    ///
+    /// Not (yet) in the IANA registry, so only useful between cooperating services.
+    Blake3,
+
+    /// Any other algorithm key, stored in lowercase.
+    Extension(String),
+}
+
+impl DigestAlgorithm {
+    /// Returns the dictionary key of this algorithm.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Sha256 => "sha-256",
+            Self::Sha512 => "sha-512",
+            Self::Blake3 => "blake3",
+            Self::Extension(key) => key,
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "sha-256" => Self::Sha256,
+            "sha-512" => Self::Sha512,
+            "blake3" => Self::Blake3,
+            key => Self::Extension(key.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single algorithm and digest pair in a [`ContentDigest`] or [`ReprDigest`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestValue {
+    /// Hashing algorithm used.
+    pub algorithm: DigestAlgorithm,
+
+    /// Raw (not base64 encoded) digest bytes.
+    pub digest: Vec<u8>,
+}
+
+impl DigestValue {
+    /// Constructs a new digest value from an algorithm and raw digest bytes.
+    pub fn new(algorithm: DigestAlgorithm, digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            algorithm,
+            digest: digest.into(),
+        }
+    }
+
+    /// Computes the `sha-256` digest of `data`.
+    pub fn sha256(data: impl AsRef<[u8]>) -> Self {
+        Self::new(DigestAlgorithm::Sha256, Sha256::digest(data).to_vec())
+    }
+
+    /// Computes the `sha-512` digest of `data`.
+    pub fn sha512(data: impl AsRef<[u8]>) -> Self {
+        Self::new(DigestAlgorithm::Sha512, Sha512::digest(data).to_vec())
+    }
+}
+
+impl fmt::Display for DigestValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}=:{}:",
+            self.algorithm,
+            BASE64_STANDARD.encode(&self.digest)
+        )
+    }
+}
+
+impl str::FromStr for DigestValue {
+    type Err = ParseError;
+
+    fn from_str(member: &str) -> Result<Self, Self::Err> {
+        let (key, value) = member.trim().split_once('=').ok_or(ParseError::Header)?;
+
+        let key = key.trim();
+        if key.is_empty()
+            || !key
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*'))
+        {
+            return Err(ParseError::Header);
+        }
+
+        // parameters on dictionary members are permitted but have no defined meaning
+        let value = value.split(';').next().unwrap_or_default().trim();
+
+        let digest = value
+            .strip_prefix(':')
+            .and_then(|value| value.strip_suffix(':'))
+            .ok_or(ParseError::Header)?;
+
+        let digest = BASE64_STANDARD
+            .decode(digest)
+            .map_err(|_| ParseError::Header)?;
+
+        Ok(Self::new(DigestAlgorithm::from_key(key), digest))
+    }
+}
+
+/// Parses dictionary members from all header values with the given name.
+fn parse_digests<M: HttpMessage>(
+    msg: &M,
+    name: HeaderName,
+) -> Result<Vec<DigestValue>, ParseError> {
+    let mut digests = Vec::new();
+
+    for hdr in msg.headers().get_all(name) {
+        let hdr = hdr.to_str().map_err(|_| ParseError::Header)?;
+
+        for member in hdr.split(',').filter(|member| !member.trim().is_empty()) {
+            let digest = member.parse::<DigestValue>()?;
+
+            // later dictionary members override earlier ones with the same key
+            digests.retain(|existing: &DigestValue| existing.algorithm != digest.algorithm);
+            digests.push(digest);
+        }
+    }
+
+    if digests.is_empty() {
+        return Err(ParseError::Header);
+    }
+
+    Ok(digests)
+}
+
+fn fmt_digests(f: &mut fmt::Formatter<'_>, digests: &[DigestValue]) -> fmt::Result {
+    let mut digests = digests.iter();
+
+    let Some(digest) = digests.next() else {
+        return Ok(());
+    };
+
+    write!(f, "{digest}")?;
+
+    for digest in digests {
+        write!(f, ", {digest}")?;
+    }
+
+    Ok(())
+}
+
+/// The `Content-Digest` header, defined in [RFC 9530 §2].
+///
+/// Contains one or more digests of the message content (i.e., after any content coding is
+/// applied), each calculated with a different [algorithm](DigestAlgorithm).
+///
+/// # ABNF
+/// ```text
+/// Content-Digest = sf-dictionary
+/// ```
+///
+/// # Sample Values
+/// - `sha-256=:RK/0qy18MlBSVnWgjwz6lZEWjP/lF5HF9bvEF8FabDg=:`
+/// - `sha-256=:RK/0qy18MlBSVnWgjwz6lZEWjP/lF5HF9bvEF8FabDg=:, sha-512=:...:`
+///
+/// # Examples
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::{ContentDigest, DigestAlgorithm, DigestValue};
+///
+/// let body = r#"{"hello": "world"}"#;
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(ContentDigest(vec![
+///     DigestValue::sha256(body),
+///     DigestValue::sha512(body),
+/// ]));
+///
+/// let digest = ContentDigest(vec![DigestValue::sha256(body)]);
+/// assert!(digest.verify(body.as_bytes()));
+/// assert!(digest.get(&DigestAlgorithm::Sha256).is_some());
+/// ```
+///
+/// [RFC 9530 §2]: https://datatracker.ietf.org/doc/html/rfc9530#section-2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentDigest(pub Vec<DigestValue>);
+
+/// The `Repr-Digest` header, defined in [RFC 9530 §3].
+///
+/// Contains one or more digests of the selected representation (i.e., the full resource, before
+/// any range is applied), each calculated with a different [algorithm](DigestAlgorithm).
+///
+/// Has the same syntax and methods as [`ContentDigest`].
+///
+/// # Examples
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::{DigestValue, ReprDigest};
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(ReprDigest(vec![DigestValue::sha256("full resource")]));
+/// ```
+///
+/// [RFC 9530 §3]: https://datatracker.ietf.org/doc/html/rfc9530#section-3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReprDigest(pub Vec<DigestValue>);
+
+macro_rules! digest_header {
+    ($name:ident, $header_name:literal) => {
+        impl_more::forward_deref_and_mut!($name => [DigestValue]);
+
+        impl $name {
+            /// Returns the digest bytes for the given algorithm, if present.
+            pub fn get(&self, algorithm: &DigestAlgorithm) -> Option<&[u8]> {
+                self.0
+                    .iter()
+                    .find(|digest| &digest.algorithm == algorithm)
+                    .map(|digest| digest.digest.as_slice())
+            }
+
+            /// Returns true if at least one digest uses a supported algorithm and all digests using
+            /// supported algorithms match `data`.
+            ///
+            /// The supported algorithms are `sha-256` and `sha-512`.
+            pub fn verify(&self, data: &[u8]) -> bool {
+                let mut verified = false;
+
+                for digest in &self.0 {
+                    let expected = match digest.algorithm {
+                        DigestAlgorithm::Sha256 => DigestValue::sha256(data),
+                        DigestAlgorithm::Sha512 => DigestValue::sha512(data),
+                        _ => continue,
+                    };
+
+                    if expected.digest != digest.digest {
+                        return false;
+                    }
+
+                    verified = true;
+                }
+
+                verified
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt_digests(f, &self.0)
+            }
+        }
+
+        impl TryIntoHeaderValue for $name {
+            type Error = InvalidHeaderValue;
+
+            fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+                HeaderValue::try_from(self.to_string())
+            }
+        }
+
+        impl Header for $name {
+            fn name() -> HeaderName {
+                HeaderName::from_static($header_name)
+            }
+
+            fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+                parse_digests(msg, Self::name()).map(Self)
+            }
+        }
+    };
+}
+
+digest_header!(ContentDigest, "content-digest");
+digest_header!(ReprDigest, "repr-digest");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    // base64 of SHA-256 hash of "abc"
+    const ABC_SHA256: &str = "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=";
+
+    #[test]
+    fn compute_and_verify() {
+        let digest = ContentDigest(vec![DigestValue::sha256("abc"), DigestValue::sha512("abc")]);
+        assert!(digest
+            .to_string()
+            .starts_with(&format!("sha-256=:{ABC_SHA256}:, sha-512=:")));
+        assert!(digest.verify(b"abc"));
+        assert!(!digest.verify(b"abd"));
+        assert_eq!(digest.get(&DigestAlgorithm::Sha512).unwrap().len(), 64);
+        assert!(digest.get(&DigestAlgorithm::Blake3).is_none());
+
+        let unsupported = ReprDigest(vec![DigestValue::new(DigestAlgorithm::Blake3, [0; 32])]);
+        assert!(!unsupported.verify(b"abc"));
+    }
+
+    #[test]
+    fn parse_members() {
+        assert_eq!(
+            "sha-256=:AAAA:;foo=bar".parse::<DigestValue>().unwrap(),
+            DigestValue::new(DigestAlgorithm::Sha256, [0, 0, 0]),
+        );
+        assert_eq!(
+            "md5=:AAAA:".parse::<DigestValue>().unwrap().algorithm,
+            DigestAlgorithm::Extension("md5".to_owned()),
+        );
+
+        "SHA-256=:AAAA:".parse::<DigestValue>().unwrap_err();
+        "sha-256=AAAA".parse::<DigestValue>().unwrap_err();
+        "sha-256=:!!:".parse::<DigestValue>().unwrap_err();
+        "sha-256".parse::<DigestValue>().unwrap_err();
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        assert_parse_eq(
+            ["sha-256=:AAAA:, sha-256=:AAAB:"],
+            ContentDigest(vec![DigestValue::new(DigestAlgorithm::Sha256, [0, 0, 1])]),
+        );
+        assert_parse_eq(
+            ["sha-256=:AAAA:"],
+            ReprDigest(vec![DigestValue::new(DigestAlgorithm::Sha256, [0, 0, 0])]),
+        );
+        assert_parse_fail::<ReprDigest, _, _>(["sha-256=:AAAA:, bad"]);
+    }
+}
+
+#[cfg(test)]
+crate::test::header_test_module! {
+    ContentDigest,
+    tests_parse_and_format {
+        header_round_trip_test!(no_headers, [b""; 0], None);
+        header_round_trip_test!(empty_header, [b""; 1], None);
+        header_round_trip_test!(bad_syntax, [b"sha-256"], None);
+        header_round_trip_test!(bad_base64, [b"sha-256=:***:"], None);
+
+        header_round_trip_test!(
+            single_digest,
+            [b"sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:"],
+            Some(ContentDigest(vec![DigestValue::sha256("abc")]))
+        );
+
+        header_round_trip_test!(
+            multiple_digests,
+            [b"sha-256=:AAAA:, blake3=:AAAA:"],
+            Some(ContentDigest(vec![
+                DigestValue::new(DigestAlgorithm::Sha256, [0, 0, 0]),
+                DigestValue::new(DigestAlgorithm::Blake3, [0, 0, 0]),
+            ]))
+        );
+
+        header_round_trip_test!(
+            multiple_headers,
+            [&b"sha-256=:AAAA:"[..], &b"sha-512=:AAAA:"[..]],
+            Some(ContentDigest(vec![
+                DigestValue::new(DigestAlgorithm::Sha256, [0, 0, 0]),
+                DigestValue::new(DigestAlgorithm::Sha512, [0, 0, 0]),
+            ]))
+        );
+    }
+}
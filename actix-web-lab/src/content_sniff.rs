@@ -0,0 +1,364 @@
+//! Content-Type verification extractor and payload adaptor.
+//!
+//! See [`VerifiedContentType`] docs.
+
+use std::{
+    cell::Cell,
+    future::Future,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{ready, Context, Poll},
+};
+
+use actix_http::{error::PayloadError, BoxedPayloadStream};
+use actix_web::{dev, http::StatusCode, FromRequest, HttpMessage as _, HttpRequest, ResponseError};
+use bytes::{Bytes, BytesMut};
+use derive_more::Display;
+use futures_core::Stream;
+use mime::Mime;
+
+/// A file signature; all parts must match at their offsets.
+type Signature = &'static [(usize, &'static [u8])];
+
+/// Returns the known signatures for a media type, any of which is accepted.
+fn signatures(mime: &Mime) -> Option<&'static [Signature]> {
+    Some(match mime.essence_str() {
+        "image/png" => &[&[(0, b"\x89PNG\r\n\x1a\n")]],
+        "image/jpeg" => &[&[(0, b"\xff\xd8\xff")]],
+        "image/gif" => &[&[(0, b"GIF87a")], &[(0, b"GIF89a")]],
+        "image/webp" => &[&[(0, b"RIFF"), (8, b"WEBP")]],
+        "image/bmp" => &[&[(0, b"BM")]],
+        "image/tiff" => &[&[(0, b"II*\0")], &[(0, b"MM\0*")]],
+        "application/pdf" => &[&[(0, b"%PDF-")]],
+        "application/zip" | "application/x-zip-compressed" => &[
+            &[(0, b"PK\x03\x04")],
+            &[(0, b"PK\x05\x06")],
+            &[(0, b"PK\x07\x08")],
+        ],
+        _ => return None,
+    })
+}
+
+/// Returns the number of leading bytes needed to check all `signatures`.
+fn prefix_len(signatures: &[Signature]) -> usize {
+    signatures
+        .iter()
+        .flat_map(|sig| sig.iter())
+        .map(|(offset, magic)| offset + magic.len())
+        .max()
+        .unwrap_or(0)
+}
+
+fn matches_any(signatures: &[Signature], prefix: &[u8]) -> bool {
+    signatures.iter().any(|sig| {
+        sig.iter()
+            .all(|(offset, magic)| prefix.get(*offset..offset + magic.len()) == Some(magic))
+    })
+}
+
+/// Wraps a request payload so that its first bytes are checked against the signature ("magic
+/// number") of the `declared` media type.
+///
+/// Signatures are known for PNG, JPEG, GIF, WebP, BMP, and TIFF images, PDF documents, and ZIP
+/// archives. Payloads declared as any other media type are passed through unchecked.
+///
+/// Only enough of the payload to check the signature is buffered. If it does not match, an error
+/// is yielded instead of the payload; it results in a `400 Bad Request` response when converted
+/// directly.
+///
+/// This adaptor can be used with any extractor that reads the request body. See
+/// [`VerifiedContentType`](crate::extract::VerifiedContentType) for an extractor wrapper that
+/// applies it based on the request's `Content-Type` header.
+pub fn verify_content_type(payload: &mut dev::Payload, declared: &Mime) {
+    verify(payload, declared, Rc::default());
+}
+
+fn verify(payload: &mut dev::Payload, declared: &Mime, mismatch: Rc<Cell<bool>>) {
+    let Some(signatures) = signatures(declared) else {
+        return;
+    };
+
+    let stream: BoxedPayloadStream = Box::pin(SignatureCheck {
+        stream: payload.take(),
+        signatures,
+        prefix: BytesMut::new(),
+        checked: false,
+        done: false,
+        mismatch,
+    });
+
+    *payload = dev::Payload::from(stream);
+}
+
+pin_project_lite::pin_project! {
+    struct SignatureCheck<S> {
+        #[pin]
+        stream: S,
+        signatures: &'static [Signature],
+        prefix: BytesMut,
+        checked: bool,
+        done: bool,
+        mismatch: Rc<Cell<bool>>,
+    }
+}
+
+impl<S> Stream for SignatureCheck<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>>,
+{
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        if *this.checked {
+            return this.stream.poll_next(cx);
+        }
+
+        let needed = prefix_len(this.signatures);
+
+        // buffer until there are enough bytes to check the signature or the payload ends
+        let ended = loop {
+            if this.prefix.len() >= needed {
+                break false;
+            }
+
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => this.prefix.extend_from_slice(&chunk),
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => break true,
+            }
+        };
+
+        *this.checked = true;
+
+        if !matches_any(this.signatures, this.prefix) {
+            *this.done = true;
+            this.mismatch.set(true);
+
+            return Poll::Ready(Some(Err(PayloadError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "payload does not match declared content type",
+            )))));
+        }
+
+        *this.done = ended;
+        Poll::Ready(Some(Ok(this.prefix.split().freeze())))
+    }
+}
+
+/// Extractor wrapper that rejects payloads whose first bytes do not match their `Content-Type`.
+///
+/// The request payload is checked against the signature ("magic number") of the media type
+/// declared in the `Content-Type` header, then read by the wrapped extractor as normal. This stops
+/// clients from, for example, uploading an executable labelled as an image. See
+/// [`verify_content_type()`](crate::util::verify_content_type) for the supported media types.
+///
+/// If the payload does not match, extraction fails with a `415 Unsupported Media Type` response,
+/// regardless of how the wrapped extractor reports payload errors. Requests without a valid
+/// `Content-Type` header, or with a media type that has no known signature, are passed through
+/// unchecked.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{post, web::Bytes, Responder};
+/// use actix_web_lab::extract::VerifiedContentType;
+///
+/// #[post("/avatar")]
+/// async fn upload_avatar(body: VerifiedContentType<Bytes>) -> impl Responder {
+///     let image = body.into_inner();
+///     format!("received {} byte image", image.len())
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerifiedContentType<T> {
+    inner: T,
+}
+
+impl<T> VerifiedContentType<T> {
+    /// Returns inner extracted type.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> FromRequest for VerifiedContentType<T>
+where
+    T: FromRequest + 'static,
+{
+    type Error = VerifiedContentTypeError;
+    type Future = VerifiedContentTypeFut<T>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let mismatch = Rc::<Cell<bool>>::default();
+
+        if let Ok(Some(mime)) = req.mime_type() {
+            verify(payload, &mime, Rc::clone(&mismatch));
+        }
+
+        VerifiedContentTypeFut {
+            fut: Box::pin(T::from_request(req, payload)),
+            mismatch,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct VerifiedContentTypeFut<T>
+where
+    T: FromRequest,
+{
+    fut: Pin<Box<T::Future>>,
+    mismatch: Rc<Cell<bool>>,
+}
+
+impl<T> Future for VerifiedContentTypeFut<T>
+where
+    T: FromRequest,
+{
+    type Output = Result<VerifiedContentType<T>, VerifiedContentTypeError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = ready!(this.fut.as_mut().poll(cx));
+
+        // mismatch takes priority over however the inner extractor reports payload errors
+        if this.mismatch.get() {
+            return Poll::Ready(Err(VerifiedContentTypeError::Mismatch));
+        }
+
+        Poll::Ready(
+            res.map(|inner| VerifiedContentType { inner })
+                .map_err(|err| VerifiedContentTypeError::Extractor(err.into())),
+        )
+    }
+}
+
+/// Errors that can occur when extracting a [`VerifiedContentType`].
+#[derive(Debug, Display)]
+#[non_exhaustive]
+pub enum VerifiedContentTypeError {
+    /// Wrapped extractor failed.
+    #[display("Wrapped extractor error: {_0}")]
+    Extractor(actix_web::Error),
+
+    /// Payload did not match the signature of its declared media type.
+    #[display("Payload does not match declared content type")]
+    Mismatch,
+}
+
+impl ResponseError for VerifiedContentTypeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Extractor(err) => err.as_response_error().status_code(),
+            Self::Mismatch => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    static_assertions::assert_impl_all!(VerifiedContentTypeFut<Bytes>: Unpin);
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn parts(content_type: &'static str, body: &'static [u8]) -> (HttpRequest, dev::Payload) {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(Bytes::from_static(body))
+            .to_http_parts()
+    }
+
+    #[actix_web::test]
+    async fn matching_signature() {
+        let (req, mut pl) = parts("image/png", PNG);
+
+        let body = VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), Bytes::from_static(PNG));
+
+        let (req, mut pl) = parts("image/webp", b"RIFF\x24\0\0\0WEBPVP8 ");
+        VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+
+        let (req, mut pl) = parts("application/pdf", b"%PDF-1.7\n");
+        VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn mismatched_signature() {
+        let (req, mut pl) = parts("image/jpeg", PNG);
+
+        let err = VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, VerifiedContentTypeError::Mismatch));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = parts("application/zip", b"MZ\x90\0");
+        let err = VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, VerifiedContentTypeError::Mismatch));
+
+        // too short to contain the signature
+        let (req, mut pl) = parts("image/png", b"\x89PN");
+        let err = VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, VerifiedContentTypeError::Mismatch));
+    }
+
+    #[actix_web::test]
+    async fn unchecked_types() {
+        let (req, mut pl) = parts("text/plain", b"hello");
+        let body = VerifiedContentType::<String>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), "hello");
+
+        let (_req, mut pl) = parts("image/png", b"hello");
+        let req = TestRequest::default().to_http_request();
+        let body = VerifiedContentType::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), Bytes::from_static(b"hello"));
+    }
+
+    #[actix_web::test]
+    async fn payload_adaptor() {
+        // signature split across chunks
+        let chunks = [&PNG[..3], &PNG[3..9], &PNG[9..]]
+            .map(|chunk| Ok::<_, PayloadError>(Bytes::from_static(chunk)));
+        let stream: BoxedPayloadStream = Box::pin(stream::iter(chunks));
+        let mut pl = dev::Payload::from(stream);
+
+        verify_content_type(&mut pl, &"image/png".parse().unwrap());
+
+        // buffered chunks are yielded together
+        assert_eq!(pl.next().await.unwrap().unwrap(), &PNG[..9]);
+        assert_eq!(pl.next().await.unwrap().unwrap(), &PNG[9..]);
+        assert!(pl.next().await.is_none());
+
+        let (_req, mut pl) = parts("image/gif", PNG);
+        verify_content_type(&mut pl, &mime::IMAGE_GIF);
+
+        assert!(matches!(pl.next().await.unwrap(), Err(PayloadError::Io(_)),));
+        assert!(pl.next().await.is_none());
+    }
+}
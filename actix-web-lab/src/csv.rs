@@ -2,16 +2,26 @@ use std::{convert::Infallible, error::Error as StdError};
 
 use actix_web::{
     body::{BodyStream, MessageBody},
+    http::header::ContentDisposition,
     HttpResponse, Responder,
 };
 use bytes::{Bytes, BytesMut};
 use futures_core::Stream;
-use futures_util::TryStreamExt as _;
+use futures_util::{stream, StreamExt as _, TryStreamExt as _};
 use mime::Mime;
 use pin_project_lite::pin_project;
 use serde::Serialize;
 
-use crate::util::{InfallibleStream, MutWriter};
+use crate::util::{
+    CompletionCallback, CountingStream, ErrorTrailerFn, ErrorTrailerStream, InfallibleStream,
+    MutWriter,
+};
+
+/// UTF-8 byte order mark, which Excel uses to detect the encoding of CSV files.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Maps a stream error to a comment message.
+type ErrorCommentFn = Box<dyn FnOnce(Box<dyn StdError>) -> String>;
 
 pin_project! {
     /// A buffered CSV serializing body stream.
@@ -40,13 +50,124 @@ pin_project! {
         // The wrapped item stream.
         #[pin]
         stream: S,
+
+        // Called with the number of serialized rows after the stream completes.
+        on_complete: Option<CompletionCallback>,
+
+        // Maps a stream error to a trailing comment row.
+        on_error: Option<ErrorCommentFn>,
+
+        // Whether to write a byte order mark and CRLF line endings.
+        excel_compatible: bool,
+
+        // Whether to write a `sep=,` line before the first row.
+        separator_hint: bool,
+
+        // Filename for the `Content-Disposition` header set by `into_responder()`.
+        filename: Option<String>,
     }
 }
 
 impl<S> Csv<S> {
-    /// Constructs a new `Csv` from a stream of rows.
+    /// Constructs a new `Csv` from a fallible stream of rows.
+    ///
+    /// An error from the stream aborts the response, which clients usually see as an unexpectedly
+    /// closed connection. See [`error_comment()`](Self::error_comment) for an alternative.
     pub fn new(stream: S) -> Self {
-        Self { stream }
+        Self {
+            stream,
+            on_complete: None,
+            on_error: None,
+            excel_compatible: false,
+            separator_hint: false,
+            filename: None,
+        }
+    }
+
+    /// Sets a callback that is called with the number of serialized rows once the stream has been
+    /// fully consumed.
+    ///
+    /// The callback is not called if the stream yields an error or if the body is dropped before
+    /// completion (e.g., because the client disconnected), so it can be used to verify in logs that
+    /// an export was delivered in full without pre-counting rows.
+    ///
+    /// Actix Web does not currently support sending HTTP trailers, so the count cannot be reported
+    /// to clients after the body has started streaming.
+    pub fn on_complete(mut self, on_complete: impl FnOnce(usize) + 'static) -> Self {
+        self.on_complete = Some(Box::new(on_complete));
+        self
+    }
+
+    /// Sets a function that maps a stream error to a comment written as the last row of the body.
+    ///
+    /// Instead of aborting the response, the message returned by `error_comment` is written with
+    /// each line prefixed by `# ` and the response completes normally. This makes the reason for a
+    /// truncated export visible to whoever opens the file, but since comments are not part of the
+    /// CSV standard, clients that need to detect failures programmatically must look for them.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::Responder;
+    /// # use actix_web_lab::respond::Csv;
+    /// # use futures_util::stream;
+    /// async fn handler() -> impl Responder {
+    ///     let rows = stream::iter([Ok([1, 2]), Err(std::io::Error::other("db gone"))]);
+    ///
+    ///     Csv::new(rows)
+    ///         .error_comment(|err| format!("export failed: {err}"))
+    ///         .into_responder()
+    /// }
+    /// ```
+    pub fn error_comment(
+        mut self,
+        error_comment: impl FnOnce(Box<dyn StdError>) -> String + 'static,
+    ) -> Self {
+        self.on_error = Some(Box::new(error_comment));
+        self
+    }
+
+    /// Formats output so that it opens correctly when double-clicked in Microsoft Excel.
+    ///
+    /// The body starts with a UTF-8 byte order mark, without which Excel assumes a legacy encoding
+    /// and mangles non-ASCII text, and rows are terminated with CRLF instead of LF. Usually combined
+    /// with [`attachment()`](Self::attachment) so that browsers save the response as a file.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::Responder;
+    /// # use actix_web_lab::respond::Csv;
+    /// # use futures_util::stream;
+    /// async fn handler() -> impl Responder {
+    ///     let rows = stream::iter([["name", "city"], ["Zoë", "Zürich"]]);
+    ///
+    ///     Csv::new_infallible(rows)
+    ///         .excel_compatible()
+    ///         .attachment("export.csv")
+    ///         .into_responder()
+    /// }
+    /// ```
+    pub fn excel_compatible(mut self) -> Self {
+        self.excel_compatible = true;
+        self
+    }
+
+    /// Writes a `sep=,` line before the first row.
+    ///
+    /// Excel otherwise uses the list separator of the user's locale, which is `;` in many European
+    /// locales, causing each row to be shown in a single column. The hint line is not part of the
+    /// CSV standard, so other consumers will see it as an extra row. Note that Excel ignores the
+    /// byte order mark written by [`excel_compatible()`](Self::excel_compatible) when this hint is
+    /// present, so non-ASCII text may be displayed incorrectly when both are used.
+    pub fn separator_hint(mut self) -> Self {
+        self.separator_hint = true;
+        self
+    }
+
+    /// Sets a `Content-Disposition: attachment` header with the given filename when converted
+    /// using [`into_responder()`](Self::into_responder).
+    pub fn attachment(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
     }
 }
 
@@ -75,15 +196,41 @@ where
         T: 'static,
         E: 'static,
     {
-        HttpResponse::Ok()
-            .content_type(mime::TEXT_CSV_UTF_8)
-            .message_body(self.into_body_stream())
-            .unwrap()
+        let mut res = HttpResponse::Ok();
+        res.content_type(mime::TEXT_CSV_UTF_8);
+
+        if let Some(filename) = &self.filename {
+            res.insert_header(ContentDisposition::attachment(filename));
+        }
+
+        res.message_body(self.into_body_stream()).unwrap()
     }
 
     /// Creates a stream of serialized chunks.
     pub fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, E>> {
-        self.stream.map_ok(serialize_csv_row)
+        let crlf = self.excel_compatible;
+
+        let mut preamble = BytesMut::new();
+
+        if self.excel_compatible {
+            preamble.extend_from_slice(UTF8_BOM);
+        }
+
+        if self.separator_hint {
+            preamble.extend_from_slice(b"sep=,");
+            preamble.extend_from_slice(line_terminator(crlf));
+        }
+
+        let preamble = (!preamble.is_empty()).then(|| Ok(preamble.freeze()));
+
+        let on_error = self.on_error.map(|error_comment| -> ErrorTrailerFn {
+            Box::new(move |err| serialize_csv_comment(&error_comment(err), crlf))
+        });
+
+        let stream = CountingStream::new(self.stream, self.on_complete)
+            .map_ok(move |item| serialize_csv_row(item, crlf));
+
+        stream::iter(preamble).chain(ErrorTrailerStream::new(stream, on_error))
     }
 }
 
@@ -94,12 +241,28 @@ impl Csv<Infallible> {
     }
 }
 
-fn serialize_csv_row(item: impl Serialize) -> Bytes {
+fn line_terminator(crlf: bool) -> &'static [u8] {
+    if crlf {
+        b"\r\n"
+    } else {
+        b"\n"
+    }
+}
+
+fn serialize_csv_row(item: impl Serialize, crlf: bool) -> Bytes {
     let mut buf = BytesMut::new();
     let wrt = MutWriter(&mut buf);
 
+    let terminator = if crlf {
+        csv::Terminator::CRLF
+    } else {
+        csv::Terminator::Any(b'\n')
+    };
+
     // serialize CSV row to buffer
-    let mut csv_wrt = csv::Writer::from_writer(wrt);
+    let mut csv_wrt = csv::WriterBuilder::new()
+        .terminator(terminator)
+        .from_writer(wrt);
     csv_wrt.serialize(&item).unwrap();
     csv_wrt.flush().unwrap();
 
@@ -107,12 +270,23 @@ fn serialize_csv_row(item: impl Serialize) -> Bytes {
     buf.freeze()
 }
 
+fn serialize_csv_comment(comment: &str, crlf: bool) -> Bytes {
+    let mut buf = BytesMut::new();
+
+    for line in comment.lines() {
+        buf.extend_from_slice(b"# ");
+        buf.extend_from_slice(line.as_bytes());
+        buf.extend_from_slice(line_terminator(crlf));
+    }
+
+    buf.freeze()
+}
+
 #[cfg(test)]
 mod tests {
-    use std::error::Error as StdError;
+    use std::{cell::Cell, error::Error as StdError, io, rc::Rc};
 
-    use actix_web::body;
-    use futures_util::stream;
+    use actix_web::{body, http::header, test::TestRequest};
 
     use super::*;
 
@@ -140,4 +314,94 @@ mod tests {
 
         assert_eq!(body_bytes, EXP_BYTES);
     }
+
+    #[actix_web::test]
+    async fn reports_count_on_completion() {
+        let count = Rc::new(Cell::new(None));
+
+        let body = Csv::new_infallible(stream::iter([[1, 2], [3, 4], [5, 6]]))
+            .on_complete({
+                let count = Rc::clone(&count);
+                move |n| count.set(Some(n))
+            })
+            .into_body_stream();
+
+        assert!(count.get().is_none());
+        body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(count.get(), Some(3));
+
+        // not reported when stream errors part way through
+        let count = Rc::new(Cell::new(None));
+
+        let body = Csv::new(stream::iter([Ok([1, 2]), Err(io::Error::other("db gone"))]))
+            .on_complete({
+                let count = Rc::clone(&count);
+                move |n| count.set(Some(n))
+            })
+            .into_body_stream();
+
+        assert!(body::to_bytes(body).await.is_err());
+        assert!(count.get().is_none());
+    }
+
+    #[actix_web::test]
+    async fn writes_error_comment() {
+        let body = Csv::new(stream::iter([
+            Ok([1, 2]),
+            Err(io::Error::other("db gone\nretry later")),
+            Ok([3, 4]),
+        ]))
+        .error_comment(|err| format!("export failed: {err}"))
+        .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "1,2\n# export failed: db gone\n# retry later\n");
+    }
+
+    #[actix_web::test]
+    async fn excel_compatible() {
+        let body = Csv::new_infallible(stream::iter([["a", "b"], ["Zoë", "x\ny"]]))
+            .excel_compatible()
+            .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "\u{feff}a,b\r\nZoë,\"x\ny\"\r\n");
+
+        let body = Csv::new(stream::iter([Ok([1, 2]), Err(io::Error::other("db gone"))]))
+            .excel_compatible()
+            .separator_hint()
+            .error_comment(|err| err.to_string())
+            .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "\u{feff}sep=,\r\n1,2\r\n# db gone\r\n");
+
+        let body = Csv::new_infallible(stream::empty::<[u8; 0]>())
+            .separator_hint()
+            .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "sep=,\n");
+    }
+
+    #[actix_web::test]
+    async fn attachment_filename() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Csv::new_infallible(stream::iter([[1, 2]]))
+            .attachment("export.csv")
+            .into_responder()
+            .respond_to(&req);
+
+        assert_eq!(
+            res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"export.csv\"",
+        );
+
+        let res = Csv::new_infallible(stream::iter([[1, 2]]))
+            .into_responder()
+            .respond_to(&req);
+
+        assert!(!res.headers().contains_key(header::CONTENT_DISPOSITION));
+    }
 }
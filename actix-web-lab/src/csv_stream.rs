@@ -0,0 +1,402 @@
+//! Streaming CSV request body extractor.
+//!
+//! See [`CsvStream`] docs.
+
+use std::{
+    future::{ready, Ready},
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    dev::Payload,
+    error::PayloadError,
+    http::{header, StatusCode},
+    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+use bytes::BytesMut;
+use csv::StringRecord;
+use derive_more::{Display, Error};
+use futures_core::Stream;
+use futures_util::StreamExt as _;
+use serde::de::DeserializeOwned;
+
+/// Default CSV record size limit of 1MiB.
+pub const DEFAULT_CSV_RECORD_LIMIT: usize = 1_048_576;
+
+/// Errors that can occur when reading a CSV request body.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum CsvStreamError {
+    /// Content type is not a CSV type.
+    #[display("Content type is not CSV")]
+    ContentType,
+
+    /// A record exceeded the record size limit.
+    #[display("CSV record {record} is larger than {limit} bytes")]
+    RecordTooLong {
+        /// Record number (1-based, including the header record).
+        record: usize,
+
+        /// Record size limit.
+        limit: usize,
+    },
+
+    /// A record could not be parsed or deserialized.
+    #[display("Failed to deserialize CSV record {record}: {source}")]
+    Deserialize {
+        /// Record number (1-based, including the header record).
+        record: usize,
+
+        /// Deserialization error.
+        source: csv::Error,
+    },
+
+    /// Error reading request payload.
+    #[display("Error reading request payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for CsvStreamError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::RecordTooLong { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Deserialize { .. } => StatusCode::BAD_REQUEST,
+            Self::Payload(err) => err.status_code(),
+        }
+    }
+}
+
+/// Streaming CSV request body extractor.
+///
+/// The first record of the body is used as the header record and each subsequent record is
+/// deserialized into a `T`, matching fields by header name, without buffering the entire body.
+/// Quoted fields may contain line breaks. Blank lines are skipped and both `\n` and `\r\n` line
+/// endings are accepted.
+///
+/// Requests with a `Content-Type` header must use `text/csv` or `application/csv`; otherwise,
+/// extraction fails with a `415 Unsupported Media Type` response.
+///
+/// Use the `RECORD_LIMIT` const generic parameter to control the maximum size of a single record.
+/// The default limit is 1MiB. The stream yields an error and ends if a record exceeds the limit, a
+/// record cannot be deserialized, or the payload errors.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::extract::CsvStream;
+/// use futures_util::TryStreamExt as _;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Product {
+///     sku: String,
+///     price: u32,
+/// }
+///
+/// #[post("/products")]
+/// async fn import(mut products: CsvStream<Product>) -> actix_web::Result<impl Responder> {
+///     let mut total = 0;
+///
+///     while let Some(product) = products.try_next().await? {
+///         total += product.price;
+///     }
+///
+///     Ok(total.to_string())
+/// }
+/// ```
+pub struct CsvStream<T, const RECORD_LIMIT: usize = DEFAULT_CSV_RECORD_LIMIT> {
+    payload: Payload,
+    buf: BytesMut,
+
+    // number of bytes at the start of `buf` known not to contain a record terminator
+    scanned: usize,
+
+    // whether the scanned bytes end inside a quoted field
+    in_quotes: bool,
+
+    headers: Option<StringRecord>,
+
+    // number of records consumed so far, including the header record
+    record: usize,
+
+    eof: bool,
+    done: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T, const RECORD_LIMIT: usize> CsvStream<T, RECORD_LIMIT> {
+    fn new(payload: Payload) -> Self {
+        Self {
+            payload,
+            buf: BytesMut::new(),
+            scanned: 0,
+            in_quotes: false,
+            headers: None,
+            record: 0,
+            eof: false,
+            done: false,
+            _item: PhantomData,
+        }
+    }
+
+    /// Returns the header record, once it has been read.
+    pub fn headers(&self) -> Option<&StringRecord> {
+        self.headers.as_ref()
+    }
+
+    /// Returns the position of the next record terminator outside of a quoted field, if any.
+    fn find_record_end(&mut self) -> Option<usize> {
+        for idx in self.scanned..self.buf.len() {
+            match self.buf[idx] {
+                b'"' => self.in_quotes = !self.in_quotes,
+                b'\n' if !self.in_quotes => return Some(idx),
+                _ => {}
+            }
+        }
+
+        self.scanned = self.buf.len();
+        None
+    }
+
+    /// Parses a single record, not including its line break.
+    fn parse_record(&self, record: &[u8]) -> Result<StringRecord, CsvStreamError> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(record);
+
+        let mut fields = StringRecord::new();
+
+        rdr.read_record(&mut fields)
+            .map_err(|source| CsvStreamError::Deserialize {
+                record: self.record,
+                source,
+            })?;
+
+        // position is relative to this record alone so would be misleading in errors
+        fields.set_position(None);
+
+        Ok(fields)
+    }
+}
+
+impl<T, const RECORD_LIMIT: usize> std::fmt::Debug for CsvStream<T, RECORD_LIMIT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CsvStream")
+            .field("item_type", &std::any::type_name::<T>())
+            .field("record_limit", &RECORD_LIMIT)
+            .field("headers", &self.headers)
+            .field("record", &self.record)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned, const RECORD_LIMIT: usize> Stream for CsvStream<T, RECORD_LIMIT> {
+    type Item = Result<T, CsvStreamError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let record = match this.find_record_end() {
+                Some(idx) => {
+                    let record = this.buf.split_to(idx + 1);
+                    this.scanned = 0;
+                    record
+                }
+
+                None if this.buf.len() > RECORD_LIMIT => {
+                    this.done = true;
+
+                    return Poll::Ready(Some(Err(CsvStreamError::RecordTooLong {
+                        record: this.record + 1,
+                        limit: RECORD_LIMIT,
+                    })));
+                }
+
+                // final record without trailing line break
+                None if this.eof => {
+                    this.done = true;
+                    this.scanned = 0;
+                    this.buf.split()
+                }
+
+                None => {
+                    match futures_util::ready!(this.payload.poll_next_unpin(cx)) {
+                        Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+
+                        Some(Err(err)) => {
+                            this.done = true;
+                            return Poll::Ready(Some(Err(CsvStreamError::Payload(err))));
+                        }
+
+                        None => this.eof = true,
+                    }
+
+                    continue;
+                }
+            };
+
+            let record = record.trim_ascii_end();
+
+            if record.trim_ascii().is_empty() {
+                continue;
+            }
+
+            this.record += 1;
+
+            if record.len() > RECORD_LIMIT {
+                this.done = true;
+
+                return Poll::Ready(Some(Err(CsvStreamError::RecordTooLong {
+                    record: this.record,
+                    limit: RECORD_LIMIT,
+                })));
+            }
+
+            let res = this
+                .parse_record(record)
+                .and_then(|fields| match &this.headers {
+                    Some(headers) => {
+                        fields
+                            .deserialize(Some(headers))
+                            .map(Some)
+                            .map_err(|source| CsvStreamError::Deserialize {
+                                record: this.record,
+                                source,
+                            })
+                    }
+
+                    None => {
+                        this.headers = Some(fields);
+                        Ok(None)
+                    }
+                });
+
+            match res {
+                Ok(Some(item)) => return Poll::Ready(Some(Ok(item))),
+                Ok(None) => continue,
+
+                Err(err) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned, const RECORD_LIMIT: usize> FromRequest for CsvStream<T, RECORD_LIMIT> {
+    type Error = CsvStreamError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if req.headers().contains_key(header::CONTENT_TYPE) {
+            let is_csv = req.mime_type().ok().flatten().is_some_and(|mime| {
+                mime.subtype() == mime::CSV
+                    && (mime.type_() == mime::TEXT || mime.type_() == mime::APPLICATION)
+            });
+
+            if !is_csv {
+                return ready(Err(CsvStreamError::ContentType));
+            }
+        }
+
+        ready(Ok(Self::new(payload.take())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use futures_util::StreamExt as _;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        name: String,
+        qty: u32,
+    }
+
+    fn row(name: &str, qty: u32) -> Row {
+        Row {
+            name: name.to_owned(),
+            qty,
+        }
+    }
+
+    async fn collect<const RECORD_LIMIT: usize>(
+        body: &'static str,
+    ) -> Vec<Result<Row, CsvStreamError>> {
+        let (req, mut pl) = TestRequest::default().set_payload(body).to_http_parts();
+
+        CsvStream::<Row, RECORD_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .collect()
+            .await
+    }
+
+    #[actix_web::test]
+    async fn splits_records() {
+        let items = collect::<DEFAULT_CSV_RECORD_LIMIT>(
+            "qty,name\r\n1,a\n\n2,\"multi\nline, \"\"quoted\"\"\"\r\n3,c",
+        )
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            items,
+            [row("a", 1), row("multi\nline, \"quoted\"", 2), row("c", 3)],
+        );
+
+        assert!(collect::<DEFAULT_CSV_RECORD_LIMIT>("").await.is_empty());
+        assert!(collect::<DEFAULT_CSV_RECORD_LIMIT>("name,qty\n")
+            .await
+            .is_empty());
+    }
+
+    #[actix_web::test]
+    async fn errors_end_stream() {
+        let items = collect::<DEFAULT_CSV_RECORD_LIMIT>("name,qty\na,1\nb,nope\nc,3\n").await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1],
+            Err(CsvStreamError::Deserialize { record: 3, .. })
+        ));
+
+        let items = collect::<10>("name,qty\na,1\nthis record is too long,2\n").await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1],
+            Err(CsvStreamError::RecordTooLong {
+                record: 3,
+                limit: 10
+            })
+        ));
+    }
+
+    #[actix_web::test]
+    async fn checks_content_type() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .to_http_parts();
+        let res = CsvStream::<Row>::from_request(&req, &mut pl).await;
+        assert!(matches!(res, Err(CsvStreamError::ContentType)));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/csv; charset=utf-8"))
+            .to_http_parts();
+        assert!(CsvStream::<Row>::from_request(&req, &mut pl).await.is_ok());
+    }
+}
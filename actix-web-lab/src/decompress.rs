@@ -0,0 +1,113 @@
+//! Request body decompression shared by body extractors.
+
+use actix_web::{dev::Payload, http::header, HttpRequest};
+
+/// Takes the request payload, decompressing it according to its `Content-Encoding` header.
+///
+/// Decompression is only performed when the `decompress` crate feature is enabled. Otherwise, the
+/// payload is returned as-is.
+pub(crate) fn take_payload(req: &HttpRequest, payload: &mut Payload) -> Payload {
+    #[cfg(feature = "decompress")]
+    {
+        use std::pin::Pin;
+
+        use actix_http::encoding::Decoder;
+        use futures_core::Stream;
+
+        type BoxedPayloadStream =
+            Pin<Box<dyn Stream<Item = Result<bytes::Bytes, actix_web::error::PayloadError>>>>;
+
+        if is_encoded(req) {
+            let decoder = Decoder::from_headers(payload.take(), req.headers());
+            return Payload::from(Box::pin(decoder) as BoxedPayloadStream);
+        }
+    }
+
+    #[cfg(not(feature = "decompress"))]
+    let _ = req;
+
+    payload.take()
+}
+
+/// Returns true if the request body will be decompressed before being read by extractors.
+///
+/// When true, the `Content-Length` header describes the compressed size, so it cannot be compared
+/// against payload size limits, which are applied to the decompressed size.
+pub(crate) fn is_encoded(req: &HttpRequest) -> bool {
+    cfg!(feature = "decompress")
+        && req
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .is_some_and(|enc| !enc.as_bytes().eq_ignore_ascii_case(b"identity"))
+}
+
+#[cfg(all(test, feature = "decompress"))]
+mod tests {
+    use std::io::Write as _;
+
+    use actix_web::{test::TestRequest, FromRequest as _};
+    use serde::Deserialize;
+
+    use crate::extract::{Bytes, Json, UrlEncodedForm, DEFAULT_JSON_LIMIT};
+
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        name: String,
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(body).unwrap();
+        enc.finish().unwrap()
+    }
+
+    #[actix_web::test]
+    async fn decompresses_bodies() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("content-type", "application/json"))
+            .insert_header(("content-encoding", "gzip"))
+            .set_payload(gzip(br#"{"name":"gzip"}"#))
+            .to_http_parts();
+        let item = Json::<Item>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(item.name, "gzip");
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("content-type", "application/x-www-form-urlencoded"))
+            .insert_header(("content-encoding", "zstd"))
+            .set_payload(zstd::encode_all(&b"name=zstd"[..], 0).unwrap())
+            .to_http_parts();
+        let item = UrlEncodedForm::<Item>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(item.name, "zstd");
+
+        let mut compressed = Vec::new();
+        brotli::BrotliCompress(
+            &mut &b"hello world"[..],
+            &mut compressed,
+            &Default::default(),
+        )
+        .unwrap();
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("content-encoding", "br"))
+            .set_payload(compressed)
+            .to_http_parts();
+        let body = Bytes::<DEFAULT_JSON_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), "hello world");
+    }
+
+    #[actix_web::test]
+    async fn limit_applies_to_decompressed_size() {
+        let body = gzip(&[b'a'; 1024]);
+        assert!(body.len() < 64);
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("content-encoding", "gzip"))
+            .set_payload(body)
+            .to_http_parts();
+        assert!(Bytes::<64>::from_request(&req, &mut pl).await.is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! File download responder.
+//!
+//! See [`Download`] docs.
+
+use std::{
+    fmt, io,
+    io::SeekFrom,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_web::{
+    body::{BodyStream, SizedStream},
+    http::{
+        header::{self, ContentDisposition},
+        Method, StatusCode,
+    },
+    HttpRequest, HttpResponse, Responder,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use mime::Mime;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// Size of chunks read from the underlying reader.
+const CHUNK_SIZE: usize = 8_192;
+
+/// Responder that streams an `AsyncRead` to the client as a file attachment.
+///
+/// Sets the `Content-Type` and `Content-Disposition: attachment` headers. If the length of the
+/// content is known ahead of time, set it using [`content_length()`](Self::content_length) so that
+/// the `Content-Length` header is sent and clients can show download progress.
+///
+/// When the reader is also `AsyncSeek` and its length is known, [`seekable()`](Self::seekable)
+/// enables single-part `Range` request support, allowing clients to resume interrupted downloads.
+/// Multi-part range requests are answered with the full content.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::respond::Download;
+///
+/// #[get("/report")]
+/// async fn report() -> actix_web::Result<impl Responder> {
+///     let file = tokio::fs::File::open("report.pdf").await?;
+///     let len = file.metadata().await?.len();
+///
+///     Ok(
+///         Download::from_reader(file, "report.pdf", mime::APPLICATION_PDF)
+///             .content_length(len)
+///             .seekable(),
+///     )
+/// }
+/// ```
+pub struct Download<R> {
+    reader: R,
+    filename: String,
+    content_type: Mime,
+    len: Option<u64>,
+    seek: Option<SeekFns<R>>,
+}
+
+impl<R: AsyncRead> Download<R> {
+    /// Constructs a new download responder from an async reader.
+    pub fn from_reader(reader: R, filename: impl Into<String>, content_type: Mime) -> Self {
+        Self {
+            reader,
+            filename: filename.into(),
+            content_type,
+            len: None,
+            seek: None,
+        }
+    }
+
+    /// Sets the known length of the reader's content, in bytes.
+    ///
+    /// The reader is expected to yield exactly this many bytes.
+    pub fn content_length(mut self, len: u64) -> Self {
+        self.len = Some(len);
+        self
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek> Download<R> {
+    /// Enables `Range` request support.
+    ///
+    /// Ranges are only served when the content length is also known. The reader is assumed to be
+    /// positioned at the start of its content.
+    pub fn seekable(mut self) -> Self {
+        self.seek = Some(SeekFns {
+            start: R::start_seek,
+            complete: R::poll_complete,
+        });
+        self
+    }
+}
+
+impl<R> fmt::Debug for Download<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Download")
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("len", &self.len)
+            .field("seekable", &self.seek.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: AsyncRead + 'static> Responder for Download<R> {
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut res = HttpResponse::Ok();
+        res.content_type(self.content_type)
+            .insert_header(ContentDisposition::attachment(self.filename));
+
+        let (len, seek) = match (self.len, self.seek) {
+            (Some(len), Some(seek)) => (len, seek),
+
+            (Some(len), None) => {
+                return res.body(SizedStream::new(
+                    len,
+                    ReaderStream::new(self.reader, None, Some(len)),
+                ))
+            }
+
+            (None, _) => {
+                return res.body(BodyStream::new(ReaderStream::new(self.reader, None, None)))
+            }
+        };
+
+        res.insert_header((header::ACCEPT_RANGES, "bytes"));
+
+        let range = req
+            .headers()
+            .get(header::RANGE)
+            .filter(|_| req.method() == Method::GET || req.method() == Method::HEAD)
+            .and_then(|hdr| hdr.to_str().ok())
+            .map_or(ByteRange::Full, |hdr| ByteRange::parse(hdr, len));
+
+        match range {
+            ByteRange::Full => res.body(SizedStream::new(
+                len,
+                ReaderStream::new(self.reader, None, Some(len)),
+            )),
+
+            ByteRange::Partial { start, end } => {
+                let part_len = end - start + 1;
+
+                res.status(StatusCode::PARTIAL_CONTENT)
+                    .insert_header((header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}")))
+                    .body(SizedStream::new(
+                        part_len,
+                        ReaderStream::new(self.reader, Some((seek, start)), Some(part_len)),
+                    ))
+            }
+
+            ByteRange::Unsatisfiable => HttpResponse::RangeNotSatisfiable()
+                .insert_header((header::CONTENT_RANGE, format!("bytes */{len}")))
+                .finish(),
+        }
+    }
+}
+
+/// Type-erased `AsyncSeek` methods, allowing seeking to be opted into without trait bounds on the
+/// responder itself.
+struct SeekFns<R> {
+    start: fn(Pin<&mut R>, SeekFrom) -> io::Result<()>,
+    complete: fn(Pin<&mut R>, &mut Context<'_>) -> Poll<io::Result<u64>>,
+}
+
+impl<R> Clone for SeekFns<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for SeekFns<R> {}
+
+/// Outcome of evaluating a `Range` header against a known content length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ByteRange {
+    /// Range header was absent, invalid, or unsupported; serve the full content.
+    Full,
+
+    /// Serve the inclusive byte range.
+    Partial { start: u64, end: u64 },
+
+    /// Range cannot be satisfied for this content length.
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    pub(crate) fn parse(header: &str, len: u64) -> Self {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+
+        // multi-part ranges are not supported
+        if spec.contains(',') {
+            return Self::Full;
+        }
+
+        match parse_range_spec(spec, len) {
+            None => Self::Full,
+            Some(None) => Self::Unsatisfiable,
+            Some(Some((start, end))) => Self::Partial { start, end },
+        }
+    }
+}
+
+/// Outcome of evaluating a `Range` header, which may contain multiple ranges, against a known
+/// content length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ByteRanges {
+    /// Range header was absent or invalid; serve the full content.
+    Full,
+
+    /// Serve the inclusive byte ranges, in the requested order.
+    Partial(Vec<(u64, u64)>),
+
+    /// None of the ranges can be satisfied for this content length.
+    Unsatisfiable,
+}
+
+impl ByteRanges {
+    pub(crate) fn parse(header: &str, len: u64) -> Self {
+        let Some(specs) = header.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+
+        let mut ranges = Vec::new();
+
+        for spec in specs.split(',').filter(|spec| !spec.trim().is_empty()) {
+            match parse_range_spec(spec, len) {
+                None => return Self::Full,
+                // unsatisfiable ranges are ignored if others in the set are satisfiable
+                Some(None) => {}
+                Some(Some(range)) => ranges.push(range),
+            }
+        }
+
+        if ranges.is_empty() {
+            Self::Unsatisfiable
+        } else {
+            Self::Partial(ranges)
+        }
+    }
+}
+
+/// Parses a single range spec (e.g., `0-499`, `500-`, or `-500`).
+///
+/// Returns `None` if the spec is invalid and `Some(None)` if it cannot be satisfied.
+fn parse_range_spec(spec: &str, len: u64) -> Option<Option<(u64, u64)>> {
+    let (start, end) = spec.trim().split_once('-')?;
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        // suffix range: last N bytes
+        ("", suffix) => match suffix.parse::<u64>().ok()? {
+            0 => return Some(None),
+            suffix => (len.saturating_sub(suffix), len.saturating_sub(1)),
+        },
+
+        (start, "") => (start.parse::<u64>().ok()?, len.saturating_sub(1)),
+
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, end.min(len.saturating_sub(1))),
+            _ => return None,
+        },
+    };
+
+    if len == 0 || start >= len {
+        return Some(None);
+    }
+
+    Some(Some((start, end)))
+}
+
+pin_project! {
+    /// Streams chunks from a reader, optionally seeking first and limiting the number of bytes.
+    struct ReaderStream<R> {
+        #[pin]
+        reader: R,
+        seek: Option<(SeekFns<R>, u64)>,
+        seek_started: bool,
+        remaining: Option<u64>,
+        buf: Vec<u8>,
+    }
+}
+
+impl<R> ReaderStream<R> {
+    fn new(reader: R, seek: Option<(SeekFns<R>, u64)>, remaining: Option<u64>) -> Self {
+        Self {
+            reader,
+            seek,
+            seek_started: false,
+            remaining,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<R: AsyncRead> Stream for ReaderStream<R> {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if let Some((fns, pos)) = *this.seek {
+            if !*this.seek_started {
+                (fns.start)(this.reader.as_mut(), SeekFrom::Start(pos))?;
+                *this.seek_started = true;
+            }
+
+            ready!((fns.complete)(this.reader.as_mut(), cx))?;
+            *this.seek = None;
+        }
+
+        let cap = match *this.remaining {
+            Some(0) => return Poll::Ready(None),
+            Some(remaining) => remaining.min(CHUNK_SIZE as u64) as usize,
+            None => CHUNK_SIZE,
+        };
+
+        this.buf.resize(cap, 0);
+        let mut buf = ReadBuf::new(&mut this.buf[..cap]);
+        ready!(this.reader.poll_read(cx, &mut buf))?;
+
+        let chunk = buf.filled();
+
+        if chunk.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        if let Some(remaining) = this.remaining {
+            *remaining -= chunk.len() as u64;
+        }
+
+        Poll::Ready(Some(Ok(Bytes::copy_from_slice(chunk))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use actix_web::{body, test::TestRequest};
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789";
+
+    async fn respond(
+        req: TestRequest,
+        dl: Download<Cursor<&'static [u8]>>,
+    ) -> (HttpResponse<()>, Bytes) {
+        let res = dl.respond_to(&req.to_http_request());
+        let (res, body) = res.into_parts();
+        let body = body::to_bytes(body).await.unwrap();
+        (res, body)
+    }
+
+    fn download() -> Download<Cursor<&'static [u8]>> {
+        Download::from_reader(Cursor::new(DATA), "digits.txt", mime::TEXT_PLAIN)
+    }
+
+    #[test]
+    fn parse_range() {
+        assert_eq!(
+            ByteRange::parse("bytes=0-4", 10),
+            ByteRange::Partial { start: 0, end: 4 }
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=5-", 10),
+            ByteRange::Partial { start: 5, end: 9 }
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=-3", 10),
+            ByteRange::Partial { start: 7, end: 9 }
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=-30", 10),
+            ByteRange::Partial { start: 0, end: 9 }
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=8-100", 10),
+            ByteRange::Partial { start: 8, end: 9 }
+        );
+
+        assert_eq!(ByteRange::parse("bytes=10-", 10), ByteRange::Unsatisfiable);
+        assert_eq!(ByteRange::parse("bytes=-0", 10), ByteRange::Unsatisfiable);
+        assert_eq!(ByteRange::parse("bytes=0-", 0), ByteRange::Unsatisfiable);
+
+        assert_eq!(ByteRange::parse("bytes=4-2", 10), ByteRange::Full);
+        assert_eq!(ByteRange::parse("bytes=0-1,4-5", 10), ByteRange::Full);
+        assert_eq!(ByteRange::parse("items=0-1", 10), ByteRange::Full);
+        assert_eq!(ByteRange::parse("bytes=a-b", 10), ByteRange::Full);
+    }
+
+    #[test]
+    fn parse_multiple_ranges() {
+        assert_eq!(
+            ByteRanges::parse("bytes=0-1, 4-5,-2", 10),
+            ByteRanges::Partial(vec![(0, 1), (4, 5), (8, 9)])
+        );
+        assert_eq!(
+            ByteRanges::parse("bytes=0-1,20-30", 10),
+            ByteRanges::Partial(vec![(0, 1)])
+        );
+        assert_eq!(
+            ByteRanges::parse("bytes=20-,30-40", 10),
+            ByteRanges::Unsatisfiable
+        );
+        assert_eq!(ByteRanges::parse("bytes=0-1,a-b", 10), ByteRanges::Full);
+        assert_eq!(ByteRanges::parse("items=0-1,2-3", 10), ByteRanges::Full);
+    }
+
+    #[actix_web::test]
+    async fn unknown_length() {
+        let (res, body) = respond(TestRequest::default(), download()).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain"
+        );
+        assert_eq!(
+            res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            r#"attachment; filename="digits.txt""#,
+        );
+        assert!(!res.headers().contains_key(header::ACCEPT_RANGES));
+        assert_eq!(body, DATA);
+    }
+
+    #[actix_web::test]
+    async fn known_length_ignores_range_when_not_seekable() {
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=0-1"));
+        let dl = download().content_length(DATA.len() as u64);
+        let (res, body) = respond(req, dl).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body, DATA);
+    }
+
+    #[actix_web::test]
+    async fn range_requests() {
+        let dl = || download().content_length(DATA.len() as u64).seekable();
+
+        let (res, body) = respond(TestRequest::default(), dl()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+        assert_eq!(body, DATA);
+
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=3-5"));
+        let (res, body) = respond(req, dl()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 3-5/10"
+        );
+        assert_eq!(body, "345");
+
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=-2"));
+        let (_res, body) = respond(req, dl()).await;
+        assert_eq!(body, "89");
+
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=20-"));
+        let (res, body) = respond(req, dl()).await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */10"
+        );
+        assert!(body.is_empty());
+
+        let req = TestRequest::post().insert_header((header::RANGE, "bytes=3-5"));
+        let (res, body) = respond(req, dl()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body, DATA);
+    }
+}
@@ -0,0 +1,627 @@
+//! Encrypted body extractor and responder.
+//!
+//! See [`Encrypted`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Future},
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_http::{error::PayloadError, BoxedPayloadStream};
+use actix_web::{
+    body::{BodySize, BoxBody, MessageBody},
+    dev,
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        StatusCode,
+    },
+    Error, FromRequest, HttpMessage as _, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use base64::prelude::*;
+use bytes::{Bytes, BytesMut};
+use derive_more::Display;
+use futures_core::future::LocalBoxFuture;
+use futures_util::{stream, StreamExt as _};
+use pin_project_lite::pin_project;
+
+use crate::{
+    extract::DEFAULT_BODY_LIMIT,
+    lab_config::{LabConfig, PayloadLength},
+    BoxError,
+};
+
+/// Define a scheme for decrypting request bodies and encrypting response bodies with an AEAD
+/// cipher.
+///
+/// Bodies are encrypted as a whole. The nonce used for each message is sent base64url-encoded
+/// (without padding) in the [`NONCE_HEADER`](Self::NONCE_HEADER) header, which defaults to
+/// `Encryption-Nonce`.
+///
+/// # Bring Your Own Crypto
+///
+/// It is up to the implementor to ensure that best security practices are being followed when
+/// implementing this trait; in particular, nonces must never be reused with the same key. There is
+/// no inherent preference for certain crypto ecosystems though the example shown here uses types
+/// from [RustCrypto](https://github.com/RustCrypto).
+///
+/// # Examples
+///
+/// This example implementation uses AES-256-GCM with a key stored in app data. Keys could also be
+/// looked up asynchronously (e.g., from a secrets manager) in [`init`](Self::init).
+/// ```
+/// use actix_web::{error, Error, HttpRequest};
+/// use actix_web_lab::extract::EncryptionScheme;
+/// use aes_gcm::{
+///     aead::{Aead, AeadCore as _, KeyInit as _, OsRng},
+///     Aes256Gcm, Nonce,
+/// };
+///
+/// struct InternalAead {
+///     cipher: Aes256Gcm,
+/// }
+///
+/// impl EncryptionScheme for InternalAead {
+///     type Error = Error;
+///
+///     async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+///         // acquire encryption key
+///         let key = req.app_data::<[u8; 32]>().unwrap();
+///
+///         let cipher = Aes256Gcm::new_from_slice(&key[..]).unwrap();
+///         Ok(InternalAead { cipher })
+///     }
+///
+///     fn decrypt(&self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+///         if nonce.len() != 12 {
+///             return Err(error::ErrorBadRequest("invalid nonce length"));
+///         }
+///
+///         self.cipher
+///             .decrypt(Nonce::from_slice(nonce), ciphertext)
+///             .map_err(|_| error::ErrorBadRequest("decryption failed"))
+///     }
+///
+///     fn encrypt(&self, nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+///         self.cipher
+///             .encrypt(Nonce::from_slice(nonce), plaintext)
+///             .map_err(|_| error::ErrorInternalServerError("encryption failed"))
+///     }
+///
+///     fn generate_nonce(&self) -> Vec<u8> {
+///         Aes256Gcm::generate_nonce(&mut OsRng).to_vec()
+///     }
+/// }
+/// ```
+pub trait EncryptionScheme: Sized {
+    /// Name of the header carrying the base64url-encoded nonce.
+    const NONCE_HEADER: HeaderName = HeaderName::from_static("encryption-nonce");
+
+    /// Maximum size of a response body that will be buffered for encryption.
+    ///
+    /// Responses with larger bodies are aborted with an error. Defaults to [`DEFAULT_BODY_LIMIT`].
+    const RESPONSE_LIMIT: usize = DEFAULT_BODY_LIMIT;
+
+    /// Error type used by all trait methods to signal missing keys or cryptographic failures.
+    ///
+    /// Must be convertible to an error response; i.e., implements [`ResponseError`].
+    type Error: Into<Error>;
+
+    /// Initialize encryption scheme for incoming request.
+    ///
+    /// Typically used to look up the key for this request, possibly based on request headers.
+    fn init(req: &HttpRequest) -> impl Future<Output = Result<Self, Self::Error>>;
+
+    /// Decrypts and authenticates a complete request body.
+    fn decrypt(&self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Encrypts a complete response body.
+    fn encrypt(&self, nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Generates a fresh nonce for encrypting a response.
+    fn generate_nonce(&self) -> Vec<u8>;
+}
+
+/// Extractor wrapper and responder for AEAD-encrypted bodies.
+///
+/// As an extractor, reads the full request body, decrypts it using the nonce from the request's
+/// [nonce header](EncryptionScheme::NONCE_HEADER), and then runs the inner extractor `T` against
+/// the plaintext. The payload size limit is [`DEFAULT_BODY_LIMIT`] unless configured otherwise
+/// using [`LabConfig`](crate::extract::LabConfig). Strict `Content-Length` checks are applied to the
+/// encrypted payload; inner extractors are told the length of the plaintext instead.
+///
+/// As a responder, encrypts the body of the inner responder with a freshly generated nonce, which
+/// is sent in the response's nonce header. The inner body is buffered in full, up to the scheme's
+/// [response limit](EncryptionScheme::RESPONSE_LIMIT). Use [`into_parts()`](Self::into_parts) on an extracted
+/// value to obtain a scheme instance with which to encrypt the response.
+///
+/// # Examples
+/// ```
+/// # use actix_web::{error, Error, HttpRequest};
+/// # use actix_web_lab::extract::EncryptionScheme;
+/// # struct InternalAead;
+/// # impl EncryptionScheme for InternalAead {
+/// #     type Error = Error;
+/// #     async fn init(req: &HttpRequest) -> Result<Self, Self::Error> { Ok(Self) }
+/// #     fn decrypt(&self, nonce: &[u8], ct: &[u8]) -> Result<Vec<u8>, Self::Error> { Ok(ct.to_vec()) }
+/// #     fn encrypt(&self, nonce: &[u8], pt: &[u8]) -> Result<Vec<u8>, Self::Error> { Ok(pt.to_vec()) }
+/// #     fn generate_nonce(&self) -> Vec<u8> { vec![0; 12] }
+/// # }
+/// use actix_web::{post, web, Responder};
+/// use actix_web_lab::extract::Encrypted;
+///
+/// #[post("/internal/echo")]
+/// async fn echo(body: Encrypted<web::Json<serde_json::Value>, InternalAead>) -> impl Responder {
+///     let (body, scheme) = body.into_parts();
+///     Encrypted::new(body, scheme)
+/// }
+/// ```
+pub struct Encrypted<T, S> {
+    inner: T,
+    scheme: S,
+}
+
+impl<T, S> Encrypted<T, S> {
+    /// Constructs new encrypted responder from an inner responder and scheme instance.
+    pub fn new(inner: T, scheme: S) -> Self {
+        Self { inner, scheme }
+    }
+
+    /// Returns tuple containing inner value and scheme instance.
+    pub fn into_parts(self) -> (T, S) {
+        (self.inner, self.scheme)
+    }
+
+    /// Returns inner value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, S> std::ops::Deref for Encrypted<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for Encrypted<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encrypted")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors that can occur when extracting an [`Encrypted`] body.
+#[derive(Debug, Display)]
+#[non_exhaustive]
+pub enum EncryptedError {
+    /// Nonce header is missing.
+    #[display("Encryption nonce header is missing")]
+    MissingNonce,
+
+    /// Nonce header is not valid base64url.
+    #[display("Encryption nonce header is not valid base64url")]
+    InvalidNonce,
+
+    /// Payload was larger than limit.
+    #[display("Encrypted payload is larger than allowed (limit: {limit} bytes)")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload error.
+    #[display("Error that occurred while reading payload: {_0}")]
+    Payload(PayloadError),
+
+    /// Scheme initialization or decryption failed.
+    #[display("Decryption error: {_0}")]
+    Decrypt(Error),
+
+    /// Inner extractor failed.
+    #[display("Inner extractor error: {_0}")]
+    Extractor(Error),
+}
+
+impl ResponseError for EncryptedError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Decrypt(err) | Self::Extractor(err) => err.as_response_error().status_code(),
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl<T, S> FromRequest for Encrypted<T, S>
+where
+    T: FromRequest + 'static,
+    S: EncryptionScheme + 'static,
+{
+    type Error = EncryptedError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        LabConfig::apply_to_payload(req, payload);
+
+        let req = req.clone();
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let nonce = req
+                .headers()
+                .get(S::NONCE_HEADER)
+                .ok_or(EncryptedError::MissingNonce)?;
+            let nonce = BASE64_URL_SAFE_NO_PAD
+                .decode(nonce.as_bytes())
+                .map_err(|_| EncryptedError::InvalidNonce)?;
+
+            let limit = LabConfig::resolve_limit(
+                &req,
+                DEFAULT_BODY_LIMIT,
+                DEFAULT_BODY_LIMIT,
+                LabConfig::BODY,
+            );
+
+            // reject before reading any of the body when the declared length is already too large
+            if LabConfig::payload_length(&req).is_some_and(|len| len > limit) {
+                return Err(EncryptedError::Overflow { limit });
+            }
+
+            let scheme = S::init(&req)
+                .await
+                .map_err(|err| EncryptedError::Decrypt(err.into()))?;
+
+            let mut ciphertext = BytesMut::new();
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(EncryptedError::Payload)?;
+
+                if ciphertext.len() + chunk.len() > limit {
+                    return Err(EncryptedError::Overflow { limit });
+                }
+
+                ciphertext.extend_from_slice(&chunk);
+            }
+
+            let plaintext = scheme
+                .decrypt(&nonce, &ciphertext)
+                .map_err(|err| EncryptedError::Decrypt(err.into()))?;
+
+            // the request's Content-Length describes the ciphertext, not the plaintext
+            let prev_len = req.extensions_mut().insert(PayloadLength(plaintext.len()));
+
+            let plaintext: BoxedPayloadStream =
+                Box::pin(stream::once(ready(Ok(Bytes::from(plaintext)))));
+            let mut payload = dev::Payload::from(plaintext);
+
+            let inner = T::from_request(&req, &mut payload).await;
+
+            match prev_len {
+                Some(len) => req.extensions_mut().insert(len),
+                None => req.extensions_mut().remove::<PayloadLength>(),
+            };
+
+            let inner = inner.map_err(|err| EncryptedError::Extractor(err.into()))?;
+
+            Ok(Encrypted { inner, scheme })
+        })
+    }
+}
+
+impl<R, S> Responder for Encrypted<R, S>
+where
+    R: Responder,
+    R::Body: 'static,
+    S: EncryptionScheme + 'static,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let nonce = self.scheme.generate_nonce();
+        let nonce_header = HeaderValue::try_from(BASE64_URL_SAFE_NO_PAD.encode(&nonce))
+            .expect("base64url is a valid header value");
+
+        let mut res = self.inner.respond_to(req).map_body(|head, body| {
+            // ciphertext length differs from plaintext length
+            head.headers.remove(header::CONTENT_LENGTH);
+
+            EncryptBody {
+                body,
+                buf: BytesMut::new(),
+                scheme: Some(self.scheme),
+                nonce,
+            }
+            .boxed()
+        });
+
+        res.headers_mut().insert(S::NONCE_HEADER, nonce_header);
+
+        res
+    }
+}
+
+pin_project! {
+    /// Buffers the inner body then yields it encrypted as a single chunk.
+    struct EncryptBody<B, S> {
+        #[pin]
+        body: B,
+        buf: BytesMut,
+        scheme: Option<S>,
+        nonce: Vec<u8>,
+    }
+}
+
+impl<B, S> MessageBody for EncryptBody<B, S>
+where
+    B: MessageBody,
+    S: EncryptionScheme,
+{
+    type Error = BoxError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            if this.scheme.is_none() {
+                return Poll::Ready(None);
+            }
+
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    if this.buf.len() + chunk.len() > S::RESPONSE_LIMIT {
+                        this.scheme.take();
+
+                        return Poll::Ready(Some(Err(io::Error::other(format!(
+                            "response body is larger than encryption limit of {} bytes",
+                            S::RESPONSE_LIMIT,
+                        ))
+                        .into())));
+                    }
+
+                    this.buf.extend_from_slice(&chunk);
+                }
+
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+
+                None => {
+                    let scheme = this.scheme.take().unwrap();
+
+                    let ciphertext = scheme
+                        .encrypt(this.nonce, this.buf)
+                        .map_err(|err| -> BoxError { Box::new(err.into()) })?;
+
+                    return Poll::Ready(Some(Ok(Bytes::from(ciphertext))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body, error, test::TestRequest, web};
+    use aes_gcm::{
+        aead::{Aead, AeadCore as _, KeyInit as _, OsRng},
+        Aes256Gcm, Nonce,
+    };
+
+    use super::*;
+    use crate::{extract::Json, header::ContentLength};
+
+    struct TestAead {
+        cipher: Aes256Gcm,
+    }
+
+    impl EncryptionScheme for TestAead {
+        type Error = Error;
+
+        async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+            let key = req
+                .app_data::<[u8; 32]>()
+                .ok_or_else(|| error::ErrorInternalServerError("missing key"))?;
+
+            Ok(TestAead {
+                cipher: Aes256Gcm::new_from_slice(&key[..]).unwrap(),
+            })
+        }
+
+        fn decrypt(&self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            if nonce.len() != 12 {
+                return Err(error::ErrorBadRequest("invalid nonce length"));
+            }
+
+            self.cipher
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| error::ErrorBadRequest("decryption failed"))
+        }
+
+        fn encrypt(&self, nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            self.cipher
+                .encrypt(Nonce::from_slice(nonce), plaintext)
+                .map_err(|_| error::ErrorInternalServerError("encryption failed"))
+        }
+
+        fn generate_nonce(&self) -> Vec<u8> {
+            Aes256Gcm::generate_nonce(&mut OsRng).to_vec()
+        }
+    }
+
+    const KEY: [u8; 32] = [42; 32];
+
+    fn encrypt(plaintext: &[u8]) -> (String, Vec<u8>) {
+        let cipher = Aes256Gcm::new_from_slice(&KEY).unwrap();
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext).unwrap();
+        (BASE64_URL_SAFE_NO_PAD.encode(nonce), ciphertext)
+    }
+
+    #[actix_web::test]
+    async fn round_trip() {
+        let (nonce, ciphertext) = encrypt(b"hello");
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .insert_header(("encryption-nonce", nonce))
+            .set_payload(ciphertext)
+            .to_http_parts();
+
+        let body = Encrypted::<String, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(*body, "hello");
+
+        let (body, scheme) = body.into_parts();
+        let res = Encrypted::new(format!("{body} world"), scheme).respond_to(&req);
+        assert!(!res.headers().contains_key(header::CONTENT_LENGTH));
+
+        let nonce = BASE64_URL_SAFE_NO_PAD
+            .decode(res.headers().get("encryption-nonce").unwrap())
+            .unwrap();
+        let ciphertext = body::to_bytes(res.into_body()).await.unwrap();
+
+        let plaintext = Aes256Gcm::new_from_slice(&KEY)
+            .unwrap()
+            .decrypt(Nonce::from_slice(&nonce), &ciphertext[..])
+            .unwrap();
+        assert_eq!(plaintext, b"hello world");
+    }
+
+    #[actix_web::test]
+    async fn extraction_errors() {
+        let (nonce, ciphertext) = encrypt(br#"{"a":1}"#);
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .set_payload(ciphertext.clone())
+            .to_http_parts();
+        let err = Encrypted::<Bytes, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::MissingNonce));
+
+        // tampered ciphertext
+        let mut tampered = ciphertext.clone();
+        tampered[0] ^= 1;
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .insert_header(("encryption-nonce", nonce.clone()))
+            .set_payload(tampered)
+            .to_http_parts();
+        let err = Encrypted::<Bytes, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::Decrypt(_)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+
+        // inner extractor sees plaintext but rejects it
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .insert_header(("encryption-nonce", nonce))
+            .insert_header(header::ContentType::json())
+            .set_payload(ciphertext)
+            .to_http_parts();
+        let err = Encrypted::<web::Json<Vec<u8>>, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::Extractor(_)));
+
+        // declared length over limit is rejected before the body is read
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .app_data(LabConfig::new().body_limit(4))
+            .insert_header(("encryption-nonce", encrypt(b"").0))
+            .insert_header(ContentLength::from(1_000))
+            .to_http_parts();
+        let err = Encrypted::<Bytes, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::Overflow { limit: 4 }));
+    }
+
+    #[actix_web::test]
+    async fn strict_content_length_checks_ciphertext() {
+        let (nonce, ciphertext) = encrypt(br#"{"a":1}"#);
+        let ciphertext_len = ciphertext.len();
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .app_data(LabConfig::new().strict_content_length(true))
+            .insert_header(("encryption-nonce", nonce.clone()))
+            .insert_header(header::ContentType::json())
+            .insert_header(ContentLength::from(ciphertext_len))
+            .set_payload(ciphertext.clone())
+            .to_http_parts();
+        let body = Encrypted::<Json<serde_json::Value>, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            body.into_inner().into_inner(),
+            serde_json::json!({ "a": 1 })
+        );
+        assert!(req.extensions().get::<PayloadLength>().is_none());
+
+        // declared length does not match ciphertext
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .app_data(LabConfig::new().strict_content_length(true))
+            .insert_header(("encryption-nonce", nonce))
+            .insert_header(header::ContentType::json())
+            .insert_header(ContentLength::from(ciphertext_len + 1))
+            .set_payload(ciphertext)
+            .to_http_parts();
+        let err = Encrypted::<Json<serde_json::Value>, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::Payload(_)));
+    }
+
+    #[actix_web::test]
+    async fn inner_limit_applies_to_plaintext() {
+        // plaintext is exactly at the inner extractor's limit but the ciphertext is not
+        let (nonce, ciphertext) = encrypt(b"12345678");
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .insert_header(("encryption-nonce", nonce))
+            .insert_header(ContentLength::from(ciphertext.len()))
+            .set_payload(ciphertext)
+            .to_http_parts();
+        let body = Encrypted::<crate::extract::Bytes<8>, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner().into_inner(), "12345678");
+
+        let (nonce, ciphertext) = encrypt(b"123456789");
+        let (req, mut pl) = TestRequest::default()
+            .app_data(KEY)
+            .insert_header(("encryption-nonce", nonce))
+            .insert_header(ContentLength::from(ciphertext.len()))
+            .set_payload(ciphertext)
+            .to_http_parts();
+        let err = Encrypted::<crate::extract::Bytes<8>, TestAead>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EncryptedError::Extractor(_)));
+    }
+
+    #[actix_web::test]
+    async fn response_limit() {
+        let (req, _) = TestRequest::default().app_data(KEY).to_http_parts();
+        let scheme = TestAead::init(&req).await.unwrap();
+
+        let res = Encrypted::new(vec![0; DEFAULT_BODY_LIMIT + 1], scheme).respond_to(&req);
+        body::to_bytes(res.into_body()).await.unwrap_err();
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::{
     fmt,
-    future::Future,
+    future::{ready, Future},
     pin::Pin,
     rc::Rc,
     task::{ready, Context, Poll},
@@ -19,9 +19,25 @@ use ahash::AHashMap;
 use futures_core::future::LocalBoxFuture;
 use pin_project_lite::pin_project;
 
+use crate::middleware::ErrorPages;
+
 type ErrorHandlerRes<B> = Result<ServiceResponse<EitherBody<B>>>;
 type ErrorHandler<B> = dyn Fn(ServiceResponse<B>) -> LocalBoxFuture<'static, ErrorHandlerRes<B>>;
-type Handlers<B> = Rc<AHashMap<StatusCode, Box<ErrorHandler<B>>>>;
+type Handlers<B> = Rc<HandlerSet<B>>;
+
+struct HandlerSet<B> {
+    by_status: AHashMap<StatusCode, Box<ErrorHandler<B>>>,
+    default: Option<Box<ErrorHandler<B>>>,
+}
+
+impl<B> Default for HandlerSet<B> {
+    fn default() -> Self {
+        Self {
+            by_status: AHashMap::default(),
+            default: None,
+        }
+    }
+}
 
 /// Middleware for registering custom status code based error handlers.
 ///
@@ -61,8 +77,9 @@ impl<B> fmt::Debug for ErrorHandlers<B> {
         f.debug_struct("ErrorHandlers")
             .field(
                 "handlers",
-                &format_args!("[<{} items>]", self.handlers.len()),
+                &format_args!("[<{} items>]", self.handlers.by_status.len()),
             )
+            .field("has_default_handler", &self.handlers.default.is_some())
             .finish()
     }
 }
@@ -89,9 +106,32 @@ impl<B> ErrorHandlers<B> {
     {
         Rc::get_mut(&mut self.handlers)
             .unwrap()
+            .by_status
             .insert(status, Box::new(move |res| Box::pin((handler)(res))));
         self
     }
+
+    /// Register error handler for all client and server error status codes that do not have a
+    /// specific handler registered.
+    pub fn default_handler<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(ServiceResponse<B>) -> Fut + 'static,
+        Fut: Future<Output = ErrorHandlerRes<B>> + 'static,
+    {
+        Rc::get_mut(&mut self.handlers).unwrap().default =
+            Some(Box::new(move |res| Box::pin((handler)(res))));
+        self
+    }
+
+    /// Register content-negotiated error pages as the [default handler](Self::default_handler).
+    ///
+    /// See [`ErrorPages`] docs.
+    pub fn negotiated_pages(self, pages: ErrorPages) -> Self
+    where
+        B: 'static,
+    {
+        self.default_handler(move |res| ready(Ok(pages.render(res))))
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for ErrorHandlers<B>
@@ -169,7 +209,16 @@ where
             ErrorHandlersProj::ServiceFuture { fut, handlers } => {
                 let res = ready!(fut.poll(cx))?;
 
-                match handlers.get(&res.status()) {
+                let status = res.status();
+
+                // fall back to default handler for error statuses without a specific handler
+                let handler = handlers.by_status.get(&status).or_else(|| {
+                    (status.is_client_error() || status.is_server_error())
+                        .then_some(handlers.default.as_ref())
+                        .flatten()
+                });
+
+                match handler {
                     Some(handler) => {
                         let fut = handler(res);
 
@@ -0,0 +1,362 @@
+//! Content-negotiated error pages.
+//!
+//! See [`ErrorPages`] docs.
+
+use std::{fmt, rc::Rc};
+
+use actix_web::{
+    body::EitherBody,
+    dev::ServiceResponse,
+    http::{
+        header::{self, Accept, ContentType, Header as _},
+        StatusCode,
+    },
+    HttpResponse,
+};
+use serde_json::json;
+
+/// Default HTML error page template.
+const DEFAULT_HTML_TEMPLATE: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>{status} {title}</title>
+</head>
+<body>
+<h1>{status} {title}</h1>
+<p>{detail}</p>
+</body>
+</html>
+";
+
+type JsonRenderer = dyn Fn(&ErrorInfo<'_>) -> serde_json::Value;
+
+/// Details of an error response, passed to custom [JSON renderers](ErrorPages::json).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ErrorInfo<'a> {
+    /// Response status code.
+    pub status: StatusCode,
+
+    /// Canonical reason phrase for the status code (e.g., "Not Found").
+    pub title: &'a str,
+
+    /// Human-readable error description.
+    ///
+    /// For client errors, this is the error message, if any. For server errors, this is always
+    /// the reason phrase so that internal details are not exposed.
+    pub detail: &'a str,
+}
+
+/// Renders error responses as HTML for browsers and as JSON for API clients.
+///
+/// The representation is chosen by negotiating against the request's `Accept` header: if
+/// `text/html` is preferred over JSON, an HTML page is rendered; otherwise, a JSON body in the
+/// style of [RFC 9457] problem details is rendered, with an `application/problem+json` content
+/// type. Requests without an `Accept` header receive JSON.
+///
+/// Only responses without a body type (i.e., without a `Content-Type` header) or with a plain text
+/// body, such as the default responses produced by Actix Web errors, are rendered. Responses whose
+/// handlers already chose a content type are left unchanged.
+///
+/// Register on [`ErrorHandlers`] using
+/// [`negotiated_pages()`](crate::middleware::ErrorHandlers::negotiated_pages) or use
+/// [`render()`](Self::render) from a custom error handler.
+///
+/// # Templates
+/// The HTML template can be overridden using [`html_template()`](Self::html_template). The
+/// following placeholders are replaced with HTML-escaped values:
+/// - `{status}`: the status code (e.g., `404`)
+/// - `{title}`: the canonical reason phrase (e.g., `Not Found`)
+/// - `{detail}`: the error message for client errors, or the reason phrase for server errors
+///
+/// The JSON body can be overridden using [`json()`](Self::json).
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::{ErrorHandlers, ErrorPages};
+///
+/// let pages = ErrorPages::new()
+///     .html_template("<h1>Oops! {status}</h1><p>{detail}</p><a href=\"/\">Go home</a>");
+///
+/// App::new().wrap(ErrorHandlers::new().negotiated_pages(pages))
+/// # ;
+/// ```
+///
+/// [RFC 9457]: https://datatracker.ietf.org/doc/html/rfc9457
+/// [`ErrorHandlers`]: crate::middleware::ErrorHandlers
+#[derive(Clone)]
+pub struct ErrorPages {
+    html_template: Rc<str>,
+    json: Option<Rc<JsonRenderer>>,
+}
+
+impl ErrorPages {
+    /// Constructs new error page renderer with the default templates.
+    pub fn new() -> Self {
+        Self {
+            html_template: Rc::from(DEFAULT_HTML_TEMPLATE),
+            json: None,
+        }
+    }
+
+    /// Sets the HTML page template.
+    ///
+    /// See [type docs](Self#templates) for supported placeholders.
+    pub fn html_template(mut self, template: impl Into<String>) -> Self {
+        self.html_template = Rc::from(template.into());
+        self
+    }
+
+    /// Sets the function used to produce JSON error bodies.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web_lab::middleware::ErrorPages;
+    /// use serde_json::json;
+    ///
+    /// ErrorPages::new().json(|err| {
+    ///     json!({
+    ///         "code": err.status.as_u16(),
+    ///         "message": err.detail,
+    ///     })
+    /// })
+    /// # ;
+    /// ```
+    pub fn json<F>(mut self, render: F) -> Self
+    where
+        F: Fn(&ErrorInfo<'_>) -> serde_json::Value + 'static,
+    {
+        self.json = Some(Rc::new(render));
+        self
+    }
+
+    /// Renders an error page for `res`, if it is an error response eligible for rendering.
+    ///
+    /// Ineligible responses are returned unchanged, as the left body variant.
+    pub fn render<B>(&self, res: ServiceResponse<B>) -> ServiceResponse<EitherBody<B>> {
+        let status = res.status();
+
+        if !(status.is_client_error() || status.is_server_error()) || !has_plain_body(&res) {
+            return res.map_into_left_body();
+        }
+
+        let title = status.canonical_reason().unwrap_or("Error");
+
+        let detail = match res.response().error() {
+            Some(err) if status.is_client_error() => err.to_string(),
+            _ => title.to_owned(),
+        };
+
+        let info = ErrorInfo {
+            status,
+            title,
+            detail: &detail,
+        };
+
+        let (req, res) = res.into_parts();
+
+        let mut page = HttpResponse::build(status);
+
+        // keep headers such as `Allow` or `WWW-Authenticate` set by the original response
+        for (name, value) in res.headers() {
+            if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                page.append_header((name.clone(), value.clone()));
+            }
+        }
+
+        let page = if prefers_html(&req) {
+            page.insert_header(ContentType::html())
+                .body(self.render_html(&info))
+        } else {
+            let body = match &self.json {
+                Some(render) => render(&info),
+                None => json!({
+                    "title": info.title,
+                    "status": info.status.as_u16(),
+                    "detail": info.detail,
+                }),
+            };
+
+            page.insert_header((header::CONTENT_TYPE, "application/problem+json"))
+                .body(body.to_string())
+        };
+
+        ServiceResponse::new(req, page).map_into_right_body()
+    }
+
+    fn render_html(&self, info: &ErrorInfo<'_>) -> String {
+        self.html_template
+            .replace("{status}", info.status.as_str())
+            .replace("{title}", &escape_html(info.title))
+            .replace("{detail}", &escape_html(info.detail))
+    }
+}
+
+impl Default for ErrorPages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ErrorPages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorPages")
+            .field("html_template", &self.html_template)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns true if the response has no content type or a plain text one.
+fn has_plain_body<B>(res: &ServiceResponse<B>) -> bool {
+    match res.headers().get(header::CONTENT_TYPE) {
+        None => true,
+        Some(ct) => ct
+            .to_str()
+            .ok()
+            .and_then(|ct| ct.parse::<mime::Mime>().ok())
+            .is_some_and(|mime| mime.essence_str() == mime::TEXT_PLAIN.essence_str()),
+    }
+}
+
+/// Returns true if the request's `Accept` header prefers HTML over JSON.
+fn prefers_html(req: &actix_web::HttpRequest) -> bool {
+    let Ok(accept) = Accept::parse(req) else {
+        return false;
+    };
+
+    accept
+        .ranked()
+        .into_iter()
+        .find_map(|mime| {
+            if mime.subtype() == mime::HTML {
+                Some(true)
+            } else if mime.subtype() == mime::JSON
+                || mime.suffix() == Some(mime::JSON)
+                || mime.type_() == mime::STAR
+            {
+                Some(false)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(false)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::to_bytes,
+        error,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+    use crate::middleware::ErrorHandlers;
+
+    async fn body<B: actix_web::body::MessageBody>(res: ServiceResponse<B>) -> String {
+        let body = to_bytes(res.into_body()).await.ok().unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn negotiates_representation() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    ErrorHandlers::new()
+                        .negotiated_pages(ErrorPages::new().html_template("<p>{detail}</p>")),
+                )
+                .route(
+                    "/bad",
+                    web::get().to(|| async {
+                        Err::<HttpResponse, _>(error::ErrorBadRequest("<bad> input"))
+                    }),
+                )
+                .route(
+                    "/custom",
+                    web::get().to(|| async { HttpResponse::Conflict().json(json!({ "a": 1 })) }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/bad")
+            .insert_header((header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(body(res).await, "<p>&lt;bad&gt; input</p>");
+
+        let req = TestRequest::with_uri("/bad").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+        assert_eq!(
+            body(res).await,
+            r#"{"detail":"<bad> input","status":400,"title":"Bad Request"}"#
+        );
+
+        let req = TestRequest::with_uri("/missing")
+            .insert_header((header::ACCEPT, "application/json, text/html;q=0.5"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            body(res).await,
+            r#"{"detail":"Not Found","status":404,"title":"Not Found"}"#
+        );
+
+        // handler-chosen content types are kept
+        let req = TestRequest::with_uri("/custom")
+            .insert_header((header::ACCEPT, "text/html"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(body(res).await, r#"{"a":1}"#);
+    }
+
+    #[actix_web::test]
+    async fn custom_json_and_hidden_server_errors() {
+        let pages = ErrorPages::new().json(|err| json!({ "message": err.detail }));
+
+        let app = init_service(
+            App::new()
+                .wrap(ErrorHandlers::new().negotiated_pages(pages))
+                .default_service(web::to(|| async {
+                    Err::<HttpResponse, _>(error::ErrorInternalServerError(
+                        "db password is hunter2",
+                    ))
+                })),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body(res).await, r#"{"message":"Internal Server Error"}"#);
+    }
+}
@@ -0,0 +1,99 @@
+//! Expect typed header.
+//!
+//! See [`Expect`] docs.
+
+use std::{fmt, str};
+
+use actix_web::{
+    error::ParseError,
+    http::header::{self, from_one_raw_str, Header, HeaderName, HeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+
+/// The `Expect` header, defined in [RFC 9110 §10.1.1].
+///
+/// Indicates a set of behaviors (expectations) that need to be supported by the server in order to
+/// properly handle the request. The only expectation defined is `100-continue`, which a client
+/// sends when it would like to know whether the server will accept the request before sending a
+/// (usually large) body.
+///
+/// [Read more on MDN.](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Expect)
+///
+/// # Examples
+/// ```
+/// use actix_web::{HttpMessage as _, HttpRequest};
+/// use actix_web_lab::header::Expect;
+///
+/// fn wants_continue(req: &HttpRequest) -> bool {
+///     req.get_header::<Expect>() == Some(Expect::Continue)
+/// }
+/// ```
+///
+/// [RFC 9110 §10.1.1]: https://datatracker.ietf.org/doc/html/rfc9110#section-10.1.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Expect {
+    /// The `100-continue` expectation.
+    Continue,
+}
+
+impl fmt::Display for Expect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expect::Continue => f.write_str("100-continue"),
+        }
+    }
+}
+
+impl str::FromStr for Expect {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        if val.trim().eq_ignore_ascii_case("100-continue") {
+            Ok(Expect::Continue)
+        } else {
+            Err(ParseError::Header)
+        }
+    }
+}
+
+impl TryIntoHeaderValue for Expect {
+    type Error = header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        match self {
+            Expect::Continue => Ok(HeaderValue::from_static("100-continue")),
+        }
+    }
+}
+
+impl Header for Expect {
+    fn name() -> HeaderName {
+        header::EXPECT
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(Self::name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    #[test]
+    fn parsing() {
+        assert_parse_eq::<Expect, _, _>(["100-continue"], Expect::Continue);
+        assert_parse_eq::<Expect, _, _>(["100-Continue"], Expect::Continue);
+
+        assert_parse_fail::<Expect, _, _>([""]);
+        assert_parse_fail::<Expect, _, _>(["200-ok"]);
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(Expect::Continue.to_string(), "100-continue");
+        assert_eq!(Expect::Continue.try_into_value().unwrap(), "100-continue");
+    }
+}
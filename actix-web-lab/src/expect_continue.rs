@@ -0,0 +1,116 @@
+//! `Expect: 100-continue` handler.
+//!
+//! See [`ExpectContinue`] docs.
+
+use actix_http::{body::BoxBody, HttpMessage as _, Request, Response, StatusCode};
+use actix_service::{always_ready, Service, ServiceFactory};
+use actix_utils::future::{ready, Ready};
+use tracing::debug;
+
+use crate::header::ContentLength;
+
+/// Handler for `Expect: 100-continue` requests that rejects over-limit uploads before the client
+/// sends the body.
+///
+/// Clients sending `Expect: 100-continue` wait for the server's interim `100 Continue` response
+/// before sending the request body. The HTTP/1 dispatcher sends that interim response before any
+/// handler or extractor runs, so by the time the body extractors in this crate see the request's
+/// `Content-Length`, the client may already be uploading. Those extractors do reject over-limit
+/// requests without reading the body, but only this handler can stop the upload from starting.
+///
+/// Requests with a `Content-Length` greater than the configured limit receive a final `413 Payload
+/// Too Large` response instead of `100 Continue`. Other requests are passed to the app unchanged.
+///
+/// Register it as the expect handler of an [`HttpService`](actix_http::HttpService) using
+/// [`HttpServiceBuilder::expect()`](actix_http::HttpServiceBuilder::expect).
+///
+/// # Examples
+/// ```no_run
+/// use std::convert::Infallible;
+///
+/// use actix_http::{HttpService, Request, Response};
+/// use actix_web_lab::util::ExpectContinue;
+///
+/// let _service = HttpService::<tokio::net::TcpStream, _, _>::build()
+///     .expect(ExpectContinue::new(1_048_576))
+///     .finish(|_req: Request| async { Ok::<_, Infallible>(Response::ok()) });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ExpectContinue {
+    limit: usize,
+}
+
+impl ExpectContinue {
+    /// Constructs new expect handler that rejects declared body sizes greater than `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl ServiceFactory<Request> for ExpectContinue {
+    type Response = Request;
+    type Error = Response<BoxBody>;
+    type Config = ();
+    type Service = Self;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: Self::Config) -> Self::Future {
+        ready(Ok(*self))
+    }
+}
+
+impl Service<Request> for ExpectContinue {
+    type Response = Request;
+    type Error = Response<BoxBody>;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    always_ready!();
+
+    fn call(&self, req: Request) -> Self::Future {
+        match req.get_header::<ContentLength>() {
+            Some(len) if len > self.limit => {
+                debug!(
+                    "rejecting expect-continue request; declared length {} exceeds limit {}",
+                    len.into_inner(),
+                    self.limit,
+                );
+
+                ready(Err(Response::new(StatusCode::PAYLOAD_TOO_LARGE)))
+            }
+
+            _ => ready(Ok(req)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_http::{header, test::TestRequest};
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn rejects_over_limit() {
+        let handler = ExpectContinue::new(10).new_service(()).await.unwrap();
+
+        let req = TestRequest::default()
+            .insert_header((header::EXPECT, "100-continue"))
+            .insert_header((header::CONTENT_LENGTH, "10"))
+            .finish();
+        assert!(handler.call(req).await.is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((header::EXPECT, "100-continue"))
+            .insert_header((header::CONTENT_LENGTH, "11"))
+            .finish();
+        let res = handler.call(req).await.unwrap_err();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // no declared length to check
+        let req = TestRequest::default()
+            .insert_header((header::EXPECT, "100-continue"))
+            .finish();
+        assert!(handler.call(req).await.is_ok());
+    }
+}
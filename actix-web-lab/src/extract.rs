@@ -1,19 +1,105 @@
 //! Experimental extractors.
 
 /// An alias for [`actix_web::web::Data<T>`] with a more descriptive name.
+#[deprecated(
+    since = "0.24.0",
+    note = "Use `actix_web::web::Data` or `actix_web_lab::web::SharedData`."
+)]
 pub type SharedData<T> = actix_web::web::Data<T>;
 
+#[cfg(feature = "cbor")]
+pub use crate::cbor_extract::{Cbor, CborPayloadError, DEFAULT_CBOR_LIMIT};
+#[cfg(feature = "encrypted")]
+pub use crate::encrypted::{Encrypted, EncryptedError, EncryptionScheme};
+#[cfg(feature = "msgpack")]
+pub use crate::msgpack_extract::{MsgPack, MsgPackPayloadError, DEFAULT_MSGPACK_LIMIT};
+#[cfg(feature = "peer-cert")]
+pub use crate::peer_cert::{PeerCert, PeerCertError, SubjectAltName};
+#[cfg(feature = "qs")]
+pub use crate::qs_form::{QsForm, QsFormError, DEFAULT_QS_FORM_LIMIT};
+#[cfg(feature = "rng")]
+pub use crate::rng::{Rng, RngConfig};
+#[cfg(feature = "xml")]
+pub use crate::xml::{Xml, XmlDeserializeError, XmlPayloadError, DEFAULT_XML_LIMIT};
+#[cfg(feature = "yaml")]
+pub use crate::yaml::{Yaml, YamlPayloadError, DEFAULT_YAML_LIMIT};
 pub use crate::{
     body_limit::{BodyLimit, DEFAULT_BODY_LIMIT},
     bytes::{Bytes, DEFAULT_BYTES_LIMIT},
+    content_sniff::{VerifiedContentType, VerifiedContentTypeError},
+    csv_stream::{CsvStream, CsvStreamError, DEFAULT_CSV_RECORD_LIMIT},
     host::Host,
     json::{Json, DEFAULT_JSON_LIMIT},
+    json_ref::JsonRef,
+    lab_config::LabConfig,
     lazy_data::LazyData,
     local_data::LocalData,
-    path::Path,
+    matched_path::MatchedPath,
+    multipart::{
+        Multipart, MultipartError, MultipartFile, DEFAULT_MULTIPART_FIELD_LIMIT,
+        DEFAULT_MULTIPART_LIMIT,
+    },
+    ndjson_stream::{NdJsonStream, NdJsonStreamError, DEFAULT_NDJSON_LINE_LIMIT},
+    path::{Path, PathDeserializeError},
     query::{Query, QueryDeserializeError},
-    request_signature::{RequestSignature, RequestSignatureError, RequestSignatureScheme},
+    replayable_body::{
+        ReplayableBody, ReplayableBodyError, DEFAULT_REPLAYABLE_BODY_LIMIT,
+        DEFAULT_REPLAYABLE_MEMORY_LIMIT,
+    },
+    request_id::RequestId,
+    request_signature::{
+        RequestSignature, RequestSignatureError, RequestSignatureScheme, RequestSignatureStream,
+    },
+    strict_content_length::{ContentLengthMismatch, StrictContentLength, StrictContentLengthError},
     swap_data::SwapData,
+    trailers::{Trailers, TrailersError},
+    trusted_proxies::TrustedProxies,
     url_encoded_form::{UrlEncodedForm, DEFAULT_URL_ENCODED_FORM_LIMIT},
     x_forwarded_prefix::ReconstructedPath,
 };
+
+#[cfg(feature = "serde-helpers")]
+pub mod serde_helpers {
+    //! Serde helpers for common timestamp and duration formats.
+    //!
+    //! Each module contains `serialize` and `deserialize` functions for use with
+    //! `#[serde(with = "...")]` on fields of types extracted using [`Query`], [`Json`], and other
+    //! serde based extractors. Each also contains an `option` sub-module for optional fields,
+    //! which should be combined with `#[serde(default)]` so that missing query parameters are
+    //! accepted.
+    //!
+    //! # Examples
+    //! ```
+    //! use std::time::{Duration, SystemTime};
+    //!
+    //! use actix_web::{get, Responder};
+    //! use actix_web_lab::extract::{serde_helpers, Query};
+    //! use serde::Deserialize;
+    //!
+    //! #[derive(Deserialize)]
+    //! struct Search {
+    //!     // e.g., `?since=2024-02-01T12:00:00Z`
+    //!     #[serde(with = "serde_helpers::rfc3339")]
+    //!     since: SystemTime,
+    //!
+    //!     // e.g., `&until=1706788800`
+    //!     #[serde(default, with = "serde_helpers::unix_ts::option")]
+    //!     until: Option<SystemTime>,
+    //!
+    //!     // e.g., `&timeout=1m 30s`
+    //!     #[serde(default, with = "serde_helpers::humantime_duration::option")]
+    //!     timeout: Option<Duration>,
+    //! }
+    //!
+    //! #[get("/search")]
+    //! async fn search(search: Query<Search>) -> impl Responder {
+    //!     // ...
+    //!     # ""
+    //! }
+    //! ```
+    //!
+    //! [`Query`]: crate::extract::Query
+    //! [`Json`]: crate::extract::Json
+
+    pub use crate::serde_helpers::{humantime_duration, rfc3339, unix_ts};
+}
@@ -1,3 +1,12 @@
 //! Experimental route guards.
 //!
 //! Analogous to the `guard` module in Actix Web.
+
+#[cfg(feature = "peer-cert")]
+pub use crate::peer_cert::client_cert;
+#[cfg(feature = "url")]
+pub use crate::same_origin_guard::same_origin;
+pub use crate::{
+    method_guards::{idempotent_methods, not_method, safe_methods},
+    tls_policy::tls_policy,
+};
@@ -6,12 +6,22 @@ use actix_http::{error::ParseError, header::HeaderValue};
 
 #[cfg(test)]
 pub(crate) use self::header_test_helpers::{assert_parse_eq, assert_parse_fail};
+#[cfg(feature = "content-digest")]
+pub use crate::content_digest::{ContentDigest, DigestAlgorithm, DigestValue, ReprDigest};
+#[cfg(feature = "url")]
+pub use crate::origin::{Origin, Referer};
 pub use crate::{
+    age::Age,
     cache_control::{CacheControl, CacheDirective},
+    cache_status::{CacheStatus, CacheStatusEntry, ForwardReason},
     clear_site_data::{ClearSiteData, ClearSiteDataDirective},
     content_length::ContentLength,
+    expect::Expect,
     forwarded::Forwarded,
+    idempotency_key::{IdempotencyKey, IDEMPOTENCY_KEY},
+    server_timing::{ServerTiming, ServerTimingMetric},
     strict_transport_security::StrictTransportSecurity,
+    www_authenticate::{BearerError, Challenge, WwwAuthenticate},
     x_forwarded_prefix::{XForwardedPrefix, X_FORWARDED_PREFIX},
 };
 
@@ -0,0 +1,370 @@
+//! Startup audit of security-related response headers.
+//!
+//! See [`HeaderAudit`] docs.
+
+use std::{collections::HashSet, fmt};
+
+use actix_http::Request;
+use actix_service::{IntoServiceFactory, Service as _, ServiceFactory};
+use actix_web::{
+    body::MessageBody,
+    dev::{AppConfig, ServiceResponse},
+    http::header::{self, HeaderMap, HeaderName},
+    test,
+};
+
+/// Kind of problem found by a [`HeaderAudit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderFindingKind {
+    /// A deprecated header was sent; it should be removed or replaced.
+    Deprecated,
+
+    /// A recommended header was not sent.
+    Missing,
+
+    /// A recommended header was sent with an ineffective value.
+    Misconfigured,
+}
+
+/// A single problem found by a [`HeaderAudit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderFinding {
+    /// Probe URI whose response exhibited the problem.
+    pub uri: String,
+
+    /// Header the finding relates to.
+    pub header: HeaderName,
+
+    /// Kind of problem.
+    pub kind: HeaderFindingKind,
+
+    /// Suggested fix.
+    pub advice: &'static str,
+}
+
+impl fmt::Display for HeaderFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            HeaderFindingKind::Deprecated => "deprecated header",
+            HeaderFindingKind::Missing => "missing header",
+            HeaderFindingKind::Misconfigured => "misconfigured header",
+        };
+
+        write!(f, "{}: {kind} `{}`: {}", self.uri, self.header, self.advice)
+    }
+}
+
+/// Results of a [`HeaderAudit`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderAuditReport {
+    findings: Vec<HeaderFinding>,
+}
+
+impl HeaderAuditReport {
+    /// Returns all findings, in probe order.
+    pub fn findings(&self) -> &[HeaderFinding] {
+        &self.findings
+    }
+
+    /// Returns true if no problems were found.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Logs each finding as a warning.
+    pub fn log(&self) {
+        for finding in &self.findings {
+            tracing::warn!("header audit: {finding}");
+        }
+    }
+}
+
+/// Audits the security-related response headers an app sends.
+///
+/// Response headers are usually added by middleware (e.g., [`DefaultHeaders`]) and so cannot be
+/// read from an `App`'s configuration directly. Instead, the audit builds the app and sends a `GET`
+/// request to each probe URI (`/` by default), then inspects the response headers for:
+///
+/// - deprecated headers that browsers ignore or that are now harmful: `Expect-CT`,
+///   `X-XSS-Protection` (unless disabled with `0`), `Public-Key-Pins`, and `Feature-Policy`;
+/// - missing recommended headers: `Strict-Transport-Security`, `Content-Security-Policy`,
+///   `X-Content-Type-Options`, `Referrer-Policy`, and `X-Frame-Options` (unless the CSP includes
+///   a `frame-ancestors` directive).
+///
+/// Use [`ignore()`](Self::ignore) to silence findings for headers that are intentionally
+/// omitted, e.g., `Strict-Transport-Security` when TLS is terminated and the header is added by a
+/// reverse proxy. Since `Strict-Transport-Security` is often only sent over HTTPS, probe URIs may
+/// be absolute `https://` URIs.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::DefaultHeaders, web, App, HttpResponse};
+/// use actix_web_lab::util::HeaderAudit;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let app = App::new()
+///     .wrap(DefaultHeaders::new().add(("X-XSS-Protection", "1; mode=block")))
+///     .route("/", web::get().to(HttpResponse::Ok));
+///
+/// let report = HeaderAudit::new().probe("/").run(app).await;
+/// report.log();
+///
+/// assert!(!report.is_clean());
+/// # });
+/// ```
+///
+/// [`DefaultHeaders`]: actix_web::middleware::DefaultHeaders
+#[derive(Debug, Clone, Default)]
+pub struct HeaderAudit {
+    probes: Vec<String>,
+    ignored: HashSet<HeaderName>,
+}
+
+impl HeaderAudit {
+    /// Constructs a new header audit with no ignored headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a URI to send a probe request to.
+    ///
+    /// If no probes are added, `/` is probed.
+    pub fn probe(mut self, uri: impl Into<String>) -> Self {
+        self.probes.push(uri.into());
+        self
+    }
+
+    /// Ignores findings relating to `header`.
+    pub fn ignore(mut self, header: HeaderName) -> Self {
+        self.ignored.insert(header);
+        self
+    }
+
+    /// Audits the response headers of `app`.
+    ///
+    /// # Panics
+    /// Panics if the app fails to initialize.
+    pub async fn run<R, S, B>(self, app: R) -> HeaderAuditReport
+    where
+        R: IntoServiceFactory<S, Request>,
+        S: ServiceFactory<
+            Request,
+            Config = AppConfig,
+            Response = ServiceResponse<B>,
+            Error = actix_web::Error,
+        >,
+        S::InitError: fmt::Debug,
+        B: MessageBody,
+    {
+        let srv = test::init_service(app).await;
+
+        let probes = if self.probes.is_empty() {
+            vec!["/".to_owned()]
+        } else {
+            self.probes.clone()
+        };
+
+        let mut report = HeaderAuditReport::default();
+
+        for uri in probes {
+            let req = test::TestRequest::get().uri(&uri).to_request();
+
+            let headers = match srv.call(req).await {
+                Ok(res) => res.headers().clone(),
+                Err(err) => err.as_response_error().error_response().headers().clone(),
+            };
+
+            for (header, kind, advice) in audit_headers(&headers) {
+                if !self.ignored.contains(&header) {
+                    report.findings.push(HeaderFinding {
+                        uri: uri.clone(),
+                        header,
+                        kind,
+                        advice,
+                    });
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Checks a set of response headers, returning any problems found.
+fn audit_headers(headers: &HeaderMap) -> Vec<(HeaderName, HeaderFindingKind, &'static str)> {
+    use HeaderFindingKind::*;
+
+    let mut findings = Vec::new();
+
+    let deprecated = [
+        (
+            HeaderName::from_static("expect-ct"),
+            "remove it; Certificate Transparency is now enforced by browsers by default",
+        ),
+        (
+            HeaderName::from_static("public-key-pins"),
+            "remove it; HPKP is no longer supported by browsers",
+        ),
+        (
+            HeaderName::from_static("feature-policy"),
+            "replace it with `Permissions-Policy`",
+        ),
+    ];
+
+    for (name, advice) in deprecated {
+        if headers.contains_key(&name) {
+            findings.push((name, Deprecated, advice));
+        }
+    }
+
+    let xss_protection = HeaderName::from_static("x-xss-protection");
+    if headers
+        .get(&xss_protection)
+        .is_some_and(|val| val.as_bytes().trim_ascii() != b"0")
+    {
+        findings.push((
+            xss_protection,
+            Deprecated,
+            "remove it or set it to `0`, and use `Content-Security-Policy` to mitigate XSS instead",
+        ));
+    }
+
+    if !headers.contains_key(header::STRICT_TRANSPORT_SECURITY) {
+        findings.push((
+            header::STRICT_TRANSPORT_SECURITY,
+            Missing,
+            "add it to HTTPS responses, e.g., `StrictTransportSecurity::recommended()`",
+        ));
+    }
+
+    let csp = headers
+        .get(header::CONTENT_SECURITY_POLICY)
+        .and_then(|val| val.to_str().ok());
+
+    if csp.is_none() {
+        findings.push((
+            header::CONTENT_SECURITY_POLICY,
+            Missing,
+            "add a policy restricting where scripts and other resources can be loaded from",
+        ));
+    }
+
+    match headers.get(header::X_CONTENT_TYPE_OPTIONS) {
+        None => findings.push((
+            header::X_CONTENT_TYPE_OPTIONS,
+            Missing,
+            "add it with the value `nosniff`",
+        )),
+        Some(val) if !val.as_bytes().trim_ascii().eq_ignore_ascii_case(b"nosniff") => findings
+            .push((
+                header::X_CONTENT_TYPE_OPTIONS,
+                Misconfigured,
+                "`nosniff` is the only effective value",
+            )),
+        Some(_) => {}
+    }
+
+    if !headers.contains_key(header::REFERRER_POLICY) {
+        findings.push((
+            header::REFERRER_POLICY,
+            Missing,
+            "add it, e.g., with the value `strict-origin-when-cross-origin`",
+        ));
+    }
+
+    let has_frame_ancestors = csp.is_some_and(|csp| {
+        csp.split(';').any(|directive| {
+            directive
+                .split_whitespace()
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case("frame-ancestors"))
+        })
+    });
+
+    if !has_frame_ancestors && !headers.contains_key(header::X_FRAME_OPTIONS) {
+        findings.push((
+            header::X_FRAME_OPTIONS,
+            Missing,
+            "add it with the value `DENY`, or add a CSP `frame-ancestors` directive",
+        ));
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{middleware::DefaultHeaders, web, App, HttpResponse};
+
+    use super::*;
+
+    fn names(report: &HeaderAuditReport, kind: HeaderFindingKind) -> Vec<&str> {
+        report
+            .findings()
+            .iter()
+            .filter(|finding| finding.kind == kind)
+            .map(|finding| finding.header.as_str())
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn reports_deprecated_and_missing() {
+        let app = App::new()
+            .wrap(
+                DefaultHeaders::new()
+                    .add(("Expect-CT", "max-age=86400"))
+                    .add(("X-XSS-Protection", "1; mode=block"))
+                    .add(("X-Content-Type-Options", "sniff")),
+            )
+            .route("/", web::get().to(HttpResponse::Ok));
+
+        let report = HeaderAudit::new().run(app).await;
+
+        assert_eq!(
+            names(&report, HeaderFindingKind::Deprecated),
+            ["expect-ct", "x-xss-protection"],
+        );
+        assert_eq!(
+            names(&report, HeaderFindingKind::Missing),
+            [
+                "strict-transport-security",
+                "content-security-policy",
+                "referrer-policy",
+                "x-frame-options",
+            ],
+        );
+        assert_eq!(
+            names(&report, HeaderFindingKind::Misconfigured),
+            ["x-content-type-options"],
+        );
+        assert!(report.findings()[0]
+            .to_string()
+            .starts_with("/: deprecated header `expect-ct`"));
+    }
+
+    #[actix_web::test]
+    async fn clean_app() {
+        let app = App::new()
+            .wrap(
+                DefaultHeaders::new()
+                    .add(("X-XSS-Protection", "0"))
+                    .add((
+                        "Content-Security-Policy",
+                        "default-src 'self'; frame-ancestors 'none'",
+                    ))
+                    .add(("X-Content-Type-Options", "nosniff"))
+                    .add(("Referrer-Policy", "no-referrer")),
+            )
+            .route("/", web::get().to(HttpResponse::Ok));
+
+        let report = HeaderAudit::new()
+            .probe("/")
+            .probe("/not-found")
+            .ignore(header::STRICT_TRANSPORT_SECURITY)
+            .run(app)
+            .await;
+
+        assert!(report.is_clean(), "{:?}", report.findings());
+    }
+}
@@ -0,0 +1,346 @@
+//! Request header limits middleware.
+//!
+//! See [`HeaderLimits`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderMap, HeaderName},
+    HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::debug;
+
+type RejectCallback = Rc<dyn Fn(&ServiceRequest, &HeaderLimitExceeded)>;
+
+/// Middleware that rejects requests with too many or too large headers.
+///
+/// Responds with `431 Request Header Fields Too Large` when a request exceeds any of the
+/// configured limits:
+/// - the number of header fields, counting each value of repeated headers separately;
+/// - the total size of all header fields, measured as the sum of name and value lengths;
+/// - the length of any single header value.
+///
+/// Actix Web's HTTP/1 parser already enforces hard limits on the number of headers and the size of
+/// the request head. This middleware allows stricter, per-app limits to be set so that shared
+/// infrastructure can be protected from abusive clients. No limits are enforced by default.
+///
+/// A callback can be registered using [`on_reject()`](Self::on_reject) to record metrics about
+/// rejected requests.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::HeaderLimits;
+///
+/// App::new().wrap(
+///     HeaderLimits::new()
+///         .max_count(50)
+///         .max_total_size(8 * 1024)
+///         .max_value_len(4 * 1024)
+///         .on_reject(|req, exceeded| {
+///             tracing::warn!("rejected request to {}: {exceeded}", req.path());
+///         }),
+/// )
+/// # ;
+/// ```
+#[derive(Clone, Default)]
+pub struct HeaderLimits {
+    max_count: Option<usize>,
+    max_total_size: Option<usize>,
+    max_value_len: Option<usize>,
+    on_reject: Option<RejectCallback>,
+}
+
+impl HeaderLimits {
+    /// Constructs new header limits middleware with no limits set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of header fields allowed in a request.
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    /// Sets the maximum total size, in bytes, of all header names and values in a request.
+    pub fn max_total_size(mut self, max_total_size: usize) -> Self {
+        self.max_total_size = Some(max_total_size);
+        self
+    }
+
+    /// Sets the maximum length, in bytes, of any single header value in a request.
+    pub fn max_value_len(mut self, max_value_len: usize) -> Self {
+        self.max_value_len = Some(max_value_len);
+        self
+    }
+
+    /// Sets a callback that is called whenever a request is rejected.
+    ///
+    /// Mostly useful for logging or metrics publishing.
+    pub fn on_reject(
+        mut self,
+        callback: impl Fn(&ServiceRequest, &HeaderLimitExceeded) + 'static,
+    ) -> Self {
+        self.on_reject = Some(Rc::new(callback));
+        self
+    }
+
+    /// Returns the first limit that `headers` exceed, if any.
+    fn check(&self, headers: &HeaderMap) -> Option<HeaderLimitExceeded> {
+        let mut count = 0;
+        let mut total_size = 0;
+
+        for (name, value) in headers {
+            count += 1;
+            total_size += name.as_str().len() + value.len();
+
+            if let Some(limit) = self.max_value_len {
+                if value.len() > limit {
+                    return Some(HeaderLimitExceeded::ValueLength {
+                        name: name.clone(),
+                        len: value.len(),
+                        limit,
+                    });
+                }
+            }
+        }
+
+        if let Some(limit) = self.max_count {
+            if count > limit {
+                return Some(HeaderLimitExceeded::Count { count, limit });
+            }
+        }
+
+        if let Some(limit) = self.max_total_size {
+            if total_size > limit {
+                return Some(HeaderLimitExceeded::TotalSize {
+                    size: total_size,
+                    limit,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+impl fmt::Debug for HeaderLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeaderLimits")
+            .field("max_count", &self.max_count)
+            .field("max_total_size", &self.max_total_size)
+            .field("max_value_len", &self.max_value_len)
+            .field("on_reject", &self.on_reject.as_ref().map(|_| "<callback>"))
+            .finish()
+    }
+}
+
+/// The header limit that caused a request to be rejected by [`HeaderLimits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderLimitExceeded {
+    /// Request had too many header fields.
+    Count {
+        /// Number of header fields in the request.
+        count: usize,
+
+        /// Configured maximum number of header fields.
+        limit: usize,
+    },
+
+    /// Request headers were too large in total.
+    TotalSize {
+        /// Total size of header names and values in the request.
+        size: usize,
+
+        /// Configured maximum total size.
+        limit: usize,
+    },
+
+    /// A single header value was too long.
+    ValueLength {
+        /// Name of the header with the long value.
+        name: HeaderName,
+
+        /// Length of the header value.
+        len: usize,
+
+        /// Configured maximum value length.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for HeaderLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count { count, limit } => {
+                write!(f, "{count} header fields exceeds limit of {limit}")
+            }
+            Self::TotalSize { size, limit } => {
+                write!(f, "{size} bytes of headers exceeds limit of {limit}")
+            }
+            Self::ValueLength { name, len, limit } => {
+                write!(f, "{len} byte {name} header value exceeds limit of {limit}")
+            }
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for HeaderLimits
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Transform = HeaderLimitsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HeaderLimitsMiddleware {
+            service: Rc::new(service),
+            limits: self.clone(),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`HeaderLimits`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct HeaderLimitsMiddleware<S> {
+    service: Rc<S>,
+    limits: HeaderLimits,
+}
+
+impl<S, B> Service<ServiceRequest> for HeaderLimitsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(exceeded) = self.limits.check(req.headers()) {
+            debug!("rejecting request with oversized headers: {exceeded}");
+
+            if let Some(on_reject) = &self.limits.on_reject {
+                on_reject(&req, &exceeded);
+            }
+
+            let res = HttpResponse::RequestHeaderFieldsTooLarge()
+                .finish()
+                .map_into_right_body();
+            return Box::pin(async move { Ok(req.into_response(res)) });
+        }
+
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use actix_web::{
+        http::{header::HeaderValue, StatusCode},
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        for &(name, value) in pairs {
+            headers.append(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+
+        headers
+    }
+
+    #[test]
+    fn limits() {
+        let limits = HeaderLimits::new();
+        assert_eq!(limits.check(&headers(&[("a", "1"), ("b", "2")])), None);
+
+        let limits = HeaderLimits::new().max_count(2);
+        assert_eq!(limits.check(&headers(&[("a", "1"), ("a", "2")])), None);
+        assert_eq!(
+            limits.check(&headers(&[("a", "1"), ("a", "2"), ("b", "3")])),
+            Some(HeaderLimitExceeded::Count { count: 3, limit: 2 }),
+        );
+
+        let limits = HeaderLimits::new().max_total_size(6);
+        assert_eq!(limits.check(&headers(&[("a", "12"), ("b", "3")])), None);
+        assert_eq!(
+            limits.check(&headers(&[("a", "12"), ("b", "345")])),
+            Some(HeaderLimitExceeded::TotalSize { size: 7, limit: 6 }),
+        );
+
+        let limits = HeaderLimits::new().max_value_len(3);
+        assert_eq!(limits.check(&headers(&[("long-name", "123")])), None);
+        assert_eq!(
+            limits.check(&headers(&[("a", "1"), ("b", "1234")])),
+            Some(HeaderLimitExceeded::ValueLength {
+                name: HeaderName::from_static("b"),
+                len: 4,
+                limit: 3,
+            }),
+        );
+    }
+
+    #[actix_web::test]
+    async fn rejects_with_431() {
+        let rejected = Rc::new(RefCell::new(Vec::new()));
+
+        let app = init_service(
+            App::new()
+                .wrap(HeaderLimits::new().max_value_len(8).on_reject({
+                    let rejected = Rc::clone(&rejected);
+                    move |req, exceeded| {
+                        rejected
+                            .borrow_mut()
+                            .push(format!("{} {exceeded}", req.path()));
+                    }
+                }))
+                .default_service(web::to(|| async { "ok" })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/ok")
+            .insert_header(("x-short", "12345678"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/long")
+            .insert_header(("x-long", "123456789"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+
+        assert_eq!(
+            *rejected.borrow(),
+            ["/long 9 byte x-long header value exceeds limit of 8"],
+        );
+    }
+}
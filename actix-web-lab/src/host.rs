@@ -1,22 +1,147 @@
-use std::convert::Infallible;
+use std::{convert::Infallible, fmt, net::IpAddr};
 
 use actix_utils::future::{ok, Ready};
-use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use actix_web::{
+    dev::Payload,
+    http::header::{self, HeaderName},
+    FromRequest, HttpRequest,
+};
 
-/// Host information.
+use crate::extract::TrustedProxies;
+
+const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+const X_FORWARDED_PORT: HeaderName = HeaderName::from_static("x-forwarded-port");
+
+/// Client-facing host information.
+///
+/// Resolves the scheme, hostname, and port that the client used to make the request, giving one
+/// canonical way to build absolute URLs, including when running behind reverse proxies.
+///
+/// When the request's peer is a [trusted proxy](TrustedProxies), the `Forwarded` header is used,
+/// falling back to the `X-Forwarded-Host`, `X-Forwarded-Proto`, and `X-Forwarded-Port` headers.
+/// Otherwise (or if the proxy sent no forwarding headers), the `Host` header or request URI's
+/// authority is used, and the scheme is determined by whether the connection is secure. As a last
+/// resort, the host configured on the server is used.
+///
+/// Since proxies append to forwarding headers that clients may have already set, only values
+/// added by trusted proxies are used. `Forwarded` elements are walked from right to left for as
+/// long as their `for` parameter is also a trusted proxy; the leftmost of these trusted elements
+/// that includes the parameter is used. For the `X-Forwarded-*` headers, which cannot be
+/// attributed to hops, only the rightmost value (added by the peer) is used.
 ///
-/// See [`ConnectionInfo::host()`](actix_web::dev::ConnectionInfo::host) for more.
+/// Unlike [`ConnectionInfo`](actix_web::dev::ConnectionInfo), forwarding headers are ignored
+/// unless a [`TrustedProxies`] is registered in app data and it includes the peer address.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::extract::Host;
+///
+/// #[get("/")]
+/// async fn handler(host: Host) -> impl Responder {
+///     format!("{}://{}/", host.scheme(), host.authority())
+/// }
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Host(String);
-
-impl_more::impl_as_ref!(Host => String);
-impl_more::impl_into!(Host => String);
-impl_more::forward_display!(Host);
+pub struct Host {
+    scheme: String,
+    hostname: String,
+    port: Option<u16>,
+}
 
 impl Host {
-    /// Unwraps into inner string value.
+    /// Resolves host information for `req`.
+    pub(crate) fn resolve(req: &HttpRequest) -> Self {
+        let mut scheme = None;
+        let mut authority = None;
+        let mut port = None;
+
+        if TrustedProxies::is_request_trusted(req) {
+            scheme = trusted_forwarded_param(req, "proto");
+            authority = trusted_forwarded_param(req, "host");
+
+            scheme = scheme.or_else(|| last_header_value(req, X_FORWARDED_PROTO));
+            authority = authority.or_else(|| last_header_value(req, X_FORWARDED_HOST));
+            port = last_header_value(req, X_FORWARDED_PORT).and_then(|port| port.parse().ok());
+        }
+
+        let scheme = scheme
+            .or_else(|| req.uri().scheme_str().map(str::to_owned))
+            .unwrap_or_else(|| match req.app_config().secure() {
+                true => "https".to_owned(),
+                false => "http".to_owned(),
+            })
+            .to_ascii_lowercase();
+
+        let authority = authority
+            .or_else(|| {
+                req.headers()
+                    .get(header::HOST)
+                    .and_then(|host| host.to_str().ok())
+                    .map(str::to_owned)
+            })
+            .or_else(|| req.uri().authority().map(|auth| auth.as_str().to_owned()))
+            .unwrap_or_else(|| req.app_config().host().to_owned());
+
+        let (hostname, authority_port) = split_authority(&authority);
+
+        Self {
+            scheme,
+            hostname: hostname.to_owned(),
+            port: authority_port.or(port),
+        }
+    }
+
+    /// Returns the scheme (e.g., `https`).
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the hostname, without port.
+    ///
+    /// IPv6 addresses are enclosed in square brackets.
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Returns the port, if one was specified explicitly.
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Returns the port, falling back to the default port for the scheme.
+    ///
+    /// Returns `None` if no port was specified and the scheme is not `http` or `https`.
+    pub fn port_or_default(&self) -> Option<u16> {
+        self.port.or(match self.scheme.as_str() {
+            "http" | "ws" => Some(80),
+            "https" | "wss" => Some(443),
+            _ => None,
+        })
+    }
+
+    /// Returns the hostname and, if it was specified explicitly, the port (e.g., `example.com` or
+    /// `localhost:8080`).
+    pub fn authority(&self) -> String {
+        self.to_string()
+    }
+
+    /// Unwraps into the [authority](Self::authority) string.
     pub fn into_inner(self) -> String {
-        self.0
+        self.authority()
+    }
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hostname)?;
+
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -26,7 +151,98 @@ impl FromRequest for Host {
 
     #[inline]
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        ok(Host(req.connection_info().host().to_owned()))
+        ok(Host::resolve(req))
+    }
+}
+
+/// Returns the last comma-separated value of a header, which is set by the nearest proxy.
+///
+/// Values further left may have been sent by the client.
+fn last_header_value(req: &HttpRequest, name: HeaderName) -> Option<String> {
+    let val = req.headers().get_all(name).last()?.to_str().ok()?;
+    let last = val.rsplit(',').next()?.trim();
+
+    (!last.is_empty()).then(|| last.to_owned())
+}
+
+/// Returns a parameter from the `Forwarded` header elements that were added by trusted proxies.
+///
+/// Assumes the request's peer is trusted, so the rightmost element is trusted. Each element's
+/// `for` parameter identifies the previous hop; while that is also a trusted proxy, the element to
+/// its left is trusted too. Of the trusted elements, the leftmost one with the parameter wins,
+/// since it is closest to the client.
+fn trusted_forwarded_param(req: &HttpRequest, name: &str) -> Option<String> {
+    let proxies = req.app_data::<TrustedProxies>()?;
+
+    let elements = req
+        .headers()
+        .get_all(header::FORWARDED)
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(|hdr| hdr.split(','))
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, val)| (key.trim(), unquote(val)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut found = None;
+
+    for element in elements.iter().rev() {
+        let param = |key: &str| {
+            element
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, val)| *val)
+        };
+
+        if let Some(val) = param(name).filter(|val| !val.is_empty()) {
+            found = Some(val);
+        }
+
+        let prev_hop_trusted = param("for")
+            .and_then(node_ip)
+            .is_some_and(|ip| proxies.is_trusted(ip));
+
+        if !prev_hop_trusted {
+            break;
+        }
+    }
+
+    found.map(str::to_owned)
+}
+
+/// Parses the IP address from a `Forwarded` node identifier, ignoring any port.
+fn node_ip(node: &str) -> Option<IpAddr> {
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+
+    node.split(':').next()?.parse().ok()
+}
+
+/// Trims whitespace then any quote marks.
+fn unquote(val: &str) -> &str {
+    val.trim().trim_start_matches('"').trim_end_matches('"')
+}
+
+/// Splits an authority into hostname and port.
+fn split_authority(authority: &str) -> (&str, Option<u16>) {
+    // IPv6 literals contain colons so only look for port after the closing bracket
+    let port_search_start = authority.rfind(']').unwrap_or(0);
+
+    match authority[port_search_start..].rfind(':') {
+        Some(idx) => {
+            let idx = port_search_start + idx;
+
+            match authority[idx + 1..].parse() {
+                Ok(port) => (&authority[..idx], Some(port)),
+                Err(_) => (authority, None),
+            }
+        }
+        None => (authority, None),
     }
 }
 
@@ -65,4 +281,104 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
         assert_eq!(test::read_body(res).await, b"localhost:8080".as_ref());
     }
+
+    fn proxied_req() -> TestRequest {
+        TestRequest::default()
+            .peer_addr("10.0.0.1:1234".parse().unwrap())
+            .insert_header(("host", "internal:8080"))
+            .insert_header(("x-forwarded-host", "example.com"))
+            .insert_header(("x-forwarded-proto", "https"))
+    }
+
+    #[test]
+    fn structured_parts() {
+        let host = Host::resolve(
+            &TestRequest::default()
+                .insert_header(("host", "example.com:8443"))
+                .to_http_request(),
+        );
+        assert_eq!(host.scheme(), "http");
+        assert_eq!(host.hostname(), "example.com");
+        assert_eq!(host.port(), Some(8443));
+        assert_eq!(host.authority(), "example.com:8443");
+
+        let host = Host::resolve(
+            &TestRequest::default()
+                .insert_header(("host", "[::1]"))
+                .to_http_request(),
+        );
+        assert_eq!(host.hostname(), "[::1]");
+        assert_eq!(host.port(), None);
+        assert_eq!(host.port_or_default(), Some(80));
+    }
+
+    #[test]
+    fn ignores_forwarding_headers_by_default() {
+        let host = Host::resolve(&proxied_req().to_http_request());
+        assert_eq!(host.scheme(), "http");
+        assert_eq!(host.authority(), "internal:8080");
+    }
+
+    #[test]
+    fn trusted_proxy() {
+        let proxies = TrustedProxies::new().trust("10.0.0.0/8");
+
+        let host = Host::resolve(&proxied_req().app_data(proxies.clone()).to_http_request());
+        assert_eq!(host.scheme(), "https");
+        assert_eq!(host.authority(), "example.com");
+        assert_eq!(host.port_or_default(), Some(443));
+
+        // forwarded header takes precedence
+        let req = proxied_req()
+            .insert_header(("forwarded", "host=fwd.example.com;proto=http"))
+            .insert_header(("x-forwarded-port", "8080"))
+            .app_data(proxies.clone())
+            .to_http_request();
+        let host = Host::resolve(&req);
+        assert_eq!(host.scheme(), "http");
+        assert_eq!(host.authority(), "fwd.example.com:8080");
+
+        // untrusted peer
+        let req = proxied_req()
+            .peer_addr("192.168.0.1:1234".parse().unwrap())
+            .app_data(proxies)
+            .to_http_request();
+        assert_eq!(Host::resolve(&req).authority(), "internal:8080");
+    }
+
+    #[test]
+    fn ignores_client_injected_values() {
+        let proxies = TrustedProxies::new().trust("10.0.0.0/8");
+
+        // client sent its own X-Forwarded-Host which the proxy appended to
+        let req = proxied_req()
+            .insert_header(("x-forwarded-host", "evil.com, example.com"))
+            .insert_header(("x-forwarded-proto", "http, https"))
+            .app_data(proxies.clone())
+            .to_http_request();
+        let host = Host::resolve(&req);
+        assert_eq!(host.scheme(), "https");
+        assert_eq!(host.authority(), "example.com");
+
+        // client sent its own Forwarded element which the proxy appended to
+        let req = proxied_req()
+            .insert_header((
+                "forwarded",
+                "for=10.0.0.9;host=evil.com;proto=http, for=1.2.3.4;host=example.com;proto=https",
+            ))
+            .app_data(proxies.clone())
+            .to_http_request();
+        let host = Host::resolve(&req);
+        assert_eq!(host.scheme(), "https");
+        assert_eq!(host.authority(), "example.com");
+
+        // chain of trusted proxies; element from the edge proxy wins
+        let req = proxied_req()
+            .append_header(("forwarded", "for=evil;host=evil.com"))
+            .append_header(("forwarded", "for=1.2.3.4;host=example.com"))
+            .append_header(("forwarded", "for=\"[fd00::1]:4711\";host=internal"))
+            .app_data(TrustedProxies::new().trust("10.0.0.0/8").trust("fd00::/8"))
+            .to_http_request();
+        assert_eq!(Host::resolve(&req).authority(), "example.com");
+    }
 }
@@ -0,0 +1,229 @@
+//! Idempotency-Key header.
+//!
+//! See [`IdempotencyKey`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Ready},
+    str::FromStr,
+    sync::Arc,
+};
+
+use actix_http::{
+    error::ParseError,
+    header::{Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+
+use crate::request_id::RequestId;
+
+/// The `Idempotency-Key` header name.
+#[allow(clippy::declare_interior_mutable_const)]
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// The `Idempotency-Key` header, defined in [draft-ietf-httpapi-idempotency-key-header].
+///
+/// Sent by clients on non-idempotent requests, such as `POST`s, so that servers can recognize
+/// retries of a request they have already processed and avoid repeating its side effects.
+///
+/// Keys are opaque tokens, though UUIDs are recommended; see [`generate()`](Self::generate). Keys
+/// must be between 1 and [`MAX_LEN`](Self::MAX_LEN) characters long and consist only of visible
+/// ASCII characters, excluding `"` and `\`. The draft specifies that the value is a structured
+/// field string (i.e., quoted), but since many clients send the key unquoted, both forms are
+/// accepted. The header is always sent quoted.
+///
+/// Can also be used as an extractor, in which case a missing or invalid header results in a
+/// `400 Bad Request` response. Use `Option<IdempotencyKey>` to allow the header to be absent.
+///
+/// # Example Values
+///
+/// - `"8e03978e-40d5-43e8-bc93-6894a57f9324"`
+/// - `order-42-attempt`
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::{post, HttpResponse};
+/// use actix_web_lab::header::IdempotencyKey;
+///
+/// #[post("/payments")]
+/// async fn create_payment(key: IdempotencyKey) -> HttpResponse {
+///     // look up previous response for `key.as_str()` before processing payment
+///     HttpResponse::Created().finish()
+/// }
+///
+/// // in clients or tests
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(IdempotencyKey::generate());
+/// ```
+///
+/// [draft-ietf-httpapi-idempotency-key-header]: https://datatracker.ietf.org/doc/draft-ietf-httpapi-idempotency-key-header/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(Arc<str>);
+
+impl IdempotencyKey {
+    /// Maximum length of keys, in bytes.
+    pub const MAX_LEN: usize = 255;
+
+    /// Generates a new key from a random, version 4 UUID.
+    pub fn generate() -> Self {
+        Self(Arc::from(RequestId::uuid().as_str()))
+    }
+
+    /// Returns the key as a string slice, without quotes.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the key is a UUID in its hyphenated form.
+    pub fn is_uuid(&self) -> bool {
+        let key = self.0.as_bytes();
+
+        key.len() == 36
+            && key.iter().enumerate().all(|(idx, &byte)| match idx {
+                8 | 13 | 18 | 23 => byte == b'-',
+                _ => byte.is_ascii_hexdigit(),
+            })
+    }
+}
+
+impl FromStr for IdempotencyKey {
+    type Err = ParseError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        if key.is_empty() || key.len() > Self::MAX_LEN {
+            return Err(ParseError::Header);
+        }
+
+        if !key
+            .bytes()
+            .all(|byte| matches!(byte, b' '..=b'~') && byte != b'"' && byte != b'\\')
+        {
+            return Err(ParseError::Header);
+        }
+
+        Ok(Self(Arc::from(key)))
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+impl TryIntoHeaderValue for IdempotencyKey {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for IdempotencyKey {
+    fn name() -> HeaderName {
+        IDEMPOTENCY_KEY
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        let mut values = msg.headers().get_all(Self::name());
+
+        let value = values.next().ok_or(ParseError::Header)?;
+
+        // a request with conflicting keys is ambiguous
+        if values.next().is_some() {
+            return Err(ParseError::Header);
+        }
+
+        let value = value.to_str().map_err(|_| ParseError::Header)?.trim();
+
+        let key = match value.strip_prefix('"') {
+            Some(quoted) => quoted.strip_suffix('"').ok_or(ParseError::Header)?,
+            None => value,
+        };
+
+        key.parse()
+    }
+}
+
+impl FromRequest for IdempotencyKey {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(IdempotencyKey::parse(req).map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod header_tests {
+    use actix_web::{http::StatusCode, test::TestRequest};
+
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    fn key(key: &str) -> IdempotencyKey {
+        key.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        assert_parse_eq::<IdempotencyKey, _, _>(
+            [r#""8e03978e-40d5-43e8-bc93-6894a57f9324""#],
+            key("8e03978e-40d5-43e8-bc93-6894a57f9324"),
+        );
+        assert_parse_eq::<IdempotencyKey, _, _>(["order-42"], key("order-42"));
+        assert_parse_eq::<IdempotencyKey, _, _>([" \"a b\" "], key("a b"));
+
+        assert_parse_fail::<IdempotencyKey, _, _>([""]);
+        assert_parse_fail::<IdempotencyKey, _, _>([r#""""#]);
+        assert_parse_fail::<IdempotencyKey, _, _>([r#""abc"#]);
+        assert_parse_fail::<IdempotencyKey, _, _>([r#""a"b""#]);
+        assert_parse_fail::<IdempotencyKey, _, _>([r"a\b"]);
+        assert_parse_fail::<IdempotencyKey, _, _>([b"caf\xc3\xa9".as_slice()]);
+        assert_parse_fail::<IdempotencyKey, _, _>(["a".repeat(256)]);
+        assert_parse_fail::<IdempotencyKey, _, _>(["a", "b"]);
+
+        assert!(IdempotencyKey::parse(&TestRequest::default().to_http_request()).is_err());
+        assert!("a".repeat(255).parse::<IdempotencyKey>().is_ok());
+    }
+
+    #[test]
+    fn generated_keys() {
+        let first = IdempotencyKey::generate();
+        let second = IdempotencyKey::generate();
+
+        assert!(first.is_uuid());
+        assert_ne!(first, second);
+        assert!(!key("order-42").is_uuid());
+
+        // round-trips through header value
+        let req = TestRequest::default()
+            .insert_header(first.clone())
+            .to_http_request();
+        assert_eq!(IdempotencyKey::parse(&req).unwrap(), first);
+        assert_eq!(
+            req.headers().get(IDEMPOTENCY_KEY).unwrap(),
+            format!("\"{}\"", first.as_str()).as_str(),
+        );
+    }
+
+    #[actix_web::test]
+    async fn extractor() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY, "abc"))
+            .to_http_parts();
+        let key = IdempotencyKey::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(key.as_str(), "abc");
+
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let err = IdempotencyKey::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
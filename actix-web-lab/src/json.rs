@@ -7,15 +7,15 @@ use std::{
     task::{ready, Context, Poll},
 };
 
-// #[cfg(feature = "__compress")]
-// use crate::dev::Decompress;
 use actix_web::{
-    dev::Payload, error::JsonPayloadError, http::header, web, FromRequest, HttpMessage, HttpRequest,
+    dev::Payload, error::JsonPayloadError, web, FromRequest, HttpMessage, HttpRequest,
 };
 use futures_core::Stream as _;
 use serde::de::DeserializeOwned;
 use tracing::debug;
 
+use crate::{decompress, lab_config::LabConfig};
+
 /// Default JSON payload size limit of 2MiB.
 pub const DEFAULT_JSON_LIMIT: usize = 2_097_152;
 
@@ -31,6 +31,10 @@ To extract typed data from a request body, the inner type `T` must implement the
 Use the `LIMIT` const generic parameter to control the payload size limit. The default limit
 that is exported (`DEFAULT_LIMIT`) is 2MiB.
 
+When the `decompress` crate feature is enabled, request bodies compressed using gzip, Brotli, or
+Zstandard (as indicated by their `Content-Encoding` header) are decompressed and the limit is
+applied to the decompressed size.
+
 ```
 use actix_web::{error, post, App, HttpRequest, HttpResponse, Responder};
 use actix_web_lab::extract::{Json, DEFAULT_JSON_LIMIT};
@@ -164,7 +168,7 @@ impl<T: DeserializeOwned, const LIMIT: usize> Future for JsonExtractFut<T, LIMIT
 ///
 /// Returns error if:
 /// - `Content-Type` is not `application/json`.
-/// - `Content-Length` is greater than `LIMIT`.
+/// - `Content-Length` is greater than the effective limit.
 /// - The payload, when consumed, is not valid JSON.
 pub enum JsonBody<T, const LIMIT: usize> {
     Error(Option<JsonPayloadError>),
@@ -172,18 +176,17 @@ pub enum JsonBody<T, const LIMIT: usize> {
         /// Length as reported by `Content-Length` header, if present.
         #[allow(dead_code)]
         length: Option<usize>,
-        // #[cfg(feature = "__compress")]
-        // payload: Decompress<Payload>,
-        // #[cfg(not(feature = "__compress"))]
         payload: Payload,
         buf: web::BytesMut,
+        /// Effective payload size limit.
+        limit: usize,
         _res: PhantomData<T>,
     },
 }
 
 impl<T, const LIMIT: usize> Unpin for JsonBody<T, LIMIT> {}
 
-impl<T: DeserializeOwned, const LIMIT: usize> JsonBody<T, LIMIT> {
+impl<T, const LIMIT: usize> JsonBody<T, LIMIT> {
     /// Create a new future to decode a JSON request payload.
     // #[allow(clippy::borrow_interior_mutable_const)]
     pub fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
@@ -198,28 +201,18 @@ impl<T: DeserializeOwned, const LIMIT: usize> JsonBody<T, LIMIT> {
             return JsonBody::Error(Some(JsonPayloadError::ContentType));
         }
 
-        let length = req
-            .headers()
-            .get(&header::CONTENT_LENGTH)
-            .and_then(|l| l.to_str().ok())
-            .and_then(|s| s.parse::<usize>().ok());
-
-        // maybe remove support for decompression at the extractor level for all extractors ?
-        let payload = {
-            // cfg_if::cfg_if! {
-            //     if #[cfg(feature = "__compress")] {
-            //         Decompress::from_headers(payload.take(), req.headers())
-            //     } else {
-            payload.take()
-            //     }
-            // }
-        };
+        let length = LabConfig::payload_length(req).filter(|_| !decompress::is_encoded(req));
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_JSON_LIMIT, LabConfig::JSON);
+        LabConfig::apply_to_payload(req, payload);
+
+        let payload = decompress::take_payload(req, payload);
 
         if let Some(len) = length {
-            if len > LIMIT {
+            if len > limit {
                 return JsonBody::Error(Some(JsonPayloadError::OverflowKnownLength {
                     length: len,
-                    limit: LIMIT,
+                    limit,
                 }));
             }
         }
@@ -227,38 +220,40 @@ impl<T: DeserializeOwned, const LIMIT: usize> JsonBody<T, LIMIT> {
         JsonBody::Body {
             length,
             payload,
-            buf: web::BytesMut::with_capacity(8192),
+            buf: web::BytesMut::with_capacity(LabConfig::buffer_capacity(req, length)),
+            limit,
             _res: PhantomData,
         }
     }
 }
 
-impl<T: DeserializeOwned, const LIMIT: usize> Future for JsonBody<T, LIMIT> {
-    type Output = Result<T, JsonPayloadError>;
-
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
-
-        match this {
-            JsonBody::Body { buf, payload, .. } => loop {
+impl<T, const LIMIT: usize> JsonBody<T, LIMIT> {
+    /// Polls payload to completion, returning the buffered (but not yet parsed) body.
+    pub(crate) fn poll_buffered(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<web::Bytes, JsonPayloadError>> {
+        match self {
+            JsonBody::Body {
+                buf,
+                payload,
+                limit,
+                ..
+            } => loop {
                 let res = ready!(Pin::new(&mut *payload).poll_next(cx));
 
                 match res {
                     Some(chunk) => {
                         let chunk = chunk?;
                         let buf_len = buf.len() + chunk.len();
-                        if buf_len > LIMIT {
-                            return Poll::Ready(Err(JsonPayloadError::Overflow { limit: LIMIT }));
+                        if buf_len > *limit {
+                            return Poll::Ready(Err(JsonPayloadError::Overflow { limit: *limit }));
                         } else {
                             buf.extend_from_slice(&chunk);
                         }
                     }
 
-                    None => {
-                        let json = serde_json::from_slice::<T>(buf)
-                            .map_err(JsonPayloadError::Deserialize)?;
-                        return Poll::Ready(Ok(json));
-                    }
+                    None => return Poll::Ready(Ok(buf.split().freeze())),
                 }
             },
 
@@ -267,6 +262,16 @@ impl<T: DeserializeOwned, const LIMIT: usize> Future for JsonBody<T, LIMIT> {
     }
 }
 
+impl<T: DeserializeOwned, const LIMIT: usize> Future for JsonBody<T, LIMIT> {
+    type Output = Result<T, JsonPayloadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let buf = ready!(self.get_mut().poll_buffered(cx))?;
+        let json = serde_json::from_slice::<T>(&buf).map_err(JsonPayloadError::Deserialize)?;
+        Poll::Ready(Ok(json))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::{http::header, test::TestRequest, web::Bytes};
@@ -0,0 +1,156 @@
+//! JSON extractor that supports borrowed deserialization.
+//!
+//! See [`JsonRef`] docs.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_web::{dev::Payload, error::JsonPayloadError, web::Bytes, FromRequest, HttpRequest};
+use serde::Deserialize;
+
+use crate::json::{JsonBody, DEFAULT_JSON_LIMIT};
+
+/// Buffered JSON payload that can be deserialized into types that borrow from it.
+///
+/// [`Json`](crate::extract::Json) requires its inner type to own all its data, so every string in
+/// the payload is copied into a new allocation. For large payloads in hot endpoints that only need
+/// temporary access to the data, `JsonRef` instead keeps the buffered body alive and lets handlers
+/// [`parse()`](Self::parse) it into types containing `&str` or `Cow<'_, str>` fields that point
+/// into the body.
+///
+/// Request validation is the same as `Json`: the `Content-Type` must be JSON and the body is
+/// limited to `LIMIT` bytes (2MiB by default). Since the target type is only chosen in the
+/// handler, the body itself is not parsed during extraction; parse errors are returned from
+/// [`parse()`](Self::parse) and respond with `400 Bad Request` if propagated with `?`.
+///
+/// Note that strings containing escape sequences cannot be borrowed; use `Cow<'_, str>` with
+/// `#[serde(borrow)]` for fields that may contain them.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::extract::JsonRef;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Event<'a> {
+///     kind: &'a str,
+///     tags: Vec<&'a str>,
+/// }
+///
+/// #[post("/events")]
+/// async fn ingest(body: JsonRef) -> actix_web::Result<impl Responder> {
+///     let event = body.parse::<Event<'_>>()?;
+///     Ok(format!(
+///         "{} event tagged {}",
+///         event.kind,
+///         event.tags.join(", ")
+///     ))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonRef<const LIMIT: usize = DEFAULT_JSON_LIMIT> {
+    body: Bytes,
+}
+
+impl<const LIMIT: usize> JsonRef<LIMIT> {
+    /// Deserializes the buffered body into a `T`, which may borrow from it.
+    pub fn parse<'a, T: Deserialize<'a>>(&'a self) -> Result<T, JsonPayloadError> {
+        serde_json::from_slice(&self.body).map_err(JsonPayloadError::Deserialize)
+    }
+
+    /// Returns the buffered, unparsed body.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Unwraps into the buffered, unparsed body.
+    pub fn into_bytes(self) -> Bytes {
+        self.body
+    }
+}
+
+impl<const LIMIT: usize> FromRequest for JsonRef<LIMIT> {
+    type Error = JsonPayloadError;
+    type Future = JsonRefExtractFut<LIMIT>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        JsonRefExtractFut {
+            body: JsonBody::new(req, payload),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct JsonRefExtractFut<const LIMIT: usize> {
+    body: JsonBody<(), LIMIT>,
+}
+
+impl<const LIMIT: usize> Future for JsonRefExtractFut<LIMIT> {
+    type Output = Result<JsonRef<LIMIT>, JsonPayloadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let body = ready!(self.get_mut().body.poll_buffered(cx))?;
+        Poll::Ready(Ok(JsonRef { body }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        #[serde(borrow)]
+        bio: Cow<'a, str>,
+    }
+
+    #[actix_web::test]
+    async fn borrows_from_body() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload(r#"{"name": "test", "bio": "line\nbreak"}"#)
+            .to_http_parts();
+
+        let body = JsonRef::<DEFAULT_JSON_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        let item = body.parse::<Borrowed<'_>>().unwrap();
+
+        assert_eq!(item.name, "test");
+        assert!(body.as_bytes().as_ptr_range().contains(&item.name.as_ptr()));
+        assert!(matches!(item.bio, Cow::Owned(ref bio) if bio == "line\nbreak"));
+
+        let err = body.parse::<Vec<u32>>().unwrap_err();
+        assert!(matches!(err, JsonPayloadError::Deserialize(_)));
+    }
+
+    #[actix_web::test]
+    async fn validates_request() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(r#"{"name": "test"}"#)
+            .to_http_parts();
+        let err = JsonRef::<DEFAULT_JSON_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JsonPayloadError::ContentType));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload(r#"{"name": "test"}"#)
+            .to_http_parts();
+        let err = JsonRef::<10>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JsonPayloadError::Overflow { limit: 10 }));
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    error::Error as StdError,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_web::{
+    body::{BodyStream, MessageBody},
+    HttpResponse, Responder,
+};
+use bytes::{BufMut as _, Bytes, BytesMut};
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use serde::Serialize;
+
+use crate::util::{InfallibleStream, MutWriter};
+
+pin_project! {
+    /// A streaming JSON array serializing body stream.
+    ///
+    /// Items are serialized one at a time into a single JSON array (e.g., `[{"a":1},{"a":2}]`),
+    /// with the brackets and commas emitted incrementally. Unlike
+    /// [`NdJson`](crate::respond::NdJson), the complete response is a standard JSON document, so
+    /// it can be consumed by any JSON client, while the server still avoids buffering the entire
+    /// response.
+    ///
+    /// If the stream yields an error, the response is aborted, leaving clients with an incomplete
+    /// (and therefore invalid) JSON document.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::Responder;
+    /// # use actix_web_lab::respond::JsonStream;
+    /// # use futures_core::Stream;
+    /// fn streaming_data_source() -> impl Stream<Item = serde_json::Value> {
+    ///     // get item stream from source
+    ///     # futures_util::stream::empty()
+    /// }
+    ///
+    /// async fn handler() -> impl Responder {
+    ///     let data_stream = streaming_data_source();
+    ///
+    ///     JsonStream::new_infallible(data_stream)
+    ///         .into_responder()
+    /// }
+    /// ```
+    pub struct JsonStream<S> {
+        // The wrapped item stream.
+        #[pin]
+        stream: S,
+    }
+}
+
+impl<S> JsonStream<S> {
+    /// Constructs a new `JsonStream` from a stream of items.
+    pub fn new(stream: S) -> Self {
+        Self { stream }
+    }
+}
+
+impl<S> JsonStream<S> {
+    /// Constructs a new `JsonStream` from an infallible stream of items.
+    pub fn new_infallible(stream: S) -> JsonStream<InfallibleStream<S>> {
+        JsonStream::new(InfallibleStream::new(stream))
+    }
+}
+
+impl<S, T, E> JsonStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Serialize,
+    E: Into<Box<dyn StdError>> + 'static,
+{
+    /// Creates a chunked body stream that serializes as a JSON array on-the-fly.
+    pub fn into_body_stream(self) -> impl MessageBody {
+        BodyStream::new(self.into_chunk_stream())
+    }
+
+    /// Creates a `Responder` type with a serializing stream and correct Content-Type header.
+    pub fn into_responder(self) -> impl Responder
+    where
+        S: 'static,
+        T: 'static,
+        E: 'static,
+    {
+        HttpResponse::Ok()
+            .content_type(mime::APPLICATION_JSON)
+            .message_body(self.into_body_stream())
+            .unwrap()
+    }
+
+    /// Creates a stream of serialized chunks.
+    pub fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, E>> {
+        JsonArrayStream {
+            stream: self.stream,
+            buf: BytesMut::new(),
+            started: false,
+            done: false,
+        }
+    }
+}
+
+pin_project! {
+    /// Serializes items as elements of a JSON array.
+    struct JsonArrayStream<S> {
+        #[pin]
+        stream: S,
+        buf: BytesMut,
+        // whether the opening bracket has been yielded
+        started: bool,
+        // whether the closing bracket has been yielded
+        done: bool,
+    }
+}
+
+impl<S, T, E> Stream for JsonArrayStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Serialize,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(item)) => {
+                this.buf.put_u8(if *this.started { b',' } else { b'[' });
+                *this.started = true;
+
+                serde_json::to_writer(MutWriter(this.buf), &item).unwrap();
+
+                Poll::Ready(Some(Ok(this.buf.split().freeze())))
+            }
+
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+
+            None => {
+                *this.done = true;
+
+                Poll::Ready(Some(Ok(Bytes::from_static(if *this.started {
+                    b"]"
+                } else {
+                    b"[]"
+                }))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error as StdError, io};
+
+    use actix_web::body;
+    use futures_util::stream;
+    use serde_json::json;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn serializes_into_body() {
+        let body = JsonStream::new_infallible(stream::iter(vec![
+            json!(null),
+            json!(1u32),
+            json!("123"),
+            json!({ "abc": "123" }),
+            json!(["abc", 123u32]),
+        ]))
+        .into_body_stream();
+
+        let body_bytes = body::to_bytes(body)
+            .await
+            .map_err(Into::<Box<dyn StdError>>::into)
+            .unwrap();
+
+        assert_eq!(body_bytes, r#"[null,1,"123",{"abc":"123"},["abc",123]]"#);
+
+        let parsed = serde_json::from_slice::<serde_json::Value>(&body_bytes).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 5);
+    }
+
+    #[actix_web::test]
+    async fn empty_stream() {
+        let body = JsonStream::new_infallible(stream::empty::<u32>()).into_body_stream();
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "[]");
+    }
+
+    #[actix_web::test]
+    async fn aborts_on_error() {
+        let body = JsonStream::new(stream::iter([
+            Ok(json!(1u32)),
+            Err(io::Error::other("db gone")),
+        ]))
+        .into_body_stream();
+
+        assert!(body::to_bytes(body).await.is_err());
+    }
+}
@@ -0,0 +1,378 @@
+//! Global extractor configuration.
+//!
+//! See [`LabConfig`] docs.
+
+use std::{fmt, sync::Arc};
+
+use actix_web::{dev, http::StatusCode, HttpMessage as _, HttpRequest, HttpResponse};
+
+use crate::{extract::PathDeserializeError, header::ContentLength};
+
+/// Default maximum preallocated body buffer capacity of 256KiB.
+const DEFAULT_MAX_PREALLOCATION: usize = 262_144;
+
+/// Initial body buffer capacity used when the payload size is not known up front.
+const UNKNOWN_LENGTH_CAPACITY: usize = 8_192;
+
+/// Application-wide defaults consulted by this crate's body extractors.
+///
+/// Register using [`App::app_data()`](actix_web::App::app_data) (or the scope or resource
+/// equivalents) to configure extractor behavior in one place instead of threading const generics
+/// through every handler signature.
+///
+/// Const generic limits still take precedence: a configured limit is only used when an extractor's
+/// `LIMIT` parameter is left as its default value. When no `LabConfig` is registered, extractors
+/// behave exactly as their const generic parameters describe.
+///
+/// Currently consulted by [`Json`], [`Bytes`], [`UrlEncodedForm`], [`Multipart`], [`BodyLimit`], and
+/// `QsForm`.
+///
+/// The response status codes used when [`Path`] and [`Query`] deserialization fails can also be
+/// configured, since conventions differ between APIs as to whether a malformed path segment means
+/// the resource was not found or that the request was bad.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::StatusCode, App};
+/// use actix_web_lab::extract::LabConfig;
+///
+/// App::new().app_data(
+///     LabConfig::new()
+///         .body_limit(1_048_576)
+///         .json_limit(65_536)
+///         .strict_content_length(true)
+///         .path_error_status(StatusCode::BAD_REQUEST),
+/// )
+/// # ;
+/// ```
+///
+/// [`Json`]: crate::extract::Json
+/// [`Bytes`]: crate::extract::Bytes
+/// [`UrlEncodedForm`]: crate::extract::UrlEncodedForm
+/// [`Multipart`]: crate::extract::Multipart
+/// [`BodyLimit`]: crate::extract::BodyLimit
+/// [`Path`]: crate::extract::Path
+/// [`Query`]: crate::extract::Query
+#[derive(Debug, Clone, Default)]
+pub struct LabConfig {
+    body_limit: Option<usize>,
+    json_limit: Option<usize>,
+    bytes_limit: Option<usize>,
+    url_encoded_form_limit: Option<usize>,
+    strict_content_length: bool,
+    max_preallocation: Option<usize>,
+    path_error_status: Option<StatusCode>,
+    path_error_handler: Option<PathErrorHandler>,
+    query_error_status: Option<StatusCode>,
+    query_semicolon_separators: bool,
+}
+
+impl LabConfig {
+    /// Constructs a new configuration with no overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the default payload size limit for all body extractors.
+    ///
+    /// Extractor-specific limits take precedence over this value.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = Some(limit);
+        self
+    }
+
+    /// Sets the default payload size limit for [`Json`](crate::extract::Json).
+    pub fn json_limit(mut self, limit: usize) -> Self {
+        self.json_limit = Some(limit);
+        self
+    }
+
+    /// Sets the default payload size limit for [`Bytes`](crate::extract::Bytes).
+    pub fn bytes_limit(mut self, limit: usize) -> Self {
+        self.bytes_limit = Some(limit);
+        self
+    }
+
+    /// Sets the default payload size limit for
+    /// [`UrlEncodedForm`](crate::extract::UrlEncodedForm).
+    pub fn url_encoded_form_limit(mut self, limit: usize) -> Self {
+        self.url_encoded_form_limit = Some(limit);
+        self
+    }
+
+    /// Sets whether body extractors reject payloads whose size does not match their
+    /// `Content-Length` header.
+    ///
+    /// See [`enforce_content_length()`](crate::util::enforce_content_length). Defaults to false.
+    pub fn strict_content_length(mut self, strict: bool) -> Self {
+        self.strict_content_length = strict;
+        self
+    }
+
+    /// Sets the maximum buffer capacity that body extractors allocate up front.
+    ///
+    /// Buffering extractors size their initial buffer from the request's `Content-Length` (once it
+    /// has been checked against their limit) so that bodies are usually read without reallocating.
+    /// This caps that initial allocation so that a large claimed length cannot reserve memory before
+    /// the body has actually arrived. Bodies without a known length start with an 8KiB buffer, or
+    /// this value if it is smaller. Buffers still grow as needed beyond this capacity.
+    ///
+    /// Defaults to 256KiB.
+    pub fn max_preallocation(mut self, capacity: usize) -> Self {
+        self.max_preallocation = Some(capacity);
+        self
+    }
+
+    /// Sets the response status code used when [`Path`](crate::extract::Path) deserialization
+    /// fails.
+    ///
+    /// Defaults to `404 Not Found`.
+    pub fn path_error_status(mut self, status: StatusCode) -> Self {
+        self.path_error_status = Some(status);
+        self
+    }
+
+    /// Sets a handler that builds the response used when [`Path`](crate::extract::Path)
+    /// deserialization fails, such as a JSON problem details document.
+    ///
+    /// The handler is called with the error and the request when the error is converted into a
+    /// response. The error's [status code](actix_web::ResponseError::status_code) is still
+    /// determined by [`path_error_status()`](Self::path_error_status).
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{App, HttpResponse};
+    /// use actix_web_lab::extract::LabConfig;
+    ///
+    /// App::new().app_data(LabConfig::new().path_error_handler(|err, req| {
+    ///     HttpResponse::NotFound().json(serde_json::json!({
+    ///         "title": "Invalid path",
+    ///         "detail": err.to_string(),
+    ///         "instance": req.path(),
+    ///     }))
+    /// }))
+    /// # ;
+    /// ```
+    pub fn path_error_handler(
+        mut self,
+        handler: impl Fn(&PathDeserializeError, &HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        self.path_error_handler = Some(PathErrorHandler(Arc::new(handler)));
+        self
+    }
+
+    /// Sets the response status code used when [`Query`](crate::extract::Query) deserialization
+    /// fails.
+    ///
+    /// Defaults to `422 Unprocessable Entity`.
+    pub fn query_error_status(mut self, status: StatusCode) -> Self {
+        self.query_error_status = Some(status);
+        self
+    }
+
+    /// Sets whether [`Query`](crate::extract::Query) also accepts `;` as a separator between query
+    /// parameters, as well as `&`.
+    ///
+    /// Some older clients and frameworks produce query strings such as `?a=1;b=2`. Encoded
+    /// semicolons (`%3B`) in keys and values are unaffected. Defaults to false.
+    pub fn query_semicolon_separators(mut self, enabled: bool) -> Self {
+        self.query_semicolon_separators = enabled;
+        self
+    }
+}
+
+type PathErrorHandlerFn = dyn Fn(&PathDeserializeError, &HttpRequest) -> HttpResponse + Send + Sync;
+
+/// Custom [`Path`](crate::extract::Path) error response builder.
+#[derive(Clone)]
+pub(crate) struct PathErrorHandler(pub(crate) Arc<PathErrorHandlerFn>);
+
+impl fmt::Debug for PathErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PathErrorHandler")
+    }
+}
+
+/// Request extension recording the length of a payload that replaced the original request body.
+///
+/// Inserted by extractor wrappers, such as [`Encrypted`](crate::extract::Encrypted), that feed a
+/// transformed payload to an inner extractor. While present, it takes the place of the request's
+/// `Content-Length` header, which still describes the original body.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PayloadLength(pub(crate) usize);
+
+/// Selects an extractor-specific limit from a config.
+pub(crate) type LimitSelector = fn(&LabConfig) -> Option<usize>;
+
+impl LabConfig {
+    pub(crate) const JSON: LimitSelector = |cfg| cfg.json_limit;
+    pub(crate) const BYTES: LimitSelector = |cfg| cfg.bytes_limit;
+    pub(crate) const URL_ENCODED_FORM: LimitSelector = |cfg| cfg.url_encoded_form_limit;
+    pub(crate) const BODY: LimitSelector = |_cfg| None;
+
+    /// Resolves the effective payload limit for an extractor.
+    ///
+    /// A non-default const generic `limit` always wins. Otherwise, the extractor-specific limit,
+    /// then the general body limit, from any registered config are used.
+    pub(crate) fn resolve_limit(
+        req: &HttpRequest,
+        limit: usize,
+        default_limit: usize,
+        select: LimitSelector,
+    ) -> usize {
+        if limit != default_limit {
+            return limit;
+        }
+
+        req.app_data::<Self>()
+            .and_then(|cfg| select(cfg).or(cfg.body_limit))
+            .unwrap_or(limit)
+    }
+
+    /// Resolves the initial buffer capacity for a body extractor given the payload's known length.
+    pub(crate) fn buffer_capacity(req: &HttpRequest, length: Option<usize>) -> usize {
+        let max = req
+            .app_data::<Self>()
+            .and_then(|cfg| cfg.max_preallocation)
+            .unwrap_or(DEFAULT_MAX_PREALLOCATION);
+
+        length.unwrap_or(UNKNOWN_LENGTH_CAPACITY).min(max)
+    }
+
+    /// Resolves the response status code for a [`Path`](crate::extract::Path) deserialization
+    /// error.
+    pub(crate) fn path_error_status_for(req: &HttpRequest) -> StatusCode {
+        req.app_data::<Self>()
+            .and_then(|cfg| cfg.path_error_status)
+            .unwrap_or(StatusCode::NOT_FOUND)
+    }
+
+    /// Returns the configured [`Path`](crate::extract::Path) error handler, if any.
+    pub(crate) fn path_error_handler_for(req: &HttpRequest) -> Option<PathErrorHandler> {
+        req.app_data::<Self>()
+            .and_then(|cfg| cfg.path_error_handler.clone())
+    }
+
+    /// Resolves the response status code for a [`Query`](crate::extract::Query) deserialization
+    /// error.
+    pub(crate) fn query_error_status_for(req: &HttpRequest) -> StatusCode {
+        req.app_data::<Self>()
+            .and_then(|cfg| cfg.query_error_status)
+            .unwrap_or(StatusCode::UNPROCESSABLE_ENTITY)
+    }
+
+    /// Resolves whether [`Query`](crate::extract::Query) accepts `;` separators.
+    pub(crate) fn query_semicolon_separators_for(req: &HttpRequest) -> bool {
+        req.app_data::<Self>()
+            .is_some_and(|cfg| cfg.query_semicolon_separators)
+    }
+
+    /// Returns the declared length of the payload that body extractors will read.
+    ///
+    /// This is the request's `Content-Length` unless a wrapping extractor has replaced the payload
+    /// (see [`PayloadLength`]).
+    pub(crate) fn payload_length(req: &HttpRequest) -> Option<usize> {
+        if let Some(PayloadLength(len)) = req.extensions().get::<PayloadLength>() {
+            return Some(*len);
+        }
+
+        req.get_header::<ContentLength>()
+            .map(|len| len.into_inner())
+    }
+
+    /// Applies any configured payload strictness checks to the request payload.
+    pub(crate) fn apply_to_payload(req: &HttpRequest, payload: &mut dev::Payload) {
+        let strict = req
+            .app_data::<Self>()
+            .is_some_and(|cfg| cfg.strict_content_length);
+
+        if strict {
+            if let Some(len) = Self::payload_length(req) {
+                crate::util::enforce_content_length(payload, len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        error::JsonPayloadError, http::header, test::TestRequest, web, FromRequest as _,
+    };
+    use serde_json::Value;
+
+    use super::*;
+    use crate::extract::{Bytes, Json, DEFAULT_JSON_LIMIT};
+
+    fn json_parts(cfg: LabConfig, body: &'static [u8]) -> (HttpRequest, dev::Payload) {
+        TestRequest::default()
+            .app_data(cfg)
+            .insert_header(header::ContentType::json())
+            .set_payload(web::Bytes::from_static(body))
+            .to_http_parts()
+    }
+
+    #[test]
+    fn limit_precedence() {
+        let req = TestRequest::default()
+            .app_data(LabConfig::new().body_limit(10).json_limit(20))
+            .to_http_request();
+
+        assert_eq!(
+            LabConfig::resolve_limit(&req, 100, 100, LabConfig::JSON),
+            20
+        );
+        assert_eq!(
+            LabConfig::resolve_limit(&req, 100, 100, LabConfig::BYTES),
+            10
+        );
+        assert_eq!(LabConfig::resolve_limit(&req, 5, 100, LabConfig::JSON), 5);
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(
+            LabConfig::resolve_limit(&req, 100, 100, LabConfig::JSON),
+            100
+        );
+    }
+
+    #[test]
+    fn buffer_capacity() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(LabConfig::buffer_capacity(&req, None), 8_192);
+        assert_eq!(LabConfig::buffer_capacity(&req, Some(16)), 16);
+        assert_eq!(LabConfig::buffer_capacity(&req, Some(1 << 30)), 262_144);
+
+        let req = TestRequest::default()
+            .app_data(LabConfig::new().max_preallocation(1_024))
+            .to_http_request();
+        assert_eq!(LabConfig::buffer_capacity(&req, None), 1_024);
+        assert_eq!(LabConfig::buffer_capacity(&req, Some(16)), 16);
+        assert_eq!(LabConfig::buffer_capacity(&req, Some(100_000)), 1_024);
+    }
+
+    #[actix_web::test]
+    async fn configured_json_limit() {
+        let (req, mut pl) = json_parts(LabConfig::new().json_limit(4), b"[1, 2, 3]");
+        let err = Json::<Value>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JsonPayloadError::Overflow { limit: 4 }));
+
+        // const generic takes precedence
+        let (req, mut pl) = json_parts(LabConfig::new().json_limit(4), b"[1, 2, 3]");
+        Json::<Value, { DEFAULT_JSON_LIMIT - 1 }>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn strict_content_length() {
+        let (req, mut pl) = TestRequest::default()
+            .app_data(LabConfig::new().strict_content_length(true))
+            .insert_header((header::CONTENT_LENGTH, 10))
+            .set_payload(web::Bytes::from_static(b"123"))
+            .to_http_parts();
+
+        Bytes::<100>::from_request(&req, &mut pl).await.unwrap_err();
+    }
+}
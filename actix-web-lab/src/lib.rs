@@ -22,62 +22,176 @@
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+#[cfg(feature = "url")]
+mod absolute_url;
+mod accepted;
+#[cfg(feature = "admin")]
+mod admin_scope;
+mod age;
+#[cfg(feature = "signature-schemes")]
+mod aws_sigv4;
 mod body_async_write;
+mod body_capture;
 mod body_channel;
+mod body_coalesce;
+#[cfg(any(feature = "compress-brotli", feature = "compress-zstd"))]
+mod body_compress;
 mod body_limit;
+#[cfg(feature = "send-file")]
+mod body_send_file;
+mod bulk_import;
 mod bytes;
 mod cache_control;
+mod cache_status;
+mod canonical_host;
 mod catch_panic;
 #[cfg(feature = "cbor")]
 mod cbor;
+#[cfg(feature = "cbor")]
+mod cbor_extract;
 mod clear_site_data;
+#[cfg(feature = "content-digest")]
+mod content_digest;
 mod content_length;
+mod content_sniff;
 mod csv;
+mod csv_stream;
+mod decompress;
 mod display_stream;
+mod download;
+#[cfg(feature = "encrypted")]
+mod encrypted;
 mod err_handler;
+mod error_pages;
+mod expect;
+mod expect_continue;
 mod forwarded;
+mod header_audit;
+mod header_limits;
 mod host;
+mod idempotency_key;
 mod infallible_body_stream;
 mod json;
+mod json_ref;
+mod json_stream;
+mod lab_config;
 mod lazy_data;
 mod load_shed;
+#[cfg(feature = "lab-bench")]
+mod load_test;
 mod local_data;
+mod log_failed_bodies;
+mod maintenance_mode;
+mod matched_path;
+mod method_guards;
 mod middleware_map_response;
 mod middleware_map_response_body;
+mod middleware_request_id;
+mod middleware_server_timing;
 #[cfg(feature = "msgpack")]
 mod msgpack;
+#[cfg(feature = "msgpack")]
+mod msgpack_extract;
+mod multipart;
 mod ndjson;
+mod ndjson_stream;
 mod normalize_path;
+mod normalize_query;
+mod openapi;
+mod optimistic_locking;
+mod ordered_middleware;
+#[cfg(feature = "url")]
+mod origin;
 mod panic_reporter;
 mod path;
+#[cfg(feature = "peer-cert")]
+mod peer_cert;
+mod problem;
+#[cfg(feature = "qs")]
+mod qs_form;
 mod query;
+mod range_reader;
+#[cfg(feature = "record")]
+mod record;
+mod redact_headers;
+#[cfg(feature = "url")]
+mod redirect_target;
 mod redirect_to_https;
 mod redirect_to_non_www;
 mod redirect_to_www;
+mod replayable_body;
+mod request_id;
 mod request_signature;
+mod request_trailers;
+mod respond_json;
+#[cfg(feature = "xml")]
+mod respond_xml;
+#[cfg(feature = "yaml")]
+mod respond_yaml;
+mod response_cache;
+#[cfg(feature = "rng")]
+mod rng;
+#[cfg(feature = "url")]
+mod same_origin_guard;
+#[cfg(feature = "serde-helpers")]
+mod serde_helpers;
+mod server_timing;
+mod shared_data;
+mod smuggling_guard;
 #[cfg(feature = "spa")]
 mod spa;
+mod sse_broadcaster;
+mod sse_event_store;
+mod stream_metrics;
+mod stream_timeout;
+mod strict_content_length;
 mod strict_transport_security;
 mod swap_data;
+mod test_diff;
 #[cfg(test)]
 mod test_header_macros;
+mod test_probe;
+#[cfg(feature = "record")]
+mod test_replay;
 mod test_request_macros;
 mod test_response_macros;
 mod test_services;
+mod test_snapshot;
+mod throttle;
+mod tls_policy;
+mod tracing_logger;
+mod trailers;
+mod trusted_proxies;
 mod url_encoded_form;
+mod validation_problem;
+#[cfg(feature = "signature-schemes")]
+mod webhook_signature;
+mod www_authenticate;
 mod x_forwarded_prefix;
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
 
 // public API
+#[cfg(feature = "lab-bench")]
+pub mod bench;
 pub mod body;
 pub mod extract;
 pub mod guard;
 pub mod header;
 pub mod middleware;
 pub mod respond;
+#[cfg(feature = "signature-schemes")]
+pub mod signature_schemes;
 pub mod sse;
 pub mod test;
+#[cfg(feature = "tower")]
+pub mod tower;
 pub mod util;
 pub mod web;
+#[cfg(feature = "ws")]
+pub mod ws;
 
 #[cfg(feature = "derive")]
 pub use actix_web_lab_derive::FromRequest;
@@ -87,6 +201,7 @@ pub use actix_web_lab_derive::FromRequest;
 pub mod __reexports {
     pub use ::actix_web;
     pub use ::futures_util;
+    pub use ::serde;
     pub use ::serde_json;
     pub use ::tokio;
     pub use ::tracing;
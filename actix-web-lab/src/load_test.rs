@@ -0,0 +1,349 @@
+//! In-process load testing harness.
+//!
+//! See [`LoadTest`] docs.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::{self, MessageBody},
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test::TestRequest,
+    Error,
+};
+use futures_util::{stream, StreamExt as _};
+
+/// Drives concurrent synthetic requests through an in-process service and reports latencies.
+///
+/// Requests are constructed by a closure, given the index of the request, and are sent to the
+/// service without going through a network socket. Each request's latency is measured from the
+/// service being called until its response body has been fully read. This makes `LoadTest` useful
+/// for comparing the overhead of middleware and handlers between changes, either from tests or
+/// from a benchmark harness like Criterion (using [`LatencyReport::elapsed()`] with its
+/// `iter_custom` API).
+///
+/// Since services run on a single thread, "concurrency" here means the number of requests that are
+/// in-flight at once, interleaved on the current thread.
+///
+/// # Examples
+/// ```
+/// use actix_web::{test, web, App};
+/// use actix_web_lab::bench::LoadTest;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let app = test::init_service(App::new().route("/", web::get().to(|| async { "hello" }))).await;
+///
+/// let report = LoadTest::new()
+///     .requests(1_000)
+///     .concurrency(16)
+///     .run(&app, |_idx| test::TestRequest::get())
+///     .await;
+///
+/// assert_eq!(report.completed(), 1_000);
+/// println!("{report}");
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadTest {
+    requests: usize,
+    concurrency: usize,
+    warmup: usize,
+}
+
+impl LoadTest {
+    /// Constructs new load test that sends 100 requests, 10 at a time, without warmup.
+    pub fn new() -> Self {
+        Self {
+            requests: 100,
+            concurrency: 10,
+            warmup: 0,
+        }
+    }
+
+    /// Sets the total number of measured requests to send.
+    pub fn requests(mut self, requests: usize) -> Self {
+        self.requests = requests;
+        self
+    }
+
+    /// Sets the maximum number of requests in-flight at once.
+    ///
+    /// # Panics
+    /// Panics if `concurrency` is zero.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "concurrency must be at least 1");
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Sets the number of requests to send, one at a time, before measurement starts.
+    ///
+    /// Warmup requests use indices after those of the measured requests.
+    pub fn warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Runs the load test against `app`, using `make_req` to construct each request.
+    pub async fn run<S, B, F>(&self, app: &S, make_req: F) -> LatencyReport
+    where
+        S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = Error>,
+        B: MessageBody,
+        F: Fn(usize) -> TestRequest,
+    {
+        for idx in 0..self.warmup {
+            let _ = send(app, make_req(self.requests + idx)).await;
+        }
+
+        let start = Instant::now();
+
+        let outcomes = stream::iter(0..self.requests)
+            .map(|idx| send(app, make_req(idx)))
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let elapsed = start.elapsed();
+
+        let mut report = LatencyReport {
+            samples: Vec::with_capacity(outcomes.len()),
+            statuses: BTreeMap::new(),
+            errors: 0,
+            elapsed,
+        };
+
+        for outcome in outcomes {
+            match outcome {
+                Some((status, latency)) => {
+                    report.samples.push(latency);
+                    *report.statuses.entry(status.as_u16()).or_default() += 1;
+                }
+                None => report.errors += 1,
+            }
+        }
+
+        report.samples.sort_unstable();
+        report
+    }
+}
+
+impl Default for LoadTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends a single request, returning its status and latency or `None` if it failed.
+async fn send<S, B>(app: &S, req: TestRequest) -> Option<(StatusCode, Duration)>
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    let req = req.to_request();
+    let start = Instant::now();
+
+    let res = app.call(req).await.ok()?;
+    let status = res.status();
+    body::to_bytes(res.into_body()).await.ok()?;
+
+    Some((status, start.elapsed()))
+}
+
+/// Latency distribution and outcome counts from a [`LoadTest`] run.
+///
+/// Percentiles are computed over requests that completed, including those with error statuses.
+/// Requests where the service or response body returned an error are only counted by
+/// [`errors()`](Self::errors).
+#[derive(Debug, Clone)]
+pub struct LatencyReport {
+    /// Sorted latencies of completed requests.
+    samples: Vec<Duration>,
+    statuses: BTreeMap<u16, usize>,
+    errors: usize,
+    elapsed: Duration,
+}
+
+impl LatencyReport {
+    /// Returns number of requests that completed with a response.
+    pub fn completed(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns number of requests where the service or response body returned an error.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Returns number of completed responses with the given status code.
+    pub fn status_count(&self, status: StatusCode) -> usize {
+        self.statuses
+            .get(&status.as_u16())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns wall-clock time taken to send all measured requests, excluding warmup.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns completed requests per second.
+    pub fn throughput(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+
+        self.completed() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Returns the fastest latency, or zero if no requests completed.
+    pub fn min(&self) -> Duration {
+        self.samples.first().copied().unwrap_or_default()
+    }
+
+    /// Returns the slowest latency, or zero if no requests completed.
+    pub fn max(&self) -> Duration {
+        self.samples.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the mean latency, or zero if no requests completed.
+    pub fn mean(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::ZERO,
+            len => self.samples.iter().sum::<Duration>() / len as u32,
+        }
+    }
+
+    /// Returns the latency at percentile `p` using the nearest-rank method, or zero if no requests
+    /// completed.
+    ///
+    /// # Panics
+    /// Panics if `p` is not in the range `0.0..=100.0`.
+    pub fn percentile(&self, p: f64) -> Duration {
+        assert!((0.0..=100.0).contains(&p), "percentile must be in 0..=100");
+
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let rank = (p / 100.0 * self.samples.len() as f64).ceil() as usize;
+        self.samples[rank.saturating_sub(1).min(self.samples.len() - 1)]
+    }
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} requests in {:?} ({:.1} req/s), {} errors",
+            self.completed(),
+            self.elapsed,
+            self.throughput(),
+            self.errors,
+        )?;
+
+        writeln!(
+            f,
+            "latency: min {:?}, mean {:?}, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+            self.min(),
+            self.mean(),
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(99.0),
+            self.max(),
+        )?;
+
+        write!(f, "statuses:")?;
+
+        for (status, count) in &self.statuses {
+            write!(f, " {status}={count}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        error,
+        test::{init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    fn report_of(millis: impl IntoIterator<Item = u64>) -> LatencyReport {
+        let mut samples = millis
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect::<Vec<_>>();
+        samples.sort_unstable();
+
+        LatencyReport {
+            samples,
+            statuses: BTreeMap::new(),
+            errors: 0,
+            elapsed: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let report = report_of(1..=100);
+
+        assert_eq!(report.min(), Duration::from_millis(1));
+        assert_eq!(report.max(), Duration::from_millis(100));
+        assert_eq!(report.percentile(0.0), Duration::from_millis(1));
+        assert_eq!(report.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(report.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(report.percentile(99.9), Duration::from_millis(100));
+        assert_eq!(report.mean(), Duration::from_micros(50_500));
+        assert_eq!(report.throughput(), 100.0);
+
+        let empty = report_of([]);
+        assert_eq!(empty.percentile(50.0), Duration::ZERO);
+        assert_eq!(empty.mean(), Duration::ZERO);
+    }
+
+    #[actix_web::test]
+    async fn counts_outcomes() {
+        let app = init_service(
+            App::new()
+                .route("/ok", web::get().to(HttpResponse::Ok))
+                .route("/teapot", web::get().to(HttpResponse::ImATeapot))
+                .route(
+                    "/err",
+                    web::get()
+                        .to(|| async { Err::<HttpResponse, _>(error::ErrorBadRequest("nope")) }),
+                ),
+        )
+        .await;
+
+        let report = LoadTest::new()
+            .requests(30)
+            .concurrency(4)
+            .warmup(3)
+            .run(&app, |idx| match idx % 3 {
+                0 => TestRequest::get().uri("/ok"),
+                1 => TestRequest::get().uri("/teapot"),
+                _ => TestRequest::get().uri("/err"),
+            })
+            .await;
+
+        assert_eq!(report.completed(), 30);
+        assert_eq!(report.errors(), 0);
+        assert_eq!(report.status_count(StatusCode::OK), 10);
+        assert_eq!(report.status_count(StatusCode::IM_A_TEAPOT), 10);
+        assert_eq!(report.status_count(StatusCode::BAD_REQUEST), 10);
+        assert!(report.min() <= report.percentile(50.0));
+        assert!(report.percentile(50.0) <= report.max());
+        assert!(report
+            .to_string()
+            .contains("statuses: 200=10 400=10 418=10"));
+    }
+}
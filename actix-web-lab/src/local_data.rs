@@ -4,7 +4,7 @@ use actix_utils::future::{err, ok, Ready};
 use actix_web::{dev::Payload, error, Error, FromRequest, HttpRequest};
 use tracing::debug;
 
-/// A thread-local equivalent to [`SharedData`](crate::extract::SharedData).
+/// A thread-local equivalent to [`SharedData`](crate::web::SharedData).
 #[doc(alias = "state")]
 #[derive(Debug)]
 pub struct LocalData<T: ?Sized>(Rc<T>);
@@ -0,0 +1,237 @@
+//! Request body logging for debugging extractor failures.
+//!
+//! See [`LogFailedBodies`] docs.
+
+use std::{
+    fmt::Write as _,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use futures_core::future::LocalBoxFuture;
+
+use crate::body_capture::capture_request_body;
+
+/// Default number of request body bytes captured for logging.
+const DEFAULT_CAPTURE_LIMIT: usize = 1024;
+
+/// Middleware that logs the start of request bodies that fail extraction.
+///
+/// When enabled, the first bytes of each request body are copied as they are read by extractors
+/// (at most 1 KiB; see [`capture_limit()`](Self::capture_limit)). If the request then results in a
+/// client error response caused by an error, such as a [`Json`](crate::extract::Json) payload that
+/// fails to deserialize, the captured bytes are logged at `WARN` level along with the error and
+/// the matched route, making it much easier to diagnose `400` and `422` responses in production.
+///
+/// Bytes are logged exactly: printable ASCII is logged as-is, while other bytes, including
+/// non-ASCII UTF-8, are hex-escaped (e.g., `\xe2\x82\xac`). Bodies may contain sensitive data, so
+/// consider only enabling this middleware while investigating a problem.
+///
+/// Only the part of the body that was read by the failing extractor is captured; bodies are still
+/// passed to extractors in full.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::LogFailedBodies;
+///
+/// App::new().wrap(
+///     LogFailedBodies::new()
+///         .enabled(std::env::var_os("DEBUG_BODIES").is_some())
+///         .capture_limit(256),
+/// )
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogFailedBodies {
+    enabled: bool,
+    capture_limit: usize,
+}
+
+impl LogFailedBodies {
+    /// Constructs new, enabled, failed body logging middleware.
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            capture_limit: DEFAULT_CAPTURE_LIMIT,
+        }
+    }
+
+    /// Sets whether request bodies are captured and logged.
+    ///
+    /// When disabled, the middleware does nothing. Defaults to true.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets the maximum number of request body bytes captured and logged.
+    ///
+    /// Defaults to 1024.
+    pub fn capture_limit(mut self, limit: usize) -> Self {
+        self.capture_limit = limit;
+        self
+    }
+}
+
+impl Default for LogFailedBodies {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for LogFailedBodies
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Transform = LogFailedBodiesMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LogFailedBodiesMiddleware {
+            service: Rc::new(service),
+            enabled: self.enabled,
+            capture_limit: self.capture_limit,
+        }))
+    }
+}
+
+/// Middleware service implementation for [`LogFailedBodies`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct LogFailedBodiesMiddleware<S> {
+    service: Rc<S>,
+    enabled: bool,
+    capture_limit: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for LogFailedBodiesMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        if !self.enabled {
+            return Box::pin(async move { service.call(req).await });
+        }
+
+        let capture = capture_request_body(&mut req, self.capture_limit);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            if res.status().is_client_error() {
+                if let Some(err) = res.response().error() {
+                    let capture = capture.borrow();
+
+                    tracing::warn!(
+                        "Request body failed extraction. \
+                        Request path: \"{}\". \
+                        Status: {}. \
+                        Error: {}. \
+                        Body{}: \"{}\".",
+                        res.request()
+                            .match_pattern()
+                            .as_deref()
+                            .unwrap_or(res.request().path()),
+                        res.status().as_u16(),
+                        err,
+                        if capture.is_truncated() {
+                            " (truncated)"
+                        } else {
+                            ""
+                        },
+                        escape_body(capture.bytes()),
+                    );
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// Escapes body bytes for logging, hex-escaping all but printable ASCII.
+fn escape_body(body: &[u8]) -> String {
+    let mut escaped = String::with_capacity(body.len());
+
+    for &byte in body {
+        match byte {
+            b'\n' => escaped.push_str("\\n"),
+            b'\r' => escaped.push_str("\\r"),
+            b'\t' => escaped.push_str("\\t"),
+            b'"' => escaped.push_str("\\\""),
+            b'\\' => escaped.push_str("\\\\"),
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => {
+                let _ = write!(escaped, "\\x{byte:02x}");
+            }
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::extract::Json;
+
+    #[derive(Deserialize)]
+    struct Item {
+        qty: u32,
+    }
+
+    #[test]
+    fn escapes_bytes() {
+        assert_eq!(escape_body(b""), "");
+        assert_eq!(escape_body(b"{\"a\": 1}\n"), r#"{\"a\": 1}\n"#);
+        assert_eq!(escape_body("€\0\\".as_bytes()), r"\xe2\x82\xac\x00\\");
+    }
+
+    #[actix_web::test]
+    async fn body_still_reaches_extractor() {
+        let app = init_service(
+            App::new()
+                .wrap(LogFailedBodies::new().capture_limit(4))
+                .route(
+                    "/",
+                    web::post().to(|item: Json<Item>| async move { item.qty.to_string() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .set_json(serde_json::json!({ "qty": 42 }))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::post()
+            .set_json(serde_json::json!({ "qty": "lots" }))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert!(res.status().is_client_error());
+        assert!(res.response().error().is_some());
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use actix_web::{
+    body::{BoxBody, EitherBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderValue},
+    web::Bytes,
+    HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+use mime::Mime;
+
+/// Default `Retry-After` duration of 60 seconds.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Runtime switch for [`MaintenanceMode`].
+///
+/// Cloning produces a handle to the same switch, so it can be kept in app data (e.g., for an admin
+/// endpoint) or moved to another thread.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceHandle {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceHandle {
+    /// Constructs a new handle with maintenance mode disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables maintenance mode.
+    pub fn enable(&self) {
+        self.set(true);
+    }
+
+    /// Disables maintenance mode.
+    pub fn disable(&self) {
+        self.set(false);
+    }
+
+    /// Enables or disables maintenance mode.
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns true if maintenance mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// Middleware to respond with `503 Service Unavailable` while maintenance mode is enabled.
+///
+/// Maintenance mode is toggled at runtime using a [`MaintenanceHandle`]. While enabled, requests
+/// are answered with a `503 Service Unavailable` response including a `Retry-After` header and,
+/// optionally, a static body. Requests whose paths start with one of the prefixes added using
+/// [`allow_path_prefix()`](Self::allow_path_prefix) (e.g., health checks and admin endpoints) are
+/// passed through.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// use actix_web_lab::middleware::{MaintenanceHandle, MaintenanceMode};
+///
+/// let handle = MaintenanceHandle::new();
+///
+/// let mw = MaintenanceMode::from_handle(handle.clone())
+///     .retry_after(Duration::from_secs(300))
+///     .body(mime::TEXT_HTML_UTF_8, "<h1>Down for maintenance</h1>")
+///     .allow_path_prefix("/health")
+///     .allow_path_prefix("/admin");
+///
+/// App::new().app_data(handle).wrap(mw)
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    handle: MaintenanceHandle,
+    retry_after: Duration,
+    body: Option<(Mime, Bytes)>,
+    allowed: Vec<String>,
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::from_handle(MaintenanceHandle::new())
+    }
+}
+
+impl MaintenanceMode {
+    /// Constructs new maintenance mode middleware, initially disabled.
+    ///
+    /// Use [`handle()`](Self::handle) to obtain a handle for toggling it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs new maintenance mode middleware controlled by `handle`.
+    pub fn from_handle(handle: MaintenanceHandle) -> Self {
+        Self {
+            handle,
+            retry_after: DEFAULT_RETRY_AFTER,
+            body: None,
+            allowed: Vec::new(),
+        }
+    }
+
+    /// Returns a handle for toggling maintenance mode.
+    pub fn handle(&self) -> MaintenanceHandle {
+        self.handle.clone()
+    }
+
+    /// Sets the duration sent in the `Retry-After` header, rounded down to whole seconds.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Sets a static body, with the given content type, for maintenance responses.
+    ///
+    /// By default, responses have an empty body.
+    pub fn body(mut self, content_type: Mime, body: impl Into<Bytes>) -> Self {
+        self.body = Some((content_type, body.into()));
+        self
+    }
+
+    /// Allows requests whose paths start with `prefix` to pass through during maintenance.
+    ///
+    /// Can be called multiple times to allow several prefixes.
+    pub fn allow_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.allowed.push(prefix.into());
+        self
+    }
+
+    fn maintenance_response(&self) -> HttpResponse {
+        let mut res = HttpResponse::ServiceUnavailable();
+        res.insert_header((
+            header::RETRY_AFTER,
+            HeaderValue::from(self.retry_after.as_secs()),
+        ));
+
+        match &self.body {
+            Some((content_type, body)) => res.content_type(content_type.clone()).body(body.clone()),
+            None => res.finish(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MaintenanceMode
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = S::Error;
+    type Transform = MaintenanceModeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceModeMiddleware {
+            service: Rc::new(service),
+            config: Rc::new(self.clone()),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`MaintenanceMode`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct MaintenanceModeMiddleware<S> {
+    service: Rc<S>,
+    config: Rc<MaintenanceMode>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceModeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let config = &self.config;
+
+        let in_maintenance = config.handle.is_enabled()
+            && !config
+                .allowed
+                .iter()
+                .any(|prefix| req.path().starts_with(prefix.as_str()));
+
+        if in_maintenance {
+            let res = config.maintenance_response().map_into_right_body();
+            return Box::pin(ready(Ok(req.into_response(res))));
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn toggles_at_runtime() {
+        let mw = MaintenanceMode::new()
+            .retry_after(Duration::from_secs(120))
+            .body(mime::TEXT_PLAIN_UTF_8, "back soon")
+            .allow_path_prefix("/health");
+        let handle = mw.handle();
+
+        let app = init_service(
+            App::new()
+                .wrap(mw)
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        handle.enable();
+        assert!(handle.is_enabled());
+
+        let res = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "120");
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8",
+        );
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.ok().unwrap(),
+            "back soon"
+        );
+
+        let res = call_service(&app, TestRequest::with_uri("/health").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        handle.disable();
+
+        let res = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn shared_handle() {
+        let handle = MaintenanceHandle::new();
+        handle.enable();
+
+        let app = init_service(
+            App::new()
+                .wrap(MaintenanceMode::from_handle(handle.clone()))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "60");
+        assert!(res.headers().get(header::CONTENT_TYPE).is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//! Matched route pattern extractor.
+//!
+//! See [`MatchedPath`] docs.
+
+use std::{fmt, sync::Arc};
+
+use actix_utils::future::{err, ok, Ready};
+use actix_web::{dev::Payload, error, Error, FromRequest, HttpMessage as _, HttpRequest};
+use tracing::debug;
+
+/// Pattern of the route that matched the current request.
+///
+/// Returns the route pattern (e.g., `/users/{id}`) instead of the request path (e.g.,
+/// `/users/42`), making it suitable as a low-cardinality label for metrics and logs. Trailing
+/// slashes are removed so that routes like `/users/` and `/users` produce the same label.
+///
+/// Cheap to clone. Extraction fails with a `500 Internal Server Error` if the request did not
+/// match a route, which is only the case for default services.
+///
+/// [`TracingLogger`](crate::middleware::TracingLogger) and
+/// [`CatchPanic`](crate::middleware::CatchPanic) use this as their source of route labels.
+///
+/// # Examples
+/// ```
+/// use actix_web::get;
+/// use actix_web_lab::extract::MatchedPath;
+///
+/// #[get("/users/{id}/")]
+/// async fn user(path: MatchedPath) -> String {
+///     // e.g., for a request to `/users/42/`
+///     format!("matched {path}") // "matched /users/{id}"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchedPath(Arc<str>);
+
+impl MatchedPath {
+    /// Constructs a matched path from a route pattern, removing any trailing slashes.
+    pub(crate) fn from_pattern(pattern: &str) -> Self {
+        match pattern.trim_end_matches('/') {
+            "" => Self(Arc::from("/")),
+            pattern => Self(Arc::from(pattern)),
+        }
+    }
+
+    /// Returns the matched path of `req`, if it matched a route.
+    ///
+    /// The result is cached in the request's extensions.
+    pub(crate) fn for_request(req: &HttpRequest) -> Option<Self> {
+        if let Some(path) = req.extensions().get::<Self>() {
+            return Some(path.clone());
+        }
+
+        let path = Self::from_pattern(&req.match_pattern()?);
+        req.extensions_mut().insert(path.clone());
+        Some(path)
+    }
+
+    /// Returns the matched path as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for MatchedPath {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for MatchedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for MatchedPath {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        match Self::for_request(req) {
+            Some(path) => ok(path),
+
+            None => {
+                debug!(
+                    "Failed to extract `MatchedPath` for request to `{}`; no route was matched.",
+                    req.path()
+                );
+
+                err(error::ErrorInternalServerError(
+                    "Request did not match a route.",
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_and_read_body, call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn normalizes_trailing_slashes() {
+        assert_eq!(
+            MatchedPath::from_pattern("/users/{id}").as_str(),
+            "/users/{id}"
+        );
+        assert_eq!(
+            MatchedPath::from_pattern("/users/{id}/").as_str(),
+            "/users/{id}"
+        );
+        assert_eq!(MatchedPath::from_pattern("/users//").as_str(), "/users");
+        assert_eq!(MatchedPath::from_pattern("/").as_str(), "/");
+        assert_eq!(MatchedPath::from_pattern("").as_str(), "/");
+    }
+
+    #[actix_web::test]
+    async fn extracts_route_pattern() {
+        let app = init_service(
+            App::new()
+                .service(web::scope("/api").route(
+                    "/users/{id}/",
+                    web::get().to(|path: MatchedPath| async move { path.to_string() }),
+                ))
+                .default_service(web::to(|path: Option<MatchedPath>| async move {
+                    format!("{path:?}")
+                })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/api/users/42/").to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "/api/users/{id}");
+
+        let req = TestRequest::with_uri("/unknown").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "None");
+    }
+}
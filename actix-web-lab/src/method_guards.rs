@@ -0,0 +1,178 @@
+//! Method-based route guards.
+//!
+//! See [`safe_methods`], [`idempotent_methods`], and [`not_method`] docs.
+
+use actix_web::{
+    guard::{Guard, GuardContext},
+    http::Method,
+};
+
+/// Methods defined as "safe" (read-only) by [RFC 9110 §9.2.1].
+///
+/// [RFC 9110 §9.2.1]: https://datatracker.ietf.org/doc/html/rfc9110#section-9.2.1
+const SAFE_METHODS: &[Method] = &[Method::GET, Method::HEAD, Method::OPTIONS, Method::TRACE];
+
+/// Methods defined as idempotent by [RFC 9110 §9.2.2].
+///
+/// [RFC 9110 §9.2.2]: https://datatracker.ietf.org/doc/html/rfc9110#section-9.2.2
+const IDEMPOTENT_METHODS: &[Method] = &[
+    Method::GET,
+    Method::HEAD,
+    Method::OPTIONS,
+    Method::TRACE,
+    Method::PUT,
+    Method::DELETE,
+];
+
+/// Returns true if `method` is considered safe (`GET`, `HEAD`, `OPTIONS`, or `TRACE`).
+fn is_safe_method(method: &Method) -> bool {
+    SAFE_METHODS.contains(method)
+}
+
+/// Returns true if `method` is considered idempotent (safe methods plus `PUT` and `DELETE`).
+fn is_idempotent_method(method: &Method) -> bool {
+    IDEMPOTENT_METHODS.contains(method)
+}
+
+/// Creates a guard that matches requests using a safe method (`GET`, `HEAD`, `OPTIONS`, or `TRACE`).
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_lab::guard;
+///
+/// App::new().route(
+///     "/",
+///     web::route()
+///         .guard(guard::safe_methods())
+///         .to(|| async { HttpResponse::Ok() }),
+/// );
+/// ```
+pub fn safe_methods() -> impl Guard {
+    MethodSetGuard(is_safe_method)
+}
+
+/// Creates a guard that matches requests using an idempotent method.
+///
+/// Idempotent methods are the [safe methods](safe_methods) plus `PUT` and `DELETE`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_lab::guard;
+///
+/// App::new().route(
+///     "/",
+///     web::route()
+///         .guard(guard::idempotent_methods())
+///         .to(|| async { HttpResponse::Ok() }),
+/// );
+/// ```
+pub fn idempotent_methods() -> impl Guard {
+    MethodSetGuard(is_idempotent_method)
+}
+
+/// Creates a guard that matches requests using any method except `method`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::Method, web, App, HttpResponse};
+/// use actix_web_lab::guard;
+///
+/// App::new().route(
+///     "/",
+///     web::route()
+///         .guard(guard::not_method(Method::DELETE))
+///         .to(|| async { HttpResponse::Ok() }),
+/// );
+/// ```
+pub fn not_method(method: Method) -> impl Guard {
+    NotMethodGuard(method)
+}
+
+#[derive(Debug)]
+struct MethodSetGuard(fn(&Method) -> bool);
+
+impl Guard for MethodSetGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        (self.0)(&ctx.head().method)
+    }
+}
+
+#[derive(Debug)]
+struct NotMethodGuard(Method);
+
+impl Guard for NotMethodGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        ctx.head().method != self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn check(guard: &impl Guard, method: Method) -> bool {
+        let req = TestRequest::default().method(method).to_srv_request();
+        guard.check(&req.guard_ctx())
+    }
+
+    #[test]
+    fn safe() {
+        let guard = safe_methods();
+
+        assert!(check(&guard, Method::GET));
+        assert!(check(&guard, Method::HEAD));
+        assert!(check(&guard, Method::OPTIONS));
+        assert!(check(&guard, Method::TRACE));
+        assert!(!check(&guard, Method::POST));
+        assert!(!check(&guard, Method::PUT));
+        assert!(!check(&guard, Method::DELETE));
+        assert!(!check(&guard, Method::PATCH));
+    }
+
+    #[test]
+    fn idempotent() {
+        let guard = idempotent_methods();
+
+        assert!(check(&guard, Method::GET));
+        assert!(check(&guard, Method::HEAD));
+        assert!(check(&guard, Method::OPTIONS));
+        assert!(check(&guard, Method::TRACE));
+        assert!(check(&guard, Method::PUT));
+        assert!(check(&guard, Method::DELETE));
+        assert!(!check(&guard, Method::POST));
+        assert!(!check(&guard, Method::PATCH));
+    }
+
+    #[test]
+    fn not() {
+        let guard = not_method(Method::POST);
+
+        assert!(check(&guard, Method::GET));
+        assert!(check(&guard, Method::PUT));
+        assert!(!check(&guard, Method::POST));
+    }
+
+    #[test]
+    fn helpers_agree_with_guards() {
+        for method in [
+            Method::GET,
+            Method::TRACE,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+        ] {
+            assert_eq!(
+                is_safe_method(&method),
+                check(&safe_methods(), method.clone())
+            );
+            assert_eq!(
+                is_idempotent_method(&method),
+                check(&idempotent_methods(), method.clone()),
+            );
+        }
+    }
+}
@@ -2,15 +2,39 @@
 //!
 //! Analogous to the `middleware` module in Actix Web.
 
+#[cfg(feature = "peer-cert")]
+pub use crate::peer_cert::load_peer_cert;
+#[cfg(feature = "record")]
+pub use crate::record::{
+    Record, RecordSink, RecordedBody, RecordedRequest, RecordedResponse, Recording, RecordingFile,
+};
+#[doc(inline)]
+pub use crate::tracing_logger::request_span;
 pub use crate::{
-    catch_panic::CatchPanic,
+    canonical_host::CanonicalHost,
+    catch_panic::{CatchPanic, PanicContext, PanicCounters, PanicStats},
     err_handler::ErrorHandlers,
+    error_pages::{ErrorInfo, ErrorPages},
+    header_limits::{HeaderLimitExceeded, HeaderLimits},
     load_shed::LoadShed,
+    log_failed_bodies::LogFailedBodies,
+    maintenance_mode::{MaintenanceHandle, MaintenanceMode},
     middleware_map_response::{map_response, MapResMiddleware},
     middleware_map_response_body::{map_response_body, MapResBodyMiddleware},
+    middleware_request_id::RequestId,
+    middleware_server_timing::{ServerTiming, ServerTimingRecorder},
     normalize_path::NormalizePath,
+    normalize_query::NormalizeQuery,
+    optimistic_locking::{InMemoryVersionStore, OptimisticLocking, VersionStore},
+    ordered_middleware::{OrderedMiddleware, OrderedService},
     panic_reporter::PanicReporter,
     redirect_to_https::RedirectHttps,
     redirect_to_non_www::redirect_to_non_www,
     redirect_to_www::redirect_to_www,
+    response_cache::ResponseCache,
+    smuggling_guard::SmugglingGuard,
+    stream_timeout::StreamTimeout,
+    throttle::Throttle,
+    tls_policy::{load_tls_info, TlsPolicy},
+    tracing_logger::TracingLogger,
 };
@@ -0,0 +1,258 @@
+//! Request ID middleware.
+//!
+//! See [`RequestId`] docs.
+
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    HttpMessage as _,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::Instrument as _;
+
+use crate::extract::RequestId as RequestIdExtractor;
+
+/// Maximum length of request IDs accepted from clients.
+const MAX_INCOMING_LEN: usize = 128;
+
+/// Middleware for assigning an identifier to each request.
+///
+/// Requests are assigned a random, version 4 UUID (or, [optionally](Self::ulid), a ULID). If the
+/// request already carries an ID in the `X-Request-Id` header (e.g., from a load balancer), it is
+/// propagated instead, as long as it is at most 128 visible ASCII characters long. The ID is:
+/// - stored in request extensions, so it can be read using the
+///   [`RequestId`](crate::extract::RequestId) extractor;
+/// - set as the `X-Request-Id` header on the response;
+/// - recorded in the `request_id` field of the current [tracing span], if it declares one; and
+/// - attached to a `request_id` span that the wrapped service runs within, so events logged by
+///   handlers include it.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, App};
+/// use actix_web_lab::{extract, middleware::RequestId};
+///
+/// #[get("/")]
+/// async fn index(request_id: extract::RequestId) -> String {
+///     format!("your request ID is {request_id}")
+/// }
+///
+/// App::new().wrap(RequestId::new()).service(index)
+/// # ;
+/// ```
+///
+/// [tracing span]: tracing::Span
+#[derive(Debug, Clone)]
+pub struct RequestId {
+    header: HeaderName,
+    ulid: bool,
+    propagate: bool,
+}
+
+impl RequestId {
+    /// Constructs new request ID middleware that generates UUIDs and uses the `X-Request-Id`
+    /// header.
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+            ulid: false,
+            propagate: true,
+        }
+    }
+
+    /// Sets the header used to read incoming IDs and to send IDs in responses.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Generates ULIDs instead of UUIDs, which are shorter and sort by creation time.
+    pub fn ulid(mut self) -> Self {
+        self.ulid = true;
+        self
+    }
+
+    /// Sets whether IDs sent by clients are propagated.
+    ///
+    /// Enabled by default. Disable this if requests do not pass through a trusted proxy that sets
+    /// the header, to prevent clients from choosing their own IDs.
+    pub fn propagate(mut self, propagate: bool) -> Self {
+        self.propagate = propagate;
+        self
+    }
+
+    fn request_id(&self, req: &ServiceRequest) -> RequestIdExtractor {
+        let incoming = self
+            .propagate
+            .then(|| req.headers().get(&self.header))
+            .flatten()
+            .and_then(|val| val.to_str().ok())
+            .filter(|val| {
+                !val.is_empty()
+                    && val.len() <= MAX_INCOMING_LEN
+                    && val.bytes().all(|b| b.is_ascii_graphic())
+            });
+
+        match incoming {
+            Some(id) => RequestIdExtractor::new(id),
+            None if self.ulid => RequestIdExtractor::ulid(),
+            None => RequestIdExtractor::uuid(),
+        }
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware {
+            service: Rc::new(service),
+            config: Rc::new(self.clone()),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`RequestId`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct RequestIdMiddleware<S> {
+    service: Rc<S>,
+    config: Rc<RequestId>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let config = Rc::clone(&self.config);
+
+        let id = config.request_id(&req);
+        req.extensions_mut().insert(id.clone());
+
+        tracing::Span::current().record("request_id", id.as_str());
+        let span = tracing::info_span!("request_id", request_id = id.as_str());
+
+        let fut = self.service.call(req).instrument(span);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            // generated and validated IDs are always valid header values
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                res.headers_mut().insert(config.header.clone(), value);
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    async fn echo_id(id: RequestIdExtractor) -> String {
+        id.to_string()
+    }
+
+    #[actix_web::test]
+    async fn generates_id() {
+        let app = init_service(
+            App::new()
+                .wrap(RequestId::new())
+                .route("/", web::get().to(echo_id)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        let header = res.headers().get("x-request-id").unwrap().clone();
+        assert_eq!(header.len(), 36);
+        assert_eq!(read_body(res).await, header.as_bytes());
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_ne!(res.headers().get("x-request-id").unwrap(), header);
+    }
+
+    #[actix_web::test]
+    async fn propagates_valid_ids() {
+        let app = init_service(
+            App::new()
+                .wrap(RequestId::new().ulid())
+                .route("/", web::get().to(echo_id)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header(("x-request-id", "lb-1234"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.headers().get("x-request-id").unwrap(), "lb-1234");
+        assert_eq!(read_body(res).await, "lb-1234");
+
+        let long_id = "a".repeat(MAX_INCOMING_LEN + 1);
+
+        for invalid in ["has space", long_id.as_str()] {
+            let req = TestRequest::default()
+                .insert_header(("x-request-id", invalid))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.headers().get("x-request-id").unwrap().len(), 26);
+        }
+    }
+
+    #[actix_web::test]
+    async fn custom_header_without_propagation() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    RequestId::new()
+                        .header(HeaderName::from_static("x-correlation-id"))
+                        .propagate(false),
+                )
+                .route("/", web::get().to(echo_id)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header(("x-correlation-id", "client-chosen"))
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        let header = res.headers().get("x-correlation-id").unwrap().clone();
+        assert_ne!(header, "client-chosen");
+        assert!(!res.headers().contains_key("x-request-id"));
+        assert_eq!(read_body(res).await, header.as_bytes());
+    }
+}
@@ -0,0 +1,286 @@
+//! Server-Timing middleware.
+//!
+//! See [`ServerTiming`] docs.
+
+use std::{
+    cell::RefCell,
+    convert::Infallible,
+    future::{ready, Future, Ready},
+    mem,
+    rc::Rc,
+    time::Instant,
+};
+
+use actix_web::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{Header as _, TryIntoHeaderValue as _},
+    FromRequest, HttpMessage as _, HttpRequest,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::header::{ServerTiming as ServerTimingHeader, ServerTimingMetric};
+
+/// Handle for adding custom metrics to the `Server-Timing` header of the current response.
+///
+/// Inserted into request extensions by the [`ServerTiming`] middleware and can be used as an
+/// extractor. If the middleware is not in use, the extracted recorder is detached and any metrics
+/// recorded with it are discarded.
+///
+/// # Examples
+/// ```
+/// use actix_web::get;
+/// use actix_web_lab::{header::ServerTimingMetric, middleware::ServerTimingRecorder};
+///
+/// # async fn query_db() {}
+/// #[get("/")]
+/// async fn index(timing: ServerTimingRecorder) -> &'static str {
+///     timing.record(ServerTimingMetric::new("cache").description("miss"));
+///
+///     // records a `db` metric with the duration of the query
+///     timing.measure("db", query_db()).await;
+///
+///     "Hello World!"
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ServerTimingRecorder {
+    metrics: Rc<RefCell<Vec<ServerTimingMetric>>>,
+}
+
+impl ServerTimingRecorder {
+    /// Adds a metric to the response's `Server-Timing` header.
+    pub fn record(&self, metric: ServerTimingMetric) {
+        self.metrics.borrow_mut().push(metric);
+    }
+
+    /// Awaits `fut` and adds a metric named `name` with the time it took to complete.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid token.
+    pub async fn measure<F: Future>(&self, name: &str, fut: F) -> F::Output {
+        let metric = ServerTimingMetric::new(name);
+
+        let start = Instant::now();
+        let output = fut.await;
+        self.record(metric.duration(start.elapsed()));
+
+        output
+    }
+
+    fn take(&self) -> Vec<ServerTimingMetric> {
+        mem::take(&mut self.metrics.borrow_mut())
+    }
+}
+
+impl FromRequest for ServerTimingRecorder {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(req
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .unwrap_or_default()))
+    }
+}
+
+/// Middleware for emitting a `Server-Timing` header containing request handling latency.
+///
+/// Measures the time taken from the request reaching this middleware until the wrapped service
+/// produces a response (not including streaming of the response body) and appends it, after any
+/// metrics added by handlers using a [`ServerTimingRecorder`], to the response's `Server-Timing`
+/// header. Existing `Server-Timing` headers are preserved.
+///
+/// Timing information can reveal details about an application's internals, so consider only
+/// enabling this middleware for trusted clients or in non-production environments.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::ServerTiming;
+///
+/// App::new().wrap(ServerTiming::new().metric_name("app"))
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServerTiming {
+    metric_name: String,
+}
+
+impl ServerTiming {
+    /// Constructs new Server-Timing middleware that reports latency as a `total` metric.
+    pub fn new() -> Self {
+        Self {
+            metric_name: "total".to_owned(),
+        }
+    }
+
+    /// Sets the name of the metric used to report request handling latency.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid token.
+    pub fn metric_name(mut self, name: impl Into<String>) -> Self {
+        self.metric_name = ServerTimingMetric::new(name).name;
+        self
+    }
+}
+
+impl Default for ServerTiming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ServerTiming
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Transform = ServerTimingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ServerTimingMiddleware {
+            service: Rc::new(service),
+            metric_name: Rc::from(self.metric_name.as_str()),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`ServerTiming`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct ServerTimingMiddleware<S> {
+    service: Rc<S>,
+    metric_name: Rc<str>,
+}
+
+impl<S, B> Service<ServiceRequest> for ServerTimingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+
+        let recorder = ServerTimingRecorder::default();
+        req.extensions_mut().insert(recorder.clone());
+
+        let metric_name = Rc::clone(&self.metric_name);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let mut metrics = recorder.take();
+            metrics.push(ServerTimingMetric {
+                name: metric_name.to_string(),
+                duration: Some(start.elapsed()),
+                description: None,
+            });
+
+            if let Ok(value) = ServerTimingHeader(metrics).try_into_value() {
+                res.headers_mut().append(ServerTimingHeader::name(), value);
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    fn timings<B>(res: &ServiceResponse<B>) -> ServerTimingHeader {
+        let req = res
+            .headers()
+            .get_all(ServerTimingHeader::name())
+            .fold(TestRequest::default(), |req, value| {
+                req.append_header((ServerTimingHeader::name(), value.clone()))
+            })
+            .to_http_request();
+
+        ServerTimingHeader::parse(&req).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn reports_total() {
+        let app = init_service(
+            App::new()
+                .wrap(ServerTiming::new())
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let timings = timings(&res);
+        assert_eq!(timings.len(), 1);
+        assert_eq!(timings[0].name, "total");
+        assert!(timings[0].duration.is_some());
+    }
+
+    #[actix_web::test]
+    async fn handler_metrics() {
+        let app = init_service(
+            App::new()
+                .wrap(ServerTiming::new().metric_name("app"))
+                .route(
+                    "/",
+                    web::get().to(|timing: ServerTimingRecorder| async move {
+                        timing.record(ServerTimingMetric::new("cache").description("miss"));
+                        timing.measure("db", async {}).await;
+
+                        HttpResponse::Ok()
+                            .insert_header(ServerTimingHeader(vec![ServerTimingMetric::new(
+                                "edge",
+                            )]))
+                            .finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+
+        let names = timings(&res)
+            .iter()
+            .map(|metric| metric.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["edge", "cache", "db", "app"]);
+    }
+
+    #[actix_web::test]
+    async fn detached_recorder() {
+        let app = init_service(App::new().route(
+            "/",
+            web::get().to(|timing: ServerTimingRecorder| async move {
+                timing.record(ServerTimingMetric::new("cache"));
+                HttpResponse::Ok().finish()
+            }),
+        ))
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(ServerTimingHeader::name()));
+    }
+}
@@ -0,0 +1,275 @@
+//! MessagePack extractor with const-generic payload size limit.
+
+use actix_web::{
+    dev::Payload, error::PayloadError, http::StatusCode, FromRequest, HttpMessage as _,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{decompress, lab_config::LabConfig};
+
+/// Default MessagePack payload size limit of 2MiB.
+pub const DEFAULT_MSGPACK_LIMIT: usize = 2_097_152;
+
+/// [MessagePack] extractor with const-generic payload size limit.
+///
+/// Deserializes a `T` from a request body with an `application/msgpack`,
+/// `application/x-msgpack`, or `application/vnd.msgpack` `Content-Type`, buffering up to `LIMIT`
+/// bytes. The default limit is 2MiB.
+///
+/// Both the compact (array) and named (map) struct encodings produced by the
+/// [`MessagePack`](crate::respond::MessagePack) and
+/// [`MessagePackNamed`](crate::respond::MessagePackNamed) responders are accepted.
+///
+/// # Examples
+/// ```
+/// use actix_web::post;
+/// use actix_web_lab::extract::MsgPack;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// #[post("/")]
+/// async fn index(info: MsgPack<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// const LIMIT_32_MB: usize = 33_554_432;
+///
+/// /// Deserialize payload with a higher 32MiB limit.
+/// #[post("/big-payload")]
+/// async fn big_payload(info: MsgPack<Info, LIMIT_32_MB>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+///
+/// [MessagePack]: https://msgpack.org/
+#[derive(Debug)]
+pub struct MsgPack<T, const LIMIT: usize = DEFAULT_MSGPACK_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> std::ops::Deref for MsgPack<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> std::ops::DerefMut for MsgPack<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const LIMIT: usize> MsgPack<T, LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static, const LIMIT: usize> FromRequest for MsgPack<T, LIMIT> {
+    type Error = MsgPackPayloadError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let can_parse_msgpack = match req.mime_type() {
+            Ok(Some(mime)) => {
+                mime.type_() == mime::APPLICATION
+                    && matches!(
+                        mime.subtype().as_str(),
+                        "msgpack" | "x-msgpack" | "vnd.msgpack"
+                    )
+            }
+            _ => false,
+        };
+
+        let length = req
+            .get_header::<crate::header::ContentLength>()
+            .map(|cl| cl.into_inner())
+            .filter(|_| !decompress::is_encoded(req));
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_MSGPACK_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let capacity = LabConfig::buffer_capacity(req, length);
+        let mut payload = decompress::take_payload(req, payload);
+        let req = req.clone();
+
+        Box::pin(async move {
+            if !can_parse_msgpack {
+                return Err(MsgPackPayloadError::ContentType);
+            }
+
+            if let Some(length) = length.filter(|&len| len > limit) {
+                return Err(MsgPackPayloadError::OverflowKnownLength { length, limit });
+            }
+
+            let mut buf = actix_web::web::BytesMut::with_capacity(capacity);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(MsgPackPayloadError::Payload)?;
+
+                if buf.len() + chunk.len() > limit {
+                    return Err(MsgPackPayloadError::Overflow { limit });
+                }
+
+                buf.extend_from_slice(&chunk);
+            }
+
+            rmp_serde::from_slice(&buf)
+                .map(MsgPack)
+                .map_err(MsgPackPayloadError::Deserialize)
+                .inspect_err(|err| {
+                    debug!(
+                        "Failed to deserialize MsgPack<{}> from payload in handler: {}: {err}",
+                        core::any::type_name::<T>(),
+                        req.match_name().unwrap_or_else(|| req.path()),
+                    );
+                })
+        })
+    }
+}
+
+/// Errors that can occur when extracting a MessagePack payload.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum MsgPackPayloadError {
+    /// Request does not have a MessagePack `Content-Type`.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Payload size is bigger than allowed and content length header is set.
+    #[display(
+        "MessagePack payload ({length} bytes) is larger than allowed (limit: {limit} bytes)."
+    )]
+    OverflowKnownLength {
+        /// Length reported by `Content-Length` header.
+        length: usize,
+
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload size is bigger than allowed but no content length header set.
+    #[display("MessagePack payload has exceeded limit ({limit} bytes).")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload could not be deserialized into the target type.
+    #[display("MessagePack deserialize error: {_0}")]
+    Deserialize(rmp_serde::decode::Error),
+
+    /// Error that occurred when reading the payload.
+    #[display("Error that occurred when reading payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for MsgPackPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::OverflowKnownLength { .. } | Self::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Deserialize(_) | Self::Payload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct MyObject {
+        name: String,
+        tags: Vec<u32>,
+    }
+
+    fn msgpack_req(content_type: &'static str, body: Vec<u8>) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn extracts_payload() {
+        let item = MyObject {
+            name: "test".to_owned(),
+            tags: vec![1, 2],
+        };
+
+        let (req, mut pl) =
+            msgpack_req("application/msgpack", rmp_serde::to_vec(&item).unwrap()).to_http_parts();
+        let extracted = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(extracted.into_inner(), item);
+
+        let (req, mut pl) = msgpack_req(
+            "application/x-msgpack",
+            rmp_serde::to_vec_named(&item).unwrap(),
+        )
+        .to_http_parts();
+        let extracted = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(extracted.into_inner(), item);
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_requests() {
+        let body = rmp_serde::to_vec(&MyObject {
+            name: "test".to_owned(),
+            tags: vec![],
+        })
+        .unwrap();
+
+        let (req, mut pl) = msgpack_req("application/json", body.clone()).to_http_parts();
+        let err = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MsgPackPayloadError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = msgpack_req("application/msgpack", body.clone()).to_http_parts();
+        let err = MsgPack::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MsgPackPayloadError::OverflowKnownLength { limit: 4, .. }
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/msgpack"))
+            .set_payload(body)
+            .to_http_parts();
+        let err = MsgPack::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MsgPackPayloadError::Overflow { limit: 4 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (req, mut pl) = msgpack_req("application/msgpack", vec![0xc1]).to_http_parts();
+        let err = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MsgPackPayloadError::Deserialize(_)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
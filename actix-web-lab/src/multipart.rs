@@ -0,0 +1,910 @@
+//! Typed multipart form extractor with const-generic size limits.
+
+use std::{fmt, vec};
+
+use actix_web::{
+    dev::Payload,
+    error::PayloadError,
+    http::{
+        header::{ContentDisposition, HeaderValue},
+        StatusCode,
+    },
+    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+use bytes::{Bytes, BytesMut};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use mime::Mime;
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer, StringDeserializer},
+    DeserializeOwned, IntoDeserializer,
+};
+use tracing::debug;
+
+use crate::lab_config::LabConfig;
+
+/// Default multipart form payload size limit of 8MiB.
+pub const DEFAULT_MULTIPART_LIMIT: usize = 8_388_608;
+
+/// Default multipart form field size limit of 4MiB.
+pub const DEFAULT_MULTIPART_FIELD_LIMIT: usize = 4_194_304;
+
+/// Errors that can occur when extracting a multipart form.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum MultipartError {
+    /// Content type is not `multipart/form-data` or has no boundary.
+    #[display("Content type is not multipart/form-data with a boundary")]
+    ContentType,
+
+    /// Payload is larger than the payload size limit.
+    #[display("Multipart payload is larger than {limit} bytes")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// A field is larger than the field size limit.
+    #[display("Multipart field \"{field}\" is larger than {limit} bytes")]
+    FieldOverflow {
+        /// Field name.
+        #[error(not(source))]
+        field: String,
+
+        /// Field size limit.
+        limit: usize,
+    },
+
+    /// Payload is not a well-formed multipart body.
+    #[display("Malformed multipart body: {_0}")]
+    Malformed(#[error(not(source))] &'static str),
+
+    /// Fields could not be deserialized into the target type.
+    #[display("Failed to deserialize multipart form: {_0}")]
+    Deserialize(de::value::Error),
+
+    /// Error reading request payload.
+    #[display("Error reading request payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for MultipartError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Overflow { .. } | Self::FieldOverflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Malformed(_) | Self::Deserialize(_) => StatusCode::BAD_REQUEST,
+            Self::Payload(err) => err.status_code(),
+        }
+    }
+}
+
+/// A file uploaded as part of a [`Multipart`] form.
+///
+/// Use as a field type of the form struct. Wrap in `Option` if the file is optional or use a `Vec`
+/// to accept multiple files in one field. A file input submitted without a file selected is treated
+/// as absent.
+#[derive(Debug, Clone)]
+pub struct MultipartFile {
+    file_name: Option<String>,
+    content_type: Option<Mime>,
+    data: Bytes,
+}
+
+impl MultipartFile {
+    /// Returns the file name sent by the client, if any.
+    ///
+    /// This is untrusted client input and should not be used directly as a file system path.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the content type sent by the client, if any.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns the file contents.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Unwraps into the file contents.
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+impl<'de> de::Deserialize<'de> for MultipartFile {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileVisitor;
+
+        impl<'de> de::Visitor<'de> for FileVisitor {
+            type Value = MultipartFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a multipart file field")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut file_name = None;
+                let mut content_type = None;
+                let mut data = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        FILE_NAME => file_name = map.next_value::<Option<String>>()?,
+                        CONTENT_TYPE => content_type = map.next_value::<Option<String>>()?,
+                        DATA => data = Some(map.next_value::<FileData>()?.0),
+                        _ => return Err(de::Error::unknown_field(&key, FILE_FIELDS)),
+                    }
+                }
+
+                Ok(MultipartFile {
+                    file_name,
+                    content_type: content_type.and_then(|ct| ct.parse().ok()),
+                    data: data.ok_or_else(|| de::Error::missing_field(DATA))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct("MultipartFile", FILE_FIELDS, FileVisitor)
+    }
+}
+
+const FILE_NAME: &str = "file_name";
+const CONTENT_TYPE: &str = "content_type";
+const DATA: &str = "data";
+const FILE_FIELDS: &[&str] = &[FILE_NAME, CONTENT_TYPE, DATA];
+
+/// File contents, deserialized from bytes.
+struct FileData(Bytes);
+
+impl<'de> de::Deserialize<'de> for FileData {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DataVisitor;
+
+        impl de::Visitor<'_> for DataVisitor {
+            type Value = FileData;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("file contents")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(FileData(Bytes::copy_from_slice(v)))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(FileData(Bytes::from(v)))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(DataVisitor)
+    }
+}
+
+/// Typed `multipart/form-data` extractor with const-generic size limits.
+///
+/// Deserializes text fields and file fields of a multipart form into a `T`, which must implement
+/// [`serde::Deserialize`]. Text fields are deserialized from their string values, so they can be
+/// numbers, booleans, or unit enum variants as well as strings. File fields use the
+/// [`MultipartFile`] type. Fields that are sent more than once can be collected into a `Vec`.
+///
+/// The whole payload is buffered before deserialization. Use the `LIMIT` const generic parameter to
+/// control the total payload size limit (default 8MiB) and the `FIELD_LIMIT` const generic
+/// parameter to control the size limit of each field (default 4MiB).
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::extract::{Multipart, MultipartFile};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Upload {
+///     title: String,
+///     public: bool,
+///     cover: Option<MultipartFile>,
+///     attachments: Vec<MultipartFile>,
+/// }
+///
+/// #[post("/upload")]
+/// async fn upload(form: Multipart<Upload>) -> impl Responder {
+///     format!("{}: {} attachments", form.title, form.attachments.len())
+/// }
+///
+/// const LIMIT_64_MB: usize = 67_108_864;
+///
+/// /// Accept larger uploads.
+/// #[post("/big-upload")]
+/// async fn big_upload(form: Multipart<Upload, LIMIT_64_MB, LIMIT_64_MB>) -> impl Responder {
+///     form.title.clone()
+/// }
+/// ```
+#[doc(alias = "form_data", alias = "form data")]
+#[derive(Debug)]
+pub struct Multipart<
+    T,
+    const LIMIT: usize = DEFAULT_MULTIPART_LIMIT,
+    const FIELD_LIMIT: usize = DEFAULT_MULTIPART_FIELD_LIMIT,
+>(pub T);
+
+impl<T, const LIMIT: usize, const FIELD_LIMIT: usize> Multipart<T, LIMIT, FIELD_LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, const LIMIT: usize, const FIELD_LIMIT: usize> std::ops::Deref
+    for Multipart<T, LIMIT, FIELD_LIMIT>
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize, const FIELD_LIMIT: usize> std::ops::DerefMut
+    for Multipart<T, LIMIT, FIELD_LIMIT>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned, const LIMIT: usize, const FIELD_LIMIT: usize> FromRequest
+    for Multipart<T, LIMIT, FIELD_LIMIT>
+{
+    type Error = MultipartError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let boundary = req
+            .mime_type()
+            .ok()
+            .flatten()
+            .filter(|mime| mime.type_() == mime::MULTIPART && mime.subtype() == mime::FORM_DATA)
+            .and_then(|mime| {
+                mime.get_param(mime::BOUNDARY)
+                    .map(|b| b.as_str().to_owned())
+            });
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_MULTIPART_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let length = LabConfig::payload_length(req);
+
+        let req = req.clone();
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let res = async {
+                let boundary = boundary.ok_or(MultipartError::ContentType)?;
+
+                if length.is_some_and(|len| len > limit) {
+                    return Err(MultipartError::Overflow { limit });
+                }
+
+                let mut body = BytesMut::new();
+
+                while let Some(chunk) = payload.next().await {
+                    let chunk = chunk.map_err(MultipartError::Payload)?;
+
+                    if body.len() + chunk.len() > limit {
+                        return Err(MultipartError::Overflow { limit });
+                    }
+
+                    body.extend_from_slice(&chunk);
+                }
+
+                let fields = parse_fields(body.freeze(), &boundary, FIELD_LIMIT)?;
+
+                T::deserialize(FormDeserializer(fields))
+                    .map(Multipart)
+                    .map_err(MultipartError::Deserialize)
+            }
+            .await;
+
+            if let Err(err) = &res {
+                debug!(
+                    "Failed to extract Multipart<{}> for `{}` handler: {err}",
+                    std::any::type_name::<T>(),
+                    req.match_name().unwrap_or_else(|| req.path()),
+                );
+            }
+
+            res
+        })
+    }
+}
+
+/// A single field value.
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    File(MultipartFile),
+}
+
+/// Parses a buffered multipart body, grouping values by field name in order of first appearance.
+fn parse_fields(
+    body: Bytes,
+    boundary: &str,
+    field_limit: usize,
+) -> Result<Vec<(String, Vec<Part>)>, MultipartError> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+
+    let mut pos = find(&body, delimiter).ok_or(MultipartError::Malformed("missing boundary"))?
+        + delimiter.len();
+
+    let mut fields = Vec::<(String, Vec<Part>)>::new();
+
+    loop {
+        // close delimiter
+        if body[pos..].starts_with(b"--") {
+            break;
+        }
+
+        // skip transport padding and line break after delimiter
+        while matches!(body.get(pos), Some(b' ' | b'\t')) {
+            pos += 1;
+        }
+
+        pos += line_break_len(&body[pos..]).ok_or(MultipartError::Malformed(
+            "expected line break after boundary",
+        ))?;
+
+        let (headers, content_start) = match find(&body[pos..], b"\r\n\r\n") {
+            // part without headers
+            _ if body[pos..].starts_with(b"\r\n") => (&body[pos..pos], pos + 2),
+            Some(idx) => (&body[pos..pos + idx], pos + idx + 4),
+            None => return Err(MultipartError::Malformed("unterminated part headers")),
+        };
+
+        let mut close = b"\r\n".to_vec();
+        close.extend_from_slice(delimiter);
+
+        let content_len = find(&body[content_start..], &close)
+            .ok_or(MultipartError::Malformed("missing closing boundary"))?;
+        let content = body.slice(content_start..content_start + content_len);
+
+        pos = content_start + content_len + close.len();
+
+        let mut disposition = None;
+        let mut content_type = None;
+
+        for line in headers.split(|&byte| byte == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+            let Some(colon) = line.iter().position(|&byte| byte == b':') else {
+                continue;
+            };
+
+            let name = line[..colon].trim_ascii();
+            let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+                .map_err(|_| MultipartError::Malformed("invalid part header"))?;
+
+            if name.eq_ignore_ascii_case(b"content-disposition") {
+                disposition = ContentDisposition::from_raw(&value).ok();
+            } else if name.eq_ignore_ascii_case(b"content-type") {
+                content_type = value.to_str().ok().and_then(|ct| ct.parse::<Mime>().ok());
+            }
+        }
+
+        let disposition = disposition.filter(ContentDisposition::is_form_data).ok_or(
+            MultipartError::Malformed("missing form-data content disposition"),
+        )?;
+
+        let name = disposition
+            .get_name()
+            .ok_or(MultipartError::Malformed("missing field name"))?
+            .to_owned();
+
+        if content.len() > field_limit {
+            return Err(MultipartError::FieldOverflow {
+                field: name,
+                limit: field_limit,
+            });
+        }
+
+        let part = match disposition.get_filename() {
+            // file input submitted without a file selected
+            Some("") if content.is_empty() => continue,
+
+            Some(file_name) => Part::File(MultipartFile {
+                file_name: Some(file_name.to_owned()),
+                content_type,
+                data: content,
+            }),
+
+            None if content_type.is_some() => Part::File(MultipartFile {
+                file_name: None,
+                content_type,
+                data: content,
+            }),
+
+            None => Part::Text(
+                String::from_utf8(content.to_vec())
+                    .map_err(|_| MultipartError::Malformed("text field is not valid UTF-8"))?,
+            ),
+        };
+
+        match fields.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, parts)) => parts.push(part),
+            None => fields.push((name, vec![part])),
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Returns the length of the line break at the start of `buf`, if any.
+fn line_break_len(buf: &[u8]) -> Option<usize> {
+    if buf.starts_with(b"\r\n") {
+        Some(2)
+    } else if buf.starts_with(b"\n") {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+type DeError = de::value::Error;
+
+/// Deserializes a form from its fields.
+struct FormDeserializer(Vec<(String, Vec<Part>)>);
+
+impl<'de> de::Deserializer<'de> for FormDeserializer {
+    type Error = DeError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let fields = self
+            .0
+            .into_iter()
+            .map(|(name, parts)| (name, FieldDeserializer(parts)));
+
+        visitor.visit_map(MapDeserializer::new(fields))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// Deserializes all values of a single field.
+struct FieldDeserializer(Vec<Part>);
+
+impl FieldDeserializer {
+    fn single(self) -> Result<Part, DeError> {
+        let mut parts = self.0.into_iter();
+
+        match (parts.next(), parts.next()) {
+            (Some(part), None) => Ok(part),
+            _ => Err(de::Error::custom(
+                "field was sent multiple times but does not accept a sequence",
+            )),
+        }
+    }
+}
+
+impl IntoDeserializer<'_, DeError> for FieldDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer {
+    type Error = DeError;
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter()))
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_single! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf deserialize_unit
+        deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, DeError> for Part {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! parse_text {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self {
+                    Self::Text(text) => visitor.$visit(text.parse().map_err(de::Error::custom)?),
+                    Self::File(_) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Part {
+    type Error = DeError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Self::Text(text) => visitor.visit_string(text),
+            Self::File(file) => visitor.visit_map(MapDeserializer::new(file_attrs(file))),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Self::Text(text) => {
+                let text: StringDeserializer<DeError> = text.into_deserializer();
+                text.deserialize_enum(name, variants, visitor)
+            }
+            Self::File(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    parse_text! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+/// Returns the attributes of a file, in the form expected by [`MultipartFile`]'s deserializer.
+fn file_attrs(file: MultipartFile) -> vec::IntoIter<(&'static str, FileAttr)> {
+    vec![
+        (FILE_NAME, FileAttr::Text(file.file_name)),
+        (
+            CONTENT_TYPE,
+            FileAttr::Text(file.content_type.map(|ct| ct.to_string())),
+        ),
+        (DATA, FileAttr::Data(file.data)),
+    ]
+    .into_iter()
+}
+
+/// A single attribute of a file.
+enum FileAttr {
+    Text(Option<String>),
+    Data(Bytes),
+}
+
+impl IntoDeserializer<'_, DeError> for FileAttr {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FileAttr {
+    type Error = DeError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Self::Text(Some(text)) => visitor.visit_string(text),
+            Self::Text(None) => visitor.visit_none(),
+            Self::Data(data) => visitor.visit_byte_buf(data.to_vec()),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Self::Text(None) => visitor.visit_none(),
+            attr => visitor.visit_some(attr),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Visibility {
+        Public,
+        Private,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Upload {
+        title: String,
+        count: u32,
+        visibility: Visibility,
+        tags: Vec<String>,
+        note: Option<String>,
+        cover: Option<MultipartFile>,
+        attachments: Vec<MultipartFile>,
+    }
+
+    const BOUNDARY: &str = "X-BOUNDARY";
+
+    fn body(parts: &[(&str, Option<&str>, &str)]) -> String {
+        let mut body = String::new();
+
+        for (name, file_name, content) in parts {
+            body.push_str(&format!("--{BOUNDARY}\r\n"));
+
+            match file_name {
+                Some(file_name) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\
+                    Content-Type: text/plain\r\n\r\n"
+                )),
+                None => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"\r\n\r\n"
+                )),
+            }
+
+            body.push_str(content);
+            body.push_str("\r\n");
+        }
+
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        body
+    }
+
+    fn request(body: String) -> TestRequest {
+        TestRequest::post()
+            .insert_header((
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            ))
+            .set_payload(body)
+    }
+
+    async fn extract<T: DeserializeOwned, const LIMIT: usize, const FIELD_LIMIT: usize>(
+        body: String,
+    ) -> Result<T, MultipartError> {
+        let (req, mut pl) = request(body).to_http_parts();
+
+        Multipart::<T, LIMIT, FIELD_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .map(Multipart::into_inner)
+    }
+
+    #[actix_web::test]
+    async fn text_and_file_fields() {
+        let body = body(&[
+            ("title", None, "Holiday\r\nphotos"),
+            ("count", None, "2"),
+            ("visibility", None, "public"),
+            ("tags", None, "beach"),
+            ("tags", None, "sun"),
+            ("cover", Some(""), ""),
+            ("attachments", Some("a.txt"), "first"),
+            ("attachments", Some("b.txt"), "second"),
+        ]);
+
+        let upload =
+            extract::<Upload, DEFAULT_MULTIPART_LIMIT, DEFAULT_MULTIPART_FIELD_LIMIT>(body)
+                .await
+                .unwrap();
+
+        assert_eq!(upload.title, "Holiday\r\nphotos");
+        assert_eq!(upload.count, 2);
+        assert_eq!(upload.visibility, Visibility::Public);
+        assert_eq!(upload.tags, ["beach", "sun"]);
+        assert_eq!(upload.note, None);
+        assert!(upload.cover.is_none());
+
+        assert_eq!(upload.attachments.len(), 2);
+        assert_eq!(upload.attachments[0].file_name(), Some("a.txt"));
+        assert_eq!(
+            upload.attachments[0].content_type(),
+            Some(&mime::TEXT_PLAIN)
+        );
+        assert_eq!(upload.attachments[1].data(), "second");
+    }
+
+    #[actix_web::test]
+    async fn limits() {
+        let fields = [
+            ("title", None, "this title is quite long"),
+            ("count", None, "1"),
+            ("visibility", None, "private"),
+        ];
+
+        let err = extract::<Upload, 64, DEFAULT_MULTIPART_FIELD_LIMIT>(body(&fields))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MultipartError::Overflow { limit: 64 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let err = extract::<Upload, DEFAULT_MULTIPART_LIMIT, 8>(body(&fields))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MultipartError::FieldOverflow { ref field, limit: 8 } if field == "title")
+        );
+    }
+
+    #[actix_web::test]
+    async fn errors() {
+        // missing required field
+        let err = extract::<Upload, DEFAULT_MULTIPART_LIMIT, DEFAULT_MULTIPART_FIELD_LIMIT>(body(
+            &[("title", None, "t")],
+        ))
+        .await
+        .unwrap_err();
+        assert!(matches!(err, MultipartError::Deserialize(_)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+
+        // not a number
+        let err =
+            extract::<Upload, DEFAULT_MULTIPART_LIMIT, DEFAULT_MULTIPART_FIELD_LIMIT>(body(&[
+                ("title", None, "t"),
+                ("count", None, "many"),
+                ("visibility", None, "public"),
+            ]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MultipartError::Deserialize(_)));
+
+        let err = extract::<Upload, DEFAULT_MULTIPART_LIMIT, DEFAULT_MULTIPART_FIELD_LIMIT>(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nb"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, MultipartError::Malformed(_)));
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header(header::ContentType::json())
+            .to_http_parts();
+        let err = Multipart::<Upload>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MultipartError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[actix_web::test]
+    async fn as_extractor() {
+        #[derive(Deserialize)]
+        struct Form {
+            name: String,
+        }
+
+        let app = init_service(App::new().default_service(web::to(
+            |form: Multipart<Form>| async move { HttpResponse::Ok().body(form.0.name) },
+        )))
+        .await;
+
+        let req = request(body(&[("name", None, "actix")])).to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
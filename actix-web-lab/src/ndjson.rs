@@ -1,4 +1,6 @@
-use std::{convert::Infallible, error::Error as StdError, io::Write as _, sync::LazyLock};
+use std::{
+    convert::Infallible, error::Error as StdError, io::Write as _, sync::LazyLock, time::Duration,
+};
 
 use actix_web::{
     body::{BodyStream, MessageBody},
@@ -11,10 +13,21 @@ use mime::Mime;
 use pin_project_lite::pin_project;
 use serde::Serialize;
 
-use crate::util::{InfallibleStream, MutWriter};
+use crate::{
+    body::coalesce,
+    util::{
+        CompletionCallback, CountingStream, ErrorTrailerFn, ErrorTrailerStream, InfallibleStream,
+        MutWriter,
+    },
+};
 
 static NDJSON_MIME: LazyLock<Mime> = LazyLock::new(|| "application/x-ndjson".parse().unwrap());
 
+/// Size of chunks that lines are buffered into when a flush interval is set.
+const FLUSH_CHUNK_SIZE: usize = 16 * 1024;
+
+type ErrorRecordFn = Box<dyn FnOnce(Box<dyn StdError>) -> serde_json::Value>;
+
 pin_project! {
     /// A buffered [NDJSON] serializing body stream.
     ///
@@ -44,13 +57,101 @@ pin_project! {
         // The wrapped item stream.
         #[pin]
         stream: S,
+
+        // Called with the number of serialized items after the stream completes.
+        on_complete: Option<CompletionCallback>,
+
+        // Whether lines are pretty-printed.
+        pretty: bool,
+
+        // Maps a stream error to a trailing error record.
+        on_error: Option<ErrorRecordFn>,
+
+        // Maximum time lines are buffered for before being sent.
+        flush_interval: Option<Duration>,
     }
 }
 
 impl<S> NdJson<S> {
     /// Constructs a new `NdJson` from a stream of items.
     pub fn new(stream: S) -> Self {
-        Self { stream }
+        Self {
+            stream,
+            on_complete: None,
+            pretty: false,
+            on_error: None,
+            flush_interval: None,
+        }
+    }
+
+    /// Sets a callback that is called with the number of serialized items once the stream has been
+    /// fully consumed.
+    ///
+    /// The callback is not called if the stream yields an error or if the body is dropped before
+    /// completion (e.g., because the client disconnected), so it can be used to verify in logs that
+    /// an export was delivered in full without pre-counting items.
+    ///
+    /// Actix Web does not currently support sending HTTP trailers, so the count cannot be reported
+    /// to clients after the body has started streaming.
+    pub fn on_complete(mut self, on_complete: impl FnOnce(usize) + 'static) -> Self {
+        self.on_complete = Some(Box::new(on_complete));
+        self
+    }
+
+    /// Sets whether items are pretty-printed.
+    ///
+    /// Pretty-printed items span multiple lines, so the output is no longer valid NDJSON. This is
+    /// intended for making output easier to read while debugging. Defaults to off.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Sets a function that maps a stream error to a final error record.
+    ///
+    /// By default, an error from the stream aborts the response, which clients usually see as an
+    /// unexpectedly closed connection. When an error record function is set, the record it returns
+    /// is instead written as the last line of the body and the response completes normally, giving
+    /// clients a chance to tell an incomplete export apart from a network failure.
+    ///
+    /// If the returned record fails to serialize, `{"error":"stream error"}` is written instead.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::Responder;
+    /// # use actix_web_lab::respond::NdJson;
+    /// # use futures_util::stream;
+    /// # use serde_json::json;
+    /// async fn handler() -> impl Responder {
+    ///     let rows = stream::iter([Ok(json!({ "id": 1 })), Err(std::io::Error::other("db gone"))]);
+    ///
+    ///     NdJson::new(rows)
+    ///         .error_record(|err| json!({ "error": err.to_string() }))
+    ///         .into_responder()
+    /// }
+    /// ```
+    pub fn error_record<R: Serialize>(
+        mut self,
+        error_record: impl FnOnce(Box<dyn StdError>) -> R + 'static,
+    ) -> Self {
+        self.on_error = Some(Box::new(move |err| {
+            serde_json::to_value(error_record(err)).unwrap_or_else(|err| {
+                tracing::debug!("failed to serialize NDJSON error record: {err}");
+                serde_json::json!({ "error": "stream error" })
+            })
+        }));
+        self
+    }
+
+    /// Sets the maximum time that serialized items are buffered for before being sent.
+    ///
+    /// By default, each item is sent as soon as it is serialized. When a flush interval is set,
+    /// items that are produced in quick succession are buffered into larger chunks, reducing
+    /// per-write overhead, but never held for longer than `flush_interval` while waiting for more
+    /// items from slow streams. See [`body::coalesce()`](crate::body::coalesce).
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = Some(flush_interval);
+        self
     }
 }
 
@@ -69,7 +170,17 @@ where
 {
     /// Creates a chunked body stream that serializes as NDJSON on-the-fly.
     pub fn into_body_stream(self) -> impl MessageBody {
-        BodyStream::new(self.into_chunk_stream())
+        // without a flush interval, every non-empty chunk is passed through as soon as it is ready
+        let (min_chunk, max_delay) = match self.flush_interval {
+            Some(flush_interval) => (FLUSH_CHUNK_SIZE, flush_interval),
+            None => (1, Duration::ZERO),
+        };
+
+        coalesce(
+            BodyStream::new(self.into_chunk_stream()),
+            min_chunk,
+            max_delay,
+        )
     }
 
     /// Creates a `Responder` type with a serializing stream and correct Content-Type header.
@@ -87,7 +198,19 @@ where
 
     /// Creates a stream of serialized chunks.
     pub fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, E>> {
-        self.stream.map_ok(serialize_json_line)
+        let pretty = self.pretty;
+        let mut buf = BytesMut::new();
+
+        let stream = CountingStream::new(self.stream, self.on_complete)
+            .map_ok(move |item| serialize_json_line(&mut buf, item, pretty));
+
+        let on_error = self.on_error.map(|error_record| {
+            Box::new(move |err| {
+                serialize_json_line(&mut BytesMut::new(), error_record(err), pretty)
+            }) as ErrorTrailerFn
+        });
+
+        ErrorTrailerStream::new(stream, on_error)
     }
 }
 
@@ -98,22 +221,27 @@ impl NdJson<Infallible> {
     }
 }
 
-fn serialize_json_line(item: impl Serialize) -> Bytes {
-    let mut buf = BytesMut::new();
-    let mut wrt = MutWriter(&mut buf);
+/// Serializes `item` as a JSON line using `buf`'s spare capacity, which is reclaimed for subsequent
+/// lines once previously yielded chunks have been dropped.
+fn serialize_json_line(buf: &mut BytesMut, item: impl Serialize, pretty: bool) -> Bytes {
+    let mut wrt = MutWriter(buf);
 
     // serialize JSON line to buffer
-    serde_json::to_writer(&mut wrt, &item).unwrap();
+    if pretty {
+        serde_json::to_writer_pretty(&mut wrt, &item).unwrap();
+    } else {
+        serde_json::to_writer(&mut wrt, &item).unwrap();
+    }
 
     // add line break to buffer
     wrt.write_all(b"\n").unwrap();
 
-    buf.freeze()
+    buf.split().freeze()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error as StdError;
+    use std::{cell::Cell, collections::BTreeMap, error::Error as StdError, io, rc::Rc};
 
     use actix_web::body;
     use futures_util::stream;
@@ -145,4 +273,90 @@ mod tests {
 
         assert_eq!(body_bytes, EXP_BYTES);
     }
+
+    #[actix_web::test]
+    async fn reports_count_on_completion() {
+        let count = Rc::new(Cell::new(None));
+
+        let body =
+            NdJson::new_infallible(stream::iter(vec![json!(1u32), json!(2u32), json!(3u32)]))
+                .on_complete({
+                    let count = Rc::clone(&count);
+                    move |n| count.set(Some(n))
+                })
+                .into_body_stream();
+
+        assert!(count.get().is_none());
+        body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(count.get(), Some(3));
+
+        // not reported when stream errors part way through
+        let count = Rc::new(Cell::new(None));
+
+        let body = NdJson::new(stream::iter([
+            Ok(json!(1u32)),
+            Err(io::Error::other("db gone")),
+        ]))
+        .on_complete({
+            let count = Rc::clone(&count);
+            move |n| count.set(Some(n))
+        })
+        .into_body_stream();
+
+        assert!(body::to_bytes(body).await.is_err());
+        assert!(count.get().is_none());
+    }
+
+    #[actix_web::test]
+    async fn pretty_prints() {
+        let body = NdJson::new_infallible(stream::iter(vec![json!({ "a": 1u32 }), json!(2u32)]))
+            .pretty(true)
+            .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "{\n  \"a\": 1\n}\n2\n");
+    }
+
+    #[actix_web::test]
+    async fn writes_trailing_error_record() {
+        let body = NdJson::new(stream::iter([
+            Ok(json!(1u32)),
+            Err(io::Error::other("db gone")),
+            Ok(json!(2u32)),
+        ]))
+        .error_record(|err| json!({ "error": err.to_string() }))
+        .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "1\n{\"error\":\"db gone\"}\n");
+    }
+
+    #[actix_web::test]
+    async fn unserializable_error_record() {
+        let body = NdJson::new(stream::iter([
+            Ok(json!(1u32)),
+            Err(io::Error::other("db gone")),
+        ]))
+        // maps with non-string keys cannot be serialized to JSON
+        .error_record(|_| BTreeMap::from([((1u32, 2u32), 3u32)]))
+        .into_body_stream();
+
+        let body_bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(body_bytes, "1\n{\"error\":\"stream error\"}\n");
+    }
+
+    #[actix_web::test]
+    async fn buffers_until_flush_interval() {
+        let body =
+            NdJson::new_infallible(stream::iter(vec![json!(1u32), json!(2u32), json!(3u32)]))
+                .flush_interval(Duration::from_millis(10))
+                .into_body_stream();
+        let mut body = std::pin::pin!(body);
+
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().ok().unwrap(), "1\n2\n3\n");
+
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert!(chunk.is_none());
+    }
 }
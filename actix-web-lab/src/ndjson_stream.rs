@@ -0,0 +1,415 @@
+//! Streaming NDJSON request body extractor.
+//!
+//! See [`NdJsonStream`] docs.
+
+use std::{
+    future::{ready, Future, Ready},
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    dev::Payload,
+    error::PayloadError,
+    http::{header, StatusCode},
+    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+use bytes::BytesMut;
+use derive_more::{Display, Error};
+use futures_core::Stream;
+use futures_util::{future::Either, FutureExt as _, StreamExt as _};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::respond::NdJson;
+
+/// Default NDJSON line size limit of 1MiB.
+pub const DEFAULT_NDJSON_LINE_LIMIT: usize = 1_048_576;
+
+/// Errors that can occur when reading an NDJSON request body.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum NdJsonStreamError {
+    /// Content type is not an NDJSON type.
+    #[display("Content type is not NDJSON")]
+    ContentType,
+
+    /// A line exceeded the line size limit.
+    #[display("NDJSON line {line} is larger than {limit} bytes")]
+    LineTooLong {
+        /// Line number (1-based).
+        line: usize,
+
+        /// Line size limit.
+        limit: usize,
+    },
+
+    /// A line could not be deserialized.
+    #[display("Failed to deserialize NDJSON line {line}: {source}")]
+    Deserialize {
+        /// Line number (1-based).
+        line: usize,
+
+        /// Deserialization error.
+        source: serde_json::Error,
+    },
+
+    /// Error reading request payload.
+    #[display("Error reading request payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for NdJsonStreamError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::LineTooLong { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Deserialize { .. } => StatusCode::BAD_REQUEST,
+            Self::Payload(err) => err.status_code(),
+        }
+    }
+}
+
+/// Streaming [NDJSON] request body extractor.
+///
+/// Yields one deserialized item per line of the request body, without buffering the entire body.
+/// Blank lines are skipped and both `\n` and `\r\n` line endings are accepted.
+///
+/// Requests with a `Content-Type` header must use one of `application/x-ndjson`,
+/// `application/ndjson`, `application/jsonl`, or `application/x-jsonlines`; otherwise, extraction
+/// fails with a `415 Unsupported Media Type` response.
+///
+/// Use the `LINE_LIMIT` const generic parameter to control the maximum size of a single line. The
+/// default limit is 1MiB. The stream yields an error and ends if a line exceeds the limit, a line
+/// cannot be deserialized, or the payload errors.
+///
+/// # Transform Endpoints
+/// [`transform()`](Self::transform) wires the request stream directly into an [`NdJson`] response,
+/// so that memory usage is bounded regardless of how many items are sent.
+///
+/// ```
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::extract::NdJsonStream;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize)]
+/// struct Address {
+///     street: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Enriched {
+///     street: String,
+///     postcode: Option<String>,
+/// }
+///
+/// async fn look_up_postcode(_street: &str) -> Option<String> {
+///     # None
+///     // ...
+/// }
+///
+/// #[post("/enrich")]
+/// async fn enrich(addresses: NdJsonStream<Address>) -> impl Responder {
+///     addresses
+///         .transform(8, |addr| async move {
+///             let postcode = look_up_postcode(&addr.street).await;
+///
+///             Enriched {
+///                 street: addr.street,
+///                 postcode,
+///             }
+///         })
+///         .into_responder()
+/// }
+/// ```
+///
+/// [NDJSON]: https://github.com/ndjson/ndjson-spec
+pub struct NdJsonStream<T, const LINE_LIMIT: usize = DEFAULT_NDJSON_LINE_LIMIT> {
+    payload: Payload,
+    buf: BytesMut,
+
+    // number of bytes at the start of `buf` known not to contain a line break
+    scanned: usize,
+
+    // number of lines consumed so far
+    line: usize,
+
+    eof: bool,
+    done: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T, const LINE_LIMIT: usize> NdJsonStream<T, LINE_LIMIT> {
+    fn new(payload: Payload) -> Self {
+        Self {
+            payload,
+            buf: BytesMut::new(),
+            scanned: 0,
+            line: 0,
+            eof: false,
+            done: false,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned, const LINE_LIMIT: usize> NdJsonStream<T, LINE_LIMIT> {
+    /// Transforms each item using `f` and streams the results as an [`NdJson`] response body.
+    ///
+    /// Up to `concurrency` calls to `f` are in progress at once. Output order matches input order.
+    /// If the request stream yields an error, the response body ends with that error, which aborts
+    /// the response.
+    ///
+    /// # Panics
+    /// Panics if `concurrency` is zero.
+    pub fn transform<F, Fut, U>(
+        self,
+        concurrency: usize,
+        mut f: F,
+    ) -> NdJson<impl Stream<Item = Result<U, NdJsonStreamError>>>
+    where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = U>,
+        U: Serialize,
+    {
+        assert!(concurrency > 0, "concurrency should be non-zero");
+
+        let stream = self
+            .map(move |res| match res {
+                Ok(item) => Either::Left(f(item).map(Ok)),
+                Err(err) => Either::Right(ready(Err(err))),
+            })
+            .buffered(concurrency);
+
+        NdJson::new(stream)
+    }
+
+    /// Deserializes a single line, not including its line break.
+    fn parse_line(&self, line: &[u8]) -> Result<T, NdJsonStreamError> {
+        serde_json::from_slice(line).map_err(|source| NdJsonStreamError::Deserialize {
+            line: self.line,
+            source,
+        })
+    }
+}
+
+impl<T, const LINE_LIMIT: usize> std::fmt::Debug for NdJsonStream<T, LINE_LIMIT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NdJsonStream")
+            .field("item_type", &std::any::type_name::<T>())
+            .field("line_limit", &LINE_LIMIT)
+            .field("line", &self.line)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned, const LINE_LIMIT: usize> Stream for NdJsonStream<T, LINE_LIMIT> {
+    type Item = Result<T, NdJsonStreamError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let line_end = this.buf[this.scanned..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map(|idx| this.scanned + idx);
+
+            let line = match line_end {
+                Some(idx) => {
+                    let line = this.buf.split_to(idx + 1);
+                    this.scanned = 0;
+                    line
+                }
+
+                None if this.buf.len() > LINE_LIMIT => {
+                    this.done = true;
+
+                    return Poll::Ready(Some(Err(NdJsonStreamError::LineTooLong {
+                        line: this.line + 1,
+                        limit: LINE_LIMIT,
+                    })));
+                }
+
+                // final line without trailing line break
+                None if this.eof => {
+                    this.done = true;
+                    this.buf.split()
+                }
+
+                None => {
+                    this.scanned = this.buf.len();
+
+                    match futures_util::ready!(this.payload.poll_next_unpin(cx)) {
+                        Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+
+                        Some(Err(err)) => {
+                            this.done = true;
+                            return Poll::Ready(Some(Err(NdJsonStreamError::Payload(err))));
+                        }
+
+                        None => this.eof = true,
+                    }
+
+                    continue;
+                }
+            };
+
+            this.line += 1;
+
+            if line.trim_ascii_end().len() > LINE_LIMIT {
+                this.done = true;
+
+                return Poll::Ready(Some(Err(NdJsonStreamError::LineTooLong {
+                    line: this.line,
+                    limit: LINE_LIMIT,
+                })));
+            }
+
+            let line = line.trim_ascii();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let res = this.parse_line(line);
+
+            if res.is_err() {
+                this.done = true;
+            }
+
+            return Poll::Ready(Some(res));
+        }
+    }
+}
+
+impl<T: DeserializeOwned, const LINE_LIMIT: usize> FromRequest for NdJsonStream<T, LINE_LIMIT> {
+    type Error = NdJsonStreamError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if req.headers().contains_key(header::CONTENT_TYPE) {
+            let is_ndjson = req.mime_type().ok().flatten().is_some_and(|mime| {
+                mime.type_() == mime::APPLICATION
+                    && matches!(
+                        mime.subtype().as_str(),
+                        "x-ndjson" | "ndjson" | "jsonl" | "x-jsonlines"
+                    )
+            });
+
+            if !is_ndjson {
+                return ready(Err(NdJsonStreamError::ContentType));
+            }
+        }
+
+        ready(Ok(Self::new(payload.take())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+    use futures_util::StreamExt as _;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    async fn collect<const LINE_LIMIT: usize>(
+        body: &'static str,
+    ) -> Vec<Result<Value, NdJsonStreamError>> {
+        let (req, mut pl) = TestRequest::default().set_payload(body).to_http_parts();
+
+        NdJsonStream::<Value, LINE_LIMIT>::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .collect()
+            .await
+    }
+
+    #[actix_web::test]
+    async fn splits_lines() {
+        let items = collect::<DEFAULT_NDJSON_LINE_LIMIT>("1\n\n{\"a\":2}\r\n  \n[3]")
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(items, [json!(1), json!({ "a": 2 }), json!([3])]);
+
+        assert!(collect::<DEFAULT_NDJSON_LINE_LIMIT>("").await.is_empty());
+        assert!(collect::<DEFAULT_NDJSON_LINE_LIMIT>("\n\n")
+            .await
+            .is_empty());
+    }
+
+    #[actix_web::test]
+    async fn errors_end_stream() {
+        let items = collect::<DEFAULT_NDJSON_LINE_LIMIT>("1\nnope\n3\n").await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1],
+            Err(NdJsonStreamError::Deserialize { line: 2, .. })
+        ));
+
+        let items = collect::<8>("1\n\"this line is too long\"\n3\n").await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1],
+            Err(NdJsonStreamError::LineTooLong { line: 2, limit: 8 })
+        ));
+
+        let items = collect::<8>("\"this line is too long with no break\"").await;
+        assert!(matches!(
+            items[..],
+            [Err(NdJsonStreamError::LineTooLong { line: 1, .. })]
+        ));
+    }
+
+    #[actix_web::test]
+    async fn checks_content_type() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .to_http_parts();
+        let res = NdJsonStream::<Value>::from_request(&req, &mut pl).await;
+        assert!(matches!(res, Err(NdJsonStreamError::ContentType)));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+            .to_http_parts();
+        assert!(NdJsonStream::<Value>::from_request(&req, &mut pl)
+            .await
+            .is_ok());
+    }
+
+    #[actix_web::test]
+    async fn transform_endpoint() {
+        let app = init_service(App::new().default_service(web::to(
+            |items: NdJsonStream<Value>| async move {
+                items
+                    .transform(2, |item| async move {
+                        json!({ "doubled": item.as_i64().unwrap() * 2 })
+                    })
+                    .into_responder()
+            },
+        )))
+        .await;
+
+        let req = TestRequest::post().set_payload("1\n2\n3\n").to_request();
+        let res = call_service(&app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-ndjson",
+        );
+
+        let body = body::to_bytes(res.into_body()).await.ok().unwrap();
+        assert_eq!(body, "{\"doubled\":2}\n{\"doubled\":4}\n{\"doubled\":6}\n",);
+    }
+}
@@ -0,0 +1,271 @@
+//! Query string UTF-8 normalization middleware.
+//!
+//! See [`NormalizeQuery`] docs.
+
+use std::{
+    fmt::Write as _,
+    future::{ready, Ready},
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::uri::{PathAndQuery, Uri},
+    HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::debug;
+
+/// Percent-encoded UTF-8 form of the replacement character (U+FFFD).
+const ENCODED_REPLACEMENT_CHAR: &str = "%EF%BF%BD";
+
+/// Middleware for validating that percent-encoded query strings decode to valid UTF-8.
+///
+/// Extractors like [`Query`](crate::extract::Query) reject query strings containing invalid UTF-8
+/// with a deserialization error that is often hard to diagnose, and query strings logged by other
+/// middleware can contain garbled data. This middleware checks query strings before any extractors
+/// run. Depending on configuration, requests with invalid UTF-8 in their query strings are either:
+/// - [rejected](Self::reject) with a `400 Bad Request` response; or
+/// - [rewritten](Self::lossy) so that each invalid sequence is replaced with the percent-encoded
+///   form of the replacement character (`U+FFFD`).
+///
+/// Query strings that are valid UTF-8 are passed through unchanged, as are literal `+` characters
+/// and malformed percent-escapes.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::NormalizeQuery;
+///
+/// App::new().wrap(NormalizeQuery::lossy())
+/// # ;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizeQuery {
+    lossy: bool,
+}
+
+impl NormalizeQuery {
+    /// Constructs new middleware that rejects requests with invalid UTF-8 in their query strings.
+    pub fn reject() -> Self {
+        Self { lossy: false }
+    }
+
+    /// Constructs new middleware that replaces invalid UTF-8 in query strings with the replacement
+    /// character.
+    pub fn lossy() -> Self {
+        Self { lossy: true }
+    }
+}
+
+impl Default for NormalizeQuery {
+    fn default() -> Self {
+        Self::reject()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for NormalizeQuery
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Transform = NormalizeQueryMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(NormalizeQueryMiddleware {
+            service,
+            lossy: self.lossy,
+        }))
+    }
+}
+
+/// Middleware service implementation for [`NormalizeQuery`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct NormalizeQueryMiddleware<S> {
+    service: S,
+    lossy: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for NormalizeQueryMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        if let Some(query) = normalize_query(req.query_string()) {
+            if !self.lossy {
+                debug!(
+                    "Rejecting request to {} with invalid UTF-8 in query string",
+                    req.path()
+                );
+
+                let res = HttpResponse::BadRequest()
+                    .body("query string contains invalid UTF-8")
+                    .map_into_right_body();
+
+                return Box::pin(async move { Ok(req.into_response(res)) });
+            }
+
+            let mut parts = req.head().uri.clone().into_parts();
+            let path_and_query = format!("{}?{query}", req.path());
+            parts.path_and_query = Some(PathAndQuery::try_from(path_and_query).unwrap());
+
+            // re-encoded query string only contains characters from the original or escapes
+            let uri = Uri::from_parts(parts).unwrap();
+            req.match_info_mut().get_mut().update(&uri);
+            req.head_mut().uri = uri;
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+/// Returns a lossily re-encoded query string if any percent-encoded sequences in `query` are not
+/// valid UTF-8.
+fn normalize_query(query: &str) -> Option<String> {
+    let bytes = query.as_bytes();
+
+    let mut out = String::new();
+    let mut invalid = false;
+
+    // start of the section of `query` not yet copied to `out`
+    let mut copied = 0;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        // find a run of consecutive percent-escapes
+        let start = idx;
+        let mut decoded = Vec::new();
+
+        while let Some(byte) = decode_escape(&bytes[idx..]) {
+            decoded.push(byte);
+            idx += 3;
+        }
+
+        if decoded.is_empty() {
+            idx += 1;
+            continue;
+        }
+
+        if std::str::from_utf8(&decoded).is_ok() {
+            continue;
+        }
+
+        invalid = true;
+        out.push_str(&query[copied..start]);
+
+        for chunk in decoded.utf8_chunks() {
+            for byte in chunk.valid().bytes() {
+                write!(out, "%{byte:02X}").unwrap();
+            }
+
+            if !chunk.invalid().is_empty() {
+                out.push_str(ENCODED_REPLACEMENT_CHAR);
+            }
+        }
+
+        copied = idx;
+    }
+
+    invalid.then(|| {
+        out.push_str(&query[copied..]);
+        out
+    })
+}
+
+/// Decodes a percent-escape at the start of `bytes`.
+fn decode_escape(bytes: &[u8]) -> Option<u8> {
+    match bytes {
+        [b'%', hi, lo, ..] => {
+            let hex = |b: u8| (b as char).to_digit(16);
+            Some((hex(*hi)? * 16 + hex(*lo)?) as u8)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpRequest,
+    };
+
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize_query("a=1&b=%C3%A9+x"), None);
+        assert_eq!(normalize_query("a=%ZZ&b=%"), None);
+        assert_eq!(
+            normalize_query("a=%FF&b=caf%C3%A9%C3").unwrap(),
+            "a=%EF%BF%BD&b=caf%C3%A9%EF%BF%BD",
+        );
+        assert_eq!(
+            normalize_query("q=%41%FE%42+%2B").unwrap(),
+            "q=%41%EF%BF%BD%42+%2B",
+        );
+    }
+
+    async fn echo_query(req: HttpRequest) -> String {
+        req.query_string().to_owned()
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_utf8() {
+        let app = init_service(
+            App::new()
+                .wrap(NormalizeQuery::reject())
+                .route("/", web::get().to(echo_query)),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/?name=%C3%A9").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(read_body(res).await, "name=%C3%A9");
+
+        let req = TestRequest::with_uri("/?name=%C3").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn lossy_replacement() {
+        let app = init_service(App::new().wrap(NormalizeQuery::lossy()).route(
+            "/",
+            web::get().to(
+                |query: crate::extract::Query<BTreeMap<String, String>>| async move {
+                    query
+                        .0
+                        .into_iter()
+                        .map(|(k, v)| format!("{k}={v};"))
+                        .collect::<String>()
+                },
+            ),
+        ))
+        .await;
+
+        let req = TestRequest::with_uri("/?name=caf%C3%A9%FF&x=1").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(read_body(res).await, "name=café\u{FFFD};x=1;");
+    }
+}
@@ -0,0 +1,619 @@
+//! OpenAPI document registry and service.
+//!
+//! See [`OpenApi`] and [`openapi_json()`] docs.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{
+    http::{Method, StatusCode},
+    web, HttpResponse, Resource,
+};
+use serde_json::{json, Map, Value};
+
+/// Types that can describe themselves with a JSON Schema for use in OpenAPI documents.
+///
+/// Implementations are provided for common primitive and container types. Implement it for
+/// request and response payload types to reference them from an [`ApiOperation`].
+///
+/// # Examples
+/// ```
+/// use actix_web_lab::web::ToSchema;
+/// use serde_json::{json, Value};
+///
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// impl ToSchema for User {
+///     fn schema_name() -> Option<&'static str> {
+///         Some("User")
+///     }
+///
+///     fn schema() -> Value {
+///         json!({
+///             "type": "object",
+///             "required": ["id", "name"],
+///             "properties": {
+///                 "id": u64::schema(),
+///                 "name": String::schema(),
+///             },
+///         })
+///     }
+/// }
+/// ```
+pub trait ToSchema {
+    /// Returns the JSON Schema describing this type.
+    fn schema() -> Value;
+
+    /// Returns the name to register this type's schema under in the document's components.
+    ///
+    /// When `None` (the default), the schema is inlined wherever the type is used.
+    fn schema_name() -> Option<&'static str> {
+        None
+    }
+}
+
+macro_rules! impl_to_schema {
+    ($schema:tt => $($ty:ty),+) => {
+        $(
+            impl ToSchema for $ty {
+                fn schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )+
+    };
+}
+
+impl_to_schema!({ "type": "string" } => String, str);
+impl_to_schema!({ "type": "boolean" } => bool);
+impl_to_schema!({ "type": "integer" } => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_to_schema!({ "type": "number" } => f32, f64);
+impl_to_schema!({} => Value);
+
+impl<T: ToSchema + ?Sized> ToSchema for &T {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn schema_name() -> Option<&'static str> {
+        T::schema_name()
+    }
+}
+
+impl<T: ToSchema> ToSchema for Option<T> {
+    fn schema() -> Value {
+        json!({ "anyOf": [T::schema(), { "type": "null" }] })
+    }
+}
+
+impl<T: ToSchema> ToSchema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: ToSchema> ToSchema for [T] {
+    fn schema() -> Value {
+        Vec::<T>::schema()
+    }
+}
+
+/// A schema captured from a [`ToSchema`] type.
+#[derive(Debug, Clone)]
+struct SchemaRef {
+    name: Option<&'static str>,
+    schema: Value,
+}
+
+impl SchemaRef {
+    fn of<T: ToSchema + ?Sized>() -> Self {
+        Self {
+            name: T::schema_name(),
+            schema: T::schema(),
+        }
+    }
+
+    /// Returns a `$ref` to named schemas and the schema itself otherwise.
+    fn to_json(&self) -> Value {
+        match self.name {
+            Some(name) => json!({ "$ref": format!("#/components/schemas/{name}") }),
+            None => self.schema.clone(),
+        }
+    }
+}
+
+/// Metadata describing a single API operation (a method on a path).
+///
+/// # Examples
+/// ```
+/// use actix_web::http::StatusCode;
+/// use actix_web_lab::web::ApiOperation;
+///
+/// let op = ApiOperation::new()
+///     .summary("Get greeting")
+///     .tag("greetings")
+///     .response::<String>(StatusCode::OK, "The greeting");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ApiOperation {
+    operation_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    request_body: Option<SchemaRef>,
+    responses: BTreeMap<u16, (String, Option<SchemaRef>)>,
+}
+
+impl ApiOperation {
+    /// Constructs new operation metadata with no fields set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the operation's unique ID.
+    pub fn operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    /// Sets a short summary of what the operation does.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Sets a longer description of the operation.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds a tag used to group operations.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Sets the JSON request body type.
+    pub fn request_body<T: ToSchema + ?Sized>(mut self) -> Self {
+        self.request_body = Some(SchemaRef::of::<T>());
+        self
+    }
+
+    /// Adds a response with a JSON body of type `T`.
+    pub fn response<T: ToSchema + ?Sized>(
+        mut self,
+        status: StatusCode,
+        description: impl Into<String>,
+    ) -> Self {
+        self.responses.insert(
+            status.as_u16(),
+            (description.into(), Some(SchemaRef::of::<T>())),
+        );
+        self
+    }
+
+    /// Adds a response without a body.
+    pub fn empty_response(mut self, status: StatusCode, description: impl Into<String>) -> Self {
+        self.responses
+            .insert(status.as_u16(), (description.into(), None));
+        self
+    }
+
+    /// Collects named schemas used by this operation.
+    fn named_schemas(&self) -> impl Iterator<Item = &SchemaRef> {
+        self.request_body
+            .iter()
+            .chain(
+                self.responses
+                    .values()
+                    .filter_map(|(_, schema)| schema.as_ref()),
+            )
+            .filter(|schema| schema.name.is_some())
+    }
+
+    fn to_json(&self, path: &str) -> Value {
+        let mut op = Map::new();
+
+        if let Some(operation_id) = &self.operation_id {
+            op.insert("operationId".to_owned(), json!(operation_id));
+        }
+
+        if let Some(summary) = &self.summary {
+            op.insert("summary".to_owned(), json!(summary));
+        }
+
+        if let Some(description) = &self.description {
+            op.insert("description".to_owned(), json!(description));
+        }
+
+        if !self.tags.is_empty() {
+            op.insert("tags".to_owned(), json!(self.tags));
+        }
+
+        let params = path_params(path)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if !params.is_empty() {
+            op.insert("parameters".to_owned(), Value::Array(params));
+        }
+
+        if let Some(schema) = &self.request_body {
+            op.insert(
+                "requestBody".to_owned(),
+                json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": schema.to_json() } },
+                }),
+            );
+        }
+
+        // OpenAPI requires at least one response
+        let responses = if self.responses.is_empty() {
+            json!({ "default": { "description": "" } })
+        } else {
+            self.responses
+                .iter()
+                .map(|(status, (description, schema))| {
+                    let mut res = json!({ "description": description });
+
+                    if let Some(schema) = schema {
+                        res["content"] =
+                            json!({ "application/json": { "schema": schema.to_json() } });
+                    }
+
+                    (status.to_string(), res)
+                })
+                .collect::<Map<_, _>>()
+                .into()
+        };
+
+        op.insert("responses".to_owned(), responses);
+
+        Value::Object(op)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Document {
+    /// Path => method => operation.
+    paths: BTreeMap<String, BTreeMap<String, ApiOperation>>,
+}
+
+/// Registry of API operations that is assembled into an [OpenAPI] document.
+///
+/// The registry is a cheaply cloneable handle; clones (including [scoped](Self::scope) ones) all
+/// register operations into the same document. Registering an operation for a method and path that
+/// already has one replaces it, so it is safe to register operations inside the `App` factory
+/// closure, which runs once per worker.
+///
+/// Operations are not checked against the app's actual routes. Path patterns use Actix Web's
+/// syntax; custom regex segments (e.g., `{id:\d+}`) are reduced to plain path parameters.
+///
+/// This is a preview of first-party OpenAPI support and covers only a small subset of the
+/// specification.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::StatusCode, web, App, HttpResponse};
+/// use actix_web_lab::web::{openapi_json, ApiOperation, OpenApi};
+///
+/// let api = OpenApi::new("Greeter", "1.0.0");
+///
+/// App::new()
+///     .service(openapi_json("/openapi.json", api.clone()))
+///     .configure(|cfg| {
+///         let api = api.scope("/greet");
+///
+///         api.operation(
+///             "GET",
+///             "/{name}",
+///             ApiOperation::new()
+///                 .summary("Greet someone")
+///                 .response::<String>(StatusCode::OK, "The greeting"),
+///         );
+///
+///         cfg.service(web::scope("/greet").route(
+///             "/{name}",
+///             web::get().to(|name: web::Path<String>| async move { format!("Hello {name}!") }),
+///         ));
+///     })
+/// # ;
+/// ```
+///
+/// [OpenAPI]: https://spec.openapis.org/oas/v3.1.0
+#[derive(Clone)]
+pub struct OpenApi {
+    title: Arc<str>,
+    version: Arc<str>,
+    prefix: String,
+    document: Arc<Mutex<Document>>,
+}
+
+impl OpenApi {
+    /// Constructs new, empty registry for an API with the given title and version.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into().into(),
+            version: version.into().into(),
+            prefix: String::new(),
+            document: Arc::default(),
+        }
+    }
+
+    /// Returns a handle that registers operations with paths prefixed by `prefix`.
+    ///
+    /// Mirrors registering routes on a [`Scope`](actix_web::Scope).
+    pub fn scope(&self, prefix: &str) -> Self {
+        Self {
+            prefix: join_paths(&self.prefix, prefix),
+            ..self.clone()
+        }
+    }
+
+    /// Registers metadata for the operation handling `method` requests to `path`.
+    ///
+    /// # Panics
+    /// Panics if `method` is not a valid HTTP method.
+    pub fn operation(&self, method: impl AsRef<str>, path: &str, operation: ApiOperation) -> &Self {
+        let method = method
+            .as_ref()
+            .parse::<Method>()
+            .expect("OpenAPI operation method should be a valid HTTP method");
+
+        let path = openapi_path(&join_paths(&self.prefix, path));
+
+        self.document
+            .lock()
+            .unwrap()
+            .paths
+            .entry(path)
+            .or_default()
+            .insert(method.as_str().to_ascii_lowercase(), operation);
+
+        self
+    }
+
+    /// Assembles the OpenAPI document from the registered operations.
+    pub fn to_json(&self) -> Value {
+        let document = self.document.lock().unwrap();
+
+        let mut schemas = Map::new();
+        let mut paths = Map::new();
+
+        for (path, operations) in &document.paths {
+            let mut item = Map::new();
+
+            for (method, operation) in operations {
+                for schema in operation.named_schemas() {
+                    schemas.insert(schema.name.unwrap().to_owned(), schema.schema.clone());
+                }
+
+                item.insert(method.clone(), operation.to_json(path));
+            }
+
+            paths.insert(path.clone(), Value::Object(item));
+        }
+
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": { "title": &*self.title, "version": &*self.version },
+            "paths": paths,
+        });
+
+        if !schemas.is_empty() {
+            doc["components"] = json!({ "schemas": schemas });
+        }
+
+        doc
+    }
+}
+
+impl fmt::Debug for OpenApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenApi")
+            .field("title", &self.title)
+            .field("version", &self.version)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a resource that serves the OpenAPI document assembled from `api` at `path`.
+///
+/// The document is assembled on each request so operations registered after the service is
+/// created are included.
+///
+/// See [`OpenApi`] docs for an example.
+pub fn openapi_json(path: &str, api: OpenApi) -> Resource {
+    web::resource(path).route(web::get().to(move || {
+        let doc = api.to_json();
+        async move { HttpResponse::Ok().json(doc) }
+    }))
+}
+
+/// Joins two path segments with a single slash.
+fn join_paths(prefix: &str, path: &str) -> String {
+    match (prefix.trim_end_matches('/'), path.trim_start_matches('/')) {
+        ("", "") => "/".to_owned(),
+        (prefix, "") => prefix.to_owned(),
+        (prefix, path) => format!("{prefix}/{path}"),
+    }
+}
+
+/// Converts an Actix Web path pattern to an OpenAPI path template.
+fn openapi_path(pattern: &str) -> String {
+    let mut path = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        path.push_str(&rest[..start]);
+
+        // regex segments may contain braces so the closing brace is found by depth
+        let mut depth = 0;
+        let end = rest[start..]
+            .char_indices()
+            .find_map(|(idx, ch)| {
+                match ch {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+
+                (depth == 0).then_some(start + idx)
+            })
+            .unwrap_or(rest.len() - 1);
+
+        let segment = &rest[start + 1..end];
+        let name = segment.split_once(':').map_or(segment, |(name, _)| name);
+
+        path.push('{');
+        path.push_str(name);
+        path.push('}');
+
+        rest = &rest[end + 1..];
+    }
+
+    path.push_str(rest);
+    path
+}
+
+/// Returns the parameter names in an OpenAPI path template.
+fn path_params(path: &str) -> impl Iterator<Item = &str> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|segment| segment.split_once('}').map(|(name, _)| name))
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    use super::*;
+
+    struct User;
+
+    impl ToSchema for User {
+        fn schema_name() -> Option<&'static str> {
+            Some("User")
+        }
+
+        fn schema() -> Value {
+            json!({ "type": "object", "properties": { "name": String::schema() } })
+        }
+    }
+
+    #[test]
+    fn path_conversion() {
+        assert_eq!(openapi_path("/users"), "/users");
+        assert_eq!(openapi_path("/users/{id}"), "/users/{id}");
+        assert_eq!(openapi_path("/users/{id:\\d+}/posts"), "/users/{id}/posts");
+        assert_eq!(openapi_path("/{a:[0-9]{2}}/{b}"), "/{a}/{b}");
+
+        assert_eq!(path_params("/{a}/x/{b}").collect::<Vec<_>>(), ["a", "b"]);
+
+        assert_eq!(join_paths("", ""), "/");
+        assert_eq!(join_paths("/api/", "/users"), "/api/users");
+        assert_eq!(join_paths("/api", ""), "/api");
+    }
+
+    #[test]
+    fn assembles_document() {
+        let api = OpenApi::new("Test", "0.1.0");
+
+        api.scope("/users")
+            .operation(
+                "POST",
+                "",
+                ApiOperation::new()
+                    .summary("Create user")
+                    .request_body::<User>()
+                    .response::<User>(StatusCode::CREATED, "Created"),
+            )
+            .operation(
+                "get",
+                "/{id:\\d+}",
+                ApiOperation::new().response::<Option<User>>(StatusCode::OK, "Maybe user"),
+            );
+
+        // re-registering replaces the existing operation
+        api.operation("GET", "/health", ApiOperation::new().summary("old"));
+        api.operation(
+            "GET",
+            "/health",
+            ApiOperation::new().empty_response(StatusCode::NO_CONTENT, "Healthy"),
+        );
+
+        let doc = api.to_json();
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["info"]["title"], "Test");
+
+        let create = &doc["paths"]["/users"]["post"];
+        assert_eq!(create["summary"], "Create user");
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/User",
+        );
+        assert_eq!(create["responses"]["201"]["description"], "Created",);
+
+        let get = &doc["paths"]["/users/{id}"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "id");
+        assert_eq!(get["parameters"][0]["in"], "path");
+        assert_eq!(
+            get["responses"]["200"]["content"]["application/json"]["schema"]["anyOf"][1],
+            json!({ "type": "null" }),
+        );
+
+        let health = &doc["paths"]["/health"]["get"];
+        assert!(health.get("summary").is_none());
+        assert_eq!(
+            health["responses"]["204"],
+            json!({ "description": "Healthy" })
+        );
+
+        assert_eq!(doc["components"]["schemas"]["User"]["type"], "object",);
+    }
+
+    #[actix_web::test]
+    async fn serves_document() {
+        let api = OpenApi::new("Test", "0.1.0");
+
+        let app =
+            init_service(App::new().service(openapi_json("/openapi.json", api.clone()))).await;
+
+        // registered after the service is created
+        api.operation("GET", "/", ApiOperation::new().summary("Index"));
+
+        let req = TestRequest::get().uri("/openapi.json").to_request();
+        let res = call_service(&app, req).await;
+        assert!(res.status().is_success());
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let doc = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(doc["paths"]["/"]["get"]["summary"], "Index");
+        assert_eq!(
+            doc["paths"]["/"]["get"]["responses"]["default"]["description"],
+            ""
+        );
+    }
+}
@@ -0,0 +1,494 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    future::{ready, Future, Ready},
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{
+    body::{BoxBody, EitherBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error,
+    http::{
+        header::{self, ETag, EntityTag, IfMatch, TryIntoHeaderValue as _},
+        Method,
+    },
+    Error, HttpMessage as _, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::BoxError;
+
+/// A store of resource versions, used by [`OptimisticLocking`] to issue and verify ETags.
+///
+/// Versions are keyed by request path and are used as strong entity tags, so they must only contain
+/// characters valid in an ETag (i.e., visible ASCII characters, excluding `"`).
+pub trait VersionStore: 'static {
+    /// Error type returned when versions cannot be loaded or updated.
+    type Error: Into<BoxError> + 'static;
+
+    /// Returns the current version of the resource at `path`, or `None` if it has no version yet.
+    fn current(&self, path: &str) -> impl Future<Output = Result<Option<String>, Self::Error>>;
+
+    /// Assigns a new version to the resource at `path` if its current version is `expected`.
+    ///
+    /// The comparison and update must happen atomically. Returns `Ok(new_version)` if the version
+    /// was updated, or `Err(current_version)` if the current version did not match `expected`.
+    /// Versions must never be reused for the same path, even after being removed.
+    fn bump_if(
+        &self,
+        path: &str,
+        expected: Option<&str>,
+    ) -> impl Future<Output = Result<Result<String, Option<String>>, Self::Error>>;
+
+    /// Sets the version of the resource at `path` to `version` (or removes it, if `None`) if its
+    /// current version is `expected`.
+    ///
+    /// The comparison and update must happen atomically. Returns true if the version was updated.
+    fn reset_if(
+        &self,
+        path: &str,
+        expected: &str,
+        version: Option<&str>,
+    ) -> impl Future<Output = Result<bool, Self::Error>>;
+}
+
+/// A [`VersionStore`] that keeps incrementing version numbers in memory.
+///
+/// Version numbers are shared across all paths, so a version is never reused.
+///
+/// Cloning produces a handle to the same store, so it can be shared between workers.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryVersionStore {
+    versions: Arc<Mutex<Versions>>,
+}
+
+#[derive(Debug, Default)]
+struct Versions {
+    current: HashMap<String, String>,
+    last: u64,
+}
+
+impl InMemoryVersionStore {
+    /// Constructs a new, empty version store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VersionStore for InMemoryVersionStore {
+    type Error = Infallible;
+
+    async fn current(&self, path: &str) -> Result<Option<String>, Self::Error> {
+        let versions = self.versions.lock().unwrap();
+        Ok(versions.current.get(path).cloned())
+    }
+
+    async fn bump_if(
+        &self,
+        path: &str,
+        expected: Option<&str>,
+    ) -> Result<Result<String, Option<String>>, Self::Error> {
+        let mut versions = self.versions.lock().unwrap();
+
+        let current = versions.current.get(path).map(String::as_str);
+        if current != expected {
+            return Ok(Err(current.map(str::to_owned)));
+        }
+
+        versions.last += 1;
+        let version = versions.last.to_string();
+        versions.current.insert(path.to_owned(), version.clone());
+
+        Ok(Ok(version))
+    }
+
+    async fn reset_if(
+        &self,
+        path: &str,
+        expected: &str,
+        version: Option<&str>,
+    ) -> Result<bool, Self::Error> {
+        let mut versions = self.versions.lock().unwrap();
+
+        if versions.current.get(path).map(String::as_str) != Some(expected) {
+            return Ok(false);
+        }
+
+        match version {
+            Some(version) => versions.current.insert(path.to_owned(), version.to_owned()),
+            None => versions.current.remove(path),
+        };
+
+        Ok(true)
+    }
+}
+
+/// Middleware implementing optimistic concurrency control using ETags and `If-Match`.
+///
+/// Successful `GET` and `HEAD` responses are given an `ETag` header containing the current version
+/// of the resource at the request path, taken from a [`VersionStore`]. If the resource has no
+/// version yet, one is assigned.
+///
+/// `PUT`, `PATCH`, and `DELETE` requests to a resource that has a version must include an
+/// `If-Match` header naming the current version:
+/// - If the header is missing, a `428 Precondition Required` response is returned.
+/// - If it does not match the current version (i.e., the resource was modified since the client
+///   last read it), a `412 Precondition Failed` response is returned.
+///
+/// Writes to resources without a version (e.g., creation using `PUT`) do not require an `If-Match`
+/// header. The resource's next version is reserved before the handler is called, so only one of
+/// several concurrent writes based on the same version reaches the handler; the others receive a
+/// `412 Precondition Failed` response. If the handler does not succeed, the reservation is undone.
+/// After a successful `PUT` or `PATCH`, the new ETag is included in the response. After a
+/// successful `DELETE`, the resource's version is removed.
+///
+/// `ETag` headers already set by handlers are not overwritten.
+///
+/// # Examples
+/// ```
+/// # use actix_web::App;
+/// use actix_web_lab::middleware::{InMemoryVersionStore, OptimisticLocking};
+///
+/// let versions = InMemoryVersionStore::new();
+///
+/// App::new().wrap(OptimisticLocking::new(versions))
+/// # ;
+/// ```
+pub struct OptimisticLocking<St> {
+    store: Rc<St>,
+}
+
+impl<St> OptimisticLocking<St> {
+    /// Constructs new optimistic locking middleware using the given version store.
+    pub fn new(store: St) -> Self {
+        Self {
+            store: Rc::new(store),
+        }
+    }
+}
+
+impl<St> fmt::Debug for OptimisticLocking<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OptimisticLocking").finish_non_exhaustive()
+    }
+}
+
+impl<S, B, St> Transform<S, ServiceRequest> for OptimisticLocking<St>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    St: VersionStore,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Transform = OptimisticLockingMiddleware<S, St>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(OptimisticLockingMiddleware {
+            service: Rc::new(service),
+            store: Rc::clone(&self.store),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`OptimisticLocking`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct OptimisticLockingMiddleware<S, St> {
+    service: Rc<S>,
+    store: Rc<St>,
+}
+
+impl<S, B, St> Service<ServiceRequest> for OptimisticLockingMiddleware<S, St>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    St: VersionStore,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let store = Rc::clone(&self.store);
+
+        Box::pin(async move {
+            let method = req.method().clone();
+            let path = req.path().to_owned();
+
+            if method == Method::PUT || method == Method::PATCH || method == Method::DELETE {
+                let current = store.current(&path).await.map_err(store_error)?;
+
+                if let Some(current) = &current {
+                    if let Err(res) = check_if_match(&req, current) {
+                        return Ok(req.into_response(res).map_into_right_body());
+                    }
+                }
+
+                // reserve the next version before calling the handler so that concurrent writes
+                // based on the same version cannot both succeed
+                let version = match store
+                    .bump_if(&path, current.as_deref())
+                    .await
+                    .map_err(store_error)?
+                {
+                    Ok(version) => version,
+                    Err(_) => {
+                        let res = HttpResponse::PreconditionFailed().finish();
+                        return Ok(req.into_response(res).map_into_right_body());
+                    }
+                };
+
+                let mut res = match service.call(req).await {
+                    Ok(res) if res.status().is_success() => res,
+                    res => {
+                        store
+                            .reset_if(&path, &version, current.as_deref())
+                            .await
+                            .map_err(store_error)?;
+
+                        return res.map(ServiceResponse::map_into_left_body);
+                    }
+                };
+
+                if method == Method::DELETE {
+                    store
+                        .reset_if(&path, &version, None)
+                        .await
+                        .map_err(store_error)?;
+                } else if !res.headers().contains_key(header::ETAG) {
+                    insert_etag(&mut res, version)?;
+                }
+
+                return Ok(res.map_into_left_body());
+            }
+
+            let mut res = service.call(req).await?;
+
+            if (method == Method::GET || method == Method::HEAD)
+                && res.status().is_success()
+                && !res.headers().contains_key(header::ETAG)
+            {
+                let version = match store.current(&path).await.map_err(store_error)? {
+                    Some(version) => Some(version),
+                    // a concurrent request may assign the first version before this one does
+                    None => match store.bump_if(&path, None).await.map_err(store_error)? {
+                        Ok(version) => Some(version),
+                        Err(version) => version,
+                    },
+                };
+
+                if let Some(version) = version {
+                    insert_etag(&mut res, version)?;
+                }
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Verifies that the request's `If-Match` header matches the `current` version.
+fn check_if_match(req: &ServiceRequest, current: &str) -> Result<(), HttpResponse> {
+    if !req.headers().contains_key(header::IF_MATCH) {
+        return Err(HttpResponse::PreconditionRequired().body("If-Match header is required"));
+    }
+
+    let current = EntityTag::new_strong(current.to_owned());
+
+    match req.get_header::<IfMatch>() {
+        Some(IfMatch::Any) => Ok(()),
+        Some(IfMatch::Items(tags)) if tags.iter().any(|tag| tag.strong_eq(&current)) => Ok(()),
+        _ => Err(HttpResponse::PreconditionFailed().finish()),
+    }
+}
+
+fn insert_etag<B>(res: &mut ServiceResponse<B>, version: String) -> Result<(), Error> {
+    let etag = ETag(EntityTag::new_strong(version))
+        .try_into_value()
+        .map_err(error::ErrorInternalServerError)?;
+
+    res.headers_mut().insert(header::ETAG, etag);
+
+    Ok(())
+}
+
+fn store_error(err: impl Into<BoxError>) -> Error {
+    error::ErrorInternalServerError(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, pin::pin, task::Poll};
+
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn get_then_conditional_put() {
+        let app = init_service(
+            App::new()
+                .wrap(OptimisticLocking::new(InMemoryVersionStore::new()))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::with_uri("/item/1").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+        // version is stable between reads
+        let res = call_service(&app, TestRequest::with_uri("/item/1").to_request()).await;
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+        let req = TestRequest::put().uri("/item/1").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_REQUIRED);
+
+        let req = TestRequest::put()
+            .uri("/item/1")
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+        // stale version
+        let req = TestRequest::patch()
+            .uri("/item/1")
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let req = TestRequest::patch()
+            .uri("/item/1")
+            .insert_header((header::IF_MATCH, "*"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"3\"");
+    }
+
+    #[actix_web::test]
+    async fn unversioned_and_failed_writes() {
+        let store = InMemoryVersionStore::new();
+
+        let app = init_service(
+            App::new()
+                .wrap(OptimisticLocking::new(store.clone()))
+                .route("/new", web::put().to(HttpResponse::Created))
+                .route("/bad", web::put().to(HttpResponse::BadRequest)),
+        )
+        .await;
+
+        // creation does not require If-Match
+        let req = TestRequest::put().uri("/new").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+        // unsuccessful writes do not bump the version
+        let req = TestRequest::put().uri("/bad").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(res.headers().get(header::ETAG).is_none());
+        assert_eq!(store.current("/bad").await.unwrap(), None);
+    }
+
+    #[actix_web::test]
+    async fn interleaved_writes() {
+        let gate = Arc::new(Notify::new());
+
+        let app = init_service(
+            App::new()
+                .wrap(OptimisticLocking::new(InMemoryVersionStore::new()))
+                .route("/item", web::get().to(HttpResponse::Ok))
+                .route(
+                    "/item",
+                    web::put().to({
+                        let gate = Arc::clone(&gate);
+                        move || {
+                            let gate = Arc::clone(&gate);
+                            async move {
+                                gate.notified().await;
+                                HttpResponse::Ok().finish()
+                            }
+                        }
+                    }),
+                ),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::with_uri("/item").to_request()).await;
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+        let put = || {
+            TestRequest::put()
+                .uri("/item")
+                .insert_header((header::IF_MATCH, "\"1\""))
+                .to_request()
+        };
+
+        // first write is waiting in the handler
+        let mut first = pin!(app.call(put()));
+        assert!(poll_fn(|cx| Poll::Ready(first.as_mut().poll(cx).is_pending())).await);
+
+        // second write, based on the same version, is rejected without reaching the handler
+        let res = call_service(&app, put()).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        gate.notify_one();
+        let res = first.await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"2\"");
+    }
+
+    #[actix_web::test]
+    async fn delete_removes_version() {
+        let store = InMemoryVersionStore::new();
+
+        let app = init_service(
+            App::new()
+                .wrap(OptimisticLocking::new(store.clone()))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::put().uri("/item").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+        let req = TestRequest::delete().uri("/item").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_REQUIRED);
+
+        let req = TestRequest::delete()
+            .uri("/item")
+            .insert_header((header::IF_MATCH, "\"1\""))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(store.current("/item").await.unwrap(), None);
+
+        // re-creation does not require If-Match and does not reuse the old version
+        let req = TestRequest::put().uri("/item").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"3\"");
+    }
+}
@@ -0,0 +1,204 @@
+//! Priority-ordered middleware stack.
+//!
+//! See [`OrderedMiddleware`] docs.
+
+use std::{fmt, rc::Rc};
+
+use actix_service::{boxed, ServiceExt as _};
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::error;
+
+/// Type-erased service that each layer of an [`OrderedMiddleware`] stack wraps.
+pub type OrderedService = boxed::BoxService<ServiceRequest, ServiceResponse<BoxBody>, Error>;
+
+type Layer = dyn Fn(OrderedService) -> LocalBoxFuture<'static, Result<OrderedService, ()>>;
+
+/// Middleware stack that is assembled by explicit priority instead of declaration order.
+///
+/// With `App::wrap()`, the middleware registered _last_ runs _first_, which is easy to get wrong
+/// when combining several middleware (e.g., [`CatchPanic`] needs to be outermost to catch panics
+/// from other middleware). `OrderedMiddleware` lets each middleware declare a priority; higher
+/// priorities are placed further out and so see requests earlier and responses later. Middleware
+/// with equal priorities are ordered as they are declared, with the first declared outermost.
+///
+/// Response bodies are boxed at each layer so that middleware with different body types can be
+/// combined. Middleware errors must be convertible into [`actix_web::Error`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::Logger, App};
+/// use actix_web_lab::middleware::{CatchPanic, NormalizePath, OrderedMiddleware};
+///
+/// App::new().wrap(
+///     OrderedMiddleware::new()
+///         .wrap(0, NormalizePath::trim())
+///         .wrap(50, Logger::default())
+///         // declared last but still placed outermost
+///         .wrap(100, CatchPanic::default()),
+/// )
+/// # ;
+/// ```
+///
+/// [`CatchPanic`]: crate::middleware::CatchPanic
+#[doc(alias = "wrap_ordered")]
+#[derive(Default)]
+pub struct OrderedMiddleware {
+    /// Layers sorted from outermost to innermost.
+    layers: Vec<(i32, Rc<Layer>)>,
+}
+
+impl OrderedMiddleware {
+    /// Constructs new, empty middleware stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a middleware to the stack with the given priority.
+    ///
+    /// Higher priorities are placed further out in the stack.
+    ///
+    /// If the middleware fails to initialize, its error is logged along with its priority and the
+    /// whole stack fails to initialize.
+    pub fn wrap<M, B>(mut self, priority: i32, middleware: M) -> Self
+    where
+        M: Transform<OrderedService, ServiceRequest, Response = ServiceResponse<B>> + 'static,
+        M::Transform: 'static,
+        M::Error: Into<Error>,
+        M::InitError: fmt::Debug,
+        M::Future: 'static,
+        <M::Transform as Service<ServiceRequest>>::Future: 'static,
+        B: MessageBody + 'static,
+    {
+        let layer = move |service: OrderedService| -> LocalBoxFuture<'static, _> {
+            let fut = middleware.new_transform(service);
+
+            Box::pin(async move {
+                let service = fut.await.map_err(|err| {
+                    error!("failed to initialize middleware with priority {priority}: {err:?}");
+                })?;
+
+                Ok(boxed::service(
+                    service
+                        .map(ServiceResponse::map_into_boxed_body)
+                        .map_err(Into::into),
+                ))
+            })
+        };
+
+        // place after existing layers with equal or higher priority
+        let idx = self
+            .layers
+            .iter()
+            .position(|(existing, _)| *existing < priority)
+            .unwrap_or(self.layers.len());
+
+        self.layers.insert(idx, (priority, Rc::new(layer)));
+        self
+    }
+}
+
+impl fmt::Debug for OrderedMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderedMiddleware")
+            .field(
+                "priorities",
+                &self.layers.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for OrderedMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = OrderedService;
+    type InitError = ();
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let layers = self
+            .layers
+            .iter()
+            .map(|(_, layer)| Rc::clone(layer))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            let mut service = boxed::service(service.map(ServiceResponse::map_into_boxed_body));
+
+            // build from the inside out
+            for layer in layers.iter().rev() {
+                service = layer(service).await?;
+            }
+
+            Ok(service)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    use super::*;
+    use crate::test::ProbeLog;
+
+    #[actix_web::test]
+    async fn orders_by_priority() {
+        let log = ProbeLog::new();
+
+        let app = init_service(
+            App::new()
+                .wrap(
+                    OrderedMiddleware::new()
+                        .wrap(0, log.probe("inner"))
+                        .wrap(100, log.probe("outer"))
+                        .wrap(50, log.probe("middle-1"))
+                        .wrap(50, log.probe("middle-2")),
+                )
+                .default_service(log.service()),
+        )
+        .await;
+
+        call_service(&app, TestRequest::default().to_request()).await;
+
+        log.assert_order(&[
+            "req:outer",
+            "req:middle-1",
+            "req:middle-2",
+            "req:inner",
+            "handler",
+            "res:inner",
+            "res:middle-2",
+            "res:middle-1",
+            "res:outer",
+        ]);
+    }
+
+    #[actix_web::test]
+    async fn empty_stack() {
+        let log = ProbeLog::new();
+
+        let app = init_service(
+            App::new()
+                .wrap(OrderedMiddleware::new())
+                .default_service(log.service()),
+        )
+        .await;
+
+        call_service(&app, TestRequest::default().to_request()).await;
+        log.assert_order(&["handler"]);
+    }
+}
@@ -0,0 +1,337 @@
+//! Origin and Referer typed headers.
+//!
+//! See [`Origin`] and [`Referer`] docs.
+
+use std::{fmt, str};
+
+use actix_web::{
+    dev::ConnectionInfo,
+    error::ParseError,
+    http::header::{self, from_one_raw_str, Header, HeaderName, HeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+use url::Url;
+
+/// The `Origin` header, defined in [RFC 6454 §7].
+///
+/// Indicates the origin (scheme, host, and port) that caused the request. Browsers send it on
+/// cross-origin requests and on all non-`GET`/`HEAD` requests, which makes it a useful signal for
+/// rejecting cross-site request forgery. Privacy-sensitive contexts send the opaque origin `null`.
+///
+/// [Read more on MDN.](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Origin)
+///
+/// # Examples
+/// ```
+/// use actix_web::{web::Header, HttpRequest, HttpResponse};
+/// use actix_web_lab::header::Origin;
+///
+/// async fn handler(req: HttpRequest, Header(origin): Header<Origin>) -> HttpResponse {
+///     if !origin.same_origin(&req.connection_info()) {
+///         return HttpResponse::Forbidden().finish();
+///     }
+///
+///     HttpResponse::Ok().finish()
+/// }
+/// ```
+///
+/// [RFC 6454 §7]: https://datatracker.ietf.org/doc/html/rfc6454#section-7
+#[derive(Debug, Clone)]
+pub struct Origin(url::Origin);
+
+impl Origin {
+    /// Constructs the opaque `null` origin.
+    pub fn null() -> Self {
+        Self(url::Origin::new_opaque())
+    }
+
+    /// Returns true if this is the opaque `null` origin.
+    pub fn is_null(&self) -> bool {
+        !self.0.is_tuple()
+    }
+
+    /// Returns the parsed origin.
+    pub fn as_url_origin(&self) -> &url::Origin {
+        &self.0
+    }
+
+    /// Returns the origin's host and, if it is not the scheme's default, port.
+    ///
+    /// Returns `None` for the `null` origin.
+    pub fn authority(&self) -> Option<String> {
+        match &self.0 {
+            url::Origin::Opaque(_) => None,
+            url::Origin::Tuple(scheme, host, port) => {
+                Some(match default_port(scheme) == Some(*port) {
+                    true => host.to_string(),
+                    false => format!("{host}:{port}"),
+                })
+            }
+        }
+    }
+
+    /// Returns true if this origin matches the scheme and host that the request was sent to.
+    ///
+    /// The `null` origin is never the same origin as any request.
+    pub fn same_origin(&self, conn: &ConnectionInfo) -> bool {
+        let Ok(url) = Url::parse(&format!("{}://{}", conn.scheme(), conn.host())) else {
+            return false;
+        };
+
+        self.0.is_tuple() && self.0 == url.origin()
+    }
+}
+
+// opaque origins are never equal to each other but all `null` header values are equivalent
+impl PartialEq for Origin {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (url::Origin::Opaque(_), url::Origin::Opaque(_)) => true,
+            (this, other) => this == other,
+        }
+    }
+}
+
+impl Eq for Origin {}
+
+impl From<&Url> for Origin {
+    fn from(url: &Url) -> Self {
+        Self(url.origin())
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.ascii_serialization())
+    }
+}
+
+impl str::FromStr for Origin {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let val = val.trim();
+
+        if val == "null" {
+            return Ok(Self::null());
+        }
+
+        let url = Url::parse(val).map_err(|_| ParseError::Header)?;
+
+        // origins are only a scheme, host, and optional port
+        if url.path() != "/"
+            || val.ends_with('/')
+            || url.query().is_some()
+            || url.fragment().is_some()
+            || !url.username().is_empty()
+            || url.password().is_some()
+        {
+            return Err(ParseError::Header);
+        }
+
+        let origin = url.origin();
+
+        if !origin.is_tuple() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(Self(origin))
+    }
+}
+
+impl TryIntoHeaderValue for Origin {
+    type Error = header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for Origin {
+    fn name() -> HeaderName {
+        header::ORIGIN
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(Self::name()))
+    }
+}
+
+/// The `Referer` header, defined in [RFC 9110 §10.1.3].
+///
+/// Contains the address of the resource from which the request was made. Only absolute URLs are
+/// accepted; relative references are rejected when parsing.
+///
+/// Browsers may omit or truncate this header depending on the page's referrer policy so it should
+/// only be used as a fallback when [`Origin`] is not sent.
+///
+/// [Read more on MDN.](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Referer)
+///
+/// # Examples
+/// ```
+/// use actix_web::{web::Header, HttpResponse};
+/// use actix_web_lab::header::Referer;
+///
+/// async fn handler(Header(referer): Header<Referer>) -> HttpResponse {
+///     HttpResponse::Ok().body(format!("came from {}", referer.url().path()))
+/// }
+/// ```
+///
+/// [RFC 9110 §10.1.3]: https://datatracker.ietf.org/doc/html/rfc9110#section-10.1.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[doc(alias = "referrer")]
+pub struct Referer(Url);
+
+impl Referer {
+    /// Constructs new `Referer` header from a URL.
+    pub fn new(url: Url) -> Self {
+        Self(url)
+    }
+
+    /// Returns the referring URL.
+    pub fn url(&self) -> &Url {
+        &self.0
+    }
+
+    /// Returns the origin of the referring URL.
+    pub fn origin(&self) -> Origin {
+        Origin::from(&self.0)
+    }
+
+    /// Returns true if the referring URL has the same origin as the request.
+    ///
+    /// See [`Origin::same_origin()`].
+    pub fn same_origin(&self, conn: &ConnectionInfo) -> bool {
+        self.origin().same_origin(conn)
+    }
+
+    /// Unwraps into the referring URL.
+    pub fn into_inner(self) -> Url {
+        self.0
+    }
+}
+
+impl fmt::Display for Referer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.as_str())
+    }
+}
+
+impl str::FromStr for Referer {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        Url::parse(val.trim())
+            .map(Self)
+            .map_err(|_| ParseError::Header)
+    }
+}
+
+impl TryIntoHeaderValue for Referer {
+    type Error = header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.0.as_str())
+    }
+}
+
+impl Header for Referer {
+    fn name() -> HeaderName {
+        header::REFERER
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(Self::name()))
+    }
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        "ftp" => Some(21),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    fn origin(val: &str) -> Origin {
+        val.parse().unwrap()
+    }
+
+    #[test]
+    fn origin_parsing() {
+        assert_parse_eq::<Origin, _, _>(["null"], Origin::null());
+        assert_parse_eq::<Origin, _, _>(["https://example.com"], origin("https://example.com"));
+        assert_parse_eq::<Origin, _, _>(["https://example.com:443"], origin("https://example.com"));
+
+        assert_parse_fail::<Origin, _, _>([""]);
+        assert_parse_fail::<Origin, _, _>(["example.com"]);
+        assert_parse_fail::<Origin, _, _>(["https://example.com/"]);
+        assert_parse_fail::<Origin, _, _>(["https://example.com/path"]);
+        assert_parse_fail::<Origin, _, _>(["https://user@example.com"]);
+    }
+
+    #[test]
+    fn origin_formatting() {
+        assert_eq!(Origin::null().to_string(), "null");
+        assert_eq!(
+            origin("HTTPS://Example.com:443").to_string(),
+            "https://example.com",
+        );
+        assert_eq!(
+            origin("http://localhost:8080").try_into_value().unwrap(),
+            "http://localhost:8080",
+        );
+    }
+
+    #[test]
+    fn origin_authority() {
+        assert_eq!(Origin::null().authority(), None);
+        assert_eq!(
+            origin("https://example.com").authority().unwrap(),
+            "example.com",
+        );
+        assert_eq!(
+            origin("http://localhost:8080").authority().unwrap(),
+            "localhost:8080",
+        );
+    }
+
+    #[test]
+    fn same_origin() {
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "example.com"))
+            .to_http_request();
+        let conn = req.connection_info();
+
+        assert!(origin("http://example.com").same_origin(&conn));
+        assert!(origin("http://example.com:80").same_origin(&conn));
+        assert!(!origin("https://example.com").same_origin(&conn));
+        assert!(!origin("http://example.com:8080").same_origin(&conn));
+        assert!(!origin("http://evil.com").same_origin(&conn));
+        assert!(!Origin::null().same_origin(&conn));
+
+        let referer = "http://example.com/page?q=1".parse::<Referer>().unwrap();
+        assert!(referer.same_origin(&conn));
+
+        let referer = "http://evil.com/page".parse::<Referer>().unwrap();
+        assert!(!referer.same_origin(&conn));
+    }
+
+    #[test]
+    fn referer_parsing() {
+        assert_parse_eq::<Referer, _, _>(
+            ["https://example.com/page"],
+            Referer::new(Url::parse("https://example.com/page").unwrap()),
+        );
+
+        assert_parse_fail::<Referer, _, _>([""]);
+        assert_parse_fail::<Referer, _, _>(["/relative/path"]);
+    }
+}
@@ -4,13 +4,15 @@ use actix_router::PathDeserializer;
 use actix_utils::future::{ready, Ready};
 use actix_web::{
     dev::Payload,
-    error::{Error, ErrorNotFound},
-    FromRequest, HttpRequest,
+    http::{header::ContentType, StatusCode},
+    FromRequest, HttpRequest, HttpResponse, ResponseError,
 };
-use derive_more::Display;
+use derive_more::{Display, Error};
 use serde::de;
 use tracing::debug;
 
+use crate::lab_config::{LabConfig, PathErrorHandler};
+
 /// Extract typed data from request path segments.
 ///
 /// Alternative to `web::Path` extractor from Actix Web that allows deconstruction, but omits the
@@ -53,6 +55,12 @@ use tracing::debug;
 ///     format!("Welcome {}!", info.name)
 /// }
 /// ```
+///
+/// # Errors
+/// Deserialization failures respond with `404 Not Found` by default. This can be changed using
+/// [`LabConfig::path_error_status()`](crate::extract::LabConfig::path_error_status), or the whole
+/// response can be customized using
+/// [`LabConfig::path_error_handler()`](crate::extract::LabConfig::path_error_handler).
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Display)]
 pub struct Path<T>(pub T);
 
@@ -71,7 +79,7 @@ impl<T> FromRequest for Path<T>
 where
     T: de::DeserializeOwned,
 {
-    type Error = Error;
+    type Error = PathDeserializeError;
     type Future = Ready<Result<Self, Self::Error>>;
 
     #[inline]
@@ -86,12 +94,48 @@ where
                         req.path()
                     );
 
-                    ErrorNotFound(err)
+                    PathDeserializeError {
+                        source: err,
+                        status: LabConfig::path_error_status_for(req),
+                        handler: LabConfig::path_error_handler_for(req)
+                            .map(|handler| (handler, req.clone())),
+                    }
                 }),
         )
     }
 }
 
+/// Deserialization errors that can occur during parsing path segments.
+///
+/// Responds with `404 Not Found` unless configured otherwise using
+/// [`LabConfig::path_error_status()`](crate::extract::LabConfig::path_error_status) or
+/// [`LabConfig::path_error_handler()`](crate::extract::LabConfig::path_error_handler).
+#[derive(Debug, Display, Error)]
+#[display("{source}")]
+pub struct PathDeserializeError {
+    source: de::value::Error,
+    #[error(not(source))]
+    status: StatusCode,
+    #[error(not(source))]
+    handler: Option<(PathErrorHandler, HttpRequest)>,
+}
+
+impl ResponseError for PathDeserializeError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match &self.handler {
+            Some((handler, req)) => (handler.0)(self, req),
+            // same as default `ResponseError::error_response()`
+            None => HttpResponse::build(self.status_code())
+                .insert_header(ContentType::plaintext())
+                .body(self.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::{dev::ResourceDef, test::TestRequest};
@@ -207,6 +251,47 @@ mod tests {
         assert_eq!(s[1], "32".to_owned());
     }
 
+    #[actix_web::test]
+    async fn error_status() {
+        let resource = ResourceDef::new("/{value}");
+
+        let mut req = TestRequest::with_uri("/abc").to_srv_request();
+        resource.capture_match_info(req.match_info_mut());
+        let (req, mut pl) = req.into_parts();
+
+        let err = Path::<u32>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+
+        let mut req = TestRequest::with_uri("/abc")
+            .app_data(LabConfig::new().path_error_status(StatusCode::BAD_REQUEST))
+            .to_srv_request();
+        resource.capture_match_info(req.match_info_mut());
+        let (req, mut pl) = req.into_parts();
+
+        let err = Path::<u32>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn error_handler() {
+        let resource = ResourceDef::new("/{value}");
+
+        let mut req = TestRequest::with_uri("/abc")
+            .app_data(LabConfig::new().path_error_handler(|err, req| {
+                HttpResponse::BadRequest().body(format!("{}: {err}", req.path()))
+            }))
+            .to_srv_request();
+        resource.capture_match_info(req.match_info_mut());
+        let (req, mut pl) = req.into_parts();
+
+        let err = Path::<u32>::from_request(&req, &mut pl).await.unwrap_err();
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "/abc: can not parse \"abc\" to a u32");
+    }
+
     #[actix_web::test]
     async fn paths_decoded() {
         let resource = ResourceDef::new("/{key}/{value}");
@@ -0,0 +1,647 @@
+//! Client certificate information for mutual TLS.
+//!
+//! See [`PeerCert`] docs.
+
+use std::{
+    fmt::{self, Write as _},
+    net::IpAddr,
+};
+
+use actix_utils::future::{err, ok, Ready};
+use actix_web::{
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error,
+    guard::{Guard, GuardContext},
+    middleware::Next,
+    FromRequest, HttpMessage as _, HttpRequest,
+};
+use derive_more::{Display, Error};
+use sha2::{Digest as _, Sha256};
+use tracing::debug;
+
+/// Error returned when a client certificate cannot be parsed.
+#[derive(Debug, Display, Error)]
+#[display("invalid X.509 certificate")]
+#[non_exhaustive]
+pub struct PeerCertError;
+
+/// Subject alternative name of a client certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubjectAltName {
+    /// DNS name.
+    Dns(String),
+
+    /// Email address.
+    Email(String),
+
+    /// URI (e.g., a SPIFFE ID).
+    Uri(String),
+
+    /// IP address.
+    Ip(IpAddr),
+}
+
+/// Client certificate presented during a mutual TLS handshake.
+///
+/// Provides typed access to the subject, subject alternative names, and fingerprint of the
+/// client's leaf certificate.
+///
+/// # Setup
+/// The certificate must be stored in the connection's data when the connection is accepted, using
+/// [`HttpServer::on_connect()`]. With the `rustls-0_23` crate feature enabled,
+/// `PeerCert::rustls_0_23_on_connect` can be used directly. For other TLS implementations,
+/// construct a `PeerCert` from the DER-encoded certificate using [`from_der()`](Self::from_der)
+/// and insert it into the connection's extensions.
+///
+/// Certificates presented by clients are verified during the handshake by the server's
+/// configured client certificate verifier, so the presence of a `PeerCert` indicates a verified
+/// client certificate.
+///
+/// # Extractor
+/// Extraction fails with a `401 Unauthorized` response if the client did not present a
+/// certificate. Use `Option<PeerCert>` for endpoints where a client certificate is optional.
+///
+/// # Guard
+/// [`guard::client_cert()`](crate::guard::client_cert) requires that the client presented a
+/// certificate. Since guards cannot access connection data, the
+/// [`load_peer_cert`](crate::middleware::load_peer_cert) middleware must be used, too.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::extract::PeerCert;
+///
+/// #[get("/whoami")]
+/// async fn whoami(cert: PeerCert) -> impl Responder {
+///     format!(
+///         "subject: {}\nfingerprint: {}",
+///         cert.subject(),
+///         cert.fingerprint_sha256_hex(),
+///     )
+/// }
+/// ```
+///
+/// [`HttpServer::on_connect()`]: actix_web::HttpServer::on_connect
+#[derive(Clone, PartialEq, Eq)]
+pub struct PeerCert {
+    der: Vec<u8>,
+    subject: String,
+    common_name: Option<String>,
+    subject_alt_names: Vec<SubjectAltName>,
+    fingerprint: [u8; 32],
+}
+
+impl PeerCert {
+    /// Parses a DER-encoded X.509 certificate.
+    ///
+    /// # Errors
+    /// Returns an error if `der` is not a well-formed X.509 certificate.
+    pub fn from_der(der: impl Into<Vec<u8>>) -> Result<Self, PeerCertError> {
+        let der = der.into();
+        let info = x509::parse(&der).ok_or(PeerCertError)?;
+
+        Ok(Self {
+            fingerprint: Sha256::digest(&der).into(),
+            der,
+            subject: info.subject,
+            common_name: info.common_name,
+            subject_alt_names: info.subject_alt_names,
+        })
+    }
+
+    /// Returns the DER-encoded certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Returns the certificate's subject as comma-separated attributes, in certificate order
+    /// (e.g., `C=GB, O=Acme Corp, CN=client.example.com`).
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the common name (CN) attribute of the certificate's subject, if present.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Returns the certificate's subject alternative names.
+    ///
+    /// Name types other than those in [`SubjectAltName`] are omitted.
+    pub fn subject_alt_names(&self) -> &[SubjectAltName] {
+        &self.subject_alt_names
+    }
+
+    /// Returns the SHA-256 fingerprint of the certificate.
+    pub fn fingerprint_sha256(&self) -> &[u8; 32] {
+        &self.fingerprint
+    }
+
+    /// Returns the SHA-256 fingerprint of the certificate as lowercase hex.
+    pub fn fingerprint_sha256_hex(&self) -> String {
+        self.fingerprint
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            })
+    }
+
+    /// Connection callback that stores the client's certificate for Rustls v0.23 connections.
+    ///
+    /// Pass to [`HttpServer::on_connect()`](actix_web::HttpServer::on_connect).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{App, HttpServer};
+    /// use actix_web_lab::extract::PeerCert;
+    ///
+    /// # fn tls_config() -> rustls::ServerConfig { unimplemented!() }
+    /// # async fn run() -> std::io::Result<()> {
+    /// HttpServer::new(|| App::new())
+    ///     .on_connect(PeerCert::rustls_0_23_on_connect)
+    ///     .bind_rustls_0_23(("127.0.0.1", 8443), tls_config())?
+    ///     .run()
+    ///     .await
+    /// # }
+    /// ```
+    #[cfg(feature = "rustls-0_23")]
+    pub fn rustls_0_23_on_connect(conn: &dyn std::any::Any, ext: &mut actix_web::dev::Extensions) {
+        use actix_tls::accept::rustls_0_23::TlsStream;
+        use actix_web::rt::net::TcpStream;
+
+        let Some(tls) = conn.downcast_ref::<TlsStream<TcpStream>>() else {
+            return;
+        };
+
+        let (_, session) = tls.get_ref();
+
+        let Some(leaf) = session.peer_certificates().and_then(|certs| certs.first()) else {
+            return;
+        };
+
+        match Self::from_der(leaf.as_ref()) {
+            Ok(cert) => {
+                ext.insert(cert);
+            }
+            Err(_) => debug!("failed to parse client certificate"),
+        }
+    }
+
+    /// Returns client certificate from connection data or, if loaded by middleware, request data.
+    fn from_req(req: &HttpRequest) -> Option<Self> {
+        req.conn_data::<Self>()
+            .cloned()
+            .or_else(|| req.extensions().get::<Self>().cloned())
+    }
+}
+
+impl fmt::Debug for PeerCert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PeerCert")
+            .field("subject", &self.subject)
+            .field("subject_alt_names", &self.subject_alt_names)
+            .field("fingerprint", &self.fingerprint_sha256_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromRequest for PeerCert {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match Self::from_req(req) {
+            Some(cert) => ok(cert),
+            None => {
+                debug!(
+                    "Failed to extract `PeerCert` for `{}` handler. Either the client did not \
+                    present a certificate or no connection callback is storing certificates.",
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                err(error::ErrorUnauthorized("client certificate required"))
+            }
+        }
+    }
+}
+
+/// A function middleware that makes the client certificate available to guards.
+///
+/// Required by [`guard::client_cert()`](crate::guard::client_cert). See [`PeerCert`] docs.
+///
+/// # Examples
+/// ```
+/// # use actix_web::App;
+/// use actix_web::middleware::from_fn;
+/// use actix_web_lab::middleware::load_peer_cert;
+///
+/// App::new().wrap(from_fn(load_peer_cert))
+///     # ;
+/// ```
+pub async fn load_peer_cert(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if let Some(cert) = req.conn_data::<PeerCert>().cloned() {
+        req.extensions_mut().insert(cert);
+    }
+
+    next.call(req).await
+}
+
+/// Creates a guard that requires the client to have presented a verified certificate.
+///
+/// The [`load_peer_cert`](crate::middleware::load_peer_cert) middleware must wrap the app or
+/// scope. See [`PeerCert`] docs.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::from_fn, web, App, HttpResponse};
+/// use actix_web_lab::{guard::client_cert, middleware::load_peer_cert};
+///
+/// App::new().wrap(from_fn(load_peer_cert)).service(
+///     web::resource("/internal")
+///         .guard(client_cert())
+///         .to(HttpResponse::Ok),
+/// )
+/// # ;
+/// ```
+pub fn client_cert() -> impl Guard {
+    ClientCertGuard
+}
+
+struct ClientCertGuard;
+
+impl Guard for ClientCertGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        ctx.req_data().contains::<PeerCert>()
+    }
+}
+
+/// Minimal DER reader for the certificate fields exposed by [`PeerCert`].
+mod x509 {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use super::SubjectAltName;
+
+    const SEQUENCE: u8 = 0x30;
+    const SET: u8 = 0x31;
+    const OID: u8 = 0x06;
+    const OCTET_STRING: u8 = 0x04;
+    const BOOLEAN: u8 = 0x01;
+    const VERSION: u8 = 0xa0;
+    const EXTENSIONS: u8 = 0xa3;
+
+    const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+    const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+
+    pub(super) struct CertInfo {
+        pub(super) subject: String,
+        pub(super) common_name: Option<String>,
+        pub(super) subject_alt_names: Vec<SubjectAltName>,
+    }
+
+    /// Reads one tag-length-value item, returning the tag, value, and remaining input.
+    fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+        let (&tag, input) = input.split_first()?;
+        let (&len, mut input) = input.split_first()?;
+
+        let len = if len < 0x80 {
+            usize::from(len)
+        } else {
+            let n_bytes = usize::from(len & 0x7f);
+
+            if n_bytes == 0 || n_bytes > 4 || input.len() < n_bytes {
+                return None;
+            }
+
+            let (len_bytes, rest) = input.split_at(n_bytes);
+            input = rest;
+
+            len_bytes
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | usize::from(byte))
+        };
+
+        if input.len() < len {
+            return None;
+        }
+
+        let (value, rest) = input.split_at(len);
+        Some((tag, value, rest))
+    }
+
+    /// Reads an item that must have the given tag.
+    fn expect(tag: u8, input: &[u8]) -> Option<(&[u8], &[u8])> {
+        let (actual, value, rest) = read_tlv(input)?;
+        (actual == tag).then_some((value, rest))
+    }
+
+    pub(super) fn parse(der: &[u8]) -> Option<CertInfo> {
+        let (cert, _) = expect(SEQUENCE, der)?;
+        let (tbs, _) = expect(SEQUENCE, cert)?;
+
+        let mut tbs = tbs;
+
+        // optional version
+        if let Some((VERSION, _, rest)) = read_tlv(tbs) {
+            tbs = rest;
+        }
+
+        // serial number, signature algorithm, issuer, validity
+        for _ in 0..4 {
+            tbs = read_tlv(tbs)?.2;
+        }
+
+        let (subject, rest) = expect(SEQUENCE, tbs)?;
+        let (subject, common_name) = parse_name(subject)?;
+
+        // subject public key info
+        let (_, _, mut rest) = read_tlv(rest)?;
+
+        let mut subject_alt_names = Vec::new();
+
+        // optional issuer/subject unique IDs and extensions
+        while !rest.is_empty() {
+            let (tag, value, next) = read_tlv(rest)?;
+
+            if tag == EXTENSIONS {
+                let (extensions, _) = expect(SEQUENCE, value)?;
+                subject_alt_names = parse_extensions(extensions)?;
+            }
+
+            rest = next;
+        }
+
+        Some(CertInfo {
+            subject,
+            common_name,
+            subject_alt_names,
+        })
+    }
+
+    /// Parses a distinguished name into a display string and its common name.
+    fn parse_name(mut name: &[u8]) -> Option<(String, Option<String>)> {
+        let mut display = String::new();
+        let mut common_name = None;
+
+        while !name.is_empty() {
+            let (mut rdn, rest) = expect(SET, name)?;
+            name = rest;
+
+            while !rdn.is_empty() {
+                let (attr, rest) = expect(SEQUENCE, rdn)?;
+                rdn = rest;
+
+                let (oid, attr) = expect(OID, attr)?;
+                let (tag, value, _) = read_tlv(attr)?;
+                let value = decode_string(tag, value)?;
+
+                if !display.is_empty() {
+                    display.push_str(", ");
+                }
+
+                display.push_str(&attribute_name(oid));
+                display.push('=');
+                display.push_str(&value);
+
+                if oid == OID_COMMON_NAME {
+                    common_name = Some(value);
+                }
+            }
+        }
+
+        Some((display, common_name))
+    }
+
+    fn parse_extensions(mut extensions: &[u8]) -> Option<Vec<SubjectAltName>> {
+        while !extensions.is_empty() {
+            let (ext, rest) = expect(SEQUENCE, extensions)?;
+            extensions = rest;
+
+            let (oid, mut ext) = expect(OID, ext)?;
+
+            if oid != OID_SUBJECT_ALT_NAME {
+                continue;
+            }
+
+            // optional criticality flag
+            if let Some((BOOLEAN, _, rest)) = read_tlv(ext) {
+                ext = rest;
+            }
+
+            let (value, _) = expect(OCTET_STRING, ext)?;
+            let (names, _) = expect(SEQUENCE, value)?;
+
+            return parse_general_names(names);
+        }
+
+        Some(Vec::new())
+    }
+
+    fn parse_general_names(mut names: &[u8]) -> Option<Vec<SubjectAltName>> {
+        let mut sans = Vec::new();
+
+        while !names.is_empty() {
+            let (tag, value, rest) = read_tlv(names)?;
+            names = rest;
+
+            let ia5 = || String::from_utf8(value.to_vec()).ok();
+
+            let san = match tag {
+                0x81 => SubjectAltName::Email(ia5()?),
+                0x82 => SubjectAltName::Dns(ia5()?),
+                0x86 => SubjectAltName::Uri(ia5()?),
+
+                0x87 => match value.len() {
+                    4 => SubjectAltName::Ip(IpAddr::V4(Ipv4Addr::from(
+                        <[u8; 4]>::try_from(value).ok()?,
+                    ))),
+                    16 => SubjectAltName::Ip(IpAddr::V6(Ipv6Addr::from(
+                        <[u8; 16]>::try_from(value).ok()?,
+                    ))),
+                    _ => return None,
+                },
+
+                // other name types are not exposed
+                _ => continue,
+            };
+
+            sans.push(san);
+        }
+
+        Some(sans)
+    }
+
+    fn decode_string(tag: u8, value: &[u8]) -> Option<String> {
+        match tag {
+            // UTF8String, PrintableString, TeletexString, IA5String
+            0x0c | 0x13 | 0x14 | 0x16 => String::from_utf8(value.to_vec()).ok(),
+
+            // BMPString
+            0x1e => {
+                let units = value
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>();
+
+                String::from_utf16(&units).ok()
+            }
+
+            _ => None,
+        }
+    }
+
+    fn attribute_name(oid: &[u8]) -> String {
+        let name = match oid {
+            [0x55, 0x04, 0x03] => "CN",
+            [0x55, 0x04, 0x05] => "serialNumber",
+            [0x55, 0x04, 0x06] => "C",
+            [0x55, 0x04, 0x07] => "L",
+            [0x55, 0x04, 0x08] => "ST",
+            [0x55, 0x04, 0x09] => "street",
+            [0x55, 0x04, 0x0a] => "O",
+            [0x55, 0x04, 0x0b] => "OU",
+            [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01] => "emailAddress",
+            [0x09, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 0x01, 0x19] => "DC",
+            [0x09, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 0x01, 0x01] => "UID",
+            _ => return oid_to_dotted(oid),
+        };
+
+        name.to_owned()
+    }
+
+    pub(super) fn oid_to_dotted(oid: &[u8]) -> String {
+        let mut arcs = Vec::new();
+        let mut arc = 0u64;
+
+        for &byte in oid {
+            arc = (arc << 7) | u64::from(byte & 0x7f);
+
+            if byte & 0x80 == 0 {
+                arcs.push(arc);
+                arc = 0;
+            }
+        }
+
+        let mut dotted = String::new();
+
+        if let Some(&first) = arcs.first() {
+            let (a, b) = match first {
+                0..40 => (0, first),
+                40..80 => (1, first - 40),
+                _ => (2, first - 80),
+            };
+
+            dotted = format!("{a}.{b}");
+        }
+
+        for arc in arcs.iter().skip(1) {
+            dotted.push('.');
+            dotted.push_str(&arc.to_string());
+        }
+
+        dotted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        middleware::from_fn,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+    use base64::prelude::*;
+
+    use super::*;
+
+    // self-signed certificate with subject `/C=GB/O=Acme Corp/CN=client.example.com` and DNS,
+    // email, IP, and URI subject alternative names
+    const CERT: &str = "\
+        MIICJzCCAc6gAwIBAgIUToGfeBU1xG+jOGWy+YrRCrlnTuEwCgYIKoZIzj0EAwIwPjELMAkGA1UEBhMCR0IxEjAQBgNVBAoMCUFj\
+        bWUgQ29ycDEbMBkGA1UEAwwSY2xpZW50LmV4YW1wbGUuY29tMCAXDTI2MTAxNjE0NTU1NVoYDzIxMjYwOTIyMTQ1NTU1WjA+MQsw\
+        CQYDVQQGEwJHQjESMBAGA1UECgwJQWNtZSBDb3JwMRswGQYDVQQDDBJjbGllbnQuZXhhbXBsZS5jb20wWTATBgcqhkjOPQIBBggq\
+        hkjOPQMBBwNCAAQPribum4XXSvQIeA+8dhJlb/s8yfJYaeg0SOo/u3fHCNKH47BxJTHZBRLccDfXctw5kI2d/kcBGBI0cy63m5Nt\
+        o4GnMIGkMB0GA1UdDgQWBBQZ6DeAGeYDlPsTHU7iJtRMd4BI1TAfBgNVHSMEGDAWgBQZ6DeAGeYDlPsTHU7iJtRMd4BI1TAPBgNV\
+        HRMBAf8EBTADAQH/MFEGA1UdEQRKMEiCEmNsaWVudC5leGFtcGxlLmNvbYEPb3BzQGV4YW1wbGUuY29thwQKAAABhhtzcGlmZmU6\
+        Ly9leGFtcGxlLm9yZy9jbGllbnQwCgYIKoZIzj0EAwIDRwAwRAIgCQ+I3jP+W/i1NZ3uA0qvw1v5ViyZeVflwPrGK/uphOICICUD\
+        OebwgFnun29x7l0f3gmZ62kLmeRGM9A6Xvwsd2l8";
+
+    fn cert() -> PeerCert {
+        PeerCert::from_der(BASE64_STANDARD.decode(CERT).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_certificate() {
+        let cert = cert();
+
+        assert_eq!(cert.subject(), "C=GB, O=Acme Corp, CN=client.example.com");
+        assert_eq!(cert.common_name(), Some("client.example.com"));
+        assert_eq!(
+            cert.subject_alt_names(),
+            [
+                SubjectAltName::Dns("client.example.com".to_owned()),
+                SubjectAltName::Email("ops@example.com".to_owned()),
+                SubjectAltName::Ip("10.0.0.1".parse().unwrap()),
+                SubjectAltName::Uri("spiffe://example.org/client".to_owned()),
+            ],
+        );
+        assert_eq!(
+            cert.fingerprint_sha256_hex(),
+            "029780bb16b758fad4b8b55b16d28dcfb07b9018a6c62a7bd78de6199454a21d",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_der() {
+        let der = BASE64_STANDARD.decode(CERT).unwrap();
+
+        assert!(PeerCert::from_der(&b""[..]).is_err());
+        assert!(PeerCert::from_der(&b"not a certificate"[..]).is_err());
+        assert!(PeerCert::from_der(&der[..100]).is_err());
+    }
+
+    #[test]
+    fn dotted_oids() {
+        assert_eq!(
+            x509::oid_to_dotted(&[0x2b, 0x06, 0x01, 0x04, 0x01]),
+            "1.3.6.1.4.1"
+        );
+        assert_eq!(x509::oid_to_dotted(&[0x55, 0x1d, 0x11]), "2.5.29.17");
+    }
+
+    #[actix_web::test]
+    async fn extractor_and_guard() {
+        let app = init_service(
+            App::new().wrap(from_fn(load_peer_cert)).service(
+                web::resource("/")
+                    .guard(client_cert())
+                    .to(|cert: PeerCert| async move {
+                        HttpResponse::Ok().body(cert.common_name().unwrap().to_owned())
+                    }),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        // connection data cannot be set in tests; simulate certificate loaded by middleware
+        let req = TestRequest::default().to_request();
+        req.extensions_mut().insert(cert());
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let app =
+            init_service(App::new().default_service(web::to(|_: PeerCert| HttpResponse::Ok())))
+                .await;
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,321 @@
+//! Problem details responder.
+//!
+//! See [`Problem`] docs.
+
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, error::JsonPayloadError, http::StatusCode, HttpRequest, HttpResponse, Responder,
+    ResponseError,
+};
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::extract::{PathDeserializeError, QueryDeserializeError};
+
+/// Content type of problem details documents.
+pub(crate) const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Members defined by RFC 9457 which cannot be used as extension members.
+const RESERVED_MEMBERS: [&str; 5] = ["type", "title", "status", "detail", "instance"];
+
+/// Problem details document ([RFC 9457]).
+///
+/// Responds with the given status code and an `application/problem+json` body:
+///
+/// ```json
+/// {
+///   "type": "https://example.com/probs/out-of-credit",
+///   "title": "You do not have enough credit.",
+///   "status": 403,
+///   "detail": "Your current balance is 30, but that costs 50.",
+///   "instance": "/account/12345/msgs/abc",
+///   "balance": 30
+/// }
+/// ```
+///
+/// Also implements [`ResponseError`] so it can be returned from handlers using `?`. Errors from
+/// Actix Web's JSON extractors and this crate's [`Query`] and [`Path`] extractors can be converted
+/// into problems.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, http::StatusCode, web, Responder};
+/// use actix_web_lab::respond::Problem;
+///
+/// #[get("/account/{id}/balance")]
+/// async fn balance(id: web::Path<u64>) -> Result<impl Responder, Problem> {
+///     let balance = 30;
+///
+///     if balance < 50 {
+///         return Err(Problem::new(StatusCode::FORBIDDEN)
+///             .type_uri("https://example.com/probs/out-of-credit")
+///             .title("You do not have enough credit.")
+///             .detail(format!(
+///                 "Your current balance is {balance}, but that costs 50."
+///             ))
+///             .instance(format!("/account/{id}"))
+///             .extension("balance", balance));
+///     }
+///
+///     Ok("ok")
+/// }
+/// ```
+///
+/// [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457
+/// [`Query`]: crate::extract::Query
+/// [`Path`]: crate::extract::Path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    status: StatusCode,
+    type_uri: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Constructs a new problem with the given status code.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            type_uri: None,
+            title: None,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the `type` member; a URI identifying the problem type.
+    ///
+    /// When not set, the problem type is implicitly `about:blank`.
+    pub fn type_uri(mut self, type_uri: impl Into<String>) -> Self {
+        self.type_uri = Some(type_uri.into());
+        self
+    }
+
+    /// Sets the `title` member; a short summary of the problem type.
+    ///
+    /// Defaults to the status code's canonical reason phrase.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the `detail` member; an explanation specific to this occurrence of the problem.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the `instance` member; a URI identifying this occurrence of the problem.
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member.
+    ///
+    /// Extension members named `type`, `title`, `status`, `detail`, or `instance` are ignored.
+    pub fn extension(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        let name = name.into();
+
+        if !RESERVED_MEMBERS.contains(&name.as_str()) {
+            self.extensions.insert(name, value.into());
+        }
+
+        self
+    }
+
+    /// Returns the extension members added so far.
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+
+    fn title_or_default(&self) -> &str {
+        self.title
+            .as_deref()
+            .or(self.status.canonical_reason())
+            .unwrap_or_default()
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        match serde_json::to_string(self) {
+            Ok(body) => HttpResponse::build(self.status)
+                .content_type(PROBLEM_JSON)
+                .body(body),
+
+            Err(err) => HttpResponse::from_error(err),
+        }
+    }
+}
+
+impl Serialize for Problem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut doc = serializer.serialize_map(None)?;
+
+        if let Some(type_uri) = &self.type_uri {
+            doc.serialize_entry("type", type_uri)?;
+        }
+
+        doc.serialize_entry("title", self.title_or_default())?;
+        doc.serialize_entry("status", &self.status.as_u16())?;
+
+        if let Some(detail) = &self.detail {
+            doc.serialize_entry("detail", detail)?;
+        }
+
+        if let Some(instance) = &self.instance {
+            doc.serialize_entry("instance", instance)?;
+        }
+
+        for (name, val) in &self.extensions {
+            doc.serialize_entry(name, val)?;
+        }
+
+        doc.end()
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title_or_default())?;
+
+        if let Some(detail) = &self.detail {
+            write!(f, ": {detail}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Problem {}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.to_response()
+    }
+}
+
+impl Responder for Problem {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.to_response()
+    }
+}
+
+impl From<JsonPayloadError> for Problem {
+    fn from(err: JsonPayloadError) -> Self {
+        Self::new(err.status_code()).detail(err.to_string())
+    }
+}
+
+impl From<QueryDeserializeError> for Problem {
+    fn from(err: QueryDeserializeError) -> Self {
+        let detail = match std::error::Error::source(&err) {
+            Some(source) => format!("{err}: {source}"),
+            None => err.to_string(),
+        };
+
+        Self::new(err.status_code()).detail(detail)
+    }
+}
+
+impl From<PathDeserializeError> for Problem {
+    fn from(err: PathDeserializeError) -> Self {
+        Self::new(err.status_code()).detail(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body, http::header, test::TestRequest};
+    use serde_json::json;
+
+    use super::*;
+    use crate::extract::Query;
+
+    async fn body_json(res: HttpResponse) -> serde_json::Value {
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn all_members() {
+        let problem = Problem::new(StatusCode::FORBIDDEN)
+            .type_uri("https://example.com/probs/out-of-credit")
+            .title("You do not have enough credit.")
+            .detail("Your current balance is 30, but that costs 50.")
+            .instance("/account/12345/msgs/abc")
+            .extension("balance", 30)
+            .extension("accounts", json!(["/account/12345", "/account/67890"]))
+            .extension("status", 200);
+
+        assert_eq!(
+            problem.to_string(),
+            "You do not have enough credit.: Your current balance is 30, but that costs 50."
+        );
+
+        let req = TestRequest::default().to_http_request();
+        let res = problem.respond_to(&req);
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+
+        assert_eq!(
+            body_json(res).await,
+            json!({
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "You do not have enough credit.",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50.",
+                "instance": "/account/12345/msgs/abc",
+                "balance": 30,
+                "accounts": ["/account/12345", "/account/67890"],
+            }),
+        );
+    }
+
+    #[actix_web::test]
+    async fn defaults() {
+        let problem = Problem::new(StatusCode::NOT_FOUND);
+        assert_eq!(problem.to_string(), "Not Found");
+
+        let res = actix_web::Error::from(problem).error_response();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            body_json(res).await,
+            json!({ "title": "Not Found", "status": 404 }),
+        );
+    }
+
+    #[test]
+    fn from_extractor_errors() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            page: u32,
+        }
+
+        let err = Query::<Params>::from_query("page=abc").unwrap_err();
+        let problem = Problem::from(err);
+        assert_eq!(problem.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            problem.detail.unwrap(),
+            "Query deserialization failed at path: page: invalid digit found in string",
+        );
+
+        let problem = Problem::from(JsonPayloadError::ContentType);
+        assert_eq!(problem.status, StatusCode::BAD_REQUEST);
+        assert_eq!(problem.detail.unwrap(), "Content type error");
+    }
+}
@@ -0,0 +1,337 @@
+//! URL-encoded form extractor with nested bracket syntax support.
+//!
+//! See [`QsForm`] docs.
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use actix_web::{
+    dev::Payload, error::PayloadError, http::StatusCode, web, FromRequest, HttpMessage as _,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::Stream as _;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::lab_config::LabConfig;
+
+/// Default nested form payload size limit of 2MiB.
+pub const DEFAULT_QS_FORM_LIMIT: usize = 2_097_152;
+
+/// Maximum depth of bracket nesting that will be deserialized.
+const MAX_DEPTH: usize = 5;
+
+/// URL-encoded form extractor supporting PHP/Rails-style nested bracket syntax.
+///
+/// Unlike [`UrlEncodedForm`](crate::extract::UrlEncodedForm), this extractor understands keys like
+/// `user[name]=x` and `user[tags][]=a&user[tags][]=b`, which are commonly emitted by frontend
+/// libraries, and deserializes them into nested structures and sequences. Percent-encoded brackets
+/// (as sent by browsers) are also supported. Nesting is limited to a depth of 5.
+///
+/// Use the `LIMIT` const generic parameter to control the payload size limit. The default limit
+/// that is exported (`DEFAULT_QS_FORM_LIMIT`) is 2MiB.
+///
+/// # Examples
+/// ```
+/// use actix_web::post;
+/// use actix_web_lab::extract::QsForm;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     tags: Vec<String>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Signup {
+///     user: User,
+/// }
+///
+/// /// Deserializes `user[name]=x&user[tags][]=a&user[tags][]=b` payloads.
+/// #[post("/")]
+/// async fn index(form: QsForm<Signup>) -> String {
+///     format!("Welcome {}!", form.user.name)
+/// }
+/// ```
+#[doc(alias = "nested_form", alias = "qs")]
+#[derive(Debug)]
+pub struct QsForm<T, const LIMIT: usize = DEFAULT_QS_FORM_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> std::ops::Deref for QsForm<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> std::ops::DerefMut for QsForm<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const LIMIT: usize> QsForm<T, LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Errors that can occur when extracting a [`QsForm`].
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum QsFormError {
+    /// Content type of request was not `application/x-www-form-urlencoded`.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Payload was larger than limit.
+    #[display("URL encoded payload is larger ({size} bytes) than allowed (limit: {limit} bytes).")]
+    Overflow {
+        /// Size of payload, as indicated by header or as received so far.
+        size: usize,
+
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload error.
+    #[display("Error that occur during reading payload: {_0}")]
+    Payload(PayloadError),
+
+    /// Nested form deserialization error.
+    #[display("Nested form deserialize error: {_0}")]
+    Deserialize(serde_qs::Error),
+}
+
+impl ResponseError for QsFormError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl<T: DeserializeOwned, const LIMIT: usize> FromRequest for QsForm<T, LIMIT> {
+    type Error = QsFormError;
+    type Future = QsFormExtractFut<T, LIMIT>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        QsFormExtractFut {
+            req: Some(req.clone()),
+            fut: QsFormBody::new(req, payload),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct QsFormExtractFut<T, const LIMIT: usize> {
+    req: Option<HttpRequest>,
+    fut: QsFormBody<T, LIMIT>,
+}
+
+impl<T: DeserializeOwned, const LIMIT: usize> Future for QsFormExtractFut<T, LIMIT> {
+    type Output = Result<QsForm<T, LIMIT>, QsFormError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = ready!(Pin::new(&mut this.fut).poll(cx));
+
+        let res = match res {
+            Err(err) => {
+                let req = this.req.take().unwrap();
+                debug!(
+                    "Failed to deserialize QsForm<{}> from payload in handler: {}",
+                    core::any::type_name::<T>(),
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                Err(err)
+            }
+            Ok(data) => Ok(QsForm(data)),
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+enum QsFormBody<T, const LIMIT: usize> {
+    Error(Option<QsFormError>),
+    Body {
+        payload: Payload,
+        buf: web::BytesMut,
+        limit: usize,
+        _res: PhantomData<T>,
+    },
+}
+
+impl<T, const LIMIT: usize> Unpin for QsFormBody<T, LIMIT> {}
+
+impl<T: DeserializeOwned, const LIMIT: usize> QsFormBody<T, LIMIT> {
+    fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+        let can_parse_form = matches!(
+            req.mime_type(),
+            Ok(Some(mime)) if mime == mime::APPLICATION_WWW_FORM_URLENCODED
+        );
+
+        if !can_parse_form {
+            return QsFormBody::Error(Some(QsFormError::ContentType));
+        }
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_QS_FORM_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let length = LabConfig::payload_length(req);
+
+        if let Some(len) = length {
+            if len > limit {
+                return QsFormBody::Error(Some(QsFormError::Overflow { size: len, limit }));
+            }
+        }
+
+        QsFormBody::Body {
+            payload: payload.take(),
+            buf: web::BytesMut::with_capacity(LabConfig::buffer_capacity(req, length)),
+            limit,
+            _res: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned, const LIMIT: usize> Future for QsFormBody<T, LIMIT> {
+    type Output = Result<T, QsFormError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        match this {
+            QsFormBody::Body {
+                buf,
+                payload,
+                limit,
+                ..
+            } => loop {
+                let res = ready!(Pin::new(&mut *payload).poll_next(cx));
+
+                match res {
+                    Some(chunk) => {
+                        let chunk = chunk.map_err(QsFormError::Payload)?;
+                        let buf_len = buf.len() + chunk.len();
+
+                        if buf_len > *limit {
+                            return Poll::Ready(Err(QsFormError::Overflow {
+                                size: buf_len,
+                                limit: *limit,
+                            }));
+                        } else {
+                            buf.extend_from_slice(&chunk);
+                        }
+                    }
+
+                    None => {
+                        // non-strict mode allows percent-encoded brackets
+                        let form = serde_qs::Config::new(MAX_DEPTH, false)
+                            .deserialize_bytes::<T>(buf)
+                            .map_err(QsFormError::Deserialize)?;
+
+                        return Poll::Ready(Ok(form));
+                    }
+                }
+            },
+
+            QsFormBody::Error(err) => Poll::Ready(Err(err.take().unwrap())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use actix_web::{http::header, test::TestRequest, web::Bytes};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct User {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Signup {
+        user: User,
+        meta: HashMap<String, String>,
+    }
+
+    fn form_parts(body: &'static [u8]) -> (HttpRequest, Payload) {
+        TestRequest::default()
+            .insert_header(header::ContentType::form_url_encoded())
+            .set_payload(Bytes::from_static(body))
+            .to_http_parts()
+    }
+
+    #[actix_web::test]
+    async fn nested_brackets() {
+        let (req, mut pl) =
+            form_parts(b"user[name]=x&user[tags][]=a&user[tags][]=b&meta[source]=web");
+
+        let form = QsForm::<Signup>::from_request(&req, &mut pl).await.unwrap();
+
+        assert_eq!(
+            form.into_inner(),
+            Signup {
+                user: User {
+                    name: "x".to_owned(),
+                    tags: vec!["a".to_owned(), "b".to_owned()],
+                },
+                meta: HashMap::from([("source".to_owned(), "web".to_owned())]),
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn percent_encoded_brackets() {
+        let (req, mut pl) = form_parts(b"name=x&tags%5B0%5D=a&tags%5B1%5D=b");
+
+        let form = QsForm::<User>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(form.name, "x");
+        assert_eq!(form.tags, ["a", "b"]);
+    }
+
+    #[actix_web::test]
+    async fn errors() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::plaintext())
+            .set_payload(Bytes::from_static(b"name=x"))
+            .to_http_parts();
+        let err = QsForm::<User>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, QsFormError::ContentType));
+
+        let (req, mut pl) = form_parts(b"name=x&tags[]=a");
+        let err = QsForm::<User, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, QsFormError::Overflow { size: 15, limit: 4 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (req, mut pl) = form_parts(b"tags[]=a");
+        let err = QsForm::<User>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, QsFormError::Deserialize(_)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
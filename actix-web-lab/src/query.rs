@@ -9,6 +9,8 @@ use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest, Respon
 use derive_more::Error;
 use serde::de::DeserializeOwned;
 
+use crate::lab_config::LabConfig;
+
 /// Extract typed information from the request's query.
 ///
 /// To extract typed data from the URL query string, the inner type `T` must implement the
@@ -18,8 +20,13 @@ use serde::de::DeserializeOwned;
 /// This extractor uses `serde_html_form` under-the-hood which supports multi-value items. These are
 /// sent by HTML select inputs when multiple options are chosen and can be collected into a `Vec`.
 ///
+/// Parameters separated by `;` instead of `&` (e.g., `?a=1;b=2`) can be accepted by enabling
+/// [`LabConfig::query_semicolon_separators()`](crate::extract::LabConfig::query_semicolon_separators).
+///
 /// This version also removes the custom error handler config; users should instead prefer to handle
-/// errors using the explicit `Result<Query<T>, E>` extractor in their handlers.
+/// errors using the explicit `Result<Query<T>, E>` extractor in their handlers. Deserialization
+/// failures respond with `422 Unprocessable Entity` by default; this status can be changed using
+/// [`LabConfig::query_error_status()`](crate::extract::LabConfig::query_error_status).
 ///
 /// # Panics
 /// A query string consists of unordered `key=value` pairs, therefore it cannot be decoded into any
@@ -89,7 +96,25 @@ impl<T: DeserializeOwned> Query<T> {
     /// assert!(numbers.get("three").is_none());
     /// ```
     pub fn from_query(query_str: &str) -> Result<Self, QueryDeserializeError> {
-        let parser = form_urlencoded::parse(query_str.as_bytes());
+        Self::deserialize(query_str.as_bytes())
+    }
+
+    /// Deserialize a `T` from the URL encoded query parameter string, accepting both `&` and `;` as
+    /// parameter separators.
+    ///
+    /// ```
+    /// # use actix_web_lab::extract::Query;
+    /// let ids = Query::<Vec<(String, u32)>>::from_query_with_semicolons("id=1;id=2&id=3").unwrap();
+    ///
+    /// assert_eq!(ids.len(), 3);
+    /// ```
+    pub fn from_query_with_semicolons(query_str: &str) -> Result<Self, QueryDeserializeError> {
+        let query = query_str.replace(';', "&");
+        Self::deserialize(query.as_bytes())
+    }
+
+    fn deserialize(query: &[u8]) -> Result<Self, QueryDeserializeError> {
+        let parser = form_urlencoded::parse(query);
         let de = serde_html_form::Deserializer::new(parser);
 
         serde_path_to_error::deserialize(de)
@@ -97,6 +122,7 @@ impl<T: DeserializeOwned> Query<T> {
             .map_err(|err| QueryDeserializeError {
                 path: err.path().clone(),
                 source: err.into_inner(),
+                status: StatusCode::UNPROCESSABLE_ENTITY,
             })
     }
 }
@@ -108,7 +134,13 @@ impl<T: DeserializeOwned> FromRequest for Query<T> {
 
     #[inline]
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        ready(Self::from_query(req.query_string()).inspect_err(|err| {
+        let res = if LabConfig::query_semicolon_separators_for(req) {
+            Self::from_query_with_semicolons(req.query_string())
+        } else {
+            Self::from_query(req.query_string())
+        };
+
+        ready(res.map_err(|mut err| {
             tracing::debug!(
                 "Failed during Query extractor deserialization. \
                 Request path: \"{}\". \
@@ -116,6 +148,9 @@ impl<T: DeserializeOwned> FromRequest for Query<T> {
                 req.match_name().unwrap_or(req.path()),
                 err.path(),
             );
+
+            err.status = LabConfig::query_error_status_for(req);
+            err
         }))
     }
 }
@@ -125,6 +160,8 @@ impl<T: DeserializeOwned> FromRequest for Query<T> {
 pub struct QueryDeserializeError {
     path: serde_path_to_error::Path,
     source: serde::de::value::Error,
+    #[error(not(source))]
+    status: StatusCode,
 }
 
 impl QueryDeserializeError {
@@ -148,7 +185,7 @@ impl fmt::Display for QueryDeserializeError {
 
 impl ResponseError for QueryDeserializeError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::UNPROCESSABLE_ENTITY
+        self.status
     }
 }
 
@@ -214,6 +251,42 @@ mod tests {
         assert_eq!(s.id, "test1");
     }
 
+    #[actix_web::test]
+    async fn error_status() {
+        let (req, mut pl) = TestRequest::with_uri("/?id").to_http_parts();
+        let err = Query::<Vec<u32>>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (req, mut pl) = TestRequest::with_uri("/")
+            .app_data(LabConfig::new().query_error_status(StatusCode::BAD_REQUEST))
+            .to_http_parts();
+        let err = Query::<Id>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn semicolon_separators() {
+        #[derive(Debug, Deserialize)]
+        struct Test {
+            id: Vec<u32>,
+            name: String,
+        }
+
+        let (req, mut pl) = TestRequest::with_uri("/?id=1;id=2&name=a%3Bb").to_http_parts();
+        Query::<Test>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+
+        let (req, mut pl) = TestRequest::with_uri("/?id=1;id=2&name=a%3Bb")
+            .app_data(LabConfig::new().query_semicolon_separators(true))
+            .to_http_parts();
+        let Query(test) = Query::<Test>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(test.id, [1, 2]);
+        assert_eq!(test.name, "a;b");
+    }
+
     #[actix_web::test]
     #[should_panic]
     async fn test_tuple_panic() {
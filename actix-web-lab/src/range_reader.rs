@@ -0,0 +1,606 @@
+//! Ranged responses from random-access storage.
+//!
+//! See [`RangedObject`] docs.
+
+use std::{fmt, future::Future, io, rc::Rc, time::SystemTime};
+
+use actix_web::{
+    body::{BoxBody, SizedStream},
+    http::{
+        header::{
+            self, EntityTag, HttpDate, IfMatch, IfModifiedSince, IfNoneMatch, IfRange,
+            IfUnmodifiedSince,
+        },
+        Method, StatusCode,
+    },
+    HttpMessage as _, HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::{future, stream, StreamExt as _};
+use mime::Mime;
+
+use crate::download::ByteRanges;
+
+/// Default size of reads issued to a [`RangeReader`].
+const DEFAULT_CHUNK_SIZE: usize = 65_536;
+
+/// Maximum number of ranges served in a single `multipart/byteranges` response.
+const MAX_RANGES: usize = 16;
+
+/// Random-access reader over a stored object of known length.
+///
+/// Implement this for storage clients (e.g., ranged `GET`s against S3-compatible object stores) or
+/// local files to serve their contents with [`RangedObject`] without loading whole objects into
+/// memory.
+pub trait RangeReader {
+    /// Reads up to `len` bytes starting at `offset`.
+    ///
+    /// Returning fewer bytes than requested is allowed; the remainder is requested in a later call.
+    /// Returning no bytes before the end of the object is treated as an unexpected EOF.
+    fn read_at(&self, offset: u64, len: usize) -> impl Future<Output = io::Result<Bytes>>;
+}
+
+impl RangeReader for Bytes {
+    async fn read_at(&self, offset: u64, len: usize) -> io::Result<Bytes> {
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(self.len());
+        let end = start.saturating_add(len).min(self.len());
+        Ok(self.slice(start..end))
+    }
+}
+
+/// Responder that serves full or ranged responses from a [`RangeReader`].
+///
+/// `Range` requests are answered with `206 Partial Content`. Requests for a single range get a
+/// response with a `Content-Range` header, and requests for multiple ranges get a
+/// `multipart/byteranges` response containing each range as a separate part. Requests for more
+/// than 16 ranges, or for overlapping ranges, are answered with the full content. When
+/// [validators](Self::etag) are set, `If-Range` is
+/// respected and the conditional request headers `If-Match`, `If-None-Match`,
+/// `If-Unmodified-Since`, and `If-Modified-Since` are evaluated, responding with
+/// `304 Not Modified` or `412 Precondition Failed` where appropriate.
+///
+/// Content is read lazily in chunks (64KiB by default) as the response body is polled.
+///
+/// # Examples
+/// ```
+/// use std::io;
+///
+/// use actix_web::{get, web::Bytes, Responder};
+/// use actix_web_lab::respond::{RangeReader, RangedObject};
+///
+/// /// Stand-in for an object storage client.
+/// struct StoredObject {
+///     data: Bytes,
+/// }
+///
+/// impl RangeReader for StoredObject {
+///     async fn read_at(&self, offset: u64, len: usize) -> io::Result<Bytes> {
+///         // e.g., issue `GET` with `Range: bytes={offset}-{offset + len - 1}`
+///         let start = offset as usize;
+///         Ok(self.data.slice(start..(start + len).min(self.data.len())))
+///     }
+/// }
+///
+/// #[get("/video")]
+/// async fn video() -> impl Responder {
+///     let object = StoredObject {
+///         data: Bytes::from_static(b"..."),
+///     };
+///     let len = object.data.len() as u64;
+///
+///     RangedObject::new(object, len)
+///         .content_type(mime::APPLICATION_OCTET_STREAM)
+///         .etag(actix_web::http::header::EntityTag::new_strong(
+///             "v1".to_owned(),
+///         ))
+/// }
+/// ```
+pub struct RangedObject<R> {
+    reader: R,
+    len: u64,
+    content_type: Option<Mime>,
+    etag: Option<EntityTag>,
+    last_modified: Option<HttpDate>,
+    chunk_size: usize,
+}
+
+impl<R: RangeReader> RangedObject<R> {
+    /// Constructs a new ranged responder for an object of `len` bytes.
+    pub fn new(reader: R, len: u64) -> Self {
+        Self {
+            reader,
+            len,
+            content_type: None,
+            etag: None,
+            last_modified: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the `Content-Type` of the response.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Sets the `ETag` validator of the object.
+    pub fn etag(mut self, etag: EntityTag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Sets the `Last-Modified` validator of the object.
+    pub fn last_modified(mut self, last_modified: SystemTime) -> Self {
+        self.last_modified = Some(last_modified.into());
+        self
+    }
+
+    /// Sets the maximum number of bytes requested from the reader at once.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Evaluates conditional request headers, returning the status to respond with if a
+    /// precondition short-circuits the request.
+    fn check_preconditions(&self, req: &HttpRequest) -> Option<StatusCode> {
+        let is_get_or_head = req.method() == Method::GET || req.method() == Method::HEAD;
+
+        match req.get_header::<IfMatch>() {
+            Some(IfMatch::Any) => {}
+            Some(IfMatch::Items(tags)) => {
+                let matched = self
+                    .etag
+                    .as_ref()
+                    .is_some_and(|etag| tags.iter().any(|tag| tag.strong_eq(etag)));
+
+                if !matched {
+                    return Some(StatusCode::PRECONDITION_FAILED);
+                }
+            }
+            None => {
+                if let (Some(IfUnmodifiedSince(since)), Some(last_modified)) =
+                    (req.get_header::<IfUnmodifiedSince>(), self.last_modified)
+                {
+                    if SystemTime::from(last_modified) > SystemTime::from(since) {
+                        return Some(StatusCode::PRECONDITION_FAILED);
+                    }
+                }
+            }
+        }
+
+        let not_modified_status = if is_get_or_head {
+            StatusCode::NOT_MODIFIED
+        } else {
+            StatusCode::PRECONDITION_FAILED
+        };
+
+        match req.get_header::<IfNoneMatch>() {
+            Some(IfNoneMatch::Any) => return Some(not_modified_status),
+            Some(IfNoneMatch::Items(tags)) => {
+                let matched = self
+                    .etag
+                    .as_ref()
+                    .is_some_and(|etag| tags.iter().any(|tag| tag.weak_eq(etag)));
+
+                if matched {
+                    return Some(not_modified_status);
+                }
+            }
+            None if is_get_or_head => {
+                if let (Some(IfModifiedSince(since)), Some(last_modified)) =
+                    (req.get_header::<IfModifiedSince>(), self.last_modified)
+                {
+                    if SystemTime::from(last_modified) <= SystemTime::from(since) {
+                        return Some(StatusCode::NOT_MODIFIED);
+                    }
+                }
+            }
+            None => {}
+        }
+
+        None
+    }
+
+    /// Returns true if the `If-Range` header is absent or matches the object's validators.
+    fn if_range_matches(&self, req: &HttpRequest) -> bool {
+        if !req.headers().contains_key(header::IF_RANGE) {
+            return true;
+        }
+
+        match req.get_header::<IfRange>() {
+            Some(IfRange::EntityTag(tag)) => {
+                self.etag.as_ref().is_some_and(|etag| tag.strong_eq(etag))
+            }
+            Some(IfRange::Date(date)) => self.last_modified == Some(date),
+            None => false,
+        }
+    }
+
+    fn insert_validators(&self, res: &mut HttpResponseBuilder) {
+        if let Some(etag) = &self.etag {
+            res.insert_header(header::ETag(etag.clone()));
+        }
+
+        if let Some(last_modified) = self.last_modified {
+            res.insert_header(header::LastModified(last_modified));
+        }
+    }
+}
+
+impl<R> fmt::Debug for RangedObject<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangedObject")
+            .field("len", &self.len)
+            .field("content_type", &self.content_type)
+            .field("etag", &self.etag)
+            .field("last_modified", &self.last_modified)
+            .field("chunk_size", &self.chunk_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: RangeReader + 'static> Responder for RangedObject<R> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        if let Some(status) = self.check_preconditions(req) {
+            let mut res = HttpResponse::build(status);
+
+            if status == StatusCode::NOT_MODIFIED {
+                self.insert_validators(&mut res);
+            }
+
+            return res.finish();
+        }
+
+        let mut res = HttpResponse::Ok();
+        res.insert_header((header::ACCEPT_RANGES, "bytes"));
+        self.insert_validators(&mut res);
+
+        if let Some(content_type) = &self.content_type {
+            res.insert_header((header::CONTENT_TYPE, content_type.as_ref()));
+        }
+
+        let len = self.len;
+
+        let ranges = req
+            .headers()
+            .get(header::RANGE)
+            .filter(|_| req.method() == Method::GET || req.method() == Method::HEAD)
+            .filter(|_| self.if_range_matches(req))
+            .and_then(|hdr| hdr.to_str().ok())
+            .map_or(ByteRanges::Full, |hdr| ByteRanges::parse(hdr, len));
+
+        let ranges = match ranges {
+            ByteRanges::Partial(ranges) if ranges.len() > MAX_RANGES || overlapping(&ranges) => {
+                ByteRanges::Full
+            }
+            ranges => ranges,
+        };
+
+        match ranges {
+            ByteRanges::Full => res.body(SizedStream::new(
+                len,
+                read_stream(self.reader, 0, len, self.chunk_size),
+            )),
+
+            ByteRanges::Partial(ranges) if ranges.len() > 1 => {
+                let boundary = format!("{:032x}", crate::util::random_u128());
+
+                let parts = ranges
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, (start, end))| {
+                        let mut head = String::new();
+
+                        if idx > 0 {
+                            head.push_str("\r\n");
+                        }
+
+                        head.push_str(&format!("--{boundary}\r\n"));
+
+                        if let Some(content_type) = &self.content_type {
+                            head.push_str(&format!("Content-Type: {content_type}\r\n"));
+                        }
+
+                        head.push_str(&format!("Content-Range: bytes {start}-{end}/{len}\r\n\r\n"));
+
+                        (Bytes::from(head), start, end - start + 1)
+                    })
+                    .collect::<Vec<_>>();
+
+                let closing = Bytes::from(format!("\r\n--{boundary}--\r\n"));
+
+                let body_len = parts
+                    .iter()
+                    .map(|(head, _, part_len)| head.len() as u64 + part_len)
+                    .sum::<u64>()
+                    + closing.len() as u64;
+
+                res.status(StatusCode::PARTIAL_CONTENT)
+                    .insert_header((
+                        header::CONTENT_TYPE,
+                        format!("multipart/byteranges; boundary={boundary}"),
+                    ))
+                    .body(SizedStream::new(
+                        body_len,
+                        multipart_stream(self.reader, parts, closing, self.chunk_size),
+                    ))
+            }
+
+            ByteRanges::Partial(ranges) => {
+                let (start, end) = ranges[0];
+                let part_len = end - start + 1;
+
+                res.status(StatusCode::PARTIAL_CONTENT)
+                    .insert_header((header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}")))
+                    .body(SizedStream::new(
+                        part_len,
+                        read_stream(self.reader, start, part_len, self.chunk_size),
+                    ))
+            }
+
+            ByteRanges::Unsatisfiable => HttpResponse::RangeNotSatisfiable()
+                .insert_header((header::CONTENT_RANGE, format!("bytes */{len}")))
+                .finish(),
+        }
+    }
+}
+
+/// Returns true if any of the inclusive byte ranges overlap.
+fn overlapping(ranges: &[(u64, u64)]) -> bool {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+    sorted.windows(2).any(|pair| pair[1].0 <= pair[0].1)
+}
+
+/// Allows one reader to be shared between the streams of each part of a multipart response.
+struct SharedReader<R>(Rc<R>);
+
+impl<R: RangeReader> RangeReader for SharedReader<R> {
+    fn read_at(&self, offset: u64, len: usize) -> impl Future<Output = io::Result<Bytes>> {
+        self.0.read_at(offset, len)
+    }
+}
+
+/// Streams a `multipart/byteranges` body from `reader`.
+///
+/// Each part is given as its encoded head (delimiter and headers), offset, and length.
+fn multipart_stream<R: RangeReader>(
+    reader: R,
+    parts: Vec<(Bytes, u64, u64)>,
+    closing: Bytes,
+    chunk_size: usize,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    let reader = Rc::new(reader);
+
+    let parts = parts.into_iter().map(move |(head, offset, len)| {
+        let reader = SharedReader(Rc::clone(&reader));
+
+        stream::once(future::ready(Ok(head))).chain(read_stream(reader, offset, len, chunk_size))
+    });
+
+    stream::iter(parts)
+        .flatten()
+        .chain(stream::once(future::ready(Ok(closing))))
+}
+
+/// Streams `len` bytes from `reader`, starting at `offset`, in chunks of at most `chunk_size`.
+fn read_stream<R: RangeReader>(
+    reader: R,
+    offset: u64,
+    len: u64,
+    chunk_size: usize,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    stream::try_unfold(
+        (reader, offset, len),
+        move |(reader, offset, remaining)| async move {
+            if remaining == 0 {
+                return Ok(None);
+            }
+
+            let want = remaining.min(chunk_size as u64) as usize;
+            let mut chunk = reader.read_at(offset, want).await?;
+
+            if chunk.is_empty() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            chunk.truncate(want);
+            let read = chunk.len() as u64;
+
+            Ok(Some((chunk, (reader, offset + read, remaining - read))))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use actix_web::{body, test::TestRequest};
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789";
+
+    async fn respond(req: TestRequest, obj: RangedObject<Bytes>) -> (HttpResponse<()>, Bytes) {
+        let res = obj.respond_to(&req.to_http_request());
+        let (res, body) = res.into_parts();
+        let body = body::to_bytes(body).await.unwrap();
+        (res, body)
+    }
+
+    fn modified() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    }
+
+    fn object() -> RangedObject<Bytes> {
+        RangedObject::new(Bytes::from_static(DATA), DATA.len() as u64)
+            .content_type(mime::TEXT_PLAIN)
+            .etag(EntityTag::new_strong("v1".to_owned()))
+            .last_modified(modified())
+            .chunk_size(3)
+    }
+
+    #[actix_web::test]
+    async fn full_and_ranged() {
+        let (res, body) = respond(TestRequest::default(), object()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"v1\"");
+        assert!(res.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(body, DATA);
+
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=2-8"));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 2-8/10"
+        );
+        assert_eq!(body, "2345678");
+
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=20-"));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    #[actix_web::test]
+    async fn multiple_ranges() {
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=1-2,-3"));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(!res.headers().contains_key(header::CONTENT_RANGE));
+
+        let content_type = res.headers().get(header::CONTENT_TYPE).unwrap();
+        let boundary = content_type
+            .to_str()
+            .unwrap()
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap();
+
+        assert_eq!(
+            body,
+            format!(
+                "--{boundary}\r\n\
+                Content-Type: text/plain\r\n\
+                Content-Range: bytes 1-2/10\r\n\
+                \r\n\
+                12\r\n\
+                --{boundary}\r\n\
+                Content-Type: text/plain\r\n\
+                Content-Range: bytes 7-9/10\r\n\
+                \r\n\
+                789\r\n\
+                --{boundary}--\r\n"
+            ),
+        );
+
+        // a single satisfiable range gets a single-part response
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=1-2,20-30"));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 1-2/10"
+        );
+        assert_eq!(body, "12");
+
+        // overlapping ranges are ignored
+        let req = TestRequest::default().insert_header((header::RANGE, "bytes=0-5,4-6"));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body, DATA);
+
+        // ranges are ignored if If-Range does not match
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=0-1,4-5"))
+            .insert_header((header::IF_RANGE, "\"v0\""));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body, DATA);
+    }
+
+    #[actix_web::test]
+    async fn if_range() {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=0-1"))
+            .insert_header((header::IF_RANGE, "\"v1\""));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body, "01");
+
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=0-1"))
+            .insert_header((header::IF_RANGE, "\"v0\""));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body, DATA);
+
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=0-1"))
+            .insert_header(IfRange::Date(modified().into()));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    }
+
+    #[actix_web::test]
+    async fn conditional_requests() {
+        let req = TestRequest::default().insert_header((header::IF_NONE_MATCH, "W/\"v1\""));
+        let (res, body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"v1\"");
+        assert!(body.is_empty());
+
+        let req = TestRequest::default().insert_header(IfModifiedSince(modified().into()));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::default().insert_header(IfModifiedSince(
+            (modified() - Duration::from_secs(60)).into(),
+        ));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::default().insert_header((header::IF_MATCH, "\"v0\""));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let req = TestRequest::default().insert_header(IfUnmodifiedSince(
+            (modified() - Duration::from_secs(60)).into(),
+        ));
+        let (res, _body) = respond(req, object()).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[actix_web::test]
+    async fn short_reads() {
+        struct OneByte(Bytes);
+
+        impl RangeReader for OneByte {
+            async fn read_at(&self, offset: u64, _len: usize) -> io::Result<Bytes> {
+                let start = (offset as usize).min(self.0.len());
+                Ok(self.0.slice(start..(start + 1).min(self.0.len())))
+            }
+        }
+
+        let res = RangedObject::new(OneByte(Bytes::from_static(DATA)), DATA.len() as u64)
+            .respond_to(&TestRequest::default().to_http_request());
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, DATA);
+
+        // object shorter than declared length
+        let res = RangedObject::new(Bytes::from_static(DATA), 20)
+            .respond_to(&TestRequest::default().to_http_request());
+        assert!(body::to_bytes(res.into_body()).await.is_err());
+    }
+}
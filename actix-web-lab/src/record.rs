@@ -0,0 +1,565 @@
+//! Request/response recording middleware.
+//!
+//! See [`Record`] docs.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    fs::{File, OpenOptions},
+    future::{ready, Ready},
+    io::{self, BufRead as _, BufReader, Write as _},
+    path::Path,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::HeaderMap,
+    web::Bytes,
+};
+use base64::prelude::*;
+use futures_core::future::LocalBoxFuture;
+use pin_project_lite::pin_project;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::{
+    body_capture::{capture_request_body, Capture},
+    util::{redact_headers, RedactionPolicy},
+};
+
+/// Default maximum number of body bytes captured per request and per response.
+const DEFAULT_BODY_LIMIT: usize = 64 * 1024;
+
+/// Destination for recordings made by the [`Record`] middleware.
+///
+/// Implemented for closures, unbounded Tokio channel senders, and [`RecordingFile`].
+pub trait RecordSink: 'static {
+    /// Stores a completed recording.
+    fn record(&self, recording: Recording);
+}
+
+impl<F: Fn(Recording) + 'static> RecordSink for F {
+    fn record(&self, recording: Recording) {
+        (self)(recording)
+    }
+}
+
+impl RecordSink for mpsc::UnboundedSender<Recording> {
+    fn record(&self, recording: Recording) {
+        // receiver being gone just means nobody is interested in recordings anymore
+        let _ = self.send(recording);
+    }
+}
+
+/// Record sink that appends recordings to a file as newline-delimited JSON.
+///
+/// Writes happen synchronously on the worker thread so this is intended for development and
+/// corpus-gathering environments rather than production traffic.
+#[derive(Debug, Clone)]
+pub struct RecordingFile {
+    file: Arc<Mutex<File>>,
+}
+
+impl RecordingFile {
+    /// Opens file at `path` for appending recordings, creating it if it does not exist.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Reads all recordings from a newline-delimited JSON file at `path`.
+    ///
+    /// Blank lines are ignored.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<Recording>> {
+        let file = BufReader::new(File::open(path)?);
+        let mut recordings = Vec::new();
+
+        for line in file.lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            recordings.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(recordings)
+    }
+}
+
+impl RecordSink for RecordingFile {
+    fn record(&self, recording: Recording) {
+        let mut line = match serde_json::to_vec(&recording) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!("failed to serialize recording: {err}");
+                return;
+            }
+        };
+
+        line.push(b'\n');
+
+        // a single write keeps lines intact when several workers share the file
+        if let Err(err) = self.file.lock().unwrap().write_all(&line) {
+            warn!("failed to write recording: {err}");
+        }
+    }
+}
+
+/// A recorded request/response pair.
+///
+/// Serializes to a JSON object suitable for storing one-per-line; see [`RecordingFile`]. Replay
+/// recordings against a service using [`test::replay()`](crate::test::replay).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The recorded request.
+    pub request: RecordedRequest,
+
+    /// The recorded response.
+    pub response: RecordedResponse,
+}
+
+/// Request half of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// Request method.
+    pub method: String,
+
+    /// Request URI (path and query).
+    pub uri: String,
+
+    /// Request headers, with sensitive values redacted.
+    pub headers: Vec<(String, String)>,
+
+    /// Request body, as read by the handler.
+    pub body: RecordedBody,
+}
+
+/// Response half of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Response status code.
+    pub status: u16,
+
+    /// Response headers, with sensitive values redacted.
+    pub headers: Vec<(String, String)>,
+
+    /// Response body.
+    pub body: RecordedBody,
+}
+
+/// Body of a recorded request or response.
+///
+/// UTF-8 bodies are stored as-is; other bodies are stored Base64-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedBody {
+    data: String,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    base64: bool,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    truncated: bool,
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8], truncated: bool) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(data) => Self {
+                data: data.to_owned(),
+                base64: false,
+                truncated,
+            },
+            Err(_) => Self {
+                data: BASE64_STANDARD.encode(bytes),
+                base64: true,
+                truncated,
+            },
+        }
+    }
+
+    /// Returns the recorded body bytes.
+    ///
+    /// Returns `None` if the stored Base64 data is invalid.
+    pub fn bytes(&self) -> Option<Bytes> {
+        if self.base64 {
+            BASE64_STANDARD.decode(&self.data).ok().map(Bytes::from)
+        } else {
+            Some(Bytes::from(self.data.clone()))
+        }
+    }
+
+    /// Returns true if the body exceeded the capture limit and only its start was recorded.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+fn is_false(val: &bool) -> bool {
+    !*val
+}
+
+fn header_pairs(headers: &HeaderMap, policy: &RedactionPolicy) -> Vec<(String, String)> {
+    redact_headers(headers, policy)
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_owned(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Middleware that records request/response pairs to a pluggable [sink](RecordSink).
+///
+/// Recordings contain the method, URI, headers, and body of both the request and response, in a
+/// format that can be [replayed](crate::test::replay) against a service later, making it easy to
+/// build regression corpora for complex routing setups.
+///
+/// Sensitive headers are redacted using the default [`RedactionPolicy`] unless a different one
+/// is set using [`redaction_policy()`](Self::redaction_policy). Bodies are not redacted.
+///
+/// Only the part of the request body that was read by the handler is recorded. At most 64 KiB of
+/// each body is captured (see [`body_limit()`](Self::body_limit)); bodies are still streamed in
+/// full. A recording is only made once the response body has been sent completely, so responses
+/// that fail or are abandoned mid-stream are not recorded.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::{Record, Recording};
+///
+/// let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Recording>();
+///
+/// App::new().wrap(Record::new(tx))
+/// # ;
+/// ```
+pub struct Record {
+    sink: Rc<dyn RecordSink>,
+    redaction_policy: Rc<RedactionPolicy>,
+    body_limit: usize,
+}
+
+impl Record {
+    /// Constructs new recording middleware that stores recordings in `sink`.
+    pub fn new(sink: impl RecordSink) -> Self {
+        Self {
+            sink: Rc::new(sink),
+            redaction_policy: Rc::new(RedactionPolicy::default()),
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    /// Sets the policy used to redact sensitive request and response headers.
+    pub fn redaction_policy(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction_policy = Rc::new(policy);
+        self
+    }
+
+    /// Sets the maximum number of bytes captured from each request and response body.
+    pub fn body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+}
+
+impl fmt::Debug for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Record")
+            .field("redaction_policy", &self.redaction_policy)
+            .field("body_limit", &self.body_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Record
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<RecordingBody<B>>;
+    type Error = S::Error;
+    type Transform = RecordMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RecordMiddleware {
+            service: Rc::new(service),
+            sink: Rc::clone(&self.sink),
+            redaction_policy: Rc::clone(&self.redaction_policy),
+            body_limit: self.body_limit,
+        }))
+    }
+}
+
+/// Middleware service implementation for [`Record`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct RecordMiddleware<S> {
+    service: Rc<S>,
+    sink: Rc<dyn RecordSink>,
+    redaction_policy: Rc<RedactionPolicy>,
+    body_limit: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for RecordMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<RecordingBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let sink = Rc::clone(&self.sink);
+        let policy = Rc::clone(&self.redaction_policy);
+        let body_limit = self.body_limit;
+
+        let method = req.method().to_string();
+        let uri = req
+            .uri()
+            .path_and_query()
+            .map_or_else(|| req.path().to_owned(), |pq| pq.as_str().to_owned());
+        let req_headers = header_pairs(req.headers(), &policy);
+
+        let req_body = capture_request_body(&mut req, body_limit);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            let status = res.status().as_u16();
+            let res_headers = header_pairs(res.headers(), &policy);
+
+            Ok(res.map_body(move |_, body| {
+                let mut pending = Some(PendingRecording {
+                    method,
+                    uri,
+                    req_headers,
+                    req_body,
+                    status,
+                    res_headers,
+                    sink,
+                });
+
+                // empty bodies are not necessarily polled so record them straight away
+                if matches!(body.size(), BodySize::None | BodySize::Sized(0)) {
+                    pending.take().unwrap().finish(&Capture::new(0));
+                }
+
+                RecordingBody {
+                    body,
+                    capture: Capture::new(body_limit),
+                    pending,
+                }
+            }))
+        })
+    }
+}
+
+impl Capture {
+    fn to_body(&self) -> RecordedBody {
+        RecordedBody::new(self.bytes(), self.is_truncated())
+    }
+}
+
+struct PendingRecording {
+    method: String,
+    uri: String,
+    req_headers: Vec<(String, String)>,
+    req_body: Rc<RefCell<Capture>>,
+    status: u16,
+    res_headers: Vec<(String, String)>,
+    sink: Rc<dyn RecordSink>,
+}
+
+impl PendingRecording {
+    /// Sends the completed recording to the sink.
+    fn finish(self, res_body: &Capture) {
+        let recording = Recording {
+            request: RecordedRequest {
+                method: self.method,
+                uri: self.uri,
+                headers: self.req_headers,
+                body: self.req_body.borrow().to_body(),
+            },
+            response: RecordedResponse {
+                status: self.status,
+                headers: self.res_headers,
+                body: res_body.to_body(),
+            },
+        };
+
+        self.sink.record(recording);
+    }
+}
+
+pin_project! {
+    /// Response body wrapper used by the [`Record`] middleware.
+    pub struct RecordingBody<B> {
+        #[pin]
+        body: B,
+        capture: Capture,
+        // taken when the recording is sent to the sink
+        pending: Option<PendingRecording>,
+    }
+}
+
+impl<B> fmt::Debug for RecordingBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingBody").finish_non_exhaustive()
+    }
+}
+
+impl<B: MessageBody> MessageBody for RecordingBody<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+        let item = futures_util::ready!(this.body.poll_next(cx));
+
+        match &item {
+            Some(Ok(chunk)) => this.capture.push(chunk),
+
+            Some(Err(_)) => {
+                // failed responses are not replayable
+                this.pending.take();
+            }
+
+            None => {
+                if let Some(pending) = this.pending.take() {
+                    pending.finish(this.capture);
+                }
+            }
+        }
+
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        http::header,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn records_exchanges() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let app = init_service(App::new().wrap(Record::new(tx)).route(
+            "/echo",
+            web::post().to(|body: Bytes| async move {
+                HttpResponse::Created()
+                    .insert_header((header::SET_COOKIE, "session=secret"))
+                    .body(body)
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/echo?x=1")
+            .insert_header((header::COOKIE, "session=secret"))
+            .set_payload("hello")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        // recorded once the body has been sent
+        assert!(rx.try_recv().is_err());
+        body::to_bytes(res.into_body()).await.unwrap();
+
+        let recording = rx.try_recv().unwrap();
+        assert_eq!(recording.request.method, "POST");
+        assert_eq!(recording.request.uri, "/echo?x=1");
+        assert!(recording
+            .request
+            .headers
+            .contains(&("cookie".to_owned(), "[redacted]".to_owned())));
+        assert_eq!(recording.request.body.bytes().unwrap(), "hello");
+
+        assert_eq!(recording.response.status, 201);
+        assert!(recording
+            .response
+            .headers
+            .contains(&("set-cookie".to_owned(), "[redacted]".to_owned())));
+        assert_eq!(recording.response.body.bytes().unwrap(), "hello");
+    }
+
+    #[actix_web::test]
+    async fn truncates_large_bodies() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let app = init_service(
+            App::new()
+                .wrap(Record::new(tx).body_limit(4))
+                .default_service(web::to(|| async {
+                    HttpResponse::Ok().body(vec![0xff; 10])
+                })),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        let bytes = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(bytes.len(), 10);
+
+        let recording = rx.try_recv().unwrap();
+        assert!(recording.response.body.is_truncated());
+        assert_eq!(recording.response.body.bytes().unwrap(), vec![0xff; 4]);
+        assert!(!recording.request.body.is_truncated());
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let recording = Recording {
+            request: RecordedRequest {
+                method: "GET".to_owned(),
+                uri: "/".to_owned(),
+                headers: vec![("accept".to_owned(), "*/*".to_owned())],
+                body: RecordedBody::default(),
+            },
+            response: RecordedResponse {
+                status: 200,
+                headers: vec![],
+                body: RecordedBody::new(&[0, 159, 146, 150], false),
+            },
+        };
+
+        let json = serde_json::to_string(&recording).unwrap();
+        assert!(json.contains(r#""base64":true"#));
+        assert!(!json.contains("truncated"));
+
+        let parsed = serde_json::from_str::<Recording>(&json).unwrap();
+        assert_eq!(parsed, recording);
+        assert_eq!(
+            parsed.response.body.bytes().unwrap(),
+            &[0, 159, 146, 150][..]
+        );
+    }
+}
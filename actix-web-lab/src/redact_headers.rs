@@ -0,0 +1,226 @@
+//! Header value redaction for logging.
+//!
+//! See [`redact_headers()`] docs.
+
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+
+/// Placeholder text that replaces redacted header values.
+const REDACTED: &str = "[redacted]";
+
+/// Describes which headers are sensitive and how their values are redacted.
+///
+/// Headers in the redacted set have their values replaced entirely. Headers in the partial set keep
+/// their authentication scheme (e.g., `Bearer`) and the first few characters of the credential,
+/// which is usually enough to correlate log lines with tokens without revealing them.
+///
+/// The default policy fully redacts `Cookie`, `Set-Cookie`, `X-API-Key`, `X-Auth-Token`, and
+/// `X-CSRF-Token`; and partially redacts `Authorization` and `Proxy-Authorization`, keeping 4
+/// characters of their credentials.
+///
+/// # Examples
+/// ```
+/// use actix_web::http::header::HeaderName;
+/// use actix_web_lab::util::RedactionPolicy;
+///
+/// let policy = RedactionPolicy::default()
+///     .redact(HeaderName::from_static("x-session"))
+///     .visible_chars(6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedactionPolicy {
+    redacted: Vec<HeaderName>,
+    partial: Vec<HeaderName>,
+    visible_chars: usize,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self {
+            redacted: vec![
+                header::COOKIE,
+                header::SET_COOKIE,
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static("x-auth-token"),
+                HeaderName::from_static("x-csrf-token"),
+            ],
+            partial: vec![header::AUTHORIZATION, header::PROXY_AUTHORIZATION],
+            visible_chars: 4,
+        }
+    }
+}
+
+impl RedactionPolicy {
+    /// Constructs a policy that does not redact any headers.
+    pub fn empty() -> Self {
+        Self {
+            redacted: Vec::new(),
+            partial: Vec::new(),
+            visible_chars: 4,
+        }
+    }
+
+    /// Adds a header whose values are fully redacted.
+    pub fn redact(mut self, name: HeaderName) -> Self {
+        self.partial.retain(|n| *n != name);
+        self.redacted.push(name);
+        self
+    }
+
+    /// Adds a header whose values are partially redacted.
+    pub fn redact_partially(mut self, name: HeaderName) -> Self {
+        self.redacted.retain(|n| *n != name);
+        self.partial.push(name);
+        self
+    }
+
+    /// Sets the number of credential characters left visible by partial redaction.
+    ///
+    /// Credentials too short to keep this many characters hidden are fully redacted.
+    pub fn visible_chars(mut self, visible_chars: usize) -> Self {
+        self.visible_chars = visible_chars;
+        self
+    }
+
+    /// Returns true if values of the named header are redacted in any way.
+    pub fn is_sensitive(&self, name: &HeaderName) -> bool {
+        self.redacted.contains(name) || self.partial.contains(name)
+    }
+
+    /// Returns the redacted form of a single header value.
+    pub fn redact_value(&self, name: &HeaderName, value: &HeaderValue) -> HeaderValue {
+        if self.redacted.contains(name) {
+            return HeaderValue::from_static(REDACTED);
+        }
+
+        if !self.partial.contains(name) {
+            return value.clone();
+        }
+
+        let Ok(value) = value.to_str() else {
+            return HeaderValue::from_static(REDACTED);
+        };
+
+        let (scheme, credential) = match value.trim().split_once(' ') {
+            Some((scheme, credential)) => (Some(scheme), credential.trim()),
+            None => (None, value.trim()),
+        };
+
+        // require at least as many hidden characters as visible ones
+        let visible = match credential.char_indices().nth(self.visible_chars) {
+            Some((idx, _)) if credential.len() >= idx * 2 => &credential[..idx],
+            _ => "",
+        };
+
+        let redacted = match scheme {
+            Some(scheme) => format!("{scheme} {visible}{REDACTED}"),
+            None => format!("{visible}{REDACTED}"),
+        };
+
+        // only consists of chars from a valid header value and the placeholder
+        HeaderValue::try_from(redacted).unwrap_or(HeaderValue::from_static(REDACTED))
+    }
+}
+
+/// Returns a copy of `headers` with sensitive values redacted according to `policy`.
+///
+/// Use this before logging or otherwise persisting request or response headers.
+///
+/// # Examples
+/// ```
+/// use actix_web::http::header::{self, HeaderMap, HeaderValue};
+/// use actix_web_lab::util::{redact_headers, RedactionPolicy};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(header::COOKIE, HeaderValue::from_static("session=secret"));
+/// headers.insert(
+///     header::AUTHORIZATION,
+///     HeaderValue::from_static("Bearer abcdefghijkl"),
+/// );
+///
+/// let redacted = redact_headers(&headers, &RedactionPolicy::default());
+/// assert_eq!(redacted.get(header::COOKIE).unwrap(), "[redacted]");
+/// assert_eq!(
+///     redacted.get(header::AUTHORIZATION).unwrap(),
+///     "Bearer abcd[redacted]",
+/// );
+/// ```
+pub fn redact_headers(headers: &HeaderMap, policy: &RedactionPolicy) -> HeaderMap {
+    let mut redacted = HeaderMap::with_capacity(headers.len());
+
+    for (name, value) in headers.iter() {
+        redacted.append(name.clone(), policy.redact_value(name, value));
+    }
+
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(policy: &RedactionPolicy, name: HeaderName, value: &'static str) -> HeaderValue {
+        policy.redact_value(&name, &HeaderValue::from_static(value))
+    }
+
+    #[test]
+    fn default_policy() {
+        let policy = RedactionPolicy::default();
+
+        assert_eq!(redact(&policy, header::COOKIE, "a=b"), REDACTED);
+        assert_eq!(redact(&policy, header::SET_COOKIE, "a=b"), REDACTED);
+        assert_eq!(
+            redact(&policy, header::CONTENT_TYPE, "text/plain"),
+            "text/plain"
+        );
+
+        assert_eq!(
+            redact(&policy, header::AUTHORIZATION, "Bearer 0123456789"),
+            "Bearer 0123[redacted]",
+        );
+        assert_eq!(
+            redact(&policy, header::AUTHORIZATION, "0123456789"),
+            "0123[redacted]",
+        );
+
+        // too short to keep anything visible
+        assert_eq!(
+            redact(&policy, header::AUTHORIZATION, "Basic abcdefg"),
+            "Basic [redacted]",
+        );
+    }
+
+    #[test]
+    fn custom_policy() {
+        let policy = RedactionPolicy::empty()
+            .redact(header::AUTHORIZATION)
+            .redact_partially(header::COOKIE)
+            .visible_chars(2);
+
+        assert!(policy.is_sensitive(&header::AUTHORIZATION));
+        assert!(!policy.is_sensitive(&header::SET_COOKIE));
+
+        assert_eq!(
+            redact(&policy, header::AUTHORIZATION, "Bearer abcdef"),
+            REDACTED
+        );
+        assert_eq!(
+            redact(&policy, header::COOKIE, "session=abc"),
+            "se[redacted]"
+        );
+        assert_eq!(redact(&policy, header::SET_COOKIE, "a=b"), "a=b");
+    }
+
+    #[test]
+    fn redacts_all_values() {
+        let mut headers = HeaderMap::new();
+        headers.append(header::COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(header::COOKIE, HeaderValue::from_static("b=2"));
+        headers.append(header::ACCEPT, HeaderValue::from_static("*/*"));
+
+        let redacted = redact_headers(&headers, &RedactionPolicy::default());
+
+        assert_eq!(redacted.len(), 3);
+        assert!(redacted.get_all(header::COOKIE).all(|val| val == REDACTED));
+        assert_eq!(redacted.get(header::ACCEPT).unwrap(), "*/*");
+    }
+}
@@ -0,0 +1,257 @@
+//! Redirect target validation.
+//!
+//! See [`validate_redirect_target()`] docs.
+
+use std::{borrow::Cow, fmt};
+
+use actix_web::{http::StatusCode, ResponseError};
+use derive_more::{Display, Error};
+use url::Url;
+
+/// Validates a redirect target, such as a `?next=` query parameter, to prevent open redirects.
+///
+/// Accepts:
+/// - relative references that stay on the current origin (e.g., `/account`, `settings?tab=2`);
+/// - absolute `http` and `https` URLs, and protocol-relative URLs (e.g., `//example.com/`), whose
+///   host is in `allow_list`. Hosts are compared case-insensitively and must match exactly, so
+///   subdomains need to be listed individually.
+///
+/// Targets containing whitespace, control characters, or backslashes are always rejected since
+/// browsers strip or reinterpret these in ways that can turn a seemingly relative path into an
+/// external URL.
+///
+/// The returned [`RedirectTarget`] can be passed directly to [`Redirect::to()`] and other redirect
+/// helpers.
+///
+/// # Errors
+/// Returns an error describing why the target is not safe to redirect to. The error responds with
+/// `400 Bad Request` when returned from handlers.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, Responder};
+/// use actix_web_lab::util::validate_redirect_target;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Login {
+///     next: String,
+/// }
+///
+/// #[get("/login/done")]
+/// async fn login_done(query: web::Query<Login>) -> actix_web::Result<impl Responder> {
+///     let target = validate_redirect_target(&query.next, &["accounts.example.com"])?;
+///     Ok(web::Redirect::to(target).see_other())
+/// }
+///
+/// let allow_list = &["accounts.example.com"];
+///
+/// assert!(validate_redirect_target("/account", allow_list).is_ok());
+/// assert!(validate_redirect_target("https://accounts.example.com/", allow_list).is_ok());
+/// assert!(validate_redirect_target("https://evil.example/", allow_list).is_err());
+/// assert!(validate_redirect_target("//evil.example/", allow_list).is_err());
+/// ```
+///
+/// [`Redirect::to()`]: actix_web::web::Redirect::to
+pub fn validate_redirect_target(
+    url: &str,
+    allow_list: &[&str],
+) -> Result<RedirectTarget, RedirectTargetError> {
+    if url.is_empty() {
+        return Err(RedirectTargetError::Empty);
+    }
+
+    if url
+        .chars()
+        .any(|ch| ch.is_whitespace() || ch.is_control() || ch == '\\')
+    {
+        return Err(RedirectTargetError::InvalidCharacters);
+    }
+
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+
+        // protocol-relative URLs take the current scheme but point at another host
+        Err(url::ParseError::RelativeUrlWithoutBase) if url.starts_with("//") => {
+            Url::parse(&format!("https:{url}")).map_err(|_| RedirectTargetError::Malformed)?
+        }
+
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            return Ok(RedirectTarget(url.to_owned()));
+        }
+
+        Err(_) => return Err(RedirectTargetError::Malformed),
+    };
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(RedirectTargetError::UnsupportedScheme);
+    }
+
+    let host = parsed.host_str().ok_or(RedirectTargetError::Malformed)?;
+
+    if !allow_list
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        return Err(RedirectTargetError::HostNotAllowed);
+    }
+
+    Ok(RedirectTarget(url.to_owned()))
+}
+
+/// A redirect target that has passed [`validate_redirect_target()`].
+///
+/// Converts into the `Cow<'static, str>` accepted by [`Redirect::to()`].
+///
+/// [`Redirect::to()`]: actix_web::web::Redirect::to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectTarget(String);
+
+impl RedirectTarget {
+    /// Returns the redirect target as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Unwraps the redirect target string.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for RedirectTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<RedirectTarget> for Cow<'static, str> {
+    fn from(target: RedirectTarget) -> Self {
+        Cow::Owned(target.0)
+    }
+}
+
+/// Reasons a redirect target can be rejected by [`validate_redirect_target()`].
+#[derive(Debug, Display, Error, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedirectTargetError {
+    /// Target is empty.
+    #[display("Redirect target is empty")]
+    Empty,
+
+    /// Target contains whitespace, control characters, or backslashes.
+    #[display("Redirect target contains invalid characters")]
+    InvalidCharacters,
+
+    /// Target could not be parsed as a URL.
+    #[display("Redirect target is malformed")]
+    Malformed,
+
+    /// Target is an absolute URL with a scheme other than `http` or `https`.
+    #[display("Redirect target scheme is not supported")]
+    UnsupportedScheme,
+
+    /// Target is an absolute URL whose host is not in the allow list.
+    #[display("Redirect target host is not allowed")]
+    HostNotAllowed,
+}
+
+impl ResponseError for RedirectTargetError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_targets() {
+        for url in [
+            "/",
+            "/account",
+            "/a/b?c=d#e",
+            "settings",
+            "../up",
+            "?tab=2",
+            "#top",
+        ] {
+            assert_eq!(
+                validate_redirect_target(url, &[]).unwrap().as_str(),
+                url,
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn absolute_targets() {
+        let allow_list = &["example.com", "accounts.example.com"];
+
+        for url in [
+            "https://example.com",
+            "http://EXAMPLE.com/path",
+            "https://accounts.example.com:8443/login",
+            "//example.com/path",
+            "https://evil.example@example.com/",
+        ] {
+            assert!(validate_redirect_target(url, allow_list).is_ok(), "{url}");
+        }
+
+        for url in [
+            "https://evil.example/",
+            "https://example.com.evil.example/",
+            "https://sub.example.com/",
+            "https://example.com@evil.example/",
+            "//evil.example/",
+            "///evil.example/",
+        ] {
+            assert_eq!(
+                validate_redirect_target(url, allow_list),
+                Err(RedirectTargetError::HostNotAllowed),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejected_targets() {
+        let allow_list = &["example.com"];
+
+        assert_eq!(
+            validate_redirect_target("", allow_list),
+            Err(RedirectTargetError::Empty)
+        );
+
+        for url in [
+            "/\\evil.example",
+            "/\t/evil.example",
+            " //evil.example",
+            "/a\nb",
+        ] {
+            assert_eq!(
+                validate_redirect_target(url, allow_list),
+                Err(RedirectTargetError::InvalidCharacters),
+                "{url:?}"
+            );
+        }
+
+        for url in [
+            "javascript:alert(1)",
+            "data:text/html,hi",
+            "ftp://example.com/",
+        ] {
+            assert_eq!(
+                validate_redirect_target(url, allow_list),
+                Err(RedirectTargetError::UnsupportedScheme),
+                "{url}"
+            );
+        }
+
+        assert_eq!(
+            validate_redirect_target("https://[::1", allow_list),
+            Err(RedirectTargetError::Malformed)
+        );
+    }
+}
@@ -24,6 +24,17 @@ use crate::header::StrictTransportSecurity;
 ///
 /// See [`StrictTransportSecurity`] docs for more info.
 ///
+/// # Exclusions
+///
+/// Requests whose paths start with one of the prefixes added using
+/// [`exclude_path_prefix()`](Self::exclude_path_prefix) are passed through without redirecting.
+/// This is most commonly needed for [ACME HTTP-01 challenges] (see
+/// [`ACME_CHALLENGE_PREFIX`](Self::ACME_CHALLENGE_PREFIX)), which must be served over plain HTTP
+/// for certificate issuance and renewal to work.
+///
+/// The HSTS header can similarly be omitted from responses to certain paths using
+/// [`exclude_hsts_path_prefix()`](Self::exclude_hsts_path_prefix).
+///
 /// # Examples
 ///
 /// ```
@@ -37,18 +48,30 @@ use crate::header::StrictTransportSecurity;
 /// let mw = RedirectHttps::with_hsts(StrictTransportSecurity::new(Duration::from_secs(60 * 60)));
 /// let mw = RedirectHttps::with_hsts(StrictTransportSecurity::recommended());
 ///
+/// // keep Let's Encrypt HTTP-01 validation working
+/// let mw = RedirectHttps::default().exclude_path_prefix(RedirectHttps::ACME_CHALLENGE_PREFIX);
+///
 /// App::new().wrap(mw)
 /// # ;
 /// ```
 ///
+/// [ACME HTTP-01 challenges]: https://letsencrypt.org/docs/challenge-types/#http-01-challenge
 /// [HTTP Strict Transport Security (HSTS)]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Strict-Transport-Security
 #[derive(Debug, Clone, Default)]
 pub struct RedirectHttps {
     hsts: Option<StrictTransportSecurity>,
     port: Option<u16>,
+    excluded: Vec<String>,
+    hsts_excluded: Vec<String>,
 }
 
 impl RedirectHttps {
+    /// Path prefix used by [ACME HTTP-01 challenges], suitable for passing to
+    /// [`exclude_path_prefix()`](Self::exclude_path_prefix).
+    ///
+    /// [ACME HTTP-01 challenges]: https://letsencrypt.org/docs/challenge-types/#http-01-challenge
+    pub const ACME_CHALLENGE_PREFIX: &'static str = "/.well-known/acme-challenge/";
+
     /// Construct new HTTP redirect middleware with strict transport security configuration.
     pub fn with_hsts(hsts: StrictTransportSecurity) -> Self {
         Self {
@@ -64,6 +87,23 @@ impl RedirectHttps {
         self.port = Some(port);
         self
     }
+
+    /// Excludes requests with paths starting with `prefix` from being redirected.
+    ///
+    /// Can be called multiple times to exclude several prefixes.
+    pub fn exclude_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.excluded.push(prefix.into());
+        self
+    }
+
+    /// Omits the HSTS header from responses to requests with paths starting with `prefix`.
+    ///
+    /// Can be called multiple times to exclude several prefixes. Has no effect if HSTS is not
+    /// configured.
+    pub fn exclude_hsts_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.hsts_excluded.push(prefix.into());
+        self
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for RedirectHttps
@@ -81,6 +121,8 @@ where
             service: Rc::new(service),
             hsts: self.hsts,
             port: self.port,
+            excluded: self.excluded.clone().into(),
+            hsts_excluded: self.hsts_excluded.clone().into(),
         }))
     }
 }
@@ -92,6 +134,8 @@ pub struct RedirectHttpsMiddleware<S> {
     service: Rc<S>,
     hsts: Option<StrictTransportSecurity>,
     port: Option<u16>,
+    excluded: Rc<[String]>,
+    hsts_excluded: Rc<[String]>,
 }
 
 impl<S, B> Service<ServiceRequest> for RedirectHttpsMiddleware<S>
@@ -108,14 +152,17 @@ where
         #![allow(clippy::await_holding_refcell_ref)] // RefCell is dropped before await
 
         let service = Rc::clone(&self.service);
-        let hsts = self.hsts;
         let port = self.port;
 
+        let path = req.path();
+        let excluded = has_prefix(&self.excluded, path);
+        let hsts = self.hsts.filter(|_| !has_prefix(&self.hsts_excluded, path));
+
         Box::pin(async move {
             let (req, pl) = req.into_parts();
             let conn_info = req.connection_info();
 
-            if conn_info.scheme() != "https" {
+            if conn_info.scheme() != "https" && !excluded {
                 let host = conn_info.host();
 
                 // construct equivalent https path
@@ -153,6 +200,13 @@ where
     }
 }
 
+/// Returns true if `path` starts with any of `prefixes`.
+fn has_prefix(prefixes: &[String], path: &str) -> bool {
+    prefixes
+        .iter()
+        .any(|prefix| path.starts_with(prefix.as_str()))
+}
+
 /// Apply HSTS config to an `HttpResponse`.
 fn apply_hsts<B>(res: &mut HttpResponse<B>, hsts: Option<StrictTransportSecurity>) {
     if let Some(hsts) = hsts {
@@ -278,4 +332,31 @@ mod tests {
         let res = test::call_service(&app, req).await;
         assert_response_matches!(res, TEMPORARY_REDIRECT; "location" => "https://localhost:8443/");
     }
+
+    #[actix_web::test]
+    async fn excluded_paths() {
+        let app = RedirectHttps::with_hsts(StrictTransportSecurity::recommended())
+            .exclude_path_prefix(RedirectHttps::ACME_CHALLENGE_PREFIX)
+            .exclude_hsts_path_prefix("/no-hsts")
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let req =
+            test_request!(GET "http://localhost/.well-known/acme-challenge/token").to_srv_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = test_request!(GET "http://localhost/.well-known/other").to_srv_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+
+        let req = test_request!(GET "https://localhost:443/no-hsts/page").to_srv_request();
+        let res = test::call_service(&app, req).await;
+        assert!(!res.headers().contains_key(StrictTransportSecurity::name()));
+
+        let req = test_request!(GET "https://localhost:443/page").to_srv_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.headers().contains_key(StrictTransportSecurity::name()));
+    }
 }
@@ -0,0 +1,404 @@
+//! Replayable request body extractor.
+//!
+//! See [`ReplayableBody`] docs.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read as _, Write as _},
+    path::PathBuf,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use actix_http::BoxedPayloadStream;
+use actix_web::{
+    dev,
+    error::PayloadError,
+    http::StatusCode,
+    web::{self, Bytes, BytesMut},
+    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::{future::Either, stream, StreamExt as _};
+use tokio::sync::oneshot;
+
+/// Default in-memory buffer size of 1MiB.
+pub const DEFAULT_REPLAYABLE_MEMORY_LIMIT: usize = 1_048_576;
+
+/// Default replayable body size limit of 64MiB.
+pub const DEFAULT_REPLAYABLE_BODY_LIMIT: usize = 67_108_864;
+
+/// Size of chunks read from spilled bodies.
+const FILE_CHUNK_SIZE: usize = 65_536;
+
+/// Counter used to generate unique spill file names.
+static SPILL_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Errors that can occur when buffering a replayable request body.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum ReplayableBodyError {
+    /// Payload exceeded the size limit.
+    #[display("Payload is larger than allowed (limit: {limit} bytes)")]
+    Overflow {
+        /// Payload size limit.
+        #[error(not(source))]
+        limit: usize,
+    },
+
+    /// Spilled payload could not be written to a temporary file.
+    #[display("Failed to buffer payload to temporary file: {_0}")]
+    Io(io::Error),
+
+    /// Error reading request payload.
+    #[display("Error reading request payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for ReplayableBodyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+        }
+    }
+}
+
+/// Temporary file holding a spilled body; removed when the last handle is dropped.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Storage {
+    Memory(Bytes),
+    File { file: Arc<SpillFile>, len: usize },
+}
+
+/// Request body extractor that buffers the payload so it can be read more than once.
+///
+/// The payload is buffered in memory, up to `MEMORY_LIMIT` bytes (default 1MiB). Larger payloads
+/// are spilled to a temporary file, which is removed once the body is no longer in use. Payloads
+/// larger than `LIMIT` bytes (default 64MiB) are rejected with a `413 Payload Too Large` response.
+///
+/// Once buffered, the body is re-injected into the request, so extractors that follow this one in
+/// a handler's arguments can read it as usual. The buffered body is also stored in the request's
+/// extensions so that, for example, middleware can forward the original body to an upstream
+/// service after the handler has run; see [`ReplayableBody::of()`]. Use
+/// [`to_payload()`](Self::to_payload) to replay the body any number of times.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web};
+/// use actix_web_lab::extract::ReplayableBody;
+///
+/// #[post("/mirror")]
+/// async fn mirror(body: ReplayableBody, text: String) -> String {
+///     // the body was buffered by `ReplayableBody` and can still be read by `String`
+///     assert_eq!(body.len(), text.len());
+///
+///     // and it can be replayed again, e.g., to forward it to another service
+///     let _payload = body.to_payload();
+///
+///     text
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayableBody<
+    const MEMORY_LIMIT: usize = DEFAULT_REPLAYABLE_MEMORY_LIMIT,
+    const LIMIT: usize = DEFAULT_REPLAYABLE_BODY_LIMIT,
+> {
+    storage: Storage,
+}
+
+impl ReplayableBody {
+    /// Returns the body buffered by a `ReplayableBody` extractor for this request, if any.
+    pub fn of(req: &HttpRequest) -> Option<Self> {
+        req.extensions().get::<Self>().cloned()
+    }
+}
+
+impl<const MEMORY_LIMIT: usize, const LIMIT: usize> ReplayableBody<MEMORY_LIMIT, LIMIT> {
+    /// Returns the size of the body, in bytes.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Memory(bytes) => bytes.len(),
+            Storage::File { len, .. } => *len,
+        }
+    }
+
+    /// Returns true if the body is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the body was too large to be buffered in memory and was spilled to a
+    /// temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.storage, Storage::File { .. })
+    }
+
+    /// Returns the body bytes, if the body is buffered in memory.
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match &self.storage {
+            Storage::Memory(bytes) => Some(bytes),
+            Storage::File { .. } => None,
+        }
+    }
+
+    /// Returns a new payload stream that yields the buffered body.
+    ///
+    /// Spilled bodies are read from their temporary file in chunks, using the blocking thread pool.
+    pub fn to_payload(&self) -> dev::Payload {
+        let stream: BoxedPayloadStream = match &self.storage {
+            Storage::Memory(bytes) => Box::pin(stream::iter([Ok(bytes.clone())])),
+            Storage::File { file, .. } => Box::pin(file_stream(Arc::clone(file))),
+        };
+
+        dev::Payload::from(stream)
+    }
+
+    fn into_default(self) -> ReplayableBody {
+        ReplayableBody {
+            storage: self.storage,
+        }
+    }
+}
+
+impl<const MEMORY_LIMIT: usize, const LIMIT: usize> FromRequest
+    for ReplayableBody<MEMORY_LIMIT, LIMIT>
+{
+    type Error = ReplayableBodyError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let req = req.clone();
+
+        let mut orig_payload = payload.take();
+
+        // re-inject the body once buffered so that following extractors can read it
+        let (tx, rx) = oneshot::channel();
+        *payload = dev::Payload::from(Box::pin(replay_stream(rx)) as BoxedPayloadStream);
+
+        Box::pin(async move {
+            let mut buf = BytesMut::new();
+            let mut spill = None;
+            let mut len = 0;
+
+            while let Some(chunk) = orig_payload.next().await {
+                let chunk = chunk.map_err(ReplayableBodyError::Payload)?;
+
+                len += chunk.len();
+
+                if len > LIMIT {
+                    return Err(ReplayableBodyError::Overflow { limit: LIMIT });
+                }
+
+                match spill.take() {
+                    Some((file, handle)) => spill = Some(write_chunk(file, handle, chunk).await?),
+
+                    None if len > MEMORY_LIMIT => {
+                        let (file, handle) = create_spill_file().await?;
+                        let initial = buf.split().freeze();
+                        let (file, handle) = write_chunk(file, handle, initial).await?;
+                        spill = Some(write_chunk(file, handle, chunk).await?);
+                    }
+
+                    None => buf.extend_from_slice(&chunk),
+                }
+            }
+
+            let storage = match spill {
+                Some((file, _handle)) => Storage::File { file, len },
+                None => Storage::Memory(buf.freeze()),
+            };
+
+            let body = Self { storage };
+
+            let _ = tx.send(body.to_payload());
+
+            // make the body available to middleware
+            req.extensions_mut().insert(body.clone().into_default());
+
+            Ok(body)
+        })
+    }
+}
+
+/// Yields the buffered body once it is sent, or an error if buffering fails.
+fn replay_stream(
+    rx: oneshot::Receiver<dev::Payload>,
+) -> impl futures_core::Stream<Item = Result<Bytes, PayloadError>> {
+    stream::once(rx).flat_map(|res| match res {
+        Ok(payload) => Either::Left(payload),
+        Err(_) => Either::Right(stream::iter([Err(PayloadError::Incomplete(None))])),
+    })
+}
+
+/// Creates a new, uniquely named temporary file for a spilled body.
+async fn create_spill_file() -> Result<(Arc<SpillFile>, File), ReplayableBodyError> {
+    let path = std::env::temp_dir().join(format!(
+        "actix-web-lab-body-{}-{}",
+        process::id(),
+        SPILL_FILE_ID.fetch_add(1, Ordering::Relaxed),
+    ));
+
+    let file = SpillFile { path };
+
+    let path = file.path.clone();
+    let handle = web::block(move || OpenOptions::new().write(true).create_new(true).open(path))
+        .await
+        .map_err(|err| ReplayableBodyError::Io(io::Error::other(err)))?
+        .map_err(ReplayableBodyError::Io)?;
+
+    Ok((Arc::new(file), handle))
+}
+
+/// Appends `chunk` to a spill file on the blocking thread pool.
+async fn write_chunk(
+    file: Arc<SpillFile>,
+    mut handle: File,
+    chunk: Bytes,
+) -> Result<(Arc<SpillFile>, File), ReplayableBodyError> {
+    let handle = web::block(move || handle.write_all(&chunk).map(|_| handle))
+        .await
+        .map_err(|err| ReplayableBodyError::Io(io::Error::other(err)))?
+        .map_err(ReplayableBodyError::Io)?;
+
+    Ok((file, handle))
+}
+
+/// Streams the contents of a spill file in chunks.
+fn file_stream(
+    file: Arc<SpillFile>,
+) -> impl futures_core::Stream<Item = Result<Bytes, PayloadError>> {
+    stream::unfold(Some((file, None::<File>)), |state| async move {
+        let (file, handle) = state?;
+        let path = file.path.clone();
+
+        let res = web::block(move || {
+            let mut handle = match handle {
+                Some(handle) => handle,
+                None => File::open(path)?,
+            };
+
+            let mut buf = vec![0; FILE_CHUNK_SIZE];
+            let n = handle.read(&mut buf)?;
+            buf.truncate(n);
+
+            Ok::<_, io::Error>((handle, buf))
+        })
+        .await
+        .map_err(io::Error::other)
+        .and_then(|res| res);
+
+        match res {
+            Ok((_, buf)) if buf.is_empty() => None,
+            Ok((handle, buf)) => Some((Ok(Bytes::from(buf)), Some((file, Some(handle))))),
+            Err(err) => Some((Err(PayloadError::Io(err)), None)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        test::{call_service, init_service, TestRequest},
+        App, HttpResponse,
+    };
+
+    use super::*;
+
+    async fn read_payload(mut payload: dev::Payload) -> Bytes {
+        let mut buf = BytesMut::new();
+
+        while let Some(chunk) = payload.next().await {
+            buf.extend_from_slice(&chunk.unwrap());
+        }
+
+        buf.freeze()
+    }
+
+    #[actix_web::test]
+    async fn buffers_in_memory() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload("hello world")
+            .to_http_parts();
+
+        let body = ReplayableBody::<64>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(!body.is_spilled());
+        assert_eq!(body.as_bytes().unwrap(), "hello world");
+        assert_eq!(read_payload(body.to_payload()).await, "hello world");
+
+        // re-injected for following extractors
+        assert_eq!(
+            String::from_request(&req, &mut pl).await.unwrap(),
+            "hello world"
+        );
+        assert_eq!(ReplayableBody::of(&req).unwrap().len(), 11);
+    }
+
+    #[actix_web::test]
+    async fn spills_to_file() {
+        let body = Bytes::from(vec![b'a'; FILE_CHUNK_SIZE + 10]);
+
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(body.clone())
+            .to_http_parts();
+
+        let replayable = ReplayableBody::<16>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(replayable.is_spilled());
+        assert!(replayable.as_bytes().is_none());
+        assert_eq!(replayable.len(), body.len());
+
+        let path = match &replayable.storage {
+            Storage::File { file, .. } => file.path.clone(),
+            Storage::Memory(_) => unreachable!(),
+        };
+
+        assert_eq!(read_payload(replayable.to_payload()).await, body);
+        assert_eq!(read_payload(replayable.to_payload()).await, body);
+        assert_eq!(web::Bytes::from_request(&req, &mut pl).await.unwrap(), body);
+
+        // file is removed once all handles, including the request extension, are dropped
+        drop(replayable);
+        drop(req);
+        assert!(!path.exists());
+    }
+
+    #[actix_web::test]
+    async fn rejects_large_bodies() {
+        let app = init_service(
+            App::new().default_service(web::to(|body: ReplayableBody<4, 8>| async move {
+                HttpResponse::Ok().streaming(body.to_payload())
+            })),
+        )
+        .await;
+
+        let req = TestRequest::default().set_payload("12345678").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body::to_bytes(res.into_body()).await.unwrap(), "12345678");
+
+        let req = TestRequest::default().set_payload("123456789").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
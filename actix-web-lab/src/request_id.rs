@@ -0,0 +1,158 @@
+//! Request ID extractor.
+//!
+//! See [`RequestId`] docs.
+
+use std::{fmt, sync::Arc};
+
+use actix_utils::future::{err, ok, Ready};
+use actix_web::{dev::Payload, error, Error, FromRequest, HttpMessage as _, HttpRequest};
+use tracing::debug;
+
+/// Crockford's Base32 alphabet, used to encode ULIDs.
+const CROCKFORD_BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Identifier of the current request, assigned by the [`RequestId`](crate::middleware::RequestId)
+/// middleware.
+///
+/// Cheap to clone, so can be moved into spawned tasks or included in error responses. Extraction
+/// fails with a `500 Internal Server Error` if the middleware is not registered.
+///
+/// # Examples
+/// ```
+/// use actix_web::get;
+/// use actix_web_lab::extract::RequestId;
+///
+/// #[get("/")]
+/// async fn index(request_id: RequestId) -> String {
+///     format!("your request ID is {request_id}")
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    pub(crate) fn new(id: impl Into<Arc<str>>) -> Self {
+        Self(id.into())
+    }
+
+    /// Generates a random, version 4 UUID in its hyphenated, lowercase form.
+    pub(crate) fn uuid() -> Self {
+        let mut id = crate::util::random_u128();
+
+        // set version 4 and RFC 9562 variant bits
+        id = (id & !(0xF << 76)) | (0x4 << 76);
+        id = (id & !(0x3 << 62)) | (0x2 << 62);
+
+        let hex = format!("{id:032x}");
+
+        Self::new(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..],
+        ))
+    }
+
+    /// Generates a ULID from the current time and random bits.
+    pub(crate) fn ulid() -> Self {
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|dur| dur.as_millis())
+            .unwrap_or_default();
+
+        let random = crate::util::random_u128() & ((1 << 80) - 1);
+        let id = ((millis & ((1 << 48) - 1)) << 80) | random;
+
+        // 26 characters of 5 bits each, the first of which only encodes the top 3 bits
+        let encoded = (0..26)
+            .map(|idx| CROCKFORD_BASE32[((id >> (125 - idx * 5)) & 0x1F) as usize] as char)
+            .collect::<String>();
+
+        Self::new(encoded)
+    }
+
+    /// Returns the request ID as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for RequestId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        match req.extensions().get::<Self>() {
+            Some(id) => ok(id.clone()),
+
+            None => {
+                debug!(
+                    "Failed to extract `RequestId` for `{}` handler. For the RequestId extractor \
+                    to work correctly, wrap the app or scope with the `RequestId` middleware.",
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                err(error::ErrorInternalServerError(
+                    "Request ID is not configured correctly. \
+                    View/enable debug logs for more details.",
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test::TestRequest};
+
+    use super::*;
+
+    #[test]
+    fn uuid_format() {
+        let id = RequestId::uuid();
+
+        assert_eq!(id.len(), 36);
+        assert_eq!(id.matches('-').count(), 4);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(id, RequestId::uuid());
+    }
+
+    #[test]
+    fn ulid_format() {
+        let id = RequestId::ulid();
+
+        assert_eq!(id.len(), 26);
+        assert!(id.bytes().all(|b| CROCKFORD_BASE32.contains(&b)));
+        // timestamps before year 10889 start with a digit from 0 to 7
+        assert!(matches!(id.as_bytes()[0], b'0'..=b'7'));
+        assert_ne!(id, RequestId::ulid());
+    }
+
+    #[actix_web::test]
+    async fn missing_middleware() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+
+        let err = RequestId::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -1,12 +1,17 @@
-use std::{fmt, future::Future};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 use actix_http::BoxedPayloadStream;
-use actix_web::{dev, web::Bytes, Error, FromRequest, HttpRequest};
+use actix_web::{dev, rt::task::JoinHandle, web::Bytes, Error, FromRequest, HttpRequest};
 use derive_more::Display;
-use futures_core::future::LocalBoxFuture;
-use futures_util::{FutureExt as _, StreamExt as _, TryFutureExt as _};
+use futures_core::{future::LocalBoxFuture, stream::LocalBoxStream, Stream};
+use futures_util::{stream, FutureExt as _, StreamExt as _, TryFutureExt as _};
 use local_channel::mpsc;
-use tokio::try_join;
+use tokio::{sync::oneshot, try_join};
 use tracing::trace;
 
 /// Define a scheme for deriving and verifying some kind of signature from request parts.
@@ -151,6 +156,8 @@ pub trait RequestSignatureScheme: Sized {
 ///
 /// Warning: Currently, this will always take the body meaning that if a body extractor is used,
 /// this needs to wrap it or else it will not work.
+///
+/// See [`RequestSignatureStream`] for a variant that does not require the body to be buffered.
 #[allow(missing_debug_implementations)]
 #[derive(Clone)]
 pub struct RequestSignature<T, S: RequestSignatureScheme> {
@@ -293,6 +300,187 @@ where
     }
 }
 
+/// Exposes the request payload as a stream, calculating a request signature as it passes through.
+///
+/// Unlike [`RequestSignature`], which wraps a buffering extractor, this extractor yields body chunks
+/// as they arrive. This allows gateways and proxies to forward the body upstream while verifying its
+/// signature, without holding the whole body in memory.
+///
+/// Chunks are folded into the signature scheme as they are yielded. When the payload ends, the
+/// signature is finalized and [verified](RequestSignatureScheme::verify). If any step fails, the
+/// stream yields the scheme's error in place of its end, so that consumers, such as an upstream
+/// server, see an incomplete body instead of a complete, unverified one. The verified signature is
+/// available through [`signature()`](Self::signature) once the stream has been fully consumed.
+///
+/// Extraction itself only fails if the scheme's [`init`](RequestSignatureScheme::init) step fails.
+///
+/// # Examples
+/// ```no_run
+/// # use actix_web::{web::Bytes, Error, HttpRequest};
+/// # use actix_web_lab::extract::RequestSignatureScheme;
+/// # struct AbcApi;
+/// # impl RequestSignatureScheme for AbcApi {
+/// #     type Signature = ();
+/// #     type Error = Error;
+/// #     async fn init(req: &HttpRequest) -> Result<Self, Self::Error> { Ok(AbcApi) }
+/// #     async fn consume_chunk(&mut self, _: &HttpRequest, _: Bytes) -> Result<(), Error> { Ok(()) }
+/// #     async fn finalize(self, _: &HttpRequest) -> Result<(), Error> { Ok(()) }
+/// # }
+/// use actix_web::{web, HttpResponse};
+/// use actix_web_lab::extract::RequestSignatureStream;
+///
+/// async fn forward(
+///     body: RequestSignatureStream<AbcApi>,
+///     client: web::Data<awc::Client>,
+/// ) -> actix_web::Result<HttpResponse> {
+///     // upstream receives an incomplete body if the signature is invalid
+///     let res = client
+///         .post("http://upstream.internal/webhook")
+///         .send_stream(body)
+///         .await
+///         .map_err(actix_web::error::ErrorBadGateway)?;
+///
+///     Ok(HttpResponse::build(res.status()).finish())
+/// }
+/// ```
+pub struct RequestSignatureStream<S: RequestSignatureScheme> {
+    stream: LocalBoxStream<'static, Result<Bytes, Error>>,
+    rx: Option<oneshot::Receiver<S::Signature>>,
+}
+
+impl<S: RequestSignatureScheme> RequestSignatureStream<S> {
+    /// Returns a future that resolves to the verified signature once the stream has been fully
+    /// consumed.
+    ///
+    /// The future resolves to `None` if the stream is dropped before it ends, yields a payload
+    /// error, or fails signature calculation or verification.
+    ///
+    /// # Panics
+    /// Panics if called more than once.
+    pub fn signature(&mut self) -> impl Future<Output = Option<S::Signature>> + 'static
+    where
+        S::Signature: 'static,
+    {
+        self.rx
+            .take()
+            .expect("RequestSignatureStream::signature() should only be called once")
+            .map(Result::ok)
+    }
+}
+
+impl<S: RequestSignatureScheme> fmt::Debug for RequestSignatureStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSignatureStream")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> FromRequest for RequestSignatureStream<S>
+where
+    S: RequestSignatureScheme + 'static,
+    S::Signature: 'static,
+{
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            trace!("initializing signature scheme");
+            let mut sig_scheme = S::init(&req).await?;
+
+            let (chunk_tx, mut chunk_rx) = mpsc::channel::<Bytes>();
+            let (sig_tx, sig_rx) = oneshot::channel();
+
+            // run update function as chunks are yielded from payload stream
+            let hash_task = actix_web::rt::spawn({
+                let req = req.clone();
+
+                async move {
+                    while let Some(chunk) = chunk_rx.recv().await {
+                        trace!("digesting chunk");
+                        sig_scheme.consume_chunk(&req, chunk).await?;
+                    }
+
+                    trace!("finalizing signature");
+                    let signature = sig_scheme.finalize(&req).await?;
+
+                    trace!("verifying signature");
+                    S::verify(signature, &req)
+                }
+            });
+
+            let state = PassthroughState::<S> {
+                payload,
+                chunk_tx: Some(chunk_tx),
+                hash_task: Some(hash_task),
+                sig_tx: Some(sig_tx),
+            };
+
+            let stream = stream::unfold(state, |mut state| async move {
+                if let Some(chunk_tx) = &state.chunk_tx {
+                    match state.payload.next().await {
+                        Some(Ok(chunk)) => {
+                            trace!("yielding {} byte chunk", chunk.len());
+
+                            // send errors mean signature calculation has failed and the error
+                            // will be reported when the payload ends
+                            let _ = chunk_tx.send(chunk.clone());
+
+                            return Some((Ok(chunk), state));
+                        }
+
+                        Some(Err(err)) => {
+                            state.chunk_tx = None;
+                            state.hash_task = None;
+                            return Some((Err(err.into()), state));
+                        }
+
+                        // closing channel allows signature to be finalized
+                        None => state.chunk_tx = None,
+                    }
+                }
+
+                let hash_task = state.hash_task.take()?;
+
+                match hash_task.await.unwrap() {
+                    Ok(signature) => {
+                        if let Some(sig_tx) = state.sig_tx.take() {
+                            let _ = sig_tx.send(signature);
+                        }
+
+                        None
+                    }
+
+                    Err(err) => Some((Err(err.into()), state)),
+                }
+            });
+
+            Ok(Self {
+                stream: Box::pin(stream),
+                rx: Some(sig_rx),
+            })
+        })
+    }
+}
+
+impl<S: RequestSignatureScheme> Stream for RequestSignatureStream<S> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+struct PassthroughState<S: RequestSignatureScheme> {
+    payload: dev::Payload,
+    chunk_tx: Option<mpsc::Sender<Bytes>>,
+    hash_task: Option<JoinHandle<Result<S::Signature, S::Error>>>,
+    sig_tx: Option<oneshot::Sender<S::Signature>>,
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::Infallible;
@@ -303,13 +491,69 @@ mod tests {
         web::{self, Bytes},
         App,
     };
-    use digest::{CtOutput, Digest as _};
+    use digest::{generic_array::GenericArray, CtOutput, Digest as _};
     use hex_literal::hex;
     use sha2::Sha256;
 
     use super::*;
     use crate::extract::Json;
 
+    #[derive(Debug, Display)]
+    #[display("signature mismatch")]
+    struct SignatureMismatch;
+
+    impl actix_web::ResponseError for SignatureMismatch {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::UNAUTHORIZED
+        }
+    }
+
+    /// Hashes body and verifies it against the hex-encoded `x-sig` header.
+    struct HeaderHash(sha2::Sha256);
+
+    impl RequestSignatureScheme for HeaderHash {
+        type Signature = CtOutput<sha2::Sha256>;
+        type Error = SignatureMismatch;
+
+        async fn init(_req: &HttpRequest) -> Result<Self, Self::Error> {
+            Ok(Self(Sha256::new()))
+        }
+
+        async fn consume_chunk(
+            &mut self,
+            _req: &HttpRequest,
+            chunk: Bytes,
+        ) -> Result<(), Self::Error> {
+            self.0.update(&chunk);
+            Ok(())
+        }
+
+        async fn finalize(self, _req: &HttpRequest) -> Result<Self::Signature, Self::Error> {
+            Ok(CtOutput::new(self.0.finalize()))
+        }
+
+        fn verify(
+            signature: Self::Signature,
+            req: &HttpRequest,
+        ) -> Result<Self::Signature, Self::Error> {
+            let candidate = req
+                .headers()
+                .get("x-sig")
+                .and_then(|sig| sig.to_str().ok())
+                .ok_or(SignatureMismatch)?;
+
+            let candidate = hex::decode(candidate).map_err(|_| SignatureMismatch)?;
+
+            if candidate.len() == 32
+                && signature == CtOutput::new(*GenericArray::from_slice(&candidate))
+            {
+                Ok(signature)
+            } else {
+                Err(SignatureMismatch)
+            }
+        }
+    }
+
     #[derive(Debug, Default)]
     struct JustHash(sha2::Sha256);
 
@@ -403,4 +647,82 @@ mod tests {
         let body = test::call_service(&app, req).await;
         assert_eq!(body.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
+
+    #[actix_web::test]
+    async fn passthrough_stream() {
+        let app = test::init_service(App::new().route(
+            "/",
+            web::post().to(|mut body: RequestSignatureStream<HeaderHash>| async move {
+                let signature = body.signature();
+
+                // echo body back, which is aborted if the signature is invalid
+                let chunks = body.collect::<Vec<_>>().await;
+                let failed = chunks.iter().any(Result::is_err);
+
+                match signature.await {
+                    Some(sig) => {
+                        assert!(!failed);
+                        sig.into_bytes().to_vec()
+                    }
+                    None => {
+                        assert!(failed);
+                        b"invalid".to_vec()
+                    }
+                }
+            }),
+        ))
+        .await;
+
+        const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+        let req = test::TestRequest::post()
+            .insert_header(("x-sig", ABC_SHA256))
+            .set_payload("abc")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            body,
+            hex!("ba7816bf 8f01cfea 414140de 5dae2223 b00361a3 96177a9c b410ff61 f20015ad")
+                .as_ref()
+        );
+
+        let req = test::TestRequest::post()
+            .insert_header(("x-sig", ABC_SHA256))
+            .set_payload("abd")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "invalid");
+
+        let req = test::TestRequest::post().set_payload("abc").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "invalid");
+    }
+
+    #[actix_web::test]
+    async fn passthrough_stream_aborts_forwarded_body() {
+        let app = test::init_service(App::new().route(
+            "/",
+            web::post().to(|body: RequestSignatureStream<HeaderHash>| async move {
+                actix_web::HttpResponse::Ok().streaming(body)
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::post()
+            .insert_header((
+                "x-sig",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ))
+            .set_payload("abc")
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            actix_web::body::to_bytes(res.into_body()).await.unwrap(),
+            "abc"
+        );
+
+        let req = test::TestRequest::post().set_payload("abc").to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(actix_web::body::to_bytes(res.into_body()).await.is_err());
+    }
 }
@@ -0,0 +1,48 @@
+//! Request trailer storage.
+//!
+//! See [`RequestTrailers`] docs.
+
+use std::{cell::RefCell, rc::Rc};
+
+use actix_web::{http::header::HeaderMap, HttpMessage as _, HttpRequest};
+
+/// Shared slot for a request's trailer fields.
+///
+/// Trailers are sent after the request body (e.g., in the final chunk of a chunked HTTP/1.1 body),
+/// so they only become available once the body has been read. Server integrations that receive
+/// trailers store them in this slot, which lives in the request's extensions, and extractors that
+/// need them read it after consuming the body. Handlers can use the
+/// [`Trailers`](crate::extract::Trailers) extractor to access them.
+///
+/// Actix Web's own HTTP/1.1 and HTTP/2 decoders do not currently surface request trailers. They
+/// are populated when an app is served through [`tower::into_tower()`](crate::tower::into_tower)
+/// (with the `tower` crate feature), or can be set by custom integrations using
+/// [`set()`](Self::set).
+///
+/// Cloning produces a handle to the same slot.
+#[derive(Debug, Clone, Default)]
+pub struct RequestTrailers {
+    trailers: Rc<RefCell<Option<HeaderMap>>>,
+}
+
+impl RequestTrailers {
+    /// Constructs a new, empty trailer slot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the trailer slot stored in the request's extensions, if any.
+    pub fn of(req: &HttpRequest) -> Option<Self> {
+        req.extensions().get::<Self>().cloned()
+    }
+
+    /// Stores the received trailer fields.
+    pub fn set(&self, trailers: HeaderMap) {
+        *self.trailers.borrow_mut() = Some(trailers);
+    }
+
+    /// Returns a copy of the received trailer fields, or `None` if none have been received.
+    pub fn get(&self) -> Option<HeaderMap> {
+        self.trailers.borrow().clone()
+    }
+}
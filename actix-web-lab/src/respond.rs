@@ -4,4 +4,19 @@
 pub use crate::cbor::Cbor;
 #[cfg(feature = "msgpack")]
 pub use crate::msgpack::{MessagePack, MessagePackNamed};
-pub use crate::{csv::Csv, display_stream::DisplayStream, ndjson::NdJson};
+#[cfg(feature = "xml")]
+pub use crate::respond_xml::Xml;
+#[cfg(feature = "yaml")]
+pub use crate::respond_yaml::Yaml;
+pub use crate::{
+    accepted::Accepted,
+    csv::Csv,
+    display_stream::DisplayStream,
+    download::Download,
+    json_stream::JsonStream,
+    ndjson::NdJson,
+    problem::Problem,
+    range_reader::{RangeReader, RangedObject},
+    respond_json::Json,
+    validation_problem::{FieldError, ValidationProblem},
+};
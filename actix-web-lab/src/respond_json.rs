@@ -0,0 +1,186 @@
+//! JSON responder.
+
+use actix_web::{
+    body::{BoxBody, MessageBody as _},
+    error::JsonPayloadError,
+    HttpRequest, HttpResponse, Responder,
+};
+use bytes::Bytes;
+use mime::Mime;
+use serde::Serialize;
+
+/// JSON responder with const-generic pretty-printing and customizable content type.
+///
+/// Serialization happens up-front so the response body is a single, sized chunk; the
+/// `Content-Length` header is therefore always set. If serialization fails, a
+/// `500 Internal Server Error` response is returned instead.
+///
+/// Use [`pretty()`](Self::pretty) (or set the `PRETTY` const generic parameter to `true`) to
+/// pretty-print the output. The content type
+/// defaults to `application/json` but can be overridden using
+/// [`with_content_type()`](Self::with_content_type), e.g., for `application/problem+json`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::respond::Json;
+/// use serde_json::json;
+///
+/// #[get("/")]
+/// async fn index() -> impl Responder {
+///     Json::new(json!({ "hello": "world" }))
+/// }
+///
+/// #[get("/pretty")]
+/// async fn pretty() -> impl Responder {
+///     Json::new(json!({ "hello": "world" })).pretty()
+/// }
+///
+/// #[get("/problem")]
+/// async fn problem() -> impl Responder {
+///     Json::new(json!({ "title": "Not Found", "status": 404 }))
+///         .with_content_type("application/problem+json".parse().unwrap())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Json<T, const PRETTY: bool = false> {
+    value: T,
+    content_type: Option<Mime>,
+}
+
+impl<T> Json<T> {
+    /// Constructs a new JSON responder.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            content_type: None,
+        }
+    }
+
+    /// Converts into a responder that pretty-prints its output.
+    pub fn pretty(self) -> Json<T, true> {
+        Json {
+            value: self.value,
+            content_type: self.content_type,
+        }
+    }
+}
+
+impl<T, const PRETTY: bool> Json<T, PRETTY> {
+    /// Sets the content type used for the response, overriding `application/json`.
+    pub fn with_content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, const PRETTY: bool> std::ops::Deref for Json<T, PRETTY> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T, const PRETTY: bool> std::ops::DerefMut for Json<T, PRETTY> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize, const PRETTY: bool> Responder for Json<T, PRETTY> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let body = if PRETTY {
+            serde_json::to_vec_pretty(&self.value)
+        } else {
+            serde_json::to_vec(&self.value)
+        };
+
+        match body {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(self.content_type.unwrap_or(mime::APPLICATION_JSON))
+                .body(Bytes::from(body).boxed()),
+
+            Err(err) => HttpResponse::from_error(JsonPayloadError::Serialize(err)),
+        }
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use actix_web::{
+        body::{self, BodySize, MessageBody as _},
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn compact() {
+        let req = TestRequest::default().to_http_request();
+        let res = Json::new(json!({ "abc": 123 })).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json",
+        );
+        assert_eq!(res.body().size(), BodySize::Sized(11));
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.unwrap(),
+            r#"{"abc":123}"#,
+        );
+    }
+
+    #[actix_web::test]
+    async fn pretty() {
+        let req = TestRequest::default().to_http_request();
+        let res = Json::new(json!({ "abc": 123 })).pretty().respond_to(&req);
+
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.unwrap(),
+            "{\n  \"abc\": 123\n}",
+        );
+    }
+
+    #[actix_web::test]
+    async fn content_type_override() {
+        let req = TestRequest::default().to_http_request();
+        let res = Json::new(json!({ "status": 404 }))
+            .with_content_type("application/problem+json".parse().unwrap())
+            .respond_to(&req);
+
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json",
+        );
+    }
+
+    #[actix_web::test]
+    async fn serialization_failure() {
+        // JSON map keys must be strings
+        let map = BTreeMap::from([(vec![1], 1)]);
+
+        let req = TestRequest::default().to_http_request();
+        let res = Json::new(map).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
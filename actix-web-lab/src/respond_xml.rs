@@ -0,0 +1,185 @@
+//! XML responder.
+
+use std::sync::LazyLock;
+
+use actix_web::{
+    body::{BoxBody, MessageBody as _},
+    HttpRequest, HttpResponse, Responder,
+};
+use bytes::Bytes;
+use mime::Mime;
+use serde::Serialize;
+
+use crate::extract::XmlPayloadError;
+
+static XML_MIME: LazyLock<Mime> = LazyLock::new(|| "application/xml".parse().unwrap());
+
+/// XML responder with customizable root element name and content type.
+///
+/// Serialization uses [`quick_xml::se`] and happens up-front so the response body is a single,
+/// sized chunk. If serialization fails, a `500 Internal Server Error` response is returned instead.
+///
+/// By default, the root element is named after the serialized type (e.g., a `struct Info` is
+/// serialized as `<Info>...</Info>`). Use [`with_root()`](Self::with_root) to choose a different
+/// name; this is required for values, like sequences and maps, that have no type name. The content
+/// type defaults to `application/xml` but can be overridden using
+/// [`with_content_type()`](Self::with_content_type).
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::respond::Xml;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// #[get("/")]
+/// async fn index() -> impl Responder {
+///     // responds with `<info><username>bob</username></info>`
+///     Xml::new(Info {
+///         username: "bob".to_owned(),
+///     })
+///     .with_root("info")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Xml<T> {
+    value: T,
+    root: Option<String>,
+    content_type: Option<Mime>,
+}
+
+impl<T> Xml<T> {
+    /// Constructs a new XML responder.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            root: None,
+            content_type: None,
+        }
+    }
+
+    /// Sets the name of the root element.
+    pub fn with_root(mut self, root: impl Into<String>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Sets the content type used for the response, overriding `application/xml`.
+    pub fn with_content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for Xml<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> std::ops::DerefMut for Xml<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize> Responder for Xml<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let body = match &self.root {
+            Some(root) => quick_xml::se::to_string_with_root(root, &self.value),
+            None => quick_xml::se::to_string(&self.value),
+        };
+
+        match body {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(self.content_type.unwrap_or_else(|| XML_MIME.clone()))
+                .body(Bytes::from(body).boxed()),
+
+            Err(err) => HttpResponse::from_error(XmlPayloadError::Serialize(err)),
+        }
+    }
+}
+
+impl<T> From<T> for Xml<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Info {
+        #[serde(rename = "@id")]
+        id: u32,
+        username: String,
+    }
+
+    fn info() -> Info {
+        Info {
+            id: 4,
+            username: "bob".to_owned(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn type_name_root() {
+        let req = TestRequest::default().to_http_request();
+        let res = Xml::new(info()).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/xml",
+        );
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.unwrap(),
+            r#"<Info id="4"><username>bob</username></Info>"#,
+        );
+    }
+
+    #[actix_web::test]
+    async fn custom_root_and_content_type() {
+        let req = TestRequest::default().to_http_request();
+        let res = Xml::new(info())
+            .with_root("user")
+            .with_content_type(mime::TEXT_XML)
+            .respond_to(&req);
+
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "text/xml");
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.unwrap(),
+            r#"<user id="4"><username>bob</username></user>"#,
+        );
+    }
+
+    #[actix_web::test]
+    async fn serialization_failure() {
+        // sequences have no type name to use as the root element
+        let req = TestRequest::default().to_http_request();
+        let res = Xml::new(vec![1, 2]).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
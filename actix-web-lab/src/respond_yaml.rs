@@ -0,0 +1,103 @@
+//! YAML responder.
+
+use std::sync::LazyLock;
+
+use actix_web::{
+    body::{BoxBody, MessageBody as _},
+    HttpRequest, HttpResponse, Responder,
+};
+use bytes::Bytes;
+use mime::Mime;
+use serde::Serialize;
+
+use crate::extract::YamlPayloadError;
+
+static YAML_MIME: LazyLock<Mime> = LazyLock::new(|| "application/yaml".parse().unwrap());
+
+/// [YAML] responder.
+///
+/// Responds with an `application/yaml` body. If serialization fails, a
+/// `500 Internal Server Error` response is returned instead.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, Responder};
+/// use actix_web_lab::respond::Yaml;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     replicas: u32,
+/// }
+///
+/// #[get("/config")]
+/// async fn config() -> impl Responder {
+///     // responds with `replicas: 3`
+///     Yaml(Config { replicas: 3 })
+/// }
+/// ```
+///
+/// [YAML]: https://yaml.org/
+#[derive(Debug)]
+pub struct Yaml<T>(pub T);
+
+impl_more::impl_deref_and_mut!(<T> in Yaml<T> => T);
+
+impl<T: Serialize> Responder for Yaml<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        match serde_norway::to_string(&self.0) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(YAML_MIME.clone())
+                .body(Bytes::from(body).boxed()),
+
+            Err(err) => HttpResponse::from_error(YamlPayloadError::Serialize(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use actix_web::{
+        body,
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn serializes_value() {
+        let req = TestRequest::default().to_http_request();
+        let res = Yaml(BTreeMap::from([("replicas", 3)])).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/yaml",
+        );
+        assert_eq!(
+            body::to_bytes(res.into_body()).await.unwrap(),
+            "replicas: 3\n"
+        );
+    }
+
+    #[actix_web::test]
+    async fn serialization_failure() {
+        struct Unserializable;
+
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("nope"))
+            }
+        }
+
+        let req = TestRequest::default().to_http_request();
+        let res = Yaml(Unserializable).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,778 @@
+//! In-memory response cache middleware.
+//!
+//! See [`ResponseCache`] docs.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::{self, BodySize, BoxBody, EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error,
+    http::{
+        header::{self, Header as _, HeaderMap, TryIntoHeaderValue as _},
+        Method, StatusCode,
+    },
+    web::Bytes,
+    Error, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    header::{Age, CacheDirective, CacheStatus, CacheStatusEntry, ForwardReason},
+    BoxError,
+};
+
+/// Cache name used in `Cache-Status` headers.
+const CACHE_NAME: &str = "actix-web-lab";
+
+/// Middleware that caches `GET` responses in memory and serves stale entries while revalidating.
+///
+/// A response is stored when all of the following are true:
+/// - the request method is `GET`;
+/// - the request does not have a `Cache-Control` header with a `no-store` directive;
+/// - the request does not have an `Authorization` header, unless the response's `Cache-Control`
+///   header has a `public`, `s-maxage`, or `must-revalidate` directive;
+/// - the response status is `200 OK`;
+/// - the response has a `Cache-Control` header with a `s-maxage` or `max-age` directive and without
+///   `no-store`, `no-cache`, or `private` directives;
+/// - the response does not have `Set-Cookie` or `Vary` headers;
+/// - the response body has a known size no larger than the [maximum body size].
+///
+/// Requests with a `Cache-Control` header containing `no-cache` or `no-store` directives are always
+/// forwarded to the wrapped service.
+///
+/// Entries are keyed by request host and URI. While an entry is fresh, it is served without calling
+/// the wrapped service. Once it becomes stale, it will still be served immediately for the number
+/// of seconds given in the response's `stale-while-revalidate=N` directive while the request is
+/// forwarded to the wrapped service in a background task to refresh the entry. Only one refresh
+/// per entry is in flight at a time; other requests for the entry continue to receive the stale
+/// response. Entries that are stale beyond this window are treated as misses.
+///
+/// Responses include a [`CacheStatus`] header. Responses served from the cache also include an
+/// [`Age`] header.
+///
+/// The cache is shared between clones of this middleware. To share it between workers, construct it
+/// outside the `HttpServer::new` closure.
+///
+/// # Examples
+/// ```
+/// # use actix_web::App;
+/// use actix_web_lab::middleware::ResponseCache;
+///
+/// let cache = ResponseCache::new().max_entries(10_000);
+///
+/// # let _ = move || {
+/// App::new().wrap(cache.clone())
+/// # };
+/// ```
+///
+/// [maximum body size]: Self::max_body_size
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    store: Arc<Mutex<CacheStore>>,
+    max_body_size: usize,
+}
+
+impl ResponseCache {
+    /// Constructs new response cache middleware with default limits.
+    ///
+    /// Up to 1024 entries are stored, each with a body of up to 1MiB.
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(Mutex::new(CacheStore {
+                entries: HashMap::new(),
+                max_entries: 1024,
+            })),
+            max_body_size: 1024 * 1024,
+        }
+    }
+
+    /// Sets the maximum number of stored entries.
+    ///
+    /// When the cache is full, the least recently stored entry is evicted.
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        self.store.lock().unwrap().max_entries = max_entries;
+        self
+    }
+
+    /// Sets the maximum body size of stored responses, in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseCache
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Transform = ResponseCacheMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ResponseCacheMiddleware {
+            service: Rc::new(service),
+            store: Arc::clone(&self.store),
+            max_body_size: self.max_body_size,
+        }))
+    }
+}
+
+/// Middleware service implementation for [`ResponseCache`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct ResponseCacheMiddleware<S> {
+    service: Rc<S>,
+    store: Arc<Mutex<CacheStore>>,
+    max_body_size: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseCacheMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let store = Arc::clone(&self.store);
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            if req.method() != Method::GET {
+                return Ok(service.call(req).await?.map_into_left_body());
+            }
+
+            let key = cache_key(&req);
+            let now = Instant::now();
+
+            let req_directives = cache_directives(req.headers());
+            let no_store = req_directives.contains(&CacheDirective::NoStore);
+            let no_cache = req_directives.contains(&CacheDirective::NoCache);
+            let authorized = req.headers().contains_key(header::AUTHORIZATION);
+
+            let lookup = if no_store || no_cache {
+                Lookup::Bypass
+            } else {
+                store.lock().unwrap().lookup(&key, now)
+            };
+
+            let reason = match lookup {
+                Lookup::Fresh(res) => return Ok(req.into_response(res).map_into_right_body()),
+
+                Lookup::Stale { res, revalidate } => {
+                    let (http_req, payload) = req.into_parts();
+
+                    if revalidate {
+                        let req = ServiceRequest::from_parts(http_req.clone(), payload);
+                        actix_web::rt::spawn(revalidate_entry(
+                            service,
+                            req,
+                            key,
+                            store,
+                            max_body_size,
+                            authorized,
+                        ));
+                    }
+
+                    return Ok(ServiceResponse::new(http_req, res).map_into_right_body());
+                }
+
+                Lookup::Bypass => ForwardReason::Request,
+                Lookup::Expired => ForwardReason::Stale,
+                Lookup::Miss => ForwardReason::UriMiss,
+            };
+
+            let res = service.call(req).await?;
+
+            let entry = if no_store {
+                None
+            } else {
+                CacheEntry::prepare(&res, max_body_size, now, authorized)
+            };
+
+            let Some(entry) = entry else {
+                let mut res = res.map_into_left_body();
+                insert_cache_status(
+                    res.headers_mut(),
+                    CacheStatusEntry::forward(CACHE_NAME, reason),
+                );
+                return Ok(res);
+            };
+
+            let (res, bytes) = buffer_response(res).await?;
+            store.lock().unwrap().insert(key, entry.with_body(bytes));
+
+            let mut res = res.map_into_right_body();
+            insert_cache_status(
+                res.headers_mut(),
+                CacheStatusEntry::forward(CACHE_NAME, reason)
+                    .fwd_status(StatusCode::OK.as_u16())
+                    .stored(),
+            );
+            Ok(res)
+        })
+    }
+}
+
+/// Forwards request to the wrapped service and updates cache entry with the response.
+///
+/// Stale entries are kept if the response cannot be stored.
+async fn revalidate_entry<S, B>(
+    service: Rc<S>,
+    req: ServiceRequest,
+    key: String,
+    store: Arc<Mutex<CacheStore>>,
+    max_body_size: usize,
+    authorized: bool,
+) where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    let now = Instant::now();
+
+    let entry = match service.call(req).await {
+        Ok(res) => match CacheEntry::prepare(&res, max_body_size, now, authorized) {
+            Some(entry) => buffer_response(res)
+                .await
+                .ok()
+                .map(|(_res, bytes)| entry.with_body(bytes)),
+            None => None,
+        },
+        Err(_) => None,
+    };
+
+    let mut store = store.lock().unwrap();
+
+    match entry {
+        Some(entry) => store.insert(key, entry),
+        None => {
+            if let Some(entry) = store.entries.get_mut(&key) {
+                entry.revalidating = false;
+            }
+        }
+    }
+}
+
+/// Collects response body so it can be both stored and sent.
+async fn buffer_response<B: MessageBody>(
+    res: ServiceResponse<B>,
+) -> Result<(ServiceResponse<BoxBody>, Bytes), Error> {
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(|err| error::ErrorInternalServerError(Into::<BoxError>::into(err)))?;
+
+    let res = res.set_body(bytes.clone()).map_into_boxed_body();
+    Ok((ServiceResponse::new(req, res), bytes))
+}
+
+/// Returns cache key for request, consisting of its host and URI path and query.
+fn cache_key(req: &ServiceRequest) -> String {
+    let host = req
+        .uri()
+        .authority()
+        .map(|authority| authority.as_str())
+        .or_else(|| req.headers().get(header::HOST)?.to_str().ok())
+        .unwrap_or_default();
+
+    let path = req
+        .uri()
+        .path_and_query()
+        .map_or(req.path(), |path| path.as_str());
+
+    format!("{host}{path}")
+}
+
+fn insert_cache_status(headers: &mut HeaderMap, entry: CacheStatusEntry) {
+    if let Ok(value) = CacheStatus(vec![entry]).try_into_value() {
+        headers.insert(CacheStatus::name(), value);
+    }
+}
+
+struct CacheStore {
+    entries: HashMap<String, CacheEntry>,
+    max_entries: usize,
+}
+
+enum Lookup {
+    Fresh(HttpResponse),
+    Stale { res: HttpResponse, revalidate: bool },
+    Bypass,
+    Expired,
+    Miss,
+}
+
+impl CacheStore {
+    fn lookup(&mut self, key: &str, now: Instant) -> Lookup {
+        let Some(entry) = self.entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+
+        let age = now.saturating_duration_since(entry.stored_at);
+
+        if age < entry.fresh_for {
+            Lookup::Fresh(entry.to_response(age))
+        } else if age < entry.fresh_for + entry.stale_for {
+            // only the first request to find the entry stale triggers revalidation
+            let revalidate = !entry.revalidating;
+            entry.revalidating = true;
+
+            Lookup::Stale {
+                res: entry.to_response(age),
+                revalidate,
+            }
+        } else {
+            self.entries.remove(key);
+            Lookup::Expired
+        }
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(key, entry);
+    }
+}
+
+struct CacheEntry {
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+    fresh_for: Duration,
+    stale_for: Duration,
+    revalidating: bool,
+}
+
+impl CacheEntry {
+    /// Returns entry (without body) if response can be stored.
+    ///
+    /// Responses to `authorized` requests are only stored if they are explicitly allowed in shared
+    /// caches.
+    fn prepare<B: MessageBody>(
+        res: &ServiceResponse<B>,
+        max_body_size: usize,
+        now: Instant,
+        authorized: bool,
+    ) -> Option<Self> {
+        if res.status() != StatusCode::OK
+            || res.headers().contains_key(header::SET_COOKIE)
+            || res.headers().contains_key(header::VARY)
+        {
+            return None;
+        }
+
+        if authorized
+            && !cache_directives(res.headers()).iter().any(|directive| {
+                matches!(
+                    directive,
+                    CacheDirective::Public
+                        | CacheDirective::SMaxAge(_)
+                        | CacheDirective::MustRevalidate
+                )
+            })
+        {
+            return None;
+        }
+
+        match res.response().body().size() {
+            BodySize::Sized(size) if size <= max_body_size as u64 => {}
+            _ => return None,
+        }
+
+        let (fresh_for, stale_for) = freshness(res.headers())?;
+
+        Some(Self {
+            headers: res.headers().clone(),
+            body: Bytes::new(),
+            stored_at: now,
+            fresh_for,
+            stale_for,
+            revalidating: false,
+        })
+    }
+
+    fn with_body(mut self, body: Bytes) -> Self {
+        self.body = body;
+        self
+    }
+
+    fn to_response(&self, age: Duration) -> HttpResponse {
+        let mut res =
+            HttpResponse::with_body(StatusCode::OK, self.body.clone()).map_into_boxed_body();
+        *res.headers_mut() = self.headers.clone();
+
+        // negative TTL indicates that a stale response was served
+        let ttl = self.fresh_for.as_secs() as i64 - age.as_secs() as i64;
+
+        let headers = res.headers_mut();
+        headers.insert(Age::name(), Age::from(age).try_into_value().unwrap());
+        insert_cache_status(headers, CacheStatusEntry::hit(CACHE_NAME).ttl(ttl));
+
+        res
+    }
+}
+
+/// Returns directives from `Cache-Control` header, or none if it is malformed.
+fn cache_directives(headers: &HeaderMap) -> Vec<CacheDirective> {
+    header::from_comma_delimited(headers.get_all(header::CACHE_CONTROL)).unwrap_or_default()
+}
+
+/// Returns freshness lifetime and stale-while-revalidate window from `Cache-Control` header.
+fn freshness(headers: &HeaderMap) -> Option<(Duration, Duration)> {
+    let directives = cache_directives(headers);
+
+    let mut max_age = None;
+    let mut s_maxage = None;
+    let mut stale_for = 0;
+
+    for directive in directives {
+        match directive {
+            CacheDirective::NoStore | CacheDirective::NoCache | CacheDirective::Private => {
+                return None
+            }
+            CacheDirective::MaxAge(secs) => max_age = Some(secs),
+            CacheDirective::SMaxAge(secs) => s_maxage = Some(secs),
+
+            // argument form is parsed as an extension directive
+            CacheDirective::Extension(name, Some(secs)) if name == "stale-while-revalidate" => {
+                stale_for = secs.parse().unwrap_or(0);
+            }
+
+            _ => {}
+        }
+    }
+
+    let fresh_for = s_maxage.or(max_age)?;
+
+    Some((
+        Duration::from_secs(fresh_for.into()),
+        Duration::from_secs(stale_for),
+    ))
+}
+
+impl fmt::Debug for CacheStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheStore")
+            .field("entries", &self.entries.len())
+            .field("max_entries", &self.max_entries)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use actix_web::{
+        http::header::HeaderValue,
+        test::{call_and_read_body, call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+    use crate::header::CacheControl;
+
+    async fn counting_app(
+        cache_control: &'static str,
+        delay: Duration,
+    ) -> (
+        Rc<Cell<u32>>,
+        impl Service<actix_http::Request, Response = ServiceResponse<impl MessageBody>, Error = Error>,
+    ) {
+        let calls = Rc::new(Cell::new(0));
+
+        let app = init_service(
+            App::new()
+                .wrap(ResponseCache::new())
+                .default_service(web::to({
+                    let calls = Rc::clone(&calls);
+
+                    move || {
+                        let calls = Rc::clone(&calls);
+
+                        async move {
+                            calls.set(calls.get() + 1);
+                            let body = calls.get().to_string();
+
+                            actix_web::rt::time::sleep(delay).await;
+
+                            HttpResponse::Ok()
+                                .insert_header((header::CACHE_CONTROL, cache_control))
+                                .body(body)
+                        }
+                    }
+                })),
+        )
+        .await;
+
+        (calls, app)
+    }
+
+    fn cache_status(res: &ServiceResponse<impl MessageBody>) -> &HeaderValue {
+        res.headers().get("cache-status").unwrap()
+    }
+
+    #[actix_web::test]
+    async fn serves_fresh_entries() {
+        let (calls, app) = counting_app("max-age=60", Duration::ZERO).await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(
+            cache_status(&res),
+            "actix-web-lab; fwd=uri-miss; fwd-status=200; stored"
+        );
+        assert_eq!(read_body(res).await, "1");
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(cache_status(&res), "actix-web-lab; hit; ttl=60");
+        assert_eq!(res.headers().get(header::AGE).unwrap(), "0");
+        assert_eq!(read_body(res).await, "1");
+
+        // different URI is a different entry
+        let req = TestRequest::default().uri("/?page=2").to_request();
+        assert_eq!(call_and_read_body(&app, req).await, "2");
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_web::test]
+    async fn serves_stale_while_revalidating() {
+        let (calls, app) =
+            counting_app("max-age=0, stale-while-revalidate=60", Duration::ZERO).await;
+
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "1");
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(cache_status(&res), "actix-web-lab; hit; ttl=0");
+        assert_eq!(read_body(res).await, "1");
+
+        // let background revalidation complete
+        actix_web::rt::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(calls.get(), 2);
+
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "2");
+    }
+
+    #[actix_web::test]
+    async fn single_flight_revalidation() {
+        let (calls, app) = counting_app(
+            "max-age=0, stale-while-revalidate=60",
+            Duration::from_millis(50),
+        )
+        .await;
+
+        call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(calls.get(), 1);
+
+        for _ in 0..3 {
+            let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+            assert_eq!(body, "1");
+        }
+
+        actix_web::rt::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(calls.get(), 2);
+
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "2");
+    }
+
+    #[actix_web::test]
+    async fn expired_entries_are_misses() {
+        let (calls, app) = counting_app("max-age=0", Duration::ZERO).await;
+
+        call_and_read_body(&app, TestRequest::default().to_request()).await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(
+            cache_status(&res),
+            "actix-web-lab; fwd=stale; fwd-status=200; stored"
+        );
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_web::test]
+    async fn uncacheable_responses() {
+        for cache_control in ["no-store, max-age=60", "private, max-age=60", "public"] {
+            let (calls, app) = counting_app(cache_control, Duration::ZERO).await;
+
+            for _ in 0..2 {
+                let res = call_service(&app, TestRequest::default().to_request()).await;
+                assert_eq!(cache_status(&res), "actix-web-lab; fwd=uri-miss");
+            }
+
+            assert_eq!(calls.get(), 2, "{cache_control}");
+        }
+
+        let (calls, app) = counting_app("max-age=60", Duration::ZERO).await;
+
+        for _ in 0..2 {
+            let req = TestRequest::post().to_request();
+            let res = call_service(&app, req).await;
+            assert!(!res.headers().contains_key("cache-status"));
+        }
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_web::test]
+    async fn entries_are_keyed_by_host() {
+        let (calls, app) = counting_app("max-age=60", Duration::ZERO).await;
+
+        for (host, body) in [("a.example", "1"), ("b.example", "2"), ("a.example", "1")] {
+            let req = TestRequest::default()
+                .insert_header((header::HOST, host))
+                .to_request();
+            assert_eq!(call_and_read_body(&app, req).await, body);
+        }
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_web::test]
+    async fn authorized_requests() {
+        let (calls, app) = counting_app("max-age=60", Duration::ZERO).await;
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer secret"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(cache_status(&res), "actix-web-lab; fwd=uri-miss");
+        assert_eq!(read_body(res).await, "1");
+
+        // response to authorized request is not served to requests without credentials
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "2");
+        assert_eq!(calls.get(), 2);
+
+        for cache_control in [
+            "public, max-age=60",
+            "s-maxage=60",
+            "max-age=60, must-revalidate",
+        ] {
+            let (calls, app) = counting_app(cache_control, Duration::ZERO).await;
+
+            let req = TestRequest::default()
+                .insert_header((header::AUTHORIZATION, "Bearer secret"))
+                .to_request();
+            call_and_read_body(&app, req).await;
+
+            let res = call_service(&app, TestRequest::default().to_request()).await;
+            assert!(cache_status(&res).to_str().unwrap().contains("hit"));
+            assert_eq!(calls.get(), 1, "{cache_control}");
+        }
+    }
+
+    #[actix_web::test]
+    async fn request_directives() {
+        let (calls, app) = counting_app("max-age=60", Duration::ZERO).await;
+
+        call_and_read_body(&app, TestRequest::default().to_request()).await;
+
+        // no-cache bypasses the stored entry but still stores the new response
+        let req = TestRequest::default()
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            cache_status(&res),
+            "actix-web-lab; fwd=request; fwd-status=200; stored"
+        );
+        assert_eq!(read_body(res).await, "2");
+
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "2");
+
+        // no-store bypasses the stored entry and does not replace it
+        let req = TestRequest::default()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(cache_status(&res), "actix-web-lab; fwd=request");
+        assert_eq!(read_body(res).await, "3");
+
+        let body = call_and_read_body(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body, "2");
+
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn freshness_directives() {
+        let mut headers = HeaderMap::new();
+        let cc = CacheControl(vec![
+            CacheDirective::MaxAge(10),
+            CacheDirective::SMaxAge(20),
+            CacheDirective::Extension("stale-while-revalidate".to_owned(), Some("30".to_owned())),
+        ]);
+        headers.insert(header::CACHE_CONTROL, cc.to_string().parse().unwrap());
+
+        assert_eq!(
+            freshness(&headers),
+            Some((Duration::from_secs(20), Duration::from_secs(30))),
+        );
+    }
+
+    #[test]
+    fn evicts_oldest_entry() {
+        let store = ResponseCache::new().max_entries(2).store;
+        let mut store = store.lock().unwrap();
+        let now = Instant::now();
+
+        for (i, key) in ["a", "b", "c"].into_iter().enumerate() {
+            let entry = CacheEntry {
+                headers: HeaderMap::new(),
+                body: Bytes::new(),
+                stored_at: now + Duration::from_secs(i as u64),
+                fresh_for: Duration::from_secs(60),
+                stale_for: Duration::ZERO,
+                revalidating: false,
+            };
+
+            store.insert(key.to_owned(), entry);
+        }
+
+        assert_eq!(store.entries.len(), 2);
+        assert!(!store.entries.contains_key("a"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Request-scoped random number generator.
+//!
+//! See [`Rng`] docs.
+
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use actix_utils::future::{ok, Ready};
+use actix_web::{
+    dev::Payload, http::header::HeaderName, FromRequest, HttpMessage as _, HttpRequest,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng as _};
+use sha2::{Digest as _, Sha256};
+
+/// Configuration for the [`Rng`] extractor.
+///
+/// Register using [`App::app_data()`](actix_web::App::app_data). When no `RngConfig` is registered,
+/// each request's generator is seeded from OS entropy.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header::HeaderName, App};
+/// use actix_web_lab::extract::RngConfig;
+///
+/// // in integration tests only
+/// App::new().app_data(RngConfig::new().seed_header(HeaderName::from_static("x-test-seed")))
+/// # ;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RngConfig {
+    seed_header: Option<HeaderName>,
+}
+
+impl RngConfig {
+    /// Constructs a new configuration that seeds generators from OS entropy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds generators from the value of the request header `name`, when it is present.
+    ///
+    /// Any header value is accepted; the same value always produces the same sequence. Requests
+    /// without the header are still seeded from OS entropy.
+    ///
+    /// This allows clients to choose the values generated for their requests, so it should only be
+    /// enabled in tests.
+    pub fn seed_header(mut self, name: HeaderName) -> Self {
+        self.seed_header = Some(name);
+        self
+    }
+}
+
+/// Request-scoped random number generator.
+///
+/// Each request gets its own generator, which is stored in the request's extensions so that all
+/// `Rng` extractions (and clones) for a request share the same sequence. By default it is seeded
+/// from OS entropy. In integration tests, register an [`RngConfig`] with a
+/// [seed header](RngConfig::seed_header) to make handlers that generate IDs or tokens
+/// deterministic without mocking any global state.
+///
+/// Implements [`rand::RngCore`], so all of [`rand::Rng`]'s methods are available.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, Responder};
+/// use actix_web_lab::extract::Rng;
+/// use rand::{distributions::Alphanumeric, Rng as _};
+///
+/// #[post("/tokens")]
+/// async fn new_token(mut rng: Rng) -> impl Responder {
+///     (&mut rng)
+///         .sample_iter(Alphanumeric)
+///         .take(32)
+///         .map(char::from)
+///         .collect::<String>()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    inner: Rc<RefCell<StdRng>>,
+}
+
+impl Rng {
+    fn for_request(req: &HttpRequest) -> Self {
+        let seed = req
+            .app_data::<RngConfig>()
+            .and_then(|config| config.seed_header.as_ref())
+            .and_then(|name| req.headers().get(name))
+            .map(|seed| Sha256::digest(seed.as_bytes()).into());
+
+        let rng = match seed {
+            Some(seed) => StdRng::from_seed(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Self {
+            inner: Rc::new(RefCell::new(rng)),
+        }
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.borrow_mut().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.borrow_mut().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.borrow_mut().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner.borrow_mut().try_fill_bytes(dest)
+    }
+}
+
+impl FromRequest for Rng {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        if let Some(rng) = req.extensions().get::<Rng>() {
+            return ok(rng.clone());
+        }
+
+        let rng = Self::for_request(req);
+        req.extensions_mut().insert(rng.clone());
+
+        ok(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_and_read_body, init_service, TestRequest},
+        web, App,
+    };
+    use rand::Rng as _;
+
+    use super::*;
+
+    const SEED_HEADER: HeaderName = HeaderName::from_static("x-test-seed");
+
+    async fn handler(mut rng1: Rng, mut rng2: Rng) -> String {
+        format!("{} {}", rng1.gen::<u64>(), rng2.gen::<u64>())
+    }
+
+    #[actix_web::test]
+    async fn seeded_from_header() {
+        let app = init_service(
+            App::new()
+                .app_data(RngConfig::new().seed_header(SEED_HEADER))
+                .default_service(web::to(handler)),
+        )
+        .await;
+
+        let req = || TestRequest::default().insert_header((SEED_HEADER, "42"));
+
+        let body1 = call_and_read_body(&app, req().to_request()).await;
+        let body2 = call_and_read_body(&app, req().to_request()).await;
+        assert_eq!(body1, body2);
+
+        // extractors share one sequence
+        let body = std::str::from_utf8(&body1).unwrap();
+        let (first, second) = body.split_once(' ').unwrap();
+        assert_ne!(first, second);
+
+        let req = TestRequest::default().insert_header((SEED_HEADER, "43"));
+        let body3 = call_and_read_body(&app, req.to_request()).await;
+        assert_ne!(body1, body3);
+    }
+
+    #[actix_web::test]
+    async fn entropy_by_default() {
+        let app = init_service(App::new().default_service(web::to(handler))).await;
+
+        // seed header is ignored without config
+        let req = || TestRequest::default().insert_header((SEED_HEADER, "42"));
+
+        let body1 = call_and_read_body(&app, req().to_request()).await;
+        let body2 = call_and_read_body(&app, req().to_request()).await;
+        assert_ne!(body1, body2);
+    }
+}
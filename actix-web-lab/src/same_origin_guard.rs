@@ -0,0 +1,133 @@
+//! Same-origin route guard.
+//!
+//! See [`same_origin`] docs.
+
+use actix_web::{
+    guard::{Guard, GuardContext},
+    http::header,
+};
+
+use crate::header::{Origin, Referer};
+
+/// Creates a guard that matches requests whose `Origin` (or, if absent, `Referer`) header has the
+/// same host and port as the request.
+///
+/// The request's host is taken from the `Host` header, falling back to the request URI's
+/// authority. Since guards cannot tell whether a request arrived over TLS, the scheme is not
+/// compared and forwarding headers are not consulted; use [`Origin::same_origin()`] with the
+/// request's [`ConnectionInfo`] in a handler or middleware when those are needed.
+///
+/// Requests that send neither header, or send the opaque `null` origin, are not matched. Combine
+/// with [`safe_methods()`] to allow read-only requests through without origin information.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_lab::guard;
+///
+/// App::new().route(
+///     "/transfer",
+///     web::post()
+///         .guard(guard::same_origin())
+///         .to(|| async { HttpResponse::Ok() }),
+/// );
+/// ```
+///
+/// [`ConnectionInfo`]: actix_web::dev::ConnectionInfo
+/// [`safe_methods()`]: crate::guard::safe_methods
+pub fn same_origin() -> impl Guard {
+    SameOriginGuard
+}
+
+#[derive(Debug)]
+struct SameOriginGuard;
+
+impl Guard for SameOriginGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        let origin = match ctx.header::<Origin>() {
+            Some(origin) => origin,
+            None => match ctx.header::<Referer>() {
+                Some(referer) => referer.origin(),
+                None => return false,
+            },
+        };
+
+        let Some(origin_authority) = origin.authority() else {
+            return false;
+        };
+
+        let head = ctx.head();
+
+        let host = head
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .or_else(|| head.uri.authority().map(|authority| authority.as_str()));
+
+        host.is_some_and(|host| host.eq_ignore_ascii_case(&origin_authority))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn check(req: TestRequest) -> bool {
+        let req = req.to_srv_request();
+        same_origin().check(&req.guard_ctx())
+    }
+
+    fn req() -> TestRequest {
+        TestRequest::post().insert_header((header::HOST, "example.com"))
+    }
+
+    #[test]
+    fn origin_header() {
+        assert!(check(
+            req().insert_header((header::ORIGIN, "https://example.com"))
+        ));
+        assert!(check(
+            req().insert_header((header::ORIGIN, "http://EXAMPLE.com"))
+        ));
+        assert!(!check(
+            req().insert_header((header::ORIGIN, "https://example.com:8443"))
+        ));
+        assert!(!check(
+            req().insert_header((header::ORIGIN, "https://evil.com"))
+        ));
+        assert!(!check(req().insert_header((header::ORIGIN, "null"))));
+        assert!(!check(req().insert_header((header::ORIGIN, "garbage"))));
+    }
+
+    #[test]
+    fn referer_fallback() {
+        assert!(check(
+            req().insert_header((header::REFERER, "https://example.com/form"))
+        ));
+        assert!(!check(
+            req().insert_header((header::REFERER, "https://evil.com/form"))
+        ));
+
+        // origin takes precedence
+        assert!(!check(
+            req()
+                .insert_header((header::ORIGIN, "https://evil.com"))
+                .insert_header((header::REFERER, "https://example.com/form"))
+        ));
+    }
+
+    #[test]
+    fn missing_headers() {
+        assert!(!check(req()));
+    }
+
+    #[test]
+    fn non_default_port() {
+        let req = TestRequest::post()
+            .insert_header((header::HOST, "localhost:8080"))
+            .insert_header((header::ORIGIN, "http://localhost:8080"));
+        assert!(check(req));
+    }
+}
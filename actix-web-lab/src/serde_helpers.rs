@@ -0,0 +1,307 @@
+//! Serde helpers for common timestamp and duration formats.
+//!
+//! See [`extract::serde_helpers`](crate::extract::serde_helpers) docs.
+
+use std::{fmt, time::SystemTime};
+
+use serde::{de, Deserialize as _, Deserializer, Serializer};
+
+/// Deserializes an optional string and parses it using `parse`.
+fn deserialize_opt_str<'de, D, T, E>(
+    deserializer: D,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    E: fmt::Display,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|val| parse(&val).map_err(de::Error::custom))
+        .transpose()
+}
+
+/// RFC 3339 timestamps (e.g., `2024-02-01T12:00:00Z`) as [`SystemTime`]s.
+///
+/// Only UTC timestamps (those with a `Z` suffix) are accepted. Fractional seconds are accepted and
+/// serialized when present.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use actix_web_lab::extract::{serde_helpers, Query};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     #[serde(with = "serde_helpers::rfc3339")]
+///     since: SystemTime,
+/// }
+///
+/// let params = Query::<Params>::from_query("since=1970-01-02T00:00:00Z").unwrap();
+/// assert_eq!(
+///     params.since,
+///     SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)
+/// );
+/// ```
+pub mod rfc3339 {
+    use super::*;
+
+    /// Serializes a [`SystemTime`] as an RFC 3339 timestamp.
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_rfc3339(*time))
+    }
+
+    /// Deserializes a [`SystemTime`] from an RFC 3339 timestamp.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let val = String::deserialize(deserializer)?;
+        humantime::parse_rfc3339(&val).map_err(de::Error::custom)
+    }
+
+    /// Optional RFC 3339 timestamps.
+    pub mod option {
+        use super::*;
+
+        /// Serializes an optional [`SystemTime`] as an RFC 3339 timestamp.
+        pub fn serialize<S: Serializer>(
+            time: &Option<SystemTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes an optional [`SystemTime`] from an RFC 3339 timestamp.
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SystemTime>, D::Error> {
+            deserialize_opt_str(deserializer, humantime::parse_rfc3339)
+        }
+    }
+}
+
+/// Unix timestamps, in whole seconds, as [`SystemTime`]s.
+///
+/// Negative values represent times before the Unix epoch. Sub-second precision is truncated when
+/// serializing.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use actix_web_lab::extract::{serde_helpers, Query};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     #[serde(with = "serde_helpers::unix_ts")]
+///     before: SystemTime,
+/// }
+///
+/// let params = Query::<Params>::from_query("before=86400").unwrap();
+/// assert_eq!(
+///     params.before,
+///     SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)
+/// );
+/// ```
+pub mod unix_ts {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn to_secs(time: &SystemTime) -> i64 {
+        match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(dur) => dur.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        }
+    }
+
+    fn from_secs<E: de::Error>(secs: i64) -> Result<SystemTime, E> {
+        let dur = Duration::from_secs(secs.unsigned_abs());
+
+        let time = if secs >= 0 {
+            SystemTime::UNIX_EPOCH.checked_add(dur)
+        } else {
+            SystemTime::UNIX_EPOCH.checked_sub(dur)
+        };
+
+        time.ok_or_else(|| E::custom("Unix timestamp out of range"))
+    }
+
+    /// Serializes a [`SystemTime`] as a Unix timestamp.
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(to_secs(time))
+    }
+
+    /// Deserializes a [`SystemTime`] from a Unix timestamp.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        from_secs(i64::deserialize(deserializer)?)
+    }
+
+    /// Optional Unix timestamps.
+    pub mod option {
+        use super::*;
+
+        /// Serializes an optional [`SystemTime`] as a Unix timestamp.
+        pub fn serialize<S: Serializer>(
+            time: &Option<SystemTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes an optional [`SystemTime`] from a Unix timestamp.
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SystemTime>, D::Error> {
+            Option::<i64>::deserialize(deserializer)?
+                .map(from_secs)
+                .transpose()
+        }
+    }
+}
+
+/// Human-friendly durations (e.g., `15days 2min 2s` or `500ms`) as [`Duration`]s.
+///
+/// See [`humantime::parse_duration()`] for accepted units.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web_lab::extract::{serde_helpers, Query};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     #[serde(with = "serde_helpers::humantime_duration")]
+///     timeout: Duration,
+/// }
+///
+/// let params = Query::<Params>::from_query("timeout=1m+30s").unwrap();
+/// assert_eq!(params.timeout, Duration::from_secs(90));
+/// ```
+///
+/// [`Duration`]: std::time::Duration
+pub mod humantime_duration {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Serializes a [`Duration`] in human-friendly format.
+    pub fn serialize<S: Serializer>(dur: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_duration(*dur))
+    }
+
+    /// Deserializes a [`Duration`] from human-friendly format.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let val = String::deserialize(deserializer)?;
+        humantime::parse_duration(&val).map_err(de::Error::custom)
+    }
+
+    /// Optional human-friendly durations.
+    pub mod option {
+        use super::*;
+
+        /// Serializes an optional [`Duration`] in human-friendly format.
+        pub fn serialize<S: Serializer>(
+            dur: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match dur {
+                Some(dur) => super::serialize(dur, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes an optional [`Duration`] from human-friendly format.
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            deserialize_opt_str(deserializer, humantime::parse_duration)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::extract::Query;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Params {
+        #[serde(with = "rfc3339")]
+        since: SystemTime,
+
+        #[serde(default, with = "unix_ts::option")]
+        until: Option<SystemTime>,
+
+        #[serde(with = "humantime_duration")]
+        timeout: Duration,
+
+        #[serde(default, with = "rfc3339::option")]
+        after: Option<SystemTime>,
+    }
+
+    fn secs(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn query() {
+        let params = Query::<Params>::from_query(
+            "since=2024-02-01T12:00:00.5Z&until=1706788800&timeout=1h+2m",
+        )
+        .unwrap()
+        .into_inner();
+
+        assert_eq!(
+            params,
+            Params {
+                since: secs(1_706_788_800) + Duration::from_millis(500),
+                until: Some(secs(1_706_788_800)),
+                timeout: Duration::from_secs(3_720),
+                after: None,
+            }
+        );
+
+        let err = Query::<Params>::from_query("since=yesterday&timeout=1s").unwrap_err();
+        assert_eq!(err.path().to_string(), "since");
+
+        let err =
+            Query::<Params>::from_query("since=2024-02-01T12:00:00Z&timeout=soon").unwrap_err();
+        assert_eq!(err.path().to_string(), "timeout");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let params = Params {
+            since: secs(86_400),
+            until: Some(SystemTime::UNIX_EPOCH - Duration::from_secs(60)),
+            timeout: Duration::from_millis(1_500),
+            after: None,
+        };
+
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "since": "1970-01-02T00:00:00Z",
+                "until": -60,
+                "timeout": "1s 500ms",
+                "after": null,
+            })
+        );
+
+        assert_eq!(serde_json::from_value::<Params>(json).unwrap(), params);
+    }
+}
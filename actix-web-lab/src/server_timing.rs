@@ -0,0 +1,319 @@
+//! Server-Timing typed header.
+//!
+//! See [`ServerTiming`] docs.
+
+use std::{fmt, str, time::Duration};
+
+use actix_http::{
+    error::ParseError,
+    header::{Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+
+use crate::cache_status::{fmt_string, parse_string, split_unquoted};
+
+/// The `Server-Timing` header, defined in the [Server Timing] specification.
+///
+/// Communicates one or more [metrics](ServerTimingMetric) about the request-response cycle to the
+/// user agent, where they are shown in browser devtools. Use the
+/// [`ServerTiming`](crate::middleware::ServerTiming) middleware to emit this header automatically.
+///
+/// Note that browsers only expose these metrics to cross-origin scripts if the response also
+/// includes an appropriate `Timing-Allow-Origin` header.
+///
+/// # ABNF
+/// ```text
+/// Server-Timing             = #server-timing-metric
+/// server-timing-metric      = metric-name *( OWS ";" OWS server-timing-param )
+/// metric-name               = token
+/// server-timing-param       = server-timing-param-name OWS "=" OWS server-timing-param-value
+/// server-timing-param-name  = token
+/// server-timing-param-value = token / quoted-string
+/// ```
+///
+/// # Sample Values
+/// - `miss, db;dur=53, app;dur=47.2`
+/// - `cache;desc="Cache Read";dur=23.2`
+/// - `total;dur=123.4`
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::{ServerTiming, ServerTimingMetric};
+///
+/// let mut res = HttpResponse::Ok();
+/// res.insert_header(ServerTiming(vec![
+///     ServerTimingMetric::new("cache").description("Cache Read"),
+///     ServerTimingMetric::new("db").duration(Duration::from_millis(53)),
+/// ]));
+/// ```
+///
+/// [Server Timing]: https://www.w3.org/TR/server-timing/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerTiming(pub Vec<ServerTimingMetric>);
+
+impl_more::forward_deref_and_mut!(ServerTiming => [ServerTimingMetric]);
+
+impl fmt::Display for ServerTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut metrics = self.0.iter();
+
+        let Some(metric) = metrics.next() else {
+            return Ok(());
+        };
+
+        write!(f, "{metric}")?;
+
+        for metric in metrics {
+            write!(f, ", {metric}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryIntoHeaderValue for ServerTiming {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for ServerTiming {
+    fn name() -> HeaderName {
+        HeaderName::from_static("server-timing")
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        let mut metrics = Vec::new();
+
+        for hdr in msg.headers().get_all(Self::name()) {
+            let hdr = hdr.to_str().map_err(|_| ParseError::Header)?;
+
+            for metric in split_unquoted(hdr, ',') {
+                if metric.trim().is_empty() {
+                    continue;
+                }
+
+                metrics.push(metric.parse()?);
+            }
+        }
+
+        if metrics.is_empty() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(ServerTiming(metrics))
+    }
+}
+
+/// A single metric in a [`ServerTiming`] header.
+///
+/// Durations are transmitted in milliseconds and are parsed with nanosecond precision. Unrecognized
+/// parameters are ignored when parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServerTimingMetric {
+    /// Name of the metric.
+    pub name: String,
+
+    /// The `dur` parameter, containing the duration of the metric.
+    pub duration: Option<Duration>,
+
+    /// The `desc` parameter, containing a human-readable description of the metric.
+    pub description: Option<String>,
+}
+
+impl ServerTimingMetric {
+    /// Constructs a metric with the given name and no parameters.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid token.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(
+            is_token(&name),
+            "invalid Server-Timing metric name: {name:?}"
+        );
+
+        Self {
+            name,
+            duration: None,
+            description: None,
+        }
+    }
+
+    /// Sets the `dur` parameter.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Sets the `desc` parameter.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl fmt::Display for ServerTimingMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+
+        if let Some(duration) = self.duration {
+            write!(f, ";dur={}", duration.as_nanos() as f64 / 1_000_000.0)?;
+        }
+
+        if let Some(description) = &self.description {
+            f.write_str(";desc=")?;
+
+            if is_token(description) {
+                f.write_str(description)?;
+            } else {
+                fmt_string(f, description)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl str::FromStr for ServerTimingMetric {
+    type Err = ParseError;
+
+    fn from_str(metric: &str) -> Result<Self, Self::Err> {
+        let mut parts = split_unquoted(metric, ';').into_iter();
+
+        let name = parts.next().unwrap_or_default().trim();
+
+        if !is_token(name) {
+            return Err(ParseError::Header);
+        }
+
+        let mut metric = Self::new(name);
+
+        for param in parts {
+            let (key, value) = param.split_once('=').ok_or(ParseError::Header)?;
+            let value = value.trim();
+
+            let value = if value.starts_with('"') {
+                parse_string(value)?
+            } else if is_token(value) {
+                value.to_owned()
+            } else {
+                return Err(ParseError::Header);
+            };
+
+            // only the first occurrence of each parameter is used, per spec
+            match key.trim().to_ascii_lowercase().as_str() {
+                "dur" if metric.duration.is_none() => {
+                    metric.duration = Some(parse_millis(&value)?);
+                }
+                "desc" if metric.description.is_none() => {
+                    metric.description = Some(value);
+                }
+                "" => return Err(ParseError::Header),
+                _ => {}
+            }
+        }
+
+        Ok(metric)
+    }
+}
+
+/// Parses a non-negative, fractional number of milliseconds.
+fn parse_millis(val: &str) -> Result<Duration, ParseError> {
+    let millis = val.parse::<f64>().map_err(|_| ParseError::Header)?;
+
+    if !millis.is_finite() || millis < 0.0 {
+        return Err(ParseError::Header);
+    }
+
+    Ok(Duration::from_nanos((millis * 1_000_000.0).round() as u64))
+}
+
+/// Returns true if `val` is an HTTP token.
+fn is_token(val: &str) -> bool {
+    !val.is_empty()
+        && val
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::assert_parse_eq;
+
+    #[test]
+    fn fractional_durations() {
+        let metric = ServerTimingMetric::new("app").duration(Duration::from_micros(47_250));
+        assert_eq!(metric.to_string(), "app;dur=47.25");
+        assert_eq!(
+            metric.to_string().parse::<ServerTimingMetric>().unwrap(),
+            metric
+        );
+
+        let metric = ServerTimingMetric::new("app").duration(Duration::from_nanos(1));
+        assert_eq!(metric.to_string(), "app;dur=0.000001");
+    }
+
+    #[test]
+    fn first_param_wins_and_unknown_params() {
+        assert_parse_eq(
+            [r#"db; dur=53; dur=99; foo="a, b"; DESC=query"#],
+            ServerTiming(vec![ServerTimingMetric::new("db")
+                .duration(Duration::from_millis(53))
+                .description("query")]),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_name() {
+        ServerTimingMetric::new("db query");
+    }
+}
+
+#[cfg(test)]
+crate::test::header_test_module! {
+    ServerTiming,
+    tests_parse_and_format {
+        header_round_trip_test!(no_headers, [b""; 0], None);
+        header_round_trip_test!(empty_header, [b""; 1], None);
+        header_round_trip_test!(bad_name, [b"db query;dur=1"], None);
+        header_round_trip_test!(bad_duration, [b"db;dur=soon"], None);
+        header_round_trip_test!(negative_duration, [b"db;dur=-1"], None);
+        header_round_trip_test!(missing_param_value, [b"db;dur"], None);
+
+        header_round_trip_test!(
+            name_only,
+            [b"miss"],
+            Some(ServerTiming(vec![ServerTimingMetric::new("miss")]))
+        );
+
+        header_round_trip_test!(
+            multiple_metrics,
+            [b"cache;dur=23.2;desc=\"Cache Read\", db;dur=53, app;dur=47.2"],
+            Some(ServerTiming(vec![
+                ServerTimingMetric::new("cache")
+                    .duration(Duration::from_micros(23_200))
+                    .description("Cache Read"),
+                ServerTimingMetric::new("db").duration(Duration::from_millis(53)),
+                ServerTimingMetric::new("app").duration(Duration::from_micros(47_200)),
+            ]))
+        );
+
+        header_round_trip_test!(
+            multiple_headers,
+            [&b"db;dur=53"[..], &b"total;desc=all"[..]],
+            Some(ServerTiming(vec![
+                ServerTimingMetric::new("db").duration(Duration::from_millis(53)),
+                ServerTimingMetric::new("total").description("all"),
+            ]))
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    any::{type_name, TypeId},
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use actix_utils::future::{err, ok, Ready};
+use actix_web::{dev::Payload, error, web, Error, FromRequest, HttpRequest};
+use tracing::debug;
+
+use crate::extract::LocalData;
+
+/// Application data that is shared by all workers.
+///
+/// Like [`web::Data`], this wraps the value in an `Arc` and can be used as an extractor. Unlike
+/// `web::Data`, it is intended to be constructed exactly once, before the `HttpServer::new` closure,
+/// and cloned into each worker's app. Constructing it inside the closure (a common mistake) gives
+/// each worker its own instance, so state is unexpectedly not shared between workers.
+///
+/// # Per-Worker Construction
+/// Constructing a `SharedData` inside the `HttpServer::new` closure compiles and works, but every
+/// worker ends up with its own `T`. Counters, caches, and other mutable state then appear to reset
+/// or diverge depending on which worker handles a request. Always construct it before calling
+/// `HttpServer::new` and move clones into the closure, as in the example below.
+///
+/// # Diagnostics
+/// When extraction fails, a debug log explains whether the data was not registered at all or was
+/// registered using a different wrapper, such as `web::Data<T>` or [`LocalData<T>`].
+///
+/// When extraction succeeds, the first instance extracted on each worker thread is compared with
+/// the first instance extracted on any thread. If they differ, a debug log explains that the data
+/// was likely constructed per-worker. Separate instances constructed on purpose (e.g., one per
+/// test app on different test threads) also trigger this log, but are otherwise unaffected.
+///
+/// # Examples
+/// ```no_run
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use actix_web::{App, HttpServer, Responder};
+/// use actix_web_lab::web::SharedData;
+///
+/// async fn count(counter: SharedData<AtomicUsize>) -> impl Responder {
+///     counter.fetch_add(1, Ordering::Relaxed).to_string()
+/// }
+///
+/// # async fn run() -> std::io::Result<()> {
+/// // construct once, outside of the app factory closure
+/// let counter = SharedData::new(AtomicUsize::new(0));
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .app_data(counter.clone())
+///         .default_service(actix_web::web::to(count))
+/// })
+/// .bind(("127.0.0.1", 8080))?
+/// .run()
+/// .await
+/// # }
+/// ```
+#[doc(alias = "state")]
+#[derive(Debug)]
+pub struct SharedData<T: ?Sized>(Arc<T>);
+
+impl<T> SharedData<T> {
+    /// Constructs a new `SharedData` instance.
+    pub fn new(item: T) -> SharedData<T> {
+        SharedData(Arc::new(item))
+    }
+}
+
+impl<T: ?Sized> SharedData<T> {
+    /// Returns reference to inner `T`.
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwraps into the inner `Arc`.
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Deref for SharedData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> Clone for SharedData<T> {
+    fn clone(&self) -> SharedData<T> {
+        SharedData(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for SharedData<T> {
+    fn from(arc: Arc<T>) -> Self {
+        SharedData(arc)
+    }
+}
+
+impl<T: ?Sized + 'static> FromRequest for SharedData<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let handler = req.match_name().unwrap_or_else(|| req.path());
+
+        if let Some(st) = req.app_data::<SharedData<T>>() {
+            if is_per_worker_instance(st) {
+                debug!(
+                    "Extracted `SharedData<{}>` for `{handler}` handler, but a different instance \
+                    was extracted on another worker. The data was likely constructed inside the \
+                    `HttpServer::new` closure, so it is not shared between workers; construct it \
+                    once outside of the closure and pass clones of it to `App::app_data()`.",
+                    type_name::<T>(),
+                );
+            }
+
+            return ok(st.clone());
+        }
+
+        let registered_as = if req.app_data::<web::Data<T>>().is_some() {
+            Some("web::Data")
+        } else if req.app_data::<LocalData<T>>().is_some() {
+            Some("LocalData")
+        } else {
+            None
+        };
+
+        match registered_as {
+            Some(wrapper) => debug!(
+                "Failed to extract `SharedData<{}>` for `{handler}` handler. The data is \
+                registered as `{wrapper}<{}>`; wrap it with `SharedData::new()` instead, outside \
+                of the `HttpServer::new` closure.",
+                type_name::<T>(),
+                type_name::<T>(),
+            ),
+
+            None => debug!(
+                "Failed to extract `SharedData<{}>` for `{handler}` handler. The data is not \
+                registered. For the SharedData extractor to work correctly, wrap the data with \
+                `SharedData::new()` outside of the `HttpServer::new` closure and pass clones of \
+                it to `App::app_data()`. Ensure that types align in both the set and retrieve \
+                calls.",
+                type_name::<T>(),
+            ),
+        }
+
+        err(error::ErrorInternalServerError(
+            "Requested application data is not configured correctly. \
+            View/enable debug logs for more details.",
+        ))
+    }
+}
+
+/// Instance addresses of the first `SharedData<T>` extracted on any thread, keyed by `T`.
+static FIRST_INSTANCES: LazyLock<Mutex<HashMap<TypeId, usize>>> = LazyLock::new(Mutex::default);
+
+thread_local! {
+    /// Types whose `SharedData` has already been checked on this thread.
+    static CHECKED_TYPES: RefCell<HashSet<TypeId>> = RefCell::default();
+}
+
+/// Returns true if `data` is the first instance extracted on this thread and differs from the first
+/// instance extracted on any thread.
+///
+/// Only the first extraction of each type on each thread takes the global lock.
+fn is_per_worker_instance<T: ?Sized + 'static>(data: &SharedData<T>) -> bool {
+    let type_id = TypeId::of::<T>();
+
+    if !CHECKED_TYPES.with(|checked| checked.borrow_mut().insert(type_id)) {
+        return false;
+    }
+
+    let addr = Arc::as_ptr(&data.0).cast::<()>() as usize;
+
+    let mut first_instances = FIRST_INSTANCES.lock().unwrap();
+    *first_instances.entry(type_id).or_insert(addr) != addr
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        App, HttpResponse,
+    };
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn extracts_shared_instance() {
+        let data = SharedData::new(42u8);
+
+        let srv = init_service(App::new().app_data(data.clone()).default_service(web::to(
+            |data: SharedData<u8>| async move { HttpResponse::Ok().body(data.to_string()) },
+        )))
+        .await;
+
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // app holds a clone of the same instance
+        assert_eq!(Arc::strong_count(&data.0), 2);
+    }
+
+    #[actix_web::test]
+    async fn missing_or_wrong_wrapper() {
+        for app in [
+            App::new(),
+            App::new().app_data(web::Data::new(1u16)),
+            App::new().app_data(LocalData::new(1u16)),
+        ] {
+            let srv =
+                init_service(app.default_service(web::to(|_: SharedData<u16>| HttpResponse::Ok())))
+                    .await;
+
+            let res = call_service(&srv, TestRequest::default().to_request()).await;
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    #[test]
+    fn per_worker_instances() {
+        struct Counter;
+
+        let shared = SharedData::new(Counter);
+
+        // same instance on every worker
+        for _ in 0..2 {
+            let data = shared.clone();
+            let per_worker = std::thread::spawn(move || is_per_worker_instance(&data));
+            assert!(!per_worker.join().unwrap());
+        }
+
+        // instance constructed on a worker
+        let per_worker = std::thread::spawn(|| {
+            let data = SharedData::new(Counter);
+            let first = is_per_worker_instance(&data);
+
+            // only the first extraction on each thread is checked
+            let second = is_per_worker_instance(&data);
+
+            (first, second)
+        });
+        assert_eq!(per_worker.join().unwrap(), (true, false));
+    }
+
+    #[test]
+    fn from_dyn_arc() {
+        trait Num {
+            fn num(&self) -> u32;
+        }
+
+        impl Num for u32 {
+            fn num(&self) -> u32 {
+                *self
+            }
+        }
+
+        let data = SharedData::<dyn Num>::from(Arc::new(7u32) as Arc<dyn Num>);
+        assert_eq!(data.num(), 7);
+        assert_eq!(data.get_ref().num(), 7);
+    }
+}
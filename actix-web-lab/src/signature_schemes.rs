@@ -0,0 +1,11 @@
+//! Ready-made [`RequestSignatureScheme`] implementations.
+//!
+//! [`RequestSignatureScheme`]: crate::extract::RequestSignatureScheme
+
+pub use crate::{
+    aws_sigv4::{AwsSigV4, AwsSigV4Config, AwsSigV4Error},
+    webhook_signature::{
+        GitHubWebhook, GitHubWebhookConfig, SlackWebhook, SlackWebhookConfig, StripeWebhook,
+        StripeWebhookConfig, WebhookSignatureError,
+    },
+};
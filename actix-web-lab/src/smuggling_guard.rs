@@ -0,0 +1,193 @@
+//! Request smuggling hardening middleware.
+//!
+//! See [`SmugglingGuard`] docs.
+
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderMap},
+    HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::debug;
+
+/// Middleware that rejects requests with ambiguous message framing.
+///
+/// Responds with `400 Bad Request` when a request:
+/// - has both `Content-Length` and `Transfer-Encoding` headers;
+/// - has more than one `Content-Length` value (either repeated headers or a comma-separated list)
+///   or a `Content-Length` value that is not a plain decimal number.
+///
+/// Header line folding (obs-fold) is not checked since Actix Web's HTTP/1 parser already rejects
+/// it, and header values containing CR or LF characters cannot be constructed.
+///
+/// Actix Web's own HTTP/1 parser already handles framing safely. This middleware is a
+/// defense-in-depth layer for deployments behind proxies that may be more lenient when
+/// interpreting these headers, where disagreements between the proxy and the app about where a
+/// request ends can lead to request smuggling.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::SmugglingGuard;
+///
+/// App::new().wrap(SmugglingGuard::default())
+/// # ;
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct SmugglingGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for SmugglingGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Transform = SmugglingGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SmugglingGuardMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`SmugglingGuard`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct SmugglingGuardMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SmugglingGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(reason) = ambiguity(req.headers()) {
+            debug!("rejecting request with ambiguous framing: {reason}");
+
+            let res = HttpResponse::BadRequest().finish().map_into_right_body();
+            return Box::pin(async move { Ok(req.into_response(res)) });
+        }
+
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) })
+    }
+}
+
+/// Returns a description of why the request framing is ambiguous, if it is.
+fn ambiguity(headers: &HeaderMap) -> Option<&'static str> {
+    let mut content_lengths = headers.get_all(header::CONTENT_LENGTH);
+
+    if let Some(content_length) = content_lengths.next() {
+        if headers.contains_key(header::TRANSFER_ENCODING) {
+            return Some("both Content-Length and Transfer-Encoding are present");
+        }
+
+        if content_lengths.next().is_some() {
+            return Some("multiple Content-Length headers");
+        }
+
+        let value = content_length.as_bytes();
+        if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+            return Some("Content-Length is not a single decimal value");
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{header::HeaderValue, StatusCode},
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        for &(name, value) in pairs {
+            headers.append(
+                header::HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+
+        headers
+    }
+
+    #[test]
+    fn detects_ambiguity() {
+        assert!(ambiguity(&headers(&[])).is_none());
+        assert!(ambiguity(&headers(&[("content-length", "42")])).is_none());
+        assert!(ambiguity(&headers(&[("transfer-encoding", "chunked")])).is_none());
+
+        assert!(ambiguity(&headers(&[
+            ("content-length", "42"),
+            ("transfer-encoding", "chunked"),
+        ]))
+        .is_some());
+        assert!(ambiguity(&headers(&[
+            ("content-length", "42"),
+            ("content-length", "42")
+        ]))
+        .is_some());
+        assert!(ambiguity(&headers(&[("content-length", "42, 42")])).is_some());
+        assert!(ambiguity(&headers(&[("content-length", "+42")])).is_some());
+        assert!(ambiguity(&headers(&[("content-length", "")])).is_some());
+    }
+
+    #[actix_web::test]
+    async fn rejects_ambiguous_requests() {
+        let app = init_service(
+            App::new()
+                .wrap(SmugglingGuard::default())
+                .default_service(web::to(|| async { "ok" })),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_LENGTH, "3"))
+            .set_payload("abc")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_LENGTH, "3"))
+            .insert_header((header::TRANSFER_ENCODING, "chunked"))
+            .set_payload("abc")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let req = TestRequest::default()
+            .append_header((header::CONTENT_LENGTH, "3"))
+            .append_header((header::CONTENT_LENGTH, "4"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
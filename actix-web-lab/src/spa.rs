@@ -1,12 +1,54 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    fmt,
+    fs::File,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
 
-use actix_files::{Files, NamedFile};
-use actix_service::fn_service;
-use actix_web::dev::{HttpServiceFactory, ResourceDef, ServiceRequest, ServiceResponse};
+use actix_files::{Files, FilesService, NamedFile};
+use actix_service::{fn_service, Service, ServiceFactory};
+use actix_web::{
+    dev::{self, AppService, HttpServiceFactory, ResourceDef, ServiceRequest, ServiceResponse},
+    http::{
+        header::{
+            self, AcceptEncoding, ContentEncoding, ContentType, Encoding, Header as _, HeaderValue,
+        },
+        Method,
+    },
+    web::{self, Bytes},
+    HttpRequest, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
 use tracing::trace;
 
+use crate::header::CacheControl;
+
 /// Single Page App (SPA) service builder.
 ///
+/// # Pre-Compressed Assets
+/// When [`precompressed()`](Self::precompressed) is enabled, static resources that have `.br` or
+/// `.gz` siblings on disk (e.g., `app.js.br` next to `app.js`) are served from those siblings if
+/// the request's `Accept-Encoding` header allows it. The `Content-Type` is still derived from the
+/// original file name and a `Vary: Accept-Encoding` header is added to static resource responses.
+///
+/// # Caching
+/// Static resources and the index file are served with `ETag` and `Last-Modified` headers. Their
+/// `Cache-Control` headers can be configured separately, which is useful when static resources
+/// have content-hashed file names that can be cached indefinitely but the index file must always
+/// be revalidated.
+///
+/// # Runtime Configuration
+/// Runtime configuration can be injected into the index file using [`index_env()`](Self::index_env)
+/// or [`index_env_with()`](Self::index_env_with), so that the same build of an SPA can be deployed
+/// to different environments. The configuration is serialized as JSON and assigned to
+/// `window.__ENV__` in a `<script>` element inserted before the index file's closing `</head>` tag.
+///
+/// The index file is read once, when it is first served, and cached for the lifetime of the
+/// worker. Responses for index files with injected configuration do not have `ETag` or
+/// `Last-Modified` headers.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -23,11 +65,72 @@ use tracing::trace;
 ///     )
 /// # ;
 /// ```
+///
+/// ```no_run
+/// # use actix_web::App;
+/// use actix_web_lab::{
+///     header::{CacheControl, CacheDirective},
+///     web::spa,
+/// };
+///
+/// App::new().service(
+///     spa()
+///         .index_file("./dist/index.html")
+///         .static_resources_mount("/assets")
+///         .static_resources_location("./dist/assets")
+///         .precompressed(true)
+///         .static_resources_cache_control(CacheControl(vec![
+///             CacheDirective::Public,
+///             CacheDirective::MaxAge(31_536_000),
+///             CacheDirective::Immutable,
+///         ]))
+///         .index_cache_control(CacheControl(vec![CacheDirective::NoCache]))
+///         .finish(),
+/// )
+/// # ;
+/// ```
+///
+/// ```no_run
+/// # use actix_web::App;
+/// use actix_web_lab::web::spa;
+/// use serde_json::json;
+///
+/// let api_url = std::env::var("API_URL").unwrap();
+///
+/// App::new().service(
+///     spa()
+///         .index_file("./dist/index.html")
+///         .index_env(json!({ "apiUrl": api_url }))
+///         .finish(),
+/// )
+/// # ;
+/// ```
 #[derive(Debug, Clone)]
 pub struct Spa {
     index_file: Cow<'static, str>,
     static_resources_mount: Cow<'static, str>,
     static_resources_location: Cow<'static, str>,
+    precompressed: bool,
+    static_resources_cache_control: Option<CacheControl>,
+    index_cache_control: Option<CacheControl>,
+    index_env: Option<IndexEnv>,
+}
+
+type EnvBuilder = Rc<dyn Fn(&HttpRequest) -> serde_json::Value>;
+
+#[derive(Clone)]
+enum IndexEnv {
+    Static(Rc<str>),
+    Dynamic(EnvBuilder),
+}
+
+impl fmt::Debug for IndexEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Static(env) => f.debug_tuple("Static").field(env).finish(),
+            Self::Dynamic(_) => f.debug_tuple("Dynamic").field(&"<function>").finish(),
+        }
+    }
 }
 
 impl Spa {
@@ -68,42 +171,171 @@ impl Spa {
         self
     }
 
+    /// Enables serving pre-compressed `.br` and `.gz` siblings of static resources.
+    ///
+    /// The default is `false`.
+    pub fn precompressed(mut self, precompressed: bool) -> Self {
+        self.precompressed = precompressed;
+        self
+    }
+
+    /// Sets the `Cache-Control` header sent with static resources.
+    ///
+    /// By default, no `Cache-Control` header is sent.
+    pub fn static_resources_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.static_resources_cache_control = Some(cache_control);
+        self
+    }
+
+    /// Sets the `Cache-Control` header sent with the index file.
+    ///
+    /// By default, no `Cache-Control` header is sent.
+    pub fn index_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.index_cache_control = Some(cache_control);
+        self
+    }
+
+    /// Injects `env` into the index file as runtime configuration.
+    ///
+    /// See [type docs](Self#runtime-configuration) for details.
+    pub fn index_env(mut self, env: serde_json::Value) -> Self {
+        self.index_env = Some(IndexEnv::Static(Rc::from(env_script(&env))));
+        self
+    }
+
+    /// Injects runtime configuration, built for each request by `env`, into the index file.
+    ///
+    /// See [type docs](Self#runtime-configuration) for details.
+    pub fn index_env_with(
+        mut self,
+        env: impl Fn(&HttpRequest) -> serde_json::Value + 'static,
+    ) -> Self {
+        self.index_env = Some(IndexEnv::Dynamic(Rc::new(env)));
+        self
+    }
+
     /// Constructs the service for use in a `.service()` call.
     pub fn finish(self) -> impl HttpServiceFactory {
-        let index_file = self.index_file.into_owned();
+        let index = Rc::new(IndexFile {
+            path: self.index_file.into_owned(),
+            cache_control: self.index_cache_control,
+            env: self.index_env,
+            template: OnceCell::new(),
+        });
         let static_resources_location = self.static_resources_location.into_owned();
         let static_resources_mount = self.static_resources_mount.into_owned();
 
         let files = {
-            let index_file = index_file.clone();
-            Files::new(&static_resources_mount, static_resources_location)
+            let index = Rc::clone(&index);
+            Files::new(&static_resources_mount, &static_resources_location)
                 // HACK: FilesService will try to read a directory listing unless index_file is provided
                 // FilesService will fail to load the index_file and will then call our default_handler
                 .index_file("extremely-unlikely-to-exist-!@$%^&*.txt")
-                .default_handler(move |req| serve_index(req, index_file.clone()))
+                .default_handler(move |req| serve_index(req, Rc::clone(&index)))
         };
 
-        SpaService { index_file, files }
+        SpaService {
+            index,
+            static_files: StaticFiles {
+                files,
+                mount: static_resources_mount.trim_end_matches('/').to_owned(),
+                location: PathBuf::from(static_resources_location),
+                precompressed: self.precompressed,
+                cache_control: self.static_resources_cache_control,
+            },
+        }
     }
 }
 
+#[derive(Debug)]
+struct IndexFile {
+    path: String,
+    cache_control: Option<CacheControl>,
+    env: Option<IndexEnv>,
+
+    /// Cached contents of the index file, only used when injecting configuration.
+    template: OnceCell<Bytes>,
+}
+
+impl IndexFile {
+    /// Returns contents of the index file, reading it on first use.
+    async fn template(&self) -> Result<Bytes, actix_web::Error> {
+        if let Some(template) = self.template.get() {
+            return Ok(template.clone());
+        }
+
+        let path = self.path.clone();
+        let template = Bytes::from(web::block(move || std::fs::read(path)).await??);
+
+        Ok(self.template.get_or_init(|| template).clone())
+    }
+
+    async fn response(&self, req: &HttpRequest) -> Result<HttpResponse, actix_web::Error> {
+        let script = match &self.env {
+            None => {
+                let file = NamedFile::open_async(&self.path).await?;
+                return Ok(file.into_response(req));
+            }
+
+            Some(IndexEnv::Static(script)) => Cow::Borrowed(&**script),
+            Some(IndexEnv::Dynamic(env)) => Cow::Owned(env_script(&env(req))),
+        };
+
+        let template = self.template().await?;
+
+        Ok(HttpResponse::Ok()
+            .insert_header(ContentType::html())
+            .body(inject_script(&template, &script)))
+    }
+}
+
+/// Builds a script element that assigns `env` to `window.__ENV__`.
+fn env_script(env: &serde_json::Value) -> String {
+    // `<` only appears in JSON strings, where it can be escaped to avoid closing the script element
+    let json = env.to_string().replace('<', "\\u003c");
+    format!("<script>window.__ENV__ = {json};</script>")
+}
+
+/// Inserts `script` before the closing `</head>` tag, or at the start if there isn't one.
+fn inject_script(template: &[u8], script: &str) -> Bytes {
+    let pos = template
+        .windows(7)
+        .position(|window| window.eq_ignore_ascii_case(b"</head>"))
+        .unwrap_or(0);
+
+    let mut html = Vec::with_capacity(template.len() + script.len());
+    html.extend_from_slice(&template[..pos]);
+    html.extend_from_slice(script.as_bytes());
+    html.extend_from_slice(&template[pos..]);
+    Bytes::from(html)
+}
+
+/// Marks responses for the index file so that static resource headers are not applied to them.
+#[derive(Debug, Clone, Copy)]
+struct ServedIndex;
+
 #[derive(Debug)]
 struct SpaService {
-    index_file: String,
-    files: Files,
+    index: Rc<IndexFile>,
+    static_files: StaticFiles,
 }
 
 impl HttpServiceFactory for SpaService {
-    fn register(self, config: &mut actix_web::dev::AppService) {
-        // let Files register its mount path as-is
-        self.files.register(config);
+    fn register(self, config: &mut AppService) {
+        // register static files with the same mount path that Files would use
+        let rdef = if config.is_root() {
+            ResourceDef::root_prefix(&self.static_files.mount)
+        } else {
+            ResourceDef::prefix(&self.static_files.mount)
+        };
+        config.register_service(rdef, None, self.static_files, None);
 
         // also define a root prefix handler directed towards our SPA index
         let rdef = ResourceDef::root_prefix("");
         config.register_service(
             rdef,
             None,
-            fn_service(move |req| serve_index(req, self.index_file.clone())),
+            fn_service(move |req| serve_index(req, Rc::clone(&self.index))),
             None,
         );
     }
@@ -111,21 +343,409 @@ impl HttpServiceFactory for SpaService {
 
 async fn serve_index(
     req: ServiceRequest,
-    index_file: String,
+    index: Rc<IndexFile>,
 ) -> Result<ServiceResponse, actix_web::Error> {
     trace!("serving default SPA page");
     let (req, _) = req.into_parts();
-    let file = NamedFile::open_async(&index_file).await?;
-    let res = file.into_response(&req);
+    let mut res = index.response(&req).await?;
+
+    if let Some(cache_control) = &index.cache_control {
+        res.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_str(&cache_control.to_string()).unwrap(),
+        );
+    }
+
+    res.extensions_mut().insert(ServedIndex);
+
     Ok(ServiceResponse::new(req, res))
 }
 
+/// Wraps [`Files`] to add pre-compressed sibling lookup and static resource headers.
+#[derive(Debug)]
+struct StaticFiles {
+    files: Files,
+    mount: String,
+    location: PathBuf,
+    precompressed: bool,
+    cache_control: Option<CacheControl>,
+}
+
+impl ServiceFactory<ServiceRequest> for StaticFiles {
+    type Response = ServiceResponse;
+    type Error = actix_web::Error;
+    type Config = ();
+    type Service = StaticFilesService;
+    type InitError = ();
+    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        let files = self.files.new_service(());
+        let location = self.location.clone();
+        let precompressed = self.precompressed;
+        let cache_control = self.cache_control.clone();
+
+        Box::pin(async move {
+            Ok(StaticFilesService {
+                files: files.await?,
+                location,
+                precompressed,
+                cache_control,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StaticFilesService {
+    files: FilesService,
+    location: PathBuf,
+    precompressed: bool,
+    cache_control: Option<CacheControl>,
+}
+
+impl Service<ServiceRequest> for StaticFilesService {
+    type Response = ServiceResponse;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    dev::always_ready!();
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let this = self.clone();
+
+        Box::pin(async move {
+            let compressed = if this.precompressed {
+                this.precompressed_file(&req).await
+            } else {
+                None
+            };
+
+            let mut res = match compressed {
+                Some(file) => {
+                    trace!("serving pre-compressed static resource");
+                    let (req, _) = req.into_parts();
+                    let res = file.into_response(&req);
+                    ServiceResponse::new(req, res)
+                }
+                None => this.files.call(req).await?,
+            };
+
+            if res.response().extensions().contains::<ServedIndex>() {
+                return Ok(res);
+            }
+
+            let headers = res.headers_mut();
+
+            if this.precompressed {
+                headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+            }
+
+            if let Some(cache_control) = this.cache_control {
+                headers.insert(
+                    header::CACHE_CONTROL,
+                    HeaderValue::from_str(&cache_control.to_string()).unwrap(),
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+impl StaticFilesService {
+    /// Opens the most preferred pre-compressed sibling of the requested file, if one exists.
+    async fn precompressed_file(&self, req: &ServiceRequest) -> Option<NamedFile> {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return None;
+        }
+
+        let path = self
+            .location
+            .join(sanitize_path(req.match_info().unprocessed())?);
+        let accept_encoding = AcceptEncoding::parse(req).ok()?;
+
+        // file system access is blocking
+        web::block(move || {
+            let file_name = path.file_name()?.to_str()?;
+
+            let candidates = [
+                (Encoding::brotli(), ContentEncoding::Brotli, "br"),
+                (Encoding::gzip(), ContentEncoding::Gzip, "gz"),
+            ]
+            .into_iter()
+            .map(|(enc, content_enc, ext)| {
+                let sibling = path.with_file_name(format!("{file_name}.{ext}"));
+                (enc, content_enc, sibling)
+            })
+            .filter(|(_, _, sibling)| sibling.is_file())
+            .collect::<Vec<_>>();
+
+            if candidates.is_empty() {
+                return None;
+            }
+
+            let identity = Encoding::identity();
+            let supported = candidates.iter().map(|(enc, _, _)| enc).chain([&identity]);
+            let chosen = accept_encoding.negotiate(supported)?;
+
+            let (_, content_enc, sibling) =
+                candidates.into_iter().find(|(enc, _, _)| *enc == chosen)?;
+
+            // content type and disposition are derived from the original file name
+            let file = File::open(sibling).ok()?;
+            let file = NamedFile::from_file(file, &path).ok()?;
+            Some(file.set_content_encoding(content_enc))
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+}
+
+/// Converts the unmatched part of a request path into a relative file system path.
+///
+/// This is deliberately conservative; paths which would require decoding or contain hidden or
+/// special segments are rejected and left for [`Files`] to handle.
+fn sanitize_path(path: &str) -> Option<PathBuf> {
+    let mut buf = PathBuf::new();
+
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        if segment.starts_with('.') || segment.contains(['%', '\\', ':', '*', '<', '>']) {
+            return None;
+        }
+
+        buf.push(segment);
+    }
+
+    buf.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then_some(buf)
+        .filter(|buf| buf != Path::new(""))
+}
+
 impl Default for Spa {
     fn default() -> Self {
         Self {
             index_file: Cow::Borrowed("./index.html"),
             static_resources_mount: Cow::Borrowed("/"),
             static_resources_location: Cow::Borrowed("./"),
+            precompressed: false,
+            static_resources_cache_control: None,
+            index_cache_control: None,
+            index_env: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use actix_web::{
+        body::to_bytes,
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        App,
+    };
+
+    use super::*;
+    use crate::header::CacheDirective;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("actix-web-lab-spa-{name}-{}", std::process::id(),));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("assets")).unwrap();
+
+        fs::write(dir.join("index.html"), "index").unwrap();
+        fs::write(dir.join("assets/app.js"), "plain").unwrap();
+        fs::write(dir.join("assets/app.js.br"), "brotli").unwrap();
+        fs::write(dir.join("assets/app.js.gz"), "gzip").unwrap();
+        fs::write(dir.join("assets/style.css"), "plain").unwrap();
+        fs::write(dir.join("assets/style.css.gz"), "gzip").unwrap();
+
+        dir
+    }
+
+    fn spa_in(dir: &Path) -> Spa {
+        Spa::default()
+            .index_file(dir.join("index.html").to_str().unwrap().to_owned())
+            .static_resources_mount("/assets")
+            .static_resources_location(dir.join("assets").to_str().unwrap().to_owned())
+    }
+
+    #[actix_web::test]
+    async fn precompressed() {
+        let dir = temp_dir("precompressed");
+        let app =
+            test::init_service(App::new().service(spa_in(&dir).precompressed(true).finish())).await;
+
+        for (path, accept, encoding, body) in [
+            ("/assets/app.js", "gzip, br", Some("br"), "brotli"),
+            ("/assets/app.js", "gzip", Some("gzip"), "gzip"),
+            ("/assets/app.js", "br;q=0.5, gzip", Some("gzip"), "gzip"),
+            ("/assets/app.js", "identity", None, "plain"),
+            ("/assets/style.css", "br, gzip", Some("gzip"), "gzip"),
+            ("/assets/style.css", "br", None, "plain"),
+        ] {
+            let req = TestRequest::with_uri(path)
+                .insert_header((header::ACCEPT_ENCODING, accept))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let headers = res.headers();
+            assert_eq!(
+                headers
+                    .get(header::CONTENT_ENCODING)
+                    .map(|enc| enc.to_str().unwrap()),
+                encoding,
+            );
+            assert_eq!(headers.get(header::VARY).unwrap(), "accept-encoding");
+            assert!(headers.contains_key(header::ETAG));
+
+            let content_type = headers.get(header::CONTENT_TYPE).unwrap().to_str().unwrap();
+            assert!(!content_type.starts_with("application/octet-stream"));
+
+            let res_body = to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(res_body, body);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn precompressed_disabled() {
+        let dir = temp_dir("disabled");
+        let app = test::init_service(App::new().service(spa_in(&dir).finish())).await;
+
+        let req = TestRequest::with_uri("/assets/app.js")
+            .insert_header((header::ACCEPT_ENCODING, "br"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!res.headers().contains_key(header::VARY));
+        assert!(!res.headers().contains_key(header::CACHE_CONTROL));
+        let res_body = to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(res_body, "plain");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn cache_control_policies() {
+        let dir = temp_dir("cache-control");
+        let app = test::init_service(
+            App::new().service(
+                spa_in(&dir)
+                    .precompressed(true)
+                    .static_resources_cache_control(CacheControl(vec![
+                        CacheDirective::MaxAge(31_536_000),
+                        CacheDirective::Immutable,
+                    ]))
+                    .index_cache_control(CacheControl(vec![CacheDirective::NoCache]))
+                    .finish(),
+            ),
+        )
+        .await;
+
+        for (path, cache_control, body) in [
+            ("/assets/app.js", "max-age=31536000, immutable", "plain"),
+            // missing static resources fall back to the index file
+            ("/assets/missing.js", "no-cache", "index"),
+            ("/some/client/route", "no-cache", "index"),
+        ] {
+            let req = TestRequest::with_uri(path).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(
+                res.headers().get(header::CACHE_CONTROL).unwrap(),
+                cache_control
+            );
+            assert!(res.headers().contains_key(header::ETAG));
+            let res_body = to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(res_body, body);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn index_env() {
+        let dir = temp_dir("index-env");
+        fs::write(
+            dir.join("index.html"),
+            "<html><head><title>app</title></HEAD><body></body></html>",
+        )
+        .unwrap();
+
+        let app = test::init_service(
+            App::new().service(
+                spa_in(&dir)
+                    .index_env(serde_json::json!({ "api": "</script>" }))
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/some/client/route").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8",
+        );
+        let res_body = to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            res_body,
+            "<html><head><title>app</title>\
+            <script>window.__ENV__ = {\"api\":\"\\u003c/script>\"};</script>\
+            </HEAD><body></body></html>",
+        );
+
+        // index file is cached after first use
+        fs::write(dir.join("index.html"), "changed").unwrap();
+        let req = TestRequest::with_uri("/assets/missing.js").to_request();
+        let res_body = test::call_and_read_body(&app, req).await;
+        assert!(res_body.starts_with(b"<html><head><title>app</title><script>"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn index_env_with() {
+        let dir = temp_dir("index-env-with");
+        let app = test::init_service(
+            App::new().service(
+                spa_in(&dir)
+                    .index_env_with(|req| serde_json::json!({ "path": req.path() }))
+                    .finish(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/route").to_request();
+        let res_body = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            res_body,
+            r#"<script>window.__ENV__ = {"path":"/route"};</script>index"#,
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sanitizes_paths() {
+        assert_eq!(sanitize_path("app.js"), Some(PathBuf::from("app.js")));
+        assert_eq!(
+            sanitize_path("js//app.js"),
+            Some(PathBuf::from("js/app.js"))
+        );
+        assert_eq!(sanitize_path(""), None);
+        assert_eq!(sanitize_path("../secret"), None);
+        assert_eq!(sanitize_path("js/.hidden"), None);
+        assert_eq!(sanitize_path("%2e%2e/secret"), None);
+        assert_eq!(sanitize_path("a\\b"), None);
+    }
+}
@@ -47,11 +47,12 @@ use std::{
 
 use actix_web::{
     body::{BodySize, BoxBody, MessageBody},
-    http::header::ContentEncoding,
+    http::header::{ContentEncoding, HeaderName, HeaderValue},
     HttpRequest, HttpResponse, Responder,
 };
 use bytes::{BufMut as _, Bytes, BytesMut};
 use bytestring::ByteString;
+use derive_more::{Display, Error};
 use futures_core::Stream;
 use pin_project_lite::pin_project;
 use serde::Serialize;
@@ -63,10 +64,22 @@ use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
     header::{CacheControl, CacheDirective},
-    util::InfallibleStream,
+    stream_metrics::StreamGuard,
+    util::{DisconnectReason, InfallibleStream, StreamMetrics},
     BoxError,
 };
 
+/// Name of the header used to disable response buffering in Nginx and compatible proxies.
+const X_ACCEL_BUFFERING: HeaderName = HeaderName::from_static("x-accel-buffering");
+
+#[cfg(feature = "derive")]
+pub use actix_web_lab_derive::IntoEvent;
+
+pub use crate::{
+    sse_broadcaster::{Broadcaster, ClientStream, LagPolicy},
+    sse_event_store::EventStore,
+};
+
 /// Server-sent events data message containing a `data` field and optional `id` and `event` fields.
 ///
 /// # Examples
@@ -121,6 +134,24 @@ impl Data {
         }
     }
 
+    /// Constructs a new SSE data message with just the `data` field, rejecting carriage returns.
+    ///
+    /// Line feeds in `data` are sent as multiple `data` lines and are reassembled by the client.
+    /// Carriage returns are also treated as line breaks by clients so [`new()`](Self::new) sends
+    /// them as line feeds; use this constructor to detect them instead.
+    ///
+    /// # Errors
+    /// Returns an error if `data` contains a carriage return.
+    pub fn try_new(data: impl Into<ByteString>) -> Result<Self, InvalidFieldError> {
+        let data = data.into();
+
+        if data.contains('\r') {
+            return Err(InvalidFieldError { field: "data" });
+        }
+
+        Ok(Self::new(data))
+    }
+
     /// Constructs a new SSE data message the `data` field set to `data` serialized as JSON.
     ///
     /// # Examples
@@ -148,25 +179,98 @@ impl Data {
     }
 
     /// Sets `id` field, returning a new data message.
+    ///
+    /// Line breaks would end the field early and allow injecting other fields, so they are
+    /// replaced with spaces. NUL characters, which cause clients to ignore the ID, are removed. Use
+    /// [`try_id()`](Self::try_id) to reject such values instead.
     pub fn id(mut self, id: impl Into<ByteString>) -> Self {
-        self.id = Some(id.into());
+        self.set_id(id);
         self
     }
 
+    /// Sets `id` field, returning a new data message.
+    ///
+    /// # Errors
+    /// Returns an error if `id` contains a line break or NUL character.
+    pub fn try_id(mut self, id: impl Into<ByteString>) -> Result<Self, InvalidFieldError> {
+        let id = id.into();
+
+        if id.contains(['\r', '\n', '\0']) {
+            return Err(InvalidFieldError { field: "id" });
+        }
+
+        self.id = Some(id);
+        Ok(self)
+    }
+
     /// Sets `id` field.
+    ///
+    /// Values are sanitized as described in [`id()`](Self::id).
     pub fn set_id(&mut self, id: impl Into<ByteString>) {
-        self.id = Some(id.into());
+        self.id = Some(sanitize_field(id.into(), true));
     }
 
     /// Sets `event` name field, returning a new data message.
+    ///
+    /// Line breaks would end the field early and allow injecting other fields, so they are
+    /// replaced with spaces. Use [`try_event()`](Self::try_event) to reject such values instead.
     pub fn event(mut self, event: impl Into<ByteString>) -> Self {
-        self.event = Some(event.into());
+        self.set_event(event);
         self
     }
 
+    /// Sets `event` name field, returning a new data message.
+    ///
+    /// # Errors
+    /// Returns an error if `event` contains a line break.
+    pub fn try_event(mut self, event: impl Into<ByteString>) -> Result<Self, InvalidFieldError> {
+        let event = event.into();
+
+        if event.contains(['\r', '\n']) {
+            return Err(InvalidFieldError { field: "event" });
+        }
+
+        self.event = Some(event);
+        Ok(self)
+    }
+
     /// Sets `event` name field.
+    ///
+    /// Values are sanitized as described in [`event()`](Self::event).
     pub fn set_event(&mut self, event: impl Into<ByteString>) {
-        self.event = Some(event.into());
+        self.event = Some(sanitize_field(event.into(), false));
+    }
+}
+
+/// Replaces line breaks with spaces and, if `strip_nul` is set, removes NUL characters.
+fn sanitize_field(val: ByteString, strip_nul: bool) -> ByteString {
+    let is_invalid = |ch: char| ch == '\r' || ch == '\n' || (strip_nul && ch == '\0');
+
+    if !val.contains(is_invalid) {
+        return val;
+    }
+
+    val.chars()
+        .filter(|&ch| !(strip_nul && ch == '\0'))
+        .map(|ch| if is_invalid(ch) { ' ' } else { ch })
+        .collect::<String>()
+        .into()
+}
+
+/// Error returned when an SSE field contains characters that cannot be represented.
+///
+/// See [`Data::try_new()`], [`Data::try_id()`], and [`Data::try_event()`].
+#[derive(Debug, Display, Error)]
+#[display("SSE `{field}` field contains invalid characters")]
+pub struct InvalidFieldError {
+    #[error(not(source))]
+    field: &'static str,
+}
+
+impl InvalidFieldError {
+    /// Returns the name of the invalid field.
+    pub fn field(&self) -> &'static str {
+        self.field
     }
 }
 
@@ -176,6 +280,36 @@ impl From<Data> for Event {
     }
 }
 
+/// Conversion of typed values into SSE data messages.
+///
+/// Allows an API's events to be modelled as an enum or as structs, rather than assembling each
+/// message with [`Data::new_json()`] and [`Data::event()`]. Usually implemented using the
+/// [derive macro](macro@IntoEvent).
+pub trait IntoEvent {
+    /// Converts `self` into a data message.
+    ///
+    /// # Errors
+    /// Returns an error if the payload fails to serialize as JSON.
+    fn into_data(self) -> Result<Data, serde_json::Error>;
+
+    /// Converts `self` into an [`Event`].
+    ///
+    /// # Errors
+    /// Returns an error if the payload fails to serialize as JSON.
+    fn into_event(self) -> Result<Event, serde_json::Error>
+    where
+        Self: Sized,
+    {
+        self.into_data().map(Event::Data)
+    }
+}
+
+impl IntoEvent for Data {
+    fn into_data(self) -> Result<Data, serde_json::Error> {
+        Ok(self)
+    }
+}
+
 /// Server-sent events message containing one or more fields.
 #[must_use]
 #[derive(Debug, Clone)]
@@ -206,23 +340,64 @@ pub enum Event {
 }
 
 impl Event {
+    /// Returns the `id` field of data messages.
+    pub(crate) fn data_id(&self) -> Option<&str> {
+        match self {
+            Event::Data(data) => data.id.as_deref(),
+            Event::Comment(_) => None,
+        }
+    }
+
     /// Splits data into lines and prepend each line with `prefix`.
-    fn line_split_with_prefix(buf: &mut BytesMut, prefix: &'static str, data: ByteString) {
+    fn line_split_with_prefix(buf: &mut BytesMut, prefix: &'static str, data: &str) {
         // initial buffer size guess is len(data) + 10 lines of prefix + EOLs + EOF
         buf.reserve(data.len() + (10 * (prefix.len() + 1)) + 1);
 
-        // append prefix + space + line to buffer
-        for line in data.split('\n') {
+        let mut rest = data;
+
+        // clients treat CRLF, CR, and LF as line endings
+        loop {
+            let (line, next) = match rest.find(['\r', '\n']) {
+                Some(idx) => {
+                    let eol_len = if rest[idx..].starts_with("\r\n") {
+                        2
+                    } else {
+                        1
+                    };
+                    (&rest[..idx], Some(&rest[idx + eol_len..]))
+                }
+                None => (rest, None),
+            };
+
+            // append prefix + space + line to buffer
             buf.put_slice(prefix.as_bytes());
             buf.put_slice(line.as_bytes());
             buf.put_u8(b'\n');
+
+            match next {
+                Some(next) => rest = next,
+                None => break,
+            }
         }
     }
 
-    /// Serializes message into event-stream format.
-    fn into_bytes(self) -> Bytes {
-        let mut buf = BytesMut::new();
-
+    /// Serializes message into event-stream format, appending it to `buf`.
+    ///
+    /// Writing into a reused buffer avoids allocating for every event, which can be useful when
+    /// forwarding high-frequency streams. The [`Sse`] responder uses this internally; it is exposed
+    /// for advanced use cases, such as custom body types or pre-serializing broadcast events.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web_lab::sse;
+    /// use bytes::BytesMut;
+    ///
+    /// let mut buf = BytesMut::new();
+    /// sse::Event::Comment("hi".into()).serialize_into(&mut buf);
+    /// sse::Event::from(sse::Data::new("foo")).serialize_into(&mut buf);
+    /// assert_eq!(buf, ": hi\n\ndata: foo\n\n");
+    /// ```
+    pub fn serialize_into(&self, buf: &mut BytesMut) {
         match self {
             Event::Data(Data { id, event, data }) => {
                 if let Some(text) = id {
@@ -237,15 +412,21 @@ impl Event {
                     buf.put_u8(b'\n');
                 }
 
-                Self::line_split_with_prefix(&mut buf, "data: ", data);
+                Self::line_split_with_prefix(buf, "data: ", data);
             }
 
-            Event::Comment(text) => Self::line_split_with_prefix(&mut buf, ": ", text),
+            Event::Comment(text) => Self::line_split_with_prefix(buf, ": ", text),
         }
 
         // final newline to mark end of message
         buf.put_u8(b'\n');
+    }
 
+    /// Serializes message into event-stream format.
+    #[cfg(test)]
+    fn into_bytes(self) -> Bytes {
+        let mut buf = BytesMut::new();
+        self.serialize_into(&mut buf);
         buf.freeze()
     }
 
@@ -258,6 +439,20 @@ impl Event {
     const fn keep_alive_bytes() -> Bytes {
         Bytes::from_static(b": keep-alive\n\n")
     }
+
+    /// Appends a padding comment to `buf` so that it is at least `min_len` bytes long.
+    fn pad_to(buf: &mut BytesMut, min_len: usize) {
+        if buf.len() >= min_len {
+            return;
+        }
+
+        // colon, spaces, and two newlines
+        let spaces = (min_len - buf.len()).saturating_sub(3);
+
+        buf.put_u8(b':');
+        buf.put_bytes(b' ', spaces);
+        buf.put_slice(b"\n\n");
+    }
 }
 
 pin_project! {
@@ -272,6 +467,12 @@ pin_project! {
         stream: S,
         keep_alive: Option<Interval>,
         retry_interval: Option<Duration>,
+        metrics: Option<StreamGuard>,
+        padding: Option<usize>,
+        // whether the initial padding block is still to be sent
+        pad_start: bool,
+        disable_proxy_buffering: bool,
+        buf: BytesMut,
     }
 }
 
@@ -286,6 +487,11 @@ where
             stream,
             keep_alive: None,
             retry_interval: None,
+            metrics: None,
+            padding: None,
+            pad_start: false,
+            disable_proxy_buffering: false,
+            buf: BytesMut::new(),
         }
     }
 }
@@ -336,6 +542,39 @@ impl<S> Sse<S> {
         self.retry_interval = Some(retry);
         self
     }
+
+    /// Records delivered events and how the stream ended in `metrics`.
+    ///
+    /// Retry and keep-alive messages are not counted as delivered events.
+    pub fn with_metrics(mut self, metrics: &StreamMetrics) -> Self {
+        self.metrics = Some(metrics.start());
+        self
+    }
+
+    /// Pads every chunk of the event stream with a comment so that it is at least `len` bytes long.
+    ///
+    /// Some proxies and legacy clients (notably older Internet Explorer polyfills) hold back
+    /// responses until a few kilobytes have been received, which delays small events indefinitely.
+    /// With padding enabled, a padding block is sent as soon as the response starts, and each event,
+    /// retry, and keep-alive message is padded so that it is flushed through such buffers
+    /// immediately. Clients ignore comments, so the events themselves are unaffected. A `len` of
+    /// 2048 is typically sufficient.
+    ///
+    /// Padding increases bandwidth use considerably for streams of small events, so it should only
+    /// be enabled when buffering intermediaries cannot be reconfigured. The `Content-Encoding:
+    /// identity` header is always sent to prevent compression middleware from buffering the stream.
+    pub fn with_padding(mut self, len: usize) -> Self {
+        self.padding = Some(len);
+        self.pad_start = true;
+        self
+    }
+
+    /// Sends the `X-Accel-Buffering: no` header, which disables response buffering in Nginx and
+    /// other proxies that respect it.
+    pub fn with_proxy_buffering_disabled(mut self) -> Self {
+        self.disable_proxy_buffering = true;
+        self
+    }
 }
 
 impl<S, E> Responder for Sse<S>
@@ -346,11 +585,16 @@ where
     type Body = BoxBody;
 
     fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
-        HttpResponse::Ok()
-            .content_type(mime::TEXT_EVENT_STREAM)
+        let mut res = HttpResponse::Ok();
+        res.content_type(mime::TEXT_EVENT_STREAM)
             .insert_header(ContentEncoding::Identity)
-            .insert_header(CacheControl(vec![CacheDirective::NoCache]))
-            .body(self)
+            .insert_header(CacheControl(vec![CacheDirective::NoCache]));
+
+        if self.disable_proxy_buffering {
+            res.insert_header((X_ACCEL_BUFFERING, HeaderValue::from_static("no")));
+        }
+
+        res.body(self)
     }
 }
 
@@ -371,22 +615,65 @@ where
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
         let this = self.project();
 
-        if let Some(retry) = this.retry_interval.take() {
+        if *this.pad_start || this.retry_interval.is_some() {
+            *this.pad_start = false;
+
+            if let Some(retry) = this.retry_interval.take() {
+                this.buf.put_slice(&Event::retry_to_bytes(retry));
+            }
+
+            if let Some(len) = *this.padding {
+                Event::pad_to(this.buf, len);
+            }
+
             cx.waker().wake_by_ref();
-            return Poll::Ready(Some(Ok(Event::retry_to_bytes(retry))));
+            return Poll::Ready(Some(Ok(this.buf.split().freeze())));
         }
 
         if let Poll::Ready(msg) = this.stream.poll_next(cx) {
             return match msg {
-                Some(Ok(msg)) => Poll::Ready(Some(Ok(msg.into_bytes()))),
-                Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
-                None => Poll::Ready(None),
+                Some(Ok(msg)) => {
+                    if let Some(metrics) = this.metrics {
+                        metrics.delivered();
+                    }
+
+                    // reuses buffer capacity once previously yielded chunks have been dropped
+                    msg.serialize_into(this.buf);
+
+                    if let Some(len) = *this.padding {
+                        Event::pad_to(this.buf, len);
+                    }
+
+                    Poll::Ready(Some(Ok(this.buf.split().freeze())))
+                }
+
+                Some(Err(err)) => {
+                    if let Some(metrics) = this.metrics {
+                        metrics.finish(DisconnectReason::Error);
+                    }
+
+                    Poll::Ready(Some(Err(err.into())))
+                }
+
+                None => {
+                    if let Some(metrics) = this.metrics {
+                        metrics.finish(DisconnectReason::Completed);
+                    }
+
+                    Poll::Ready(None)
+                }
             };
         }
 
         if let Some(ref mut keep_alive) = this.keep_alive {
             if keep_alive.poll_tick(cx).is_ready() {
-                return Poll::Ready(Some(Ok(Event::keep_alive_bytes())));
+                let Some(len) = *this.padding else {
+                    return Poll::Ready(Some(Ok(Event::keep_alive_bytes())));
+                };
+
+                this.buf.put_slice(&Event::keep_alive_bytes());
+                Event::pad_to(this.buf, len);
+                return Poll::Ready(Some(Ok(this.buf.split().freeze())));
             }
         }
 
@@ -420,12 +707,59 @@ mod tests {
     #[test]
     fn line_split_format() {
         let mut buf = BytesMut::new();
-        Event::line_split_with_prefix(&mut buf, "data: ", ByteString::from("foo"));
+        Event::line_split_with_prefix(&mut buf, "data: ", "foo");
         assert_eq!(buf, "data: foo\n");
 
         let mut buf = BytesMut::new();
-        Event::line_split_with_prefix(&mut buf, "data: ", ByteString::from("foo\nbar"));
+        Event::line_split_with_prefix(&mut buf, "data: ", "foo\nbar");
         assert_eq!(buf, "data: foo\ndata: bar\n");
+
+        let mut buf = BytesMut::new();
+        Event::line_split_with_prefix(&mut buf, "data: ", "foo\r\nbar\rbaz");
+        assert_eq!(buf, "data: foo\ndata: bar\ndata: baz\n");
+
+        let mut buf = BytesMut::new();
+        Event::line_split_with_prefix(&mut buf, "data: ", "foo\r\r\n\n");
+        assert_eq!(buf, "data: foo\ndata: \ndata: \ndata: \n");
+    }
+
+    #[test]
+    fn serialize_into_appends() {
+        let mut buf = BytesMut::from("retry: 10\n\n");
+
+        Event::Data(Data::new("foo").id("1")).serialize_into(&mut buf);
+        Event::Comment("bar".into()).serialize_into(&mut buf);
+
+        assert_eq!(buf, "retry: 10\n\nid: 1\ndata: foo\n\n: bar\n\n");
+    }
+
+    #[test]
+    fn fields_are_sanitized() {
+        let data = Data::new("foo").id("4\n2\0").event("bar\r\ndata: injected");
+        assert_eq!(
+            Event::Data(data).into_bytes(),
+            "id: 4 2\nevent: bar  data: injected\ndata: foo\n\n",
+        );
+
+        let data = Data::new("foo").id("42").event("bar");
+        assert_eq!(data.id.as_deref(), Some("42"));
+        assert_eq!(data.event.as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn fallible_constructors() {
+        assert!(Data::try_new("foo\nbar").is_ok());
+        assert_eq!(Data::try_new("foo\rbar").unwrap_err().field(), "data");
+
+        let data = Data::new("foo").try_id("42").unwrap();
+        assert!(data.try_event("bar").is_ok());
+
+        assert_eq!(Data::new("foo").try_id("4\r2").unwrap_err().field(), "id");
+        assert_eq!(Data::new("foo").try_id("42\0").unwrap_err().field(), "id");
+        assert_eq!(
+            Data::new("foo").try_event("bar\n").unwrap_err().field(),
+            "event"
+        );
     }
 
     #[test]
@@ -483,6 +817,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn typed_events() {
+        struct Tick(u32);
+
+        impl IntoEvent for Tick {
+            fn into_data(self) -> Result<Data, serde_json::Error> {
+                Ok(Data::new_json(self.0)?.event("tick"))
+            }
+        }
+
+        assert_eq!(
+            Tick(42).into_event().unwrap().into_bytes(),
+            "event: tick\ndata: 42\n\n"
+        );
+        assert_eq!(
+            Data::new("foo").into_event().unwrap().into_bytes(),
+            "data: foo\n\n"
+        );
+    }
+
     #[test]
     fn retry_is_first_msg() {
         let waker = noop_waker();
@@ -575,4 +929,72 @@ mod tests {
             res => panic!("poll should return data message, got {res:?}"),
         }
     }
+
+    #[actix_web::test]
+    async fn padding_is_applied_to_each_chunk() {
+        let st = stream::iter([
+            Ok::<_, Infallible>(Event::Data(Data::new("foo"))),
+            Ok(Event::Data(Data::new("x".repeat(40)))),
+        ]);
+        let mut sse = Sse::from_stream(st)
+            .with_retry_duration(Duration::from_millis(42))
+            .with_padding(32);
+
+        let retry = poll_fn(|cx| Pin::new(&mut sse).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retry.len(), 32);
+        assert!(retry.starts_with(b"retry: 42\n\n:   "));
+        assert!(retry.ends_with(b"  \n\n"));
+
+        let event = poll_fn(|cx| Pin::new(&mut sse).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.len(), 32);
+        assert!(event.starts_with(b"data: foo\n\n:   "));
+
+        // events longer than the padding length are not padded
+        let event = poll_fn(|cx| Pin::new(&mut sse).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event, format!("data: {}\n\n", "x".repeat(40)));
+
+        assert!(poll_fn(|cx| Pin::new(&mut sse).poll_next(cx))
+            .await
+            .is_none());
+    }
+
+    #[actix_web::test]
+    async fn padding_block_is_sent_first() {
+        let st = stream::empty::<Result<_, Infallible>>();
+        let sse = Sse::from_stream(st).with_padding(2048);
+
+        let body = body::to_bytes(sse).await.unwrap();
+        assert_eq!(body.len(), 2048);
+        assert!(body.starts_with(b":    "));
+        assert!(body.ends_with(b"\n\n"));
+
+        // parses as a single comment
+        assert_eq!(body.iter().filter(|&&byte| byte == b'\n').count(), 2);
+    }
+
+    #[actix_web::test]
+    async fn proxy_buffering_header_is_set() {
+        let req = TestRequest::default().to_http_request();
+
+        let sse = Sse::from_stream(stream::empty::<Result<_, Infallible>>());
+        let res = sse.respond_to(&req);
+        assert!(!res.headers().contains_key("x-accel-buffering"));
+
+        let sse = Sse::from_stream(stream::empty::<Result<_, Infallible>>())
+            .with_proxy_buffering_disabled();
+        let res = sse.respond_to(&req);
+        assert_response_matches!(res, OK;
+            "x-accel-buffering" => "no"
+            "content-encoding" => "identity"
+        );
+    }
 }
@@ -0,0 +1,691 @@
+//! Server-sent events broadcaster with optional replay buffer.
+//!
+//! See [`Broadcaster`] docs.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use actix_web::{http::header::HeaderName, HttpRequest};
+use futures_core::Stream;
+
+use crate::{
+    sse::{Event, EventStore, Sse},
+    sse_event_store::catch_up_stream,
+    util::{InfallibleStream, StreamMetrics},
+};
+
+/// Name of the header sent by reconnecting event stream clients.
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// Default number of events that may be queued for each client.
+const DEFAULT_CLIENT_BUFFER: usize = 16;
+
+/// Server-sent events broadcaster.
+///
+/// Fans out events to any number of connected clients. Each client is given a bounded queue; when
+/// a client's queue is full, its [lag policy](Self::with_lag_policy) is applied rather than
+/// blocking the broadcaster. Disconnected clients are removed on the next broadcast.
+///
+/// Cloning a `Broadcaster` produces a handle to the same set of clients, so it can be shared
+/// between workers using [`Data`](actix_web::web::Data) or by cloning it into app data directly.
+///
+/// # Replay Buffer
+/// When enabled using [`with_replay_buffer()`](Self::with_replay_buffer), data events that have an
+/// ID are retained so that clients reconnecting with a `Last-Event-ID` header receive the events
+/// they missed (see [`resume_client()`](Self::resume_client)). Retention is bounded by count and,
+/// optionally, by age using [`with_replay_max_age()`](Self::with_replay_max_age).
+///
+/// If the `Last-Event-ID` is not found in the buffer (e.g., because it has already been evicted),
+/// all retained events are replayed.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{get, web, HttpRequest, Responder};
+/// use actix_web_lab::sse;
+///
+/// #[get("/events")]
+/// async fn events(req: HttpRequest, broadcaster: web::Data<sse::Broadcaster>) -> impl Responder {
+///     broadcaster.resume_client(&req)
+/// }
+///
+/// let broadcaster = sse::Broadcaster::new()
+///     .with_replay_buffer(100)
+///     .with_replay_max_age(Duration::from_secs(60));
+///
+/// broadcaster.broadcast(sse::Data::new("hello").id("1"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Broadcaster {
+    inner: Arc<Mutex<BroadcasterInner>>,
+    client_buffer: usize,
+    lag_policy: LagPolicy,
+    metrics: Option<StreamMetrics>,
+}
+
+#[derive(Debug)]
+struct BroadcasterInner {
+    clients: Vec<ClientSender>,
+    replay: ReplayBuffer,
+}
+
+impl Default for Broadcaster {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BroadcasterInner {
+                clients: Vec::new(),
+                replay: ReplayBuffer::default(),
+            })),
+            client_buffer: DEFAULT_CLIENT_BUFFER,
+            lag_policy: LagPolicy::DropNewest,
+            metrics: None,
+        }
+    }
+}
+
+impl Broadcaster {
+    /// Constructs a new broadcaster with no replay buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of events that may be queued for each client before its
+    /// [lag policy](Self::with_lag_policy) is applied.
+    ///
+    /// Defaults to 16.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn with_client_buffer(mut self, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "client buffer capacity must be greater than zero"
+        );
+        self.client_buffer = capacity;
+        self
+    }
+
+    /// Sets the policy applied to clients that cannot keep up with broadcast events.
+    ///
+    /// Applies to clients registered using this handle after it is set. To use different policies
+    /// for different subscriptions, clone the broadcaster and set the policy on each clone; clones
+    /// still share the same set of clients.
+    ///
+    /// Defaults to [`LagPolicy::DropNewest`].
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web_lab::sse;
+    ///
+    /// let broadcaster = sse::Broadcaster::new();
+    ///
+    /// // dashboards only care about the latest state
+    /// let latest = broadcaster
+    ///     .clone()
+    ///     .with_lag_policy(sse::LagPolicy::coalesce(|_older, newer| newer));
+    ///
+    /// let _client = latest.new_client();
+    /// broadcaster.broadcast(sse::Data::new("hello"));
+    /// ```
+    pub fn with_lag_policy(mut self, policy: LagPolicy) -> Self {
+        self.lag_policy = policy;
+        self
+    }
+
+    /// Enables the replay buffer, retaining up to `capacity` of the most recent events with IDs.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn with_replay_buffer(self, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "replay buffer capacity must be greater than zero"
+        );
+
+        self.inner.lock().unwrap().replay.capacity = capacity;
+
+        self
+    }
+
+    /// Sets the maximum age of events retained in the replay buffer.
+    ///
+    /// Has no effect unless the replay buffer is enabled.
+    pub fn with_replay_max_age(self, max_age: Duration) -> Self {
+        self.inner.lock().unwrap().replay.max_age = Some(max_age);
+
+        self
+    }
+
+    /// Records client streams and events dropped for lagging clients in `metrics`.
+    ///
+    /// See [`Sse::with_metrics()`].
+    pub fn with_metrics(mut self, metrics: StreamMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Registers a new client and returns an SSE responder for it.
+    pub fn new_client(&self) -> Sse<InfallibleStream<ClientStream>> {
+        self.register(None)
+    }
+
+    /// Registers a new client, replaying any events missed since the request's `Last-Event-ID`,
+    /// and returns an SSE responder for it.
+    ///
+    /// Behaves the same as [`new_client()`](Self::new_client) if the request has no
+    /// `Last-Event-ID` header or the replay buffer is not enabled.
+    pub fn resume_client(&self, req: &HttpRequest) -> Sse<InfallibleStream<ClientStream>> {
+        self.register(last_event_id(req))
+    }
+
+    /// Sends an event to all connected clients.
+    ///
+    /// Data events with IDs are also recorded in the replay buffer, if enabled.
+    pub fn broadcast(&self, event: impl Into<Event>) {
+        let event = event.into();
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.replay.push(&event);
+
+        inner
+            .clients
+            .retain(|client| client.send(event.clone(), self.metrics.as_ref()));
+    }
+
+    /// Returns the number of connected clients, as of the last broadcast.
+    pub fn client_count(&self) -> usize {
+        self.inner.lock().unwrap().clients.len()
+    }
+
+    /// Registers a new client that first catches up on events after the request's
+    /// `Last-Event-ID` from `store`, then receives live events, and returns an SSE responder for it.
+    ///
+    /// See [`EventStore`] docs for details of how catch-up works. The replay buffer, if enabled, is
+    /// not used.
+    pub fn resume_client_from_store<S: EventStore>(
+        &self,
+        req: &HttpRequest,
+        store: S,
+    ) -> Sse<impl Stream<Item = Result<Event, S::Error>>> {
+        let last_event_id = last_event_id(req).map(str::to_owned);
+
+        // subscribe before catching up so no live events are missed in between
+        let rx = self.subscribe(None);
+
+        self.attach_metrics(Sse::from_stream(catch_up_stream(store, last_event_id, rx)))
+    }
+
+    fn register(&self, last_event_id: Option<&str>) -> Sse<InfallibleStream<ClientStream>> {
+        let rx = self.subscribe(last_event_id);
+        self.attach_metrics(Sse::from_infallible_stream(rx))
+    }
+
+    /// Adds a new client, queueing any events missed since `last_event_id`.
+    fn subscribe(&self, last_event_id: Option<&str>) -> ClientStream {
+        let mut inner = self.inner.lock().unwrap();
+
+        let missed = match last_event_id {
+            Some(id) => inner.replay.events_after(id),
+            None => VecDeque::new(),
+        };
+
+        let queue = Arc::new(Mutex::new(ClientQueue {
+            // make room for replayed events in addition to the normal client buffer
+            capacity: self.client_buffer + missed.len(),
+            events: missed,
+            policy: self.lag_policy.clone(),
+            waker: None,
+            closed: false,
+            receiver_gone: false,
+        }));
+
+        inner.clients.push(ClientSender {
+            queue: Arc::clone(&queue),
+        });
+
+        ClientStream { queue }
+    }
+
+    fn attach_metrics<St>(&self, sse: Sse<St>) -> Sse<St> {
+        match &self.metrics {
+            Some(metrics) => sse.with_metrics(metrics),
+            None => sse,
+        }
+    }
+}
+
+/// Policy applied when a [`Broadcaster`] client's queue is full.
+///
+/// See [`Broadcaster::with_lag_policy()`].
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum LagPolicy {
+    /// Drops the new event for the lagging client, keeping queued events.
+    DropNewest,
+
+    /// Drops the oldest queued event for the lagging client to make room for the new event.
+    DropOldest,
+
+    /// Disconnects the lagging client, discarding its queued events.
+    ///
+    /// Clients will typically reconnect and can then catch up using a replay buffer or
+    /// [`EventStore`].
+    Disconnect,
+
+    /// Merges the new event into the most recently queued event using a merge function.
+    ///
+    /// See [`LagPolicy::coalesce()`].
+    Coalesce(Arc<dyn Fn(Event, Event) -> Event + Send + Sync>),
+}
+
+impl LagPolicy {
+    /// Constructs a policy that merges events for lagging clients using `merge`.
+    ///
+    /// `merge` is called with the most recently queued event and the new event, and the event it
+    /// returns replaces the queued event. It is called while the broadcaster is locked, so it
+    /// should be cheap and must not broadcast.
+    pub fn coalesce(merge: impl Fn(Event, Event) -> Event + Send + Sync + 'static) -> Self {
+        Self::Coalesce(Arc::new(merge))
+    }
+}
+
+impl fmt::Debug for LagPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DropNewest => f.write_str("DropNewest"),
+            Self::DropOldest => f.write_str("DropOldest"),
+            Self::Disconnect => f.write_str("Disconnect"),
+            Self::Coalesce(_) => f.write_str("Coalesce(..)"),
+        }
+    }
+}
+
+/// Bounded event queue shared between the broadcaster and a client stream.
+#[derive(Debug)]
+struct ClientQueue {
+    events: VecDeque<Event>,
+    capacity: usize,
+    policy: LagPolicy,
+    waker: Option<Waker>,
+
+    // set when the broadcaster removes the client
+    closed: bool,
+
+    // set when the client stream is dropped
+    receiver_gone: bool,
+}
+
+impl ClientQueue {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Broadcaster's handle to a client queue. Closes the queue when dropped.
+#[derive(Debug)]
+struct ClientSender {
+    queue: Arc<Mutex<ClientQueue>>,
+}
+
+impl ClientSender {
+    /// Queues `event`, applying the lag policy if the queue is full.
+    ///
+    /// Returns false if the client should be removed.
+    fn send(&self, event: Event, metrics: Option<&StreamMetrics>) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+
+        if queue.receiver_gone {
+            return false;
+        }
+
+        if queue.events.len() < queue.capacity {
+            queue.events.push_back(event);
+            queue.wake();
+            return true;
+        }
+
+        match queue.policy.clone() {
+            LagPolicy::DropNewest => {
+                record_dropped(metrics, 1);
+                true
+            }
+
+            LagPolicy::DropOldest => {
+                queue.events.pop_front();
+                queue.events.push_back(event);
+                record_dropped(metrics, 1);
+                true
+            }
+
+            LagPolicy::Disconnect => {
+                record_dropped(metrics, queue.events.len() + 1);
+                queue.events.clear();
+                false
+            }
+
+            LagPolicy::Coalesce(merge) => {
+                let event = match queue.events.pop_back() {
+                    Some(queued) => merge(queued, event),
+                    None => event,
+                };
+
+                queue.events.push_back(event);
+                true
+            }
+        }
+    }
+}
+
+impl Drop for ClientSender {
+    fn drop(&mut self) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.closed = true;
+        queue.wake();
+    }
+}
+
+fn record_dropped(metrics: Option<&StreamMetrics>, n: usize) {
+    if let Some(metrics) = metrics {
+        for _ in 0..n {
+            metrics.record_dropped();
+        }
+    }
+}
+
+/// Stream of events for a single [`Broadcaster`] client.
+///
+/// Ends when the client is disconnected by its [lag policy](LagPolicy::Disconnect) or all handles
+/// to the broadcaster are dropped.
+#[derive(Debug)]
+pub struct ClientStream {
+    queue: Arc<Mutex<ClientQueue>>,
+}
+
+impl Stream for ClientStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut queue = self.queue.lock().unwrap();
+
+        if let Some(event) = queue.events.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+
+        if queue.closed {
+            return Poll::Ready(None);
+        }
+
+        queue.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for ClientStream {
+    fn drop(&mut self) {
+        self.queue.lock().unwrap().receiver_gone = true;
+    }
+}
+
+/// Returns the value of the request's `Last-Event-ID` header.
+fn last_event_id(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(LAST_EVENT_ID)
+        .and_then(|hdr| hdr.to_str().ok())
+}
+
+/// Bounded buffer of recent events with IDs. Disabled when capacity is zero.
+#[derive(Debug, Default)]
+struct ReplayBuffer {
+    events: VecDeque<(Instant, Event)>,
+    capacity: usize,
+    max_age: Option<Duration>,
+}
+
+impl ReplayBuffer {
+    fn push(&mut self, event: &Event) {
+        if self.capacity == 0 || event.data_id().is_none() {
+            return;
+        }
+
+        self.evict_expired();
+
+        while self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+
+        self.events.push_back((Instant::now(), event.clone()));
+    }
+
+    /// Returns retained events after the one with ID `id`, or all retained events if `id` is not
+    /// found.
+    fn events_after(&mut self, id: &str) -> VecDeque<Event> {
+        self.evict_expired();
+
+        let start = self
+            .events
+            .iter()
+            .rposition(|(_, ev)| ev.data_id() == Some(id))
+            .map_or(0, |idx| idx + 1);
+
+        self.events
+            .range(start..)
+            .map(|(_, ev)| ev.clone())
+            .collect()
+    }
+
+    fn evict_expired(&mut self) {
+        let Some(max_age) = self.max_age else {
+            return;
+        };
+
+        while self
+            .events
+            .front()
+            .is_some_and(|(at, _)| at.elapsed() > max_age)
+        {
+            self.events.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Poll};
+
+    use actix_web::{body::MessageBody as _, test::TestRequest};
+    use futures_util::{task::noop_waker, StreamExt as _};
+
+    use super::*;
+    use crate::{sse::Data, util::DisconnectReason};
+
+    static_assertions::assert_impl_all!(Broadcaster: Send, Sync, Clone);
+
+    /// Collects all immediately available chunks from an SSE body.
+    fn ready_chunks(sse: Sse<InfallibleStream<ClientStream>>) -> String {
+        let mut sse = std::pin::pin!(sse);
+        let mut buf = String::new();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        while let Poll::Ready(Some(Ok(chunk))) = sse.as_mut().poll_next(&mut cx) {
+            buf.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+
+        buf
+    }
+
+    fn resume_req(last_event_id: &str) -> HttpRequest {
+        TestRequest::default()
+            .insert_header((LAST_EVENT_ID, last_event_id))
+            .to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn broadcasts_to_clients() {
+        let broadcaster = Broadcaster::new();
+
+        let client1 = broadcaster.new_client();
+        let client2 = broadcaster.new_client();
+        assert_eq!(broadcaster.client_count(), 2);
+
+        broadcaster.broadcast(Data::new("foo"));
+
+        assert_eq!(ready_chunks(client1), "data: foo\n\n");
+        assert_eq!(ready_chunks(client2), "data: foo\n\n");
+
+        broadcaster.broadcast(Data::new("bar"));
+        assert_eq!(broadcaster.client_count(), 0);
+    }
+
+    #[actix_web::test]
+    async fn records_metrics() {
+        let metrics = StreamMetrics::new();
+        let broadcaster = Broadcaster::new()
+            .with_client_buffer(1)
+            .with_metrics(metrics.clone());
+
+        let client = broadcaster.new_client();
+        assert_eq!(metrics.active(), 1);
+
+        broadcaster.broadcast(Data::new("foo"));
+        broadcaster.broadcast(Data::new("bar"));
+        assert_eq!(metrics.dropped(), 1);
+
+        assert_eq!(ready_chunks(client), "data: foo\n\n");
+        assert_eq!(metrics.delivered(), 1);
+        assert_eq!(metrics.active(), 0);
+        assert_eq!(metrics.ended(DisconnectReason::ClientGone), 1);
+    }
+
+    #[actix_web::test]
+    async fn lag_policy_drop_oldest() {
+        let metrics = StreamMetrics::new();
+        let broadcaster = Broadcaster::new()
+            .with_client_buffer(2)
+            .with_lag_policy(LagPolicy::DropOldest)
+            .with_metrics(metrics.clone());
+
+        let client = broadcaster.new_client();
+
+        broadcaster.broadcast(Data::new("a"));
+        broadcaster.broadcast(Data::new("b"));
+        broadcaster.broadcast(Data::new("c"));
+        assert_eq!(metrics.dropped(), 1);
+
+        assert_eq!(ready_chunks(client), "data: b\n\ndata: c\n\n");
+    }
+
+    #[actix_web::test]
+    async fn lag_policy_disconnect() {
+        let metrics = StreamMetrics::new();
+        let broadcaster = Broadcaster::new()
+            .with_client_buffer(1)
+            .with_metrics(metrics.clone());
+
+        let fast = broadcaster.new_client();
+        let slow = broadcaster
+            .clone()
+            .with_lag_policy(LagPolicy::Disconnect)
+            .new_client();
+
+        broadcaster.broadcast(Data::new("a"));
+        broadcaster.broadcast(Data::new("b"));
+        assert_eq!(broadcaster.client_count(), 1);
+        assert_eq!(metrics.dropped(), 3);
+
+        assert_eq!(ready_chunks(slow), "");
+        assert_eq!(metrics.ended(DisconnectReason::Completed), 1);
+
+        assert_eq!(ready_chunks(fast), "data: a\n\n");
+    }
+
+    #[actix_web::test]
+    async fn lag_policy_coalesce() {
+        let broadcaster =
+            Broadcaster::new()
+                .with_client_buffer(2)
+                .with_lag_policy(LagPolicy::coalesce(|older, newer| match (older, newer) {
+                    (Event::Comment(older), Event::Comment(newer)) => {
+                        Event::Comment(format!("{older}+{newer}").into())
+                    }
+                    (_, newer) => newer,
+                }));
+
+        let client = broadcaster.new_client();
+
+        for text in ["a", "b", "c", "d"] {
+            broadcaster.broadcast(Event::Comment(text.into()));
+        }
+
+        assert_eq!(ready_chunks(client), ": a\n\n: b+c+d\n\n");
+    }
+
+    #[actix_web::test]
+    async fn client_stream_ends_with_broadcaster() {
+        let broadcaster = Broadcaster::new();
+        let client = broadcaster.subscribe(None);
+
+        broadcaster.broadcast(Data::new("foo"));
+        drop(broadcaster);
+
+        let events = client.collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn no_replay_by_default() {
+        let broadcaster = Broadcaster::new();
+        broadcaster.broadcast(Data::new("foo").id("1"));
+
+        let client = broadcaster.resume_client(&resume_req("0"));
+        assert_eq!(ready_chunks(client), "");
+    }
+
+    #[actix_web::test]
+    async fn replays_missed_events() {
+        let broadcaster = Broadcaster::new().with_replay_buffer(2);
+
+        broadcaster.broadcast(Data::new("a").id("1"));
+        broadcaster.broadcast(Data::new("b").id("2"));
+        broadcaster.broadcast(Event::Comment("not replayed".into()));
+        broadcaster.broadcast(Data::new("c").id("3"));
+
+        let client = broadcaster.resume_client(&resume_req("2"));
+        assert_eq!(ready_chunks(client), "id: 3\ndata: c\n\n");
+
+        let client = broadcaster.resume_client(&resume_req("3"));
+        assert_eq!(ready_chunks(client), "");
+
+        // ID 1 has been evicted so all retained events are replayed
+        let client = broadcaster.resume_client(&resume_req("1"));
+        assert_eq!(ready_chunks(client), "id: 2\ndata: b\n\nid: 3\ndata: c\n\n",);
+
+        // clients without Last-Event-ID only receive new events
+        let client = broadcaster.resume_client(&TestRequest::default().to_http_request());
+        assert_eq!(ready_chunks(client), "");
+    }
+
+    #[actix_web::test]
+    async fn replay_max_age() {
+        let broadcaster = Broadcaster::new()
+            .with_replay_buffer(10)
+            .with_replay_max_age(Duration::from_millis(50));
+
+        broadcaster.broadcast(Data::new("a").id("1"));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        broadcaster.broadcast(Data::new("b").id("2"));
+
+        let client = broadcaster.resume_client(&resume_req("0"));
+        assert_eq!(ready_chunks(client), "id: 2\ndata: b\n\n");
+    }
+}
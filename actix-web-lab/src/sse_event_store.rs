@@ -0,0 +1,284 @@
+//! Persistent event source for reliable server-sent event feeds.
+//!
+//! See [`EventStore`] docs.
+
+use std::{collections::HashSet, future::Future, vec};
+
+use futures_core::Stream;
+use futures_util::{stream, StreamExt as _};
+
+use crate::{
+    sse::{Data, Event},
+    BoxError,
+};
+
+/// A persistent source of historical events, used to catch up reconnecting event stream clients.
+///
+/// Used with [`Broadcaster::resume_client_from_store()`](crate::sse::Broadcaster::resume_client_from_store)
+/// to implement the standard pattern for reliable SSE feeds: when a client reconnects with a
+/// `Last-Event-ID` header, events after that ID are first replayed from the store (e.g., a
+/// database), then the client is switched over to live events from the broadcaster.
+///
+/// # Pagination
+/// [`events_after()`](Self::events_after) is called repeatedly, using the ID of the last event of
+/// the previous page as the cursor, until it returns an empty page. Pages can therefore be limited
+/// to a reasonable size, such as by using `LIMIT` in a database query. Catch-up also ends if the
+/// last event of a page has no ID.
+///
+/// # Live Events
+/// The client is subscribed to the broadcaster before catch-up starts, so live events broadcast
+/// during catch-up are queued, up to the broadcaster's
+/// [client buffer](crate::sse::Broadcaster::with_client_buffer) size. Queued live events whose IDs
+/// were already replayed from the store are skipped.
+///
+/// If the store returns an error, the event stream ends with that error. Clients will reconnect
+/// and try again.
+///
+/// # Examples
+/// ```
+/// use std::convert::Infallible;
+///
+/// use actix_web::{get, web, HttpRequest, Responder};
+/// use actix_web_lab::sse;
+///
+/// #[derive(Clone)]
+/// struct Db;
+///
+/// impl sse::EventStore for Db {
+///     type Error = Infallible;
+///
+///     async fn events_after(&self, last_event_id: &str) -> Result<Vec<sse::Data>, Self::Error> {
+///         // e.g., SELECT id, data FROM events WHERE id > $1 ORDER BY id LIMIT 100
+///         # let _ = last_event_id;
+///         Ok(vec![])
+///     }
+/// }
+///
+/// #[get("/events")]
+/// async fn events(
+///     req: HttpRequest,
+///     db: web::Data<Db>,
+///     broadcaster: web::Data<sse::Broadcaster>,
+/// ) -> impl Responder {
+///     broadcaster.resume_client_from_store(&req, db.get_ref().clone())
+/// }
+/// ```
+pub trait EventStore: 'static {
+    /// Error type returned when events cannot be loaded.
+    type Error: Into<BoxError> + 'static;
+
+    /// Returns a page of events after the event with ID `last_event_id`, in order.
+    ///
+    /// Returns an empty page when there are no more events.
+    fn events_after(
+        &self,
+        last_event_id: &str,
+    ) -> impl Future<Output = Result<Vec<Data>, Self::Error>>;
+}
+
+struct CatchUp<S, L> {
+    store: S,
+
+    // ID of last replayed event; catch-up ends when this is `None`
+    cursor: Option<String>,
+    page: vec::IntoIter<Data>,
+
+    // IDs of replayed events, used to skip live events that were queued during catch-up
+    replayed: HashSet<String>,
+
+    live: L,
+
+    // set when the store returns an error
+    failed: bool,
+}
+
+/// Returns a stream that replays events after `last_event_id` from `store`, then yields live
+/// events from `live`.
+pub(crate) fn catch_up_stream<S, L>(
+    store: S,
+    last_event_id: Option<String>,
+    live: L,
+) -> impl Stream<Item = Result<Event, S::Error>>
+where
+    S: EventStore,
+    L: Stream<Item = Event> + Unpin,
+{
+    let state = CatchUp {
+        store,
+        cursor: last_event_id,
+        page: Vec::new().into_iter(),
+        replayed: HashSet::new(),
+        live,
+        failed: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.failed {
+            return None;
+        }
+
+        // catch-up phase
+        while let Some(cursor) = &state.cursor {
+            if let Some(data) = state.page.next() {
+                let event = Event::Data(data);
+
+                state.cursor = event.data_id().map(str::to_owned);
+
+                if let Some(id) = &state.cursor {
+                    state.replayed.insert(id.clone());
+                }
+
+                return Some((Ok(event), state));
+            }
+
+            match state.store.events_after(cursor).await {
+                Ok(page) if page.is_empty() => state.cursor = None,
+                Ok(page) => state.page = page.into_iter(),
+
+                Err(err) => {
+                    state.failed = true;
+                    return Some((Err(err), state));
+                }
+            }
+        }
+
+        // live phase
+        loop {
+            let event = state.live.next().await?;
+
+            if !state.replayed.is_empty() {
+                if let Some(id) = event.data_id() {
+                    if state.replayed.contains(id) {
+                        continue;
+                    }
+
+                    // first new event; subsequent events were broadcast after catch-up
+                    state.replayed = HashSet::new();
+                }
+            }
+
+            return Some((Ok(event), state));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, rc::Rc};
+
+    use actix_web::{body, test::TestRequest};
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::ReceiverStream;
+
+    use super::*;
+    use crate::sse::Broadcaster;
+
+    #[derive(Clone)]
+    struct MemoryStore {
+        events: Rc<Vec<u32>>,
+        page_size: usize,
+    }
+
+    impl EventStore for MemoryStore {
+        type Error = io::Error;
+
+        async fn events_after(&self, last_event_id: &str) -> Result<Vec<Data>, Self::Error> {
+            let last_event_id = last_event_id.parse::<u32>().map_err(io::Error::other)?;
+
+            Ok(self
+                .events
+                .iter()
+                .filter(|&&id| id > last_event_id)
+                .take(self.page_size)
+                .map(|id| Data::new(format!("event {id}")).id(id.to_string()))
+                .collect())
+        }
+    }
+
+    fn store() -> MemoryStore {
+        MemoryStore {
+            events: Rc::new((1..=5).collect()),
+            page_size: 2,
+        }
+    }
+
+    async fn next_chunk(sse: &mut (impl actix_web::body::MessageBody + Unpin)) -> String {
+        let chunk = std::future::poll_fn(|cx| std::pin::Pin::new(&mut *sse).poll_next(cx))
+            .await
+            .unwrap()
+            .ok()
+            .unwrap();
+
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn catches_up_then_goes_live() {
+        let broadcaster = Broadcaster::new();
+
+        let req = TestRequest::default()
+            .insert_header(("last-event-id", "2"))
+            .to_http_request();
+        let sse = broadcaster.resume_client_from_store(&req, store());
+        let mut sse = Box::pin(sse);
+
+        // event 5 was broadcast while catching up; event 6 is new
+        broadcaster.broadcast(Data::new("event 5").id("5"));
+        broadcaster.broadcast(Data::new("event 6").id("6"));
+
+        let mut chunks = Vec::new();
+        for _ in 0..4 {
+            chunks.push(next_chunk(&mut sse).await);
+        }
+
+        assert_eq!(
+            chunks,
+            [
+                "id: 3\ndata: event 3\n\n",
+                "id: 4\ndata: event 4\n\n",
+                "id: 5\ndata: event 5\n\n",
+                "id: 6\ndata: event 6\n\n",
+            ],
+        );
+    }
+
+    #[actix_web::test]
+    async fn no_last_event_id() {
+        let broadcaster = Broadcaster::new();
+
+        let req = TestRequest::default().to_http_request();
+        let mut sse = Box::pin(broadcaster.resume_client_from_store(&req, store()));
+
+        broadcaster.broadcast(Data::new("live").id("9"));
+        assert_eq!(next_chunk(&mut sse).await, "id: 9\ndata: live\n\n");
+    }
+
+    #[actix_web::test]
+    async fn store_error_ends_stream() {
+        let (_tx, rx) = mpsc::channel(1);
+
+        let items = catch_up_stream(
+            store(),
+            Some("not a number".to_owned()),
+            ReceiverStream::new(rx),
+        )
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+
+        let (tx, rx) = mpsc::channel(1);
+        drop(tx);
+
+        let sse = crate::sse::Sse::from_stream(catch_up_stream(
+            store(),
+            Some("4".to_owned()),
+            ReceiverStream::new(rx),
+        ));
+        assert_eq!(
+            body::to_bytes(sse).await.ok().unwrap(),
+            "id: 5\ndata: event 5\n\n",
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Streaming response health metrics.
+//!
+//! See [`StreamMetrics`] docs.
+
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    web::Bytes,
+};
+use pin_project_lite::pin_project;
+
+type DisconnectCallback = dyn Fn(DisconnectReason) + Send + Sync;
+
+/// Reason a streaming response ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DisconnectReason {
+    /// The stream ended normally.
+    Completed,
+
+    /// The response was dropped before the stream ended, usually because the client disconnected.
+    ClientGone,
+
+    /// The stream yielded an error.
+    Error,
+}
+
+/// Counters and callbacks for observing the health of streaming responses.
+///
+/// Attach to [`Sse`](crate::sse::Sse) responses, [`Broadcaster`](crate::sse::Broadcaster)s, or any
+/// body using [`body::metered()`](crate::body::metered). Clones share the same counters so one
+/// instance can be kept in app data (or a metrics exporter) and attached to every stream of an
+/// endpoint.
+///
+/// # Examples
+/// ```
+/// use actix_web_lab::{
+///     sse,
+///     util::{DisconnectReason, StreamMetrics},
+/// };
+///
+/// let metrics = StreamMetrics::new().on_disconnect(|reason| {
+///     if reason == DisconnectReason::ClientGone {
+///         tracing::debug!("event stream client went away");
+///     }
+/// });
+///
+/// let broadcaster = sse::Broadcaster::new().with_metrics(metrics.clone());
+///
+/// // e.g., periodically export to your metrics system
+/// let (active, delivered, dropped) = (metrics.active(), metrics.delivered(), metrics.dropped());
+/// ```
+#[derive(Clone, Default)]
+pub struct StreamMetrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    active: AtomicU64,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    completed: AtomicU64,
+    client_gone: AtomicU64,
+    errored: AtomicU64,
+    on_disconnect: Option<Box<DisconnectCallback>>,
+}
+
+impl StreamMetrics {
+    /// Constructs new metrics with all counters set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a callback that is called with the reason each stream ended.
+    ///
+    /// # Panics
+    /// Panics if called after this instance has been cloned.
+    pub fn on_disconnect(
+        mut self,
+        on_disconnect: impl Fn(DisconnectReason) + Send + Sync + 'static,
+    ) -> Self {
+        Arc::get_mut(&mut self.inner)
+            .expect("on_disconnect() should be called before metrics are cloned")
+            .on_disconnect = Some(Box::new(on_disconnect));
+
+        self
+    }
+
+    /// Returns the number of streams currently in progress.
+    pub fn active(&self) -> u64 {
+        self.inner.active.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of events (or body chunks) delivered to clients.
+    pub fn delivered(&self) -> u64 {
+        self.inner.delivered.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of events dropped because a client was lagging behind.
+    pub fn dropped(&self) -> u64 {
+        self.inner.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of streams that ended with the given reason.
+    pub fn ended(&self, reason: DisconnectReason) -> u64 {
+        let counter = match reason {
+            DisconnectReason::Completed => &self.inner.completed,
+            DisconnectReason::ClientGone => &self.inner.client_gone,
+            DisconnectReason::Error => &self.inner.errored,
+        };
+
+        counter.load(Ordering::Relaxed)
+    }
+
+    /// Records an event that was dropped for a lagging client.
+    pub(crate) fn record_dropped(&self) {
+        self.inner.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the start of a stream, returning a guard that records its end.
+    pub(crate) fn start(&self) -> StreamGuard {
+        self.inner.active.fetch_add(1, Ordering::Relaxed);
+
+        StreamGuard {
+            metrics: Some(self.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for StreamMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamMetrics")
+            .field("active", &self.active())
+            .field("delivered", &self.delivered())
+            .field("dropped", &self.dropped())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Tracks a single stream; records [`DisconnectReason::ClientGone`] if dropped before finishing.
+#[derive(Debug)]
+pub(crate) struct StreamGuard {
+    // taken when the stream ends
+    metrics: Option<StreamMetrics>,
+}
+
+impl StreamGuard {
+    /// Records a delivered event or chunk.
+    pub(crate) fn delivered(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.inner.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records the end of the stream. Subsequent calls have no effect.
+    pub(crate) fn finish(&mut self, reason: DisconnectReason) {
+        let Some(metrics) = self.metrics.take() else {
+            return;
+        };
+
+        let inner = &metrics.inner;
+
+        inner.active.fetch_sub(1, Ordering::Relaxed);
+
+        let counter = match reason {
+            DisconnectReason::Completed => &inner.completed,
+            DisconnectReason::ClientGone => &inner.client_gone,
+            DisconnectReason::Error => &inner.errored,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(on_disconnect) = &inner.on_disconnect {
+            on_disconnect(reason);
+        }
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.finish(DisconnectReason::ClientGone);
+    }
+}
+
+/// Wraps `body`, recording delivered chunks and how the stream ended in `metrics`.
+///
+/// Useful for observing [channel bodies](crate::body::channel) and other streaming responses.
+///
+/// # Examples
+/// ```
+/// use std::convert::Infallible;
+///
+/// use actix_web::{web, HttpResponse, Responder};
+/// use actix_web_lab::{body, util::StreamMetrics};
+///
+/// async fn handler(metrics: web::Data<StreamMetrics>) -> impl Responder {
+///     let (tx, body) = body::channel::<Infallible>();
+///     # drop(tx);
+///
+///     HttpResponse::Ok().body(body::metered(body, &metrics))
+/// }
+/// ```
+pub fn metered<B: MessageBody>(body: B, metrics: &StreamMetrics) -> MeteredBody<B> {
+    MeteredBody {
+        body,
+        guard: metrics.start(),
+    }
+}
+
+pin_project! {
+    /// Body wrapper that records streaming metrics.
+    ///
+    /// Created by [`metered()`].
+    #[derive(Debug)]
+    pub struct MeteredBody<B> {
+        #[pin]
+        body: B,
+        guard: StreamGuard,
+    }
+}
+
+impl<B: MessageBody> MessageBody for MeteredBody<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+        let item = futures_util::ready!(this.body.poll_next(cx));
+
+        match &item {
+            Some(Ok(_)) => this.guard.delivered(),
+            Some(Err(_)) => this.guard.finish(DisconnectReason::Error),
+            None => this.guard.finish(DisconnectReason::Completed),
+        }
+
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, io, sync::Mutex};
+
+    use actix_web::body::{self, BodyStream};
+    use futures_util::stream;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn records_completed_body() {
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+
+        let metrics = StreamMetrics::new().on_disconnect({
+            let reasons = Arc::clone(&reasons);
+            move |reason| reasons.lock().unwrap().push(reason)
+        });
+
+        let body = metered(
+            BodyStream::new(stream::iter([
+                Ok::<_, Infallible>(Bytes::from_static(b"a")),
+                Ok(Bytes::from_static(b"b")),
+            ])),
+            &metrics,
+        );
+        assert_eq!(metrics.active(), 1);
+
+        body::to_bytes(body).await.ok().unwrap();
+
+        assert_eq!(metrics.active(), 0);
+        assert_eq!(metrics.delivered(), 2);
+        assert_eq!(metrics.ended(DisconnectReason::Completed), 1);
+        assert_eq!(*reasons.lock().unwrap(), [DisconnectReason::Completed]);
+    }
+
+    #[actix_web::test]
+    async fn records_errors_and_disconnects() {
+        let metrics = StreamMetrics::new();
+
+        let body = metered(
+            BodyStream::new(stream::iter([Err::<Bytes, _>(io::Error::other("boom"))])),
+            &metrics,
+        );
+        assert!(body::to_bytes(body).await.is_err());
+        assert_eq!(metrics.ended(DisconnectReason::Error), 1);
+
+        let body = metered(
+            BodyStream::new(stream::pending::<Result<Bytes, Infallible>>()),
+            &metrics,
+        );
+        assert_eq!(metrics.active(), 1);
+        drop(body);
+
+        assert_eq!(metrics.active(), 0);
+        assert_eq!(metrics.ended(DisconnectReason::ClientGone), 1);
+        assert_eq!(metrics.ended(DisconnectReason::Completed), 0);
+    }
+}
@@ -0,0 +1,265 @@
+//! Response streaming timeout middleware.
+//!
+//! See [`StreamTimeout`] docs.
+
+use std::{
+    future::{ready, Future as _, Ready},
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web::Bytes,
+};
+use futures_core::future::LocalBoxFuture;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Instant, Sleep};
+use tracing::warn;
+
+use crate::BoxError;
+
+/// Middleware that aborts response bodies that stall while streaming.
+///
+/// The timeout applies to the time between body chunks, starting from when the body is first
+/// polled. It is independent of how long the handler took to produce the response; it is intended
+/// to catch streams that stop making progress, such as a proxied upstream that hangs mid-response.
+///
+/// When the timeout elapses, a warning is logged that includes the matched route and the body
+/// yields a [`TimedOut`](io::ErrorKind::TimedOut) error, causing the connection to be closed.
+///
+/// Register it on a [`Scope`](actix_web::Scope) or [`Resource`](actix_web::Resource) to apply
+/// different timeouts to different groups of routes.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{web, App};
+/// use actix_web_lab::middleware::StreamTimeout;
+///
+/// App::new().service(
+///     web::scope("/stream")
+///         .wrap(StreamTimeout::new(Duration::from_secs(30)))
+///         .route("/events", web::get().to(|| async { "..." })),
+/// )
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamTimeout {
+    timeout: Duration,
+}
+
+impl StreamTimeout {
+    /// Constructs new stream timeout middleware that allows at most `timeout` between chunks.
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for StreamTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<TimeoutBody<B>>;
+    type Error = S::Error;
+    type Transform = StreamTimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(StreamTimeoutMiddleware {
+            service: Rc::new(service),
+            timeout: self.timeout,
+        }))
+    }
+}
+
+/// Middleware service implementation for [`StreamTimeout`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct StreamTimeoutMiddleware<S> {
+    service: Rc<S>,
+    timeout: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for StreamTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<TimeoutBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let timeout = self.timeout;
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            let route = res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| res.request().path().to_owned());
+
+            Ok(res.map_body(|_, body| TimeoutBody::new(body, timeout).route(route)))
+        })
+    }
+}
+
+pin_project! {
+    /// Body wrapper that errors if the inner body does not yield a chunk within a timeout.
+    ///
+    /// Created by the [`StreamTimeout`] middleware but can also be used directly.
+    pub struct TimeoutBody<B> {
+        #[pin]
+        body: B,
+        timeout: Duration,
+        route: Option<String>,
+        sleep: Option<Pin<Box<Sleep>>>,
+        // set when a chunk has been yielded since the timer was last (re)started
+        progressed: bool,
+    }
+}
+
+impl<B> TimeoutBody<B> {
+    /// Wraps `body`, allowing at most `timeout` between chunks.
+    pub fn new(body: B, timeout: Duration) -> Self {
+        Self {
+            body,
+            timeout,
+            route: None,
+            sleep: None,
+            progressed: false,
+        }
+    }
+
+    /// Sets the route reported in the warning logged when the timeout elapses.
+    pub fn route(mut self, route: impl Into<String>) -> Self {
+        self.route = Some(route.into());
+        self
+    }
+}
+
+impl<B> std::fmt::Debug for TimeoutBody<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutBody")
+            .field("timeout", &self.timeout)
+            .field("route", &self.route)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: MessageBody> MessageBody for TimeoutBody<B> {
+    type Error = BoxError;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        if let Poll::Ready(item) = this.body.poll_next(cx) {
+            *this.progressed = true;
+            return Poll::Ready(item.map(|res| res.map_err(Into::into)));
+        }
+
+        let timeout = *this.timeout;
+
+        let timer = this.sleep.get_or_insert_with(|| Box::pin(sleep(timeout)));
+
+        if *this.progressed {
+            timer.as_mut().reset(Instant::now() + timeout);
+            *this.progressed = false;
+        }
+
+        if timer.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        let route = this.route.as_deref().unwrap_or("<unknown>");
+        warn!("response stream for {route} stalled for {timeout:?}; aborting");
+
+        Poll::Ready(Some(Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "response stream timed out",
+        )
+        .into())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use actix_web::{
+        body::{self, BodyStream},
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn stalling_body() -> BodyStream<impl futures_core::Stream<Item = Result<Bytes, Infallible>>> {
+        BodyStream::new(stream::iter([Ok(Bytes::from_static(b"foo"))]).chain(stream::pending()))
+    }
+
+    #[actix_web::test]
+    async fn aborts_stalled_body() {
+        let body = TimeoutBody::new(stalling_body(), Duration::from_millis(20));
+        let err = body::to_bytes(body).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+
+    #[actix_web::test]
+    async fn slow_but_progressing_body() {
+        let chunks = stream::iter(0..5).then(|_| async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<_, Infallible>(Bytes::from_static(b"a"))
+        });
+
+        // total time exceeds timeout but each gap does not
+        let body = TimeoutBody::new(BodyStream::new(chunks), Duration::from_millis(30));
+        let bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(bytes, "aaaaa");
+    }
+
+    #[actix_web::test]
+    async fn scoped_middleware() {
+        let app = init_service(
+            App::new()
+                .service(
+                    web::scope("/stream")
+                        .wrap(StreamTimeout::new(Duration::from_millis(20)))
+                        .route(
+                            "/stall",
+                            web::get().to(|| async { HttpResponse::Ok().body(stalling_body()) }),
+                        ),
+                )
+                .route("/", web::get().to(|| async { "ok" })),
+        )
+        .await;
+
+        let req = TestRequest::get().uri("/stream/stall").to_request();
+        let res = call_service(&app, req).await;
+        assert!(body::to_bytes(res.into_body()).await.is_err());
+
+        let req = TestRequest::get().uri("/").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(body::to_bytes(res.into_body()).await.ok().unwrap(), "ok");
+    }
+}
@@ -0,0 +1,336 @@
+//! Content-Length enforcement extractor and payload adaptor.
+//!
+//! See [`StrictContentLength`] docs.
+
+use std::{
+    cell::Cell,
+    future::Future,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{ready, Context, Poll},
+};
+
+use actix_http::{error::PayloadError, BoxedPayloadStream};
+use actix_web::{dev, http::StatusCode, FromRequest, HttpRequest, ResponseError};
+use bytes::Bytes;
+use derive_more::Display;
+use futures_core::Stream;
+
+use crate::lab_config::LabConfig;
+
+/// Wraps a request payload so that its streamed size is checked against `content_length`.
+///
+/// If the payload yields more bytes than declared, an error is yielded instead of the offending
+/// chunk. If the payload ends before the declared number of bytes is received, an error is yielded
+/// before the end of the stream. Both errors result in `400 Bad Request` responses when converted
+/// directly.
+///
+/// This adaptor can be used with any extractor that reads the request body. See
+/// [`StrictContentLength`](crate::extract::StrictContentLength) for an extractor wrapper that
+/// applies it based on the request's `Content-Length` header.
+pub fn enforce_content_length(payload: &mut dev::Payload, content_length: usize) {
+    enforce(payload, content_length, Rc::default());
+}
+
+fn enforce(
+    payload: &mut dev::Payload,
+    declared: usize,
+    mismatch: Rc<Cell<Option<ContentLengthMismatch>>>,
+) {
+    let stream: BoxedPayloadStream = Box::pin(ContentLengthCheck {
+        stream: payload.take(),
+        declared,
+        received: 0,
+        done: false,
+        mismatch,
+    });
+
+    *payload = dev::Payload::from(stream);
+}
+
+/// Details of a mismatch between the declared and received payload size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[non_exhaustive]
+pub enum ContentLengthMismatch {
+    /// Payload ended before the declared length was received.
+    #[display("Payload ended after {received} of {declared} declared bytes")]
+    TooShort {
+        /// Length declared in the `Content-Length` header.
+        declared: usize,
+
+        /// Number of bytes actually received.
+        received: usize,
+    },
+
+    /// Payload continued past the declared length.
+    #[display("Payload exceeded declared length of {declared} bytes")]
+    TooLong {
+        /// Length declared in the `Content-Length` header.
+        declared: usize,
+    },
+}
+
+pin_project_lite::pin_project! {
+    struct ContentLengthCheck<S> {
+        #[pin]
+        stream: S,
+        declared: usize,
+        received: usize,
+        done: bool,
+        mismatch: Rc<Cell<Option<ContentLengthMismatch>>>,
+    }
+}
+
+impl<S> Stream for ContentLengthCheck<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>>,
+{
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                *this.received += chunk.len();
+
+                if *this.received > *this.declared {
+                    *this.done = true;
+
+                    let mismatch = ContentLengthMismatch::TooLong {
+                        declared: *this.declared,
+                    };
+                    this.mismatch.set(Some(mismatch));
+
+                    return Poll::Ready(Some(Err(PayloadError::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        mismatch.to_string(),
+                    )))));
+                }
+
+                Poll::Ready(Some(Ok(chunk)))
+            }
+
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+
+            None => {
+                *this.done = true;
+
+                if *this.received < *this.declared {
+                    let mismatch = ContentLengthMismatch::TooShort {
+                        declared: *this.declared,
+                        received: *this.received,
+                    };
+                    this.mismatch.set(Some(mismatch));
+
+                    return Poll::Ready(Some(Err(PayloadError::Incomplete(Some(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        mismatch.to_string(),
+                    ))))));
+                }
+
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+/// Extractor wrapper that rejects payloads whose size does not match their `Content-Length`.
+///
+/// The wrapped extractor reads the payload as normal. If the streamed payload is shorter or longer
+/// than the length declared in the `Content-Length` header, extraction fails with a
+/// `400 Bad Request` response, regardless of how the wrapped extractor reports payload errors.
+/// This catches broken clients and some request smuggling attempts.
+///
+/// Requests without a `Content-Length` header are passed through unchecked.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{post, web::Bytes, Responder};
+/// use actix_web_lab::extract::StrictContentLength;
+///
+/// #[post("/")]
+/// async fn handler(body: StrictContentLength<Bytes>) -> impl Responder {
+///     body.into_inner()
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct StrictContentLength<T> {
+    inner: T,
+}
+
+impl<T> StrictContentLength<T> {
+    /// Returns inner extracted type.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> FromRequest for StrictContentLength<T>
+where
+    T: FromRequest + 'static,
+{
+    type Error = StrictContentLengthError;
+    type Future = StrictContentLengthFut<T>;
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let mismatch = Rc::<Cell<Option<ContentLengthMismatch>>>::default();
+
+        if let Some(len) = LabConfig::payload_length(req) {
+            enforce(payload, len, Rc::clone(&mismatch));
+        }
+
+        StrictContentLengthFut {
+            fut: Box::pin(T::from_request(req, payload)),
+            mismatch,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct StrictContentLengthFut<T>
+where
+    T: FromRequest,
+{
+    fut: Pin<Box<T::Future>>,
+    mismatch: Rc<Cell<Option<ContentLengthMismatch>>>,
+}
+
+impl<T> Future for StrictContentLengthFut<T>
+where
+    T: FromRequest,
+{
+    type Output = Result<StrictContentLength<T>, StrictContentLengthError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = ready!(this.fut.as_mut().poll(cx));
+
+        // mismatch takes priority over however the inner extractor reports payload errors
+        if let Some(mismatch) = this.mismatch.get() {
+            return Poll::Ready(Err(StrictContentLengthError::Mismatch(mismatch)));
+        }
+
+        Poll::Ready(
+            res.map(|inner| StrictContentLength { inner })
+                .map_err(|err| StrictContentLengthError::Extractor(err.into())),
+        )
+    }
+}
+
+/// Errors that can occur when extracting a [`StrictContentLength`].
+#[derive(Debug, Display)]
+#[non_exhaustive]
+pub enum StrictContentLengthError {
+    /// Wrapped extractor failed.
+    #[display("Wrapped extractor error: {_0}")]
+    Extractor(actix_web::Error),
+
+    /// Payload size did not match the `Content-Length` header.
+    #[display("{_0}")]
+    Mismatch(ContentLengthMismatch),
+}
+
+impl ResponseError for StrictContentLengthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Extractor(err) => err.as_response_error().status_code(),
+            Self::Mismatch(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use futures_util::StreamExt as _;
+
+    use super::*;
+
+    static_assertions::assert_impl_all!(StrictContentLengthFut<Bytes>: Unpin);
+
+    fn parts(declared: &'static str, body: &'static [u8]) -> (HttpRequest, dev::Payload) {
+        TestRequest::default()
+            .insert_header((header::CONTENT_LENGTH, declared))
+            .set_payload(Bytes::from_static(body))
+            .to_http_parts()
+    }
+
+    #[actix_web::test]
+    async fn matching_length() {
+        let (req, mut pl) = parts("9", b"123456789");
+
+        let body = StrictContentLength::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), Bytes::from_static(b"123456789"));
+    }
+
+    #[actix_web::test]
+    async fn too_short() {
+        let (req, mut pl) = parts("10", b"123");
+
+        let err = StrictContentLength::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            StrictContentLengthError::Mismatch(ContentLengthMismatch::TooShort {
+                declared: 10,
+                received: 3,
+            }),
+        ));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn too_long() {
+        let (req, mut pl) = parts("2", b"123");
+
+        let err = StrictContentLength::<String>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            StrictContentLengthError::Mismatch(ContentLengthMismatch::TooLong { declared: 2 }),
+        ));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn no_content_length() {
+        // set_payload adds a Content-Length header so the payload is taken from another request
+        let (_req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(b"abc"))
+            .to_http_parts();
+        let req = TestRequest::default().to_http_request();
+        assert!(!req.headers().contains_key(header::CONTENT_LENGTH));
+
+        let body = StrictContentLength::<Bytes>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body.into_inner(), Bytes::from_static(b"abc"));
+    }
+
+    #[actix_web::test]
+    async fn payload_adaptor() {
+        let (_req, mut pl) = parts("4", b"123");
+        enforce_content_length(&mut pl, 4);
+
+        assert_eq!(
+            pl.next().await.unwrap().unwrap(),
+            Bytes::from_static(b"123")
+        );
+        assert!(matches!(
+            pl.next().await.unwrap(),
+            Err(PayloadError::Incomplete(_)),
+        ));
+        assert!(pl.next().await.is_none());
+    }
+}
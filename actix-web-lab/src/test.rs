@@ -3,8 +3,17 @@
 #[doc(inline)]
 #[cfg(test)]
 pub(crate) use crate::test_header_macros::{header_round_trip_test, header_test_module};
+#[cfg(feature = "record")]
+pub use crate::test_replay::replay;
 #[doc(inline)]
 pub use crate::test_request_macros::test_request;
 #[doc(inline)]
 pub use crate::test_response_macros::assert_response_matches;
-pub use crate::test_services::echo_path_service;
+#[doc(inline)]
+pub use crate::test_snapshot::assert_snapshot;
+pub use crate::{
+    test_diff::{diff_responses, ResponseDiff},
+    test_probe::{Probe, ProbeEvent, ProbeLog, ProbeService},
+    test_services::echo_path_service,
+    test_snapshot::Snapshot,
+};
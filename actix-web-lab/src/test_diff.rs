@@ -0,0 +1,299 @@
+//! Structural response diffs.
+//!
+//! See [`diff_responses`] docs.
+
+use std::{collections::BTreeSet, fmt};
+
+use actix_web::{
+    body::{self, MessageBody},
+    dev::ServiceResponse,
+    web::Bytes,
+};
+use serde_json::Value;
+
+/// Differences between two responses, returned from [`diff_responses()`].
+///
+/// Displays one line per difference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseDiff {
+    entries: Vec<String>,
+}
+
+impl ResponseDiff {
+    /// Returns true if no differences were found.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the differences found, one per entry.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+}
+
+impl fmt::Display for ResponseDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() {
+            return f.write_str("responses are identical");
+        }
+
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares two responses and describes the differences between them.
+///
+/// - Status codes are compared directly.
+/// - Headers are compared without regard to order; each header name is compared using the sorted
+///   set of its values.
+/// - If both bodies are valid JSON, they are compared structurally, so formatting and object member
+///   order are ignored and differences are reported by path (e.g., `$.items[1].id`). Otherwise,
+///   bodies are compared byte-by-byte and the first differing offset is reported.
+///
+/// Useful for checking that a refactored service (or a proxy or cache in front of it) responds the
+/// same way as the original.
+///
+/// # Panics
+/// Panics if either body can not be read.
+///
+/// # Examples
+/// ```
+/// use actix_web::{dev::ServiceResponse, test::TestRequest, HttpResponse};
+/// use actix_web_lab::test::diff_responses;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let req = TestRequest::default().to_http_request();
+/// let a = ServiceResponse::new(req.clone(), HttpResponse::Ok().body(r#"{"a":1,"b":2}"#));
+/// let b = ServiceResponse::new(req, HttpResponse::Ok().body(r#"{ "b": 2, "a": 1 }"#));
+///
+/// let diff = diff_responses(a, b).await;
+/// assert!(diff.is_empty(), "{diff}");
+/// # });
+/// ```
+pub async fn diff_responses<A, B>(a: ServiceResponse<A>, b: ServiceResponse<B>) -> ResponseDiff
+where
+    A: MessageBody,
+    B: MessageBody,
+{
+    let mut entries = Vec::new();
+
+    if a.status() != b.status() {
+        entries.push(format!("status: {} != {}", a.status(), b.status()));
+    }
+
+    let names = a
+        .headers()
+        .keys()
+        .chain(b.headers().keys())
+        .map(|name| name.as_str())
+        .collect::<BTreeSet<_>>();
+
+    for name in names {
+        let a_vals = sorted_values(&a, name);
+        let b_vals = sorted_values(&b, name);
+
+        if a_vals != b_vals {
+            entries.push(format!("header {name}: {a_vals:?} != {b_vals:?}"));
+        }
+    }
+
+    let a_body = read_body(a).await;
+    let b_body = read_body(b).await;
+
+    match (
+        serde_json::from_slice::<Value>(&a_body),
+        serde_json::from_slice::<Value>(&b_body),
+    ) {
+        (Ok(a_json), Ok(b_json)) => diff_json("$", &a_json, &b_json, &mut entries),
+        _ => diff_bytes(&a_body, &b_body, &mut entries),
+    }
+
+    ResponseDiff { entries }
+}
+
+fn sorted_values<B>(res: &ServiceResponse<B>, name: &str) -> Vec<String> {
+    let mut vals = res
+        .headers()
+        .get_all(name)
+        .map(|val| String::from_utf8_lossy(val.as_bytes()).into_owned())
+        .collect::<Vec<_>>();
+
+    vals.sort_unstable();
+    vals
+}
+
+async fn read_body<B: MessageBody>(res: ServiceResponse<B>) -> Bytes {
+    match body::to_bytes(res.into_body()).await {
+        Ok(body) => body,
+        Err(_) => panic!("could not read response body"),
+    }
+}
+
+fn diff_json(path: &str, a: &Value, b: &Value, entries: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys = a.keys().chain(b.keys()).collect::<BTreeSet<_>>();
+
+            for key in keys {
+                let path = format!("{path}.{key}");
+
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_json(&path, a, b, entries),
+                    (Some(a), None) => entries.push(format!("body {path}: {a} != (missing)")),
+                    (None, Some(b)) => entries.push(format!("body {path}: (missing) != {b}")),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+
+        (Value::Array(a_items), Value::Array(b_items)) => {
+            for (idx, (a, b)) in a_items.iter().zip(b_items).enumerate() {
+                diff_json(&format!("{path}[{idx}]"), a, b, entries);
+            }
+
+            if a_items.len() != b_items.len() {
+                entries.push(format!(
+                    "body {path}: array length {} != {}",
+                    a_items.len(),
+                    b_items.len(),
+                ));
+            }
+        }
+
+        (a, b) if a != b => entries.push(format!("body {path}: {a} != {b}")),
+
+        _ => {}
+    }
+}
+
+fn diff_bytes(a: &[u8], b: &[u8], entries: &mut Vec<String>) {
+    let Some(offset) = a
+        .iter()
+        .zip(b)
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then_some(a.len().min(b.len())))
+    else {
+        return;
+    };
+
+    // show some context around the first difference
+    let start = offset.saturating_sub(16);
+    let excerpt = |body: &[u8]| {
+        let end = (offset + 16).min(body.len());
+        let excerpt = String::from_utf8_lossy(&body[start.min(end)..end]).into_owned();
+        format!("{excerpt:?}")
+    };
+
+    entries.push(format!(
+        "body differs at byte {offset} (lengths {} and {}): {} != {}",
+        a.len(),
+        b.len(),
+        excerpt(a),
+        excerpt(b),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test::TestRequest, HttpResponse, HttpResponseBuilder};
+
+    use super::*;
+
+    fn res(mut builder: HttpResponseBuilder, body: &'static str) -> ServiceResponse {
+        ServiceResponse::new(TestRequest::default().to_http_request(), builder.body(body))
+    }
+
+    #[actix_web::test]
+    async fn identical() {
+        let a = res(
+            HttpResponse::Ok()
+                .append_header(("x-a", "1"))
+                .append_header(("x-b", "2"))
+                .append_header(("x-b", "3"))
+                .take(),
+            "hello",
+        );
+        let b = res(
+            HttpResponse::Ok()
+                .append_header(("x-b", "3"))
+                .append_header(("x-a", "1"))
+                .append_header(("x-b", "2"))
+                .take(),
+            "hello",
+        );
+
+        let diff = diff_responses(a, b).await;
+        assert!(diff.is_empty(), "{diff}");
+        assert_eq!(diff.to_string(), "responses are identical");
+    }
+
+    #[actix_web::test]
+    async fn status_and_headers() {
+        let a = res(HttpResponse::Ok().append_header(("x-a", "1")).take(), "");
+        let b = res(
+            HttpResponse::NotFound()
+                .append_header(("x-a", "2"))
+                .append_header(("x-b", "3"))
+                .take(),
+            "",
+        );
+
+        let diff = diff_responses(a, b).await;
+        assert_eq!(
+            diff.entries(),
+            [
+                "status: 200 OK != 404 Not Found",
+                r#"header x-a: ["1"] != ["2"]"#,
+                r#"header x-b: [] != ["3"]"#,
+            ],
+        );
+    }
+
+    #[actix_web::test]
+    async fn json_bodies() {
+        let a = res(
+            HttpResponse::Ok().take(),
+            r#"{"id":1,"tags":["a","b"],"user":{"name":"x"},"old":true}"#,
+        );
+        let b = res(
+            HttpResponse::Ok().take(),
+            r#"{ "user": { "name": "y" }, "tags": ["a", "c", "d"], "id": 1 }"#,
+        );
+
+        let diff = diff_responses(a, b).await;
+        assert_eq!(
+            diff.entries(),
+            [
+                "body $.old: true != (missing)",
+                r#"body $.tags[1]: "b" != "c""#,
+                "body $.tags: array length 2 != 3",
+                r#"body $.user.name: "x" != "y""#,
+            ],
+        );
+    }
+
+    #[actix_web::test]
+    async fn byte_bodies() {
+        let a = res(HttpResponse::Ok().take(), "hello world");
+        let b = res(HttpResponse::Ok().take(), "hello there");
+
+        let diff = diff_responses(a, b).await;
+        assert_eq!(
+            diff.entries(),
+            [r#"body differs at byte 6 (lengths 11 and 11): "hello world" != "hello there""#],
+        );
+
+        let a = res(HttpResponse::Ok().take(), "hello");
+        let b = res(HttpResponse::Ok().take(), "hello!");
+
+        let diff = diff_responses(a, b).await;
+        assert_eq!(
+            diff.entries(),
+            [r#"body differs at byte 5 (lengths 5 and 6): "hello" != "hello!""#],
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Middleware ordering probes.
+//!
+//! See [`ProbeLog`] docs.
+
+use std::{
+    any::type_name,
+    cell::RefCell,
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+
+/// An event recorded by a [`ProbeLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProbeEvent {
+    /// A request passed into the named probe middleware.
+    Request {
+        /// Label of the probe.
+        label: String,
+    },
+
+    /// A request reached the terminal [`ProbeService`].
+    Handler,
+
+    /// A response passed out of the named probe middleware.
+    Response {
+        /// Label of the probe.
+        label: String,
+
+        /// Status code of the response.
+        status: StatusCode,
+
+        /// Type name of the response body at this point in the stack.
+        body_type: &'static str,
+    },
+
+    /// An error passed out of the named probe middleware.
+    Error {
+        /// Label of the probe.
+        label: String,
+    },
+}
+
+/// Formats events compactly: `req:<label>`, `handler`, `res:<label>`, or `err:<label>`.
+impl fmt::Display for ProbeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeEvent::Request { label } => write!(f, "req:{label}"),
+            ProbeEvent::Handler => f.write_str("handler"),
+            ProbeEvent::Response { label, .. } => write!(f, "res:{label}"),
+            ProbeEvent::Error { label } => write!(f, "err:{label}"),
+        }
+    }
+}
+
+/// Shared log of execution events used to verify middleware ordering.
+///
+/// Create [`Probe`] middleware with [`probe()`](Self::probe) and wrap them between the middleware
+/// under test. Each probe records when a request passes in and when a response (including its body
+/// type) passes out. A terminal [`ProbeService`] can be created with [`service()`](Self::service).
+///
+/// Cloning a `ProbeLog` produces a handle to the same log.
+///
+/// # Examples
+/// ```
+/// use actix_web::{test, App};
+/// use actix_web_lab::test::ProbeLog;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let log = ProbeLog::new();
+///
+/// let app = test::init_service(
+///     App::new()
+///         .wrap(log.probe("inner"))
+///         .wrap(log.probe("outer"))
+///         .default_service(log.service()),
+/// )
+/// .await;
+///
+/// test::call_service(&app, test::TestRequest::default().to_request()).await;
+///
+/// log.assert_order(&[
+///     "req:outer",
+///     "req:inner",
+///     "handler",
+///     "res:inner",
+///     "res:outer",
+/// ]);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProbeLog {
+    events: Rc<RefCell<Vec<ProbeEvent>>>,
+}
+
+impl ProbeLog {
+    /// Constructs a new, empty probe log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a probe middleware that records events to this log under `label`.
+    pub fn probe(&self, label: impl Into<String>) -> Probe {
+        Probe {
+            label: label.into(),
+            log: self.clone(),
+        }
+    }
+
+    /// Returns a terminal service that records a [`ProbeEvent::Handler`] event and responds with
+    /// an empty `200 OK` response.
+    pub fn service(&self) -> ProbeService {
+        ProbeService {
+            log: self.clone(),
+            status: StatusCode::OK,
+        }
+    }
+
+    /// Returns a copy of the recorded events.
+    pub fn events(&self) -> Vec<ProbeEvent> {
+        self.events.borrow().clone()
+    }
+
+    /// Clears all recorded events.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear();
+    }
+
+    /// Returns the response body type name recorded by the probe labelled `label`, if any.
+    ///
+    /// Useful for asserting on body type nesting (e.g., `EitherBody`) at a given point in the stack.
+    pub fn body_type(&self, label: &str) -> Option<&'static str> {
+        self.events.borrow().iter().find_map(|ev| match ev {
+            ProbeEvent::Response {
+                label: ev_label,
+                body_type,
+                ..
+            } if ev_label == label => Some(*body_type),
+            _ => None,
+        })
+    }
+
+    /// Asserts that the recorded events, in their compact display form, match `expected`.
+    ///
+    /// # Panics
+    /// Panics if the recorded events do not match.
+    #[track_caller]
+    pub fn assert_order(&self, expected: &[&str]) {
+        let actual = self
+            .events
+            .borrow()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, expected, "probe events do not match expected order");
+    }
+
+    fn record(&self, event: ProbeEvent) {
+        self.events.borrow_mut().push(event);
+    }
+}
+
+/// Middleware that records request and response events to a [`ProbeLog`].
+///
+/// See [`ProbeLog::probe()`].
+#[derive(Debug, Clone)]
+pub struct Probe {
+    label: String,
+    log: ProbeLog,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Probe
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ProbeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ProbeMiddleware {
+            service: Rc::new(service),
+            label: self.label.clone(),
+            log: self.log.clone(),
+        }))
+    }
+}
+
+/// Middleware that records request and response events to a [`ProbeLog`].
+///
+/// See [`Probe`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct ProbeMiddleware<S> {
+    service: Rc<S>,
+    label: String,
+    log: ProbeLog,
+}
+
+impl<S, B> Service<ServiceRequest> for ProbeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let label = self.label.clone();
+        let log = self.log.clone();
+
+        Box::pin(async move {
+            log.record(ProbeEvent::Request {
+                label: label.clone(),
+            });
+
+            match service.call(req).await {
+                Ok(res) => {
+                    log.record(ProbeEvent::Response {
+                        label,
+                        status: res.status(),
+                        body_type: type_name::<B>(),
+                    });
+
+                    Ok(res)
+                }
+
+                Err(err) => {
+                    log.record(ProbeEvent::Error { label });
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+/// Terminal service that records a [`ProbeEvent::Handler`] event to a [`ProbeLog`].
+///
+/// Can be used as both a service and a service factory (e.g., with `App::default_service()`).
+///
+/// See [`ProbeLog::service()`].
+#[derive(Debug, Clone)]
+pub struct ProbeService {
+    log: ProbeLog,
+    status: StatusCode,
+}
+
+impl ProbeService {
+    /// Sets the status code used for responses.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl Service<ServiceRequest> for ProbeService {
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::always_ready!();
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        self.log.record(ProbeEvent::Handler);
+        ready(Ok(req.into_response(HttpResponse::new(self.status))))
+    }
+}
+
+impl ServiceFactory<ServiceRequest> for ProbeService {
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Config = ();
+    type Service = ProbeService;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _cfg: Self::Config) -> Self::Future {
+        ready(Ok(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::EitherBody, middleware::Condition, test, App};
+
+    use super::*;
+    use crate::middleware::{CatchPanic, RedirectHttps};
+
+    #[actix_web::test]
+    async fn records_wrap_order() {
+        let log = ProbeLog::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(log.probe("a"))
+                .wrap(CatchPanic::default())
+                .wrap(log.probe("b"))
+                .default_service(log.service().status(StatusCode::IM_A_TEAPOT)),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+
+        log.assert_order(&["req:b", "req:a", "handler", "res:a", "res:b"]);
+
+        log.clear();
+        assert!(log.events().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn records_body_types() {
+        let log = ProbeLog::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(log.probe("inner"))
+                .wrap(RedirectHttps::default())
+                .wrap(log.probe("outer"))
+                .default_service(log.service()),
+        )
+        .await;
+
+        let req = test::TestRequest::default()
+            .uri("https://localhost/")
+            .to_request();
+        test::call_service(&app, req).await;
+
+        assert_eq!(log.body_type("inner"), Some(type_name::<BoxBody>()));
+        assert_eq!(
+            log.body_type("outer"),
+            Some(type_name::<EitherBody<BoxBody, ()>>()),
+        );
+        assert!(log.body_type("missing").is_none());
+    }
+
+    #[actix_web::test]
+    async fn condition_skips_middleware() {
+        let log = ProbeLog::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(Condition::new(false, log.probe("disabled")))
+                .wrap(log.probe("enabled"))
+                .default_service(log.service()),
+        )
+        .await;
+
+        test::call_service(&app, test::TestRequest::default().to_request()).await;
+
+        log.assert_order(&["req:enabled", "handler", "res:enabled"]);
+        assert_eq!(
+            log.body_type("enabled"),
+            Some(type_name::<EitherBody<BoxBody>>()),
+        );
+    }
+}
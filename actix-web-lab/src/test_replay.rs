@@ -0,0 +1,159 @@
+//! Replay of recorded request/response pairs.
+//!
+//! See [`replay()`] docs.
+
+use actix_web::{
+    body::{self, MessageBody},
+    dev::{Service, ServiceResponse},
+    http::Method,
+    test::TestRequest,
+    Error,
+};
+
+use crate::middleware::Recording;
+
+/// Replays recorded requests against `app`, asserting that responses match the recordings.
+///
+/// Each recorded request is sent in order, including its headers and body. The response status
+/// must match the recorded status and, unless the recorded response body was truncated, the
+/// response body must match the recorded body. Response headers are not compared since they often
+/// contain dynamic values.
+///
+/// Recordings are made using the [`Record`](crate::middleware::Record) middleware. Headers that
+/// were redacted during recording are sent with their redacted values.
+///
+/// # Panics
+/// Panics with a description of the first recording whose response does not match.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{test, web, App};
+/// use actix_web_lab::{middleware::RecordingFile, test::replay};
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let app = test::init_service(App::new().route("/", web::get().to(|| async { "hello" }))).await;
+///
+/// let recordings = RecordingFile::read("tests/recordings/routing.ndjson").unwrap();
+/// replay(&app, recordings).await;
+/// # });
+/// ```
+pub async fn replay<S, B>(app: &S, recordings: impl IntoIterator<Item = Recording>)
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    for (idx, recording) in recordings.into_iter().enumerate() {
+        let recorded_req = &recording.request;
+        let label = format!(
+            "recording #{idx} ({} {})",
+            recorded_req.method, recorded_req.uri
+        );
+
+        let method = recorded_req
+            .method
+            .parse::<Method>()
+            .unwrap_or_else(|_| panic!("{label}: invalid method"));
+
+        let mut req = TestRequest::default().method(method).uri(&recorded_req.uri);
+
+        for (name, value) in &recorded_req.headers {
+            req = req.append_header((name.as_str(), value.as_str()));
+        }
+
+        if let Some(body) = recorded_req.body.bytes() {
+            req = req.set_payload(body);
+        }
+
+        let res = app
+            .call(req.to_request())
+            .await
+            .unwrap_or_else(|err| panic!("{label}: service returned an error: {err}"));
+
+        assert_eq!(
+            res.status().as_u16(),
+            recording.response.status,
+            "{label}: status does not match recording",
+        );
+
+        let recorded_body = &recording.response.body;
+
+        if recorded_body.is_truncated() {
+            continue;
+        }
+
+        let body = body::to_bytes(res.into_body())
+            .await
+            .unwrap_or_else(|_| panic!("{label}: failed to read response body"));
+
+        let expected = recorded_body
+            .bytes()
+            .unwrap_or_else(|| panic!("{label}: recorded response body is invalid"));
+
+        assert_eq!(body, expected, "{label}: body does not match recording");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_service, init_service},
+        web, App, HttpResponse,
+    };
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::middleware::Record;
+
+    fn app_config(cfg: &mut web::ServiceConfig) {
+        cfg.route("/", web::get().to(|| async { "index" })).route(
+            "/echo",
+            web::post().to(|body: web::Bytes| async move { HttpResponse::Ok().body(body) }),
+        );
+    }
+
+    async fn record() -> Vec<Recording> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = init_service(App::new().wrap(Record::new(tx)).configure(app_config)).await;
+
+        for req in [
+            TestRequest::get().uri("/"),
+            TestRequest::post().uri("/echo").set_payload("abc"),
+            TestRequest::get().uri("/missing"),
+        ] {
+            let res = call_service(&app, req.to_request()).await;
+            body::to_bytes(res.into_body()).await.unwrap();
+        }
+
+        let mut recordings = Vec::new();
+
+        while let Ok(recording) = rx.try_recv() {
+            recordings.push(recording);
+        }
+
+        recordings
+    }
+
+    #[actix_web::test]
+    async fn replays_recordings() {
+        let recordings = record().await;
+        assert_eq!(recordings.len(), 3);
+
+        let app = init_service(App::new().configure(app_config)).await;
+        replay(&app, recordings).await;
+    }
+
+    #[actix_web::test]
+    #[should_panic = "status does not match recording"]
+    async fn detects_regressions() {
+        let recordings = record().await;
+
+        // index route has been removed
+        let app = init_service(App::new().route(
+            "/echo",
+            web::post().to(|body: web::Bytes| async move { HttpResponse::Ok().body(body) }),
+        ))
+        .await;
+
+        replay(&app, recordings).await;
+    }
+}
@@ -0,0 +1,364 @@
+//! Golden-file response snapshots.
+//!
+//! See [`Snapshot`] and [`assert_snapshot`](crate::assert_snapshot) docs.
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use actix_web::{
+    body::{self, MessageBody},
+    dev::ServiceResponse,
+    http::header::{self, HeaderName},
+};
+use regex::Regex;
+
+use crate::util::RedactionPolicy;
+
+/// Environment variable that, when set to a non-empty value, causes existing snapshots to be
+/// overwritten instead of compared.
+const UPDATE_ENV_VAR: &str = "ACTIX_WEB_LAB_UPDATE_SNAPSHOTS";
+
+/// Placeholder text that replaces values of redacted headers.
+const REDACTED: &str = "[redacted]";
+
+type Redactor = Box<dyn Fn(&str) -> String>;
+
+/// Golden-file snapshot of a response's status, selected headers, and body.
+///
+/// Snapshots are stored in `<CARGO_MANIFEST_DIR>/tests/snapshots/<name>.snap` by default. When the
+/// snapshot file does not yet exist, it is created and the assertion passes. Set the
+/// `ACTIX_WEB_LAB_UPDATE_SNAPSHOTS` environment variable to re-record existing snapshots.
+///
+/// By default, only the `Content-Type` header is recorded. Headers whose values change on each run
+/// (e.g., `Date` or request IDs) can be recorded with a placeholder value using
+/// [`redact_header()`](Self::redact_header). Dynamic parts of header values and bodies can be
+/// replaced using [`redact()`](Self::redact) or [`redact_with()`](Self::redact_with). Sensitive
+/// headers, such as `Set-Cookie`, are redacted according to the default [`RedactionPolicy`] unless
+/// a different one is set using [`redaction_policy()`](Self::redaction_policy).
+///
+/// Usually used through the [`assert_snapshot!`](crate::assert_snapshot) macro.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{
+///     dev::ServiceResponse, http::header::HeaderName, test::TestRequest, HttpResponse,
+/// };
+/// use actix_web_lab::{assert_snapshot, test::Snapshot};
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let res = ServiceResponse::new(
+///     TestRequest::default().to_http_request(),
+///     HttpResponse::Ok()
+///         .insert_header(("x-request-id", "4b1d"))
+///         .body("generated at 2024-01-01T00:00:00Z"),
+/// );
+///
+/// assert_snapshot!(
+///     res,
+///     Snapshot::new("report")
+///         .redact_header(HeaderName::from_static("x-request-id"))
+///         .redact(r"\d{4}-\d{2}-\d{2}T[\d:]+Z", "[timestamp]")
+/// );
+/// # });
+/// ```
+pub struct Snapshot {
+    name: String,
+    dir: Option<PathBuf>,
+    headers: Vec<HeaderName>,
+    redacted_headers: Vec<HeaderName>,
+    redaction_policy: RedactionPolicy,
+    redactors: Vec<Redactor>,
+}
+
+impl Snapshot {
+    /// Constructs a new snapshot with the given name.
+    ///
+    /// The name is used as the snapshot's file stem.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            dir: None,
+            headers: vec![header::CONTENT_TYPE],
+            redacted_headers: Vec::new(),
+            redaction_policy: RedactionPolicy::default(),
+            redactors: Vec::new(),
+        }
+    }
+
+    /// Sets the directory in which the snapshot file is stored.
+    ///
+    /// Relative paths are resolved against `CARGO_MANIFEST_DIR`, if set.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Includes the named header in the snapshot.
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.headers.push(name);
+        self
+    }
+
+    /// Includes the named header in the snapshot, replacing its value with a placeholder.
+    ///
+    /// Useful for headers like `Date` whose value changes on every run.
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.headers.push(name.clone());
+        self.redacted_headers.push(name);
+        self
+    }
+
+    /// Sets the policy used to redact sensitive header values.
+    pub fn redaction_policy(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction_policy = policy;
+        self
+    }
+
+    /// Replaces all matches of the regular expression `pattern` in header values and the body.
+    ///
+    /// # Panics
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn redact(self, pattern: &str, replacement: impl Into<String>) -> Self {
+        let re = Regex::new(pattern).expect("invalid snapshot redaction pattern");
+        let replacement = replacement.into();
+
+        self.redact_with(move |text| re.replace_all(text, replacement.as_str()).into_owned())
+    }
+
+    /// Applies a custom redaction function to header values and the body.
+    pub fn redact_with(mut self, redactor: impl Fn(&str) -> String + 'static) -> Self {
+        self.redactors.push(Box::new(redactor));
+        self
+    }
+
+    /// Compares the response to the stored snapshot, recording it first if necessary.
+    ///
+    /// # Panics
+    /// Panics if the response does not match the stored snapshot or if the snapshot file can not
+    /// be read or written.
+    pub async fn assert<B>(self, res: ServiceResponse<B>)
+    where
+        B: MessageBody,
+    {
+        let actual = self.render(res).await;
+        let path = self.path();
+
+        let update = env::var_os(UPDATE_ENV_VAR).is_some_and(|val| !val.is_empty());
+
+        if update || !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("could not create snapshot directory");
+            }
+
+            fs::write(&path, &actual).expect("could not write snapshot file");
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).expect("could not read snapshot file");
+
+        if expected != actual {
+            panic!(
+                "response does not match snapshot `{}` ({})\n\n--- expected\n{expected}\n--- actual\n{actual}\n\nset {UPDATE_ENV_VAR}=1 to update snapshot",
+                self.name,
+                path.display(),
+            );
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from);
+
+        let dir = match (&self.dir, manifest_dir) {
+            (Some(dir), _) if dir.is_absolute() => dir.clone(),
+            (Some(dir), Some(manifest_dir)) => manifest_dir.join(dir),
+            (Some(dir), None) => dir.clone(),
+            (None, Some(manifest_dir)) => manifest_dir.join("tests").join("snapshots"),
+            (None, None) => Path::new("tests").join("snapshots"),
+        };
+
+        dir.join(format!("{}.snap", self.name))
+    }
+
+    async fn render<B>(&self, res: ServiceResponse<B>) -> String
+    where
+        B: MessageBody,
+    {
+        let mut out = format!("status: {}\n", res.status());
+
+        for name in &self.headers {
+            for val in res.headers().get_all(name) {
+                let val = if self.redacted_headers.contains(name) {
+                    REDACTED.to_owned()
+                } else {
+                    let val = self.redaction_policy.redact_value(name, val);
+                    self.apply_redactions(&String::from_utf8_lossy(val.as_bytes()))
+                };
+
+                out.push_str(&format!("{name}: {val}\n"));
+            }
+        }
+
+        out.push('\n');
+
+        let body = body::to_bytes(res.into_body())
+            .await
+            .map_err(Into::<crate::BoxError>::into)
+            .expect("could not read response body");
+
+        match std::str::from_utf8(&body) {
+            Ok(body) => out.push_str(&self.apply_redactions(body)),
+            Err(_) => out.push_str(&self.apply_redactions(&body.escape_ascii().to_string())),
+        }
+
+        out
+    }
+
+    fn apply_redactions(&self, text: &str) -> String {
+        self.redactors
+            .iter()
+            .fold(text.to_owned(), |text, redactor| redactor(&text))
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("name", &self.name)
+            .field("dir", &self.dir)
+            .field("headers", &self.headers)
+            .field("redacted_headers", &self.redacted_headers)
+            .field("redactors", &self.redactors.len())
+            .finish()
+    }
+}
+
+/// Asserts that a `ServiceResponse` matches a golden-file snapshot.
+///
+/// Takes either a snapshot name or a configured [`Snapshot`](crate::test::Snapshot). Must be used
+/// in an async context (e.g., `#[actix_web::test]`).
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{dev::ServiceResponse, test::TestRequest, HttpResponse};
+/// use actix_web_lab::assert_snapshot;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let res = ServiceResponse::new(
+///     TestRequest::default().to_http_request(),
+///     HttpResponse::Ok().body("Hello World!"),
+/// );
+///
+/// assert_snapshot!(res, "hello");
+/// # });
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($res:expr, $name:literal) => {{
+        $crate::test::Snapshot::new($name).assert($res).await
+    }};
+
+    ($res:expr, $snapshot:expr) => {{
+        $snapshot.assert($res).await
+    }};
+}
+
+pub use assert_snapshot;
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header::HeaderValue, test::TestRequest, HttpResponse};
+
+    use super::*;
+
+    fn response(body: &'static str) -> ServiceResponse {
+        ServiceResponse::new(
+            TestRequest::default().to_http_request(),
+            HttpResponse::Ok()
+                .insert_header(header::ContentType::plaintext())
+                .insert_header((header::DATE, HeaderValue::from_static("today")))
+                .insert_header(("x-request-id", "abc123"))
+                .body(body),
+        )
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "actix-web-lab-snapshot-{name}-{}",
+            std::process::id(),
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[actix_web::test]
+    async fn renders_selected_headers() {
+        let snap = Snapshot::new("test").redact_header(header::DATE);
+
+        assert_eq!(
+            snap.render(response("body")).await,
+            "status: 200 OK\ncontent-type: text/plain; charset=utf-8\ndate: [redacted]\n\nbody",
+        );
+    }
+
+    #[actix_web::test]
+    async fn redaction_hooks() {
+        let snap = Snapshot::new("test")
+            .header(HeaderName::from_static("x-request-id"))
+            .redact(r"\d+", "N")
+            .redact_with(|text| text.to_uppercase());
+
+        assert_eq!(
+            snap.render(response("id 42")).await,
+            "status: 200 OK\ncontent-type: TEXT/PLAIN; CHARSET=UTF-N\nx-request-id: ABCN\n\nID N",
+        );
+    }
+
+    #[actix_web::test]
+    async fn sensitive_headers() {
+        let res = ServiceResponse::new(
+            TestRequest::default().to_http_request(),
+            HttpResponse::Ok()
+                .insert_header((header::SET_COOKIE, "session=secret"))
+                .finish(),
+        );
+
+        let snap = Snapshot::new("test").header(header::SET_COOKIE);
+        assert_eq!(
+            snap.render(res).await,
+            "status: 200 OK\nset-cookie: [redacted]\n\n",
+        );
+    }
+
+    #[actix_web::test]
+    async fn records_then_compares() {
+        let dir = temp_dir("match");
+
+        Snapshot::new("greeting")
+            .dir(&dir)
+            .assert(response("hello"))
+            .await;
+        assert!(dir.join("greeting.snap").exists());
+
+        assert_snapshot!(response("hello"), Snapshot::new("greeting").dir(&dir));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[actix_web::test]
+    #[should_panic = "response does not match snapshot"]
+    async fn mismatch_panics() {
+        let dir = temp_dir("mismatch");
+
+        Snapshot::new("greeting")
+            .dir(&dir)
+            .assert(response("hello"))
+            .await;
+
+        Snapshot::new("greeting")
+            .dir(&dir)
+            .assert(response("goodbye"))
+            .await;
+    }
+}
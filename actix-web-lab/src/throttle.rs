@@ -0,0 +1,495 @@
+//! Response body bandwidth throttling.
+//!
+//! See [`throttle()`], [`TokenBucket`], and [`Throttle`] docs.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{ready, Future as _, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web::Bytes,
+};
+use futures_core::future::LocalBoxFuture;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Instant, Sleep};
+
+/// Token bucket that limits throughput to a number of bytes per second.
+///
+/// The bucket starts full and holds at most one second's worth of tokens unless a different burst
+/// size is set using [`burst()`](Self::burst). Clones share the same bucket, so bodies throttled
+/// with clones of a bucket share its bandwidth fairly.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, HttpResponse, Responder};
+/// use actix_web_lab::{body, util::TokenBucket};
+///
+/// // shared by all exports across workers
+/// let bucket = TokenBucket::new(10 * 1024 * 1024);
+///
+/// async fn export(bucket: web::Data<TokenBucket>) -> impl Responder {
+///     let data = vec![0; 64 * 1024];
+///     HttpResponse::Ok().body(body::throttle_with(data, bucket.get_ref().clone()))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TokenBucket {
+    state: Arc<Mutex<BucketState>>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Constructs new token bucket allowing `bytes_per_sec` bytes per second.
+    ///
+    /// # Panics
+    /// Panics if `bytes_per_sec` is zero.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "throttle rate must be non-zero");
+
+        let rate = bytes_per_sec as f64;
+
+        Self {
+            state: Arc::new(Mutex::new(BucketState {
+                rate,
+                burst: rate,
+                tokens: rate,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Sets the maximum number of bytes that can be sent at once after a period of inactivity.
+    ///
+    /// # Panics
+    /// Panics if `bytes` is zero.
+    pub fn burst(self, bytes: u64) -> Self {
+        assert!(bytes > 0, "throttle burst size must be non-zero");
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.burst = bytes as f64;
+            state.tokens = state.tokens.min(state.burst);
+        }
+
+        self
+    }
+
+    /// Takes up to `max` tokens from the bucket.
+    ///
+    /// Returns the number of tokens taken or, if the bucket is empty, how long to wait before
+    /// trying again.
+    fn take(&self, max: usize) -> Result<usize, Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * state.rate).min(state.burst);
+        state.updated = now;
+
+        if state.tokens >= 1.0 {
+            let taken = (state.tokens as usize).min(max);
+            state.tokens -= taken as f64;
+            return Ok(taken);
+        }
+
+        // wait until a reasonably sized chunk can be sent
+        let wanted = (max as f64).min(state.burst);
+        Err(Duration::from_secs_f64(
+            (wanted - state.tokens) / state.rate,
+        ))
+    }
+}
+
+impl fmt::Debug for TokenBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+
+        f.debug_struct("TokenBucket")
+            .field("rate", &state.rate)
+            .field("burst", &state.burst)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Set of [`TokenBucket`]s keyed by name, created on first use.
+///
+/// Useful as app data for sharing bandwidth between responses for the same user or endpoint.
+/// Clones share the same buckets. Buckets that are no longer used by any response are discarded
+/// when new buckets are created.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, HttpRequest, HttpResponse, Responder};
+/// use actix_web_lab::{body, util::TokenBuckets};
+///
+/// async fn export(req: HttpRequest, buckets: web::Data<TokenBuckets>) -> impl Responder {
+///     let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+///     let bucket = buckets.bucket(ip.unwrap_or_default());
+///
+///     HttpResponse::Ok().body(body::throttle_with(vec![0; 64 * 1024], bucket))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TokenBuckets {
+    bytes_per_sec: u64,
+    burst: Option<u64>,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl TokenBuckets {
+    /// Constructs new set of buckets that each allow `bytes_per_sec` bytes per second.
+    ///
+    /// # Panics
+    /// Panics if `bytes_per_sec` is zero.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "throttle rate must be non-zero");
+
+        Self {
+            bytes_per_sec,
+            burst: None,
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Sets the burst size of created buckets.
+    ///
+    /// See [`TokenBucket::burst()`].
+    pub fn burst(mut self, bytes: u64) -> Self {
+        self.burst = Some(bytes);
+        self
+    }
+
+    /// Returns the bucket for `key`, creating it if necessary.
+    pub fn bucket(&self, key: impl Into<String>) -> TokenBucket {
+        let key = key.into();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if let Some(bucket) = buckets.get(&key) {
+            return bucket.clone();
+        }
+
+        // discard buckets not held by any in-flight response
+        buckets.retain(|_, bucket| Arc::strong_count(&bucket.state) > 1);
+
+        let mut bucket = TokenBucket::new(self.bytes_per_sec);
+
+        if let Some(burst) = self.burst {
+            bucket = bucket.burst(burst);
+        }
+
+        buckets.insert(key, bucket.clone());
+        bucket
+    }
+}
+
+impl fmt::Debug for TokenBuckets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenBuckets")
+            .field("bytes_per_sec", &self.bytes_per_sec)
+            .field("burst", &self.burst)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Throttles `body` to stream at most `bytes_per_sec` bytes per second.
+///
+/// Chunks from the wrapped body are split as needed so that no more than one second's worth of
+/// data is sent at once.
+///
+/// # Panics
+/// Panics if `bytes_per_sec` is zero.
+///
+/// # Examples
+/// ```
+/// use actix_web::{HttpResponse, Responder};
+/// use actix_web_lab::body;
+///
+/// async fn handler() -> impl Responder {
+///     let data = vec![0; 64 * 1024];
+///
+///     // first 16 KiB is sent immediately, the rest takes around 3 seconds
+///     HttpResponse::Ok().body(body::throttle(data, 16 * 1024))
+/// }
+/// ```
+pub fn throttle<B: MessageBody>(body: B, bytes_per_sec: u64) -> ThrottledBody<B> {
+    throttle_with(body, TokenBucket::new(bytes_per_sec))
+}
+
+/// Throttles `body` using tokens from a (possibly shared) `bucket`.
+///
+/// See [`TokenBucket`] docs for an example.
+pub fn throttle_with<B: MessageBody>(body: B, bucket: TokenBucket) -> ThrottledBody<B> {
+    ThrottledBody {
+        body,
+        bucket,
+        buf: Bytes::new(),
+        sleep: None,
+    }
+}
+
+pin_project! {
+    /// Body wrapper that limits streaming throughput.
+    ///
+    /// Created by [`throttle()`], [`throttle_with()`], and the [`Throttle`] middleware.
+    pub struct ThrottledBody<B> {
+        #[pin]
+        body: B,
+        bucket: TokenBucket,
+        // part of the last chunk that has not been sent yet
+        buf: Bytes,
+        sleep: Option<Pin<Box<Sleep>>>,
+    }
+}
+
+impl<B> fmt::Debug for ThrottledBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThrottledBody")
+            .field("bucket", &self.bucket)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: MessageBody> MessageBody for ThrottledBody<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            if let Some(timer) = this.sleep.as_mut() {
+                if timer.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                *this.sleep = None;
+            }
+
+            if this.buf.is_empty() {
+                match futures_util::ready!(this.body.as_mut().poll_next(cx)) {
+                    Some(Ok(chunk)) if chunk.is_empty() => continue,
+                    Some(Ok(chunk)) => *this.buf = chunk,
+                    item => return Poll::Ready(item),
+                }
+            }
+
+            match this.bucket.take(this.buf.len()) {
+                Ok(n) => return Poll::Ready(Some(Ok(this.buf.split_to(n)))),
+                Err(wait) => *this.sleep = Some(Box::pin(sleep(wait))),
+            }
+        }
+    }
+}
+
+type KeyFn = dyn Fn(&ServiceRequest) -> String;
+
+/// Middleware that throttles response bodies.
+///
+/// Throttle each response independently using [`new()`](Self::new) or share bandwidth between
+/// responses with the same key using [`per_key()`](Self::per_key).
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+/// use actix_web_lab::{middleware::Throttle, util::TokenBuckets};
+///
+/// let per_ip = TokenBuckets::new(1024 * 1024);
+///
+/// App::new()
+///     .service(
+///         web::scope("/exports").wrap(Throttle::per_key(per_ip, |req| {
+///             req.peer_addr()
+///                 .map(|addr| addr.ip().to_string())
+///                 .unwrap_or_default()
+///         })),
+///     )
+///     .service(web::scope("/downloads").wrap(Throttle::new(512 * 1024)))
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct Throttle {
+    mode: ThrottleMode,
+}
+
+#[derive(Clone)]
+enum ThrottleMode {
+    PerResponse(u64),
+    PerKey(TokenBuckets, Rc<KeyFn>),
+}
+
+impl Throttle {
+    /// Constructs new middleware that throttles each response to `bytes_per_sec` bytes per second.
+    ///
+    /// # Panics
+    /// Panics if `bytes_per_sec` is zero.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "throttle rate must be non-zero");
+
+        Self {
+            mode: ThrottleMode::PerResponse(bytes_per_sec),
+        }
+    }
+
+    /// Constructs new middleware that throttles responses using the bucket from `buckets` named by
+    /// `key_fn`, so that responses with the same key share bandwidth.
+    pub fn per_key(
+        buckets: TokenBuckets,
+        key_fn: impl Fn(&ServiceRequest) -> String + 'static,
+    ) -> Self {
+        Self {
+            mode: ThrottleMode::PerKey(buckets, Rc::new(key_fn)),
+        }
+    }
+}
+
+impl fmt::Debug for Throttle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.mode {
+            ThrottleMode::PerResponse(rate) => f
+                .debug_struct("Throttle")
+                .field("bytes_per_sec", rate)
+                .finish(),
+            ThrottleMode::PerKey(buckets, _) => f
+                .debug_struct("Throttle")
+                .field("buckets", buckets)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Throttle
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<ThrottledBody<B>>;
+    type Error = S::Error;
+    type Transform = ThrottleMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ThrottleMiddleware {
+            service: Rc::new(service),
+            mode: self.mode.clone(),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`Throttle`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct ThrottleMiddleware<S> {
+    service: Rc<S>,
+    mode: ThrottleMode,
+}
+
+impl<S, B> Service<ServiceRequest> for ThrottleMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<ThrottledBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let bucket = match &self.mode {
+            ThrottleMode::PerResponse(rate) => TokenBucket::new(*rate),
+            ThrottleMode::PerKey(buckets, key_fn) => buckets.bucket(key_fn(&req)),
+        };
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            Ok(res.map_body(|_, body| throttle_with(body, bucket)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    #[test]
+    fn bucket_take() {
+        let bucket = TokenBucket::new(1000).burst(100);
+
+        assert_eq!(bucket.take(60), Ok(60));
+        assert_eq!(bucket.take(60), Ok(40));
+
+        let wait = bucket.take(60).unwrap_err();
+        assert!(wait <= Duration::from_millis(60));
+        assert!(wait >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn buckets_are_shared_by_key() {
+        let buckets = TokenBuckets::new(1000).burst(100);
+
+        let a = buckets.bucket("a");
+        assert_eq!(a.take(100), Ok(100));
+        assert!(buckets.bucket("a").take(1).is_err());
+        assert_eq!(buckets.bucket("b").take(100), Ok(100));
+
+        // unused buckets are discarded
+        drop(a);
+        buckets.bucket("c");
+        assert_eq!(buckets.buckets.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn throttles_body() {
+        let start = Instant::now();
+
+        let body = throttle_with(vec![b'a'; 3000], TokenBucket::new(10_000).burst(1000));
+        let bytes = body::to_bytes(body).await.ok().unwrap();
+
+        assert_eq!(bytes.len(), 3000);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[actix_web::test]
+    async fn middleware() {
+        let app = init_service(
+            App::new()
+                .wrap(Throttle::new(1_000_000))
+                .default_service(web::to(|| async { HttpResponse::Ok().body("hello") })),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(body::to_bytes(res.into_body()).await.ok().unwrap(), "hello");
+    }
+}
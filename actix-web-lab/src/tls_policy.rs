@@ -0,0 +1,437 @@
+//! TLS version and cipher suite policy enforcement.
+//!
+//! See [`TlsPolicy`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    guard::{Guard, GuardContext},
+    http::header::{self, HeaderValue},
+    middleware::Next,
+    HttpMessage as _, HttpResponse,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::debug;
+
+/// TLS protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum TlsVersion {
+    /// TLS 1.0.
+    Tls1_0,
+
+    /// TLS 1.1.
+    Tls1_1,
+
+    /// TLS 1.2.
+    Tls1_2,
+
+    /// TLS 1.3.
+    Tls1_3,
+}
+
+impl TlsVersion {
+    /// Returns the version with the given wire protocol version (e.g., `0x0303` for TLS 1.2), if
+    /// it is a known TLS version.
+    pub fn from_wire(version: u16) -> Option<Self> {
+        match version {
+            0x0301 => Some(Self::Tls1_0),
+            0x0302 => Some(Self::Tls1_1),
+            0x0303 => Some(Self::Tls1_2),
+            0x0304 => Some(Self::Tls1_3),
+            _ => None,
+        }
+    }
+
+    /// Returns the version number used in `Upgrade` headers (e.g., `1.2`).
+    fn number(self) -> &'static str {
+        match self {
+            Self::Tls1_0 => "1.0",
+            Self::Tls1_1 => "1.1",
+            Self::Tls1_2 => "1.2",
+            Self::Tls1_3 => "1.3",
+        }
+    }
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TLSv{}", self.number())
+    }
+}
+
+/// Parameters negotiated during a connection's TLS handshake.
+///
+/// Used by [`TlsPolicy`]; see its docs for setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsInfo {
+    version: TlsVersion,
+    cipher_suite: u16,
+}
+
+impl TlsInfo {
+    /// Constructs new TLS info from the negotiated version and [IANA cipher suite] value.
+    ///
+    /// [IANA cipher suite]: https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-4
+    pub fn new(version: TlsVersion, cipher_suite: u16) -> Self {
+        Self {
+            version,
+            cipher_suite,
+        }
+    }
+
+    /// Returns the negotiated TLS version.
+    pub fn version(&self) -> TlsVersion {
+        self.version
+    }
+
+    /// Returns the IANA value of the negotiated cipher suite (e.g., `0x1301` for
+    /// `TLS_AES_128_GCM_SHA256`).
+    pub fn cipher_suite(&self) -> u16 {
+        self.cipher_suite
+    }
+
+    /// Connection callback that stores negotiated TLS parameters for Rustls v0.23 connections.
+    ///
+    /// Pass to [`HttpServer::on_connect()`](actix_web::HttpServer::on_connect). Since only one
+    /// callback can be registered, call this from a closure to combine it with others, like
+    /// `PeerCert::rustls_0_23_on_connect`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{App, HttpServer};
+    /// use actix_web_lab::util::TlsInfo;
+    ///
+    /// # fn tls_config() -> rustls::ServerConfig { unimplemented!() }
+    /// # async fn run() -> std::io::Result<()> {
+    /// HttpServer::new(|| App::new())
+    ///     .on_connect(TlsInfo::rustls_0_23_on_connect)
+    ///     .bind_rustls_0_23(("127.0.0.1", 8443), tls_config())?
+    ///     .run()
+    ///     .await
+    /// # }
+    /// ```
+    #[cfg(feature = "rustls-0_23")]
+    pub fn rustls_0_23_on_connect(conn: &dyn std::any::Any, ext: &mut actix_web::dev::Extensions) {
+        use actix_tls::accept::rustls_0_23::TlsStream;
+        use actix_web::rt::net::TcpStream;
+
+        let Some(tls) = conn.downcast_ref::<TlsStream<TcpStream>>() else {
+            return;
+        };
+
+        let (_, session) = tls.get_ref();
+
+        let version = session
+            .protocol_version()
+            .and_then(|version| TlsVersion::from_wire(version.into()));
+        let cipher_suite = session.negotiated_cipher_suite();
+
+        if let (Some(version), Some(cipher_suite)) = (version, cipher_suite) {
+            ext.insert(Self::new(version, cipher_suite.suite().into()));
+        }
+    }
+
+    /// Returns TLS info from connection data or, if loaded by middleware, request data.
+    fn from_req(req: &ServiceRequest) -> Option<Self> {
+        req.conn_data::<Self>()
+            .copied()
+            .or_else(|| req.extensions().get::<Self>().copied())
+    }
+}
+
+/// Middleware for rejecting requests made over connections that do not meet a TLS policy.
+///
+/// Policies consist of a minimum TLS version (TLS 1.2, by default) and, optionally, a set of
+/// allowed cipher suites. Requests that do not meet the policy receive a `426 Upgrade Required`
+/// response with an `Upgrade` header naming the minimum TLS version. Requests over connections for
+/// which no [`TlsInfo`] is available (e.g., plain-text connections) are also rejected.
+///
+/// For per-route policies, use [`guard::tls_policy()`](crate::guard::tls_policy) instead.
+///
+/// # Setup
+/// Negotiated TLS parameters must be stored in the connection's data when the connection is
+/// accepted, using [`HttpServer::on_connect()`]. With the `rustls-0_23` crate feature enabled,
+/// `TlsInfo::rustls_0_23_on_connect` can be used directly. For other TLS implementations (e.g.,
+/// OpenSSL), construct a [`TlsInfo`] from the negotiated parameters and insert it into the
+/// connection's extensions.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::{middleware::TlsPolicy, util::TlsVersion};
+///
+/// App::new().wrap(
+///     TlsPolicy::new()
+///         .min_version(TlsVersion::Tls1_3)
+///         // TLS_AES_256_GCM_SHA384 and TLS_CHACHA20_POLY1305_SHA256
+///         .allowed_cipher_suites([0x1302, 0x1303]),
+/// )
+/// # ;
+/// ```
+///
+/// [`HttpServer::on_connect()`]: actix_web::HttpServer::on_connect
+#[derive(Debug, Clone)]
+pub struct TlsPolicy {
+    min_version: TlsVersion,
+    cipher_suites: Option<Vec<u16>>,
+}
+
+impl TlsPolicy {
+    /// Constructs new TLS policy that requires at least TLS 1.2 and allows any cipher suite.
+    pub fn new() -> Self {
+        Self {
+            min_version: TlsVersion::Tls1_2,
+            cipher_suites: None,
+        }
+    }
+
+    /// Sets the minimum allowed TLS version.
+    pub fn min_version(mut self, version: TlsVersion) -> Self {
+        self.min_version = version;
+        self
+    }
+
+    /// Restricts connections to the given cipher suites, identified by their IANA values.
+    pub fn allowed_cipher_suites(mut self, cipher_suites: impl IntoIterator<Item = u16>) -> Self {
+        self.cipher_suites = Some(cipher_suites.into_iter().collect());
+        self
+    }
+
+    /// Returns true if a connection with the given TLS parameters meets this policy.
+    pub fn allows(&self, info: &TlsInfo) -> bool {
+        info.version >= self.min_version
+            && self
+                .cipher_suites
+                .as_ref()
+                .map_or(true, |suites| suites.contains(&info.cipher_suite))
+    }
+
+    fn upgrade_required(&self) -> HttpResponse {
+        HttpResponse::UpgradeRequired()
+            .insert_header((
+                header::UPGRADE,
+                HeaderValue::from_str(&format!("TLS/{}", self.min_version.number())).unwrap(),
+            ))
+            .insert_header((header::CONNECTION, HeaderValue::from_static("Upgrade")))
+            .body("connection does not meet TLS policy")
+    }
+}
+
+impl Default for TlsPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TlsPolicy
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Transform = TlsPolicyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TlsPolicyMiddleware {
+            service: Rc::new(service),
+            policy: Rc::new(self.clone()),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`TlsPolicy`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct TlsPolicyMiddleware<S> {
+    service: Rc<S>,
+    policy: Rc<TlsPolicy>,
+}
+
+impl<S, B> Service<ServiceRequest> for TlsPolicyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let info = TlsInfo::from_req(&req);
+
+        if !info.is_some_and(|info| self.policy.allows(&info)) {
+            debug!("rejecting request over connection not meeting TLS policy: {info:?}");
+
+            let res = self.policy.upgrade_required().map_into_right_body();
+            return Box::pin(async move { Ok(req.into_response(res)) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+/// A function middleware that makes negotiated TLS parameters available to guards.
+///
+/// Required by [`guard::tls_policy()`](crate::guard::tls_policy). See [`TlsPolicy`] docs for
+/// setup.
+///
+/// # Examples
+/// ```
+/// # use actix_web::App;
+/// use actix_web::middleware::from_fn;
+/// use actix_web_lab::middleware::load_tls_info;
+///
+/// App::new().wrap(from_fn(load_tls_info))
+///     # ;
+/// ```
+pub async fn load_tls_info(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if let Some(info) = req.conn_data::<TlsInfo>().copied() {
+        req.extensions_mut().insert(info);
+    }
+
+    next.call(req).await
+}
+
+/// Creates a guard that requires the connection to meet a TLS policy.
+///
+/// Unlike the [`TlsPolicy`] middleware, requests that do not match can fall through to other
+/// routes, and the guard can be composed with others (e.g., using [`Any`](actix_web::guard::Any)).
+/// The [`load_tls_info`](crate::middleware::load_tls_info) middleware must wrap the app or scope.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::from_fn, web, App, HttpResponse};
+/// use actix_web_lab::{
+///     guard::tls_policy,
+///     middleware::{load_tls_info, TlsPolicy},
+///     util::TlsVersion,
+/// };
+///
+/// App::new().wrap(from_fn(load_tls_info)).service(
+///     web::resource("/payments")
+///         .guard(tls_policy(TlsPolicy::new().min_version(TlsVersion::Tls1_3)))
+///         .to(HttpResponse::Ok),
+/// )
+/// # ;
+/// ```
+pub fn tls_policy(policy: TlsPolicy) -> impl Guard {
+    TlsPolicyGuard(policy)
+}
+
+struct TlsPolicyGuard(TlsPolicy);
+
+impl Guard for TlsPolicyGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        ctx.req_data()
+            .get::<TlsInfo>()
+            .is_some_and(|info| self.0.allows(info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    const TLS_AES_128_GCM_SHA256: u16 = 0x1301;
+    const TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256: u16 = 0xC02F;
+
+    fn req(info: Option<TlsInfo>) -> actix_http::Request {
+        let req = TestRequest::default().to_request();
+
+        if let Some(info) = info {
+            req.extensions_mut().insert(info);
+        }
+
+        req
+    }
+
+    #[test]
+    fn policy_checks() {
+        let tls12 = TlsInfo::new(TlsVersion::Tls1_2, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256);
+        let tls13 = TlsInfo::new(TlsVersion::Tls1_3, TLS_AES_128_GCM_SHA256);
+
+        let policy = TlsPolicy::new();
+        assert!(policy.allows(&tls12));
+        assert!(policy.allows(&tls13));
+        assert!(!policy.allows(&TlsInfo::new(TlsVersion::Tls1_1, 0x002F)));
+
+        let policy = TlsPolicy::new().min_version(TlsVersion::Tls1_3);
+        assert!(!policy.allows(&tls12));
+        assert!(policy.allows(&tls13));
+
+        let policy = TlsPolicy::new().allowed_cipher_suites([TLS_AES_128_GCM_SHA256]);
+        assert!(!policy.allows(&tls12));
+        assert!(policy.allows(&tls13));
+
+        assert_eq!(TlsVersion::from_wire(0x0304), Some(TlsVersion::Tls1_3));
+        assert_eq!(TlsVersion::from_wire(0x0300), None);
+        assert_eq!(TlsVersion::Tls1_2.to_string(), "TLSv1.2");
+    }
+
+    #[actix_web::test]
+    async fn middleware_rejects() {
+        let app = init_service(
+            App::new()
+                .wrap(TlsPolicy::new().min_version(TlsVersion::Tls1_3))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let info = TlsInfo::new(TlsVersion::Tls1_3, TLS_AES_128_GCM_SHA256);
+        let res = call_service(&app, req(Some(info))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let info = TlsInfo::new(TlsVersion::Tls1_2, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256);
+        let res = call_service(&app, req(Some(info))).await;
+        assert_eq!(res.status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(res.headers().get(header::UPGRADE).unwrap(), "TLS/1.3");
+        assert_eq!(res.headers().get(header::CONNECTION).unwrap(), "Upgrade");
+
+        let res = call_service(&app, req(None)).await;
+        assert_eq!(res.status(), StatusCode::UPGRADE_REQUIRED);
+    }
+
+    #[actix_web::test]
+    async fn guard_falls_through() {
+        let app = init_service(
+            App::new()
+                .service(
+                    web::resource("/")
+                        .guard(tls_policy(TlsPolicy::new().min_version(TlsVersion::Tls1_3)))
+                        .to(HttpResponse::Ok),
+                )
+                .default_service(web::to(HttpResponse::Forbidden)),
+        )
+        .await;
+
+        let info = TlsInfo::new(TlsVersion::Tls1_3, TLS_AES_128_GCM_SHA256);
+        let res = call_service(&app, req(Some(info))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let info = TlsInfo::new(TlsVersion::Tls1_2, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256);
+        let res = call_service(&app, req(Some(info))).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
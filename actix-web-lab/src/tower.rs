@@ -0,0 +1,688 @@
+//! Interoperability with [`tower`](https://docs.rs/tower) services.
+//!
+//! Enables gradual migrations between Actix Web and tower-based frameworks (such as axum) and
+//! reuse of tower layers:
+//! - [`from_tower()`] adapts a tower service, such as an axum router, into an Actix Web service.
+//! - [`into_tower()`] adapts an Actix Web [`App`](actix_web::App) into a tower service, which can
+//!   then be wrapped by tower layers.
+//!
+//! Services use the `http` v1 types that the tower ecosystem is built on. Request bodies are
+//! buffered before being passed to the adapted service; response bodies are streamed.
+//!
+//! Requires the `tower` crate feature.
+
+use std::{
+    convert::Infallible,
+    fmt,
+    future::{poll_fn, ready, Ready},
+    pin::{pin, Pin},
+    rc::Rc,
+    task::{ready, Context, Poll},
+};
+
+use actix_http::Request;
+use actix_service::{IntoServiceFactory, Service, ServiceFactory};
+use actix_web::{
+    body::{BodySize, BoxBody, MessageBody},
+    dev::{AppConfig, Payload, ServiceRequest, ServiceResponse},
+    error::{self, PayloadError},
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue},
+        Method, StatusCode, Uri, Version,
+    },
+    Error, HttpMessage as _, HttpResponse,
+};
+use bytes::{Buf as _, BufMut as _, Bytes, BytesMut};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::{BodyExt as _, Full};
+use pin_project_lite::pin_project;
+
+use crate::{extract::DEFAULT_BODY_LIMIT, util::RequestTrailers, BoxError};
+
+/// Adapts a tower service into an Actix Web service factory.
+///
+/// See [`FromTower`] docs.
+pub fn from_tower<S>(service: S) -> FromTower<S> {
+    FromTower {
+        service,
+        body_limit: DEFAULT_BODY_LIMIT,
+    }
+}
+
+/// Actix Web service factory that forwards requests to a tower service.
+///
+/// Register using [`App::default_service()`](actix_web::App::default_service) or
+/// [`web::service()`](actix_web::web::service). The tower service is cloned for each request, so
+/// it should be cheap to clone, as axum routers are.
+///
+/// Request bodies are buffered, up to the [body limit](Self::body_limit), and passed to the tower
+/// service as [`Full<Bytes>`]. Errors returned by the tower service result in a
+/// `500 Internal Server Error` response.
+///
+/// # Examples
+/// ```
+/// # use std::{convert::Infallible, future::{ready, Ready}, task::{Context, Poll}};
+/// # use bytes::Bytes;
+/// # use http_body_util::Full;
+/// # #[derive(Clone)]
+/// # struct Hello;
+/// # impl tower_service::Service<http_1::Request<Full<Bytes>>> for Hello {
+/// #     type Response = http_1::Response<Full<Bytes>>;
+/// #     type Error = Infallible;
+/// #     type Future = Ready<Result<Self::Response, Infallible>>;
+/// #     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// #     fn call(&mut self, _: http_1::Request<Full<Bytes>>) -> Self::Future {
+/// #         ready(Ok(http_1::Response::new(Full::new(Bytes::from_static(b"hello")))))
+/// #     }
+/// # }
+/// # fn axum_router() -> Hello { Hello }
+/// use actix_web::{web, App};
+/// use actix_web_lab::tower::from_tower;
+///
+/// App::new()
+///     // ...new routes implemented with Actix Web...
+///     .route("/new", web::get().to(|| async { "new" }))
+///     // ...remaining routes are still served by the existing axum router
+///     .default_service(from_tower(axum_router()))
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct FromTower<S> {
+    service: S,
+    body_limit: usize,
+}
+
+impl<S> FromTower<S> {
+    /// Sets the maximum size of request bodies, in bytes.
+    ///
+    /// Requests with larger bodies are rejected with a `413 Payload Too Large` response. Defaults
+    /// to 2MiB.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = limit;
+        self
+    }
+}
+
+impl<S> fmt::Debug for FromTower<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromTower")
+            .field("service", &std::any::type_name::<S>())
+            .field("body_limit", &self.body_limit)
+            .finish()
+    }
+}
+
+impl<S, RB> ServiceFactory<ServiceRequest> for FromTower<S>
+where
+    S: tower_service::Service<http_1::Request<Full<Bytes>>, Response = http_1::Response<RB>>
+        + Clone
+        + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: 'static,
+    RB: HttpBody + 'static,
+    RB::Error: Into<BoxError>,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Config = ();
+    type Service = FromTower<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _cfg: ()) -> Self::Future {
+        ready(Ok(self.clone()))
+    }
+}
+
+impl<S, RB> Service<ServiceRequest> for FromTower<S>
+where
+    S: tower_service::Service<http_1::Request<Full<Bytes>>, Response = http_1::Response<RB>>
+        + Clone
+        + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: 'static,
+    RB: HttpBody + 'static,
+    RB::Error: Into<BoxError>,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let mut service = self.service.clone();
+        let body_limit = self.body_limit;
+
+        Box::pin(async move {
+            let (req, mut payload) = req.into_parts();
+
+            let body = read_payload(&mut payload, body_limit).await?;
+
+            let mut tower_req = http_1::Request::builder()
+                .method(req.method().as_str())
+                .uri(req.uri().to_string())
+                .version(version_to_tower(req.version()));
+
+            for (name, value) in req.headers() {
+                tower_req = tower_req.header(name.as_str(), value.as_bytes());
+            }
+
+            let tower_req = tower_req
+                .body(Full::new(body))
+                .map_err(error::ErrorInternalServerError)?;
+
+            poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .map_err(tower_error)?;
+
+            let tower_res = service.call(tower_req).await.map_err(tower_error)?;
+            let (parts, body) = tower_res.into_parts();
+
+            let status = StatusCode::from_u16(parts.status.as_u16())
+                .map_err(error::ErrorInternalServerError)?;
+
+            let mut res = HttpResponse::build(status);
+
+            for (name, value) in &parts.headers {
+                res.append_header((name.as_str(), value.as_bytes()));
+            }
+
+            let res = res.body(TowerBody { body });
+
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
+
+/// Buffers the request payload, up to `limit` bytes.
+async fn read_payload(payload: &mut Payload, limit: usize) -> Result<Bytes, Error> {
+    let mut body = BytesMut::new();
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+
+        if body.len() + chunk.len() > limit {
+            return Err(PayloadError::Overflow.into());
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body.freeze())
+}
+
+fn tower_error(err: impl Into<BoxError>) -> Error {
+    error::ErrorInternalServerError(err.into())
+}
+
+pin_project! {
+    /// Tower response body, adapted into an Actix Web body.
+    struct TowerBody<B> {
+        #[pin]
+        body: B,
+    }
+}
+
+impl<B> MessageBody for TowerBody<B>
+where
+    B: HttpBody,
+    B::Error: Into<BoxError>,
+{
+    type Error = BoxError;
+
+    fn size(&self) -> BodySize {
+        match self.body.size_hint().exact() {
+            Some(len) => BodySize::Sized(len),
+            None => BodySize::Stream,
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            return match ready!(this.body.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(mut data) => Poll::Ready(Some(Ok(data.copy_to_bytes(data.remaining())))),
+
+                    // trailers are not supported
+                    Err(_frame) => continue,
+                },
+
+                Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
+                None => Poll::Ready(None),
+            };
+        }
+    }
+}
+
+/// Adapts an Actix Web [`App`](actix_web::App) into a tower service.
+///
+/// See [`IntoTower`] docs.
+pub async fn into_tower<R, S, B>(app: R) -> Result<IntoTower<S::Service>, S::InitError>
+where
+    R: IntoServiceFactory<S, Request>,
+    S: ServiceFactory<Request, Config = AppConfig, Response = ServiceResponse<B>, Error = Error>,
+{
+    let service = app.into_factory().new_service(AppConfig::default()).await?;
+
+    Ok(IntoTower {
+        service: Rc::new(service),
+        body_limit: DEFAULT_BODY_LIMIT,
+    })
+}
+
+/// Tower service that forwards requests to an Actix Web app.
+///
+/// Constructed using [`into_tower()`]. Since Actix Web apps are not thread-safe, neither this
+/// service nor its futures are `Send`; run it on a single-threaded runtime or a `LocalSet`.
+///
+/// Request bodies are buffered, up to the [body limit](Self::body_limit), before being passed to
+/// the app. Any request trailers are made available to the app through [`RequestTrailers`].
+/// Errors, including failure to read the request body, are converted to error responses, so the
+/// service never fails.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+/// use actix_web_lab::tower::into_tower;
+/// use bytes::Bytes;
+/// use http_body_util::{BodyExt as _, Full};
+/// use tower_service::Service as _;
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let app = App::new().route("/", web::get().to(|| async { "hello" }));
+/// let mut service = into_tower(app).await.unwrap();
+///
+/// let req = http_1::Request::new(Full::new(Bytes::new()));
+/// let res = service.call(req).await.unwrap();
+///
+/// let body = res.into_body().collect().await.unwrap().to_bytes();
+/// assert_eq!(body, "hello");
+/// # });
+/// ```
+pub struct IntoTower<S> {
+    service: Rc<S>,
+    body_limit: usize,
+}
+
+impl<S> IntoTower<S> {
+    /// Sets the maximum size of request bodies, in bytes.
+    ///
+    /// Requests with larger bodies are rejected with a `413 Payload Too Large` response. Defaults
+    /// to 2MiB.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = limit;
+        self
+    }
+}
+
+impl<S> Clone for IntoTower<S> {
+    fn clone(&self) -> Self {
+        Self {
+            service: Rc::clone(&self.service),
+            body_limit: self.body_limit,
+        }
+    }
+}
+
+impl<S> fmt::Debug for IntoTower<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoTower")
+            .field("body_limit", &self.body_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B, RB> tower_service::Service<http_1::Request<RB>> for IntoTower<S>
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+    RB: HttpBody + 'static,
+    RB::Error: Into<BoxError>,
+{
+    type Response = http_1::Response<ActixBody>;
+    type Error = Infallible;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // readiness errors are reported as error responses when called
+        let _ = ready!(self.service.poll_ready(cx));
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http_1::Request<RB>) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let body_limit = self.body_limit;
+
+        Box::pin(async move {
+            let res = match into_actix_request(req, body_limit).await {
+                Ok(req) => match service.call(req).await {
+                    Ok(res) => res.into_parts().1.map_into_boxed_body(),
+                    Err(err) => err.error_response(),
+                },
+
+                Err(err) => err.error_response(),
+            };
+
+            let mut tower_res = http_1::Response::builder().status(res.status().as_u16());
+
+            for (name, value) in res.headers() {
+                tower_res = tower_res.header(name.as_str(), value.as_bytes());
+            }
+
+            let body = ActixBody {
+                body: res.into_body(),
+            };
+
+            Ok(tower_res.body(body).unwrap_or_else(|_| {
+                let mut res = http_1::Response::new(ActixBody {
+                    body: BoxBody::new(()),
+                });
+                *res.status_mut() = http_1::StatusCode::INTERNAL_SERVER_ERROR;
+                res
+            }))
+        })
+    }
+}
+
+/// Converts a tower request into an Actix Web request, buffering up to `limit` bytes of the body.
+async fn into_actix_request<RB>(req: http_1::Request<RB>, limit: usize) -> Result<Request, Error>
+where
+    RB: HttpBody,
+    RB::Error: Into<BoxError>,
+{
+    let (parts, body) = req.into_parts();
+    let mut body = pin!(body);
+
+    let mut buf = BytesMut::new();
+    let trailers = RequestTrailers::new();
+
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|err| error::ErrorBadRequest(err.into()))?;
+
+        match frame.into_data() {
+            Ok(chunk) => {
+                if buf.len() + chunk.remaining() > limit {
+                    return Err(PayloadError::Overflow.into());
+                }
+
+                buf.put(chunk);
+            }
+
+            Err(frame) => {
+                if let Ok(fields) = frame.into_trailers() {
+                    trailers.set(headers_from_tower(&fields)?);
+                }
+            }
+        }
+    }
+
+    let body = buf.freeze();
+
+    let (_, mut payload) = actix_http::h1::Payload::create(true);
+    payload.unread_data(body);
+
+    let mut req = Request::with_payload(payload.into());
+    let head = req.head_mut();
+
+    head.method =
+        Method::from_bytes(parts.method.as_str().as_bytes()).map_err(error::ErrorBadRequest)?;
+    head.uri = parts
+        .uri
+        .to_string()
+        .parse::<Uri>()
+        .map_err(error::ErrorBadRequest)?;
+    head.version = version_from_tower(parts.version);
+    head.headers = headers_from_tower(&parts.headers)?;
+
+    req.extensions_mut().insert(trailers);
+
+    Ok(req)
+}
+
+fn headers_from_tower(headers: &http_1::HeaderMap) -> Result<HeaderMap, Error> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+
+    for (name, value) in headers {
+        let name =
+            HeaderName::from_bytes(name.as_str().as_bytes()).map_err(error::ErrorBadRequest)?;
+        let value = HeaderValue::from_bytes(value.as_bytes()).map_err(error::ErrorBadRequest)?;
+
+        map.append(name, value);
+    }
+
+    Ok(map)
+}
+
+/// Actix Web response body, adapted into a tower (`http-body`) body.
+pub struct ActixBody {
+    body: BoxBody,
+}
+
+impl fmt::Debug for ActixBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActixBody")
+            .field("size", &self.body.size())
+            .finish()
+    }
+}
+
+impl HttpBody for ActixBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match ready!(Pin::new(&mut self.body).poll_next(cx)) {
+            Some(Ok(chunk)) => Poll::Ready(Some(Ok(Frame::data(chunk)))),
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.body.size(), BodySize::None | BodySize::Sized(0))
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.body.size() {
+            BodySize::None => SizeHint::with_exact(0),
+            BodySize::Sized(len) => SizeHint::with_exact(len),
+            BodySize::Stream => SizeHint::default(),
+        }
+    }
+}
+
+fn version_to_tower(version: Version) -> http_1::Version {
+    match version {
+        Version::HTTP_09 => http_1::Version::HTTP_09,
+        Version::HTTP_10 => http_1::Version::HTTP_10,
+        Version::HTTP_2 => http_1::Version::HTTP_2,
+        Version::HTTP_3 => http_1::Version::HTTP_3,
+        _ => http_1::Version::HTTP_11,
+    }
+}
+
+fn version_from_tower(version: http_1::Version) -> Version {
+    match version {
+        http_1::Version::HTTP_09 => Version::HTTP_09,
+        http_1::Version::HTTP_10 => Version::HTTP_10,
+        http_1::Version::HTTP_2 => Version::HTTP_2,
+        http_1::Version::HTTP_3 => Version::HTTP_3,
+        _ => Version::HTTP_11,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::to_bytes,
+        http::header,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpRequest,
+    };
+    use tower_service::Service as _;
+
+    use super::*;
+
+    /// Echoes the request method, path, and body, and a custom header.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl tower_service::Service<http_1::Request<Full<Bytes>>> for Echo {
+        type Response = http_1::Response<Full<Bytes>>;
+        type Error = Infallible;
+        type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http_1::Request<Full<Bytes>>) -> Self::Future {
+            Box::pin(async move {
+                let (parts, body) = req.into_parts();
+                let body = body.collect().await.unwrap().to_bytes();
+
+                let echo = format!(
+                    "{} {} {}",
+                    parts.method,
+                    parts.uri.path(),
+                    String::from_utf8_lossy(&body),
+                );
+
+                Ok(http_1::Response::builder()
+                    .status(http_1::StatusCode::CREATED)
+                    .header("x-tower", parts.headers["x-actix"].as_bytes())
+                    .body(Full::new(Bytes::from(echo)))
+                    .unwrap())
+            })
+        }
+    }
+
+    #[actix_web::test]
+    async fn tower_in_actix() {
+        let app = init_service(
+            App::new()
+                .route("/actix", web::get().to(|| async { "actix" }))
+                .default_service(from_tower(Echo).body_limit(16)),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/actix").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(to_bytes(res.into_body()).await.unwrap(), "actix");
+
+        let req = TestRequest::post()
+            .uri("/tower?q=1")
+            .insert_header(("x-actix", "hi"))
+            .set_payload("body")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get("x-tower").unwrap(), "hi");
+        assert_eq!(to_bytes(res.into_body()).await.unwrap(), "POST /tower body");
+
+        let req = TestRequest::post()
+            .uri("/tower")
+            .insert_header(("x-actix", "hi"))
+            .set_payload("this body is too long")
+            .to_request();
+        let res = app.call(req).await.unwrap_err();
+        assert_eq!(
+            res.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[actix_web::test]
+    async fn actix_in_tower() {
+        let app = App::new().route(
+            "/echo",
+            web::post().to(|req: HttpRequest, body: Bytes| async move {
+                HttpResponse::Ok()
+                    .insert_header(("x-actix", req.headers().get("x-tower").unwrap().clone()))
+                    .body(body)
+            }),
+        );
+
+        let mut service = into_tower(app).await.unwrap();
+
+        let req = http_1::Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header("x-tower", "hi")
+            .body(Full::new(Bytes::from_static(b"body")))
+            .unwrap();
+
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), http_1::StatusCode::OK);
+        assert_eq!(res.headers()["x-actix"], "hi");
+        assert_eq!(res.size_hint().exact(), Some(4));
+        assert_eq!(res.into_body().collect().await.unwrap().to_bytes(), "body");
+
+        let req = http_1::Request::builder()
+            .uri("/missing")
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), http_1::StatusCode::NOT_FOUND);
+        assert!(res.headers().get(header::CONTENT_TYPE.as_str()).is_none());
+    }
+
+    #[actix_web::test]
+    async fn actix_in_tower_body_limit() {
+        let app = App::new().default_service(web::to(|body: Bytes| async move { body }));
+
+        let mut service = into_tower(app).await.unwrap().body_limit(16);
+
+        let req = http_1::Request::new(Full::new(Bytes::from_static(b"body")));
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), http_1::StatusCode::OK);
+
+        let req = http_1::Request::new(Full::new(Bytes::from_static(b"this body is too long")));
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), http_1::StatusCode::PAYLOAD_TOO_LARGE);
+
+        // streaming bodies without a size hint are limited as they are read
+        let body = http_body_util::StreamBody::new(futures_util::stream::iter([
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"this body "))),
+            Ok(Frame::data(Bytes::from_static(b"is too long"))),
+        ]));
+        let res = service.call(http_1::Request::new(body)).await.unwrap();
+        assert_eq!(res.status(), http_1::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_web::test]
+    async fn actix_in_tower_trailers() {
+        let app = App::new().default_service(web::to(|req: HttpRequest, body: Bytes| async move {
+            let trailers = RequestTrailers::of(&req).unwrap().get().unwrap();
+            let checksum = trailers.get("x-checksum").unwrap().to_str().unwrap();
+            format!("{} {checksum}", body.len())
+        }));
+
+        let mut service = into_tower(app).await.unwrap();
+
+        let mut fields = http_1::HeaderMap::new();
+        fields.insert("x-checksum", http_1::HeaderValue::from_static("abc"));
+
+        let body = http_body_util::StreamBody::new(futures_util::stream::iter([
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"body"))),
+            Ok(Frame::trailers(fields)),
+        ]));
+
+        let res = service.call(http_1::Request::new(body)).await.unwrap();
+        assert_eq!(res.into_body().collect().await.unwrap().to_bytes(), "4 abc");
+    }
+}
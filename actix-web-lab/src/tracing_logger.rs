@@ -0,0 +1,373 @@
+//! Structured request logging middleware.
+//!
+//! See [`TracingLogger`] docs.
+
+use std::{
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+    time::Instant,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_core::future::LocalBoxFuture;
+use tracing::{Instrument as _, Span};
+
+use crate::extract::MatchedPath;
+
+/// Constructs the [`tracing`] span used by [`TracingLogger`] for a request.
+///
+/// The span is named `http_request` and is created at the `INFO` level with the following fields:
+/// - `method`: the request method;
+/// - `path`: the request path;
+/// - `route`: the [matched route pattern](MatchedPath), recorded once the response is ready;
+/// - `status`: the response status code, recorded once the response is ready;
+/// - `latency_ms`: the time taken to produce the response, in milliseconds;
+/// - `error`: the error that caused the response, if any; and
+/// - `request_id`: empty, so it can be recorded by the
+///   [`RequestId`](crate::middleware::RequestId) middleware when it is wrapped by the logger.
+///
+/// Additional fields can be added using the same syntax as [`tracing::info_span!`]. Fields declared
+/// as [`Empty`](tracing::field::Empty) can be recorded later, e.g., by handlers using
+/// [`Span::current()`].
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::{middleware::TracingLogger, request_span};
+///
+/// let logger = TracingLogger::new().span_builder(|req| {
+///     let tenant = req
+///         .headers()
+///         .get("x-tenant")
+///         .and_then(|tenant| tenant.to_str().ok())
+///         .unwrap_or("default");
+///
+///     request_span!(req, tenant, user_id = tracing::field::Empty)
+/// });
+///
+/// App::new().wrap(logger)
+/// # ;
+/// ```
+#[macro_export]
+macro_rules! request_span {
+    ($req:expr $(, $($field:tt)+)?) => {{
+        let req: &$crate::__reexports::actix_web::dev::ServiceRequest = $req;
+
+        $crate::__reexports::tracing::info_span!(
+            "http_request",
+            method = %req.method(),
+            path = req.path(),
+            route = $crate::__reexports::tracing::field::Empty,
+            status = $crate::__reexports::tracing::field::Empty,
+            latency_ms = $crate::__reexports::tracing::field::Empty,
+            error = $crate::__reexports::tracing::field::Empty,
+            request_id = $crate::__reexports::tracing::field::Empty,
+            $($($field)+)?
+        )
+    }};
+}
+
+pub use request_span;
+
+type SpanBuilder = Rc<dyn Fn(&ServiceRequest) -> Span>;
+
+/// Middleware for structured request logging using [`tracing`].
+///
+/// Opens a span for each request, using [`request_span!`] by default, within which the rest of
+/// the service chain runs. Once the response is ready, the matched route pattern, response status,
+/// latency, and any error are recorded on the span and a `request completed` event is emitted: at
+/// the `ERROR` level for `5xx` responses and at the `INFO` level otherwise. Latency does not
+/// include the time taken to stream the response body.
+///
+/// Unlike Actix Web's `Logger`, output is not formatted by the middleware itself, so it can be
+/// consumed by any [`tracing` subscriber](https://docs.rs/tracing-subscriber) (e.g., as JSON).
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::middleware::{RequestId, TracingLogger};
+///
+/// App::new()
+///     // RequestId is registered first so that it runs inside the logger's span
+///     .wrap(RequestId::new())
+///     .wrap(TracingLogger::new())
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct TracingLogger {
+    span_builder: SpanBuilder,
+}
+
+impl TracingLogger {
+    /// Constructs new tracing logger middleware that uses [`request_span!`] to create spans.
+    pub fn new() -> Self {
+        Self {
+            span_builder: Rc::new(|req| request_span!(req)),
+        }
+    }
+
+    /// Sets the function used to create each request's span.
+    ///
+    /// Use the [`request_span!`] macro to add custom fields to the default set. Spans created some
+    /// other way will only have the standard fields recorded if they declare them.
+    pub fn span_builder(
+        mut self,
+        span_builder: impl Fn(&ServiceRequest) -> Span + 'static,
+    ) -> Self {
+        self.span_builder = Rc::new(span_builder);
+        self
+    }
+}
+
+impl Default for TracingLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TracingLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TracingLogger")
+            .field("span_builder", &"<function>")
+            .finish()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TracingLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TracingLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TracingLoggerMiddleware {
+            service: Rc::new(service),
+            span_builder: Rc::clone(&self.span_builder),
+        }))
+    }
+}
+
+/// Middleware service implementation for [`TracingLogger`].
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct TracingLoggerMiddleware<S> {
+    service: Rc<S>,
+    span_builder: SpanBuilder,
+}
+
+impl<S, B> Service<ServiceRequest> for TracingLoggerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let span = (self.span_builder)(&req);
+        let start = Instant::now();
+
+        // inner middleware (e.g., RequestId) may record fields on the current span during `call`
+        let fut = span.in_scope(|| self.service.call(req));
+
+        Box::pin(async move {
+            let res = fut.instrument(span.clone()).await;
+
+            span.record("latency_ms", start.elapsed().as_secs_f64() * 1000.0);
+
+            let status = match &res {
+                Ok(res) => {
+                    if let Some(route) = MatchedPath::for_request(res.request()) {
+                        span.record("route", route.as_str());
+                    }
+
+                    if let Some(err) = res.response().error() {
+                        span.record("error", tracing::field::display(err));
+                    }
+
+                    res.status()
+                }
+
+                Err(err) => {
+                    span.record("error", tracing::field::display(err));
+                    err.as_response_error().status_code()
+                }
+            };
+
+            span.record("status", status.as_u16());
+
+            if status.is_server_error() {
+                tracing::error!(parent: &span, "request completed");
+            } else {
+                tracing::info!(parent: &span, "request completed");
+            }
+
+            res
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    use actix_web::{
+        error,
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+    use super::*;
+    use crate::middleware::RequestId;
+
+    type Fields = BTreeMap<String, String>;
+
+    /// Layer that stores the fields of each span, and the levels of events.
+    #[derive(Clone, Default)]
+    struct Capture {
+        spans: Arc<Mutex<Vec<(span::Id, Fields)>>>,
+        events: Arc<Mutex<Vec<tracing::Level>>>,
+    }
+
+    impl Capture {
+        fn fields(&self) -> Vec<Fields> {
+            let spans = self.spans.lock().unwrap();
+            spans.iter().map(|(_, fields)| fields.clone()).collect()
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut Fields);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(
+                field.name().to_owned(),
+                format!("{value:?}").replace('"', ""),
+            );
+        }
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, _: Context<'_, S>) {
+            let mut fields = Fields::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.spans.lock().unwrap().push((id.clone(), fields));
+        }
+
+        fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _: Context<'_, S>) {
+            let mut spans = self.spans.lock().unwrap();
+
+            // span IDs can be reused after spans close, so find the latest one
+            if let Some((_, fields)) = spans.iter_mut().rev().find(|(span, _)| span == id) {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            self.events.lock().unwrap().push(*event.metadata().level());
+        }
+    }
+
+    fn capture() -> (Capture, tracing::subscriber::DefaultGuard) {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        (capture, tracing::subscriber::set_default(subscriber))
+    }
+
+    #[actix_web::test]
+    async fn records_fields() {
+        let (capture, _guard) = capture();
+
+        let app = init_service(
+            App::new()
+                .wrap(RequestId::new())
+                .wrap(TracingLogger::new())
+                .route("/user/{id}", web::get().to(HttpResponse::Ok))
+                .route(
+                    "/fail",
+                    web::get().to(|| async {
+                        Err::<HttpResponse, _>(error::ErrorInternalServerError("boom"))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/user/42")
+            .insert_header(("x-request-id", "abc"))
+            .to_request();
+        call_service(&app, req).await;
+
+        let req = TestRequest::get().uri("/fail").to_request();
+        call_service(&app, req).await;
+
+        let spans = capture.fields();
+        let spans = spans
+            .iter()
+            .filter(|fields| fields.contains_key("status"))
+            .collect::<Vec<_>>();
+        assert_eq!(spans.len(), 2);
+
+        assert_eq!(spans[0]["method"], "GET");
+        assert_eq!(spans[0]["path"], "/user/42");
+        assert_eq!(spans[0]["route"], "/user/{id}");
+        assert_eq!(spans[0]["status"], "200");
+        assert_eq!(spans[0]["request_id"], "abc");
+        assert!(spans[0].contains_key("latency_ms"));
+        assert!(!spans[0].contains_key("error"));
+
+        assert_eq!(spans[1]["status"], "500");
+        assert_eq!(spans[1]["error"], "boom");
+
+        let events = capture.events.lock().unwrap();
+        assert!(events.contains(&tracing::Level::INFO));
+        assert!(events.contains(&tracing::Level::ERROR));
+    }
+
+    #[actix_web::test]
+    async fn custom_fields() {
+        let (capture, _guard) = capture();
+
+        let app = init_service(
+            App::new()
+                .wrap(TracingLogger::new().span_builder(|req| {
+                    request_span!(req, tenant = req.headers().contains_key("x-tenant"))
+                }))
+                .default_service(web::to(HttpResponse::NotFound)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header(("x-tenant", "acme"))
+            .to_request();
+        call_service(&app, req).await;
+
+        let spans = capture.fields();
+        let span = spans
+            .iter()
+            .find(|fields| fields.contains_key("tenant"))
+            .unwrap();
+        assert_eq!(span["tenant"], "true");
+        assert_eq!(span["status"], "404");
+    }
+}
@@ -0,0 +1,179 @@
+//! Request trailers extractor.
+//!
+//! See [`Trailers`] docs.
+
+use actix_web::{
+    dev::Payload,
+    error::PayloadError,
+    http::{header::HeaderMap, StatusCode},
+    FromRequest, HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+
+use crate::util::RequestTrailers;
+
+/// Extractor for request trailer fields.
+///
+/// Trailers are read from the request's [`RequestTrailers`] slot, which is only populated by server
+/// integrations that support them (such as [`into_tower()`](crate::tower::into_tower)); see its
+/// docs for details. If the request has no such slot, extraction fails with
+/// [`TrailersError::Unsupported`], rather than silently reporting that no trailers were sent.
+///
+/// Trailers are only sent once the request body is complete, so this extractor resolves after the
+/// body has been fully read. If the body has not already been taken by another extractor, it is
+/// read and discarded. Therefore, `Trailers` should be listed after any body extractors (like
+/// [`Bytes`](crate::extract::Bytes) or [`Json`](crate::extract::Json)) in a handler's arguments.
+///
+/// If the integration supports trailers but none were received, the extracted map is empty.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web::Bytes, Responder};
+/// use actix_web_lab::extract::Trailers;
+///
+/// #[post("/upload")]
+/// async fn upload(body: Bytes, trailers: Trailers) -> impl Responder {
+///     match trailers.get("x-checksum") {
+///         Some(checksum) => format!("received {} bytes, checksum {checksum:?}", body.len()),
+///         None => format!("received {} bytes without checksum", body.len()),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Trailers(pub HeaderMap);
+
+impl_more::impl_deref_and_mut!(Trailers => HeaderMap);
+
+impl Trailers {
+    /// Unwraps into inner header map.
+    pub fn into_inner(self) -> HeaderMap {
+        self.0
+    }
+}
+
+impl FromRequest for Trailers {
+    type Error = TrailersError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let Some(trailers) = RequestTrailers::of(req) else {
+            return Box::pin(async { Err(TrailersError::Unsupported) });
+        };
+
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            // trailers are only available once any unread body has been drained
+            while let Some(chunk) = payload.next().await {
+                chunk.map_err(TrailersError::Payload)?;
+            }
+
+            Ok(Self(trailers.get().unwrap_or_default()))
+        })
+    }
+}
+
+/// Errors that can occur when extracting [`Trailers`].
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum TrailersError {
+    /// Request trailers are not supported by the server integration that received the request.
+    #[display("Request trailers are not supported by this server")]
+    Unsupported,
+
+    /// Error reading request payload.
+    #[display("Error reading request payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for TrailersError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Unsupported => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header::{HeaderName, HeaderValue},
+        test::TestRequest,
+        web::Bytes,
+        HttpMessage as _,
+    };
+    use futures_util::stream;
+
+    use super::*;
+
+    const CHECKSUM: HeaderName = HeaderName::from_static("x-checksum");
+
+    /// Request whose trailers are stored once its payload has been fully read.
+    fn req_with_trailers() -> (HttpRequest, Payload) {
+        let slot = RequestTrailers::new();
+
+        let payload = stream::iter([Ok::<_, PayloadError>(Bytes::from_static(b"abc"))]).chain(
+            stream::poll_fn({
+                let slot = slot.clone();
+
+                move |_cx| {
+                    let mut trailers = HeaderMap::new();
+                    trailers.insert(CHECKSUM, HeaderValue::from_static("123"));
+                    slot.set(trailers);
+
+                    std::task::Poll::Ready(None)
+                }
+            }),
+        );
+
+        let (req, _) = TestRequest::default().to_http_parts();
+        req.extensions_mut().insert(slot);
+
+        (
+            req,
+            Payload::Stream {
+                payload: Box::pin(payload),
+            },
+        )
+    }
+
+    #[actix_web::test]
+    async fn drains_body() {
+        let (req, mut pl) = req_with_trailers();
+
+        let trailers = Trailers::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(trailers.get(CHECKSUM).unwrap(), "123");
+    }
+
+    #[actix_web::test]
+    async fn after_body_extractor() {
+        let (req, mut pl) = req_with_trailers();
+
+        let (body, trailers) = <(Bytes, Trailers)>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(body, "abc");
+        assert_eq!(trailers.get(CHECKSUM).unwrap(), "123");
+    }
+
+    #[actix_web::test]
+    async fn no_trailers() {
+        let (req, mut pl) = TestRequest::default().set_payload("abc").to_http_parts();
+        req.extensions_mut().insert(RequestTrailers::new());
+
+        let trailers = Trailers::from_request(&req, &mut pl).await.unwrap();
+        assert!(trailers.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn unsupported() {
+        let (req, mut pl) = TestRequest::default().set_payload("abc").to_http_parts();
+
+        let err = Trailers::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, TrailersError::Unsupported));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
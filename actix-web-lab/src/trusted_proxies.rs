@@ -0,0 +1,156 @@
+//! Trusted proxy configuration.
+//!
+//! See [`TrustedProxies`] docs.
+
+use std::net::IpAddr;
+
+use actix_web::HttpRequest;
+
+/// Set of IP address ranges whose forwarding headers (`Forwarded`, `X-Forwarded-*`) are trusted.
+///
+/// Register using [`App::app_data()`](actix_web::App::app_data) (or the scope or resource
+/// equivalents). Extractors that resolve client-facing request information, such as
+/// [`Host`](crate::extract::Host), only read forwarding headers from requests whose peer address
+/// is in one of these ranges. When no `TrustedProxies` is registered, forwarding headers are
+/// ignored, since any client can send them.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_web_lab::extract::TrustedProxies;
+///
+/// App::new().app_data(
+///     TrustedProxies::new()
+///         .trust("10.0.0.0/8")
+///         .trust("fd00::/8")
+///         .trust("127.0.0.1"),
+/// )
+/// # ;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    ranges: Vec<IpRange>,
+}
+
+impl TrustedProxies {
+    /// Constructs new, empty set of trusted proxies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a trusted address range in CIDR notation (e.g., `10.0.0.0/8`) or a single address.
+    ///
+    /// # Panics
+    /// Panics if `range` is not a valid address or CIDR range.
+    pub fn trust(mut self, range: &str) -> Self {
+        let range = IpRange::parse(range)
+            .unwrap_or_else(|| panic!("`{range}` should be a valid IP address or CIDR range"));
+
+        self.ranges.push(range);
+        self
+    }
+
+    /// Returns true if `addr` is in one of the trusted ranges.
+    pub fn is_trusted(&self, addr: IpAddr) -> bool {
+        let addr = addr.to_canonical();
+        self.ranges.iter().any(|range| range.contains(addr))
+    }
+
+    /// Returns true if the request's peer is a trusted proxy, according to the `TrustedProxies`
+    /// registered in app data.
+    pub(crate) fn is_request_trusted(req: &HttpRequest) -> bool {
+        let Some(peer) = req.peer_addr() else {
+            return false;
+        };
+
+        req.app_data::<Self>()
+            .is_some_and(|proxies| proxies.is_trusted(peer.ip()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpRange {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    fn parse(range: &str) -> Option<Self> {
+        let (addr, prefix_len) = match range.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len.parse::<u8>().ok()?)),
+            None => (range, None),
+        };
+
+        let addr = addr.parse::<IpAddr>().ok()?.to_canonical();
+
+        let max_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        let prefix_len = prefix_len.unwrap_or(max_len);
+
+        (prefix_len <= max_len).then_some(Self { addr, prefix_len })
+    }
+
+    fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(range), IpAddr::V4(addr)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+
+                u32::from(range) & mask == u32::from(addr) & mask
+            }
+
+            (IpAddr::V6(range), IpAddr::V6(addr)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+
+                u128::from(range) & mask == u128::from(addr) & mask
+            }
+
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn ranges() {
+        let proxies = TrustedProxies::new()
+            .trust("10.0.0.0/8")
+            .trust("192.168.1.1")
+            .trust("fd00::/8");
+
+        assert!(proxies.is_trusted(ip("10.1.2.3")));
+        assert!(proxies.is_trusted(ip("192.168.1.1")));
+        assert!(proxies.is_trusted(ip("fd12::1")));
+        assert!(proxies.is_trusted(ip("::ffff:10.0.0.1")));
+
+        assert!(!proxies.is_trusted(ip("11.0.0.1")));
+        assert!(!proxies.is_trusted(ip("192.168.1.2")));
+        assert!(!proxies.is_trusted(ip("fe80::1")));
+
+        assert!(TrustedProxies::new()
+            .trust("0.0.0.0/0")
+            .is_trusted(ip("1.2.3.4")));
+        assert!(!TrustedProxies::new().is_trusted(ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn parsing() {
+        assert!(IpRange::parse("10.0.0.0/33").is_none());
+        assert!(IpRange::parse("10.0.0.0/").is_none());
+        assert!(IpRange::parse("example.com").is_none());
+        assert!(IpRange::parse("::1/128").is_some());
+    }
+}
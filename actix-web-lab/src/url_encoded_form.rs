@@ -8,13 +8,14 @@ use std::{
 };
 
 use actix_web::{
-    dev::Payload, error::UrlencodedError, http::header, web, Error, FromRequest, HttpMessage,
-    HttpRequest,
+    dev::Payload, error::UrlencodedError, web, Error, FromRequest, HttpMessage, HttpRequest,
 };
 use futures_core::Stream as _;
 use serde::de::DeserializeOwned;
 use tracing::debug;
 
+use crate::{decompress, lab_config::LabConfig};
+
 /// Default URL-encoded form payload size limit of 2MiB.
 pub const DEFAULT_URL_ENCODED_FORM_LIMIT: usize = 2_097_152;
 
@@ -29,6 +30,10 @@ pub const DEFAULT_URL_ENCODED_FORM_LIMIT: usize = 2_097_152;
 /// Use the `LIMIT` const generic parameter to control the payload size limit. The default limit
 /// that is exported (`DEFAULT_LIMIT`) is 2MiB.
 ///
+/// When the `decompress` crate feature is enabled, request bodies compressed using gzip, Brotli,
+/// or Zstandard (as indicated by their `Content-Encoding` header) are decompressed and the limit
+/// is applied to the decompressed size.
+///
 /// ```
 /// use actix_web::{post, App};
 /// use actix_web_lab::extract::{UrlEncodedForm, DEFAULT_URL_ENCODED_FORM_LIMIT};
@@ -151,6 +156,8 @@ pub enum UrlEncodedFormBody<T, const LIMIT: usize> {
         length: Option<usize>,
         payload: Payload,
         buf: web::BytesMut,
+        /// Effective payload size limit.
+        limit: usize,
         _res: PhantomData<T>,
     },
 }
@@ -171,23 +178,27 @@ impl<T: DeserializeOwned, const LIMIT: usize> UrlEncodedFormBody<T, LIMIT> {
             return UrlEncodedFormBody::Error(Some(UrlencodedError::ContentType));
         }
 
-        let length = req
-            .headers()
-            .get(&header::CONTENT_LENGTH)
-            .and_then(|l| l.to_str().ok())
-            .and_then(|s| s.parse::<usize>().ok());
+        let length = LabConfig::payload_length(req).filter(|_| !decompress::is_encoded(req));
 
         // Notice the content-length is not checked against config limit here.
         // As the internal usage always call UrlEncodedBody::limit after UrlEncodedBody::new.
         // And limit check to return an error variant of UrlEncodedBody happens there.
 
-        let payload = payload.take();
+        let limit = LabConfig::resolve_limit(
+            req,
+            LIMIT,
+            DEFAULT_URL_ENCODED_FORM_LIMIT,
+            LabConfig::URL_ENCODED_FORM,
+        );
+        LabConfig::apply_to_payload(req, payload);
+
+        let payload = decompress::take_payload(req, payload);
 
         if let Some(len) = length {
-            if len > LIMIT {
+            if len > limit {
                 return UrlEncodedFormBody::Error(Some(UrlencodedError::Overflow {
                     size: len,
-                    limit: LIMIT,
+                    limit,
                 }));
             }
         }
@@ -195,7 +206,8 @@ impl<T: DeserializeOwned, const LIMIT: usize> UrlEncodedFormBody<T, LIMIT> {
         UrlEncodedFormBody::Body {
             length,
             payload,
-            buf: web::BytesMut::with_capacity(8192),
+            buf: web::BytesMut::with_capacity(LabConfig::buffer_capacity(req, length)),
+            limit,
             _res: PhantomData,
         }
     }
@@ -208,17 +220,22 @@ impl<T: DeserializeOwned, const LIMIT: usize> Future for UrlEncodedFormBody<T, L
         let this = self.get_mut();
 
         match this {
-            UrlEncodedFormBody::Body { buf, payload, .. } => loop {
+            UrlEncodedFormBody::Body {
+                buf,
+                payload,
+                limit,
+                ..
+            } => loop {
                 let res = ready!(Pin::new(&mut *payload).poll_next(cx));
 
                 match res {
                     Some(chunk) => {
                         let chunk = chunk?;
                         let buf_len = buf.len() + chunk.len();
-                        if buf_len > LIMIT {
+                        if buf_len > *limit {
                             return Poll::Ready(Err(UrlencodedError::Overflow {
                                 size: buf_len,
-                                limit: LIMIT,
+                                limit: *limit,
                             }));
                         } else {
                             buf.extend_from_slice(&chunk);
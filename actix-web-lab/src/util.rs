@@ -5,17 +5,39 @@
 
 use std::{
     convert::Infallible,
+    error::Error as StdError,
+    hash::{BuildHasher as _, RandomState},
     io,
     pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
     task::{ready, Context, Poll},
 };
 
 use actix_http::{error::PayloadError, BoxedPayloadStream};
-use actix_web::{dev, web::BufMut};
+use actix_web::{
+    dev,
+    web::{BufMut, Bytes},
+};
 use futures_core::Stream;
 use futures_util::StreamExt as _;
 use local_channel::mpsc;
 
+#[cfg(feature = "url")]
+pub use crate::absolute_url::url_for_absolute;
+#[cfg(feature = "url")]
+pub use crate::redirect_target::{validate_redirect_target, RedirectTarget, RedirectTargetError};
+pub use crate::{
+    content_sniff::verify_content_type,
+    expect_continue::ExpectContinue,
+    header_audit::{HeaderAudit, HeaderAuditReport, HeaderFinding, HeaderFindingKind},
+    redact_headers::{redact_headers, RedactionPolicy},
+    request_trailers::RequestTrailers,
+    stream_metrics::{DisconnectReason, StreamMetrics},
+    strict_content_length::enforce_content_length,
+    throttle::{TokenBucket, TokenBuckets},
+    tls_policy::{TlsInfo, TlsVersion},
+};
+
 /// Returns an effectively cloned payload that supports streaming efficiently.
 ///
 /// The cloned payload:
@@ -109,6 +131,134 @@ impl<S: Stream> Stream for InfallibleStream<S> {
     }
 }
 
+/// Callback invoked with the number of items yielded once a stream completes successfully.
+pub(crate) type CompletionCallback = Box<dyn FnOnce(usize)>;
+
+pin_project_lite::pin_project! {
+    /// Counts successful items from a fallible stream and reports the total on completion.
+    ///
+    /// The callback is not called if the stream yields an error.
+    pub(crate) struct CountingStream<S> {
+        #[pin]
+        stream: S,
+        count: usize,
+        on_complete: Option<CompletionCallback>,
+    }
+}
+
+impl<S> CountingStream<S> {
+    /// Constructs new `CountingStream` stream.
+    pub(crate) fn new(stream: S, on_complete: Option<CompletionCallback>) -> Self {
+        Self {
+            stream,
+            count: 0,
+            on_complete,
+        }
+    }
+}
+
+impl<S, T, E> Stream for CountingStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = ready!(this.stream.poll_next(cx));
+
+        match &item {
+            Some(Ok(_)) => *this.count += 1,
+
+            // incomplete output is not reported
+            Some(Err(_)) => {
+                this.on_complete.take();
+            }
+
+            None => {
+                if let Some(on_complete) = this.on_complete.take() {
+                    on_complete(*this.count);
+                }
+            }
+        }
+
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// Maps a stream error to a final chunk of a serialized body.
+pub(crate) type ErrorTrailerFn = Box<dyn FnOnce(Box<dyn StdError>) -> Bytes>;
+
+pin_project_lite::pin_project! {
+    /// Replaces the first error from a chunk stream with a trailer chunk, then ends the stream.
+    ///
+    /// Errors are passed through unchanged when no trailer function is set.
+    pub(crate) struct ErrorTrailerStream<S> {
+        #[pin]
+        stream: S,
+        on_error: Option<ErrorTrailerFn>,
+        done: bool,
+    }
+}
+
+impl<S> ErrorTrailerStream<S> {
+    /// Constructs new `ErrorTrailerStream` stream.
+    pub(crate) fn new(stream: S, on_error: Option<ErrorTrailerFn>) -> Self {
+        Self {
+            stream,
+            on_error,
+            done: false,
+        }
+    }
+}
+
+impl<S, E> Stream for ErrorTrailerStream<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<Box<dyn StdError>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Err(err)) if this.on_error.is_some() => {
+                *this.done = true;
+
+                let on_error = this.on_error.take().unwrap();
+                Poll::Ready(Some(Ok(on_error(err.into()))))
+            }
+
+            item => Poll::Ready(item),
+        }
+    }
+}
+
+/// Returns 128 unpredictable bits for use in identifiers and multipart boundaries.
+///
+/// Derived from the standard library's randomly keyed hasher so that no RNG dependency is needed.
+/// Not suitable for generating secrets.
+pub(crate) fn random_u128() -> u128 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let state = RandomState::new();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    let hi = state.hash_one((count, 0_u8));
+    let lo = state.hash_one((count, 1_u8));
+
+    (u128::from(hi) << 64) | u128::from(lo)
+}
+
 #[cfg(test)]
 #[derive(Debug, Clone, Default)]
 pub(crate) struct PollSeq<T> {
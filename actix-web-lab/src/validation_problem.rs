@@ -0,0 +1,362 @@
+//! Validation problem details responder.
+//!
+//! See [`ValidationProblem`] docs.
+
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use serde::{ser::SerializeStruct as _, Serialize, Serializer};
+
+use crate::{extract::QueryDeserializeError, respond::Problem};
+
+/// A single field error in a [`ValidationProblem`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FieldError {
+    /// Path to the invalid field, e.g., `user.email` or `items[2]`.
+    pub path: String,
+
+    /// Machine-readable error code, e.g., `required` or `too_long`.
+    pub code: String,
+
+    /// Human-readable error message.
+    pub message: String,
+}
+
+impl FieldError {
+    /// Constructs a new field error.
+    pub fn new(
+        path: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Serialize for FieldError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut err = serializer.serialize_struct("FieldError", 3)?;
+        err.serialize_field("path", &self.path)?;
+        err.serialize_field("code", &self.code)?;
+        err.serialize_field("message", &self.message)?;
+        err.end()
+    }
+}
+
+/// Problem details document ([RFC 9457]) aggregating multiple field validation errors.
+///
+/// Responds with `422 Unprocessable Entity` (by default) and an `application/problem+json` body
+/// that includes an `errors` extension member listing each [`FieldError`]:
+///
+/// ```json
+/// {
+///   "title": "Unprocessable Entity",
+///   "status": 422,
+///   "errors": [
+///     { "path": "email", "code": "required", "message": "email is required" }
+///   ]
+/// }
+/// ```
+///
+/// Collect errors using [`error()`](Self::error) or [`push_error()`](Self::push_error), then use
+/// [`check()`](Self::check) to only return the problem if any errors were found. Also implements
+/// [`ResponseError`] so it can be returned from handlers using `?`. [`Query`] deserialization
+/// errors can be converted into a validation problem with one field error. Validation problems can
+/// be converted into a [`Problem`] with an `errors` extension member.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, Responder};
+/// use actix_web_lab::respond::ValidationProblem;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct SignUp {
+///     email: String,
+///     password: String,
+/// }
+///
+/// #[post("/sign-up")]
+/// async fn sign_up(form: web::Json<SignUp>) -> Result<impl Responder, ValidationProblem> {
+///     let mut problem = ValidationProblem::new().detail("The sign up form has errors.");
+///
+///     if !form.email.contains('@') {
+///         problem.push_error("email", "invalid", "email must contain an @");
+///     }
+///
+///     if form.password.len() < 12 {
+///         problem.push_error(
+///             "password",
+///             "too_short",
+///             "password must be at least 12 characters",
+///         );
+///     }
+///
+///     problem.check()?;
+///
+///     Ok("welcome")
+/// }
+/// ```
+///
+/// [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457
+/// [`Query`]: crate::extract::Query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationProblem {
+    status: StatusCode,
+    type_uri: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    errors: Vec<FieldError>,
+}
+
+impl Default for ValidationProblem {
+    fn default() -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            type_uri: None,
+            title: None,
+            detail: None,
+            instance: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl ValidationProblem {
+    /// Constructs a new validation problem with no errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the response status code.
+    ///
+    /// Defaults to `422 Unprocessable Entity`.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the `type` member; a URI identifying the problem type.
+    pub fn type_uri(mut self, type_uri: impl Into<String>) -> Self {
+        self.type_uri = Some(type_uri.into());
+        self
+    }
+
+    /// Sets the `title` member; a short summary of the problem type.
+    ///
+    /// Defaults to the status code's canonical reason phrase.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the `detail` member; an explanation specific to this occurrence of the problem.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the `instance` member; a URI identifying this occurrence of the problem.
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds a field error.
+    pub fn error(
+        mut self,
+        path: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.push_error(path, code, message);
+        self
+    }
+
+    /// Adds a field error in place.
+    pub fn push_error(
+        &mut self,
+        path: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.errors.push(FieldError::new(path, code, message));
+    }
+
+    /// Returns the field errors collected so far.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Returns true if no field errors have been added.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns `Err(self)` if any field errors have been added.
+    #[allow(clippy::result_large_err)] // returned by value so it can be used with `?` in handlers
+    pub fn check(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        Problem::from(self.clone()).error_response()
+    }
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Validation failed with {} error(s)", self.errors.len())
+    }
+}
+
+impl std::error::Error for ValidationProblem {}
+
+impl ResponseError for ValidationProblem {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.to_response()
+    }
+}
+
+impl Responder for ValidationProblem {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.to_response()
+    }
+}
+
+impl From<ValidationProblem> for Problem {
+    fn from(problem: ValidationProblem) -> Self {
+        let mut res = Problem::new(problem.status);
+
+        if let Some(type_uri) = problem.type_uri {
+            res = res.type_uri(type_uri);
+        }
+
+        if let Some(title) = problem.title {
+            res = res.title(title);
+        }
+
+        if let Some(detail) = problem.detail {
+            res = res.detail(detail);
+        }
+
+        if let Some(instance) = problem.instance {
+            res = res.instance(instance);
+        }
+
+        match serde_json::to_value(problem.errors) {
+            Ok(errors) => res.extension("errors", errors),
+            Err(_) => res,
+        }
+    }
+}
+
+impl From<QueryDeserializeError> for ValidationProblem {
+    fn from(err: QueryDeserializeError) -> Self {
+        let message =
+            std::error::Error::source(&err).map_or_else(|| err.to_string(), ToString::to_string);
+
+        Self::new()
+            .status(err.status_code())
+            .error(err.path().to_string(), "invalid", message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body, http::header, test::TestRequest};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{extract::Query, problem::PROBLEM_JSON};
+
+    async fn body_json(res: HttpResponse) -> serde_json::Value {
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn aggregates_errors() {
+        let mut problem = ValidationProblem::new()
+            .type_uri("https://example.com/probs/validation")
+            .detail("The form has errors.")
+            .error("email", "required", "email is required");
+        problem.push_error("items[2].qty", "range", "qty must be positive");
+
+        assert_eq!(problem.errors().len(), 2);
+
+        let req = TestRequest::default().to_http_request();
+        let res = problem.respond_to(&req);
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+
+        assert_eq!(
+            body_json(res).await,
+            json!({
+                "type": "https://example.com/probs/validation",
+                "title": "Unprocessable Entity",
+                "status": 422,
+                "detail": "The form has errors.",
+                "errors": [
+                    { "path": "email", "code": "required", "message": "email is required" },
+                    { "path": "items[2].qty", "code": "range", "message": "qty must be positive" },
+                ],
+            }),
+        );
+    }
+
+    #[actix_web::test]
+    async fn check() {
+        assert!(ValidationProblem::new().check().is_ok());
+
+        let err = ValidationProblem::new()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Bad input")
+            .error("name", "required", "name is required")
+            .check()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Validation failed with 1 error(s)");
+
+        let res = actix_web::Error::from(err).error_response();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(res).await["title"], "Bad input");
+    }
+
+    #[actix_web::test]
+    async fn from_query_error() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            page: u32,
+        }
+
+        let err = Query::<Params>::from_query("page=abc").unwrap_err();
+        let problem = ValidationProblem::from(err);
+
+        assert_eq!(problem.errors().len(), 1);
+        assert_eq!(problem.errors()[0].path, "page");
+        assert_eq!(problem.errors()[0].code, "invalid");
+        assert_eq!(problem.errors()[0].message, "invalid digit found in string");
+    }
+}
@@ -2,8 +2,29 @@
 //!
 //! Analogous to the `web` module in Actix Web.
 
+#[cfg(feature = "admin")]
+pub use crate::admin_scope::AdminScope;
 #[cfg(feature = "spa")]
 pub use crate::spa::Spa;
+pub use crate::{
+    accepted::{job_status, InMemoryJobStore, JobStatus, JobStore},
+    bulk_import::BulkImport,
+    openapi::{openapi_json, ApiOperation, OpenApi, ToSchema},
+    shared_data::SharedData,
+};
+
+/// Constructs a new bulk CSV import endpoint builder that passes each row to `handler`.
+///
+/// See [`BulkImport`] docs for more details.
+pub fn bulk_import<Row, F, Fut, E>(handler: F) -> BulkImport<Row, F>
+where
+    Row: serde::de::DeserializeOwned + 'static,
+    F: Fn(Row) -> Fut + Clone + 'static,
+    Fut: std::future::Future<Output = Result<(), E>> + 'static,
+    E: std::fmt::Display + 'static,
+{
+    BulkImport::new(handler)
+}
 
 /// Constructs a new Single-page Application (SPA) builder.
 ///
@@ -27,3 +48,14 @@ pub use crate::spa::Spa;
 pub fn spa() -> Spa {
     Spa::default()
 }
+
+/// Constructs a new admin scope builder at `path`, accessible only to requests passing `auth_guard`.
+///
+/// See [`AdminScope`] docs for more details.
+#[cfg(feature = "admin")]
+pub fn admin_scope(
+    path: impl Into<String>,
+    auth_guard: impl actix_web::guard::Guard + 'static,
+) -> AdminScope {
+    AdminScope::new(path, auth_guard)
+}
@@ -0,0 +1,657 @@
+//! Webhook request signature schemes.
+//!
+//! See [`GitHubWebhook`], [`StripeWebhook`], and [`SlackWebhook`] docs.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{http::StatusCode, web::Bytes, HttpRequest, ResponseError};
+use derive_more::{Display, Error};
+use hmac::{digest::CtOutput, Mac as _, SimpleHmac};
+use sha2::Sha256;
+
+use crate::{aws_sigv4::unhex, extract::RequestSignatureScheme};
+
+type HmacSha256 = SimpleHmac<Sha256>;
+
+/// Default maximum age of timestamped webhook requests.
+const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+const X_HUB_SIGNATURE_256: &str = "x-hub-signature-256";
+const STRIPE_SIGNATURE: &str = "stripe-signature";
+const X_SLACK_SIGNATURE: &str = "x-slack-signature";
+const X_SLACK_REQUEST_TIMESTAMP: &str = "x-slack-request-timestamp";
+
+/// Errors that can occur when verifying a webhook signature.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum WebhookSignatureError {
+    /// No configuration for the signature scheme was registered as app data.
+    #[display("Webhook signature configuration is missing from app data")]
+    MissingConfig,
+
+    /// Request does not have a signature header.
+    #[display("Webhook signature is missing")]
+    MissingSignature,
+
+    /// The signature header is malformed.
+    #[display("Webhook signature is malformed")]
+    MalformedSignature,
+
+    /// The signed timestamp is missing or malformed.
+    #[display("Webhook timestamp is missing or invalid")]
+    InvalidTimestamp,
+
+    /// The signed timestamp is outside the allowed replay window.
+    #[display("Webhook timestamp is outside the allowed tolerance")]
+    TimestampOutsideTolerance,
+
+    /// The signature does not match the request.
+    #[display("Webhook signature does not match")]
+    SignatureMismatch,
+}
+
+impl ResponseError for WebhookSignatureError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::MissingSignature => StatusCode::UNAUTHORIZED,
+            Self::MalformedSignature | Self::InvalidTimestamp => StatusCode::BAD_REQUEST,
+            Self::TimestampOutsideTolerance | Self::SignatureMismatch => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// Configuration for the [`GitHubWebhook`] signature scheme.
+///
+/// Must be registered as app data (using [`App::app_data()`]) for the scheme to find it.
+///
+/// [`App::app_data()`]: actix_web::App::app_data
+#[derive(Clone)]
+pub struct GitHubWebhookConfig {
+    secret: String,
+}
+
+impl GitHubWebhookConfig {
+    /// Constructs a new configuration using the webhook's secret.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+}
+
+impl fmt::Debug for GitHubWebhookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitHubWebhookConfig")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Configuration for the [`StripeWebhook`] signature scheme.
+///
+/// Must be registered as app data (using [`App::app_data()`]) for the scheme to find it.
+///
+/// [`App::app_data()`]: actix_web::App::app_data
+#[derive(Clone)]
+pub struct StripeWebhookConfig {
+    secret: String,
+    tolerance: Duration,
+}
+
+impl StripeWebhookConfig {
+    /// Constructs a new configuration using the endpoint's signing secret (`whsec_...`) and
+    /// allowing timestamps up to 5 minutes from the current time.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets the maximum allowed difference between the signed timestamp and the current time.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+impl fmt::Debug for StripeWebhookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StripeWebhookConfig")
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Configuration for the [`SlackWebhook`] signature scheme.
+///
+/// Must be registered as app data (using [`App::app_data()`]) for the scheme to find it.
+///
+/// [`App::app_data()`]: actix_web::App::app_data
+#[derive(Clone)]
+pub struct SlackWebhookConfig {
+    secret: String,
+    tolerance: Duration,
+}
+
+impl SlackWebhookConfig {
+    /// Constructs a new configuration using the app's signing secret and allowing timestamps up to
+    /// 5 minutes from the current time.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets the maximum allowed difference between the signed timestamp and the current time.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+impl fmt::Debug for SlackWebhookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlackWebhookConfig")
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
+    }
+}
+
+/// GitHub-style webhook signature scheme for use with [`RequestSignature`].
+///
+/// Verifies the `X-Hub-Signature-256` header, which contains `sha256=` followed by the hex-encoded
+/// HMAC-SHA256 of the body. The secret is read from a [`GitHubWebhookConfig`] registered as app
+/// data. Many other services use the same scheme.
+///
+/// This scheme has no signed timestamp so it cannot limit replays itself; use the unique delivery
+/// ID sent by the service (e.g., GitHub's `X-GitHub-Delivery` header) to ignore repeated
+/// deliveries.
+///
+/// The resulting signature is the verified 32-byte HMAC.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, App};
+/// use actix_web_lab::{
+///     extract::RequestSignature,
+///     signature_schemes::{GitHubWebhook, GitHubWebhookConfig},
+/// };
+///
+/// #[post("/webhook")]
+/// async fn webhook(body: RequestSignature<web::Bytes, GitHubWebhook>) -> &'static str {
+///     let (_body, _signature) = body.into_parts();
+///     "ok"
+/// }
+///
+/// App::new()
+///     .app_data(GitHubWebhookConfig::new("It's a Secret to Everybody"))
+///     .service(webhook);
+/// ```
+///
+/// [`RequestSignature`]: crate::extract::RequestSignature
+#[derive(Debug)]
+pub struct GitHubWebhook {
+    mac: HmacSha256,
+}
+
+impl RequestSignatureScheme for GitHubWebhook {
+    type Signature = [u8; 32];
+    type Error = WebhookSignatureError;
+
+    async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+        let config = req
+            .app_data::<GitHubWebhookConfig>()
+            .ok_or(WebhookSignatureError::MissingConfig)?;
+
+        Ok(Self {
+            mac: new_mac(&config.secret),
+        })
+    }
+
+    async fn consume_chunk(&mut self, _req: &HttpRequest, chunk: Bytes) -> Result<(), Self::Error> {
+        self.mac.update(&chunk);
+        Ok(())
+    }
+
+    async fn finalize(self, _req: &HttpRequest) -> Result<Self::Signature, Self::Error> {
+        Ok(self.mac.finalize().into_bytes().into())
+    }
+
+    fn verify(
+        signature: Self::Signature,
+        req: &HttpRequest,
+    ) -> Result<Self::Signature, Self::Error> {
+        let candidate = header_str(req, X_HUB_SIGNATURE_256)?
+            .strip_prefix("sha256=")
+            .ok_or(WebhookSignatureError::MalformedSignature)?;
+
+        verify_any(signature, [candidate])
+    }
+}
+
+/// Stripe-style timestamped webhook signature scheme for use with [`RequestSignature`].
+///
+/// Verifies the `Stripe-Signature` header, which has the form `t=<timestamp>,v1=<signature>`.
+/// Each `v1` signature is the hex-encoded HMAC-SHA256 of the timestamp, a `.`, and the body. The
+/// header may contain several `v1` signatures (e.g., while a secret is being rolled); the request
+/// is accepted if any of them match. Other signature versions are ignored.
+///
+/// The secret and the replay window are read from a [`StripeWebhookConfig`] registered as app
+/// data. Requests with a timestamp outside the replay window are rejected before the body is read.
+///
+/// The resulting signature is the verified 32-byte HMAC.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{post, web, App};
+/// use actix_web_lab::{
+///     extract::RequestSignature,
+///     signature_schemes::{StripeWebhook, StripeWebhookConfig},
+/// };
+///
+/// #[post("/webhook")]
+/// async fn webhook(body: RequestSignature<web::Bytes, StripeWebhook>) -> &'static str {
+///     let (_body, _signature) = body.into_parts();
+///     "ok"
+/// }
+///
+/// App::new()
+///     .app_data(StripeWebhookConfig::new("whsec_...").tolerance(Duration::from_secs(60)))
+///     .service(webhook);
+/// ```
+///
+/// [`RequestSignature`]: crate::extract::RequestSignature
+#[derive(Debug)]
+pub struct StripeWebhook {
+    mac: HmacSha256,
+}
+
+impl StripeWebhook {
+    /// Parses the `Stripe-Signature` header into its timestamp and `v1` signatures.
+    fn parse_header(req: &HttpRequest) -> Result<(&str, Vec<&str>), WebhookSignatureError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+
+        for item in header_str(req, STRIPE_SIGNATURE)?.split(',') {
+            let (key, value) = item
+                .trim()
+                .split_once('=')
+                .ok_or(WebhookSignatureError::MalformedSignature)?;
+
+            match key {
+                "t" => timestamp = Some(value),
+                "v1" => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or(WebhookSignatureError::InvalidTimestamp)?;
+
+        if signatures.is_empty() {
+            return Err(WebhookSignatureError::MalformedSignature);
+        }
+
+        Ok((timestamp, signatures))
+    }
+}
+
+impl RequestSignatureScheme for StripeWebhook {
+    type Signature = [u8; 32];
+    type Error = WebhookSignatureError;
+
+    async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+        let config = req
+            .app_data::<StripeWebhookConfig>()
+            .ok_or(WebhookSignatureError::MissingConfig)?;
+
+        let (timestamp, _) = Self::parse_header(req)?;
+        check_timestamp(timestamp, config.tolerance)?;
+
+        let mut mac = new_mac(&config.secret);
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+
+        Ok(Self { mac })
+    }
+
+    async fn consume_chunk(&mut self, _req: &HttpRequest, chunk: Bytes) -> Result<(), Self::Error> {
+        self.mac.update(&chunk);
+        Ok(())
+    }
+
+    async fn finalize(self, _req: &HttpRequest) -> Result<Self::Signature, Self::Error> {
+        Ok(self.mac.finalize().into_bytes().into())
+    }
+
+    fn verify(
+        signature: Self::Signature,
+        req: &HttpRequest,
+    ) -> Result<Self::Signature, Self::Error> {
+        let (_, candidates) = Self::parse_header(req)?;
+        verify_any(signature, candidates)
+    }
+}
+
+/// Slack-style timestamped webhook signature scheme for use with [`RequestSignature`].
+///
+/// Verifies the `X-Slack-Signature` header, which contains `v0=` followed by the hex-encoded
+/// HMAC-SHA256 of the string `v0:<timestamp>:<body>`, where the timestamp is taken from the
+/// `X-Slack-Request-Timestamp` header.
+///
+/// The secret and the replay window are read from a [`SlackWebhookConfig`] registered as app
+/// data. Requests with a timestamp outside the replay window are rejected before the body is read.
+///
+/// The resulting signature is the verified 32-byte HMAC.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, App};
+/// use actix_web_lab::{
+///     extract::RequestSignature,
+///     signature_schemes::{SlackWebhook, SlackWebhookConfig},
+/// };
+///
+/// #[post("/slack/events")]
+/// async fn events(body: RequestSignature<web::Bytes, SlackWebhook>) -> &'static str {
+///     let (_body, _signature) = body.into_parts();
+///     "ok"
+/// }
+///
+/// App::new()
+///     .app_data(SlackWebhookConfig::new("8f742231b10e8888abcd99yyyzzz85a5"))
+///     .service(events);
+/// ```
+///
+/// [`RequestSignature`]: crate::extract::RequestSignature
+#[derive(Debug)]
+pub struct SlackWebhook {
+    mac: HmacSha256,
+}
+
+impl RequestSignatureScheme for SlackWebhook {
+    type Signature = [u8; 32];
+    type Error = WebhookSignatureError;
+
+    async fn init(req: &HttpRequest) -> Result<Self, Self::Error> {
+        let config = req
+            .app_data::<SlackWebhookConfig>()
+            .ok_or(WebhookSignatureError::MissingConfig)?;
+
+        let timestamp = header_str(req, X_SLACK_REQUEST_TIMESTAMP)
+            .map_err(|_| WebhookSignatureError::InvalidTimestamp)?;
+        check_timestamp(timestamp, config.tolerance)?;
+
+        let mut mac = new_mac(&config.secret);
+        mac.update(b"v0:");
+        mac.update(timestamp.as_bytes());
+        mac.update(b":");
+
+        Ok(Self { mac })
+    }
+
+    async fn consume_chunk(&mut self, _req: &HttpRequest, chunk: Bytes) -> Result<(), Self::Error> {
+        self.mac.update(&chunk);
+        Ok(())
+    }
+
+    async fn finalize(self, _req: &HttpRequest) -> Result<Self::Signature, Self::Error> {
+        Ok(self.mac.finalize().into_bytes().into())
+    }
+
+    fn verify(
+        signature: Self::Signature,
+        req: &HttpRequest,
+    ) -> Result<Self::Signature, Self::Error> {
+        let candidate = header_str(req, X_SLACK_SIGNATURE)?
+            .strip_prefix("v0=")
+            .ok_or(WebhookSignatureError::MalformedSignature)?;
+
+        verify_any(signature, [candidate])
+    }
+}
+
+fn new_mac(secret: &str) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length")
+}
+
+/// Returns the value of the header `name`.
+fn header_str<'a>(req: &'a HttpRequest, name: &str) -> Result<&'a str, WebhookSignatureError> {
+    req.headers()
+        .get(name)
+        .ok_or(WebhookSignatureError::MissingSignature)?
+        .to_str()
+        .map_err(|_| WebhookSignatureError::MalformedSignature)
+}
+
+/// Checks that a Unix timestamp (in seconds) is within `tolerance` of the current time.
+fn check_timestamp(timestamp: &str, tolerance: Duration) -> Result<(), WebhookSignatureError> {
+    if timestamp.is_empty() || !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(WebhookSignatureError::InvalidTimestamp);
+    }
+
+    let timestamp = timestamp
+        .parse::<u64>()
+        .map_err(|_| WebhookSignatureError::InvalidTimestamp)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(WebhookSignatureError::TimestampOutsideTolerance);
+    }
+
+    Ok(())
+}
+
+/// Compares `signature` against each hex-encoded candidate in constant time.
+fn verify_any<'a>(
+    signature: [u8; 32],
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Result<[u8; 32], WebhookSignatureError> {
+    let expected = CtOutput::<HmacSha256>::new(signature.into());
+    let mut matched = false;
+
+    for candidate in candidates {
+        let candidate = unhex::<32>(candidate).ok_or(WebhookSignatureError::MalformedSignature)?;
+
+        // don't short-circuit so timing does not reveal which candidate matched
+        matched |= expected == CtOutput::<HmacSha256>::new(candidate.into());
+    }
+
+    if matched {
+        Ok(signature)
+    } else {
+        Err(WebhookSignatureError::SignatureMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+    use crate::extract::RequestSignature;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &str = "Hello, World!";
+
+    fn hex_mac(parts: &[&[u8]]) -> String {
+        let mut mac = new_mac(SECRET);
+
+        for part in parts {
+            mac.update(part);
+        }
+
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn now() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string()
+    }
+
+    async fn status<S, C>(config: C, req: TestRequest) -> StatusCode
+    where
+        S: RequestSignatureScheme<Error = WebhookSignatureError> + 'static,
+        C: 'static,
+    {
+        let app = init_service(App::new().app_data(config).default_service(web::to(
+            |body: RequestSignature<Bytes, S>| async move {
+                let (body, _) = body.into_parts();
+                body
+            },
+        )))
+        .await;
+
+        call_service(&app, req.set_payload(BODY).to_request())
+            .await
+            .status()
+    }
+
+    #[actix_web::test]
+    async fn github() {
+        let config = || GitHubWebhookConfig::new(SECRET);
+
+        // example from GitHub's webhook validation docs
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert_eq!(signature, format!("sha256={}", hex_mac(&[BODY.as_bytes()])));
+
+        let req = TestRequest::post().insert_header((X_HUB_SIGNATURE_256, signature));
+        assert_eq!(
+            status::<GitHubWebhook, _>(config(), req).await,
+            StatusCode::OK
+        );
+
+        let req = TestRequest::post().insert_header((X_HUB_SIGNATURE_256, &signature[1..]));
+        assert_eq!(
+            status::<GitHubWebhook, _>(config(), req).await,
+            StatusCode::BAD_REQUEST,
+        );
+
+        let wrong = format!("sha256={}", hex_mac(&[b"Goodbye"]));
+        let req = TestRequest::post().insert_header((X_HUB_SIGNATURE_256, wrong));
+        assert_eq!(
+            status::<GitHubWebhook, _>(config(), req).await,
+            StatusCode::FORBIDDEN,
+        );
+
+        let req = TestRequest::post();
+        assert_eq!(
+            status::<GitHubWebhook, _>(config(), req).await,
+            StatusCode::UNAUTHORIZED,
+        );
+
+        let req = TestRequest::post().insert_header((X_HUB_SIGNATURE_256, signature));
+        assert_eq!(
+            status::<GitHubWebhook, _>((), req).await,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+    }
+
+    #[actix_web::test]
+    async fn stripe() {
+        let config = || StripeWebhookConfig::new(SECRET);
+
+        let ts = now();
+        let signature = hex_mac(&[ts.as_bytes(), b".", BODY.as_bytes()]);
+        let old_secret_signature = "0".repeat(64);
+
+        let header = format!("t={ts},v1={old_secret_signature},v1={signature},v0=abc");
+        let req = TestRequest::post().insert_header((STRIPE_SIGNATURE, header));
+        assert_eq!(
+            status::<StripeWebhook, _>(config(), req).await,
+            StatusCode::OK
+        );
+
+        let header = format!("t={ts},v1={old_secret_signature}");
+        let req = TestRequest::post().insert_header((STRIPE_SIGNATURE, header));
+        assert_eq!(
+            status::<StripeWebhook, _>(config(), req).await,
+            StatusCode::FORBIDDEN,
+        );
+
+        let header = format!("v1={signature}");
+        let req = TestRequest::post().insert_header((STRIPE_SIGNATURE, header));
+        assert_eq!(
+            status::<StripeWebhook, _>(config(), req).await,
+            StatusCode::BAD_REQUEST,
+        );
+
+        // replayed request
+        let ts = "1500000000";
+        let signature = hex_mac(&[ts.as_bytes(), b".", BODY.as_bytes()]);
+        let header = format!("t={ts},v1={signature}");
+        let req = TestRequest::post().insert_header((STRIPE_SIGNATURE, header.clone()));
+        assert_eq!(
+            status::<StripeWebhook, _>(config(), req).await,
+            StatusCode::FORBIDDEN,
+        );
+
+        let config = config().tolerance(Duration::from_secs(100 * 365 * 24 * 60 * 60));
+        let req = TestRequest::post().insert_header((STRIPE_SIGNATURE, header));
+        assert_eq!(
+            status::<StripeWebhook, _>(config, req).await,
+            StatusCode::OK
+        );
+    }
+
+    #[actix_web::test]
+    async fn slack() {
+        let config = || SlackWebhookConfig::new(SECRET);
+
+        let ts = now();
+        let signature = format!(
+            "v0={}",
+            hex_mac(&[b"v0:", ts.as_bytes(), b":", BODY.as_bytes()])
+        );
+
+        let req = TestRequest::post()
+            .insert_header((X_SLACK_REQUEST_TIMESTAMP, ts.as_str()))
+            .insert_header((X_SLACK_SIGNATURE, signature.as_str()));
+        assert_eq!(
+            status::<SlackWebhook, _>(config(), req).await,
+            StatusCode::OK
+        );
+
+        let req = TestRequest::post().insert_header((X_SLACK_SIGNATURE, signature.as_str()));
+        assert_eq!(
+            status::<SlackWebhook, _>(config(), req).await,
+            StatusCode::BAD_REQUEST,
+        );
+
+        let req = TestRequest::post()
+            .insert_header((X_SLACK_REQUEST_TIMESTAMP, "1500000000"))
+            .insert_header((X_SLACK_SIGNATURE, signature.as_str()));
+        assert_eq!(
+            status::<SlackWebhook, _>(config(), req).await,
+            StatusCode::FORBIDDEN,
+        );
+
+        let req = TestRequest::post()
+            .insert_header((X_SLACK_REQUEST_TIMESTAMP, ts.as_str()))
+            .insert_header((X_SLACK_SIGNATURE, signature.replace("v0=", "v1=")));
+        assert_eq!(
+            status::<SlackWebhook, _>(config(), req).await,
+            StatusCode::BAD_REQUEST,
+        );
+    }
+}
@@ -0,0 +1,688 @@
+//! Semantic WebSocket responder.
+//!
+//! A higher-level alternative to hand-rolling WebSocket loops. The [`Ws`] responder performs the
+//! handshake and sends [`Message`]s yielded by a stream, along with optional keep-alive pings and
+//! a close frame when the stream ends. Messages sent by the client are read using the [`Messages`]
+//! extractor. Pings from the client are answered automatically and close frames are echoed.
+//!
+//! # Examples
+//! ```no_run
+//! use actix_web::{get, Responder};
+//! use actix_web_lab::ws;
+//! use futures_util::StreamExt as _;
+//!
+//! #[get("/echo")]
+//! async fn echo(msgs: ws::Messages) -> impl Responder {
+//!     // echo text and binary messages back to the client
+//!     let replies = msgs.filter_map(|msg| async move {
+//!         match msg {
+//!             Ok(ws::Message::Close(_)) | Err(_) => None,
+//!             msg => Some(msg),
+//!         }
+//!     });
+//!
+//!     ws::Ws::from_stream(replies)
+//! }
+//!
+//! #[get("/ticks")]
+//! async fn ticks() -> impl Responder {
+//!     let (tx, rx) = tokio::sync::mpsc::channel(10);
+//!
+//!     // note: sender will typically be spawned or handed off somewhere else
+//!     let _ = tx.send(ws::Message::text("tick")).await;
+//!
+//!     ws::Ws::from_infallible_receiver(rx).with_keep_alive(std::time::Duration::from_secs(10))
+//! }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    pin::Pin,
+    rc::Rc,
+    task::{ready, Context, Poll, Waker},
+    time::Duration,
+};
+
+use actix_codec::{Decoder as _, Encoder as _};
+use actix_http::ws::{self as proto, Codec, Frame, Item};
+pub use actix_http::ws::{CloseCode, CloseReason, ProtocolError};
+use actix_web::{
+    body::{BodySize, BoxBody, MessageBody},
+    dev::Payload,
+    error::PayloadError,
+    http::StatusCode,
+    FromRequest, HttpMessage as _, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use bytes::{Bytes, BytesMut};
+use bytestring::ByteString;
+use derive_more::{Display, Error};
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use tokio::{
+    sync::mpsc,
+    time::{interval, Interval},
+};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{util::InfallibleStream, BoxError};
+
+/// Default maximum size of a (possibly fragmented) incoming message of 64KiB.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 65_536;
+
+/// A WebSocket data or close message.
+///
+/// Ping and pong control frames are handled by [`Ws`] and [`Messages`] so are not represented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Message {
+    /// Text message.
+    Text(ByteString),
+
+    /// Binary message.
+    Binary(Bytes),
+
+    /// Close message with optional reason.
+    ///
+    /// When sent, no further messages are sent. When received, the peer will not send any further
+    /// messages.
+    Close(Option<CloseReason>),
+}
+
+impl Message {
+    /// Constructs a text message.
+    pub fn text(text: impl Into<ByteString>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Constructs a binary message.
+    pub fn binary(bytes: impl Into<Bytes>) -> Self {
+        Self::Binary(bytes.into())
+    }
+
+    /// Constructs a close message with the given close code.
+    pub fn close(code: CloseCode) -> Self {
+        Self::Close(Some(code.into()))
+    }
+}
+
+impl From<Message> for proto::Message {
+    fn from(msg: Message) -> Self {
+        match msg {
+            Message::Text(text) => proto::Message::Text(text),
+            Message::Binary(bytes) => proto::Message::Binary(bytes),
+            Message::Close(reason) => proto::Message::Close(reason),
+        }
+    }
+}
+
+/// Connection state shared between [`Messages`] and [`Ws`] through request extensions.
+#[derive(Debug, Default)]
+struct Control {
+    /// Payloads of received pings, awaiting pong replies.
+    pongs: VecDeque<Bytes>,
+
+    /// Set once a close frame has been received from the client.
+    close: Option<Option<CloseReason>>,
+
+    /// Waker for the outgoing body, woken when there are control frames to send.
+    waker: Option<Waker>,
+}
+
+type SharedControl = Rc<RefCell<Control>>;
+
+fn control(req: &HttpRequest) -> SharedControl {
+    let mut extensions = req.extensions_mut();
+
+    if let Some(control) = extensions.get::<SharedControl>() {
+        return Rc::clone(control);
+    }
+
+    let control = SharedControl::default();
+    extensions.insert(Rc::clone(&control));
+    control
+}
+
+impl Control {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+pin_project! {
+    /// WebSocket responder.
+    ///
+    /// Performs the WebSocket handshake and responds with a stream of [`Message`]s. If the
+    /// request is not a valid WebSocket upgrade request, an error response is returned instead.
+    ///
+    /// Constructed using a [Tokio channel](Self::from_receiver) or using your [own
+    /// stream](Self::from_stream). When the stream ends, a normal close frame is sent.
+    #[must_use]
+    #[derive(Debug)]
+    pub struct Ws<S> {
+        #[pin]
+        stream: S,
+        keep_alive: Option<Interval>,
+    }
+}
+
+impl<S, E> Ws<S>
+where
+    S: Stream<Item = Result<Message, E>> + 'static,
+    E: Into<BoxError>,
+{
+    /// Create a WebSocket response from a stream that yields [`Message`]s.
+    pub fn from_stream(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: None,
+        }
+    }
+}
+
+impl<S> Ws<InfallibleStream<S>>
+where
+    S: Stream<Item = Message> + 'static,
+{
+    /// Create a WebSocket response from an infallible stream that yields [`Message`]s.
+    pub fn from_infallible_stream(stream: S) -> Self {
+        Ws::from_stream(InfallibleStream::new(stream))
+    }
+}
+
+impl<E> Ws<ReceiverStream<Result<Message, E>>>
+where
+    E: Into<BoxError> + 'static,
+{
+    /// Create a WebSocket response from a receiver that yields [`Message`]s.
+    pub fn from_receiver(receiver: mpsc::Receiver<Result<Message, E>>) -> Self {
+        Self::from_stream(ReceiverStream::new(receiver))
+    }
+}
+
+impl Ws<InfallibleStream<ReceiverStream<Message>>> {
+    /// Create a WebSocket response from a receiver that yields [`Message`]s.
+    pub fn from_infallible_receiver(receiver: mpsc::Receiver<Message>) -> Self {
+        Self::from_stream(InfallibleStream::new(ReceiverStream::new(receiver)))
+    }
+}
+
+impl<S> Ws<S> {
+    /// Enables ping messages to be sent to the client periodically.
+    ///
+    /// By default, no pings are sent.
+    pub fn with_keep_alive(mut self, ping_period: Duration) -> Self {
+        let mut int = interval(ping_period);
+        int.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        self.keep_alive = Some(int);
+        self
+    }
+}
+
+impl<S, E> Responder for Ws<S>
+where
+    S: Stream<Item = Result<Message, E>> + 'static,
+    E: Into<BoxError> + 'static,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut res = match proto::handshake(req.head()) {
+            Ok(res) => res,
+            Err(err) => return HttpResponse::from(actix_http::Response::from(err)),
+        };
+
+        let body = WsBody {
+            stream: self.stream,
+            keep_alive: self.keep_alive,
+            control: control(req),
+            codec: Codec::new(),
+            buf: BytesMut::new(),
+            closed: false,
+        };
+
+        HttpResponse::from(res.message_body(body.boxed()).unwrap())
+    }
+}
+
+pin_project! {
+    struct WsBody<S> {
+        #[pin]
+        stream: S,
+        keep_alive: Option<Interval>,
+        control: SharedControl,
+        codec: Codec,
+        buf: BytesMut,
+        closed: bool,
+    }
+}
+
+impl<S> WsBody<S> {
+    fn encode(codec: &mut Codec, buf: &mut BytesMut, msg: proto::Message) -> Bytes {
+        // encoding server frames is infallible
+        codec.encode(msg, buf).unwrap();
+        buf.split().freeze()
+    }
+}
+
+impl<S, E> MessageBody for WsBody<S>
+where
+    S: Stream<Item = Result<Message, E>>,
+    E: Into<BoxError>,
+{
+    type Error = BoxError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        if *this.closed {
+            return Poll::Ready(None);
+        }
+
+        {
+            let mut control = this.control.borrow_mut();
+
+            if let Some(payload) = control.pongs.pop_front() {
+                let msg = proto::Message::Pong(payload);
+                return Poll::Ready(Some(Ok(Self::encode(this.codec, this.buf, msg))));
+            }
+
+            // echo close frame from client and stop sending
+            if let Some(reason) = control.close.take() {
+                *this.closed = true;
+                let msg = proto::Message::Close(reason);
+                return Poll::Ready(Some(Ok(Self::encode(this.codec, this.buf, msg))));
+            }
+
+            control.waker = Some(cx.waker().clone());
+        }
+
+        if let Poll::Ready(msg) = this.stream.poll_next(cx) {
+            let msg = match msg {
+                Some(Ok(msg)) => msg,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                None => Message::close(CloseCode::Normal),
+            };
+
+            if matches!(msg, Message::Close(_)) {
+                *this.closed = true;
+            }
+
+            return Poll::Ready(Some(Ok(Self::encode(this.codec, this.buf, msg.into()))));
+        }
+
+        if let Some(ref mut keep_alive) = this.keep_alive {
+            if keep_alive.poll_tick(cx).is_ready() {
+                let msg = proto::Message::Ping(Bytes::new());
+                return Poll::Ready(Some(Ok(Self::encode(this.codec, this.buf, msg))));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Errors that can occur when reading WebSocket messages.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum MessagesError {
+    /// Request is not a valid WebSocket upgrade request.
+    #[display("invalid WebSocket handshake: {_0}")]
+    Handshake(proto::HandshakeError),
+
+    /// Client sent a frame that violates the WebSocket protocol.
+    #[display("WebSocket protocol error: {_0}")]
+    Protocol(ProtocolError),
+
+    /// Client sent a text message that is not valid UTF-8.
+    #[display("text message is not valid UTF-8")]
+    InvalidUtf8,
+
+    /// Client sent a fragmented message larger than the size limit.
+    #[display("message is larger than allowed (limit: {limit} bytes)")]
+    Overflow {
+        /// Message size limit.
+        limit: usize,
+    },
+
+    /// Failed to read from the connection.
+    #[display("failed to read from connection: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for MessagesError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Extractor for a stream of WebSocket [`Message`]s sent by the client.
+///
+/// Ping frames are answered with pongs, and close frames are echoed, by the [`Ws`] responder
+/// returned from the same handler. Fragmented messages are reassembled, up to
+/// [`DEFAULT_MAX_MESSAGE_SIZE`] bytes by default; see [`max_size()`](Self::max_size).
+///
+/// Extraction fails if the request is not a valid WebSocket upgrade request.
+///
+/// See [module docs](self) for an example.
+#[must_use = "streams do nothing unless polled"]
+pub struct Messages {
+    payload: Payload,
+    control: SharedControl,
+    codec: Codec,
+    buf: BytesMut,
+    partial: Option<(bool, BytesMut)>,
+    max_size: usize,
+    done: bool,
+}
+
+impl std::fmt::Debug for Messages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Messages")
+            .field("max_size", &self.max_size)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Messages {
+    /// Sets maximum size of a single, possibly fragmented, message.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self.codec = Codec::new().max_size(max_size);
+        self
+    }
+
+    fn handle_frame(&mut self, frame: Frame) -> Result<Option<Message>, MessagesError> {
+        let (is_text, bytes) = match frame {
+            Frame::Text(text) => (true, text),
+            Frame::Binary(bytes) => (false, bytes),
+
+            Frame::Continuation(Item::FirstText(bytes)) => return self.start_partial(true, bytes),
+            Frame::Continuation(Item::FirstBinary(bytes)) => {
+                return self.start_partial(false, bytes)
+            }
+            Frame::Continuation(Item::Continue(bytes)) => {
+                self.extend_partial(&bytes)?;
+                return Ok(None);
+            }
+            Frame::Continuation(Item::Last(bytes)) => {
+                self.extend_partial(&bytes)?;
+
+                // partial message is known to exist after successful extension
+                let (is_text, buf) = self.partial.take().unwrap();
+                (is_text, buf.freeze())
+            }
+
+            Frame::Ping(payload) => {
+                let mut control = self.control.borrow_mut();
+                control.pongs.push_back(payload);
+                control.wake();
+                return Ok(None);
+            }
+
+            Frame::Pong(_) => return Ok(None),
+
+            Frame::Close(reason) => {
+                self.done = true;
+
+                let mut control = self.control.borrow_mut();
+                control.close = Some(reason.clone());
+                control.wake();
+
+                return Ok(Some(Message::Close(reason)));
+            }
+        };
+
+        Ok(Some(if is_text {
+            let text = ByteString::try_from(bytes).map_err(|_| MessagesError::InvalidUtf8)?;
+            Message::Text(text)
+        } else {
+            Message::Binary(bytes)
+        }))
+    }
+
+    fn start_partial(
+        &mut self,
+        is_text: bool,
+        bytes: Bytes,
+    ) -> Result<Option<Message>, MessagesError> {
+        if self.partial.is_some() {
+            return Err(MessagesError::Protocol(ProtocolError::ContinuationStarted));
+        }
+
+        self.partial = Some((is_text, BytesMut::from(&bytes[..])));
+        Ok(None)
+    }
+
+    fn extend_partial(&mut self, bytes: &[u8]) -> Result<(), MessagesError> {
+        let Some((_, buf)) = &mut self.partial else {
+            return Err(MessagesError::Protocol(
+                ProtocolError::ContinuationNotStarted,
+            ));
+        };
+
+        if buf.len() + bytes.len() > self.max_size {
+            return Err(MessagesError::Overflow {
+                limit: self.max_size,
+            });
+        }
+
+        buf.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl FromRequest for Messages {
+    type Error = MessagesError;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        std::future::ready(
+            proto::verify_handshake(req.head())
+                .map_err(MessagesError::Handshake)
+                .map(|()| Messages {
+                    payload: payload.take(),
+                    control: control(req),
+                    codec: Codec::new().max_size(DEFAULT_MAX_MESSAGE_SIZE),
+                    buf: BytesMut::new(),
+                    partial: None,
+                    max_size: DEFAULT_MAX_MESSAGE_SIZE,
+                    done: false,
+                }),
+        )
+    }
+}
+
+impl Stream for Messages {
+    type Item = Result<Message, MessagesError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match this.codec.decode(&mut this.buf) {
+                Ok(Some(frame)) => match this.handle_frame(frame) {
+                    Ok(Some(msg)) => return Poll::Ready(Some(Ok(msg))),
+                    Ok(None) => continue,
+                    Err(err) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                },
+
+                Ok(None) => {}
+
+                Err(err) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(MessagesError::Protocol(err))));
+                }
+            }
+
+            match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+                Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+
+                Some(Err(err)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(MessagesError::Payload(err))));
+                }
+
+                None => {
+                    this.done = true;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body,
+        http::header,
+        test::{self, TestRequest},
+        web, App,
+    };
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn upgrade_req() -> TestRequest {
+        TestRequest::get()
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+    }
+
+    fn client_frames(msgs: impl IntoIterator<Item = proto::Message>) -> Bytes {
+        let mut codec = Codec::new().client_mode();
+        let mut buf = BytesMut::new();
+
+        for msg in msgs {
+            codec.encode(msg, &mut buf).unwrap();
+        }
+
+        buf.freeze()
+    }
+
+    fn server_frames(mut bytes: BytesMut) -> Vec<Frame> {
+        let mut codec = Codec::new().client_mode();
+        let mut frames = Vec::new();
+
+        while let Some(frame) = codec.decode(&mut bytes).unwrap() {
+            frames.push(frame);
+        }
+
+        frames
+    }
+
+    #[actix_web::test]
+    async fn sends_messages_then_close() {
+        let req = upgrade_req().to_http_request();
+
+        let res = Ws::from_infallible_stream(stream::iter([
+            Message::text("foo"),
+            Message::binary(&b"bar"[..]),
+        ]))
+        .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert!(res.headers().contains_key(header::SEC_WEBSOCKET_ACCEPT));
+
+        let bytes = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            server_frames(bytes.into()),
+            [
+                Frame::Text(Bytes::from_static(b"foo")),
+                Frame::Binary(Bytes::from_static(b"bar")),
+                Frame::Close(Some(CloseCode::Normal.into())),
+            ],
+        );
+    }
+
+    #[actix_web::test]
+    async fn rejects_non_upgrade_requests() {
+        let req = TestRequest::get().to_http_request();
+        let res = Ws::from_infallible_stream(stream::empty()).respond_to(&req);
+        assert!(res.status().is_client_error());
+
+        let app = test::init_service(App::new().route(
+            "/",
+            web::get().to(|_msgs: Messages| async { "unreachable" }),
+        ))
+        .await;
+        let res = test::call_service(&app, TestRequest::get().to_request()).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn reads_messages_and_answers_control_frames() {
+        let payload = client_frames([
+            proto::Message::Ping(Bytes::from_static(b"p1")),
+            proto::Message::Continuation(Item::FirstText(Bytes::from_static(b"hel"))),
+            proto::Message::Continuation(Item::Last(Bytes::from_static(b"lo"))),
+            proto::Message::Binary(Bytes::from_static(b"bin")),
+            proto::Message::Close(Some(CloseCode::Away.into())),
+        ]);
+
+        let (req, mut pl) = upgrade_req().set_payload(payload).to_http_parts();
+
+        let msgs = Messages::from_request(&req, &mut pl).await.unwrap();
+        let msgs = msgs.collect::<Vec<_>>().await;
+        let msgs = msgs.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(
+            msgs,
+            [
+                Message::text("hello"),
+                Message::binary(&b"bin"[..]),
+                Message::close(CloseCode::Away),
+            ],
+        );
+
+        // outgoing stream would continue but pong and close echo are sent first
+        let res = Ws::from_infallible_stream(stream::pending()).respond_to(&req);
+        let bytes = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            server_frames(bytes.into()),
+            [
+                Frame::Pong(Bytes::from_static(b"p1")),
+                Frame::Close(Some(CloseCode::Away.into())),
+            ],
+        );
+    }
+
+    #[actix_web::test]
+    async fn rejects_oversized_messages() {
+        let payload = client_frames([
+            proto::Message::Continuation(Item::FirstBinary(Bytes::from(vec![0; 6]))),
+            proto::Message::Continuation(Item::Last(Bytes::from(vec![0; 6]))),
+        ]);
+
+        let (req, mut pl) = upgrade_req().set_payload(payload).to_http_parts();
+
+        let mut msgs = Messages::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .max_size(10);
+        assert!(matches!(
+            msgs.next().await.unwrap().unwrap_err(),
+            MessagesError::Overflow { limit: 10 },
+        ));
+        assert!(msgs.next().await.is_none());
+    }
+}
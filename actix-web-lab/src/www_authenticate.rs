@@ -0,0 +1,536 @@
+//! WWW-Authenticate typed header.
+//!
+//! See [`WwwAuthenticate`] docs.
+
+use std::{fmt, str};
+
+use actix_http::{
+    error::ParseError,
+    header::{self, Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+use actix_web::http::StatusCode;
+
+/// The `WWW-Authenticate` header, defined in [RFC 9110 §11.6.1].
+///
+/// Contains one or more [challenges](Challenge) indicating the authentication schemes that can be
+/// used to access a resource, along with their parameters.
+///
+/// [Read more on MDN.](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/WWW-Authenticate)
+///
+/// # ABNF
+///
+/// ```text
+/// WWW-Authenticate = #challenge
+/// challenge        = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+/// auth-param       = token BWS "=" BWS ( token / quoted-string )
+/// ```
+///
+/// # Sample Values
+///
+/// - `Basic realm="admin", charset="UTF-8"`
+/// - `Bearer realm="api", error="invalid_token", error_description="The access token expired"`
+/// - `Negotiate, Basic realm="intranet"`
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web_lab::header::{BearerError, Challenge, WwwAuthenticate};
+///
+/// let mut res = HttpResponse::Unauthorized();
+/// res.insert_header(WwwAuthenticate(vec![
+///     Challenge::bearer()
+///         .realm("api")
+///         .error(BearerError::InvalidToken)
+///         .error_description("The access token expired"),
+///     Challenge::basic("api"),
+/// ]));
+/// ```
+///
+/// [RFC 9110 §11.6.1]: https://datatracker.ietf.org/doc/html/rfc9110#section-11.6.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WwwAuthenticate(pub Vec<Challenge>);
+
+impl_more::forward_deref_and_mut!(WwwAuthenticate => [Challenge]);
+
+impl fmt::Display for WwwAuthenticate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut challenges = self.0.iter();
+
+        let Some(challenge) = challenges.next() else {
+            return Ok(());
+        };
+
+        write!(f, "{challenge}")?;
+
+        for challenge in challenges {
+            write!(f, ", {challenge}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryIntoHeaderValue for WwwAuthenticate {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for WwwAuthenticate {
+    fn name() -> HeaderName {
+        header::WWW_AUTHENTICATE
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        let mut challenges = Vec::new();
+
+        for hdr in msg.headers().get_all(Self::name()) {
+            let hdr = hdr.to_str().map_err(|_| ParseError::Header)?;
+            Parser::new(hdr).parse_challenges(&mut challenges)?;
+        }
+
+        if challenges.is_empty() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(WwwAuthenticate(challenges))
+    }
+}
+
+/// An authentication challenge contained in a [`WwwAuthenticate`] header.
+///
+/// Parameter values are always quoted when formatted; quotes and backslashes in values are escaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    scheme: String,
+    token68: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    /// Constructs a challenge for a custom authentication scheme with no parameters.
+    ///
+    /// # Panics
+    /// Panics if `scheme` is not a valid token.
+    pub fn new(scheme: impl Into<String>) -> Self {
+        let scheme = scheme.into();
+        assert!(
+            is_token(&scheme),
+            "authentication scheme must be a valid token"
+        );
+
+        Self {
+            scheme,
+            token68: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// Constructs a `Basic` challenge ([RFC 7617]) with the given realm and a `UTF-8` charset.
+    ///
+    /// [RFC 7617]: https://datatracker.ietf.org/doc/html/rfc7617
+    pub fn basic(realm: impl Into<String>) -> Self {
+        Self::new("Basic").realm(realm).param("charset", "UTF-8")
+    }
+
+    /// Constructs a `Bearer` challenge ([RFC 6750]) with no parameters.
+    ///
+    /// [RFC 6750]: https://datatracker.ietf.org/doc/html/rfc6750
+    pub fn bearer() -> Self {
+        Self::new("Bearer")
+    }
+
+    /// Returns the authentication scheme.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the value of the named parameter, if present.
+    ///
+    /// Parameter names are compared case-insensitively.
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, val)| val.as_str())
+    }
+
+    /// Returns the `token68` value, if present.
+    pub fn token68(&self) -> Option<&str> {
+        self.token68.as_deref()
+    }
+
+    /// Adds an authentication parameter.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid token.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(is_token(&name), "parameter name must be a valid token");
+
+        self.params.push((name, value.into()));
+        self
+    }
+
+    /// Adds a `realm` parameter.
+    pub fn realm(self, realm: impl Into<String>) -> Self {
+        self.param("realm", realm)
+    }
+
+    /// Adds a `scope` parameter; a space-delimited list of scopes required to access the resource.
+    pub fn scope(self, scope: impl Into<String>) -> Self {
+        self.param("scope", scope)
+    }
+
+    /// Adds a Bearer `error` parameter.
+    pub fn error(self, error: BearerError) -> Self {
+        self.param("error", error.as_str())
+    }
+
+    /// Adds a Bearer `error_description` parameter; a human-readable explanation of the error.
+    pub fn error_description(self, description: impl Into<String>) -> Self {
+        self.param("error_description", description)
+    }
+
+    /// Adds a Bearer `error_uri` parameter; a URI of a human-readable page explaining the error.
+    pub fn error_uri(self, uri: impl Into<String>) -> Self {
+        self.param("error_uri", uri)
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.scheme)?;
+
+        if let Some(token68) = &self.token68 {
+            return write!(f, " {token68}");
+        }
+
+        for (idx, (name, value)) in self.params.iter().enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+            write!(f, "{sep}{name}=\"")?;
+
+            for ch in value.chars() {
+                if ch == '"' || ch == '\\' {
+                    f.write_str("\\")?;
+                }
+
+                write!(f, "{ch}")?;
+            }
+
+            f.write_str("\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error codes for `Bearer` challenges, defined in [RFC 6750 §3.1].
+///
+/// [RFC 6750 §3.1]: https://datatracker.ietf.org/doc/html/rfc6750#section-3.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BearerError {
+    /// The request is missing a required parameter or is otherwise malformed.
+    InvalidRequest,
+
+    /// The access token provided is expired, revoked, malformed, or invalid for other reasons.
+    InvalidToken,
+
+    /// The request requires higher privileges than provided by the access token.
+    InsufficientScope,
+}
+
+impl BearerError {
+    /// Returns the error code as it appears in the `error` parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BearerError::InvalidRequest => "invalid_request",
+            BearerError::InvalidToken => "invalid_token",
+            BearerError::InsufficientScope => "insufficient_scope",
+        }
+    }
+
+    /// Returns the response status code that should accompany this error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            BearerError::InvalidRequest => StatusCode::BAD_REQUEST,
+            BearerError::InvalidToken => StatusCode::UNAUTHORIZED,
+            BearerError::InsufficientScope => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl fmt::Display for BearerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn is_tchar(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+fn is_token(val: &str) -> bool {
+    !val.is_empty() && val.bytes().all(is_tchar)
+}
+
+fn is_token68_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~+/".contains(&byte)
+}
+
+/// Minimal challenge list parser.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_ws_and_commas(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b',')) {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while(&mut self, pred: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+
+        // only ASCII bytes are matched by the predicates used
+        str::from_utf8(&self.input[start..self.pos]).unwrap()
+    }
+
+    fn at_item_end(&self) -> bool {
+        matches!(self.peek(), None | Some(b','))
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<String, ParseError> {
+        // opening quote already checked
+        self.pos += 1;
+        let mut out = Vec::new();
+
+        loop {
+            match self.peek().ok_or(ParseError::Header)? {
+                b'"' => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| ParseError::Header);
+                }
+
+                b'\\' => {
+                    self.pos += 1;
+                    out.push(self.peek().ok_or(ParseError::Header)?);
+                    self.pos += 1;
+                }
+
+                byte => {
+                    out.push(byte);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Attempts to parse an auth-param, restoring position if one is not found.
+    fn parse_param(&mut self) -> Result<Option<(String, String)>, ParseError> {
+        let start = self.pos;
+
+        let name = self.take_while(is_tchar);
+        self.skip_ws();
+
+        if name.is_empty() || self.peek() != Some(b'=') {
+            self.pos = start;
+            return Ok(None);
+        }
+
+        self.pos += 1;
+        self.skip_ws();
+
+        let value = match self.peek() {
+            Some(b'"') => self.parse_quoted_string()?,
+            _ => {
+                let value = self.take_while(is_tchar);
+
+                if value.is_empty() {
+                    // token68 with padding, not a param
+                    self.pos = start;
+                    return Ok(None);
+                }
+
+                value.to_owned()
+            }
+        };
+
+        Ok(Some((name.to_owned(), value)))
+    }
+
+    fn parse_challenges(&mut self, challenges: &mut Vec<Challenge>) -> Result<(), ParseError> {
+        loop {
+            self.skip_ws_and_commas();
+
+            if self.peek().is_none() {
+                return Ok(());
+            }
+
+            let scheme = self.take_while(is_tchar);
+            if scheme.is_empty() {
+                return Err(ParseError::Header);
+            }
+
+            let mut challenge = Challenge {
+                scheme: scheme.to_owned(),
+                token68: None,
+                params: Vec::new(),
+            };
+
+            self.skip_ws();
+
+            if !self.at_item_end() {
+                match self.parse_param()? {
+                    Some(param) => challenge.params.push(param),
+
+                    None => {
+                        let token68 = self.take_while(is_token68_char);
+                        let padding = self.take_while(|b| b == b'=');
+                        self.skip_ws();
+
+                        if token68.is_empty() || !self.at_item_end() {
+                            return Err(ParseError::Header);
+                        }
+
+                        challenge.token68 = Some(format!("{token68}{padding}"));
+                    }
+                }
+            }
+
+            // subsequent comma-separated params belong to this challenge until a new scheme
+            while challenge.token68.is_none() {
+                let start = self.pos;
+                self.skip_ws_and_commas();
+
+                match self.parse_param()? {
+                    Some(param) => challenge.params.push(param),
+                    None => {
+                        self.pos = start;
+                        break;
+                    }
+                }
+
+                self.skip_ws();
+            }
+
+            challenges.push(challenge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{assert_parse_eq, assert_parse_fail};
+
+    #[test]
+    fn format() {
+        let hdr = WwwAuthenticate(vec![
+            Challenge::bearer()
+                .realm("api")
+                .error(BearerError::InsufficientScope)
+                .scope("read write"),
+            Challenge::basic("say \"hi\""),
+            Challenge::new("Negotiate"),
+        ]);
+
+        assert_eq!(
+            hdr.to_string(),
+            r#"Bearer realm="api", error="insufficient_scope", scope="read write", Basic realm="say \"hi\"", charset="UTF-8", Negotiate"#,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_scheme() {
+        Challenge::new("Bad Scheme");
+    }
+
+    #[test]
+    fn parsing() {
+        assert_parse_eq::<WwwAuthenticate, _, _>(
+            [r#"Basic realm="admin", charset="UTF-8""#],
+            WwwAuthenticate(vec![Challenge::basic("admin")]),
+        );
+
+        assert_parse_eq::<WwwAuthenticate, _, _>(
+            [
+                r#"Bearer realm="api",error=invalid_token, error_description="a \"b\"", Basic realm=x"#,
+            ],
+            WwwAuthenticate(vec![
+                Challenge::bearer()
+                    .realm("api")
+                    .param("error", "invalid_token")
+                    .error_description("a \"b\""),
+                Challenge::new("Basic").realm("x"),
+            ]),
+        );
+
+        assert_parse_eq::<WwwAuthenticate, _, _>(
+            ["Negotiate abc+/==, Basic realm=\"x\"", "Bearer"],
+            WwwAuthenticate(vec![
+                Challenge {
+                    token68: Some("abc+/==".to_owned()),
+                    ..Challenge::new("Negotiate")
+                },
+                Challenge::new("Basic").realm("x"),
+                Challenge::bearer(),
+            ]),
+        );
+
+        assert_parse_fail::<WwwAuthenticate, _, _>([""]);
+        assert_parse_fail::<WwwAuthenticate, _, _>([r#"Basic realm="unterminated"#]);
+        assert_parse_fail::<WwwAuthenticate, _, _>(["Basic !!! ???"]);
+    }
+
+    #[test]
+    fn accessors() {
+        let challenge = Challenge::bearer()
+            .realm("api")
+            .error(BearerError::InvalidToken);
+
+        assert_eq!(challenge.scheme(), "Bearer");
+        assert_eq!(challenge.get_param("REALM"), Some("api"));
+        assert_eq!(challenge.get_param("error"), Some("invalid_token"));
+        assert!(challenge.get_param("scope").is_none());
+        assert!(challenge.token68().is_none());
+
+        assert_eq!(
+            BearerError::InvalidToken.status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            BearerError::InsufficientScope.status_code(),
+            StatusCode::FORBIDDEN
+        );
+    }
+}
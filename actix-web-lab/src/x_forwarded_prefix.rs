@@ -25,10 +25,19 @@ pub const X_FORWARDED_PREFIX: HeaderName = HeaderName::from_static("x-forwarded-
 ///
 /// Also see
 ///
+/// When requests pass through multiple proxies, each may contribute a prefix, either as a
+/// comma-separated list or as repeated header lines. These are accumulated, in order, into a single
+/// prefix. Each prefix must be an absolute path without a query and must not contain empty, `.`, or
+/// `..` segments (including their percent-encoded forms); otherwise, parsing fails.
+///
+/// Can also be used as an extractor, in which case a missing or invalid header results in a
+/// `400 Bad Request` response. Use `Option<XForwardedPrefix>` to allow the header to be absent.
+///
 /// # Example Values
 ///
 /// - `/`
 /// - `/foo`
+/// - `/foo, /bar`
 ///
 /// # Examples
 ///
@@ -60,19 +69,76 @@ impl Header for XForwardedPrefix {
     }
 
     fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
-        let header = msg.headers().get(Self::name());
-
-        header
-            .and_then(|hdr| hdr.to_str().ok())
-            .map(|hdr| hdr.trim())
-            .filter(|hdr| !hdr.is_empty())
-            .and_then(|hdr| hdr.parse::<actix_web::http::uri::PathAndQuery>().ok())
-            .filter(|path| path.query().is_none())
+        let mut prefix = String::new();
+        let mut found = false;
+
+        for hdr in msg.headers().get_all(Self::name()) {
+            let hdr = hdr.to_str().map_err(|_| ParseError::Header)?;
+
+            for item in hdr
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+            {
+                let item = validate_prefix(item)?;
+                prefix.push_str(item);
+                found = true;
+            }
+        }
+
+        if !found {
+            return Err(ParseError::Header);
+        }
+
+        if prefix.is_empty() {
+            prefix.push('/');
+        }
+
+        PathAndQuery::from_maybe_shared(prefix)
             .map(XForwardedPrefix)
-            .ok_or(ParseError::Header)
+            .map_err(|_| ParseError::Header)
     }
 }
 
+impl FromRequest for XForwardedPrefix {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        _payload: &mut actix_http::Payload,
+    ) -> Self::Future {
+        ready(XForwardedPrefix::parse(req).map_err(Into::into))
+    }
+}
+
+/// Validates a single prefix, returning it without any trailing slashes.
+///
+/// Rejects prefixes that are not absolute paths, contain a query, or contain empty or dot segments
+/// which could be used for path traversal.
+fn validate_prefix(prefix: &str) -> Result<&str, ParseError> {
+    if !prefix.starts_with('/') || prefix.contains(['?', '#', '\\']) {
+        return Err(ParseError::Header);
+    }
+
+    let prefix = prefix.trim_end_matches('/');
+
+    for segment in prefix.split('/').skip(1) {
+        let segment = segment.to_ascii_lowercase().replace("%2e", ".");
+
+        if segment.is_empty() || segment == "." || segment == ".." {
+            return Err(ParseError::Header);
+        }
+    }
+
+    // ensure prefix contains only valid path characters
+    prefix
+        .parse::<PathAndQuery>()
+        .map_err(|_| ParseError::Header)?;
+
+    Ok(prefix)
+}
+
 #[cfg(test)]
 mod header_tests {
     use actix_web::test::{self};
@@ -122,13 +188,63 @@ mod header_tests {
 
         assert_eq!(
             XForwardedPrefix::parse(&req).ok().unwrap(),
-            XForwardedPrefix(PathAndQuery::from_static("/foo")),
+            XForwardedPrefix(PathAndQuery::from_static("/foo/bar")),
+        );
+    }
+
+    #[test]
+    fn comma_separated() {
+        let req = test::TestRequest::default()
+            .insert_header((X_FORWARDED_PREFIX, "/foo/, /bar , /baz/qux"))
+            .to_http_request();
+
+        assert_eq!(
+            XForwardedPrefix::parse(&req).ok().unwrap(),
+            XForwardedPrefix(PathAndQuery::from_static("/foo/bar/baz/qux")),
         );
+
+        let req = test::TestRequest::default()
+            .insert_header((X_FORWARDED_PREFIX, "/, /"))
+            .to_http_request();
+
+        assert_eq!(
+            XForwardedPrefix::parse(&req).ok().unwrap(),
+            XForwardedPrefix(PathAndQuery::from_static("/")),
+        );
+    }
+
+    #[test]
+    fn invalid_prefixes() {
+        for prefix in [
+            "foo",
+            "/foo?bar",
+            "/foo/..",
+            "/..",
+            "/./foo",
+            "/%2e%2E/foo",
+            "/foo, /..",
+            "//evil.example",
+            "/foo//bar",
+            "/foo\\bar",
+        ] {
+            let req = test::TestRequest::default()
+                .insert_header((X_FORWARDED_PREFIX, prefix))
+                .to_http_request();
+
+            assert!(
+                XForwardedPrefix::parse(&req).is_err(),
+                "expected {prefix:?} to be rejected",
+            );
+        }
     }
 }
 
 /// Reconstructed path using x-forwarded-prefix header.
 ///
+/// If the header is absent, the request path is used as-is. If the header is invalid, extraction
+/// fails with a `400 Bad Request` response. See [`XForwardedPrefix`] for how prefixes from multiple
+/// proxies are accumulated.
+///
 /// ```
 /// # use actix_web::{FromRequest as _, test::TestRequest};
 /// # actix_web::rt::System::new().block_on(async {
@@ -158,13 +274,26 @@ impl FromRequest for ReconstructedPath {
             .path_and_query
             .unwrap_or(PathAndQuery::from_static("/"));
 
-        let prefix = XForwardedPrefix::parse(req).unwrap();
+        if !req.headers().contains_key(X_FORWARDED_PREFIX) {
+            return ready(Ok(ReconstructedPath(path_and_query)));
+        }
+
+        let prefix = match XForwardedPrefix::parse(req) {
+            Ok(prefix) => prefix,
+            Err(err) => return ready(Err(err.into())),
+        };
+
+        if prefix.as_str() == "/" {
+            return ready(Ok(ReconstructedPath(path_and_query)));
+        }
 
         let reconstructed = [prefix.as_str(), path_and_query.as_str()].concat();
 
-        ready(Ok(ReconstructedPath(
-            PathAndQuery::from_maybe_shared(reconstructed).unwrap(),
-        )))
+        ready(
+            PathAndQuery::from_maybe_shared(reconstructed)
+                .map(ReconstructedPath)
+                .map_err(|_| ParseError::Header.into()),
+        )
     }
 }
 
@@ -185,4 +314,67 @@ mod extractor_tests {
             ReconstructedPath(PathAndQuery::from_static("/foo/bar")),
         );
     }
+
+    #[actix_web::test]
+    async fn multi_hop() {
+        let req = test::TestRequest::with_uri("/baz?q=1")
+            .insert_header((X_FORWARDED_PREFIX, "/foo, /bar/"))
+            .to_http_request();
+
+        assert_eq!(
+            ReconstructedPath::extract(&req).await.unwrap(),
+            ReconstructedPath(PathAndQuery::from_static("/foo/bar/baz?q=1")),
+        );
+    }
+
+    #[actix_web::test]
+    async fn missing_or_root_prefix() {
+        let req = test::TestRequest::with_uri("/bar").to_http_request();
+
+        assert_eq!(
+            ReconstructedPath::extract(&req).await.unwrap(),
+            ReconstructedPath(PathAndQuery::from_static("/bar")),
+        );
+
+        let req = test::TestRequest::with_uri("/bar")
+            .insert_header((X_FORWARDED_PREFIX, "/"))
+            .to_http_request();
+
+        assert_eq!(
+            ReconstructedPath::extract(&req).await.unwrap(),
+            ReconstructedPath(PathAndQuery::from_static("/bar")),
+        );
+    }
+
+    #[actix_web::test]
+    async fn invalid_prefix() {
+        let req = test::TestRequest::with_uri("/bar")
+            .insert_header((X_FORWARDED_PREFIX, "/foo/.."))
+            .to_http_request();
+
+        let err = ReconstructedPath::extract(&req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::BAD_REQUEST,
+        );
+    }
+
+    #[actix_web::test]
+    async fn typed_prefix_extractor() {
+        let req = test::TestRequest::with_uri("/bar")
+            .insert_header((X_FORWARDED_PREFIX, "/foo, /bar"))
+            .to_http_request();
+
+        assert_eq!(
+            XForwardedPrefix::extract(&req).await.unwrap(),
+            XForwardedPrefix(PathAndQuery::from_static("/foo/bar")),
+        );
+
+        let req = test::TestRequest::with_uri("/bar").to_http_request();
+        assert!(XForwardedPrefix::extract(&req).await.is_err());
+        assert!(Option::<XForwardedPrefix>::extract(&req)
+            .await
+            .unwrap()
+            .is_none());
+    }
 }
@@ -0,0 +1,301 @@
+//! XML extractor with const-generic payload size limit.
+
+use std::fmt;
+
+use actix_web::{
+    dev::Payload, error::PayloadError, http::StatusCode, FromRequest, HttpMessage as _,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{decompress, lab_config::LabConfig};
+
+/// Default XML payload size limit of 2MiB.
+pub const DEFAULT_XML_LIMIT: usize = 2_097_152;
+
+/// XML extractor with const-generic payload size limit.
+///
+/// Deserializes a `T` from a request body with an `application/xml`, `text/xml`, or `+xml` suffixed
+/// `Content-Type`, buffering up to `LIMIT` bytes. The default limit is 2MiB. The name of the root
+/// element is ignored.
+///
+/// Deserialization uses [`quick_xml::de`]; see its docs for how XML elements and attributes map to
+/// Rust types.
+///
+/// # Examples
+/// ```
+/// use actix_web::post;
+/// use actix_web_lab::extract::Xml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// Deserialize `Info` from request's body, e.g., `<info><username>bob</username></info>`.
+/// #[post("/")]
+/// async fn index(info: Xml<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// const LIMIT_32_MB: usize = 33_554_432;
+///
+/// /// Deserialize payload with a higher 32MiB limit.
+/// #[post("/big-payload")]
+/// async fn big_payload(info: Xml<Info, LIMIT_32_MB>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Xml<T, const LIMIT: usize = DEFAULT_XML_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> std::ops::Deref for Xml<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> std::ops::DerefMut for Xml<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const LIMIT: usize> Xml<T, LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static, const LIMIT: usize> FromRequest for Xml<T, LIMIT> {
+    type Error = XmlPayloadError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let can_parse_xml = match req.mime_type() {
+            Ok(Some(mime)) => {
+                ((mime.type_() == mime::APPLICATION || mime.type_() == mime::TEXT)
+                    && mime.subtype() == mime::XML)
+                    || mime.suffix() == Some(mime::XML)
+            }
+            _ => false,
+        };
+
+        let length = req
+            .get_header::<crate::header::ContentLength>()
+            .map(|cl| cl.into_inner())
+            .filter(|_| !decompress::is_encoded(req));
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_XML_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let capacity = LabConfig::buffer_capacity(req, length);
+        let mut payload = decompress::take_payload(req, payload);
+        let req = req.clone();
+
+        Box::pin(async move {
+            if !can_parse_xml {
+                return Err(XmlPayloadError::ContentType);
+            }
+
+            if let Some(length) = length.filter(|&len| len > limit) {
+                return Err(XmlPayloadError::OverflowKnownLength { length, limit });
+            }
+
+            let mut buf = actix_web::web::BytesMut::with_capacity(capacity);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(XmlPayloadError::Payload)?;
+
+                if buf.len() + chunk.len() > limit {
+                    return Err(XmlPayloadError::Overflow { limit });
+                }
+
+                buf.extend_from_slice(&chunk);
+            }
+
+            let mut de = quick_xml::de::Deserializer::from_reader(&buf[..]);
+
+            serde_path_to_error::deserialize(&mut de)
+                .map(Xml)
+                .map_err(|err| XmlPayloadError::Deserialize(XmlDeserializeError(err)))
+                .inspect_err(|err| {
+                    debug!(
+                        "Failed to deserialize Xml<{}> from payload in handler: {}: {err}",
+                        core::any::type_name::<T>(),
+                        req.match_name().unwrap_or_else(|| req.path()),
+                    );
+                })
+        })
+    }
+}
+
+/// Errors that can occur when extracting an XML payload or serializing an XML response.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum XmlPayloadError {
+    /// Request does not have an XML `Content-Type`.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Payload size is bigger than allowed and content length header is set.
+    #[display("XML payload ({length} bytes) is larger than allowed (limit: {limit} bytes).")]
+    OverflowKnownLength {
+        /// Length reported by `Content-Length` header.
+        length: usize,
+
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload size is bigger than allowed but no content length header set.
+    #[display("XML payload has exceeded limit ({limit} bytes).")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload could not be deserialized into the target type.
+    #[display("XML deserialize error: {_0}")]
+    Deserialize(XmlDeserializeError),
+
+    /// Response value could not be serialized.
+    #[display("XML serialize error: {_0}")]
+    Serialize(quick_xml::SeError),
+
+    /// Error that occurred when reading the payload.
+    #[display("Error that occurred when reading payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for XmlPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::OverflowKnownLength { .. } | Self::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Serialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Deserialize(_) | Self::Payload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// XML deserialization error, including the path at which it occurred.
+#[derive(Debug)]
+pub struct XmlDeserializeError(serde_path_to_error::Error<quick_xml::DeError>);
+
+impl XmlDeserializeError {
+    /// Returns the path at which the deserialization error occurred.
+    pub fn path(&self) -> impl fmt::Display + '_ {
+        self.0.path()
+    }
+}
+
+impl fmt::Display for XmlDeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.path().iter().len() > 0 {
+            write!(f, "at path {}: ", self.0.path())?;
+        }
+
+        fmt::Display::fmt(self.0.inner(), f)
+    }
+}
+
+impl std::error::Error for XmlDeserializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct MyObject {
+        #[serde(rename = "@id")]
+        id: u32,
+        name: String,
+        tag: Vec<String>,
+    }
+
+    const BODY: &str = r#"<object id="4"><name>test</name><tag>a</tag><tag>b</tag></object>"#;
+
+    fn xml_req(content_type: &'static str, body: &'static str) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn extracts_payload() {
+        let expected = MyObject {
+            id: 4,
+            name: "test".to_owned(),
+            tag: vec!["a".to_owned(), "b".to_owned()],
+        };
+
+        for content_type in [
+            "application/xml",
+            "text/xml; charset=utf-8",
+            "application/atom+xml",
+        ] {
+            let (req, mut pl) = xml_req(content_type, BODY).to_http_parts();
+            let extracted = Xml::<MyObject>::from_request(&req, &mut pl).await.unwrap();
+            assert_eq!(extracted.into_inner(), expected);
+        }
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_requests() {
+        let (req, mut pl) = xml_req("application/json", BODY).to_http_parts();
+        let err = Xml::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, XmlPayloadError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = xml_req("application/xml", BODY).to_http_parts();
+        let err = Xml::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            XmlPayloadError::OverflowKnownLength { limit: 4, .. }
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/xml"))
+            .set_payload(BODY)
+            .to_http_parts();
+        let err = Xml::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, XmlPayloadError::Overflow { limit: 4 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body = r#"<object id="x"><name>test</name></object>"#;
+        let (req, mut pl) = xml_req("application/xml", body).to_http_parts();
+        let err = Xml::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        let XmlPayloadError::Deserialize(err) = err else {
+            panic!("expected deserialize error, got {err:?}");
+        };
+        assert_eq!(err.path().to_string(), "@id");
+    }
+}
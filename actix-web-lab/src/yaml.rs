@@ -0,0 +1,265 @@
+//! YAML extractor with const-generic payload size limit.
+
+use actix_web::{
+    dev::Payload, error::PayloadError, http::StatusCode, FromRequest, HttpMessage as _,
+    HttpRequest, ResponseError,
+};
+use derive_more::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{decompress, lab_config::LabConfig};
+
+/// Default YAML payload size limit of 2MiB.
+pub const DEFAULT_YAML_LIMIT: usize = 2_097_152;
+
+/// [YAML] extractor with const-generic payload size limit.
+///
+/// Deserializes a `T` from a request body with an `application/yaml`, `application/x-yaml`,
+/// `text/yaml`, or `+yaml` suffixed `Content-Type`, buffering up to `LIMIT` bytes. The default limit
+/// is 2MiB.
+///
+/// # Examples
+/// ```
+/// use actix_web::put;
+/// use actix_web_lab::extract::Yaml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     replicas: u32,
+/// }
+///
+/// /// Deserialize `Config` from request's body, e.g., `replicas: 3`.
+/// #[put("/config")]
+/// async fn update_config(config: Yaml<Config>) -> String {
+///     format!("scaling to {} replicas", config.replicas)
+/// }
+///
+/// const LIMIT_32_MB: usize = 33_554_432;
+///
+/// /// Deserialize payload with a higher 32MiB limit.
+/// #[put("/big-config")]
+/// async fn big_config(config: Yaml<Config, LIMIT_32_MB>) -> String {
+///     format!("scaling to {} replicas", config.replicas)
+/// }
+/// ```
+///
+/// [YAML]: https://yaml.org/
+#[derive(Debug)]
+pub struct Yaml<T, const LIMIT: usize = DEFAULT_YAML_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> std::ops::Deref for Yaml<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> std::ops::DerefMut for Yaml<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const LIMIT: usize> Yaml<T, LIMIT> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static, const LIMIT: usize> FromRequest for Yaml<T, LIMIT> {
+    type Error = YamlPayloadError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let can_parse_yaml = match req.mime_type() {
+            Ok(Some(mime)) => {
+                matches!(
+                    (mime.type_().as_str(), mime.subtype().as_str()),
+                    ("application", "yaml" | "x-yaml") | ("text", "yaml")
+                ) || mime.suffix().is_some_and(|suffix| suffix == "yaml")
+            }
+            _ => false,
+        };
+
+        let length = req
+            .get_header::<crate::header::ContentLength>()
+            .map(|cl| cl.into_inner())
+            .filter(|_| !decompress::is_encoded(req));
+
+        let limit = LabConfig::resolve_limit(req, LIMIT, DEFAULT_YAML_LIMIT, LabConfig::BODY);
+        LabConfig::apply_to_payload(req, payload);
+
+        let capacity = LabConfig::buffer_capacity(req, length);
+        let mut payload = decompress::take_payload(req, payload);
+        let req = req.clone();
+
+        Box::pin(async move {
+            if !can_parse_yaml {
+                return Err(YamlPayloadError::ContentType);
+            }
+
+            if let Some(length) = length.filter(|&len| len > limit) {
+                return Err(YamlPayloadError::OverflowKnownLength { length, limit });
+            }
+
+            let mut buf = actix_web::web::BytesMut::with_capacity(capacity);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(YamlPayloadError::Payload)?;
+
+                if buf.len() + chunk.len() > limit {
+                    return Err(YamlPayloadError::Overflow { limit });
+                }
+
+                buf.extend_from_slice(&chunk);
+            }
+
+            serde_norway::from_slice(&buf)
+                .map(Yaml)
+                .map_err(YamlPayloadError::Deserialize)
+                .inspect_err(|err| {
+                    debug!(
+                        "Failed to deserialize Yaml<{}> from payload in handler: {}: {err}",
+                        core::any::type_name::<T>(),
+                        req.match_name().unwrap_or_else(|| req.path()),
+                    );
+                })
+        })
+    }
+}
+
+/// Errors that can occur when extracting a YAML payload or serializing a YAML response.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum YamlPayloadError {
+    /// Request does not have a YAML `Content-Type`.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Payload size is bigger than allowed and content length header is set.
+    #[display("YAML payload ({length} bytes) is larger than allowed (limit: {limit} bytes).")]
+    OverflowKnownLength {
+        /// Length reported by `Content-Length` header.
+        length: usize,
+
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload size is bigger than allowed but no content length header set.
+    #[display("YAML payload has exceeded limit ({limit} bytes).")]
+    Overflow {
+        /// Payload size limit.
+        limit: usize,
+    },
+
+    /// Payload could not be deserialized into the target type.
+    #[display("YAML deserialize error: {_0}")]
+    Deserialize(serde_norway::Error),
+
+    /// Response value could not be serialized.
+    #[display("YAML serialize error: {_0}")]
+    Serialize(serde_norway::Error),
+
+    /// Error that occurred when reading the payload.
+    #[display("Error that occurred when reading payload: {_0}")]
+    Payload(PayloadError),
+}
+
+impl ResponseError for YamlPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::OverflowKnownLength { .. } | Self::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Serialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Deserialize(_) | Self::Payload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct MyObject {
+        name: String,
+        tags: Vec<u32>,
+    }
+
+    const BODY: &str = "name: test\ntags: [1, 2]\n";
+
+    fn yaml_req(content_type: &'static str, body: &'static str) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn extracts_payload() {
+        let expected = MyObject {
+            name: "test".to_owned(),
+            tags: vec![1, 2],
+        };
+
+        for content_type in [
+            "application/yaml",
+            "application/x-yaml",
+            "text/yaml; charset=utf-8",
+            "application/openapi+yaml",
+        ] {
+            let (req, mut pl) = yaml_req(content_type, BODY).to_http_parts();
+            let extracted = Yaml::<MyObject>::from_request(&req, &mut pl).await.unwrap();
+            assert_eq!(extracted.into_inner(), expected);
+        }
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_requests() {
+        let (req, mut pl) = yaml_req("application/json", BODY).to_http_parts();
+        let err = Yaml::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, YamlPayloadError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = yaml_req("application/yaml", BODY).to_http_parts();
+        let err = Yaml::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            YamlPayloadError::OverflowKnownLength { limit: 4, .. }
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/yaml"))
+            .set_payload(BODY)
+            .to_http_parts();
+        let err = Yaml::<MyObject, 4>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, YamlPayloadError::Overflow { limit: 4 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (req, mut pl) = yaml_req("application/yaml", "name: test\ntags: [x]\n").to_http_parts();
+        let err = Yaml::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, YamlPayloadError::Deserialize(_)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("tags[0]"), "{err}");
+    }
+}